@@ -20,6 +20,8 @@ pub use bootstrap::Bootstrap;
 pub mod cluster;
 pub use cluster::Cluster;
 pub mod core;
+pub mod extension_config;
+pub mod layered_runtime;
 pub mod listener;
 pub use listener::Listener;
 pub mod listener_filters;
@@ -28,19 +30,28 @@ pub mod metrics;
 use log::AccessLogConfig;
 pub use log::LogConfig;
 pub mod network_filters;
+pub mod overload;
 pub mod runtime;
 pub use runtime::Runtime;
 pub mod common;
 pub mod grpc;
 pub mod secret;
 pub mod transport;
+pub mod xdstp;
 
 pub(crate) mod util;
 
 pub use crate::config::common::*;
 use crate::{options::Options, Result};
+use bootstrap::ApiType;
+use compact_str::CompactString;
+use network_filters::http_connection_manager::header_modifer::HeaderKeyValue;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{fs::File, path::Path};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub struct Config {
@@ -85,16 +96,76 @@ pub fn deserialize_yaml<T: DeserializeOwned>(path: &Path) -> Result<T> {
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub struct ConfigSource {
     pub config_source_specifier: ConfigSourceSpecifier,
+    /// How long the first resources from this source are waited for before the proxy carries on initializing
+    /// without them: 15s if unset, never giving up if zero.
+    #[serde(with = "humantime_serde", skip_serializing_if = "Option::is_none", default)]
+    pub initial_fetch_timeout: Option<Duration>,
+}
+
+impl ConfigSource {
+    pub const DEFAULT_INITIAL_FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+    /// How long the first resources from this source are waited for, `None` if they are waited for until they come.
+    pub fn fetch_timeout(&self) -> Option<Duration> {
+        match self.initial_fetch_timeout {
+            None => Some(Self::DEFAULT_INITIAL_FETCH_TIMEOUT),
+            Some(Duration::ZERO) => None,
+            Some(timeout) => Some(timeout),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub enum ConfigSourceSpecifier {
     ADS,
+    ApiConfigSource(ApiConfigSource),
+    PathConfigSource(PathConfigSource),
+}
+
+/// A file holding every resource of a single type, read again each time a file is moved onto it.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub struct PathConfigSource {
+    pub path: PathBuf,
+    /// A directory any move into which also has the file read again, as when it is a symlink to be swapped.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub watched_directory: Option<PathBuf>,
+}
+
+/// A management server of its own, serving a single type of resources.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub struct ApiConfigSource {
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub api_type: ApiType,
+    /// Clusters of the management server in order of preference, each failure to reach one having the next one
+    /// tried, and the first one again after the last.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub grpc_cluster_specifiers: Vec<CompactString>,
+    /// Headers sent along each stream opened to the management server, such as an auth token.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub initial_metadata: Vec<HeaderKeyValue>,
+    /// The longest to wait before opening a stream again after failing to, each failure doubling the wait.
+    /// Envoy hard-codes it, so there is no Envoy field for it: `refresh_delay` only paces REST polling.
+    #[serde(with = "humantime_serde", skip_serializing_if = "Option::is_none", default)]
+    pub max_backoff: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rate_limit_settings: Option<RateLimitSettings>,
+}
+
+/// Token bucket limiting the rate of the discovery requests sent to a management server.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub struct RateLimitSettings {
+    pub max_tokens: u32,
+    /// How long it takes for a single token to be added back to the bucket.
+    #[serde(with = "humantime_serde")]
+    pub fill_interval: Duration,
 }
 
 #[cfg(feature = "envoy-conversions")]
 mod envoy_conversions {
-    use std::path::Path;
+    use std::{
+        path::{Path, PathBuf},
+        time::Duration,
+    };
 
     use super::{deserialize_yaml, log::AccessLogConfig, Bootstrap, Config};
     use crate::config::common::envoy_conversions::IsUsed;
@@ -103,16 +174,24 @@ mod envoy_conversions {
         options::Options,
         Result,
     };
+    use compact_str::CompactString;
     use orion_data_plane_api::decode::from_serde_deserializer;
     pub use orion_data_plane_api::envoy_data_plane_api::envoy::config::bootstrap::v3::Bootstrap as EnvoyBootstrap;
     use orion_data_plane_api::envoy_data_plane_api::envoy::config::core::v3::{
-        config_source::ConfigSourceSpecifier as EnvoyConfigSourceSpecifier, AggregatedConfigSource,
-        ConfigSource as EnvoyConfigSource,
+        config_source::ConfigSourceSpecifier as EnvoyConfigSourceSpecifier,
+        grpc_service::{EnvoyGrpc, TargetSpecifier as EnvoyGrpcTargetSpecifier},
+        AggregatedConfigSource, ApiConfigSource as EnvoyApiConfigSource, ConfigSource as EnvoyConfigSource,
+        GrpcService as EnvoyGrpcService, PathConfigSource as EnvoyPathConfigSource,
+        RateLimitSettings as EnvoyRateLimitSettings, WatchedDirectory,
     };
     use orion_error::{Context, ErrorInfo};
     use serde::Deserialize;
 
-    use crate::config::{self, convert_opt, unsupported_field, ConfigSource, ConfigSourceSpecifier, GenericError};
+    use crate::config::{
+        self, bootstrap::ApiType, convert_opt, required, unsupported_field, util::duration_from_envoy, ApiConfigSource,
+        ConfigSource, ConfigSourceSpecifier, GenericError, HeaderKeyValue, PathConfigSource, RateLimitSettings,
+        WithNodeOnResult,
+    };
 
     #[derive(Deserialize)]
     struct Wrapper(#[serde(deserialize_with = "from_serde_deserializer")] EnvoyBootstrap);
@@ -186,13 +265,15 @@ mod envoy_conversions {
         ) -> std::result::Result<config::ConfigSource, config::common::GenericError> {
             let EnvoyConfigSource {
                 authorities,
-                initial_fetch_timeout: _,
+                initial_fetch_timeout,
                 resource_api_version: _,
                 config_source_specifier,
             } = value;
             unsupported_field!(authorities)?;
             let config_source_specifier = convert_opt!(config_source_specifier)?;
-            Ok(Self { config_source_specifier })
+            let initial_fetch_timeout =
+                initial_fetch_timeout.map(duration_from_envoy).transpose().with_node("initial_fetch_timeout")?;
+            Ok(Self { config_source_specifier, initial_fetch_timeout })
         }
     }
 
@@ -203,18 +284,129 @@ mod envoy_conversions {
         ) -> std::result::Result<config::ConfigSourceSpecifier, config::common::GenericError> {
             match value {
                 EnvoyConfigSourceSpecifier::Ads(AggregatedConfigSource {}) => Ok(Self::ADS),
-                EnvoyConfigSourceSpecifier::ApiConfigSource(_) => {
-                    Err(GenericError::unsupported_variant("ApiConfigSource"))
+                EnvoyConfigSourceSpecifier::ApiConfigSource(api_config_source) => {
+                    ApiConfigSource::try_from(api_config_source)
+                        .map(Self::ApiConfigSource)
+                        .with_node("api_config_source")
+                },
+                EnvoyConfigSourceSpecifier::Path(path) => {
+                    let path = required!(path)?.into();
+                    Ok(Self::PathConfigSource(PathConfigSource { path, watched_directory: None }))
                 },
-                EnvoyConfigSourceSpecifier::Path(_) => Err(GenericError::unsupported_variant("Path")),
-                EnvoyConfigSourceSpecifier::PathConfigSource(_) => {
-                    Err(GenericError::unsupported_variant("PathConfigSource"))
+                EnvoyConfigSourceSpecifier::PathConfigSource(EnvoyPathConfigSource { path, watched_directory }) => {
+                    (|| -> std::result::Result<_, GenericError> {
+                        let path = required!(path)?.into();
+                        let watched_directory = watched_directory
+                            .map(|WatchedDirectory { path }| required!(path).map(PathBuf::from))
+                            .transpose()
+                            .with_node("watched_directory")?;
+                        Ok(Self::PathConfigSource(PathConfigSource { path, watched_directory }))
+                    })()
+                    .with_node("path_config_source")
                 },
                 EnvoyConfigSourceSpecifier::Self_(_) => Err(GenericError::unsupported_variant("Self_")),
             }
         }
     }
 
+    impl TryFrom<EnvoyApiConfigSource> for ApiConfigSource {
+        type Error = GenericError;
+        fn try_from(value: EnvoyApiConfigSource) -> std::result::Result<Self, Self::Error> {
+            let EnvoyApiConfigSource {
+                api_type,
+                transport_api_version: _,
+                cluster_names: _,
+                grpc_services,
+                // only used by REST config sources, gRPC streams are pushed to
+                refresh_delay: _,
+                request_timeout,
+                rate_limit_settings,
+                set_node_on_first_message_only: _,
+                config_validators,
+            } = value;
+            unsupported_field!(
+                //todo(hayley): are these required to be set?
+                // api_type,
+                // transport_api_version,
+                // cluster_names,
+                // grpc_services,
+                // refresh_delay,
+                request_timeout,
+                // rate_limit_settings,
+                //set_node_on_first_message_only,
+                config_validators
+            )?;
+            let api_type = ApiType::try_from(api_type).with_node("api_type")?;
+            let rate_limit_settings =
+                rate_limit_settings.map(RateLimitSettings::try_from).transpose().with_node("rate_limit_settings")?;
+            let (grpc_cluster_specifiers, initial_metadata) = (|| -> std::result::Result<_, GenericError> {
+                let mut cluster_specifiers = Vec::new();
+                let mut metadata: Option<Vec<HeaderKeyValue>> = None;
+
+                for EnvoyGrpcService { timeout, initial_metadata, target_specifier, retry_policy } in
+                    required!(grpc_services)?
+                {
+                    unsupported_field!(timeout, retry_policy)?;
+                    let initial_metadata = initial_metadata
+                        .into_iter()
+                        .map(HeaderKeyValue::try_from)
+                        .collect::<std::result::Result<Vec<_>, _>>()
+                        .with_node("initial_metadata")?;
+                    // the clusters are fallbacks of one another, all of them being sent the same headers
+                    match &metadata {
+                        Some(metadata) if *metadata != initial_metadata => {
+                            return Err(GenericError::from_msg(
+                                "every grpc_service of a config source has to have the same initial_metadata",
+                            ));
+                        },
+                        Some(_) => {},
+                        None => metadata = Some(initial_metadata),
+                    }
+                    match required!(target_specifier)? {
+                        EnvoyGrpcTargetSpecifier::EnvoyGrpc(EnvoyGrpc {
+                            cluster_name,
+                            authority,
+                            retry_policy,
+                            max_receive_message_length,
+                            skip_envoy_headers,
+                        }) => {
+                            unsupported_field!(authority, retry_policy, max_receive_message_length, skip_envoy_headers)
+                                .with_node("target_specifier")?;
+                            let cluster_name = required!(cluster_name).with_node("target_specifier")?;
+                            cluster_specifiers.push(CompactString::from(cluster_name));
+                        },
+                        EnvoyGrpcTargetSpecifier::GoogleGrpc(_) => {
+                            return Err(GenericError::unsupported_variant("GoogleGrpc")).with_node("target_specifier");
+                        },
+                    }
+                }
+                Ok((cluster_specifiers, metadata.unwrap_or_default()))
+            })()
+            .with_node("grpc_services")?;
+            Ok(Self { api_type, grpc_cluster_specifiers, initial_metadata, max_backoff: None, rate_limit_settings })
+        }
+    }
+
+    impl TryFrom<EnvoyRateLimitSettings> for RateLimitSettings {
+        type Error = GenericError;
+        fn try_from(value: EnvoyRateLimitSettings) -> std::result::Result<Self, Self::Error> {
+            const DEFAULT_MAX_TOKENS: u32 = 100;
+            const DEFAULT_FILL_RATE: f64 = 10.0;
+            const SLOWEST_FILL_INTERVAL: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+            let EnvoyRateLimitSettings { max_tokens, fill_rate } = value;
+            let max_tokens = max_tokens.map_or(DEFAULT_MAX_TOKENS, |max_tokens| max_tokens.value);
+            let fill_rate = fill_rate.map_or(DEFAULT_FILL_RATE, |fill_rate| fill_rate.value);
+            if fill_rate.is_nan() || fill_rate <= 0.0 {
+                return Err(GenericError::from_msg(format!("fill_rate has to be positive, got {fill_rate}")))
+                    .with_node("fill_rate");
+            }
+            // rates lower than once a year are raised to once a year
+            let fill_interval = Duration::try_from_secs_f64(fill_rate.recip())
+                .map_or(SLOWEST_FILL_INTERVAL, |interval| interval.min(SLOWEST_FILL_INTERVAL));
+            Ok(Self { max_tokens, fill_interval })
+        }
+    }
+
     #[cfg(test)]
     mod tests {
         use crate::{config::Config, options::Options, Result};
@@ -15,10 +15,12 @@
 //
 //
 
-use std::time::Duration;
+use std::{collections::BTreeMap, num::NonZeroU64, time::Duration};
 
 use crate::config::{
-    cluster::Cluster, common::is_default, core::Address, listener::Listener, metrics::StatsSink, secret::Secret,
+    cluster::Cluster, common::is_default, core::Address, layered_runtime::LayeredRuntime, listener::Listener,
+    metrics::StatsSink, overload::OverloadManager, secret::Secret, xdstp::XdstpName, ApiConfigSource, ConfigSource,
+    ConfigSourceSpecifier,
 };
 use compact_str::CompactString;
 use serde::{Deserialize, Serialize};
@@ -39,11 +41,79 @@ pub struct Bootstrap {
     pub stats_sinks: Vec<StatsSink>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub bootstrap_extensions: Vec<BootstrapExtension>,
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub overload_manager: Option<OverloadManager>,
+    /// Maximum number of active downstream connections, across all listeners. Envoy sets it with the
+    /// `overload.global_downstream_max_connections` runtime key instead, which is honored as well.
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub max_downstream_connections: Option<NonZeroU64>,
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub layered_runtime: Option<LayeredRuntime>,
+    /// The load reporting service (LRS) server the load of the upstream clusters is reported to.
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub load_stats_config: Option<ApiConfigSource>,
+    /// The health discovery service (HDS) server assigning endpoints to health check, the results being streamed back.
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub hds_config: Option<ApiConfigSource>,
+    /// Where the resources of each authority of the `xdstp://` names are discovered from.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub config_sources: Vec<AuthorityConfigSource>,
+    /// Where the resources of the authorities without a config source of their own are discovered from.
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub default_config_source: Option<ConfigSource>,
+}
+
+/// A config source serving the resources of the `xdstp://` names of some authorities.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuthorityConfigSource {
+    pub authorities: Vec<CompactString>,
+    #[serde(flatten)]
+    pub config_source: ConfigSource,
+}
+
+impl AuthorityConfigSource {
+    /// The config source of an authority among these, the default one if none is set for it.
+    pub fn find<'a>(
+        config_sources: &'a [Self],
+        default_config_source: Option<&'a ConfigSource>,
+        authority: &str,
+    ) -> Option<&'a ConfigSource> {
+        config_sources
+            .iter()
+            .find(|config_source| config_source.authorities.iter().any(|name| name == authority))
+            .map(|config_source| &config_source.config_source)
+            .or(default_config_source)
+    }
 }
 
 impl Bootstrap {
+    /// Where the resources of an authority are discovered from, if anywhere.
+    pub fn authority_config_source(&self, authority: &str) -> Option<&ConfigSource> {
+        AuthorityConfigSource::find(&self.config_sources, self.default_config_source.as_ref(), authority)
+    }
+
+    pub fn get_ads_api_type(&self) -> ApiType {
+        self.dynamic_resources
+            .as_ref()
+            .map(|dynamic_resources| dynamic_resources.ads_config.api_type)
+            .unwrap_or_default()
+    }
+
     pub fn get_ads_configs(&self) -> &[CompactString] {
-        self.dynamic_resources.as_ref().map(|dr| dr.grpc_cluster_specifiers.as_slice()).unwrap_or_default()
+        self.dynamic_resources.as_ref().map(|dr| dr.ads_config.grpc_cluster_specifiers.as_slice()).unwrap_or_default()
+    }
+
+    /// Whether any resource is discovered dynamically, over ADS, a stream of its own or from a file.
+    pub fn has_dynamic_resources(&self) -> bool {
+        self.dynamic_resources.as_ref().is_some_and(|dr| {
+            !dr.ads_config.grpc_cluster_specifiers.is_empty()
+                || dr.lds_resources_locator.is_some()
+                || dr.cds_resources_locator.is_some()
+                || [&dr.lds_config, &dr.cds_config]
+                    .into_iter()
+                    .flatten()
+                    .any(|config_source| config_source.config_source_specifier != ConfigSourceSpecifier::ADS)
+        })
     }
 }
 
@@ -53,13 +123,16 @@ pub struct Node {
     pub cluster_id: CompactString,
     #[serde(skip_serializing, skip_deserializing)]
     pub metadata: Option<orion_data_plane_api::envoy_data_plane_api::google::protobuf::Struct>,
+    /// The fields of the node added to the `xdstp://` names subscribed to, as `xds.node.{field}` context parameters.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub context_params: BTreeMap<CompactString, CompactString>,
 }
 
 impl Eq for Node {}
 
 impl PartialEq for Node {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id && self.cluster_id == other.cluster_id
+        self.id == other.id && self.cluster_id == other.cluster_id && self.context_params == other.context_params
     }
 }
 
@@ -75,9 +148,34 @@ impl Node {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct DynamicResources {
-    pub grpc_cluster_specifiers: Vec<CompactString>,
+    /// The ADS management server, no ADS stream being opened when it has no clusters.
+    #[serde(flatten)]
+    pub ads_config: ApiConfigSource,
+    /// Where listeners are discovered from, over ADS when unset.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub lds_config: Option<ConfigSource>,
+    /// Where clusters are discovered from, over ADS when unset.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cds_config: Option<ConfigSource>,
+    /// The listeners discovered from the config source of the authority of the name, instead of `lds_config`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub lds_resources_locator: Option<XdstpName>,
+    /// The clusters discovered from the config source of the authority of the name, instead of `cds_config`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cds_resources_locator: Option<XdstpName>,
+}
+
+/// The flavour of the xDS protocol spoken with the management server.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiType {
+    /// State of the world: each response holds every resource of its type.
+    Grpc,
+    /// Incremental: each response only holds the resources that changed.
+    #[default]
+    DeltaGrpc,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -112,22 +210,24 @@ pub struct StaticResources {
 mod envoy_conversions {
     #![allow(deprecated)]
     use super::{
-        Admin, Bootstrap, BootstrapExtension, DynamicResources, InternalListenerBootstrap, Node, StaticResources,
+        Admin, ApiType, AuthorityConfigSource, Bootstrap, BootstrapExtension, DynamicResources,
+        InternalListenerBootstrap, Node, StaticResources,
+    };
+    use crate::config::{
+        common::*, grpc::Duration, layered_runtime::LayeredRuntime, metrics::StatsSink, overload::OverloadManager,
+        xdstp::XdstpName, ApiConfigSource, ConfigSource, ConfigSourceSpecifier,
     };
-    use crate::config::{common::*, grpc::Duration, metrics::StatsSink};
     use compact_str::CompactString;
     use orion_data_plane_api::envoy_data_plane_api::{
         envoy::{
             config::{
                 bootstrap::v3::{
                     bootstrap::{DynamicResources as EnvoyDynamicResources, StaticResources as EnvoyStaticResources},
-                    Admin as EnvoyAdmin, Bootstrap as EnvoyBootstrap,
+                    Admin as EnvoyAdmin, Bootstrap as EnvoyBootstrap, ClusterManager as EnvoyClusterManager,
                 },
                 core::v3::{
-                    address,
-                    grpc_service::{EnvoyGrpc, TargetSpecifier as EnvoyGrpcTargetSpecifier},
-                    ApiConfigSource as EnvoyApiConfigSource, GrpcService as EnvoyGrpcService, Node as EnvoyNode,
-                    TypedExtensionConfig as EnvoyTypedExtensionConfig,
+                    address, api_config_source::ApiType as EnvoyApiType, ConfigSource as EnvoyConfigSource,
+                    Node as EnvoyNode, TypedExtensionConfig as EnvoyTypedExtensionConfig,
                 },
                 metrics::v3::stats_sink::ConfigType,
             },
@@ -136,7 +236,9 @@ mod envoy_conversions {
         google::protobuf::value::Kind,
         prost::Message,
         udpa::r#type::v1::TypedStruct,
+        xds::core::v3::Authority as EnvoyAuthority,
     };
+    use std::collections::BTreeMap;
 
     use tracing::debug;
 
@@ -167,7 +269,7 @@ mod envoy_conversions {
                 watchdog,
                 watchdogs,
                 tracing,
-                layered_runtime: _,
+                layered_runtime,
                 admin,
                 overload_manager,
                 enable_dispatcher_stats,
@@ -196,11 +298,11 @@ mod envoy_conversions {
             } = envoy;
             unsupported_field!(
                 // node,
-                node_context_params,
+                // node_context_params,
                 // static_resources,
                 // dynamic_resources,
-                cluster_manager,
-                hds_config,
+                // cluster_manager,
+                // hds_config,
                 flags_path,
                 // stats_sinks,
                 //deferred_stat_options,
@@ -211,7 +313,7 @@ mod envoy_conversions {
                 tracing,
                 //layered_runtime,
                 //admin,
-                overload_manager,
+                //overload_manager,
                 enable_dispatcher_stats,
                 header_prefix,
                 stats_server_version_override,
@@ -220,8 +322,8 @@ mod envoy_conversions {
                 typed_dns_resolver_config,
                 //bootstrap_extensions,
                 fatal_actions,
-                config_sources,
-                default_config_source,
+                // config_sources,
+                // default_config_source,
                 default_socket_interface,
                 certificate_provider_instances,
                 inline_headers,
@@ -238,8 +340,28 @@ mod envoy_conversions {
             let static_resources = convert_opt!(static_resources)?;
             let dynamic_resources =
                 dynamic_resources.map(DynamicResources::try_from).transpose().with_node("dynamic_resources")?;
-            let node = node.map(Node::try_from).transpose().with_node("node")?;
+            let mut node = node.map(Node::try_from).transpose().with_node("node")?;
+            if !node_context_params.is_empty() {
+                let node = node.as_mut().ok_or(GenericError::MissingField("node")).with_node("node_context_params")?;
+                node.context_params = node_context_params
+                    .iter()
+                    .map(|field| node.context_param(field).map(|value| (format!("xds.node.{field}").into(), value)))
+                    .collect::<Result<_, _>>()
+                    .with_node("node_context_params")?;
+            }
             let admin = admin.map(Admin::try_from).transpose().with_node("admin")?;
+            let overload_manager =
+                overload_manager.map(OverloadManager::try_from).transpose().with_node("overload_manager")?;
+            let layered_runtime =
+                layered_runtime.map(LayeredRuntime::try_from).transpose().with_node("layered_runtime")?;
+            let hds_config = hds_config.map(ApiConfigSource::try_from).transpose().with_node("hds_config")?;
+            let config_sources = config_sources
+                .into_iter()
+                .map(AuthorityConfigSource::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .with_node("config_sources")?;
+            let default_config_source =
+                default_config_source.map(ConfigSource::try_from).transpose().with_node("default_config_source")?;
             let stats_flush_interval = stats_flush_interval
                 .map(|d| Duration::try_from(d).map(|d| d.0))
                 .transpose()
@@ -261,7 +383,28 @@ mod envoy_conversions {
                 .collect::<Result<Vec<_>, _>>()
                 .with_node("bootstrap_extensions")?;
 
-            Ok(Self {
+            let load_stats_config = cluster_manager
+                .map(|cluster_manager| {
+                    let EnvoyClusterManager {
+                        local_cluster_name,
+                        outlier_detection,
+                        upstream_bind_config,
+                        load_stats_config,
+                        enable_deferred_cluster_creation,
+                    } = cluster_manager;
+                    unsupported_field!(
+                        local_cluster_name,
+                        outlier_detection,
+                        upstream_bind_config,
+                        enable_deferred_cluster_creation
+                    )?;
+                    load_stats_config.map(ApiConfigSource::try_from).transpose().with_node("load_stats_config")
+                })
+                .transpose()
+                .with_node("cluster_manager")?
+                .flatten();
+
+            Self {
                 static_resources,
                 node,
                 dynamic_resources,
@@ -269,7 +412,128 @@ mod envoy_conversions {
                 stats_flush_interval,
                 stats_sinks,
                 bootstrap_extensions,
-            })
+                overload_manager,
+                // Envoy sets it with the runtime, read as connections are accepted
+                max_downstream_connections: None,
+                layered_runtime,
+                load_stats_config,
+                hds_config,
+                config_sources,
+                default_config_source,
+            }
+            .with_resources_locators_checked()
+        }
+    }
+
+    impl Bootstrap {
+        /// Resources locators have to be served by a management server of the incremental protocol, the only one
+        /// glob collections are subscribed to over.
+        fn with_resources_locators_checked(self) -> Result<Self, GenericError> {
+            let Some(dynamic_resources) = &self.dynamic_resources else {
+                return Ok(self);
+            };
+            for (locator, config_source, resource_type, field) in [
+                (
+                    &dynamic_resources.lds_resources_locator,
+                    &dynamic_resources.lds_config,
+                    LISTENER_RESOURCE_TYPE,
+                    "lds_resources_locator",
+                ),
+                (
+                    &dynamic_resources.cds_resources_locator,
+                    &dynamic_resources.cds_config,
+                    CLUSTER_RESOURCE_TYPE,
+                    "cds_resources_locator",
+                ),
+            ] {
+                let Some(locator) = locator else {
+                    continue;
+                };
+                let check = || {
+                    if locator.resource_type != resource_type {
+                        return Err(GenericError::from_msg(format!(
+                            "{locator} doesn't name {resource_type} resources"
+                        )));
+                    }
+                    let api_type = match self.authority_config_source(&locator.authority).or(config_source.as_ref()) {
+                        None => {
+                            return Err(GenericError::from_msg(format!(
+                                "no config source serves the authority {}",
+                                locator.authority
+                            )))
+                        },
+                        Some(ConfigSource { config_source_specifier: ConfigSourceSpecifier::ADS, .. }) => {
+                            if self.get_ads_configs().is_empty() {
+                                return Err(GenericError::from_msg("ADS is used without an ads_config"));
+                            }
+                            self.get_ads_api_type()
+                        },
+                        Some(ConfigSource {
+                            config_source_specifier: ConfigSourceSpecifier::ApiConfigSource(api_config_source),
+                            ..
+                        }) => api_config_source.api_type,
+                        Some(ConfigSource {
+                            config_source_specifier: ConfigSourceSpecifier::PathConfigSource(_),
+                            ..
+                        }) => {
+                            return Err(GenericError::from_msg(format!(
+                                "the authority {} is served from a file",
+                                locator.authority
+                            )))
+                        },
+                    };
+                    if api_type == ApiType::DeltaGrpc {
+                        Ok(())
+                    } else {
+                        Err(GenericError::from_msg(format!(
+                            "the authority {} is served over the state of the world protocol",
+                            locator.authority
+                        )))
+                    }
+                };
+                check().with_node(field).with_node("dynamic_resources")?;
+            }
+            Ok(self)
+        }
+    }
+
+    const LISTENER_RESOURCE_TYPE: &str = "envoy.config.listener.v3.Listener";
+    const CLUSTER_RESOURCE_TYPE: &str = "envoy.config.cluster.v3.Cluster";
+
+    impl Node {
+        /// The value of a field of the node, as a context parameter.
+        fn context_param(&self, field: &str) -> Result<CompactString, GenericError> {
+            let value = match field {
+                "id" => Some(self.id.clone()),
+                "cluster" => Some(self.cluster_id.clone()),
+                _ => {
+                    let key = field
+                        .strip_prefix("metadata.")
+                        .ok_or_else(|| GenericError::from_msg(format!("{field} is not a supported node field")))?;
+                    let value = self.metadata.as_ref().and_then(|metadata| metadata.fields.get(key));
+                    match value.and_then(|value| value.kind.as_ref()) {
+                        Some(Kind::StringValue(value)) => Some(value.into()),
+                        Some(Kind::NumberValue(value)) => Some(value.to_string().into()),
+                        Some(Kind::BoolValue(value)) => Some(value.to_string().into()),
+                        Some(_) => {
+                            return Err(GenericError::from_msg(format!("{field} is not a string, number or bool")))
+                        },
+                        None => None,
+                    }
+                },
+            };
+            value.ok_or_else(|| GenericError::from_msg(format!("the node has no {field}")))
+        }
+    }
+
+    impl TryFrom<EnvoyConfigSource> for AuthorityConfigSource {
+        type Error = GenericError;
+        fn try_from(mut value: EnvoyConfigSource) -> Result<Self, Self::Error> {
+            let authorities = std::mem::take(&mut value.authorities);
+            let authorities: Vec<CompactString> =
+                required!(authorities)?.into_iter().map(|EnvoyAuthority { name }| name.into()).collect();
+            let config_source = ConfigSource::try_from(value)?;
+            Ok(Self { authorities, config_source })
         }
     }
     impl TryFrom<EnvoyNode> for Node {
@@ -302,81 +566,72 @@ mod envoy_conversions {
 
             let id = required!(id)?.into();
             let cluster = required!(cluster)?.into();
-            Ok(Self { id, cluster_id: cluster, metadata })
+            Ok(Self { id, cluster_id: cluster, metadata, context_params: BTreeMap::new() })
+        }
+    }
+
+    /// The node as it introduces itself to management servers, which the context parameters are not part of.
+    impl From<&Node> for EnvoyNode {
+        fn from(value: &Node) -> Self {
+            let Node { id, cluster_id, metadata, context_params: _ } = value;
+            Self {
+                id: id.to_string(),
+                cluster: cluster_id.to_string(),
+                metadata: metadata.clone(),
+                ..Default::default()
+            }
         }
     }
     impl TryFrom<EnvoyDynamicResources> for DynamicResources {
         type Error = GenericError;
         fn try_from(value: EnvoyDynamicResources) -> Result<Self, Self::Error> {
             let EnvoyDynamicResources {
-                lds_config: _,
+                lds_config,
                 lds_resources_locator,
-                cds_config: _,
+                cds_config,
                 cds_resources_locator,
                 ads_config,
             } = value;
-            unsupported_field!(lds_resources_locator, cds_resources_locator)?;
-            let EnvoyApiConfigSource {
-                api_type: _,
-                transport_api_version: _,
-                cluster_names: _,
-                grpc_services,
-                refresh_delay,
-                request_timeout,
-                rate_limit_settings,
-                set_node_on_first_message_only: _,
-                config_validators,
-            } = required!(ads_config)?;
-            let grpc_cluster_specifiers = (|| -> Result<_, GenericError> {
-                unsupported_field!(
-                    //todo(hayley): are these required to be set?
-                    // api_type,
-                    // transport_api_version,
-                    // cluster_names,
-                    // grpc_services,
-                    refresh_delay,
-                    request_timeout,
-                    rate_limit_settings,
-                    //set_node_on_first_message_only,
-                    config_validators
-                )?;
-                (|| -> Result<_, GenericError> {
-                    let mut cluster_specifiers = Vec::new();
-
-                    for EnvoyGrpcService { timeout, initial_metadata, target_specifier, retry_policy } in
-                        required!(grpc_services)?
+            let lds_resources_locator = lds_resources_locator
+                .is_used()
+                .then(|| lds_resources_locator.parse::<XdstpName>())
+                .transpose()
+                .with_node("lds_resources_locator")?;
+            let cds_resources_locator = cds_resources_locator
+                .is_used()
+                .then(|| cds_resources_locator.parse::<XdstpName>())
+                .transpose()
+                .with_node("cds_resources_locator")?;
+            let ads_config =
+                ads_config.map(ApiConfigSource::try_from).transpose().with_node("ads_config")?.unwrap_or_default();
+            let lds_config = lds_config.map(ConfigSource::try_from).transpose().with_node("lds_config")?;
+            let cds_config = cds_config.map(ConfigSource::try_from).transpose().with_node("cds_config")?;
+            if ads_config.grpc_cluster_specifiers.is_empty() {
+                for (config_source, field) in [(&lds_config, "lds_config"), (&cds_config, "cds_config")] {
+                    if let Some(ConfigSource { config_source_specifier: ConfigSourceSpecifier::ADS, .. }) =
+                        config_source
                     {
-                        unsupported_field!(timeout, initial_metadata, retry_policy)?;
-                        match required!(target_specifier)? {
-                            EnvoyGrpcTargetSpecifier::EnvoyGrpc(EnvoyGrpc {
-                                cluster_name,
-                                authority,
-                                retry_policy,
-                                max_receive_message_length,
-                                skip_envoy_headers,
-                            }) => {
-                                unsupported_field!(
-                                    authority,
-                                    retry_policy,
-                                    max_receive_message_length,
-                                    skip_envoy_headers
-                                )
-                                .with_node("target_specifier")?;
-                                let cluster_name = required!(cluster_name).with_node("target_specifier")?;
-                                cluster_specifiers.push(CompactString::from(cluster_name))
-                            },
-                            EnvoyGrpcTargetSpecifier::GoogleGrpc(_) => {
-                                return Err(GenericError::unsupported_variant("GoogleGrpc"))
-                                    .with_node("target_specifier");
-                            },
-                        }
+                        return Err(GenericError::from_msg("ADS is used without an ads_config")).with_node(field);
                     }
-                    Ok(cluster_specifiers)
-                })()
-                .with_node("grpc_services")
-            })()
-            .with_node("ads_config")?;
-            Ok(DynamicResources { grpc_cluster_specifiers })
+                }
+            }
+            Ok(DynamicResources { ads_config, lds_config, cds_config, lds_resources_locator, cds_resources_locator })
+        }
+    }
+
+    impl TryFrom<i32> for ApiType {
+        type Error = GenericError;
+        fn try_from(value: i32) -> Result<Self, Self::Error> {
+            let api_type = EnvoyApiType::from_i32(value)
+                .ok_or_else(|| GenericError::from_msg(format!("[unknown ApiType {value}]")))?;
+            match api_type {
+                // an unset api_type keeps the incremental protocol, which is what was always used before
+                EnvoyApiType::DeprecatedAndUnavailableDoNotUse
+                | EnvoyApiType::DeltaGrpc
+                | EnvoyApiType::AggregatedDeltaGrpc => Ok(Self::DeltaGrpc),
+                EnvoyApiType::Grpc | EnvoyApiType::AggregatedGrpc => Ok(Self::Grpc),
+                EnvoyApiType::Rest => Err(GenericError::unsupported_variant("Rest")),
+            }
         }
     }
     impl TryFrom<EnvoyStaticResources> for StaticResources {
@@ -475,4 +730,249 @@ mod envoy_conversions {
             Ok(Self { buffer_size_kb })
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::super::{ApiType, Bootstrap, DynamicResources};
+        use crate::config::{
+            network_filters::http_connection_manager::header_modifer::HeaderKeyValue, ApiConfigSource, ConfigSource,
+            ConfigSourceSpecifier, PathConfigSource, RateLimitSettings,
+        };
+        use compact_str::CompactString;
+
+        #[test]
+        fn dynamic_resources_from_different_management_servers() {
+            const BOOTSTRAP: &str = r#"
+static_resources: {}
+dynamic_resources:
+  ads_config:
+    api_type: DELTA_GRPC
+    grpc_services:
+    - envoy_grpc:
+        cluster_name: ads_cluster
+  lds_config:
+    api_config_source:
+      api_type: GRPC
+      grpc_services:
+      - envoy_grpc:
+          cluster_name: lds_cluster
+  cds_config:
+    ads: {}
+    initial_fetch_timeout: 0s
+"#;
+            let bootstrap = Bootstrap::deserialize_from_envoy(BOOTSTRAP.as_bytes()).unwrap();
+            assert!(bootstrap.has_dynamic_resources());
+            let dynamic_resources = bootstrap.dynamic_resources.clone().unwrap();
+            assert_eq!(dynamic_resources.lds_config.unwrap().fetch_timeout(), Some(std::time::Duration::from_secs(15)));
+            assert_eq!(dynamic_resources.cds_config.unwrap().fetch_timeout(), None);
+            assert_eq!(
+                bootstrap.dynamic_resources,
+                Some(DynamicResources {
+                    ads_config: ApiConfigSource {
+                        api_type: ApiType::DeltaGrpc,
+                        grpc_cluster_specifiers: vec!["ads_cluster".into()],
+                        ..Default::default()
+                    },
+                    lds_config: Some(ConfigSource {
+                        config_source_specifier: ConfigSourceSpecifier::ApiConfigSource(ApiConfigSource {
+                            api_type: ApiType::Grpc,
+                            grpc_cluster_specifiers: vec!["lds_cluster".into()],
+                            ..Default::default()
+                        }),
+                        initial_fetch_timeout: None,
+                    }),
+                    cds_config: Some(ConfigSource {
+                        config_source_specifier: ConfigSourceSpecifier::ADS,
+                        initial_fetch_timeout: Some(std::time::Duration::ZERO),
+                    }),
+                    lds_resources_locator: None,
+                    cds_resources_locator: None,
+                })
+            );
+
+            let without_ads = "
+static_resources: {}
+dynamic_resources:
+  cds_config:
+    ads: {}
+";
+            let err = Bootstrap::deserialize_from_envoy(without_ads.as_bytes()).unwrap_err();
+            assert!(format!("{err:?}").contains("without an ads_config"), "{err:?}");
+        }
+
+        #[test]
+        fn ads_config_with_fallbacks() {
+            const BOOTSTRAP: &str = r#"
+static_resources: {}
+dynamic_resources:
+  ads_config:
+    api_type: GRPC
+    refresh_delay: 3s
+    rate_limit_settings:
+      max_tokens: 5
+      fill_rate: 2
+    grpc_services:
+    - envoy_grpc:
+        cluster_name: primary
+      initial_metadata:
+      - key: authorization
+        value: Bearer token
+    - envoy_grpc:
+        cluster_name: secondary
+      initial_metadata:
+      - key: authorization
+        value: Bearer token
+"#;
+            let bootstrap = Bootstrap::deserialize_from_envoy(BOOTSTRAP.as_bytes()).unwrap();
+            let ads_config = bootstrap.dynamic_resources.unwrap().ads_config;
+            assert_eq!(ads_config.grpc_cluster_specifiers, vec!["primary", "secondary"]);
+            assert_eq!(
+                ads_config.initial_metadata,
+                vec![HeaderKeyValue::try_from(("authorization".to_owned(), "Bearer token".to_owned())).unwrap()]
+            );
+            // refresh_delay only paces REST polling
+            assert_eq!(ads_config.max_backoff, None);
+            assert_eq!(
+                ads_config.rate_limit_settings,
+                Some(RateLimitSettings { max_tokens: 5, fill_interval: std::time::Duration::from_millis(500) })
+            );
+
+            let different_metadata = BOOTSTRAP.replacen("Bearer token", "Bearer other", 1);
+            let err = Bootstrap::deserialize_from_envoy(different_metadata.as_bytes()).unwrap_err();
+            assert!(format!("{err:?}").contains("the same initial_metadata"), "{err:?}");
+        }
+
+        #[test]
+        fn dynamic_resources_from_files() {
+            const BOOTSTRAP: &str = r#"
+static_resources: {}
+dynamic_resources:
+  lds_config:
+    path: /etc/orion/lds.yaml
+  cds_config:
+    path_config_source:
+      path: /etc/orion/config/cds.yaml
+      watched_directory:
+        path: /etc/orion/config
+"#;
+            let bootstrap = Bootstrap::deserialize_from_envoy(BOOTSTRAP.as_bytes()).unwrap();
+            assert!(bootstrap.has_dynamic_resources());
+            let dynamic_resources = bootstrap.dynamic_resources.unwrap();
+            assert_eq!(
+                dynamic_resources.lds_config,
+                Some(ConfigSource {
+                    config_source_specifier: ConfigSourceSpecifier::PathConfigSource(PathConfigSource {
+                        path: "/etc/orion/lds.yaml".into(),
+                        watched_directory: None,
+                    }),
+                    initial_fetch_timeout: None,
+                })
+            );
+            assert_eq!(
+                dynamic_resources.cds_config,
+                Some(ConfigSource {
+                    config_source_specifier: ConfigSourceSpecifier::PathConfigSource(PathConfigSource {
+                        path: "/etc/orion/config/cds.yaml".into(),
+                        watched_directory: Some("/etc/orion/config".into()),
+                    }),
+                    initial_fetch_timeout: None,
+                })
+            );
+        }
+
+        #[test]
+        fn load_reported_to_lrs_server() {
+            const BOOTSTRAP: &str = r#"
+static_resources: {}
+cluster_manager:
+  load_stats_config:
+    api_type: GRPC
+    grpc_services:
+    - envoy_grpc:
+        cluster_name: lrs_cluster
+"#;
+            let bootstrap = Bootstrap::deserialize_from_envoy(BOOTSTRAP.as_bytes()).unwrap();
+            let load_stats_config = bootstrap.load_stats_config.unwrap();
+            assert_eq!(load_stats_config.grpc_cluster_specifiers, vec!["lrs_cluster"]);
+
+            let with_local_cluster =
+                BOOTSTRAP.replace("cluster_manager:", "cluster_manager:\n  local_cluster_name: local");
+            let err = Bootstrap::deserialize_from_envoy(with_local_cluster.as_bytes()).unwrap_err();
+            assert!(format!("{err:?}").contains("local_cluster_name"), "{err:?}");
+        }
+
+        #[test]
+        fn endpoints_health_checked_for_hds_server() {
+            const BOOTSTRAP: &str = r#"
+static_resources: {}
+hds_config:
+  api_type: GRPC
+  grpc_services:
+  - envoy_grpc:
+      cluster_name: hds_cluster
+"#;
+            let bootstrap = Bootstrap::deserialize_from_envoy(BOOTSTRAP.as_bytes()).unwrap();
+            let hds_config = bootstrap.hds_config.unwrap();
+            assert_eq!(hds_config.grpc_cluster_specifiers, vec!["hds_cluster"]);
+
+            let over_rest = BOOTSTRAP.replace("api_type: GRPC", "api_type: REST");
+            assert!(Bootstrap::deserialize_from_envoy(over_rest.as_bytes()).is_err());
+        }
+
+        #[test]
+        fn resources_of_authorities_from_their_own_config_sources() {
+            const BOOTSTRAP: &str = r#"
+static_resources: {}
+node:
+  id: proxy-1
+  cluster: edge
+  metadata:
+    zone: eu-1
+node_context_params: [id, metadata.zone]
+dynamic_resources:
+  lds_resources_locator: xdstp://listeners.acme/envoy.config.listener.v3.Listener/edge/*
+  cds_resources_locator: xdstp://clusters.acme/envoy.config.cluster.v3.Cluster/*
+config_sources:
+- authorities:
+  - name: listeners.acme
+  api_config_source:
+    api_type: DELTA_GRPC
+    grpc_services:
+    - envoy_grpc:
+        cluster_name: listeners_control_plane
+default_config_source:
+  api_config_source:
+    api_type: DELTA_GRPC
+    grpc_services:
+    - envoy_grpc:
+        cluster_name: default_control_plane
+"#;
+            let bootstrap = Bootstrap::deserialize_from_envoy(BOOTSTRAP.as_bytes()).unwrap();
+            assert!(bootstrap.has_dynamic_resources());
+            let context_params = &bootstrap.node.as_ref().unwrap().context_params;
+            assert_eq!(context_params.get("xds.node.id").map(CompactString::as_str), Some("proxy-1"));
+            assert_eq!(context_params.get("xds.node.metadata.zone").map(CompactString::as_str), Some("eu-1"));
+            let dynamic_resources = bootstrap.dynamic_resources.clone().unwrap();
+            assert!(dynamic_resources.lds_resources_locator.unwrap().is_glob());
+            let grpc_cluster = |authority| match bootstrap.authority_config_source(authority) {
+                Some(ConfigSource {
+                    config_source_specifier: ConfigSourceSpecifier::ApiConfigSource(api_config_source),
+                    ..
+                }) => api_config_source.grpc_cluster_specifiers.first().cloned(),
+                _ => None,
+            };
+            assert_eq!(grpc_cluster("listeners.acme").as_deref(), Some("listeners_control_plane"));
+            assert_eq!(grpc_cluster("clusters.acme").as_deref(), Some("default_control_plane"));
+
+            let over_sotw = BOOTSTRAP.replace("api_type: DELTA_GRPC", "api_type: GRPC");
+            let err = Bootstrap::deserialize_from_envoy(over_sotw.as_bytes()).unwrap_err();
+            assert!(format!("{err:?}").contains("state of the world"), "{err:?}");
+
+            let unknown_field = BOOTSTRAP.replace("metadata.zone]", "locality.zone]");
+            assert!(Bootstrap::deserialize_from_envoy(unknown_field.as_bytes()).is_err());
+
+            let without_authorities = BOOTSTRAP.replace("- authorities:\n  - name: listeners.acme\n  api", "- api");
+            assert!(Bootstrap::deserialize_from_envoy(without_authorities.as_bytes()).is_err());
+        }
+    }
 }
@@ -17,6 +17,8 @@
 
 pub mod health_check;
 pub use health_check::HealthCheck;
+pub mod outlier_detection;
+pub use outlier_detection::OutlierDetection;
 pub mod http_protocol_options;
 pub use http_protocol_options::HttpProtocolOptions;
 pub mod cluster_specifier;
@@ -31,7 +33,7 @@ use crate::config::{
 use super::{
     common::{is_default, MetadataKey},
     secret::TlsCertificate,
-    transport::{CommonTlsValidationContext, TlsParameters, UpstreamTransportSocketConfig},
+    transport::{CommonTlsValidationContext, SdsConfig, TlsParameters, UpstreamTransportSocketConfig},
 };
 
 use compact_str::CompactString;
@@ -53,6 +55,8 @@ pub struct Cluster {
     pub http_protocol_options: HttpProtocolOptions,
     #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
     pub health_check: Option<HealthCheck>,
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub outlier_detection: Option<OutlierDetection>,
     #[serde(with = "humantime_serde")]
     #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
     pub connect_timeout: Option<Duration>,
@@ -82,13 +86,29 @@ pub struct EdsClusterConfig {
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LocalityLbEndpoints {
     pub priority: u32,
+    /// Where the endpoints are, as the load reported for them is broken down by.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub locality: Option<Locality>,
     //#[serde(serialize_with = "simplify_lb_endpoints", deserialize_with = "deser_through::<LbEndpointVecDeser,_,_>")]
     pub lb_endpoints: Vec<LbEndpoint>,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Locality {
+    #[serde(skip_serializing_if = "CompactString::is_empty", default)]
+    pub region: CompactString,
+    #[serde(skip_serializing_if = "CompactString::is_empty", default)]
+    pub zone: CompactString,
+    #[serde(skip_serializing_if = "CompactString::is_empty", default)]
+    pub sub_zone: CompactString,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct LbEndpoint {
     pub address: Address,
+    /// the host of the endpoint, which `auto_host_rewrite` rewrites the authority to
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub hostname: Option<CompactString>,
     #[serde(skip_serializing_if = "is_default", default)]
     pub health_status: HealthStatus,
     pub load_balancing_weight: NonZeroU32,
@@ -172,6 +192,8 @@ pub enum ClusterDiscoveryType {
     Static(ClusterLoadAssignment),
     #[serde(rename = "stict_dns")]
     StrictDns(ClusterLoadAssignment),
+    #[serde(rename = "logical_dns")]
+    LogicalDns(ClusterLoadAssignment),
     // The ClusterLoadAssignment is optional for EDS clusters since it cannot be
     // configured statically in the bootstrap, but we need to assign it to the
     // serializable type when returning the EDS cluster running configuration
@@ -224,7 +246,7 @@ pub struct TlsConfig {
 #[serde(rename_all = "snake_case")]
 pub enum TlsSecret {
     #[serde(rename = "tls_certificate_sds")]
-    SdsConfig(CompactString),
+    SdsConfig(SdsConfig),
     #[serde(rename = "tls_certificate")]
     Certificate(TlsCertificate),
 }
@@ -247,8 +269,9 @@ mod envoy_conversions {
     use super::{
         health_check::{ClusterHostnameError, HealthCheck, HealthCheckProtocol},
         Cluster, ClusterDiscoveryType, ClusterLoadAssignment, HealthStatus, HttpProtocolOptions,
-        InternalUpstreamTransport, LbEndpoint, LbPolicy, LocalityLbEndpoints, MetadataKind, MetadataValueSource,
-        OriginalDstConfig, OriginalDstRoutingMethod, TlsConfig, TlsSecret, TransportSocket,
+        InternalUpstreamTransport, LbEndpoint, LbPolicy, Locality, LocalityLbEndpoints, MetadataKind,
+        MetadataValueSource, OriginalDstConfig, OriginalDstRoutingMethod, OutlierDetection, TlsConfig, TlsSecret,
+        TransportSocket,
     };
     use crate::config::{
         cluster::EdsClusterConfig,
@@ -274,7 +297,7 @@ mod envoy_conversions {
                     Cluster as EnvoyCluster,
                 },
                 core::v3::{
-                    BindConfig as EnvoyBindConfig, HealthStatus as EnvoyHealthStatus,
+                    BindConfig as EnvoyBindConfig, HealthStatus as EnvoyHealthStatus, Locality as EnvoyLocality,
                     TransportSocket as EnvoyTransportSocket,
                 },
                 endpoint::v3::{
@@ -384,7 +407,7 @@ mod envoy_conversions {
                     dns_resolution_config,
                     typed_dns_resolver_config,
                     wait_for_warm_on_init,
-                    outlier_detection,
+                    // outlier_detection,
                     // cleanup_interval,
                     // upstream_bind_config,
                     lb_subset_config,
@@ -589,6 +612,10 @@ mod envoy_conversions {
                     }
                 }
 
+                let outlier_detection = outlier_detection
+                    .map(OutlierDetection::try_from)
+                    .transpose()
+                    .with_node("outlier_detection")?;
                 let connect_timeout = connect_timeout
                     .map(duration_from_envoy)
                     .transpose()
@@ -607,6 +634,7 @@ mod envoy_conversions {
                     load_balancing_policy,
                     http_protocol_options,
                     health_check,
+                    outlier_detection,
                     connect_timeout,
                     cleanup_interval,
                     internal_transport_socket: transport_socket_config,
@@ -648,7 +676,7 @@ mod envoy_conversions {
         type Error = GenericError;
         fn try_from(value: EnvoyLocalityLbEndpoints) -> Result<Self, Self::Error> {
             let EnvoyLocalityLbEndpoints {
-                locality: _,
+                locality,
                 lb_endpoints,
                 load_balancing_weight: _,
                 priority,
@@ -667,7 +695,22 @@ mod envoy_conversions {
                         .with_node("lb_endpoints");
                 }
             }
-            Ok(Self { lb_endpoints, priority })
+            let locality = locality.map(Locality::from);
+            Ok(Self { lb_endpoints, priority, locality })
+        }
+    }
+
+    impl From<EnvoyLocality> for Locality {
+        fn from(value: EnvoyLocality) -> Self {
+            let EnvoyLocality { region, zone, sub_zone } = value;
+            Self { region: region.into(), zone: zone.into(), sub_zone: sub_zone.into() }
+        }
+    }
+
+    impl From<Locality> for EnvoyLocality {
+        fn from(value: Locality) -> Self {
+            let Locality { region, zone, sub_zone } = value;
+            Self { region: region.into(), zone: zone.into(), sub_zone: sub_zone.into() }
         }
     }
 
@@ -704,15 +747,16 @@ mod envoy_conversions {
             let EnvoyLbEndpoint { health_status, metadata: _istio_ignore, load_balancing_weight, host_identifier } =
                 value;
 
-            let address = match required!(host_identifier)? {
+            let (address, hostname) = match required!(host_identifier)? {
                 EnvoyHostIdentifier::Endpoint(EnvoyEndpoint {
                     address,
                     health_check_config,
                     hostname,
                     additional_addresses,
-                }) => (|| -> Result<Address, GenericError> {
-                    unsupported_field!(health_check_config, hostname, additional_addresses)?;
-                    convert_opt!(address)
+                }) => (|| -> Result<(Address, Option<CompactString>), GenericError> {
+                    unsupported_field!(health_check_config, additional_addresses)?;
+                    let hostname = hostname.is_used().then(|| hostname.into());
+                    Ok((convert_opt!(address)?, hostname))
                 })(),
                 EnvoyHostIdentifier::EndpointName(_) => Err(GenericError::unsupported_variant("EndpointName")),
             }
@@ -722,7 +766,7 @@ mod envoy_conversions {
                 .map_err(|_| GenericError::from_msg("load_balancing_weight can't be zero"))
                 .with_node("load_balancing_weight")?;
             let health_status = health_status.try_into().with_node("health_status")?;
-            Ok(Self { address, health_status, load_balancing_weight })
+            Ok(Self { address, hostname, health_status, load_balancing_weight })
         }
     }
 
@@ -785,7 +829,18 @@ mod envoy_conversions {
                         Ok(Self::Eds(None, None))
                     }
                 },
-                (EnvoyDiscoveryType::LogicalDns, _) => Err(GenericError::unsupported_variant("LogicalDns")),
+                (EnvoyDiscoveryType::LogicalDns, Some(cla)) => {
+                    if cla.endpoints.iter().map(|e| e.lb_endpoints.len()).sum::<usize>() == 1 {
+                        Ok(ClusterDiscoveryType::LogicalDns(cla))
+                    } else {
+                        Err(GenericError::from_msg(
+                            "Logical DNS clusters are required to have exactly one endpoint configured",
+                        ))
+                    }
+                },
+                (EnvoyDiscoveryType::LogicalDns, None) => Err(GenericError::from_msg(
+                    "Logical DNS clusters are required to have a cluster load assignment configured",
+                )),
                 (EnvoyDiscoveryType::StrictDns, Some(cla)) => Ok(ClusterDiscoveryType::StrictDns(cla)),
                 (EnvoyDiscoveryType::StrictDns, None) => Err(GenericError::from_msg(
                     "Strict DNS clusters are required to have a cluster load assignment configured",
@@ -41,6 +41,9 @@ impl ClusterSpecifier {
 pub struct WeightedClusterSpecifier {
     pub cluster: CompactString,
     pub weight: NonZeroU32,
+    /// Runtime key overriding the weight, evaluated for each request.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub runtime_key: Option<CompactString>,
 }
 
 #[cfg(feature = "envoy-conversions")]
@@ -64,8 +67,13 @@ mod envoy_conversions {
         type Error = GenericError;
         fn try_from(value: EnvoyWeightedCluster) -> Result<Self, Self::Error> {
             let EnvoyWeightedCluster { clusters, total_weight, runtime_key_prefix, random_value_specifier } = value;
-            unsupported_field!(total_weight, runtime_key_prefix, random_value_specifier)?;
-            let clusters: Vec<WeightedClusterSpecifier> = convert_non_empty_vec!(clusters)?;
+            unsupported_field!(total_weight, random_value_specifier)?;
+            let mut clusters: Vec<WeightedClusterSpecifier> = convert_non_empty_vec!(clusters)?;
+            if runtime_key_prefix.is_used() {
+                for cluster in &mut clusters {
+                    cluster.runtime_key = Some(format!("{runtime_key_prefix}.{}", cluster.cluster).into());
+                }
+            }
             let mut sum = 0u32;
             for cluster in &clusters {
                 sum = sum.checked_add(cluster.weight.into()).ok_or(
@@ -134,7 +142,7 @@ mod envoy_conversions {
                     .try_into()
                     .map_err(|_| GenericError::from_msg("clusterweight has to be > 0"))
                     .with_node("weight")?;
-                Ok(Self { cluster: cluster.clone(), weight })
+                Ok(Self { cluster: cluster.clone(), weight, runtime_key: None })
             })()
             .with_name(cluster)
         }
@@ -176,7 +184,7 @@ mod envoy_conversions {
                 .try_into()
                 .map_err(|_| GenericError::from_msg("clusterweight has to be > 0"))
                 .with_node("weight")?;
-            Ok(Self { cluster, weight })
+            Ok(Self { cluster, weight, runtime_key: None })
         }
     }
 }
@@ -0,0 +1,186 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroU32, time::Duration};
+
+/// Takes the endpoints answering with consecutive server errors out of load balancing for a while.
+///
+/// Only the consecutive 5xx detection is supported: a failed request counts as a 5xx, as it does in Envoy when
+/// local origin errors are not split from the external ones.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct OutlierDetection {
+    /// The number of consecutive server errors after which an endpoint is ejected.
+    #[serde(default = "default_consecutive_5xx")]
+    pub consecutive_5xx: NonZeroU32,
+    /// An ejected endpoint stays out for this long times the number of times it was ejected.
+    #[serde(with = "humantime_serde", default = "default_base_ejection_time")]
+    pub base_ejection_time: Duration,
+    /// The longest an endpoint stays ejected.
+    #[serde(with = "humantime_serde", default = "default_max_ejection_time")]
+    pub max_ejection_time: Duration,
+    /// The most endpoints of the cluster ejected at once, in percent. One endpoint can always be ejected.
+    #[serde(default = "default_max_ejection_percent")]
+    pub max_ejection_percent: u8,
+}
+
+impl Default for OutlierDetection {
+    fn default() -> Self {
+        Self {
+            consecutive_5xx: default_consecutive_5xx(),
+            base_ejection_time: default_base_ejection_time(),
+            max_ejection_time: default_max_ejection_time(),
+            max_ejection_percent: default_max_ejection_percent(),
+        }
+    }
+}
+
+impl OutlierDetection {
+    /// How long an endpoint ejected for the `times`th time stays out.
+    pub fn ejection_time(&self, times: u32) -> Duration {
+        self.base_ejection_time.saturating_mul(times).min(self.max_ejection_time.max(self.base_ejection_time))
+    }
+
+    /// The most endpoints out of `total` that can be ejected at once.
+    pub fn max_ejected(&self, total: usize) -> usize {
+        (total * usize::from(self.max_ejection_percent) / 100).max(1)
+    }
+}
+
+const fn default_consecutive_5xx() -> NonZeroU32 {
+    NonZeroU32::MIN.saturating_add(4)
+}
+
+const fn default_base_ejection_time() -> Duration {
+    Duration::from_secs(30)
+}
+
+const fn default_max_ejection_time() -> Duration {
+    Duration::from_secs(300)
+}
+
+const fn default_max_ejection_percent() -> u8 {
+    10
+}
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    use super::OutlierDetection;
+    use crate::config::{common::*, util::duration_from_envoy};
+    use orion_data_plane_api::envoy_data_plane_api::envoy::config::cluster::v3::OutlierDetection as EnvoyOutlierDetection;
+    use std::num::NonZeroU32;
+
+    impl TryFrom<EnvoyOutlierDetection> for OutlierDetection {
+        type Error = GenericError;
+        fn try_from(value: EnvoyOutlierDetection) -> Result<Self, Self::Error> {
+            let EnvoyOutlierDetection {
+                consecutive_5xx,
+                interval: _,
+                base_ejection_time,
+                max_ejection_percent,
+                enforcing_consecutive_5xx,
+                enforcing_success_rate,
+                success_rate_minimum_hosts,
+                success_rate_request_volume,
+                success_rate_stdev_factor,
+                consecutive_gateway_failure,
+                enforcing_consecutive_gateway_failure,
+                split_external_local_origin_errors,
+                consecutive_local_origin_failure,
+                enforcing_consecutive_local_origin_failure,
+                enforcing_local_origin_success_rate,
+                failure_percentage_threshold,
+                enforcing_failure_percentage,
+                enforcing_failure_percentage_local_origin,
+                failure_percentage_minimum_hosts,
+                failure_percentage_request_volume,
+                max_ejection_time,
+                max_ejection_time_jitter,
+                successful_active_health_check_uneject_host,
+                monitors,
+                always_eject_one_host,
+            } = value;
+            // `interval` only paces the unejections in Envoy, here an endpoint is back as soon as its time is up
+            unsupported_field!(
+                // consecutive_5xx,
+                // interval,
+                // base_ejection_time,
+                // max_ejection_percent,
+                // enforcing_consecutive_5xx,
+                enforcing_success_rate,
+                success_rate_minimum_hosts,
+                success_rate_request_volume,
+                success_rate_stdev_factor,
+                consecutive_gateway_failure,
+                enforcing_consecutive_gateway_failure,
+                split_external_local_origin_errors,
+                consecutive_local_origin_failure,
+                enforcing_consecutive_local_origin_failure,
+                enforcing_local_origin_success_rate,
+                failure_percentage_threshold,
+                enforcing_failure_percentage,
+                enforcing_failure_percentage_local_origin,
+                failure_percentage_minimum_hosts,
+                failure_percentage_request_volume,
+                // max_ejection_time,
+                max_ejection_time_jitter,
+                successful_active_health_check_uneject_host,
+                monitors,
+                always_eject_one_host
+            )?;
+            if enforcing_consecutive_5xx.is_some_and(|percent| percent.value != 100) {
+                return Err(GenericError::from_msg("only ejecting every time is supported"))
+                    .with_node("enforcing_consecutive_5xx");
+            }
+            let defaults = OutlierDetection::default();
+            let consecutive_5xx = match consecutive_5xx {
+                None => defaults.consecutive_5xx,
+                Some(value) => NonZeroU32::new(value.value)
+                    .ok_or(GenericError::from_msg("must be greater than 0"))
+                    .with_node("consecutive_5xx")?,
+            };
+            let base_ejection_time = base_ejection_time
+                .map(duration_from_envoy)
+                .transpose()
+                .map_err(|e| {
+                    GenericError::from_msg_with_cause(
+                        "failed to convert {base_ejection_time} to std::time::Duration",
+                        e,
+                    )
+                    .with_node("base_ejection_time")
+                })?
+                .unwrap_or(defaults.base_ejection_time);
+            let max_ejection_time = max_ejection_time
+                .map(duration_from_envoy)
+                .transpose()
+                .map_err(|e| {
+                    GenericError::from_msg_with_cause("failed to convert {max_ejection_time} to std::time::Duration", e)
+                        .with_node("max_ejection_time")
+                })?
+                .unwrap_or(defaults.max_ejection_time);
+            let max_ejection_percent = match max_ejection_percent {
+                None => defaults.max_ejection_percent,
+                Some(percent) => u8::try_from(percent.value)
+                    .ok()
+                    .filter(|percent| *percent <= 100)
+                    .ok_or(GenericError::from_msg(format!("invalid percentage {}", percent.value)))
+                    .with_node("max_ejection_percent")?,
+            };
+            Ok(OutlierDetection { consecutive_5xx, base_ejection_time, max_ejection_time, max_ejection_percent })
+        }
+    }
+}
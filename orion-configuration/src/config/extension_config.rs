@@ -0,0 +1,215 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Filter configurations discovered over ECDS, independently of the listeners the filters are part of.
+
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    listener_filters::ListenerFilterConfig, network_filters::http_connection_manager::http_filters::HttpFilterType,
+    ConfigSource,
+};
+
+/// Where the configuration of a filter is discovered from, under the name of the filter.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ExtensionConfigSource<T> {
+    pub config_source: ConfigSource,
+    /// Used until a configuration is discovered, and if the one discovered is removed.
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub default_config: Option<T>,
+    /// Whether the listener starts with the default configuration, rather than waiting for one to be discovered.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub apply_default_config_without_warming: bool,
+    /// The types of the configurations accepted for the filter, any if empty.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub type_urls: Vec<CompactString>,
+}
+
+impl<T> ExtensionConfigSource<T> {
+    /// Whether a discovered configuration of this type can be used for the filter.
+    pub fn accepts(&self, type_url: &str) -> bool {
+        self.type_urls.is_empty() || self.type_urls.iter().any(|accepted| accepted == type_url)
+    }
+
+    /// Whether the listener of the filter has to wait for its configuration to be discovered before being used.
+    pub fn needs_warming(&self) -> bool {
+        !(self.apply_default_config_without_warming && self.default_config.is_some())
+    }
+}
+
+/// A filter configuration discovered over ECDS.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ExtensionConfig {
+    pub name: CompactString,
+    /// The type of the configuration as it was received.
+    pub type_url: CompactString,
+    pub filter: ExtensionFilter,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionFilter {
+    Http(HttpFilterType),
+    Listener(ListenerFilterConfig),
+}
+
+#[cfg(feature = "envoy-conversions")]
+pub(crate) use envoy_conversions::*;
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    use super::{ExtensionConfig, ExtensionConfigSource, ExtensionFilter};
+    use crate::config::{
+        common::*,
+        listener_filters::ListenerFilterConfig,
+        network_filters::http_connection_manager::http_filters::{HttpFilterType, SupportedEnvoyFilter},
+        ConfigSource,
+    };
+    use compact_str::CompactString;
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::config::core::v3::{
+            ExtensionConfigSource as EnvoyExtensionConfigSource, TypedExtensionConfig as EnvoyTypedExtensionConfig,
+        },
+        google::protobuf::Any,
+    };
+
+    /// Converts the config source of a filter, its default configuration being converted with `convert_default`.
+    pub(crate) fn extension_config_source<T>(
+        value: EnvoyExtensionConfigSource,
+        convert_default: impl FnOnce(Any) -> Result<T, GenericError>,
+    ) -> Result<ExtensionConfigSource<T>, GenericError> {
+        let EnvoyExtensionConfigSource {
+            config_source,
+            default_config,
+            apply_default_config_without_warming,
+            type_urls,
+        } = value;
+        let config_source: ConfigSource = convert_opt!(config_source)?;
+        let default_config = default_config.map(convert_default).transpose().with_node("default_config")?;
+        if apply_default_config_without_warming && default_config.is_none() {
+            return Err(GenericError::from_msg("apply_default_config_without_warming requires a default_config"));
+        }
+        let type_urls = type_urls.into_iter().map(CompactString::from).collect();
+        Ok(ExtensionConfigSource { config_source, default_config, apply_default_config_without_warming, type_urls })
+    }
+
+    impl TryFrom<EnvoyTypedExtensionConfig> for ExtensionConfig {
+        type Error = GenericError;
+        fn try_from(value: EnvoyTypedExtensionConfig) -> Result<Self, Self::Error> {
+            let EnvoyTypedExtensionConfig { name, typed_config } = value;
+            let name: CompactString = required!(name)?.into();
+            (|| -> Result<_, GenericError> {
+                let typed_config = required!(typed_config)?;
+                let type_url = CompactString::from(typed_config.type_url.as_str());
+                // the type of a filter tells HTTP filters from listener ones
+                let filter =
+                    match SupportedEnvoyFilter::try_from(typed_config.clone()).and_then(HttpFilterType::try_from) {
+                        Ok(filter) => ExtensionFilter::Http(filter),
+                        Err(http_err) => ListenerFilterConfig::try_from(typed_config)
+                            .map(ExtensionFilter::Listener)
+                            .map_err(|_| http_err)?,
+                    };
+                Ok(Self { name: name.clone(), type_url, filter })
+            })()
+            .with_name(name)
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "envoy-conversions")]
+mod tests {
+    use super::{extension_config_source, ExtensionConfig, ExtensionFilter};
+    use crate::config::{
+        listener_filters::ListenerFilterConfig, network_filters::http_connection_manager::http_filters::HttpFilterType,
+    };
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::{
+            config::core::v3::{
+                config_source::ConfigSourceSpecifier as EnvoyConfigSourceSpecifier, AggregatedConfigSource,
+                ConfigSource as EnvoyConfigSource, ExtensionConfigSource as EnvoyExtensionConfigSource,
+                TypedExtensionConfig as EnvoyTypedExtensionConfig,
+            },
+            extensions::filters::{http::cors::v3::Cors, listener::tls_inspector::v3::TlsInspector},
+        },
+        google::protobuf::Any,
+        prost::Message,
+    };
+
+    #[test]
+    fn http_and_listener_filter_configs() {
+        let cors = Any {
+            type_url: "type.googleapis.com/envoy.extensions.filters.http.cors.v3.Cors".to_owned(),
+            value: Cors::default().encode_to_vec(),
+        };
+        let config =
+            ExtensionConfig::try_from(EnvoyTypedExtensionConfig { name: "cors".to_owned(), typed_config: Some(cors) })
+                .unwrap();
+        assert_eq!(config.type_url, "type.googleapis.com/envoy.extensions.filters.http.cors.v3.Cors");
+        assert_eq!(config.filter, ExtensionFilter::Http(HttpFilterType::Ingored));
+
+        let tls_inspector = Any {
+            type_url: "type.googleapis.com/envoy.extensions.filters.listener.tls_inspector.v3.TlsInspector".to_owned(),
+            value: TlsInspector::default().encode_to_vec(),
+        };
+        let config = ExtensionConfig::try_from(EnvoyTypedExtensionConfig {
+            name: "tls_inspector".to_owned(),
+            typed_config: Some(tls_inspector),
+        })
+        .unwrap();
+        assert_eq!(config.filter, ExtensionFilter::Listener(ListenerFilterConfig::TlsInspector));
+
+        let unknown = Any { type_url: "type.googleapis.com/unknown.Filter".to_owned(), value: vec![] };
+        let err = ExtensionConfig::try_from(EnvoyTypedExtensionConfig {
+            name: "unknown".to_owned(),
+            typed_config: Some(unknown),
+        })
+        .unwrap_err();
+        assert!(format!("{err:?}").contains("unknown.Filter"), "{err:?}");
+    }
+
+    #[test]
+    fn default_config_applied_without_warming() {
+        let envoy_source = |default_config: Option<Any>| EnvoyExtensionConfigSource {
+            config_source: Some(EnvoyConfigSource {
+                config_source_specifier: Some(EnvoyConfigSourceSpecifier::Ads(AggregatedConfigSource {})),
+                ..Default::default()
+            }),
+            default_config,
+            apply_default_config_without_warming: true,
+            type_urls: vec![
+                "type.googleapis.com/envoy.extensions.filters.listener.tls_inspector.v3.TlsInspector".to_owned()
+            ],
+        };
+        let tls_inspector = Any {
+            type_url: "type.googleapis.com/envoy.extensions.filters.listener.tls_inspector.v3.TlsInspector".to_owned(),
+            value: TlsInspector::default().encode_to_vec(),
+        };
+        let source =
+            extension_config_source(envoy_source(Some(tls_inspector)), ListenerFilterConfig::try_from).unwrap();
+        assert_eq!(source.default_config, Some(ListenerFilterConfig::TlsInspector));
+        assert!(!source.needs_warming());
+        assert!(source.accepts("type.googleapis.com/envoy.extensions.filters.listener.tls_inspector.v3.TlsInspector"));
+        assert!(
+            !source.accepts("type.googleapis.com/envoy.extensions.filters.listener.proxy_protocol.v3.ProxyProtocol")
+        );
+
+        let err = extension_config_source(envoy_source(None), ListenerFilterConfig::try_from).unwrap_err();
+        assert!(format!("{err:?}").contains("requires a default_config"), "{err:?}");
+    }
+}
@@ -0,0 +1,375 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use crate::config::is_default;
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+/// Runtime values, looked up by key while the proxy runs. A value of a layer overrides the values of the layers
+/// before it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LayeredRuntime {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub layers: Vec<RuntimeLayer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuntimeLayer {
+    pub name: CompactString,
+    pub layer: RuntimeLayerSpecifier,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimeLayerSpecifier {
+    /// Values given in the configuration, by dotted key.
+    StaticLayer(BTreeMap<CompactString, RuntimeValue>),
+    /// Values read from a directory tree, one file per value.
+    DiskLayer(DiskLayer),
+    /// Values set through the admin interface.
+    AdminLayer,
+}
+
+/// Every file below the root is a value, whose key is the path of the file relative to the root with `/` replaced by
+/// `.`. The root is meant to be a symlink, swapped to update all the values at once: the layer is reloaded whenever
+/// the symlink changes target.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiskLayer {
+    pub symlink_root: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub subdirectory: Option<PathBuf>,
+    /// Reads the values from a further subdirectory named after the service cluster of the node.
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub append_service_cluster: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum RuntimeValue {
+    Bool(bool),
+    Number(f64),
+    FractionalPercent(FractionalPercent),
+    String(CompactString),
+}
+
+impl RuntimeValue {
+    /// Parses a value read from a file or set through the admin interface.
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        match value {
+            "true" => Self::Bool(true),
+            "false" => Self::Bool(false),
+            _ => value.parse::<f64>().map_or_else(|_| Self::String(value.into()), Self::Number),
+        }
+    }
+
+    /// The value as an integer, for integral numbers that are not negative.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+    pub fn as_integer(&self) -> Option<u64> {
+        match *self {
+            Self::Number(number) if number >= 0.0 && number.fract() == 0.0 && number < u64::MAX as f64 => {
+                Some(number as u64)
+            },
+            _ => None,
+        }
+    }
+
+    /// The value as a fraction of requests. A plain number is the numerator, over the denominator of the default.
+    pub fn as_fractional_percent(&self, default: FractionalPercent) -> Option<FractionalPercent> {
+        match self {
+            Self::FractionalPercent(percent) => Some(*percent),
+            number => number
+                .as_integer()
+                .and_then(|numerator| u32::try_from(numerator).ok())
+                .map(|numerator| FractionalPercent { numerator, denominator: default.denominator }),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::FractionalPercent(FractionalPercent { numerator, denominator }) => {
+                write!(f, "{numerator}/{}", denominator.value())
+            },
+            Self::String(value) => f.write_str(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct FractionalPercent {
+    pub numerator: u32,
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub denominator: Denominator,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Denominator {
+    #[default]
+    Hundred,
+    TenThousand,
+    Million,
+}
+
+impl Denominator {
+    pub fn value(self) -> u32 {
+        match self {
+            Self::Hundred => 100,
+            Self::TenThousand => 10_000,
+            Self::Million => 1_000_000,
+        }
+    }
+}
+
+/// A fraction of requests, which can be overridden by the runtime value of `runtime_key`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct RuntimeFractionalPercent {
+    pub default_value: FractionalPercent,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub runtime_key: Option<CompactString>,
+}
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    #![allow(deprecated)]
+    use super::{
+        Denominator, DiskLayer, FractionalPercent, LayeredRuntime, RuntimeFractionalPercent, RuntimeLayer,
+        RuntimeLayerSpecifier, RuntimeValue,
+    };
+    use crate::config::common::*;
+    use compact_str::CompactString;
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::{
+            config::{
+                bootstrap::v3::{
+                    runtime_layer::{DiskLayer as EnvoyDiskLayer, LayerSpecifier as EnvoyLayerSpecifier},
+                    LayeredRuntime as EnvoyLayeredRuntime, RuntimeLayer as EnvoyRuntimeLayer,
+                },
+                core::v3::RuntimeFractionalPercent as EnvoyRuntimeFractionalPercent,
+            },
+            r#type::v3::{
+                fractional_percent::DenominatorType as EnvoyDenominator, FractionalPercent as EnvoyFractionalPercent,
+            },
+        },
+        google::protobuf::{value::Kind, Struct},
+    };
+    use std::collections::BTreeMap;
+
+    impl TryFrom<EnvoyLayeredRuntime> for LayeredRuntime {
+        type Error = GenericError;
+        fn try_from(value: EnvoyLayeredRuntime) -> Result<Self, Self::Error> {
+            let EnvoyLayeredRuntime { layers } = value;
+            let layers = convert_vec!(layers)?;
+            Ok(Self { layers })
+        }
+    }
+
+    impl TryFrom<EnvoyRuntimeLayer> for RuntimeLayer {
+        type Error = GenericError;
+        fn try_from(value: EnvoyRuntimeLayer) -> Result<Self, Self::Error> {
+            let EnvoyRuntimeLayer { name, layer_specifier } = value;
+            let name: CompactString = required!(name)?.into();
+            (|| -> Result<_, GenericError> {
+                let layer = match required!(layer_specifier)? {
+                    EnvoyLayerSpecifier::StaticLayer(values) => {
+                        let mut layer = BTreeMap::new();
+                        flatten_static_layer(values, "", &mut layer)?;
+                        RuntimeLayerSpecifier::StaticLayer(layer)
+                    },
+                    EnvoyLayerSpecifier::DiskLayer(disk_layer) => {
+                        RuntimeLayerSpecifier::DiskLayer(disk_layer.try_into().with_node("disk_layer")?)
+                    },
+                    EnvoyLayerSpecifier::AdminLayer(_) => RuntimeLayerSpecifier::AdminLayer,
+                    EnvoyLayerSpecifier::RtdsLayer(_) => return Err(GenericError::unsupported_variant("RtdsLayer")),
+                };
+                Ok(Self { name: name.clone(), layer })
+            })()
+            .with_name(name)
+        }
+    }
+
+    /// Nested structs are flattened into dotted keys, except for the ones holding a fractional percent.
+    fn flatten_static_layer(
+        values: Struct,
+        prefix: &str,
+        layer: &mut BTreeMap<CompactString, RuntimeValue>,
+    ) -> Result<(), GenericError> {
+        for (name, value) in values.fields {
+            let key = if prefix.is_empty() { name } else { format!("{prefix}.{name}") };
+            let value = match value.kind {
+                Some(Kind::BoolValue(value)) => RuntimeValue::Bool(value),
+                Some(Kind::NumberValue(value)) => RuntimeValue::Number(value),
+                Some(Kind::StringValue(value)) => RuntimeValue::String(value.into()),
+                Some(Kind::StructValue(nested)) => {
+                    if let Some(percent) = fractional_percent_from_struct(&nested) {
+                        RuntimeValue::FractionalPercent(percent)
+                    } else {
+                        flatten_static_layer(nested, &key, layer)?;
+                        continue;
+                    }
+                },
+                Some(Kind::NullValue(_)) | None => continue,
+                Some(Kind::ListValue(_)) => {
+                    return Err(GenericError::from_msg("runtime values can't be lists").with_node(key));
+                },
+            };
+            layer.insert(key.into(), value);
+        }
+        Ok(())
+    }
+
+    fn fractional_percent_from_struct(values: &Struct) -> Option<FractionalPercent> {
+        if !values.fields.keys().all(|field| field == "numerator" || field == "denominator") {
+            return None;
+        }
+        let numerator = match values.fields.get("numerator")?.kind {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Some(Kind::NumberValue(numerator)) if numerator >= 0.0 && numerator <= f64::from(u32::MAX) => {
+                numerator as u32
+            },
+            _ => return None,
+        };
+        let denominator = match values.fields.get("denominator").and_then(|value| value.kind.as_ref()) {
+            None => Denominator::Hundred,
+            Some(Kind::StringValue(denominator)) => match denominator.as_str() {
+                "HUNDRED" => Denominator::Hundred,
+                "TEN_THOUSAND" => Denominator::TenThousand,
+                "MILLION" => Denominator::Million,
+                _ => return None,
+            },
+            Some(_) => return None,
+        };
+        Some(FractionalPercent { numerator, denominator })
+    }
+
+    impl TryFrom<EnvoyDiskLayer> for DiskLayer {
+        type Error = GenericError;
+        fn try_from(value: EnvoyDiskLayer) -> Result<Self, Self::Error> {
+            let EnvoyDiskLayer { symlink_root, subdirectory, append_service_cluster } = value;
+            let symlink_root = required!(symlink_root)?.into();
+            let subdirectory = subdirectory.is_used().then(|| subdirectory.into());
+            Ok(Self { symlink_root, subdirectory, append_service_cluster })
+        }
+    }
+
+    impl From<EnvoyDenominator> for Denominator {
+        fn from(value: EnvoyDenominator) -> Self {
+            match value {
+                EnvoyDenominator::Hundred => Self::Hundred,
+                EnvoyDenominator::TenThousand => Self::TenThousand,
+                EnvoyDenominator::Million => Self::Million,
+            }
+        }
+    }
+
+    impl TryFrom<i32> for Denominator {
+        type Error = GenericError;
+        fn try_from(value: i32) -> Result<Self, Self::Error> {
+            EnvoyDenominator::from_i32(value)
+                .ok_or_else(|| GenericError::from_msg(format!("[unknown DenominatorType {value}]")))
+                .map(Self::from)
+        }
+    }
+
+    impl TryFrom<EnvoyFractionalPercent> for FractionalPercent {
+        type Error = GenericError;
+        fn try_from(value: EnvoyFractionalPercent) -> Result<Self, Self::Error> {
+            let EnvoyFractionalPercent { numerator, denominator } = value;
+            let denominator = Denominator::try_from(denominator).with_node("denominator")?;
+            Ok(Self { numerator: numerator.min(denominator.value()), denominator })
+        }
+    }
+
+    impl TryFrom<EnvoyRuntimeFractionalPercent> for RuntimeFractionalPercent {
+        type Error = GenericError;
+        fn try_from(value: EnvoyRuntimeFractionalPercent) -> Result<Self, Self::Error> {
+            let EnvoyRuntimeFractionalPercent { default_value, runtime_key } = value;
+            let default_value = convert_opt!(default_value)?;
+            let runtime_key = runtime_key.is_used().then(|| runtime_key.into());
+            Ok(Self { default_value, runtime_key })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::config::bootstrap::v3::{runtime_layer::LayerSpecifier, RuntimeLayer as EnvoyRuntimeLayer},
+        google::protobuf::{value::Kind, Struct, Value},
+    };
+
+    fn value(kind: Kind) -> Value {
+        Value { kind: Some(kind) }
+    }
+
+    fn structure(fields: Vec<(&str, Value)>) -> Struct {
+        Struct { fields: fields.into_iter().map(|(name, value)| (name.to_owned(), value)).collect() }
+    }
+
+    #[test]
+    fn static_layer_from_envoy() {
+        let percent = structure(vec![
+            ("numerator", value(Kind::NumberValue(5.0))),
+            ("denominator", value(Kind::StringValue("TEN_THOUSAND".to_owned()))),
+        ]);
+        let nested = structure(vec![
+            ("enabled", value(Kind::BoolValue(true))),
+            ("weight", value(Kind::NumberValue(20.0))),
+            ("fraction", value(Kind::StructValue(percent))),
+        ]);
+        let envoy = EnvoyRuntimeLayer {
+            name: "static".to_owned(),
+            layer_specifier: Some(LayerSpecifier::StaticLayer(structure(vec![
+                ("feature", value(Kind::StructValue(nested))),
+                ("name", value(Kind::StringValue("orion".to_owned()))),
+            ]))),
+        };
+        let layer = RuntimeLayer::try_from(envoy).unwrap();
+        let RuntimeLayerSpecifier::StaticLayer(values) = layer.layer else {
+            unreachable!("expected a static layer");
+        };
+        assert_eq!(values.get("feature.enabled"), Some(&RuntimeValue::Bool(true)));
+        assert_eq!(values.get("feature.weight").and_then(RuntimeValue::as_integer), Some(20));
+        assert_eq!(
+            values.get("feature.fraction"),
+            Some(&RuntimeValue::FractionalPercent(FractionalPercent {
+                numerator: 5,
+                denominator: Denominator::TenThousand
+            }))
+        );
+        assert_eq!(values.get("name"), Some(&RuntimeValue::String("orion".into())));
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(RuntimeValue::parse("true\n"), RuntimeValue::Bool(true));
+        assert_eq!(RuntimeValue::parse("42").as_integer(), Some(42));
+        assert_eq!(RuntimeValue::parse("-1").as_integer(), None);
+        assert_eq!(RuntimeValue::parse("text"), RuntimeValue::String("text".into()));
+        let default = FractionalPercent { numerator: 1, denominator: Denominator::Million };
+        assert_eq!(
+            RuntimeValue::parse("30").as_fractional_percent(default),
+            Some(FractionalPercent { numerator: 30, denominator: Denominator::Million })
+        );
+    }
+}
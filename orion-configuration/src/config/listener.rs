@@ -18,13 +18,13 @@
 use super::{
     network_filters::{
         access_log::{AccessLog, AccessLogConf},
-        HttpConnectionManager, NetworkRbac, TcpProxy,
+        ConnectionLimit, HttpConnectionManager, NetworkRbac, TcpProxy,
     },
     transport::CommonTlsContext,
     GenericError,
 };
 use crate::config::network_filters::tracing::{TracingConfig, TracingKey};
-use crate::config::{listener, transport::BindDeviceOptions};
+use crate::config::{is_default, listener, transport::BindDeviceOptions};
 use compact_str::CompactString;
 use ipnet::IpNet;
 use orion_interner::StringInterner;
@@ -54,6 +54,27 @@ pub struct Listener {
     pub with_tlv_listener_filter: bool,
     #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
     pub tlv_listener_filter_config: Option<super::listener_filters::TlvListenerFilterConfig>,
+    /// The listener filters configured over ECDS, run after the ones above.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub discovered_listener_filters: Vec<super::listener_filters::DiscoveredListenerFilter>,
+    /// Limit of the active connections on this listener, across all worker threads. The connections over it are
+    /// closed, after the delay if there is one. Envoy limits the connections of a listener with the
+    /// `envoy.resource_limits.listener.<name>.connection_limit` runtime key instead, which is honored as well and
+    /// overrides the maximum configured here.
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub connection_limit: Option<ConnectionLimit>,
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub traffic_direction: TrafficDirection,
+}
+
+/// Whether a listener handles traffic coming into the node (inbound) or leaving it (outbound).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficDirection {
+    #[default]
+    Unspecified,
+    Inbound,
+    Outbound,
 }
 
 impl Listener {
@@ -155,6 +176,8 @@ pub struct FilterChain {
     pub tls_config: Option<listener::TlsConfig>,
     #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
     pub rbac: Vec<NetworkRbac>,
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub connection_limit: Option<ConnectionLimit>,
     pub terminal_filter: MainFilter,
 }
 
@@ -371,12 +394,15 @@ mod envoy_conversions {
     use std::hash::{DefaultHasher, Hash, Hasher};
     use std::str::FromStr;
 
-    use super::{FilterChain, FilterChainMatch, Listener, MainFilter, ServerNameMatch, TlsConfig};
+    use super::{
+        ConnectionLimit, FilterChain, FilterChainMatch, Listener, MainFilter, ServerNameMatch, TlsConfig,
+        TrafficDirection,
+    };
     use crate::config::transport::BindDeviceOptions;
     use crate::config::{
         common::*,
         core::{Address, CidrRange},
-        listener_filters::{ListenerFilter, ListenerFilterConfig},
+        listener_filters::{DiscoveredListenerFilter, ListenerFilter, ListenerFilterConfig},
         transport::SupportedEnvoyTransportSocket,
         util::{envoy_u32_to_u16, u32_to_u16},
     };
@@ -384,7 +410,7 @@ mod envoy_conversions {
     use orion_data_plane_api::envoy_data_plane_api::{
         envoy::{
             config::{
-                core::v3::TransportSocket as EnvoyTransportSocket,
+                core::v3::{TrafficDirection as EnvoyTrafficDirection, TransportSocket as EnvoyTransportSocket},
                 listener::v3::{
                     filter::ConfigType as EnvoyConfigType, Filter as EnvoyFilter, FilterChain as EnvoyFilterChain,
                     FilterChainMatch as EnvoyFilterChainMatch, Listener as EnvoyListener,
@@ -392,6 +418,7 @@ mod envoy_conversions {
             },
             extensions::{
                 filters::network::{
+                    connection_limit::v3::ConnectionLimit as EnvoyConnectionLimit,
                     http_connection_manager::v3::HttpConnectionManager as EnvoyHttpConnectionManager,
                     rbac::v3::Rbac as EnvoyNetworkRbac, tcp_proxy::v3::TcpProxy as EnvoyTcpProxy,
                 },
@@ -403,6 +430,25 @@ mod envoy_conversions {
     };
     use tracing::warn;
 
+    impl From<EnvoyTrafficDirection> for TrafficDirection {
+        fn from(value: EnvoyTrafficDirection) -> Self {
+            match value {
+                EnvoyTrafficDirection::Unspecified => Self::Unspecified,
+                EnvoyTrafficDirection::Inbound => Self::Inbound,
+                EnvoyTrafficDirection::Outbound => Self::Outbound,
+            }
+        }
+    }
+
+    impl TryFrom<i32> for TrafficDirection {
+        type Error = GenericError;
+        fn try_from(value: i32) -> Result<Self, Self::Error> {
+            EnvoyTrafficDirection::from_i32(value)
+                .ok_or_else(|| GenericError::from_msg(format!("[unknown TrafficDirection {value}]")))
+                .map(Self::from)
+        }
+    }
+
     impl TryFrom<EnvoyListener> for Listener {
         type Error = GenericError;
         fn try_from(envoy: EnvoyListener) -> Result<Self, Self::Error> {
@@ -426,7 +472,7 @@ mod envoy_conversions {
                 freebind,
                 socket_options,
                 tcp_fast_open_queue_length,
-                traffic_direction: _,
+                traffic_direction,
                 udp_listener_config,
                 api_listener,
                 connection_balance_config,
@@ -511,9 +557,10 @@ mod envoy_conversions {
                 let mut proxy_protocol_config = None;
                 let mut with_tlv_listener_filter = false;
                 let mut tlv_listener_filter_config = None;
+                let mut discovered_listener_filters = Vec::new();
 
-                for filter in listener_filters {
-                    match filter.config {
+                for ListenerFilter { name, config } in listener_filters {
+                    match config {
                         ListenerFilterConfig::TlsInspector => {
                             if with_tls_inspector {
                                 return Err(GenericError::from_msg("duplicate TLS inspector listener filter"))
@@ -539,6 +586,10 @@ mod envoy_conversions {
                             with_tlv_listener_filter = true;
                             tlv_listener_filter_config = Some(config);
                         },
+
+                        ListenerFilterConfig::ConfigDiscovery(source) => {
+                            discovered_listener_filters.push(DiscoveredListenerFilter { name, source: *source });
+                        },
                     }
                 }
                 let bind_device = convert_vec!(socket_options)?;
@@ -562,6 +613,10 @@ mod envoy_conversions {
                     proxy_protocol_config,
                     with_tlv_listener_filter,
                     tlv_listener_filter_config,
+                    discovered_listener_filters,
+                    // Envoy limits the connections of a listener with the runtime, read as they are accepted
+                    connection_limit: None,
+                    traffic_direction: traffic_direction.try_into().with_node("traffic_direction")?,
                 })
             }())
             .with_name(name)
@@ -601,6 +656,7 @@ mod envoy_conversions {
                     .unwrap_or_default();
                 let filters = required!(filters)?;
                 let mut rbac = Vec::new();
+                let mut connection_limit = None;
                 let mut main_filter = None;
                 for (idx, filter) in filters.into_iter().enumerate() {
                     let filter_name = filter.name.clone().is_used().then_some(filter.name.clone());
@@ -622,6 +678,20 @@ mod envoy_conversions {
                                 }
                             },
 
+                            SupportedEnvoyFilter::ConnectionLimit(limit) => {
+                                if main_filter.is_some() {
+                                    Err(GenericError::from_msg(
+                                        "connection limit filter found after a http connection manager or tcp proxy in the same filterchain",
+                                    ))
+                                } else if connection_limit.is_some() {
+                                    Err(GenericError::from_msg("multiple connection limit filters defined in filterchain"))
+                                } else {
+                                    ConnectionLimit::try_from(limit).map(|limit| {
+                                        connection_limit = Some(limit);
+                                    })
+                                }
+                            },
+
                             SupportedEnvoyFilter::HttpConnectionManager(http) => {
                                 if main_filter.is_some() {
                                     Err(GenericError::from_msg(
@@ -675,7 +745,14 @@ mod envoy_conversions {
                 filter_chain_match.hash(&mut s);
                 Ok(FilterChainWrapper((
                     filter_chain_match,
-                    FilterChain { filter_chain_match_hash: s.finish(), name, rbac, terminal_filter, tls_config },
+                    FilterChain {
+                        filter_chain_match_hash: s.finish(),
+                        name,
+                        rbac,
+                        connection_limit,
+                        terminal_filter,
+                        tls_config,
+                    },
                 )))
             }())
             .with_name(name)
@@ -779,6 +856,7 @@ mod envoy_conversions {
     enum SupportedEnvoyFilter {
         HttpConnectionManager(EnvoyHttpConnectionManager),
         NetworkRbac(EnvoyNetworkRbac),
+        ConnectionLimit(EnvoyConnectionLimit),
         TcpProxy(EnvoyTcpProxy),
         Ignored,
     }
@@ -793,6 +871,9 @@ mod envoy_conversions {
             "type.googleapis.com/envoy.extensions.filters.network.rbac.v3.RBAC" => {
                 EnvoyNetworkRbac::decode(typed_config.value.as_slice()).map(Self::NetworkRbac)
             },
+            "type.googleapis.com/envoy.extensions.filters.network.connection_limit.v3.ConnectionLimit" => {
+                EnvoyConnectionLimit::decode(typed_config.value.as_slice()).map(Self::ConnectionLimit)
+            },
             "type.googleapis.com/envoy.extensions.filters.network.tcp_proxy.v3.TcpProxy" => {
                 EnvoyTcpProxy::decode(typed_config.value.as_slice()).map(Self::TcpProxy)
             },
@@ -15,7 +15,9 @@
 //
 //
 
-use crate::config::{common::ProxyProtocolVersion, transport::ProxyProtocolPassThroughTlvs};
+use crate::config::{
+    common::ProxyProtocolVersion, extension_config::ExtensionConfigSource, transport::ProxyProtocolPassThroughTlvs,
+};
 use compact_str::CompactString;
 use serde::{Deserialize, Serialize};
 
@@ -24,11 +26,23 @@ pub struct ListenerFilter {
     pub config: ListenerFilterConfig,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum ListenerFilterConfig {
     TlsInspector,
     ProxyProtocol(DownstreamProxyProtocolConfig),
     Ignored,
     TlvListenerFilter(TlvListenerFilterConfig),
+    /// Discovered over ECDS under the name of the filter.
+    ConfigDiscovery(Box<ExtensionConfigSource<ListenerFilterConfig>>),
+}
+
+/// A listener filter whose configuration is discovered over ECDS, and looked up for every connection.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DiscoveredListenerFilter {
+    pub name: CompactString,
+    #[serde(flatten)]
+    pub source: ExtensionConfigSource<ListenerFilterConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Default)]
@@ -52,6 +66,7 @@ mod envoy_conversions {
     use super::{DownstreamProxyProtocolConfig, ListenerFilter, ListenerFilterConfig, TlvListenerFilterConfig};
     use crate::config::{
         common::{ProxyProtocolVersion, *},
+        extension_config::extension_config_source,
         transport::ProxyProtocolPassThroughTlvs,
     };
     use compact_str::CompactString;
@@ -150,8 +165,9 @@ mod envoy_conversions {
             let name: CompactString = required!(name)?.into();
             (|| -> Result<_, GenericError> {
                 let config = match required!(config_type) {
-                    Ok(EnvoyListenerFilterConfigType::ConfigDiscovery(_)) => {
-                        Err(GenericError::unsupported_variant("ConfigDiscovery"))
+                    Ok(EnvoyListenerFilterConfigType::ConfigDiscovery(config_discovery)) => {
+                        extension_config_source(config_discovery, ListenerFilterConfig::try_from)
+                            .map(|source| ListenerFilterConfig::ConfigDiscovery(Box::new(source)))
                     },
                     Ok(EnvoyListenerFilterConfigType::TypedConfig(typed_config)) => {
                         ListenerFilterConfig::try_from(typed_config)
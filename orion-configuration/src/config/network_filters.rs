@@ -20,6 +20,8 @@ pub use http_connection_manager::HttpConnectionManager;
 pub mod network_rbac;
 pub use network_rbac::NetworkRbac;
 pub mod access_log;
+pub mod connection_limit;
+pub use connection_limit::ConnectionLimit;
 pub mod tcp_proxy;
 pub mod tracing;
 pub use tcp_proxy::TcpProxy;
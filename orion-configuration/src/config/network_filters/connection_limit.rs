@@ -0,0 +1,58 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroU64, time::Duration};
+
+/// Limits the number of active connections handled by a filter chain.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ConnectionLimit {
+    pub max_connections: NonZeroU64,
+    /// How long to wait before closing a connection that exceeds the limit.
+    #[serde(with = "humantime_serde")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub delay: Option<Duration>,
+}
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    use super::ConnectionLimit;
+    use crate::config::{common::*, util::duration_from_envoy};
+    use orion_data_plane_api::envoy_data_plane_api::envoy::extensions::filters::network::connection_limit::v3::ConnectionLimit as EnvoyConnectionLimit;
+    use std::num::NonZeroU64;
+
+    impl TryFrom<EnvoyConnectionLimit> for ConnectionLimit {
+        type Error = GenericError;
+        fn try_from(value: EnvoyConnectionLimit) -> Result<Self, Self::Error> {
+            let EnvoyConnectionLimit { stat_prefix, max_connections, delay, runtime_enabled } = value;
+            unsupported_field!(
+                // stat_prefix,
+                // max_connections,
+                // delay,
+                runtime_enabled
+            )?;
+            if stat_prefix.is_used() {
+                tracing::warn!("unsupported field stat_prefix used in connection_limit. This field will be ignored.");
+            }
+            let max_connections = NonZeroU64::new(required!(max_connections)?.value)
+                .ok_or(GenericError::from_msg("max_connections must be greater than zero"))
+                .with_node("max_connections")?;
+            let delay = delay.map(duration_from_envoy).transpose().with_node("delay")?.filter(|d| !d.is_zero());
+            Ok(Self { max_connections, delay })
+        }
+    }
+}
@@ -18,7 +18,10 @@
 pub mod header_matcher;
 pub mod header_modifer;
 pub mod http_filters;
+pub mod internal_redirect;
 pub mod route;
+pub mod scoped_routes;
+pub mod uri_template;
 
 use compact_str::CompactString;
 use exponential_backoff::Backoff;
@@ -27,6 +30,7 @@ use header_modifer::{HeaderModifier, HeaderValueOption};
 use http::{HeaderName, HeaderValue, StatusCode};
 use http_filters::{FilterOverride, HttpFilter};
 use route::{Action, RouteMatch};
+use scoped_routes::ScopedRoutes;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, str::FromStr, time::Duration};
 
@@ -52,6 +56,9 @@ pub struct HttpConnectionManager {
     #[serde(with = "humantime_serde")]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub request_timeout: Option<Duration>,
+    #[serde(with = "humantime_serde")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub idle_timeout: Option<Duration>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub http_filters: Vec<HttpFilter>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
@@ -92,6 +99,7 @@ pub enum UpgradeType {
 #[serde(untagged)]
 pub enum RouteSpecifier {
     Rds(RdsSpecifier),
+    ScopedRoutes(ScopedRoutes),
     RouteConfig(RouteConfiguration),
 }
 
@@ -108,6 +116,9 @@ pub struct RouteConfiguration {
     #[serde(with = "http_serde_ext::header_name::vec")]
     pub request_headers_to_remove: Vec<HeaderName>,
     pub virtual_hosts: Vec<VirtualHost>,
+    /// Where more virtual hosts are discovered from, in addition to `virtual_hosts`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub vhds: Option<Vhds>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -386,6 +397,9 @@ pub struct Route {
     pub route_match: RouteMatch,
     #[serde(skip_serializing_if = "HashMap::is_empty", default = "Default::default")]
     pub typed_per_filter_config: std::collections::HashMap<CompactString, FilterOverride>,
+    /// how much of the request body is kept to be sent again on an internal redirect
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub per_request_buffer_limit_bytes: Option<u32>,
     #[serde(flatten)]
     pub action: Action,
 }
@@ -402,6 +416,12 @@ pub struct RdsSpecifier {
     pub config_source: ConfigSource,
 }
 
+/// Virtual hosts discovered incrementally, and asked for on demand when a request is for a host none of them match.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Vhds {
+    pub config_source: ConfigSource,
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -577,20 +597,24 @@ mod envoy_conversions {
             router::Router, FilterConfigOverride, FilterOverride, HttpFilter, HttpFilterType, SupportedEnvoyFilter,
             SupportedEnvoyHttpFilter,
         },
+        route::{Action, PathRewriteSpecifier, PathSpecifier, RouteAction, RouteMatch},
         CodecType, HttpConnectionManager, RdsSpecifier, RetryBackoff, RetryOn, RetryPolicy, Route, RouteConfiguration,
-        RouteSpecifier, UpgradeType, VirtualHost, XffSettings,
+        RouteSpecifier, UpgradeType, Vhds, VirtualHost, XffSettings,
     };
     use crate::config::{
+        bootstrap::ApiType,
+        cluster::http_protocol_options::CommonHttpOptions,
         common::*,
         network_filters::access_log::AccessLog,
         util::{duration_from_envoy, http_status_from},
+        ConfigSource, ConfigSourceSpecifier,
     };
     use compact_str::CompactString;
     use http::HeaderName;
     use orion_data_plane_api::envoy_data_plane_api::envoy::{
         config::route::v3::{
             retry_policy::RetryBackOff as EnvoyRetryBackoff, RetryPolicy as EnvoyRetryPolicy, Route as EnvoyRoute,
-            RouteConfiguration as EnvoyRouteConfiguration, VirtualHost as EnvoyVirtualHost,
+            RouteConfiguration as EnvoyRouteConfiguration, Vhds as EnvoyVhds, VirtualHost as EnvoyVirtualHost,
         },
         extensions::filters::network::http_connection_manager::v3::{
             http_connection_manager::{CodecType as EnvoyCodecType, RouteSpecifier as EnvoyRouteSpecifier},
@@ -611,7 +635,10 @@ mod envoy_conversions {
                     None => Ok(()),
                     Some(x) => match (x, &matching_filter.filter) {
                         (FilterConfigOverride::LocalRateLimit(_), HttpFilterType::RateLimit(_))
-                        | (FilterConfigOverride::Rbac(_), HttpFilterType::Rbac(_)) => Ok(()),
+                        | (FilterConfigOverride::Rbac(_), HttpFilterType::Rbac(_))
+                        | (FilterConfigOverride::OnDemand(_), HttpFilterType::OnDemand(_))
+                        // the type of a discovered filter is only known once its configuration is received
+                        | (_, HttpFilterType::ConfigDiscovery(_)) => Ok(()),
                         (_, _) => Err(GenericError::from_msg(format!(
                             "can't override http filter \"{name}\" with a different filter type"
                         ))),
@@ -687,7 +714,7 @@ mod envoy_conversions {
                 // http_filters,
                 add_user_agent,
                 // tracing,
-                common_http_protocol_options,
+                // common_http_protocol_options,
                 //http_protocol_options,
                 http2_protocol_options,
                 http3_protocol_options,
@@ -749,6 +776,11 @@ mod envoy_conversions {
                 .transpose()
                 .map_err(|_| GenericError::from_msg("failed to convert into Duration"))
                 .with_node("request_timeout")?;
+            let idle_timeout = common_http_protocol_options
+                .map(CommonHttpOptions::try_from)
+                .transpose()
+                .with_node("common_http_protocol_options")?
+                .and_then(|options| options.idle_timeout);
             let enabled_upgrades = upgrade_configs
                 .iter()
                 .filter(|upgrade_config| upgrade_config.enabled.map(|enabled| enabled.value).unwrap_or(true))
@@ -815,6 +847,7 @@ mod envoy_conversions {
                 enabled_upgrades,
                 route_specifier,
                 request_timeout,
+                idle_timeout,
                 access_log,
                 xff_settings,
                 generate_request_id: generate_request_id.map(|v| v.value).unwrap_or(true),
@@ -869,11 +902,10 @@ mod envoy_conversions {
                 Some(EnvoyRouteSpecifier::RouteConfig(envoy)) => {
                     Self::RouteConfig(envoy.try_into().with_node("route_config")?)
                 },
-                Some(EnvoyRouteSpecifier::ScopedRoutes(_)) => {
-                    return Err(GenericError::unsupported_variant("ScopedRoutes"));
+                Some(EnvoyRouteSpecifier::ScopedRoutes(envoy)) => {
+                    Self::ScopedRoutes(envoy.try_into().with_node("scoped_routes")?)
                 },
-
-                None => return Err(GenericError::MissingField("rds or route_config")),
+                None => return Err(GenericError::MissingField("rds, route_config or scoped_routes")),
             })
         }
     }
@@ -903,7 +935,7 @@ mod envoy_conversions {
             unsupported_field!(
                 // name,
                 // virtual_hosts,
-                vhds,
+                // vhds,
                 internal_only_headers,
                 // response_headers_to_add,
                 // response_headers_to_remove,
@@ -942,11 +974,15 @@ mod envoy_conversions {
                         })
                     })
                     .collect::<Result<Vec<_>, _>>()?;
-                let virtual_hosts = convert_non_empty_vec!(virtual_hosts)?;
+                let vhds = vhds.map(Vhds::try_from).transpose().with_node("vhds")?;
+                // the virtual hosts of a route configuration may all be discovered
+                let virtual_hosts =
+                    if vhds.is_some() { convert_vec!(virtual_hosts)? } else { convert_non_empty_vec!(virtual_hosts)? };
                 let response_header_modifier = HeaderModifier::new(response_headers_to_remove, response_headers_to_add);
                 Ok(Self {
                     name: name.clone(),
                     virtual_hosts,
+                    vhds,
                     most_specific_header_mutations_wins,
                     response_header_modifier,
                     request_headers_to_add,
@@ -1172,7 +1208,7 @@ mod envoy_conversions {
                 // response_headers_to_add,
                 // response_headers_to_remove,
                 tracing,
-                per_request_buffer_limit_bytes,
+                // per_request_buffer_limit_bytes,
                 stat_prefix
             )?;
             let response_headers_to_add = convert_vec!(response_headers_to_add)?;
@@ -1195,8 +1231,22 @@ mod envoy_conversions {
                     })
                 })
                 .collect::<Result<Vec<_>, _>>()?;
-            let action = convert_opt!(action)?;
-            let route_match = convert_opt!(r#match, "match")?;
+            let mut action = convert_opt!(action)?;
+            let route_match: RouteMatch = convert_opt!(r#match, "match")?;
+            // a uri_template rewrite builds the path out of the variables of the uri_template the route matches with
+            if let Action::Route(RouteAction { rewrite: Some(PathRewriteSpecifier::UriTemplate(rewrite)), .. }) =
+                &mut action
+            {
+                let Some(PathSpecifier::UriTemplate(path_template)) =
+                    route_match.path_matcher.as_ref().map(|path_matcher| &path_matcher.specifier)
+                else {
+                    return Err(GenericError::from_msg(
+                        "a uri_template path_rewrite_policy requires a uri_template path_match_policy",
+                    )
+                    .with_node("path_rewrite_policy"));
+                };
+                rewrite.bind(path_template).with_node("path_rewrite_policy")?;
+            }
             let typed_per_filter_config = {
                 typed_per_filter_config
                     .into_iter()
@@ -1215,6 +1265,7 @@ mod envoy_conversions {
                 request_headers_to_add,
                 request_headers_to_remove,
                 response_header_modifier,
+                per_request_buffer_limit_bytes: per_request_buffer_limit_bytes.map(|v| v.value),
             })
         }
     }
@@ -1228,4 +1279,155 @@ mod envoy_conversions {
             Ok(Self { route_config_name, config_source })
         }
     }
+
+    impl TryFrom<EnvoyVhds> for Vhds {
+        type Error = GenericError;
+        fn try_from(value: EnvoyVhds) -> Result<Self, Self::Error> {
+            let EnvoyVhds { config_source } = value;
+            let config_source: ConfigSource = convert_opt!(config_source)?;
+            // virtual hosts are only served incrementally
+            if let ConfigSourceSpecifier::ApiConfigSource(api_config_source) = &config_source.config_source_specifier {
+                if api_config_source.api_type != ApiType::DeltaGrpc {
+                    return Err(GenericError::from_msg("VHDS requires a DELTA_GRPC config source"))
+                        .with_node("config_source");
+                }
+            }
+            Ok(Self { config_source })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::super::{route::Action, Route, RouteConfiguration, Vhds};
+        use crate::config::{ConfigSource, ConfigSourceSpecifier};
+        use http::{uri::PathAndQuery, Request};
+        use orion_data_plane_api::envoy_data_plane_api::{
+            envoy::{
+                config::{
+                    core::v3::{
+                        api_config_source::ApiType as EnvoyApiType,
+                        config_source::ConfigSourceSpecifier as EnvoyConfigSourceSpecifier,
+                        grpc_service::{EnvoyGrpc, TargetSpecifier},
+                        AggregatedConfigSource, ApiConfigSource as EnvoyApiConfigSource,
+                        ConfigSource as EnvoyConfigSource, GrpcService,
+                        TypedExtensionConfig as EnvoyTypedExtensionConfig,
+                    },
+                    route::v3::{
+                        route::Action as EnvoyAction, route_action::ClusterSpecifier as EnvoyClusterSpecifier,
+                        route_match::PathSpecifier as EnvoyPathSpecifier, Route as EnvoyRoute,
+                        RouteAction as EnvoyRouteAction, RouteConfiguration as EnvoyRouteConfiguration,
+                        RouteMatch as EnvoyRouteMatch, Vhds as EnvoyVhds,
+                    },
+                },
+                extensions::path::{
+                    r#match::uri_template::v3::UriTemplateMatchConfig,
+                    rewrite::uri_template::v3::UriTemplateRewriteConfig,
+                },
+            },
+            google::protobuf::Any,
+            prost::Message,
+        };
+
+        #[test]
+        fn paths_rewritten_with_the_variables_of_the_uri_template_matched() {
+            let envoy_route = |path_template: &str, path_template_rewrite: &str| {
+                EnvoyRoute {
+                r#match: Some(EnvoyRouteMatch {
+                    path_specifier: Some(EnvoyPathSpecifier::PathMatchPolicy(EnvoyTypedExtensionConfig {
+                        name: "uri_template_match".to_owned(),
+                        typed_config: Some(Any {
+                            type_url: "type.googleapis.com/envoy.extensions.path.match.uri_template.v3.UriTemplateMatchConfig"
+                                .to_owned(),
+                            value: UriTemplateMatchConfig { path_template: path_template.to_owned() }.encode_to_vec(),
+                        }),
+                    })),
+                    ..Default::default()
+                }),
+                action: Some(EnvoyAction::Route(EnvoyRouteAction {
+                    cluster_specifier: Some(EnvoyClusterSpecifier::Cluster("items".to_owned())),
+                    path_rewrite_policy: Some(EnvoyTypedExtensionConfig {
+                        name: "uri_template_rewrite".to_owned(),
+                        typed_config: Some(Any {
+                            type_url:
+                                "type.googleapis.com/envoy.extensions.path.rewrite.uri_template.v3.UriTemplateRewriteConfig"
+                                    .to_owned(),
+                            value: UriTemplateRewriteConfig { path_template_rewrite: path_template_rewrite.to_owned() }
+                                .encode_to_vec(),
+                        }),
+                    }),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }
+            };
+
+            let route = Route::try_from(envoy_route("/v1/{tenant}/items/{id=**}", "/items/{id}?t={tenant}")).unwrap();
+            let request = Request::get("/v1/acme/items/a/b?page=2").body(()).unwrap();
+            let route_match = route.route_match.match_request(&request);
+            assert!(route_match.matched());
+            let rewrite = match &route.action {
+                Action::Route(action) => action.rewrite.as_ref(),
+                _ => None,
+            };
+            let rewritten = rewrite.unwrap().apply(request.uri().path_and_query(), &route_match).unwrap();
+            assert_eq!(rewritten, Some(PathAndQuery::from_static("/items/a/b?t=acme")));
+
+            let err = Route::try_from(envoy_route("/v1/{tenant}/items/{id=**}", "/items/{name}")).unwrap_err();
+            assert!(format!("{err:?}").contains("path_rewrite_policy"), "{err:?}");
+        }
+
+        #[test]
+        fn virtual_hosts_discovered_over_vhds() {
+            let vhds = |config_source_specifier| EnvoyVhds {
+                config_source: Some(EnvoyConfigSource {
+                    config_source_specifier: Some(config_source_specifier),
+                    ..Default::default()
+                }),
+            };
+            let route_configuration = |vhds| EnvoyRouteConfiguration {
+                name: "vhds_routes".to_owned(),
+                vhds: Some(vhds),
+                ..Default::default()
+            };
+
+            let routes = RouteConfiguration::try_from(route_configuration(vhds(EnvoyConfigSourceSpecifier::Ads(
+                AggregatedConfigSource {},
+            ))))
+            .unwrap();
+            assert!(routes.virtual_hosts.is_empty());
+            assert_eq!(
+                routes.vhds,
+                Some(Vhds {
+                    config_source: ConfigSource {
+                        config_source_specifier: ConfigSourceSpecifier::ADS,
+                        initial_fetch_timeout: None
+                    }
+                })
+            );
+
+            let state_of_the_world = EnvoyApiConfigSource {
+                api_type: EnvoyApiType::Grpc.into(),
+                grpc_services: vec![GrpcService {
+                    target_specifier: Some(TargetSpecifier::EnvoyGrpc(EnvoyGrpc {
+                        cluster_name: "vhds_cluster".to_owned(),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            let err = RouteConfiguration::try_from(route_configuration(vhds(
+                EnvoyConfigSourceSpecifier::ApiConfigSource(state_of_the_world),
+            )))
+            .unwrap_err();
+            assert!(format!("{err:?}").contains("DELTA_GRPC"), "{err:?}");
+
+            let err = RouteConfiguration::try_from(EnvoyRouteConfiguration {
+                name: "no_virtual_hosts".to_owned(),
+                ..Default::default()
+            })
+            .unwrap_err();
+            assert!(format!("{err:?}").contains("virtual_hosts"), "{err:?}");
+        }
+    }
 }
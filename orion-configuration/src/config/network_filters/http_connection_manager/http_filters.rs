@@ -21,6 +21,8 @@ use http_rbac::HttpRbac;
 pub mod local_rate_limit;
 use local_rate_limit::LocalRateLimit;
 pub mod filter_registry;
+pub mod health_check;
+pub mod on_demand;
 pub mod peer_metadata;
 pub mod router;
 pub mod set_filter_state;
@@ -41,6 +43,7 @@ pub enum FilterConfigOverride {
     // in Envoy this is a seperate type, RbacPerRoute, but it only has one field named rbac with the full config.
     // so we replace it with an option to be more rusty
     Rbac(Option<HttpRbac>),
+    OnDemand(on_demand::OnDemand),
 }
 
 impl From<FilterConfigOverride> for FilterOverride {
@@ -68,26 +71,36 @@ pub enum HttpFilterType {
     PeerMetadata(peer_metadata::PeerMetadataConfig),
     /// Envoy set filter state filter (parsed but may not be executed)
     SetFilterState(set_filter_state::SetFilterStateConfig),
+    HealthCheck(health_check::HealthCheck),
+    OnDemand(on_demand::OnDemand),
+    /// Discovered over ECDS under the name of the filter, and looked up for every request.
+    ConfigDiscovery(Box<ExtensionConfigSource<HttpFilterType>>),
 }
 
 #[cfg(feature = "envoy-conversions")]
 pub(crate) use envoy_conversions::*;
 
 use super::is_default;
+use crate::config::extension_config::ExtensionConfigSource;
 
 #[cfg(feature = "envoy-conversions")]
 mod envoy_conversions {
     #![allow(deprecated)]
     use super::filter_registry::ensure_filters_registered;
     use super::{FilterConfigOverride, FilterOverride, HttpFilter, HttpFilterType, HttpRbac};
-    use crate::config::common::*;
+    use crate::config::{common::*, extension_config::extension_config_source};
     use compact_str::CompactString;
     use orion_data_plane_api::envoy_data_plane_api::{
         envoy::{
-            config::route::v3::FilterConfig as EnvoyFilterConfig,
+            config::{
+                core::v3::ExtensionConfigSource as EnvoyExtensionConfigSource,
+                route::v3::FilterConfig as EnvoyFilterConfig,
+            },
             extensions::filters::{
                 http::{
+                    health_check::v3::HealthCheck as EnvoyHealthCheck,
                     local_ratelimit::v3::LocalRateLimit as EnvoyLocalRateLimit,
+                    on_demand::v3::{OnDemand as EnvoyOnDemand, PerRouteConfig as EnvoyOnDemandPerRoute},
                     rbac::v3::{Rbac as EnvoyRbac, RbacPerRoute as EnvoyRbacPerRoute},
                     router::v3::Router as EnvoyRouter,
                 },
@@ -115,8 +128,8 @@ mod envoy_conversions {
             unsupported_field!(is_optional)?;
             let name: CompactString = required!(name)?.into();
             match required!(config_type).map(|x| match x {
-                EnvoyConfigType::ConfigDiscovery(_) => {
-                    Err(GenericError::unsupported_variant("ConfigDiscovery")).with_node(name.clone())
+                EnvoyConfigType::ConfigDiscovery(config_discovery) => {
+                    Ok(SupportedEnvoyFilter::ConfigDiscovery(config_discovery))
                 },
                 EnvoyConfigType::TypedConfig(typed_config) => SupportedEnvoyFilter::try_from(typed_config),
             }) {
@@ -146,6 +159,15 @@ mod envoy_conversions {
                 SupportedEnvoyFilter::Ignored => Ok(Self::Ingored),
                 SupportedEnvoyFilter::PeerMetadata(config) => Ok(Self::PeerMetadata(config)),
                 SupportedEnvoyFilter::SetFilterState(config) => Ok(Self::SetFilterState(config)),
+                SupportedEnvoyFilter::HealthCheck(hc) => hc.try_into().map(Self::HealthCheck),
+                SupportedEnvoyFilter::OnDemand(on_demand) => on_demand.try_into().map(Self::OnDemand),
+                SupportedEnvoyFilter::ConfigDiscovery(config_discovery) => {
+                    extension_config_source(config_discovery, |default_config| {
+                        SupportedEnvoyFilter::try_from(default_config).and_then(Self::try_from)
+                    })
+                    .map(|source| Self::ConfigDiscovery(Box::new(source)))
+                    .with_node("config_discovery")
+                },
             }
         }
     }
@@ -159,6 +181,9 @@ mod envoy_conversions {
         Ignored,
         PeerMetadata(super::peer_metadata::PeerMetadataConfig),
         SetFilterState(super::set_filter_state::SetFilterStateConfig),
+        HealthCheck(EnvoyHealthCheck),
+        OnDemand(EnvoyOnDemand),
+        ConfigDiscovery(EnvoyExtensionConfigSource),
     }
 
     impl TryFrom<Any> for SupportedEnvoyFilter {
@@ -204,6 +229,12 @@ mod envoy_conversions {
                     "type.googleapis.com/envoy.extensions.filters.http.router.v3.Router" => {
                         EnvoyRouter::decode(typed_config.value.as_slice()).map(Self::Router)
                     },
+                    "type.googleapis.com/envoy.extensions.filters.http.health_check.v3.HealthCheck" => {
+                        EnvoyHealthCheck::decode(typed_config.value.as_slice()).map(Self::HealthCheck)
+                    },
+                    "type.googleapis.com/envoy.extensions.filters.http.on_demand.v3.OnDemand" => {
+                        EnvoyOnDemand::decode(typed_config.value.as_slice()).map(Self::OnDemand)
+                    },
                     "type.googleapis.com/udpa.type.v1.TypedStruct"
                     | "type.googleapis.com/stats.PluginConfig"
                     | "type.googleapis.com/envoy.extensions.filters.http.grpc_stats.v3.FilterConfig"
@@ -235,6 +266,7 @@ mod envoy_conversions {
     pub enum SupportedEnvoyFilterOverride {
         LocalRateLimit(EnvoyLocalRateLimit),
         Rbac(EnvoyRbacPerRoute),
+        OnDemand(EnvoyOnDemandPerRoute),
     }
 
     impl TryFrom<Any> for SupportedEnvoyFilterOverride {
@@ -247,6 +279,9 @@ mod envoy_conversions {
                 "type.googleapis.com/envoy.extensions.filters.http.rbac.v3.RBACPerRoute" => {
                     EnvoyRbacPerRoute::decode(typed_config.value.as_slice()).map(Self::Rbac)
                 },
+                "type.googleapis.com/envoy.extensions.filters.http.on_demand.v3.PerRouteConfig" => {
+                    EnvoyOnDemandPerRoute::decode(typed_config.value.as_slice()).map(Self::OnDemand)
+                },
                 _ => {
                     return Err(GenericError::unsupported_variant(format!(
                         "HTTP Filter override unsupported variant {}",
@@ -294,6 +329,7 @@ mod envoy_conversions {
                 SupportedEnvoyFilterOverride::Rbac(EnvoyRbacPerRoute { rbac }) => {
                     rbac.map(HttpRbac::try_from).transpose().map(Self::Rbac)
                 },
+                SupportedEnvoyFilterOverride::OnDemand(envoy) => envoy.try_into().map(Self::OnDemand),
             }
         }
     }
@@ -0,0 +1,91 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use crate::config::{is_default, network_filters::http_connection_manager::header_matcher::HeaderMatcher};
+use http::Request;
+use serde::{Deserialize, Serialize};
+
+/// Answers health check requests on behalf of the proxy, failing them once the health check has been failed through
+/// the admin interface.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HealthCheck {
+    /// When set, healthy health check requests are forwarded upstream instead of being answered by the filter.
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub pass_through_mode: bool,
+    /// A request is a health check request when it matches all of these.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub headers: Vec<HeaderMatcher>,
+}
+
+impl HealthCheck {
+    pub fn is_health_check<B>(&self, request: &Request<B>) -> bool {
+        self.headers.iter().all(|matcher| matcher.request_matches(request))
+    }
+}
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    use super::HealthCheck;
+    use crate::config::common::*;
+    use orion_data_plane_api::envoy_data_plane_api::envoy::extensions::filters::http::health_check::v3::HealthCheck as EnvoyHealthCheck;
+
+    impl TryFrom<EnvoyHealthCheck> for HealthCheck {
+        type Error = GenericError;
+        fn try_from(value: EnvoyHealthCheck) -> Result<Self, Self::Error> {
+            let EnvoyHealthCheck { pass_through_mode, cache_time, cluster_min_healthy_percentages, headers } = value;
+            unsupported_field!(cache_time, cluster_min_healthy_percentages)?;
+            let pass_through_mode = required!(pass_through_mode)?.value;
+            let headers = convert_vec!(headers)?;
+            Ok(Self { pass_through_mode, headers })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::{
+            config::route::v3::{header_matcher::HeaderMatchSpecifier, HeaderMatcher as EnvoyHeaderMatcher},
+            extensions::filters::http::health_check::v3::HealthCheck as EnvoyHealthCheck,
+        },
+        google::protobuf::BoolValue,
+    };
+
+    #[test]
+    #[allow(deprecated)]
+    fn health_check_from_envoy() {
+        let envoy = EnvoyHealthCheck {
+            pass_through_mode: Some(BoolValue { value: false }),
+            headers: vec![EnvoyHeaderMatcher {
+                name: ":path".to_owned(),
+                header_match_specifier: Some(HeaderMatchSpecifier::ExactMatch("/healthz".to_owned())),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let health_check = HealthCheck::try_from(envoy).unwrap();
+        assert!(!health_check.pass_through_mode);
+
+        let request = Request::get("http://example.com/healthz").body(()).unwrap();
+        assert!(health_check.is_health_check(&request));
+        let request = Request::get("http://example.com/other").body(()).unwrap();
+        assert!(!health_check.is_health_check(&request));
+
+        assert!(HealthCheck::try_from(EnvoyHealthCheck::default()).is_err());
+    }
+}
@@ -15,11 +15,12 @@
 //
 //
 
+use crate::config::layered_runtime::RuntimeFractionalPercent;
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct LocalRateLimit {
     #[serde(
         with = "http_serde_ext::status_code",
@@ -28,6 +29,13 @@ pub struct LocalRateLimit {
     )]
     pub status: StatusCode,
     pub token_bucket: Option<TokenBucket>,
+    /// The fraction of requests the rate limit is checked for. Checked for every request when not set.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub filter_enabled: Option<RuntimeFractionalPercent>,
+    /// The fraction of rate limited requests that are rejected, the others are only counted. Every rate limited
+    /// request is rejected when not set.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub filter_enforced: Option<RuntimeFractionalPercent>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -53,6 +61,7 @@ mod envoy_conversions {
     use super::{LocalRateLimit, TokenBucket};
     use crate::config::{
         common::*,
+        layered_runtime::RuntimeFractionalPercent,
         util::{duration_from_envoy, http_status_from_envoy},
     };
     use http::StatusCode;
@@ -86,8 +95,8 @@ mod envoy_conversions {
                 // stat_prefix,
                 // status,
                 // token_bucket,
-                filter_enabled,
-                filter_enforced,
+                // filter_enabled,
+                // filter_enforced,
                 request_headers_to_add_when_not_enforced,
                 response_headers_to_add,
                 descriptors,
@@ -111,6 +120,10 @@ mod envoy_conversions {
                 .transpose()
                 .with_node("status")?
                 .unwrap_or(StatusCode::TOO_MANY_REQUESTS);
+            let filter_enabled =
+                filter_enabled.map(RuntimeFractionalPercent::try_from).transpose().with_node("filter_enabled")?;
+            let filter_enforced =
+                filter_enforced.map(RuntimeFractionalPercent::try_from).transpose().with_node("filter_enforced")?;
             if let Some(tb) = token_bucket {
                 let EnvoyTokenBucket { max_tokens, tokens_per_fill, fill_interval } = tb;
                 let max_tokens = required!(max_tokens).with_node("token_bucket")?;
@@ -126,9 +139,11 @@ mod envoy_conversions {
                 return Ok(Self {
                     status,
                     token_bucket: Some(TokenBucket { max_tokens, tokens_per_fill, fill_interval }),
+                    filter_enabled,
+                    filter_enforced,
                 });
             }
-            Ok(Self { status, token_bucket: None })
+            Ok(Self { status, token_bucket: None, filter_enabled, filter_enforced })
         }
     }
 }
@@ -0,0 +1,150 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use crate::config::ConfigSource;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Pauses the requests routed to a cluster that isn't loaded, while the cluster is discovered on demand.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct OnDemand {
+    /// Clusters aren't discovered on demand if unset.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub odcds: Option<OnDemandCds>,
+}
+
+/// Where clusters are discovered from on demand, and how long a request or connection waits for one.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct OnDemandCds {
+    pub config_source: ConfigSource,
+    #[serde(with = "humantime_serde", default = "OnDemandCds::default_timeout")]
+    pub timeout: Duration,
+}
+
+impl OnDemandCds {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn default_timeout() -> Duration {
+        Self::DEFAULT_TIMEOUT
+    }
+}
+
+#[cfg(feature = "envoy-conversions")]
+pub(crate) use envoy_conversions::on_demand_cds;
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    use super::{OnDemand, OnDemandCds};
+    use crate::config::{common::*, util::duration_from_envoy, ConfigSource};
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::{
+            config::core::v3::ConfigSource as EnvoyConfigSource,
+            extensions::filters::http::on_demand::v3::{
+                OnDemand as EnvoyOnDemand, OnDemandCds as EnvoyOnDemandCds, PerRouteConfig as EnvoyPerRouteConfig,
+            },
+        },
+        google::protobuf::Duration as PbDuration,
+    };
+
+    /// Shared by the on-demand filter and the TCP proxy, which configure on-demand CDS with the same fields.
+    pub(crate) fn on_demand_cds(
+        source: Option<EnvoyConfigSource>,
+        resources_locator: String,
+        timeout: Option<PbDuration>,
+    ) -> Result<OnDemandCds, GenericError> {
+        unsupported_field!(resources_locator)?;
+        let config_source: ConfigSource = convert_opt!(source)?;
+        let timeout =
+            timeout.map(duration_from_envoy).transpose().with_node("timeout")?.unwrap_or(OnDemandCds::DEFAULT_TIMEOUT);
+        Ok(OnDemandCds { config_source, timeout })
+    }
+
+    impl TryFrom<EnvoyOnDemandCds> for OnDemandCds {
+        type Error = GenericError;
+        fn try_from(value: EnvoyOnDemandCds) -> Result<Self, Self::Error> {
+            let EnvoyOnDemandCds { source, resources_locator, timeout } = value;
+            on_demand_cds(source, resources_locator, timeout)
+        }
+    }
+
+    impl TryFrom<EnvoyOnDemand> for OnDemand {
+        type Error = GenericError;
+        fn try_from(value: EnvoyOnDemand) -> Result<Self, Self::Error> {
+            let EnvoyOnDemand { odcds } = value;
+            let odcds = odcds.map(OnDemandCds::try_from).transpose().with_node("odcds")?;
+            Ok(Self { odcds })
+        }
+    }
+
+    impl TryFrom<EnvoyPerRouteConfig> for OnDemand {
+        type Error = GenericError;
+        fn try_from(value: EnvoyPerRouteConfig) -> Result<Self, Self::Error> {
+            let EnvoyPerRouteConfig { odcds } = value;
+            EnvoyOnDemand { odcds }.try_into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigSourceSpecifier;
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::{
+            config::core::v3::{
+                config_source::ConfigSourceSpecifier as EnvoyConfigSourceSpecifier, AggregatedConfigSource,
+                ConfigSource as EnvoyConfigSource,
+            },
+            extensions::filters::http::on_demand::v3::{OnDemand as EnvoyOnDemand, OnDemandCds as EnvoyOnDemandCds},
+        },
+        google::protobuf::Duration as PbDuration,
+    };
+
+    #[test]
+    fn on_demand_cds_from_envoy() {
+        let ads = EnvoyConfigSource {
+            config_source_specifier: Some(EnvoyConfigSourceSpecifier::Ads(AggregatedConfigSource {})),
+            ..Default::default()
+        };
+        let envoy = EnvoyOnDemand {
+            odcds: Some(EnvoyOnDemandCds {
+                source: Some(ads.clone()),
+                timeout: Some(PbDuration { seconds: 2, nanos: 0 }),
+                ..Default::default()
+            }),
+        };
+        let odcds = OnDemand::try_from(envoy).unwrap().odcds.unwrap();
+        assert_eq!(odcds.config_source.config_source_specifier, ConfigSourceSpecifier::ADS);
+        assert_eq!(odcds.timeout, Duration::from_secs(2));
+
+        let envoy = EnvoyOnDemand { odcds: Some(EnvoyOnDemandCds { source: Some(ads.clone()), ..Default::default() }) };
+        assert_eq!(OnDemand::try_from(envoy).unwrap().odcds.unwrap().timeout, OnDemandCds::DEFAULT_TIMEOUT);
+
+        assert_eq!(OnDemand::try_from(EnvoyOnDemand::default()).unwrap(), OnDemand::default());
+        let envoy = EnvoyOnDemand { odcds: Some(EnvoyOnDemandCds::default()) };
+        assert!(OnDemand::try_from(envoy).is_err(), "a config source is required");
+        let envoy = EnvoyOnDemand {
+            odcds: Some(EnvoyOnDemandCds {
+                source: Some(ads),
+                resources_locator: "xdstp://example.com/envoy.config.cluster.v3.Cluster/*".to_owned(),
+                ..Default::default()
+            }),
+        };
+        let err = OnDemand::try_from(envoy).unwrap_err();
+        assert!(format!("{err:?}").contains("resources_locator"), "{err:?}");
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Redirects answered by an upstream which the proxy follows itself instead of handing them to the client.
+//!
+//! The request is routed again with the host and path of the `Location` of the redirect, as many times as the policy
+//! allows, and only to the routes its predicates accept.
+
+use compact_str::CompactString;
+use http::{uri::Scheme, HeaderName, StatusCode};
+use serde::{Deserialize, Serialize};
+
+pub(crate) const DEFAULT_MAX_INTERNAL_REDIRECTS: u32 = 1;
+const fn default_max_internal_redirects() -> u32 {
+    DEFAULT_MAX_INTERNAL_REDIRECTS
+}
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_default_max_internal_redirects(value: &u32) -> bool {
+    *value == DEFAULT_MAX_INTERNAL_REDIRECTS
+}
+
+fn default_redirect_response_codes() -> Vec<StatusCode> {
+    vec![StatusCode::FOUND]
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct InternalRedirectPolicy {
+    #[serde(skip_serializing_if = "is_default_max_internal_redirects", default = "default_max_internal_redirects")]
+    pub max_internal_redirects: u32,
+    #[serde(with = "http_serde_ext::status_code::vec", default = "default_redirect_response_codes")]
+    pub redirect_response_codes: Vec<StatusCode>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    pub predicates: Vec<InternalRedirectPredicate>,
+    #[serde(skip_serializing_if = "std::ops::Not::not", default = "Default::default")]
+    pub allow_cross_scheme_redirect: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
+    #[serde(with = "http_serde_ext::header_name::vec")]
+    pub response_headers_to_copy: Vec<HeaderName>,
+}
+
+impl Default for InternalRedirectPolicy {
+    fn default() -> Self {
+        Self {
+            max_internal_redirects: DEFAULT_MAX_INTERNAL_REDIRECTS,
+            redirect_response_codes: default_redirect_response_codes(),
+            predicates: Vec::new(),
+            allow_cross_scheme_redirect: false,
+            response_headers_to_copy: Vec::new(),
+        }
+    }
+}
+
+impl InternalRedirectPolicy {
+    pub fn redirects_on(&self, status: StatusCode) -> bool {
+        self.redirect_response_codes.contains(&status)
+    }
+
+    /// Whether the request, received over `downstream_scheme`, may be redirected to the route named `route_name`
+    /// at a location of scheme `target_scheme`. `previously_visited` tells whether an earlier hop of the same request
+    /// was already routed there.
+    pub fn allows_target(
+        &self,
+        route_name: &str,
+        previously_visited: bool,
+        downstream_scheme: &Scheme,
+        target_scheme: &Scheme,
+    ) -> bool {
+        (self.allow_cross_scheme_redirect || downstream_scheme == target_scheme)
+            && self.predicates.iter().all(|predicate| match predicate {
+                InternalRedirectPredicate::AllowListedRoutes(routes) => routes.iter().any(|name| name == route_name),
+                InternalRedirectPredicate::PreviousRoutes => !previously_visited,
+                InternalRedirectPredicate::SafeCrossScheme => {
+                    *downstream_scheme == Scheme::HTTPS || *target_scheme == Scheme::HTTP
+                },
+            })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InternalRedirectPredicate {
+    /// only redirects to the routes of these names
+    AllowListedRoutes(Vec<CompactString>),
+    /// never redirects twice to the same route
+    PreviousRoutes,
+    /// only redirects from https, or to http
+    SafeCrossScheme,
+}
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    use super::{InternalRedirectPolicy, InternalRedirectPredicate, DEFAULT_MAX_INTERNAL_REDIRECTS};
+    use crate::config::{common::*, util::http_status_from};
+    use http::{HeaderName, StatusCode};
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::{
+            config::{
+                core::v3::TypedExtensionConfig as EnvoyTypedExtensionConfig,
+                route::v3::InternalRedirectPolicy as EnvoyInternalRedirectPolicy,
+            },
+            extensions::internal_redirect::allow_listed_routes::v3::AllowListedRoutesConfig as EnvoyAllowListedRoutesConfig,
+        },
+        prost::Message,
+    };
+    use std::str::FromStr;
+
+    const ALLOW_LISTED_ROUTES_TYPE_URL: &str =
+        "type.googleapis.com/envoy.extensions.internal_redirect.allow_listed_routes.v3.AllowListedRoutesConfig";
+    const PREVIOUS_ROUTES_TYPE_URL: &str =
+        "type.googleapis.com/envoy.extensions.internal_redirect.previous_routes.v3.PreviousRoutesConfig";
+    const SAFE_CROSS_SCHEME_TYPE_URL: &str =
+        "type.googleapis.com/envoy.extensions.internal_redirect.safe_cross_scheme.v3.SafeCrossSchemeConfig";
+
+    impl TryFrom<EnvoyInternalRedirectPolicy> for InternalRedirectPolicy {
+        type Error = GenericError;
+        fn try_from(value: EnvoyInternalRedirectPolicy) -> Result<Self, Self::Error> {
+            let EnvoyInternalRedirectPolicy {
+                max_internal_redirects,
+                redirect_response_codes,
+                predicates,
+                allow_cross_scheme_redirect,
+                response_headers_to_copy,
+            } = value;
+            let max_internal_redirects = max_internal_redirects.map_or(DEFAULT_MAX_INTERNAL_REDIRECTS, |v| v.value);
+            // like envoy, only the redirect codes are honoured and anything else is ignored
+            let redirect_response_codes = redirect_response_codes
+                .into_iter()
+                .map(http_status_from)
+                .collect::<Result<Vec<_>, _>>()
+                .with_node("redirect_response_codes")?
+                .into_iter()
+                .filter(|code| {
+                    matches!(
+                        *code,
+                        StatusCode::MOVED_PERMANENTLY
+                            | StatusCode::FOUND
+                            | StatusCode::SEE_OTHER
+                            | StatusCode::TEMPORARY_REDIRECT
+                            | StatusCode::PERMANENT_REDIRECT
+                    )
+                })
+                .collect::<Vec<_>>();
+            let redirect_response_codes = if redirect_response_codes.is_empty() {
+                super::default_redirect_response_codes()
+            } else {
+                redirect_response_codes
+            };
+            let predicates = convert_vec!(predicates)?;
+            let response_headers_to_copy = response_headers_to_copy
+                .into_iter()
+                .map(|s| {
+                    HeaderName::from_str(&s).map_err(|e| {
+                        GenericError::from_msg_with_cause(format!("failed to convert \"{s}\" into HeaderName"), e)
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .with_node("response_headers_to_copy")?;
+            Ok(Self {
+                max_internal_redirects,
+                redirect_response_codes,
+                predicates,
+                allow_cross_scheme_redirect,
+                response_headers_to_copy,
+            })
+        }
+    }
+
+    impl TryFrom<EnvoyTypedExtensionConfig> for InternalRedirectPredicate {
+        type Error = GenericError;
+        fn try_from(value: EnvoyTypedExtensionConfig) -> Result<Self, Self::Error> {
+            let EnvoyTypedExtensionConfig { name, typed_config } = value;
+            (|| -> Result<_, GenericError> {
+                let typed_config = required!(typed_config)?;
+                match typed_config.type_url.as_str() {
+                    ALLOW_LISTED_ROUTES_TYPE_URL => {
+                        let EnvoyAllowListedRoutesConfig { allowed_route_names } =
+                            EnvoyAllowListedRoutesConfig::decode(typed_config.value.as_slice()).map_err(|e| {
+                                GenericError::from_msg_with_cause("failed to decode AllowListedRoutesConfig", e)
+                            })?;
+                        if allowed_route_names.is_empty() {
+                            return Err(GenericError::MissingField("allowed_route_names"));
+                        }
+                        Ok(Self::AllowListedRoutes(allowed_route_names.into_iter().map(Into::into).collect()))
+                    },
+                    PREVIOUS_ROUTES_TYPE_URL => Ok(Self::PreviousRoutes),
+                    SAFE_CROSS_SCHEME_TYPE_URL => Ok(Self::SafeCrossScheme),
+                    _ => Err(GenericError::unsupported_variant(typed_config.type_url)),
+                }
+            })()
+            .with_name(name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirect_targets_accepted_by_the_predicates() {
+        let policy = InternalRedirectPolicy {
+            predicates: vec![
+                InternalRedirectPredicate::AllowListedRoutes(vec!["legacy".into(), "current".into()]),
+                InternalRedirectPredicate::PreviousRoutes,
+            ],
+            ..Default::default()
+        };
+        assert!(policy.redirects_on(StatusCode::FOUND));
+        assert!(!policy.redirects_on(StatusCode::MOVED_PERMANENTLY));
+        assert!(policy.allows_target("current", false, &Scheme::HTTP, &Scheme::HTTP));
+        assert!(!policy.allows_target("current", true, &Scheme::HTTP, &Scheme::HTTP), "already visited");
+        assert!(!policy.allows_target("other", false, &Scheme::HTTP, &Scheme::HTTP), "not allow listed");
+        assert!(!policy.allows_target("current", false, &Scheme::HTTP, &Scheme::HTTPS), "cross scheme");
+
+        let policy = InternalRedirectPolicy {
+            allow_cross_scheme_redirect: true,
+            predicates: vec![InternalRedirectPredicate::SafeCrossScheme],
+            ..Default::default()
+        };
+        assert!(policy.allows_target("any", true, &Scheme::HTTPS, &Scheme::HTTP));
+        assert!(!policy.allows_target("any", true, &Scheme::HTTP, &Scheme::HTTPS));
+    }
+}
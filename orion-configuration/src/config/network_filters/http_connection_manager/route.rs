@@ -15,11 +15,17 @@
 //
 //
 
-use super::{header_matcher::HeaderMatcher, RetryPolicy};
+use super::{
+    header_matcher::HeaderMatcher,
+    internal_redirect::InternalRedirectPolicy,
+    uri_template::{UriTemplate, UriTemplateRewrite},
+    RetryPolicy,
+};
 use crate::config::{
     cluster::ClusterSpecifier,
     common::*,
     core::{CaseSensitive, DataSource, StringMatcher},
+    layered_runtime::RuntimeFractionalPercent,
 };
 use bytes::Bytes;
 use compact_str::CompactString;
@@ -79,6 +85,7 @@ pub enum PathRewriteSpecifier {
     Path(#[serde(with = "http_serde_ext::path_and_query")] PathAndQuery),
     Prefix(CompactString),
     Regex(RegexMatchAndSubstitute),
+    UriTemplate(UriTemplateRewrite),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -87,6 +94,8 @@ pub enum AuthorityRewriteSpecifier {
     Authority(#[serde(with = "http_serde_ext::authority")] Authority),
     Header(CompactString),
     Regex(RegexMatchAndSubstitute),
+    /// the authority is the path of the request, rewritten with the regex
+    PathRegex(RegexMatchAndSubstitute),
     AutoHostRewrite,
 }
 
@@ -121,6 +130,13 @@ impl PathRewriteSpecifier {
                     return Ok(None);
                 }
             },
+            PathRewriteSpecifier::UriTemplate(rewrite) => {
+                if let Some(rewritten) = rewrite.apply(old_path) {
+                    rewritten.into()
+                } else {
+                    return Ok(None);
+                }
+            },
         };
         if let Some(old_query) = old_query {
             if !new_path.contains('?') {
@@ -165,6 +181,15 @@ impl AuthorityRewriteSpecifier {
                 }
             },
 
+            AuthorityRewriteSpecifier::PathRegex(regex) => {
+                let replacement = regex.pattern.replace_all(uri.path(), regex.substitution.as_str());
+                if let std::borrow::Cow::Borrowed(_) = replacement {
+                    None
+                } else {
+                    Authority::from_str(&replacement).ok()
+                }
+            },
+
             AuthorityRewriteSpecifier::AutoHostRewrite => Some(upstream_authority.clone()),
         }
     }
@@ -280,12 +305,17 @@ pub struct RouteAction {
     pub rewrite: Option<PathRewriteSpecifier>,
     #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
     pub authority_rewrite: Option<AuthorityRewriteSpecifier>,
+    /// whether the host is appended to `x-forwarded-host` when the authority is rewritten
+    #[serde(skip_serializing_if = "std::ops::Not::not", default = "Default::default")]
+    pub append_x_forwarded_host: bool,
     #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
     pub retry_policy: Option<RetryPolicy>,
     #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
     pub upgrade_config: Option<UpgradeConfig>,
     #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
     pub hash_policy: Vec<HashPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub internal_redirect_policy: Option<Box<InternalRedirectPolicy>>,
 }
 
 const DEFAULT_CLUSTER_NOT_FOUND_STATUSCODE: StatusCode = StatusCode::SERVICE_UNAVAILABLE;
@@ -453,6 +483,9 @@ pub struct RouteMatch {
     pub headers: Vec<HeaderMatcher>,
     #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
     pub query_parameters: Vec<QueryParameterMatcher>,
+    /// The fraction of the requests matched by the route, evaluated for each request.
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub runtime_fraction: Option<RuntimeFractionalPercent>,
 }
 
 impl Default for RouteMatch {
@@ -462,6 +495,7 @@ impl Default for RouteMatch {
             method_matcher: None,
             headers: Vec::new(),
             query_parameters: Vec::new(),
+            runtime_fraction: None,
         }
     }
 }
@@ -575,6 +609,7 @@ impl PathMatcher {
                 }
             },
             PathSpecifier::Regex(r) => r.matches_full(path).then_some(path.len()),
+            PathSpecifier::UriTemplate(template) => template.matches(path).then_some(path.len()),
         };
         PathMatcherResult { inner }
     }
@@ -587,6 +622,7 @@ pub enum PathSpecifier {
     Exact(CompactString),
     Regex(#[serde(with = "serde_regex")] Regex),
     PathSeparatedPrefix(CompactString),
+    UriTemplate(UriTemplate),
 }
 
 impl PartialEq for PathSpecifier {
@@ -596,6 +632,7 @@ impl PartialEq for PathSpecifier {
             (Self::Prefix(s1), Self::Prefix(s2))
             | (Self::Exact(s1), Self::Exact(s2))
             | (Self::PathSeparatedPrefix(s1), Self::PathSeparatedPrefix(s2)) => s1.eq(s2),
+            (Self::UriTemplate(t1), Self::UriTemplate(t2)) => t1.eq(t2),
             _ => false,
         }
     }
@@ -608,6 +645,7 @@ impl Hash for PathSpecifier {
         match self {
             Self::Regex(r) => r.as_str().hash(state),
             Self::Prefix(s) | Self::Exact(s) | Self::PathSeparatedPrefix(s) => s.hash(state),
+            Self::UriTemplate(t) => t.hash(state),
         }
     }
 }
@@ -669,6 +707,7 @@ mod tests {
                     pattern: StringMatcherPattern::Exact("v1".into()),
                 }),
             }],
+            runtime_fraction: None,
         };
         let req = Request::builder().uri("/test?version=v1").body(()).unwrap();
         let result = route_match.match_request(&req);
@@ -685,6 +724,7 @@ mod tests {
                 name: "x".into(),
                 match_specifier: QueryParameterMatchSpecifier::PresentMatch(true),
             }],
+            runtime_fraction: None,
         };
         let req = Request::builder().uri("/test?x=true").body(()).unwrap();
         let result = route_match.match_request(&req);
@@ -704,6 +744,7 @@ mod tests {
                 name: "y".into(),
                 match_specifier: QueryParameterMatchSpecifier::PresentMatch(false),
             }],
+            runtime_fraction: None,
         };
         let req = Request::builder().uri("/test?y=true").body(()).unwrap();
         let result = route_match.match_request(&req);
@@ -729,6 +770,7 @@ mod tests {
                     match_specifier: QueryParameterMatchSpecifier::PresentMatch(true),
                 },
             ],
+            runtime_fraction: None,
         };
         let req = Request::builder().uri("/test?version=v1&x=true").body(()).unwrap();
         let result = route_match.match_request(&req);
@@ -822,6 +864,26 @@ mod tests {
         let result = authority_rewrite.apply(&uri, &headers, &upstream_authority);
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn test_authority_rewrite_path_regex() {
+        let regex = RegexMatchAndSubstitute {
+            pattern: Regex::new(r"^/tenants/([^/]+)/.*$").unwrap(),
+            substitution: "${1}.saas.example.com".into(),
+        };
+        let authority_rewrite = AuthorityRewriteSpecifier::PathRegex(regex);
+        let upstream_authority = Authority::from_str("upstream.example.com:8080").unwrap();
+        let mut headers = http::HeaderMap::new();
+        headers.insert("host", "original.example.com".parse().unwrap());
+
+        let uri = "http://original.example.com/tenants/acme/orders?page=2".parse::<http::Uri>().unwrap();
+        let result = authority_rewrite.apply(&uri, &headers, &upstream_authority);
+        assert_eq!(result, Some(Authority::from_str("acme.saas.example.com").unwrap()));
+
+        let uri = "http://original.example.com/health".parse::<http::Uri>().unwrap();
+        let result = authority_rewrite.apply(&uri, &headers, &upstream_authority);
+        assert_eq!(result, None);
+    }
 }
 
 #[cfg(feature = "envoy-conversions")]
@@ -831,12 +893,17 @@ mod envoy_conversions {
         Action, AuthorityRedirect, AuthorityRewriteSpecifier, Connect, DirectResponseAction, DirectResponseBody,
         HashPolicy, MethodMatcher, MethodSpecifier, PathMatcher, PathRewriteSpecifier, PathSpecifier, PolicySpecifier,
         QueryParameterMatchSpecifier, QueryParameterMatcher, RedirectAction, RedirectResponseCode,
-        RegexMatchAndSubstitute, RouteAction, RouteMatch, UpgradeConfig, Websocket, DEFAULT_TIMEOUT,
+        RegexMatchAndSubstitute, RouteAction, RouteMatch, UpgradeConfig, UriTemplate, UriTemplateRewrite, Websocket,
+        DEFAULT_TIMEOUT,
     };
     use crate::config::{
         common::*,
         core::{regex_from_envoy, DataSource},
-        network_filters::http_connection_manager::RetryPolicy,
+        layered_runtime::RuntimeFractionalPercent,
+        network_filters::http_connection_manager::{
+            internal_redirect::{InternalRedirectPolicy, DEFAULT_MAX_INTERNAL_REDIRECTS},
+            RetryPolicy,
+        },
         util::{duration_from_envoy, http_status_from, parse_cluster_not_found_response_code},
     };
     use http::{
@@ -857,7 +924,7 @@ mod envoy_conversions {
                     PolicySpecifier as EnvoyPolicySpecifier, QueryParameter as EnvoyQueryParameter,
                 },
                 HashPolicy as EnvoyHashPolicy, HostRewriteSpecifier as EnvoyHostRewriteSpecifier,
-                UpgradeConfig as EnvoyUpgradeConfig,
+                InternalRedirectAction as EnvoyInternalRedirectAction, UpgradeConfig as EnvoyUpgradeConfig,
             },
             route_match::PathSpecifier as EnvoyPathSpecifier,
             DirectResponseAction as EnvoyDirectResponseAction, QueryParameterMatcher as EnvoyQueryParameterMatcher,
@@ -1063,8 +1130,8 @@ mod envoy_conversions {
                 metadata_match,
                 // prefix_rewrite,
                 // regex_rewrite,
-                path_rewrite_policy,
-                append_x_forwarded_host,
+                // path_rewrite_policy,
+                // append_x_forwarded_host,
                 // timeout,
                 idle_timeout,
                 early_data_policy,
@@ -1079,9 +1146,9 @@ mod envoy_conversions {
                 //max_grpc_timeout,
                 grpc_timeout_offset,
                 // upgrade_configs,
-                internal_redirect_policy,
-                internal_redirect_action,
-                max_internal_redirects,
+                // internal_redirect_policy,
+                // internal_redirect_action,
+                // max_internal_redirects,
                 hedge_policy // cluster_specifier,
                              // host_rewrite_specifier
             )?;
@@ -1092,21 +1159,49 @@ mod envoy_conversions {
             // in order to better match the rest of the code/rust, we map disabled to None and the default to Some(15s)
             let timeout = if timeout.is_zero() { None } else { Some(timeout) };
             let cluster_specifier = convert_opt!(cluster_specifier)?;
-            let rewrite = match (prefix_rewrite.is_used().then_some(prefix_rewrite), regex_rewrite) {
-                (None, None) => None,
-                (Some(s), None) => Some(PathRewriteSpecifier::Prefix(s.into())),
-                (None, Some(regex)) => {
+            let rewrite = match (prefix_rewrite.is_used().then_some(prefix_rewrite), regex_rewrite, path_rewrite_policy)
+            {
+                (None, None, None) => None,
+                (Some(s), None, None) => Some(PathRewriteSpecifier::Prefix(s.into())),
+                (None, Some(regex), None) => {
                     Some(regex.try_into().map(PathRewriteSpecifier::Regex).with_node("regex_rewrite")?)
                 },
-                (Some(_), Some(_)) => {
+                (None, None, Some(policy)) => Some(
+                    UriTemplateRewrite::try_from(policy)
+                        .map(PathRewriteSpecifier::UriTemplate)
+                        .with_node("path_rewrite_policy")?,
+                ),
+                _ => {
                     return Err(GenericError::from_msg(
-                        "only one of `prefix_rewrite` and `regex_rewrite` may be specified",
+                        "only one of `prefix_rewrite`, `regex_rewrite` and `path_rewrite_policy` may be specified",
                     ));
                 },
             };
             let retry_policy = retry_policy.map(RetryPolicy::try_from).transpose().with_node("retry_policy")?;
             let upgrade_config = upgrade_configs.try_into().with_node("upgrade_configs").ok();
             let hash_policy = convert_vec!(hash_policy)?;
+            let internal_redirect_policy = match internal_redirect_policy {
+                Some(policy) => {
+                    Some(Box::new(InternalRedirectPolicy::try_from(policy).with_node("internal_redirect_policy")?))
+                },
+                // the deprecated way of handling redirects, superseded by the policy
+                None => match EnvoyInternalRedirectAction::from_i32(internal_redirect_action) {
+                    Some(EnvoyInternalRedirectAction::HandleInternalRedirect) => {
+                        Some(Box::new(InternalRedirectPolicy {
+                            max_internal_redirects: max_internal_redirects
+                                .map_or(DEFAULT_MAX_INTERNAL_REDIRECTS, |v| v.value),
+                            ..Default::default()
+                        }))
+                    },
+                    Some(EnvoyInternalRedirectAction::PassThroughInternalRedirect) => None,
+                    None => {
+                        return Err(GenericError::from_msg(format!(
+                            "[unknown internal_redirect_action {internal_redirect_action}]"
+                        ))
+                        .with_node("internal_redirect_action"));
+                    },
+                },
+            };
             let authority_rewrite = match host_rewrite_specifier {
                 Some(EnvoyHostRewriteSpecifier::AutoHostRewrite(bv)) => {
                     if bv.value {
@@ -1132,7 +1227,7 @@ mod envoy_conversions {
                         )),
                     },
                     EnvoyHostRewriteSpecifier::HostRewritePathRegex(regex) => {
-                        regex.try_into().map(AuthorityRewriteSpecifier::Regex)
+                        regex.try_into().map(AuthorityRewriteSpecifier::PathRegex)
                     },
                     EnvoyHostRewriteSpecifier::AutoHostRewrite(_) => unreachable!(),
                 }
@@ -1146,9 +1241,11 @@ mod envoy_conversions {
                 cluster_specifier,
                 rewrite,
                 authority_rewrite,
+                append_x_forwarded_host,
                 retry_policy,
                 upgrade_config,
                 hash_policy,
+                internal_redirect_policy,
             })
         }
     }
@@ -1240,7 +1337,7 @@ mod envoy_conversions {
             } = value;
             unsupported_field!(
                 // case_sensitive,
-                runtime_fraction,
+                // runtime_fraction,
                 // headers,
                 // query_parameters,
                 grpc,
@@ -1263,7 +1360,9 @@ mod envoy_conversions {
 
             let headers = convert_vec!(headers)?;
             let query_parameters = convert_vec!(query_parameters)?;
-            Ok(Self { path_matcher, method_matcher, headers, query_parameters })
+            let runtime_fraction =
+                runtime_fraction.map(RuntimeFractionalPercent::try_from).transpose().with_node("runtime_fraction")?;
+            Ok(Self { path_matcher, method_matcher, headers, query_parameters, runtime_fraction })
         }
     }
 
@@ -1287,7 +1386,9 @@ mod envoy_conversions {
                 EnvoyPathSpecifier::ConnectMatcher(_) => {
                     Err(GenericError::from_msg("ConnectMatcher should be converted to MethodMatcher in RouteMatch"))
                 },
-                EnvoyPathSpecifier::PathMatchPolicy(_) => Err(GenericError::unsupported_variant("PathMatchPolicy")),
+                EnvoyPathSpecifier::PathMatchPolicy(policy) => {
+                    UriTemplate::try_from(policy).map(Self::UriTemplate).with_node("path_match_policy")
+                },
             }
         }
     }
@@ -1361,6 +1462,7 @@ mod envoy_conversions {
                 path_matcher: Some(PathMatcher { specifier: PathSpecifier::Prefix("/api".into()), ignore_case: false }),
                 headers: Vec::new(),
                 query_parameters: Vec::new(),
+                runtime_fraction: None,
             };
 
             let req = Request::builder().method(http::Method::CONNECT).uri("/api/test").body(()).unwrap();
@@ -1371,5 +1473,78 @@ mod envoy_conversions {
             let result = route_match.match_request(&req);
             assert!(!result.matched(), "Should not match GET even with matching path");
         }
+
+        #[test]
+        fn test_internal_redirect_policy() {
+            use super::super::super::internal_redirect::InternalRedirectPredicate;
+            use orion_data_plane_api::envoy_data_plane_api::{
+                envoy::{
+                    config::{
+                        core::v3::TypedExtensionConfig,
+                        route::v3::{
+                            route_action::{ClusterSpecifier as EnvoyClusterSpecifier, InternalRedirectAction},
+                            InternalRedirectPolicy as EnvoyInternalRedirectPolicy, RouteAction as EnvoyRouteAction,
+                        },
+                    },
+                    extensions::internal_redirect::allow_listed_routes::v3::AllowListedRoutesConfig,
+                },
+                google::protobuf::{Any, UInt32Value},
+                prost::Message,
+            };
+            let predicate = |type_url: &str, value: Vec<u8>| TypedExtensionConfig {
+                name: "predicate".to_owned(),
+                typed_config: Some(Any { type_url: type_url.to_owned(), value }),
+            };
+            let envoy_action = |internal_redirect_policy, internal_redirect_action| EnvoyRouteAction {
+                cluster_specifier: Some(EnvoyClusterSpecifier::Cluster("cluster".to_owned())),
+                internal_redirect_policy,
+                internal_redirect_action,
+                max_internal_redirects: Some(UInt32Value { value: 3 }),
+                ..Default::default()
+            };
+
+            let allow_listed = AllowListedRoutesConfig { allowed_route_names: vec!["current".to_owned()] };
+            let policy = EnvoyInternalRedirectPolicy {
+                max_internal_redirects: Some(UInt32Value { value: 2 }),
+                redirect_response_codes: vec![301, 302, 404],
+                predicates: vec![
+                    predicate(
+                        "type.googleapis.com/envoy.extensions.internal_redirect.allow_listed_routes.v3.AllowListedRoutesConfig",
+                        allow_listed.encode_to_vec(),
+                    ),
+                    predicate(
+                        "type.googleapis.com/envoy.extensions.internal_redirect.previous_routes.v3.PreviousRoutesConfig",
+                        Vec::new(),
+                    ),
+                ],
+                ..Default::default()
+            };
+            let action = RouteAction::try_from(envoy_action(Some(policy.clone()), 0)).unwrap();
+            let internal_redirect_policy = action.internal_redirect_policy.unwrap();
+            assert_eq!(internal_redirect_policy.max_internal_redirects, 2);
+            assert_eq!(
+                internal_redirect_policy.redirect_response_codes,
+                vec![http::StatusCode::MOVED_PERMANENTLY, http::StatusCode::FOUND],
+                "only redirect codes are kept"
+            );
+            assert_eq!(
+                internal_redirect_policy.predicates,
+                vec![
+                    InternalRedirectPredicate::AllowListedRoutes(vec!["current".into()]),
+                    InternalRedirectPredicate::PreviousRoutes
+                ]
+            );
+
+            let action =
+                RouteAction::try_from(envoy_action(None, InternalRedirectAction::HandleInternalRedirect.into()))
+                    .unwrap();
+            assert_eq!(action.internal_redirect_policy.unwrap().max_internal_redirects, 3);
+            let action = RouteAction::try_from(envoy_action(None, 0)).unwrap();
+            assert_eq!(action.internal_redirect_policy, None);
+
+            let mut unknown = policy;
+            unknown.predicates = vec![predicate("type.googleapis.com/some.Predicate", Vec::new())];
+            assert!(RouteAction::try_from(envoy_action(Some(unknown), 0)).is_err());
+        }
     }
 }
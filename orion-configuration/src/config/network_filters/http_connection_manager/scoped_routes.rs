@@ -0,0 +1,365 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use crate::config::{common::is_default, ConfigSource};
+use compact_str::CompactString;
+use http::{HeaderName, Request};
+use serde::{Deserialize, Serialize};
+
+/// Route configurations each request is routed with one of, selected by the scope key built from the request.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ScopedRoutes {
+    pub name: CompactString,
+    pub scope_key_builder: ScopeKeyBuilder,
+    /// Where the route configurations of the scopes are discovered from over RDS.
+    pub rds_config_source: ConfigSource,
+    pub scopes: ScopesSpecifier,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScopesSpecifier {
+    /// The scopes are configured along with the connection manager.
+    Scopes(Vec<ScopedRouteConfiguration>),
+    /// The scopes are discovered over SRDS, shared by every connection manager discovering them.
+    ScopedRds(ConfigSource),
+}
+
+/// A scope of the route configurations, routing the requests whose scope key is its own.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ScopedRouteConfiguration {
+    pub name: CompactString,
+    /// When set, the route configuration of the scope is only discovered once a request needs it.
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub on_demand: bool,
+    pub route_configuration_name: CompactString,
+    pub key: ScopeKey,
+}
+
+/// The fragments a scope key is made of, in order.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub struct ScopeKey(pub Vec<CompactString>);
+
+/// Builds the scope key of a request, one fragment from each of its headers.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ScopeKeyBuilder {
+    pub fragments: Vec<HeaderValueExtractor>,
+}
+
+impl ScopeKeyBuilder {
+    /// The scope key of a request, `None` if any of its fragments can't be extracted from the request.
+    pub fn scope_key<B>(&self, request: &Request<B>) -> Option<ScopeKey> {
+        self.fragments.iter().map(|fragment| fragment.extract(request)).collect::<Option<Vec<_>>>().map(ScopeKey)
+    }
+}
+
+/// Extracts a fragment of a scope key from the first value of a header, split into elements by a separator.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HeaderValueExtractor {
+    #[serde(with = "http_serde_ext::header_name")]
+    pub name: HeaderName,
+    /// The whole value is a single element if empty.
+    #[serde(skip_serializing_if = "CompactString::is_empty", default)]
+    pub element_separator: CompactString,
+    pub extract: ExtractType,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractType {
+    /// The element at an index.
+    Index(u32),
+    /// The value of the first `<key><separator><value>` element with a key, empty if the element has no separator.
+    Element { separator: CompactString, key: CompactString },
+}
+
+impl HeaderValueExtractor {
+    fn extract<B>(&self, request: &Request<B>) -> Option<CompactString> {
+        let value = request.headers().get(&self.name)?.to_str().ok()?;
+        let mut elements: Box<dyn Iterator<Item = &str>> = if self.element_separator.is_empty() {
+            Box::new(std::iter::once(value))
+        } else {
+            Box::new(value.split(self.element_separator.as_str()))
+        };
+        match &self.extract {
+            ExtractType::Index(index) => elements.nth(usize::try_from(*index).ok()?).map(CompactString::from),
+            ExtractType::Element { separator, key } => {
+                elements.filter(|element| !element.is_empty()).find_map(|element| {
+                    match element.split_once(separator.as_str()) {
+                        Some((element_key, value)) => (element_key == key).then(|| value.into()),
+                        None => (element == key).then(CompactString::default),
+                    }
+                })
+            },
+        }
+    }
+}
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    use super::{
+        ExtractType, HeaderValueExtractor, ScopeKey, ScopeKeyBuilder, ScopedRouteConfiguration, ScopedRoutes,
+        ScopesSpecifier,
+    };
+    use crate::config::{common::*, ConfigSource};
+    use compact_str::CompactString;
+    use http::HeaderName;
+    use orion_data_plane_api::envoy_data_plane_api::envoy::{
+        config::route::v3::{
+            scoped_route_configuration::{
+                key::{fragment::Type as EnvoyFragmentType, Fragment as EnvoyFragment},
+                Key as EnvoyKey,
+            },
+            ScopedRouteConfiguration as EnvoyScopedRouteConfiguration,
+        },
+        extensions::filters::network::http_connection_manager::v3::{
+            scoped_routes::{
+                scope_key_builder::{
+                    fragment_builder::{
+                        header_value_extractor::{ExtractType as EnvoyExtractType, KvElement as EnvoyKvElement},
+                        HeaderValueExtractor as EnvoyHeaderValueExtractor, Type as EnvoyFragmentBuilderType,
+                    },
+                    FragmentBuilder as EnvoyFragmentBuilder,
+                },
+                ConfigSpecifier as EnvoyConfigSpecifier, ScopeKeyBuilder as EnvoyScopeKeyBuilder,
+            },
+            ScopedRds as EnvoyScopedRds, ScopedRouteConfigurationsList as EnvoyScopedRouteConfigurationsList,
+            ScopedRoutes as EnvoyScopedRoutes,
+        },
+    };
+    use std::str::FromStr;
+
+    impl TryFrom<EnvoyScopedRoutes> for ScopedRoutes {
+        type Error = GenericError;
+        fn try_from(value: EnvoyScopedRoutes) -> Result<Self, Self::Error> {
+            let EnvoyScopedRoutes { name, scope_key_builder, rds_config_source, config_specifier } = value;
+            let name: CompactString = required!(name)?.into();
+            (|| -> Result<_, GenericError> {
+                let scope_key_builder = convert_opt!(scope_key_builder)?;
+                let rds_config_source: ConfigSource = convert_opt!(rds_config_source)?;
+                let scopes = match required!(config_specifier)? {
+                    EnvoyConfigSpecifier::ScopedRouteConfigurationsList(EnvoyScopedRouteConfigurationsList {
+                        scoped_route_configurations,
+                    }) => ScopesSpecifier::Scopes(convert_vec!(scoped_route_configurations)?),
+                    EnvoyConfigSpecifier::ScopedRds(EnvoyScopedRds {
+                        scoped_rds_config_source,
+                        srds_resources_locator,
+                    }) => {
+                        unsupported_field!(srds_resources_locator).with_node("scoped_rds")?;
+                        ScopesSpecifier::ScopedRds(convert_opt!(scoped_rds_config_source).with_node("scoped_rds")?)
+                    },
+                };
+                Ok(Self { name: name.clone(), scope_key_builder, rds_config_source, scopes })
+            })()
+            .with_name(name.clone())
+        }
+    }
+
+    impl TryFrom<EnvoyScopeKeyBuilder> for ScopeKeyBuilder {
+        type Error = GenericError;
+        fn try_from(value: EnvoyScopeKeyBuilder) -> Result<Self, Self::Error> {
+            let EnvoyScopeKeyBuilder { fragments } = value;
+            let fragments = required!(fragments)?
+                .into_iter()
+                .map(|EnvoyFragmentBuilder { r#type }| match required!(r#type)? {
+                    EnvoyFragmentBuilderType::HeaderValueExtractor(extractor) => {
+                        HeaderValueExtractor::try_from(extractor).with_node("header_value_extractor")
+                    },
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .with_node("fragments")?;
+            Ok(Self { fragments })
+        }
+    }
+
+    impl TryFrom<EnvoyHeaderValueExtractor> for HeaderValueExtractor {
+        type Error = GenericError;
+        fn try_from(value: EnvoyHeaderValueExtractor) -> Result<Self, Self::Error> {
+            let EnvoyHeaderValueExtractor { name, element_separator, extract_type } = value;
+            let name = required!(name)?;
+            let name = HeaderName::from_str(&name).map_err(|e| {
+                GenericError::from_msg_with_cause(format!("Couldn't convert \"{name}\" to a header name"), e)
+                    .with_node("name")
+            })?;
+            let extract = match required!(extract_type)? {
+                EnvoyExtractType::Index(index) => {
+                    if element_separator.is_empty() && index != 0 {
+                        return Err(GenericError::from_msg("the index has to be 0 without an element separator")
+                            .with_node("index"));
+                    }
+                    ExtractType::Index(index)
+                },
+                EnvoyExtractType::Element(EnvoyKvElement { separator, key }) => {
+                    let separator: CompactString = required!(separator).with_node("element")?.into();
+                    let key: CompactString = required!(key).with_node("element")?.into();
+                    ExtractType::Element { separator, key }
+                },
+            };
+            Ok(Self { name, element_separator: element_separator.into(), extract })
+        }
+    }
+
+    impl TryFrom<EnvoyScopedRouteConfiguration> for ScopedRouteConfiguration {
+        type Error = GenericError;
+        fn try_from(value: EnvoyScopedRouteConfiguration) -> Result<Self, Self::Error> {
+            let EnvoyScopedRouteConfiguration { on_demand, name, route_configuration_name, route_configuration, key } =
+                value;
+            let name: CompactString = required!(name)?.into();
+            (|| -> Result<_, GenericError> {
+                unsupported_field!(route_configuration)?;
+                let route_configuration_name = required!(route_configuration_name)?.into();
+                let key = convert_opt!(key)?;
+                Ok(Self { name: name.clone(), on_demand, route_configuration_name, key })
+            })()
+            .with_name(name.clone())
+        }
+    }
+
+    impl TryFrom<EnvoyKey> for ScopeKey {
+        type Error = GenericError;
+        fn try_from(value: EnvoyKey) -> Result<Self, Self::Error> {
+            let EnvoyKey { fragments } = value;
+            required!(fragments)?
+                .into_iter()
+                .map(|EnvoyFragment { r#type }| match required!(r#type)? {
+                    EnvoyFragmentType::StringKey(key) => Ok(key.into()),
+                })
+                .collect::<Result<Vec<_>, GenericError>>()
+                .with_node("fragments")
+                .map(Self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_data_plane_api::envoy_data_plane_api::envoy::{
+        config::{
+            core::v3::{
+                config_source::ConfigSourceSpecifier as EnvoyConfigSourceSpecifier, AggregatedConfigSource,
+                ConfigSource as EnvoyConfigSource,
+            },
+            route::v3::{
+                scoped_route_configuration::{
+                    key::{fragment::Type as EnvoyFragmentType, Fragment as EnvoyFragment},
+                    Key as EnvoyKey,
+                },
+                ScopedRouteConfiguration as EnvoyScopedRouteConfiguration,
+            },
+        },
+        extensions::filters::network::http_connection_manager::v3::{
+            scoped_routes::{
+                scope_key_builder::{
+                    fragment_builder::{
+                        header_value_extractor::{ExtractType as EnvoyExtractType, KvElement as EnvoyKvElement},
+                        HeaderValueExtractor as EnvoyHeaderValueExtractor, Type as EnvoyFragmentBuilderType,
+                    },
+                    FragmentBuilder as EnvoyFragmentBuilder,
+                },
+                ConfigSpecifier as EnvoyConfigSpecifier, ScopeKeyBuilder as EnvoyScopeKeyBuilder,
+            },
+            ScopedRds as EnvoyScopedRds, ScopedRoutes as EnvoyScopedRoutes,
+        },
+    };
+
+    fn header_value_extractor(name: &str, element_separator: &str, extract: EnvoyExtractType) -> EnvoyFragmentBuilder {
+        EnvoyFragmentBuilder {
+            r#type: Some(EnvoyFragmentBuilderType::HeaderValueExtractor(EnvoyHeaderValueExtractor {
+                name: name.to_owned(),
+                element_separator: element_separator.to_owned(),
+                extract_type: Some(extract),
+            })),
+        }
+    }
+
+    fn key_builder(fragments: Vec<EnvoyFragmentBuilder>) -> ScopeKeyBuilder {
+        EnvoyScopeKeyBuilder { fragments }.try_into().unwrap()
+    }
+
+    fn scope_key(fragments: &[&str]) -> ScopeKey {
+        ScopeKey(fragments.iter().copied().map(CompactString::from).collect())
+    }
+
+    #[test]
+    fn scope_keys_built_from_headers() {
+        let builder = key_builder(vec![
+            header_value_extractor(
+                "x-tenant",
+                ";",
+                EnvoyExtractType::Element(EnvoyKvElement { separator: "=".to_owned(), key: "tenant".to_owned() }),
+            ),
+            header_value_extractor("x-region", ",", EnvoyExtractType::Index(1)),
+        ]);
+        let request = |tenant: &str, region: &str| {
+            Request::get("http://example.com/").header("x-tenant", tenant).header("x-region", region).body(()).unwrap()
+        };
+        assert_eq!(builder.scope_key(&request("a=b;;tenant=acme", "eu,us")), Some(scope_key(&["acme", "us"])));
+        assert_eq!(builder.scope_key(&request("tenant;tenant=acme", "eu,us")), Some(scope_key(&["", "us"])));
+        assert_eq!(builder.scope_key(&request("owner=acme", "eu,us")), None);
+        assert_eq!(builder.scope_key(&request("tenant=acme", "eu")), None);
+        assert_eq!(builder.scope_key(&Request::get("http://example.com/").body(()).unwrap()), None);
+
+        let builder = key_builder(vec![header_value_extractor("x-tenant", "", EnvoyExtractType::Index(0))]);
+        assert_eq!(builder.scope_key(&request("acme;eu", "eu")), Some(scope_key(&["acme;eu"])));
+        let envoy = EnvoyScopeKeyBuilder {
+            fragments: vec![header_value_extractor("x-tenant", "", EnvoyExtractType::Index(1))],
+        };
+        assert!(ScopeKeyBuilder::try_from(envoy).is_err(), "the whole value is the only element");
+    }
+
+    #[test]
+    fn scoped_routes_from_envoy() {
+        let ads = EnvoyConfigSource {
+            config_source_specifier: Some(EnvoyConfigSourceSpecifier::Ads(AggregatedConfigSource {})),
+            ..Default::default()
+        };
+        let envoy = EnvoyScopedRoutes {
+            name: "tenants".to_owned(),
+            scope_key_builder: Some(EnvoyScopeKeyBuilder {
+                fragments: vec![header_value_extractor("x-tenant", "", EnvoyExtractType::Index(0))],
+            }),
+            rds_config_source: Some(ads.clone()),
+            config_specifier: Some(EnvoyConfigSpecifier::ScopedRds(EnvoyScopedRds {
+                scoped_rds_config_source: Some(ads.clone()),
+                srds_resources_locator: String::new(),
+            })),
+        };
+        let scoped_routes = ScopedRoutes::try_from(envoy.clone()).unwrap();
+        assert!(matches!(scoped_routes.scopes, ScopesSpecifier::ScopedRds(_)));
+        let json = serde_json::to_value(&scoped_routes).unwrap();
+        assert_eq!(serde_json::from_value::<ScopedRoutes>(json).unwrap(), scoped_routes);
+
+        let err = ScopedRoutes::try_from(EnvoyScopedRoutes { rds_config_source: None, ..envoy }).unwrap_err();
+        assert!(format!("{err:?}").contains("rds_config_source"), "{err:?}");
+
+        let scope = EnvoyScopedRouteConfiguration {
+            name: "acme".to_owned(),
+            on_demand: true,
+            route_configuration_name: "acme_routes".to_owned(),
+            key: Some(EnvoyKey {
+                fragments: vec![EnvoyFragment { r#type: Some(EnvoyFragmentType::StringKey("acme".to_owned())) }],
+            }),
+            ..Default::default()
+        };
+        let scope = ScopedRouteConfiguration::try_from(scope).unwrap();
+        assert!(scope.on_demand);
+        assert_eq!(scope.route_configuration_name, "acme_routes");
+        assert_eq!(scope.key, scope_key(&["acme"]));
+        assert!(ScopedRouteConfiguration::try_from(EnvoyScopedRouteConfiguration::default()).is_err());
+    }
+}
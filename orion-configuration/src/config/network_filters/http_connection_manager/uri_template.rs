@@ -0,0 +1,354 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Path templates of the `uri_template` match and rewrite extensions.
+//!
+//! A template such as `/v1/{tenant}/items/{id=**}` matches a whole path: `*` matches a single segment, `**` any
+//! number of them and has to come last, and `{name}` or `{name=pattern}` binds what it matches to a variable. A
+//! rewrite such as `/items/{id}?t={tenant}` then builds the new path out of the variables of the template the path
+//! was matched with.
+
+use std::{
+    fmt::Write,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use compact_str::CompactString;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::common::GenericError;
+
+/// The characters of a segment, the ones allowed in a path besides `/`.
+const SEGMENT: &str = "[a-zA-Z0-9-._~%!$&'()+,;:@=]+";
+/// Any number of segments, `/` included.
+const SEGMENTS: &str = "[a-zA-Z0-9-._~%!$&'()+,;:@=/]*";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "CompactString", into = "CompactString")]
+pub struct UriTemplate {
+    template: CompactString,
+    regex: Regex,
+    variables: Vec<CompactString>,
+}
+
+impl UriTemplate {
+    pub fn matches(&self, path: &str) -> bool {
+        self.regex.is_match(path)
+    }
+
+    pub fn variables(&self) -> &[CompactString] {
+        &self.variables
+    }
+}
+
+impl PartialEq for UriTemplate {
+    fn eq(&self, other: &Self) -> bool {
+        self.template == other.template
+    }
+}
+
+impl Eq for UriTemplate {}
+
+impl Hash for UriTemplate {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.template.hash(state);
+    }
+}
+
+impl FromStr for UriTemplate {
+    type Err = GenericError;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| GenericError::from_msg(format!("invalid path template {template}: {reason}"));
+        if !template.starts_with('/') {
+            return Err(invalid("it doesn't start with /"));
+        }
+        let mut pattern = String::from("^");
+        let mut variables: Vec<CompactString> = Vec::new();
+        let mut any_segments = false;
+        let mut rest = template;
+        while !rest.is_empty() {
+            if let Some(variable) = rest.strip_prefix('{') {
+                let (variable, after) = variable.split_once('}').ok_or_else(|| invalid("unclosed {"))?;
+                let (name, operators) = variable.split_once('=').unwrap_or((variable, "*"));
+                if !is_variable_name(name) {
+                    return Err(invalid(&format!("{name} is not a variable name")));
+                }
+                if variables.iter().any(|variable| variable == name) {
+                    return Err(invalid(&format!("{name} is bound twice")));
+                }
+                let _ = write!(pattern, "(?P<{name}>");
+                push_operators(&mut pattern, operators, &mut any_segments).map_err(|reason| invalid(&reason))?;
+                pattern.push(')');
+                variables.push(name.into());
+                rest = after;
+            } else {
+                let end = rest.find('{').unwrap_or(rest.len());
+                push_operators(&mut pattern, &rest[..end], &mut any_segments).map_err(|reason| invalid(&reason))?;
+                rest = &rest[end..];
+            }
+        }
+        pattern.push('$');
+        let regex = Regex::new(&pattern).map_err(|e| GenericError::from_msg_with_cause("invalid path template", e))?;
+        Ok(Self { template: template.into(), regex, variables })
+    }
+}
+
+/// Adds the literals and wildcards of a template to the pattern matching it.
+fn push_operators(pattern: &mut String, operators: &str, any_segments: &mut bool) -> Result<(), String> {
+    let mut rest = operators;
+    while !rest.is_empty() {
+        let (operator, after) = if let Some(after) = rest.strip_prefix("**") {
+            (Some(SEGMENTS), after)
+        } else if let Some(after) = rest.strip_prefix('*') {
+            (Some(SEGMENT), after)
+        } else {
+            (None, rest)
+        };
+        if let Some(operator) = operator {
+            if *any_segments {
+                return Err("nothing but literals may follow **".to_owned());
+            }
+            *any_segments = operator == SEGMENTS;
+            pattern.push_str(operator);
+            rest = after;
+        } else {
+            let end = rest.find(['*', '{', '}']).unwrap_or(rest.len());
+            let literal = &rest[..end];
+            if end == 0 || !literal.chars().all(is_path_char) {
+                return Err(format!("{rest} is not a literal of a path"));
+            }
+            pattern.push_str(&regex::escape(literal));
+            rest = &rest[end..];
+        }
+    }
+    Ok(())
+}
+
+fn is_variable_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-._~%!$&'()+,;:@=/".contains(c)
+}
+
+impl TryFrom<CompactString> for UriTemplate {
+    type Error = GenericError;
+    fn try_from(template: CompactString) -> Result<Self, Self::Error> {
+        template.parse()
+    }
+}
+
+impl From<UriTemplate> for CompactString {
+    fn from(template: UriTemplate) -> Self {
+        template.template
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RewriteSegment {
+    Literal(CompactString),
+    Variable(CompactString),
+}
+
+/// A path built out of the variables of the template the route matched the path with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "SerializedUriTemplateRewrite", into = "SerializedUriTemplateRewrite")]
+pub struct UriTemplateRewrite {
+    path_template_rewrite: CompactString,
+    segments: Box<[RewriteSegment]>,
+    /// The template of the route, only known once the rewrite is part of it.
+    path_template: Option<Box<UriTemplate>>,
+}
+
+impl UriTemplateRewrite {
+    /// Rewrites paths matched with the template of the route, every variable used having to be one of its own.
+    pub fn bind(&mut self, path_template: &UriTemplate) -> Result<(), GenericError> {
+        for segment in &self.segments {
+            if let RewriteSegment::Variable(name) = segment {
+                if !path_template.variables().contains(name) {
+                    return Err(GenericError::from_msg(format!(
+                        "{name} of {} isn't bound by the path template",
+                        self.path_template_rewrite
+                    )));
+                }
+            }
+        }
+        self.path_template = Some(Box::new(path_template.clone()));
+        Ok(())
+    }
+
+    /// The path rewritten, if it matches the template of the route.
+    pub fn apply(&self, path: &str) -> Option<String> {
+        let captures = self.path_template.as_ref()?.regex.captures(path)?;
+        let mut rewritten = String::with_capacity(self.path_template_rewrite.len());
+        for segment in &self.segments {
+            match segment {
+                RewriteSegment::Literal(literal) => rewritten.push_str(literal),
+                RewriteSegment::Variable(name) => {
+                    rewritten.push_str(captures.name(name).map(|value| value.as_str()).unwrap_or_default());
+                },
+            }
+        }
+        Some(rewritten)
+    }
+}
+
+impl FromStr for UriTemplateRewrite {
+    type Err = GenericError;
+
+    fn from_str(rewrite: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |reason: &str| GenericError::from_msg(format!("invalid path template rewrite {rewrite}: {reason}"));
+        if !rewrite.starts_with('/') {
+            return Err(invalid("it doesn't start with /"));
+        }
+        let mut segments = Vec::new();
+        let mut rest = rewrite;
+        while !rest.is_empty() {
+            if let Some(variable) = rest.strip_prefix('{') {
+                let (name, after) = variable.split_once('}').ok_or_else(|| invalid("unclosed {"))?;
+                if !is_variable_name(name) {
+                    return Err(invalid(&format!("{name} is not a variable name")));
+                }
+                segments.push(RewriteSegment::Variable(name.into()));
+                rest = after;
+            } else {
+                let end = rest.find('{').unwrap_or(rest.len());
+                let literal = &rest[..end];
+                if !literal.chars().all(|c| is_path_char(c) || c == '?') {
+                    return Err(invalid(&format!("{literal} is not a literal of a path")));
+                }
+                segments.push(RewriteSegment::Literal(literal.into()));
+                rest = &rest[end..];
+            }
+        }
+        Ok(Self { path_template_rewrite: rewrite.into(), segments: segments.into(), path_template: None })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedUriTemplateRewrite {
+    path_template_rewrite: CompactString,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    path_template: Option<UriTemplate>,
+}
+
+impl TryFrom<SerializedUriTemplateRewrite> for UriTemplateRewrite {
+    type Error = GenericError;
+    fn try_from(value: SerializedUriTemplateRewrite) -> Result<Self, Self::Error> {
+        let SerializedUriTemplateRewrite { path_template_rewrite, path_template } = value;
+        let mut rewrite: Self = path_template_rewrite.parse()?;
+        if let Some(path_template) = path_template {
+            rewrite.bind(&path_template)?;
+        }
+        Ok(rewrite)
+    }
+}
+
+impl From<UriTemplateRewrite> for SerializedUriTemplateRewrite {
+    fn from(value: UriTemplateRewrite) -> Self {
+        Self {
+            path_template_rewrite: value.path_template_rewrite,
+            path_template: value.path_template.map(|template| *template),
+        }
+    }
+}
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    use super::{UriTemplate, UriTemplateRewrite};
+    use crate::config::common::*;
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::{
+            config::core::v3::TypedExtensionConfig as EnvoyTypedExtensionConfig,
+            extensions::path::{
+                r#match::uri_template::v3::UriTemplateMatchConfig as EnvoyUriTemplateMatchConfig,
+                rewrite::uri_template::v3::UriTemplateRewriteConfig as EnvoyUriTemplateRewriteConfig,
+            },
+        },
+        prost::Message,
+    };
+
+    const MATCH_TYPE_URL: &str =
+        "type.googleapis.com/envoy.extensions.path.match.uri_template.v3.UriTemplateMatchConfig";
+    const REWRITE_TYPE_URL: &str =
+        "type.googleapis.com/envoy.extensions.path.rewrite.uri_template.v3.UriTemplateRewriteConfig";
+
+    impl TryFrom<EnvoyTypedExtensionConfig> for UriTemplate {
+        type Error = GenericError;
+        fn try_from(value: EnvoyTypedExtensionConfig) -> Result<Self, Self::Error> {
+            let EnvoyTypedExtensionConfig { name: _, typed_config } = value;
+            let typed_config = required!(typed_config)?;
+            if typed_config.type_url != MATCH_TYPE_URL {
+                return Err(GenericError::unsupported_variant(typed_config.type_url));
+            }
+            let EnvoyUriTemplateMatchConfig { path_template } =
+                EnvoyUriTemplateMatchConfig::decode(typed_config.value.as_slice())
+                    .map_err(|e| GenericError::from_msg_with_cause("failed to decode UriTemplateMatchConfig", e))?;
+            required!(path_template)?.parse().with_node("path_template")
+        }
+    }
+
+    impl TryFrom<EnvoyTypedExtensionConfig> for UriTemplateRewrite {
+        type Error = GenericError;
+        fn try_from(value: EnvoyTypedExtensionConfig) -> Result<Self, Self::Error> {
+            let EnvoyTypedExtensionConfig { name: _, typed_config } = value;
+            let typed_config = required!(typed_config)?;
+            if typed_config.type_url != REWRITE_TYPE_URL {
+                return Err(GenericError::unsupported_variant(typed_config.type_url));
+            }
+            let EnvoyUriTemplateRewriteConfig { path_template_rewrite } =
+                EnvoyUriTemplateRewriteConfig::decode(typed_config.value.as_slice())
+                    .map_err(|e| GenericError::from_msg_with_cause("failed to decode UriTemplateRewriteConfig", e))?;
+            required!(path_template_rewrite)?.parse().with_node("path_template_rewrite")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variables_of_the_template_rewritten_into_the_path() {
+        let template: UriTemplate = "/v1/{tenant}/items/{id=**}".parse().unwrap();
+        assert!(template.matches("/v1/acme/items/a/b"));
+        assert!(!template.matches("/v1/acme/other/a"));
+        assert!(!template.matches("/v1/acme/corp/items/a"));
+
+        let mut rewrite: UriTemplateRewrite = "/items/{id}?t={tenant}".parse().unwrap();
+        assert_eq!(rewrite.apply("/v1/acme/items/a/b"), None, "not bound to a template yet");
+        rewrite.bind(&template).unwrap();
+        assert_eq!(rewrite.apply("/v1/acme/items/a/b").as_deref(), Some("/items/a/b?t=acme"));
+        assert_eq!(rewrite.apply("/v2/acme/items/a"), None);
+
+        let mut unbound: UriTemplateRewrite = "/items/{name}".parse().unwrap();
+        assert!(unbound.bind(&template).is_err());
+
+        let videos: UriTemplate = "/videos/*/{id}/{segment=**}.ts".parse().unwrap();
+        assert!(videos.matches("/videos/hd/42/part/1.ts"));
+        assert!(!videos.matches("/videos/hd/42/part/1.m4s"));
+
+        assert!("/v1/{id=**}/items/*".parse::<UriTemplate>().is_err(), "** comes last");
+        assert!("/v1/{id}/{id}".parse::<UriTemplate>().is_err());
+        assert!("v1/{id}".parse::<UriTemplate>().is_err());
+    }
+}
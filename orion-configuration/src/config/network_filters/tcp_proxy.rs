@@ -20,21 +20,29 @@
 use crate::config::cluster::ClusterSpecifier;
 use serde::{Deserialize, Serialize};
 
-use super::access_log::AccessLog;
+use super::{access_log::AccessLog, http_connection_manager::http_filters::on_demand::OnDemandCds};
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 pub struct TcpProxy {
     pub cluster_specifier: ClusterSpecifier,
     #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
     pub access_log: Vec<AccessLog>,
+    /// Connections to a cluster that isn't loaded wait for it to be discovered, if set.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub on_demand: Option<OnDemandCds>,
 }
 
 #[cfg(feature = "envoy-conversions")]
 mod envoy_conversions {
     #![allow(deprecated)]
     use super::TcpProxy;
-    use crate::config::{common::*, network_filters::access_log::AccessLog};
-    use orion_data_plane_api::envoy_data_plane_api::envoy::extensions::filters::network::tcp_proxy::v3::TcpProxy as EnvoyTcpProxy;
+    use crate::config::{
+        common::*,
+        network_filters::{access_log::AccessLog, http_connection_manager::http_filters::on_demand::on_demand_cds},
+    };
+    use orion_data_plane_api::envoy_data_plane_api::envoy::extensions::filters::network::tcp_proxy::v3::{
+        tcp_proxy::OnDemand as EnvoyOnDemand, TcpProxy as EnvoyTcpProxy,
+    };
 
     impl TryFrom<EnvoyTcpProxy> for TcpProxy {
         type Error = GenericError;
@@ -60,7 +68,7 @@ mod envoy_conversions {
             } = value;
             unsupported_field!(
                 // stat_prefix,
-                on_demand,
+                // on_demand,
                 metadata_match,
                 idle_timeout,
                 downstream_idle_timeout,
@@ -84,7 +92,16 @@ mod envoy_conversions {
             let access_log =
                 access_log.iter().map(|al| AccessLog::try_from(al.clone())).collect::<Result<Vec<_>, _>>()?;
 
-            Ok(Self { cluster_specifier, access_log })
+            // clusters aren't discovered on demand without a config source to discover them from
+            let on_demand = on_demand
+                .filter(|on_demand| on_demand.odcds_config.is_some())
+                .map(|EnvoyOnDemand { odcds_config, resources_locator, timeout }| {
+                    on_demand_cds(odcds_config, resources_locator, timeout)
+                })
+                .transpose()
+                .with_node("on_demand")?;
+
+            Ok(Self { cluster_specifier, access_log, on_demand })
         }
     }
 }
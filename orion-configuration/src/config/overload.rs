@@ -0,0 +1,533 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroU64, time::Duration};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OverloadManager {
+    #[serde(with = "humantime_serde", default = "default_refresh_interval")]
+    pub refresh_interval: Duration,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub resource_monitors: Vec<ResourceMonitor>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub actions: Vec<OverloadAction>,
+}
+
+const fn default_refresh_interval() -> Duration {
+    Duration::from_secs(1)
+}
+
+impl Default for OverloadManager {
+    fn default() -> Self {
+        Self { refresh_interval: default_refresh_interval(), resource_monitors: Vec::new(), actions: Vec::new() }
+    }
+}
+
+impl OverloadManager {
+    pub fn resource_monitor(&self, kind: ResourceMonitorKind) -> Option<&ResourceMonitor> {
+        self.resource_monitors.iter().find(|monitor| monitor.kind() == kind)
+    }
+
+    pub fn action(&self, kind: OverloadActionKind) -> Option<&OverloadAction> {
+        self.actions.iter().find(|action| action.action.kind() == kind)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum ResourceMonitor {
+    /// Resident memory of the process, relative to `max_heap_size_bytes`.
+    FixedHeap { max_heap_size_bytes: NonZeroU64 },
+    /// Active downstream connections across all listeners. Connections above the limit are rejected at accept time.
+    GlobalDownstreamMaxConnections { max_active_downstream_connections: NonZeroU64 },
+    /// Open file descriptors of the process. Defaults to the soft `RLIMIT_NOFILE` when no limit is configured.
+    FileDescriptors {
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        max_file_descriptors: Option<NonZeroU64>,
+    },
+}
+
+impl ResourceMonitor {
+    pub fn kind(&self) -> ResourceMonitorKind {
+        match self {
+            Self::FixedHeap { .. } => ResourceMonitorKind::FixedHeap,
+            Self::GlobalDownstreamMaxConnections { .. } => ResourceMonitorKind::GlobalDownstreamMaxConnections,
+            Self::FileDescriptors { .. } => ResourceMonitorKind::FileDescriptors,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceMonitorKind {
+    FixedHeap,
+    GlobalDownstreamMaxConnections,
+    FileDescriptors,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OverloadAction {
+    #[serde(flatten)]
+    pub action: OverloadActionType,
+    pub triggers: Vec<Trigger>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum OverloadActionType {
+    StopAcceptingConnections,
+    DisableHttpKeepalive,
+    ReduceTimeouts {
+        #[serde(skip_serializing_if = "Vec::is_empty", default)]
+        timer_scale_factors: Vec<ScaleTimer>,
+    },
+    StopAcceptingRequests,
+    ResetHighMemoryStream,
+}
+
+impl OverloadActionType {
+    pub fn kind(&self) -> OverloadActionKind {
+        match self {
+            Self::StopAcceptingConnections => OverloadActionKind::StopAcceptingConnections,
+            Self::DisableHttpKeepalive => OverloadActionKind::DisableHttpKeepalive,
+            Self::ReduceTimeouts { .. } => OverloadActionKind::ReduceTimeouts,
+            Self::StopAcceptingRequests => OverloadActionKind::StopAcceptingRequests,
+            Self::ResetHighMemoryStream => OverloadActionKind::ResetHighMemoryStream,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OverloadActionKind {
+    StopAcceptingConnections,
+    DisableHttpKeepalive,
+    ReduceTimeouts,
+    StopAcceptingRequests,
+    ResetHighMemoryStream,
+}
+
+impl OverloadActionKind {
+    pub const ALL: [Self; 5] = [
+        Self::StopAcceptingConnections,
+        Self::DisableHttpKeepalive,
+        Self::ReduceTimeouts,
+        Self::StopAcceptingRequests,
+        Self::ResetHighMemoryStream,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerType {
+    HttpDownstreamConnectionIdle,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ScaleTimer {
+    pub timer: TimerType,
+    pub overload_adjust: OverloadAdjust,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OverloadAdjust {
+    MinTimeout {
+        #[serde(with = "humantime_serde")]
+        value: Duration,
+    },
+    /// Fraction of the configured timeout, between 0.0 and 1.0.
+    MinScale { value: f64 },
+}
+
+impl OverloadAdjust {
+    /// Scales `timeout` for an action state between 0.0 (inactive) and 1.0 (saturated).
+    pub fn scale(&self, timeout: Duration, state: f64) -> Duration {
+        let min = match self {
+            Self::MinTimeout { value } => (*value).min(timeout),
+            Self::MinScale { value } => timeout.mul_f64(value.clamp(0.0, 1.0)),
+        };
+        timeout.saturating_sub((timeout.saturating_sub(min)).mul_f64(state.clamp(0.0, 1.0)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Trigger {
+    pub resource: ResourceMonitorKind,
+    #[serde(flatten)]
+    pub kind: TriggerKind,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerKind {
+    Threshold { value: f64 },
+    Scaled { scaling_threshold: f64, saturation_threshold: f64 },
+}
+
+impl TriggerKind {
+    /// Maps a resource pressure to an action state between 0.0 (inactive) and 1.0 (saturated).
+    pub fn evaluate(&self, pressure: f64) -> f64 {
+        match *self {
+            Self::Threshold { value } => {
+                if pressure >= value {
+                    1.0
+                } else {
+                    0.0
+                }
+            },
+            Self::Scaled { scaling_threshold, saturation_threshold } => {
+                if pressure >= saturation_threshold {
+                    1.0
+                } else if pressure <= scaling_threshold {
+                    0.0
+                } else {
+                    (pressure - scaling_threshold) / (saturation_threshold - scaling_threshold)
+                }
+            },
+        }
+    }
+}
+
+#[cfg(feature = "envoy-conversions")]
+mod envoy_conversions {
+    #![allow(deprecated)]
+    use std::{collections::HashSet, num::NonZeroU64};
+
+    use super::{
+        OverloadAction, OverloadActionType, OverloadAdjust, OverloadManager, ResourceMonitor, ResourceMonitorKind,
+        ScaleTimer, TimerType, Trigger, TriggerKind,
+    };
+    use crate::config::{common::*, util::duration_from_envoy};
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::{
+            config::overload::v3::{
+                resource_monitor::ConfigType as EnvoyResourceMonitorConfigType,
+                scale_timers_overload_action_config::{
+                    scale_timer::OverloadAdjust as EnvoyOverloadAdjust, ScaleTimer as EnvoyScaleTimer,
+                    TimerType as EnvoyTimerType,
+                },
+                trigger::TriggerOneof as EnvoyTriggerOneof,
+                OverloadAction as EnvoyOverloadAction, OverloadManager as EnvoyOverloadManager,
+                ResourceMonitor as EnvoyResourceMonitor, ScaleTimersOverloadActionConfig as EnvoyScaleTimersConfig,
+                ScaledTrigger as EnvoyScaledTrigger, ThresholdTrigger as EnvoyThresholdTrigger,
+                Trigger as EnvoyTrigger,
+            },
+            extensions::resource_monitors::{
+                downstream_connections::v3::DownstreamConnectionsConfig as EnvoyDownstreamConnectionsConfig,
+                fixed_heap::v3::FixedHeapConfig as EnvoyFixedHeapConfig,
+            },
+            r#type::v3::Percent,
+        },
+        google::protobuf::Any,
+        prost::Message,
+    };
+
+    impl TryFrom<&str> for ResourceMonitorKind {
+        type Error = GenericError;
+        fn try_from(name: &str) -> Result<Self, Self::Error> {
+            match name {
+                "envoy.resource_monitors.fixed_heap" => Ok(Self::FixedHeap),
+                "envoy.resource_monitors.global_downstream_max_connections"
+                | "envoy.resource_monitors.downstream_connections" => Ok(Self::GlobalDownstreamMaxConnections),
+                "orion.resource_monitors.file_descriptors" => Ok(Self::FileDescriptors),
+                _ => Err(GenericError::unsupported_variant(name.to_owned())),
+            }
+        }
+    }
+
+    fn non_zero_u64(value: u64) -> Result<NonZeroU64, GenericError> {
+        NonZeroU64::new(value).ok_or(GenericError::from_msg("value must be greater than zero"))
+    }
+
+    fn decode_any<M: Message + Default>(any: &Any) -> Result<M, GenericError> {
+        M::decode(any.value.as_slice()).map_err(|e| {
+            GenericError::from_msg_with_cause(format!("failed to parse protobuf for \"{}\"", any.type_url), e)
+        })
+    }
+
+    impl TryFrom<EnvoyOverloadManager> for OverloadManager {
+        type Error = GenericError;
+        fn try_from(envoy: EnvoyOverloadManager) -> Result<Self, Self::Error> {
+            let EnvoyOverloadManager {
+                refresh_interval,
+                resource_monitors,
+                actions,
+                loadshed_points,
+                buffer_factory_config,
+            } = envoy;
+            unsupported_field!(loadshed_points, buffer_factory_config)?;
+            let refresh_interval = refresh_interval
+                .map(duration_from_envoy)
+                .transpose()
+                .with_node("refresh_interval")?
+                .unwrap_or_else(super::default_refresh_interval);
+            if refresh_interval.is_zero() {
+                return Err(GenericError::from_msg("refresh_interval must be greater than zero"))
+                    .with_node("refresh_interval");
+            }
+            let resource_monitors: Vec<ResourceMonitor> = convert_vec!(resource_monitors)?;
+            let mut configured = HashSet::new();
+            for (idx, monitor) in resource_monitors.iter().enumerate() {
+                if !configured.insert(monitor.kind()) {
+                    return Err(GenericError::from_msg(format!("duplicate resource monitor {:?}", monitor.kind()))
+                        .with_index(idx)
+                        .with_node("resource_monitors"));
+                }
+            }
+            let actions: Vec<OverloadAction> = convert_vec!(actions)?;
+            let mut seen_actions = HashSet::new();
+            for (idx, action) in actions.iter().enumerate() {
+                (|| -> Result<(), GenericError> {
+                    if !seen_actions.insert(action.action.kind()) {
+                        return Err(GenericError::from_msg(format!(
+                            "duplicate overload action {:?}",
+                            action.action.kind()
+                        )));
+                    }
+                    for trigger in &action.triggers {
+                        if !configured.contains(&trigger.resource) {
+                            return Err(GenericError::from_msg(format!(
+                                "trigger references resource monitor {:?} which is not configured",
+                                trigger.resource
+                            )))
+                            .with_node("triggers");
+                        }
+                    }
+                    Ok(())
+                })()
+                .with_index(idx)
+                .with_node("actions")?;
+            }
+            Ok(Self { refresh_interval, resource_monitors, actions })
+        }
+    }
+
+    impl TryFrom<EnvoyResourceMonitor> for ResourceMonitor {
+        type Error = GenericError;
+        fn try_from(envoy: EnvoyResourceMonitor) -> Result<Self, Self::Error> {
+            let EnvoyResourceMonitor { name, config_type } = envoy;
+            let name = required!(name)?;
+            (|| -> Result<_, GenericError> {
+                let typed_config = config_type.map(|EnvoyResourceMonitorConfigType::TypedConfig(any)| any);
+                match ResourceMonitorKind::try_from(name.as_str())? {
+                    ResourceMonitorKind::FixedHeap => {
+                        let EnvoyFixedHeapConfig { max_heap_size_bytes } =
+                            decode_any(&required!(typed_config)?).with_node("typed_config")?;
+                        let max_heap_size_bytes = non_zero_u64(max_heap_size_bytes).with_node("max_heap_size_bytes")?;
+                        Ok(Self::FixedHeap { max_heap_size_bytes })
+                    },
+                    ResourceMonitorKind::GlobalDownstreamMaxConnections => {
+                        let EnvoyDownstreamConnectionsConfig { max_active_downstream_connections } =
+                            decode_any(&required!(typed_config)?).with_node("typed_config")?;
+                        let max_active_downstream_connections = u64::try_from(max_active_downstream_connections)
+                            .map_err(|_| GenericError::from_msg("value must be positive"))
+                            .and_then(non_zero_u64)
+                            .with_node("max_active_downstream_connections")?;
+                        Ok(Self::GlobalDownstreamMaxConnections { max_active_downstream_connections })
+                    },
+                    ResourceMonitorKind::FileDescriptors => {
+                        unsupported_field!(typed_config)?;
+                        Ok(Self::FileDescriptors { max_file_descriptors: None })
+                    },
+                }
+            })()
+            .with_name(name)
+        }
+    }
+
+    impl TryFrom<EnvoyOverloadAction> for OverloadAction {
+        type Error = GenericError;
+        fn try_from(envoy: EnvoyOverloadAction) -> Result<Self, Self::Error> {
+            let EnvoyOverloadAction { name, triggers, typed_config } = envoy;
+            let name = required!(name)?;
+            (|| -> Result<_, GenericError> {
+                let action = match name.as_str() {
+                    "envoy.overload_actions.stop_accepting_connections" => {
+                        unsupported_field!(typed_config)?;
+                        OverloadActionType::StopAcceptingConnections
+                    },
+                    "envoy.overload_actions.disable_http_keepalive" => {
+                        unsupported_field!(typed_config)?;
+                        OverloadActionType::DisableHttpKeepalive
+                    },
+                    "envoy.overload_actions.stop_accepting_requests" => {
+                        unsupported_field!(typed_config)?;
+                        OverloadActionType::StopAcceptingRequests
+                    },
+                    "envoy.overload_actions.reset_high_memory_stream" => {
+                        unsupported_field!(typed_config)?;
+                        OverloadActionType::ResetHighMemoryStream
+                    },
+                    "envoy.overload_actions.reduce_timeouts" => {
+                        let EnvoyScaleTimersConfig { timer_scale_factors } =
+                            decode_any(&required!(typed_config)?).with_node("typed_config")?;
+                        let timer_scale_factors =
+                            convert_non_empty_vec!(timer_scale_factors).with_node("typed_config")?;
+                        OverloadActionType::ReduceTimeouts { timer_scale_factors }
+                    },
+                    _ => return Err(GenericError::unsupported_variant(name.clone())),
+                };
+                let triggers = convert_non_empty_vec!(triggers)?;
+                Ok(Self { action, triggers })
+            })()
+            .with_name(name)
+        }
+    }
+
+    impl TryFrom<EnvoyTrigger> for Trigger {
+        type Error = GenericError;
+        fn try_from(envoy: EnvoyTrigger) -> Result<Self, Self::Error> {
+            let EnvoyTrigger { name, trigger_oneof } = envoy;
+            let name = required!(name)?;
+            (|| -> Result<_, GenericError> {
+                let resource = ResourceMonitorKind::try_from(name.as_str())?;
+                let kind = match required!(trigger_oneof)? {
+                    EnvoyTriggerOneof::Threshold(EnvoyThresholdTrigger { value }) => {
+                        if !(0.0..=1.0).contains(&value) {
+                            return Err(GenericError::from_msg("threshold must be between 0.0 and 1.0"))
+                                .with_node("threshold");
+                        }
+                        TriggerKind::Threshold { value }
+                    },
+                    EnvoyTriggerOneof::Scaled(EnvoyScaledTrigger { scaling_threshold, saturation_threshold }) => {
+                        if !(0.0..=1.0).contains(&scaling_threshold) || !(0.0..=1.0).contains(&saturation_threshold) {
+                            return Err(GenericError::from_msg("thresholds must be between 0.0 and 1.0"))
+                                .with_node("scaled");
+                        }
+                        if scaling_threshold >= saturation_threshold {
+                            return Err(GenericError::from_msg(
+                                "scaling_threshold must be smaller than saturation_threshold",
+                            ))
+                            .with_node("scaled");
+                        }
+                        TriggerKind::Scaled { scaling_threshold, saturation_threshold }
+                    },
+                };
+                Ok(Self { resource, kind })
+            })()
+            .with_name(name)
+        }
+    }
+
+    impl TryFrom<EnvoyScaleTimer> for ScaleTimer {
+        type Error = GenericError;
+        fn try_from(envoy: EnvoyScaleTimer) -> Result<Self, Self::Error> {
+            let EnvoyScaleTimer { timer, overload_adjust } = envoy;
+            let timer = match EnvoyTimerType::from_i32(timer) {
+                Some(EnvoyTimerType::HttpDownstreamConnectionIdle) => TimerType::HttpDownstreamConnectionIdle,
+                Some(EnvoyTimerType::Unspecified) => {
+                    return Err(GenericError::from_msg("timer type must be specified")).with_node("timer")
+                },
+                Some(other) => {
+                    return Err(GenericError::unsupported_variant(other.as_str_name())).with_node("timer");
+                },
+                None => return Err(GenericError::unsupported_variant("[unknown timer type]")).with_node("timer"),
+            };
+            let overload_adjust = match required!(overload_adjust)? {
+                EnvoyOverloadAdjust::MinTimeout(duration) => {
+                    OverloadAdjust::MinTimeout { value: duration_from_envoy(duration).with_node("min_timeout")? }
+                },
+                EnvoyOverloadAdjust::MinScale(Percent { value }) => {
+                    if !(0.0..=100.0).contains(&value) {
+                        return Err(GenericError::from_msg("min_scale must be between 0 and 100"))
+                            .with_node("min_scale");
+                    }
+                    OverloadAdjust::MinScale { value: value / 100.0 }
+                },
+            };
+            Ok(Self { timer, overload_adjust })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_trigger_interpolates_between_thresholds() {
+        let trigger = TriggerKind::Scaled { scaling_threshold: 0.5, saturation_threshold: 0.9 };
+        assert!(trigger.evaluate(0.4).abs() < f64::EPSILON);
+        assert!((trigger.evaluate(0.7) - 0.5).abs() < 1e-9);
+        assert!((trigger.evaluate(0.95) - 1.0).abs() < f64::EPSILON);
+
+        let trigger = TriggerKind::Threshold { value: 0.8 };
+        assert!(trigger.evaluate(0.79).abs() < f64::EPSILON);
+        assert!((trigger.evaluate(0.8) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn reduce_timeouts_scales_towards_minimum() {
+        let timeout = Duration::from_secs(100);
+        let adjust = OverloadAdjust::MinTimeout { value: Duration::from_secs(10) };
+        assert_eq!(adjust.scale(timeout, 0.0), timeout);
+        assert_eq!(adjust.scale(timeout, 0.5), Duration::from_secs(55));
+        assert_eq!(adjust.scale(timeout, 1.0), Duration::from_secs(10));
+
+        let adjust = OverloadAdjust::MinScale { value: 0.2 };
+        assert_eq!(adjust.scale(timeout, 1.0), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn deserialize_overload_manager() {
+        let yaml = r"
+refresh_interval: 250ms
+resource_monitors:
+  - name: fixed_heap
+    max_heap_size_bytes: 1073741824
+  - name: file_descriptors
+actions:
+  - name: stop_accepting_requests
+    triggers:
+      - resource: fixed_heap
+        type: threshold
+        value: 0.95
+  - name: reduce_timeouts
+    timer_scale_factors:
+      - timer: http_downstream_connection_idle
+        overload_adjust:
+          type: min_timeout
+          value: 2s
+    triggers:
+      - resource: file_descriptors
+        type: scaled
+        scaling_threshold: 0.8
+        saturation_threshold: 0.95
+";
+        let config: OverloadManager = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.refresh_interval, Duration::from_millis(250));
+        assert_eq!(
+            config.resource_monitor(ResourceMonitorKind::FileDescriptors),
+            Some(&ResourceMonitor::FileDescriptors { max_file_descriptors: None })
+        );
+        let reduce_timeouts = config.action(OverloadActionKind::ReduceTimeouts).unwrap();
+        assert_eq!(
+            reduce_timeouts.action,
+            OverloadActionType::ReduceTimeouts {
+                timer_scale_factors: vec![ScaleTimer {
+                    timer: TimerType::HttpDownstreamConnectionIdle,
+                    overload_adjust: OverloadAdjust::MinTimeout { value: Duration::from_secs(2) },
+                }]
+            }
+        );
+        let serialized = serde_yaml::to_string(&config).unwrap();
+        let roundtrip: OverloadManager = serde_yaml::from_str(&serialized).unwrap();
+        assert_eq!(config, roundtrip);
+    }
+}
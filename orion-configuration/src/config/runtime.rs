@@ -21,6 +21,8 @@ use std::{
     fmt::Display,
     num::{NonZeroU32, NonZeroUsize},
     ops::Deref,
+    path::PathBuf,
+    time::Duration,
 };
 use tracing;
 
@@ -43,8 +45,18 @@ pub struct Runtime {
     pub event_interval: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub max_io_events_per_tick: Option<NonZeroUsize>,
+    /// How long listeners keep accepting connections, with keep-alive disabled, when drained gracefully.
+    #[serde(with = "humantime_serde", skip_serializing_if = "Option::is_none", default)]
+    pub drain_time: Option<Duration>,
+    /// Where the last xDS resources accepted are kept, for the proxy to start with them when the management server
+    /// can't be reached.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub xds_cache_dir: Option<PathBuf>,
 }
 
+/// Same as Envoy's `--drain-time-s` default.
+const DEFAULT_DRAIN_TIME: Duration = Duration::from_secs(600);
+
 fn one() -> NonZeroU32 {
     NonZeroU32::MIN
 }
@@ -90,6 +102,19 @@ impl Runtime {
                 .or(opt.max_io_events_per_tick)
                 .or(self.max_io_events_per_tick),
 
+            drain_time: var("ORION_DRAIN_TIME_S")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .or(opt.drain_time_s)
+                .map(Duration::from_secs)
+                .or(self.drain_time),
+
+            xds_cache_dir: var("ORION_XDS_CACHE_DIR")
+                .ok()
+                .map(PathBuf::from)
+                .or_else(|| opt.xds_cache_dir.clone())
+                .or(self.xds_cache_dir),
+
             affinity_strategy: self.affinity_strategy,
         }
     }
@@ -101,6 +126,9 @@ impl Runtime {
     pub fn num_cpus(&self) -> usize {
         self.num_cpus.get()
     }
+    pub fn drain_time(&self) -> Duration {
+        self.drain_time.unwrap_or(DEFAULT_DRAIN_TIME)
+    }
 }
 
 #[allow(clippy::expect_used)]
@@ -218,6 +246,8 @@ impl Default for Runtime {
             event_interval: None,
             max_io_events_per_tick: None,
             affinity_strategy: None,
+            drain_time: None,
+            xds_cache_dir: None,
         }
     }
 }
@@ -340,6 +370,8 @@ mod tests {
             max_io_events_per_tick: None,
             clusters_manager_queue_length: None,
             core_ids: None,
+            drain_time_s: None,
+            xds_cache_dir: None,
         };
         let updated_runtime = runtime.update_from_env_and_options(&options);
 
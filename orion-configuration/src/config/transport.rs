@@ -16,7 +16,7 @@
 //
 
 use super::secret::{TlsCertificate, ValidationContext};
-use crate::config::{cluster, common::*, core::Address};
+use crate::config::{cluster, common::*, core::Address, ConfigSource};
 use base64::Engine as _;
 use compact_str::CompactString;
 use serde::{
@@ -218,15 +218,20 @@ impl Default for TlsParameters {
     }
 }
 
+/// A secret referred to by name, discovered over SDS from its config source or one of the static secrets when it
+/// has none.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SdsConfig {
     pub name: CompactString,
+    #[serde(skip_serializing_if = "Option::is_none", default = "Default::default")]
+    pub config_source: Option<Box<ConfigSource>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum Secrets {
     #[serde(rename = "tls_certificates_sds")]
-    SdsConfig(Vec<CompactString>),
+    SdsConfig(Vec<SdsConfig>),
     #[serde(rename = "tls_certificates")]
     Certificates(Vec<TlsCertificate>),
 }
@@ -246,7 +251,7 @@ impl Secrets {
 
 impl From<Vec<SdsConfig>> for Secrets {
     fn from(value: Vec<SdsConfig>) -> Self {
-        Self::SdsConfig(value.into_iter().map(|x| x.name).collect())
+        Self::SdsConfig(value)
     }
 }
 
@@ -260,7 +265,7 @@ impl From<Vec<TlsCertificate>> for Secrets {
 #[serde(rename_all = "snake_case")]
 pub enum CommonTlsValidationContext {
     #[serde(rename = "validation_context_sds")]
-    SdsConfig(CompactString),
+    SdsConfig(SdsConfig),
     ValidationContext(ValidationContext),
 }
 
@@ -308,7 +313,7 @@ mod envoy_conversions {
         BindDevice, CommonTlsContext, CommonTlsValidationContext, PassTlvMatchType, ProxyProtocolPassThroughTlvs,
         SdsConfig, Secrets, TlsCertificate, TlsParameters, TlsVersion, TlvEntry, UpstreamProxyProtocolConfig,
     };
-    use crate::config::{cluster, common::*};
+    use crate::config::{cluster, common::*, ConfigSource};
     use compact_str::CompactString;
     use orion_data_plane_api::envoy_data_plane_api::{
         envoy::{
@@ -475,10 +480,12 @@ mod envoy_conversions {
     impl TryFrom<EnvoySdsSecretConfig> for SdsConfig {
         type Error = GenericError;
         fn try_from(value: EnvoySdsSecretConfig) -> Result<Self, Self::Error> {
-            let EnvoySdsSecretConfig { name, sds_config: _ } = value;
+            let EnvoySdsSecretConfig { name, sds_config } = value;
             let name: CompactString = required!(name)?.into();
-            //unsupported_field!(sds_config).with_name(name.clone())?;
-            Ok(Self { name })
+            let config_source =
+                sds_config.map(ConfigSource::try_from).transpose().with_node("sds_config").with_name(name.clone())?;
+            let config_source = config_source.map(Box::new);
+            Ok(Self { name, config_source })
         }
     }
 
@@ -490,13 +497,13 @@ mod envoy_conversions {
                     cert_validation_ctx.try_into().map(Self::ValidationContext)
                 },
                 EnvoyValidationContextType::ValidationContextSdsSecretConfig(x) => {
-                    SdsConfig::try_from(x).map(|sds| Self::SdsConfig(sds.name))
+                    SdsConfig::try_from(x).map(Self::SdsConfig)
                 },
                 EnvoyValidationContextType::CombinedValidationContext(combined_context) => {
                     if let Some(context) = combined_context.default_validation_context {
                         context.try_into().map(Self::ValidationContext)
                     } else if let Some(context) = combined_context.validation_context_sds_secret_config {
-                        SdsConfig::try_from(context).map(|sds| Self::SdsConfig(sds.name))
+                        SdsConfig::try_from(context).map(Self::SdsConfig)
                     } else {
                         Err(GenericError::Message(
                             "CombinedValidationContext at least one validation method needs to be set".into(),
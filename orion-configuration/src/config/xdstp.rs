@@ -0,0 +1,196 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! `xdstp://` resource names, which name a resource along with the authority owning it, so that resources owned by
+//! different management servers are discovered from each of them.
+//!
+//! A name is `xdstp://{authority}/{resource type}/{id}?{context parameters}`. The context parameters are part of the
+//! name, and are written in the order of their keys. An id ending with `*` names a glob collection, every resource
+//! whose id is the same up to its last `/` belonging to it.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write},
+    str::FromStr,
+};
+
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use super::GenericError;
+
+const SCHEME: &str = "xdstp://";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "CompactString", into = "CompactString")]
+pub struct XdstpName {
+    pub authority: CompactString,
+    pub resource_type: CompactString,
+    pub id: CompactString,
+    pub context_params: BTreeMap<CompactString, CompactString>,
+}
+
+impl XdstpName {
+    /// Whether a resource name is an `xdstp://` one, any other name being a plain name.
+    pub fn is_xdstp(name: &str) -> bool {
+        name.starts_with(SCHEME)
+    }
+
+    pub fn is_glob(&self) -> bool {
+        self.id == "*" || self.id.ends_with("/*")
+    }
+
+    /// The glob collection this resource belongs to.
+    #[must_use]
+    pub fn collection(&self) -> Self {
+        let id = match self.id.rsplit_once('/') {
+            Some((path, _)) => format!("{path}/*").into(),
+            None => "*".into(),
+        };
+        Self { id, ..self.clone() }
+    }
+
+    /// Adds the context parameters the name doesn't set itself.
+    #[must_use]
+    pub fn with_context_params(mut self, context_params: &BTreeMap<CompactString, CompactString>) -> Self {
+        for (key, value) in context_params {
+            self.context_params.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        self
+    }
+
+    /// Removes the context parameters set to these values.
+    #[must_use]
+    pub fn without_context_params(mut self, context_params: &BTreeMap<CompactString, CompactString>) -> Self {
+        self.context_params.retain(|key, value| context_params.get(key) != Some(value));
+        self
+    }
+}
+
+impl FromStr for XdstpName {
+    type Err = GenericError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| GenericError::from_msg(format!("invalid xdstp:// name {name}: {reason}"));
+        let rest = name.strip_prefix(SCHEME).ok_or_else(|| invalid("not an xdstp:// name"))?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut segments = path.splitn(3, '/');
+        let (Some(authority), Some(resource_type), Some(id)) = (segments.next(), segments.next(), segments.next())
+        else {
+            return Err(invalid("expected xdstp://{authority}/{resource type}/{id}"));
+        };
+        if resource_type.is_empty() || id.is_empty() {
+            return Err(invalid("expected xdstp://{authority}/{resource type}/{id}"));
+        }
+        let context_params = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let (key, value) = param.split_once('=').unwrap_or((param, ""));
+                Ok((
+                    decode(key).ok_or_else(|| invalid("bad escape"))?,
+                    decode(value).ok_or_else(|| invalid("bad escape"))?,
+                ))
+            })
+            .collect::<Result<_, GenericError>>()?;
+        Ok(Self { authority: authority.into(), resource_type: resource_type.into(), id: id.into(), context_params })
+    }
+}
+
+impl TryFrom<CompactString> for XdstpName {
+    type Error = GenericError;
+    fn try_from(name: CompactString) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+impl From<XdstpName> for CompactString {
+    fn from(name: XdstpName) -> Self {
+        name.to_string().into()
+    }
+}
+
+impl Display for XdstpName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{SCHEME}{}/{}/{}", self.authority, self.resource_type, self.id)?;
+        for (i, (key, value)) in self.context_params.iter().enumerate() {
+            write!(f, "{}{}={}", if i == 0 { '?' } else { '&' }, encode(key), encode(value))?;
+        }
+        Ok(())
+    }
+}
+
+/// The characters escaped in context parameters, for them to be told apart from the rest of the name.
+const RESERVED: &[char] = &['%', '&', '=', '?', '#', '/'];
+
+fn encode(param: &str) -> String {
+    param.chars().fold(String::with_capacity(param.len()), |mut encoded, c| {
+        if RESERVED.contains(&c) {
+            let _ = write!(encoded, "%{:02X}", u32::from(c));
+        } else {
+            encoded.push(c);
+        }
+        encoded
+    })
+}
+
+fn decode(param: &str) -> Option<CompactString> {
+    let mut bytes = Vec::with_capacity(param.len());
+    let mut rest = param.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok().map(CompactString::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_written_with_their_context_params_in_order() {
+        let name: XdstpName =
+            "xdstp://control-plane.acme/envoy.config.listener.v3.Listener/edge/public?z=1&a=b%26c".parse().unwrap();
+        assert_eq!(name.authority, "control-plane.acme");
+        assert_eq!(name.resource_type, "envoy.config.listener.v3.Listener");
+        assert_eq!(name.id, "edge/public");
+        assert_eq!(name.context_params.get("a").map(CompactString::as_str), Some("b&c"));
+        assert_eq!(
+            name.to_string(),
+            "xdstp://control-plane.acme/envoy.config.listener.v3.Listener/edge/public?a=b%26c&z=1"
+        );
+        assert!(!name.is_glob());
+        assert_eq!(name.collection().id, "edge/*");
+        assert!(name.collection().is_glob());
+
+        let node_params = BTreeMap::from([("xds.node.id".into(), "proxy-1".into()), ("z".into(), "2".into())]);
+        let effective = name.clone().with_context_params(&node_params);
+        assert_eq!(effective.context_params.len(), 3);
+        assert_eq!(effective.context_params.get("z").map(CompactString::as_str), Some("1"), "set by the name");
+        assert_eq!(effective.without_context_params(&node_params), name);
+
+        assert!("xdstp://authority/envoy.config.listener.v3.Listener".parse::<XdstpName>().is_err());
+        assert!("listener_0".parse::<XdstpName>().is_err());
+    }
+}
@@ -60,6 +60,13 @@ pub struct Options {
         value_delimiter = ',',
     )]
     pub core_ids: Option<Vec<usize>>,
+    #[arg(
+        help = "Time in seconds listeners keep accepting connections when drained gracefully",
+        long = "drain-time-s"
+    )]
+    pub drain_time_s: Option<u64>,
+    #[arg(help = "Directory the last accepted xDS resources are cached in", long = "xds-cache-dir")]
+    pub xds_cache_dir: Option<PathBuf>,
 }
 
 impl Options {
@@ -78,6 +85,8 @@ impl Options {
             max_io_events_per_tick: None,
             clusters_manager_queue_length: None,
             core_ids: None,
+            drain_time_s: None,
+            xds_cache_dir: None,
         }
     }
     pub fn from_path_to_envoy(path: impl Into<PathBuf>) -> Self {
@@ -91,6 +100,8 @@ impl Options {
             max_io_events_per_tick: None,
             clusters_manager_queue_length: None,
             core_ids: None,
+            drain_time_s: None,
+            xds_cache_dir: None,
         }
     }
 }
@@ -46,6 +46,7 @@ mod connect_method_integration_tests {
             ),
             headers: Vec::new(),
             query_parameters: Vec::new(),
+            runtime_fraction: None,
         };
 
         // CONNECT method should match even with path that would otherwise match
@@ -133,6 +134,7 @@ mod connect_method_integration_tests {
             ),
             headers: Vec::new(),
             query_parameters: Vec::new(),
+            runtime_fraction: None,
         };
 
         // GET request should match based on path
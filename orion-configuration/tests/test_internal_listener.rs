@@ -22,7 +22,9 @@ use orion_configuration::config::{
         TransportSocket,
     },
     core::envoy_conversions::{Address, InternalAddress},
-    listener::{FilterChain, FilterChainMatch, InternalListener, Listener, ListenerAddress, MainFilter},
+    listener::{
+        FilterChain, FilterChainMatch, InternalListener, Listener, ListenerAddress, MainFilter, TrafficDirection,
+    },
     network_filters::tcp_proxy::TcpProxy,
     transport::BindDeviceOptions,
 };
@@ -42,9 +44,11 @@ fn test_internal_listener_serialization() {
                 name: "test_filter_chain".into(),
                 tls_config: None,
                 rbac: Vec::new(),
+                connection_limit: None,
                 terminal_filter: MainFilter::Tcp(TcpProxy {
                     cluster_specifier: ClusterSpecifier::Cluster("test_cluster".into()),
                     access_log: Vec::new(),
+                    on_demand: None,
                 }),
             },
         )]),
@@ -53,6 +57,9 @@ fn test_internal_listener_serialization() {
         proxy_protocol_config: None,
         with_tlv_listener_filter: false,
         tlv_listener_filter_config: None,
+        discovered_listener_filters: Vec::new(),
+        connection_limit: None,
+        traffic_direction: TrafficDirection::default(),
     };
 
     let yaml = serde_yaml::to_string(&listener).unwrap();
@@ -69,6 +76,7 @@ fn test_internal_endpoint_serialization() {
 
     let endpoint = LbEndpoint {
         address: Address::Internal(internal_addr),
+        hostname: None,
         health_status: Default::default(),
         load_balancing_weight: NonZeroU32::new(1).unwrap(),
     };
@@ -111,6 +119,13 @@ fn test_bootstrap_extension_serialization() {
         stats_flush_interval: None,
         stats_sinks: Vec::new(),
         bootstrap_extensions: vec![bootstrap_extension],
+        overload_manager: None,
+        max_downstream_connections: None,
+        layered_runtime: None,
+        load_stats_config: None,
+        hds_config: None,
+        config_sources: Vec::new(),
+        default_config_source: None,
     };
 
     let yaml = serde_yaml::to_string(&bootstrap).unwrap();
@@ -135,9 +150,11 @@ fn test_complete_internal_listener_config() {
                 name: "test_filter_chain".into(),
                 tls_config: None,
                 rbac: Vec::new(),
+                connection_limit: None,
                 terminal_filter: MainFilter::Tcp(TcpProxy {
                     cluster_specifier: ClusterSpecifier::Cluster("internal_cluster".into()),
                     access_log: Vec::new(),
+                    on_demand: None,
                 }),
             },
         )]),
@@ -146,6 +163,9 @@ fn test_complete_internal_listener_config() {
         proxy_protocol_config: None,
         with_tlv_listener_filter: false,
         tlv_listener_filter_config: None,
+        discovered_listener_filters: Vec::new(),
+        connection_limit: None,
+        traffic_direction: TrafficDirection::default(),
     };
 
     let internal_addr =
@@ -153,6 +173,7 @@ fn test_complete_internal_listener_config() {
 
     let endpoint = LbEndpoint {
         address: Address::Internal(internal_addr),
+        hostname: None,
         health_status: Default::default(),
         load_balancing_weight: NonZeroU32::new(1).unwrap(),
     };
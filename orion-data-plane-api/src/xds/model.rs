@@ -33,6 +33,9 @@ pub enum TypeUrl {
     RouteConfiguration,
     ClusterLoadAssignment,
     Secret,
+    TypedExtensionConfig,
+    VirtualHost,
+    ScopedRouteConfiguration,
 }
 
 impl fmt::Display for TypeUrl {
@@ -48,6 +51,11 @@ impl fmt::Display for TypeUrl {
                 TypeUrl::ClusterLoadAssignment =>
                     "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment".to_owned(),
                 TypeUrl::Secret => "type.googleapis.com/envoy.extensions.transport_sockets.tls.v3.Secret".to_owned(),
+                TypeUrl::TypedExtensionConfig =>
+                    "type.googleapis.com/envoy.config.core.v3.TypedExtensionConfig".to_owned(),
+                TypeUrl::VirtualHost => "type.googleapis.com/envoy.config.route.v3.VirtualHost".to_owned(),
+                TypeUrl::ScopedRouteConfiguration =>
+                    "type.googleapis.com/envoy.config.route.v3.ScopedRouteConfiguration".to_owned(),
             }
         )
     }
@@ -63,6 +71,11 @@ impl TryFrom<&str> for TypeUrl {
             "type.googleapis.com/envoy.config.route.v3.RouteConfiguration" => Ok(TypeUrl::RouteConfiguration),
             "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment" => Ok(TypeUrl::ClusterLoadAssignment),
             "type.googleapis.com/envoy.extensions.transport_sockets.tls.v3.Secret" => Ok(TypeUrl::Secret),
+            "type.googleapis.com/envoy.config.core.v3.TypedExtensionConfig" => Ok(TypeUrl::TypedExtensionConfig),
+            "type.googleapis.com/envoy.config.route.v3.VirtualHost" => Ok(TypeUrl::VirtualHost),
+            "type.googleapis.com/envoy.config.route.v3.ScopedRouteConfiguration" => {
+                Ok(TypeUrl::ScopedRouteConfiguration)
+            },
             value => Err(XdsError::UnknownResourceType(format!("did not recognise type_url {value}"))),
         }
     }
@@ -95,6 +95,7 @@ fn benchmark_rust_format(c: &mut Criterion) {
         duration: Duration::from_millis(100),
         bytes_received: 128,
         bytes_sent: 256,
+        upstream_request_attempt_count: 1,
         response_flags: ResponseFlags::empty(),
         upstream_failure: None,
         response_code_details: None,
@@ -177,6 +178,7 @@ fn benchmark_log_formatter(c: &mut Criterion) {
         duration: Duration::from_millis(100),
         bytes_received: 128,
         bytes_sent: 256,
+        upstream_request_attempt_count: 1,
         response_flags: ResponseFlags::empty(),
         upstream_failure: None,
         response_code_details: None,
@@ -250,6 +252,7 @@ fn benchmark_request_parts(c: &mut Criterion) {
         duration: Duration::from_millis(100),
         bytes_received: 128,
         bytes_sent: 256,
+        upstream_request_attempt_count: 1,
         response_flags: ResponseFlags::empty(),
         upstream_failure: None,
         response_code_details: None,
@@ -366,6 +369,7 @@ fn benchmark_log_headers(c: &mut Criterion) {
         duration: Duration::from_millis(100),
         bytes_received: 128,
         bytes_sent: 256,
+        upstream_request_attempt_count: 1,
         response_flags: ResponseFlags::empty(),
         upstream_failure: None,
         response_code_details: None,
@@ -59,6 +59,7 @@ fn main() -> Result<(), BoxError> {
         duration: Duration::from_millis(100),
         bytes_received: 128,
         bytes_sent: 256,
+        upstream_request_attempt_count: 1,
         response_flags: ResponseFlags::empty(),
         upstream_failure: None,
         response_code_details: None,
@@ -252,6 +252,7 @@ pub struct FinishContext {
     pub duration: Duration,
     pub bytes_received: u64,
     pub bytes_sent: u64,
+    pub upstream_request_attempt_count: u32,
     pub response_flags: ResponseFlags,
     pub upstream_failure: Option<&'static str>,
     pub response_code_details: Option<&'static str>,
@@ -278,6 +279,10 @@ impl Context for FinishContext {
                 let mut buffer = itoa::Buffer::new();
                 StringType::Smol(SmolStr::new(buffer.format(self.bytes_sent)))
             },
+            Operator::UpstreamRequestAttemptCount => {
+                let mut buffer = itoa::Buffer::new();
+                StringType::Smol(SmolStr::new(buffer.format(self.upstream_request_attempt_count)))
+            },
             Operator::UpstreamTransportFailureReason => {
                 self.upstream_failure.map_or(StringType::None, |msg| StringType::Smol(SmolStr::new_static(msg)))
             },
@@ -133,7 +133,12 @@ static ENVOY_PATTERNS: LazyLock<Trie<u8, (Operator, Category, usize, bool)>> = L
         Category::DOWNSTREAM_CONTEXT
     );
     trie_mapstr!(trie, "DOWNSTREAM_REMOTE_PORT", Operator::DownstreamRemotePort, Category::DOWNSTREAM_CONTEXT);
-    trie_mapstr!(trie, "UPSTREAM_REQUEST_ATTEMPT_COUNT", Operator::UpstreamRequestAttemptCount, Category::UNSUPPORTED);
+    trie_mapstr!(
+        trie,
+        "UPSTREAM_REQUEST_ATTEMPT_COUNT",
+        Operator::UpstreamRequestAttemptCount,
+        Category::FINISH_CONTEXT
+    );
     trie_mapstr!(trie, "UPSTREAM_TLS_CIPHER", Operator::UpstreamTlsCipher, Category::UNSUPPORTED);
     trie_mapstr!(trie, "UPSTREAM_TLS_VERSION", Operator::UpstreamTlsVersion, Category::UNSUPPORTED);
     trie_mapstr!(trie, "UPSTREAM_TLS_SESSION_ID", Operator::UpstreamTlsSessionId, Category::UNSUPPORTED);
@@ -413,6 +413,24 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_upstream_request_attempt_count() {
+        let source = LogFormatter::try_new("%UPSTREAM_REQUEST_ATTEMPT_COUNT%", false).unwrap();
+        let mut formatter = source.local_clone();
+        formatter.with_context(&FinishContext {
+            duration: Duration::from_millis(100),
+            bytes_received: 0,
+            bytes_sent: 0,
+            upstream_request_attempt_count: 3,
+            response_flags: ResponseFlags::empty(),
+            upstream_failure: None,
+            response_code_details: None,
+            connection_termination_details: None,
+        });
+        let actual = format!("{}", &formatter.into_message());
+        assert_eq!(actual, "3");
+    }
+
     #[test]
     fn test_unevaluated_operator() {
         let source = LogFormatter::try_new("%REQ(USER-AGENT)%", false).unwrap();
@@ -444,6 +462,7 @@ mod tests {
             duration: Duration::from_millis(100),
             bytes_received: 128,
             bytes_sent: 256,
+            upstream_request_attempt_count: 1,
             response_flags: ResponseFlags::NO_HEALTHY_UPSTREAM,
             upstream_failure: None,
             response_code_details: None,
@@ -475,6 +494,7 @@ mod tests {
             duration: Duration::from_millis(100),
             bytes_received: 128,
             bytes_sent: 256,
+            upstream_request_attempt_count: 1,
             response_flags: ResponseFlags::NO_HEALTHY_UPSTREAM,
             upstream_failure: None,
             response_code_details: None,
@@ -28,6 +28,10 @@ custom_header!(
     /// The `x-envoy-original-path` header is used to store the original request path
     X_ENVOY_ORIGINAL_PATH, "x-envoy-original-path");
 
+custom_header!(
+    /// The `x-envoy-original-url` header is used to pass the URL a request had before following an internal redirect
+    X_ENVOY_ORIGINAL_URL, "x-envoy-original-url");
+
 custom_header!(
     /// The `B3` header is used for B3 single header tracing
     B3, "b3");
@@ -84,6 +88,10 @@ custom_header!(
     /// The `x-forwarded-for` header is used to identify the originating IP address of a client
     X_FORWARDED_FOR, "x-forwarded-for");
 
+custom_header!(
+    /// The `x-forwarded-host` header is used to identify the host originally requested by a client
+    X_FORWARDED_HOST, "x-forwarded-host");
+
 custom_header!(
     /// The `x-envoy-external-address` header is used to pass the external address in Envoy
     X_ENVOY_EXTERNAL_ADDRESS, "x-envoy-external-address");
@@ -250,6 +250,7 @@ mod tests {
             duration: Duration::from_millis(100),
             bytes_received: 128,
             bytes_sent: 256,
+            upstream_request_attempt_count: 1,
             response_flags: ResponseFlags::NO_HEALTHY_UPSTREAM,
             upstream_failure: None,
             response_code_details: None,
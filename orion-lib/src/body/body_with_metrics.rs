@@ -42,6 +42,12 @@ pub struct MetricsState {
     on_complete: Mutex<Option<MetricsClosure>>,
 }
 
+impl MetricsState {
+    pub fn bytes(&self) -> u64 {
+        self.bytes_counter.load(Ordering::Relaxed)
+    }
+}
+
 /// Pin-project prevents the struct to implement `Drop`.
 /// This workaround allows us to use `Drop` and invoke the closure, if not already executed.
 #[derive(Clone)]
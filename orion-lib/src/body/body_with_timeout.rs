@@ -103,6 +103,14 @@ where
             this.body.poll_frame(cx).map_err(TimeoutBodyError::BodyError)
         }
     }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
 }
 
 /// Error for [`TimeoutBody`].
@@ -34,4 +34,5 @@
 pub mod body_with_metrics;
 pub mod body_with_timeout;
 pub mod poly_body;
+pub mod read_ahead;
 pub(crate) mod response_flags;
@@ -15,12 +15,17 @@
 //
 //
 
-use super::body_with_timeout::{BodyWithTimeout, TimeoutBodyError};
+use super::{
+    body_with_metrics::BodyWithMetrics,
+    body_with_timeout::{BodyWithTimeout, TimeoutBodyError},
+    read_ahead::ReadAhead,
+};
 use bytes::Bytes;
 use http_body_util::{Empty, Full};
 use hyper::body::{Body, Incoming};
 use orion_xds::grpc_deps::{GrpcBody, Status as GrpcError};
 use pin_project::pin_project;
+use std::pin::Pin;
 
 #[pin_project(project = PolyBodyProj)]
 pub enum PolyBody {
@@ -29,6 +34,7 @@ pub enum PolyBody {
     Incoming(#[pin] Incoming),
     Timeout(#[pin] BodyWithTimeout<Incoming>),
     Grpc(#[pin] GrpcBody),
+    ReadAhead(#[pin] Pin<Box<ReadAhead<BodyWithMetrics<PolyBody>>>>),
 }
 
 impl Default for PolyBody {
@@ -46,6 +52,7 @@ impl std::fmt::Debug for PolyBody {
             PolyBody::Incoming(_) => f.write_str("PolyBody::Incoming"),
             PolyBody::Timeout(body) => f.write_str(&format!("PolyBody::Timeout: {body:?}")),
             PolyBody::Grpc(_) => f.write_str("PolyBody::Grpc"),
+            PolyBody::ReadAhead(_) => f.write_str("PolyBody::ReadAhead"),
         }
     }
 }
@@ -88,6 +95,7 @@ impl Body for PolyBody {
             PolyBodyProj::Incoming(i) => i.poll_frame(cx).map_err(Into::into),
             PolyBodyProj::Timeout(t) => t.poll_frame(cx).map_err(Into::into),
             PolyBodyProj::Grpc(g) => g.poll_frame(cx).map_err(Into::into),
+            PolyBodyProj::ReadAhead(r) => r.poll_frame(cx).map_err(Into::into),
         }
     }
 }
@@ -126,3 +134,10 @@ impl From<GrpcBody> for PolyBody {
         PolyBody::Grpc(body)
     }
 }
+
+impl From<ReadAhead<BodyWithMetrics<PolyBody>>> for PolyBody {
+    #[inline]
+    fn from(body: ReadAhead<BodyWithMetrics<PolyBody>>) -> Self {
+        PolyBody::ReadAhead(Box::pin(body))
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! A body the first frames of which were read before it is sent, as when trying to buffer it.
+
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[pin_project]
+pub struct ReadAhead<B> {
+    read: VecDeque<Frame<Bytes>>,
+    #[pin]
+    rest: B,
+}
+
+impl<B> ReadAhead<B> {
+    /// The frames already read, followed by the rest of the body they were read from.
+    pub fn new(read: impl IntoIterator<Item = Frame<Bytes>>, rest: B) -> Self {
+        Self { read: read.into_iter().collect(), rest }
+    }
+}
+
+impl<B> std::fmt::Debug for ReadAhead<B>
+where
+    B: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadAhead").field("read", &self.read.len()).field("rest", &self.rest).finish()
+    }
+}
+
+impl<B: Body<Data = Bytes>> Body for ReadAhead<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        match this.read.pop_front() {
+            Some(frame) => Poll::Ready(Some(Ok(frame))),
+            None => this.rest.poll_frame(cx),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.read.is_empty() && self.rest.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let read: u64 = self.read.iter().filter_map(Frame::data_ref).map(|data| data.len() as u64).sum();
+        let rest = self.rest.size_hint();
+        let mut hint = SizeHint::new();
+        hint.set_lower(rest.lower() + read);
+        if let Some(upper) = rest.upper() {
+            hint.set_upper(upper + read);
+        }
+        hint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::{BodyExt, Full};
+
+    #[tokio::test]
+    async fn frames_read_come_before_the_rest() {
+        let read = [Frame::data(Bytes::from_static(b"first ")), Frame::data(Bytes::from_static(b"second "))];
+        let body = ReadAhead::new(read, Full::new(Bytes::from_static(b"rest")));
+        assert_eq!(body.size_hint().exact(), Some(17));
+        assert_eq!(body.collect().await.unwrap().to_bytes(), Bytes::from_static(b"first second rest"));
+    }
+}
@@ -101,6 +101,13 @@ where
         Err(format!("Can't find endpoint {id:?}").into())
     }
 
+    pub fn health(&self, id: &E) -> Option<HealthStatus>
+    where
+        E: PartialEq,
+    {
+        self.priorities.values().find_map(|priority_info| priority_info.balancer.health(id))
+    }
+
     fn recalculate_priority_load_factors(
         priorities: &HashMap<u32, PriorityInfo<HealthyBalancer<B, E>>>,
     ) -> WeightedRoundRobinBalancer<u32> {
@@ -194,6 +201,7 @@ mod test {
                 name: "Cluster1",
                 endpoints: lb_endpoints,
                 priority,
+                locality: None,
                 healthy_endpoints: healthy,
                 total_endpoints: u32::try_from(len).expect("Too many endpoints"),
                 transport_socket: UpstreamTransportSocketConfigurator::None,
@@ -74,6 +74,14 @@ where
         }
     }
 
+    /// The health the balancer currently assumes for `id`, if it balances over it.
+    pub fn health(&self, id: &E) -> Option<HealthStatus>
+    where
+        E: PartialEq,
+    {
+        self.items.iter().find(|f| id == f.item.as_ref()).map(|f| f.health)
+    }
+
     fn reload(&mut self) {
         self.balancer =
             self.items.iter().filter_map(|item| item.health.is_healthy().then_some(Arc::clone(&item.item))).collect();
@@ -31,7 +31,10 @@ use tracing::{debug, warn};
 
 use super::health::HealthStatus;
 use crate::{
-    clusters::load_assignment::{ClusterLoadAssignmentBuilder, PartialClusterLoadAssignment},
+    clusters::{
+        load_assignment::{ClusterLoadAssignmentBuilder, PartialClusterLoadAssignment},
+        status::HostStatus,
+    },
     secrets::TransportSecret,
     transport::{GrpcService, HttpChannel, TcpChannelConnector, UpstreamTransportSocketConfigurator},
     Error, Result, SecretManager,
@@ -72,6 +75,7 @@ impl TryFrom<(ClusterConfig, &SecretManager)> for PartialClusterType {
                     .with_bind_device_options(bind_device_options)
                     .with_lb_policy(load_balancing_policy)
                     .with_connection_timeout(cluster.connect_timeout)
+                    .with_outlier_detection(cluster.outlier_detection)
                     .with_transport_socket(transport_socket.clone())
                     .with_server_name(server_name)
                     .with_protocol_options(Some(protocol_options))
@@ -87,7 +91,7 @@ impl TryFrom<(ClusterConfig, &SecretManager)> for PartialClusterType {
             },
 
             // at the moment there is no difference for us since both cluster types are using the same resolver
-            ClusterDiscoveryType::StrictDns(cla) => {
+            ClusterDiscoveryType::StrictDns(cla) | ClusterDiscoveryType::LogicalDns(cla) => {
                 let server_name = transport_socket
                     .tls_configurator()
                     .map(|tls_configurator| ServerName::try_from(tls_configurator.sni()))
@@ -99,6 +103,7 @@ impl TryFrom<(ClusterConfig, &SecretManager)> for PartialClusterType {
                     .with_bind_device_options(bind_device_options)
                     .with_lb_policy(load_balancing_policy)
                     .with_connection_timeout(cluster.connect_timeout)
+                    .with_outlier_detection(cluster.outlier_detection)
                     .with_transport_socket(transport_socket.clone())
                     .with_server_name(server_name)
                     .with_protocol_options(Some(protocol_options))
@@ -165,6 +170,7 @@ pub trait ClusterOps {
     fn get_tcp_connection(&mut self, context: RoutingContext) -> Result<TcpChannelConnector>;
     fn get_grpc_connection(&mut self, context: RoutingContext) -> Result<GrpcService>;
     fn get_routing_requirements(&self) -> RoutingRequirement;
+    fn hosts_status(&self) -> Vec<HostStatus>;
 }
 
 #[derive(Clone)]
@@ -233,6 +239,7 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
+    use crate::clusters::load_assignment::EndpointAddressType;
 
     fn check_bind_device(c: &ClusterType, device_name: &str) {
         let expected_bind_device = Some(BindDevice::from_str(device_name).unwrap());
@@ -309,6 +316,66 @@ upstream_bind_config:
         check_bind_device(&c, "virt1");
     }
 
+    #[test]
+    fn endpoints_named_by_their_hostname() {
+        const STATIC: &str = r#"
+name: static
+type: STATIC
+load_assignment:
+  endpoints:
+    - lb_endpoints:
+        - endpoint:
+            hostname: tenant.saas.example.com
+            address:
+              socket_address:
+                address: 192.168.2.10
+                port_value: 80
+        - endpoint:
+            address:
+              socket_address:
+                address: 192.168.2.11
+                port_value: 80
+"#;
+
+        const LOGICAL_DNS: &str = r#"
+name: saas
+type: LOGICAL_DNS
+load_assignment:
+  endpoints:
+    - lb_endpoints:
+        - endpoint:
+            address:
+              socket_address:
+                address: api.saas.example.com
+                port_value: 443
+"#;
+
+        fn hostnames(cluster: &str) -> Vec<Option<String>> {
+            let secrets_man = SecretManager::new();
+            let envoy_cluster: EnvoyCluster = from_yaml(cluster).unwrap();
+            let cluster = ClusterConfig::try_from(envoy_cluster).unwrap();
+            let c = PartialClusterType::try_from((cluster, &secrets_man)).unwrap().build().unwrap();
+            let ClusterType::Static(s) = c else { unreachable!("not a static cluster") };
+            s.load_assignment
+                .endpoints
+                .iter()
+                .flat_map(|lep| &lep.endpoints)
+                .map(|ep| match &ep.address {
+                    EndpointAddressType::Socket(_, http_channel, _) => {
+                        http_channel.hostname.as_ref().map(ToString::to_string)
+                    },
+                    _ => unreachable!("not a socket endpoint"),
+                })
+                .collect()
+        }
+
+        assert_eq!(hostnames(LOGICAL_DNS), vec![Some("api.saas.example.com".to_owned())]);
+        assert_eq!(hostnames(STATIC), vec![Some("tenant.saas.example.com".to_owned()), None]);
+
+        let envoy_cluster: EnvoyCluster = from_yaml(&STATIC.replace("STATIC", "LOGICAL_DNS")).unwrap();
+        assert!(ClusterConfig::try_from(envoy_cluster).is_err(), "logical DNS clusters have a single endpoint");
+    }
+
     #[test]
     fn cluster_2_health_check_not_supported() {
         const CLUSTER: &str = r#"
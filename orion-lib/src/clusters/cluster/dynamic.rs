@@ -29,6 +29,7 @@ use crate::{
     clusters::{
         clusters_manager::{RoutingContext, RoutingRequirement},
         load_assignment::ClusterLoadAssignment,
+        status::HostStatus,
         GrpcService,
     },
     secrets::TransportSecret,
@@ -124,6 +125,10 @@ impl ClusterOps for DynamicCluster {
         }
     }
 
+    fn hosts_status(&self) -> Vec<HostStatus> {
+        self.load_assignment.as_ref().map_or(Vec::new(), ClusterLoadAssignment::hosts_status)
+    }
+
     fn get_http_connection(&mut self, context: RoutingContext) -> Result<HttpChannel> {
         if let Some(cla) = self.load_assignment.as_mut() {
             match context {
@@ -177,12 +182,13 @@ impl TryFrom<&DynamicCluster> for ClusterLoadAssignmentConfig {
                             .ok_or_else(|| format!("Invalid load balancing weight: {}", ep.weight))?;
                         Ok(LbEndpointConfig {
                             address: ep.address.to_address(),
+                            hostname: ep.hostname.clone(),
                             health_status: ep.health_status,
                             load_balancing_weight,
                         })
                     })
                     .collect::<crate::Result<Vec<_>>>()?;
-                Ok(LocalityLbEndpointsConfig { priority: lep.priority, lb_endpoints })
+                Ok(LocalityLbEndpointsConfig { priority: lep.priority, locality: lep.locality.clone(), lb_endpoints })
             })
             .collect::<crate::Result<Vec<_>>>()?;
         Ok(ClusterLoadAssignmentConfig { cluster_name: cluster.name.to_owned(), endpoints })
@@ -15,7 +15,11 @@
 //
 //
 
-use std::{net::SocketAddr, str::FromStr, time::Duration};
+use std::{
+    net::SocketAddr,
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 use lru_time_cache::LruCache;
 
@@ -31,6 +35,7 @@ use crate::{
     clusters::{
         clusters_manager::{RoutingContext, RoutingRequirement},
         health::HealthStatus,
+        status::HostStatus,
     },
     secrets::{TlsConfigurator, TransportSecret, WantsToBuildClient},
     transport::{
@@ -171,6 +176,22 @@ impl ClusterOps for OriginalDstCluster {
         // ORIGINAL_DST clusters do not support health checks
     }
 
+    fn hosts_status(&self) -> Vec<HostStatus> {
+        self.endpoints
+            .peek_iter()
+            .map(|(addr, endpoint)| HostStatus {
+                address: addr.0.to_string(),
+                health: HealthStatus::Healthy,
+                eds_health: HealthStatus::Healthy,
+                weight: 1,
+                priority: 0,
+                locality: None,
+                outlier_ejected: endpoint.http_channel.stats.is_ejected(Instant::now()),
+                stats: endpoint.http_channel.stats.snapshot(),
+            })
+            .collect()
+    }
+
     fn get_http_connection(&mut self, context: RoutingContext) -> Result<HttpChannel> {
         warn!("OriginalDstCluster get HTTP connection for {:?}", context);
         match context {
@@ -525,6 +546,7 @@ mod tests {
             load_balancing_policy: LbPolicy::ClusterProvided,
             http_protocol_options: HttpProtocolOptions::default(),
             health_check: None,
+            outlier_detection: None,
             connect_timeout: None,
         }
     }
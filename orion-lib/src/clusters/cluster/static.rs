@@ -20,6 +20,7 @@ use crate::{
     clusters::{
         clusters_manager::{RoutingContext, RoutingRequirement},
         load_assignment::{ClusterLoadAssignment, ClusterLoadAssignmentBuilder},
+        status::HostStatus,
         GrpcService,
     },
     secrets::TransportSecret,
@@ -96,6 +97,10 @@ impl ClusterOps for StaticCluster {
         self.load_assignment.update_endpoint_health(endpoint, health);
     }
 
+    fn hosts_status(&self) -> Vec<HostStatus> {
+        self.load_assignment.hosts_status()
+    }
+
     fn get_http_connection(&mut self, context: RoutingContext) -> Result<HttpChannel> {
         debug!("{} : Getting connection", self.name);
         match context {
@@ -21,18 +21,22 @@ use super::{
     cluster::ClusterType,
     health::HealthStatus,
     load_assignment::{ClusterLoadAssignmentBuilder, PartialClusterLoadAssignment},
+    status::ClusterStatus,
 };
 use crate::{
-    body::{body_with_metrics::BodyWithMetrics, body_with_timeout::BodyWithTimeout},
+    body::body_with_metrics::BodyWithMetrics,
     clusters::cluster::{ClusterOps, PartialClusterType},
+    layered_runtime,
     secrets::TransportSecret,
     transport::{GrpcService, HttpChannel, TcpChannelConnector},
-    Result,
+    PolyBody, Result,
 };
 use http::{uri::Authority, HeaderName, HeaderValue, Request};
-use hyper::body::Incoming;
 use orion_configuration::config::{
-    cluster::{Cluster as ClusterConfig, ClusterSpecifier as ClusterSpecifierConfig},
+    cluster::{
+        cluster_specifier::WeightedClusterSpecifier, Cluster as ClusterConfig,
+        ClusterSpecifier as ClusterSpecifierConfig,
+    },
     transport::BindDeviceOptions,
 };
 use orion_interner::StringInterner;
@@ -84,23 +88,13 @@ impl std::fmt::Display for RoutingContext<'_> {
     }
 }
 
-impl<'a>
-    TryFrom<(
-        &'a RoutingRequirement,
-        &'a Request<BodyWithMetrics<BodyWithTimeout<Incoming>>>,
-        HashState<'a>,
-        SocketAddr,
-    )> for RoutingContext<'a>
+impl<'a> TryFrom<(&'a RoutingRequirement, &'a Request<BodyWithMetrics<PolyBody>>, HashState<'a>, SocketAddr)>
+    for RoutingContext<'a>
 {
     type Error = String;
 
     fn try_from(
-        value: (
-            &'a RoutingRequirement,
-            &'a Request<BodyWithMetrics<BodyWithTimeout<Incoming>>>,
-            HashState<'a>,
-            SocketAddr,
-        ),
+        value: (&'a RoutingRequirement, &'a Request<BodyWithMetrics<PolyBody>>, HashState<'a>, SocketAddr),
     ) -> std::result::Result<Self, Self::Error> {
         let (routing_requirement, request, hash_state, original_destination_address) = value;
         match routing_requirement {
@@ -153,13 +147,29 @@ thread_local! {
 pub fn resolve_cluster(selector: &ClusterSpecifierConfig) -> Option<ClusterID> {
     match selector {
         ClusterSpecifierConfig::Cluster(cluster_name) => Some(cluster_name.to_static_str()),
-        ClusterSpecifierConfig::WeightedCluster(weighted_clusters) => weighted_clusters
-            .choose_weighted(&mut rand::rng(), |cluster| u32::from(cluster.weight))
-            .ok()
-            .map(|cluster| cluster.cluster.to_static_str()),
+        ClusterSpecifierConfig::WeightedCluster(weighted_clusters) => {
+            choose_weighted_cluster(weighted_clusters, |cluster| {
+                let weight = u64::from(u32::from(cluster.weight));
+                cluster.runtime_key.as_ref().map_or(weight, |key| layered_runtime::get_integer(key, weight))
+            })
+            .map(|cluster| cluster.cluster.to_static_str())
+        },
     }
 }
 
+/// Picks a cluster by its weight, falling back to the configured weights when the runtime overrides leave every
+/// cluster with a zero weight.
+fn choose_weighted_cluster(
+    weighted_clusters: &[WeightedClusterSpecifier],
+    weight: impl Fn(&WeightedClusterSpecifier) -> u64,
+) -> Option<&WeightedClusterSpecifier> {
+    let mut rng = rand::rng();
+    weighted_clusters
+        .choose_weighted(&mut rng, weight)
+        .or_else(|_| weighted_clusters.choose_weighted(&mut rng, |cluster| u32::from(cluster.weight)))
+        .ok()
+}
+
 pub fn get_cluster_routing_requirements(cluster_id: ClusterID) -> RoutingRequirement {
     with_cluster(cluster_id, |cluster| Ok(cluster.get_routing_requirements())).unwrap_or(RoutingRequirement::None)
 }
@@ -175,6 +185,7 @@ pub fn change_cluster_load_assignment(name: &str, cla: &PartialClusterLoadAssign
                         .with_cluster_name(dynamic_cluster.name)
                         .with_bind_device_options(dynamic_cluster.bind_device_options.clone())
                         .with_lb_policy(dynamic_cluster.load_balancing_policy)
+                        .with_outlier_detection(dynamic_cluster.config.outlier_detection)
                         .prepare();
                     cla.build().map(|cla| dynamic_cluster.change_load_assignment(Some(cla)))?;
                     Ok(cluster.clone())
@@ -188,6 +199,7 @@ pub fn change_cluster_load_assignment(name: &str, cla: &PartialClusterLoadAssign
                         .with_cluster_name(static_cluster.name)
                         .with_bind_device_options(BindDeviceOptions::default())
                         .with_lb_policy(orion_configuration::config::cluster::LbPolicy::RoundRobin)
+                        .with_outlier_detection(static_cluster.config.outlier_detection)
                         .prepare();
                     cla.build().map(|cla|static_cluster.change_load_assignment(cla))?;
                     Ok(cluster.clone())
@@ -285,6 +297,19 @@ pub fn get_all_clusters() -> Vec<ClusterConfig> {
     CLUSTERS_MAP.get_clone().0.values().by_ref().filter_map(|cluster| ClusterConfig::try_from(cluster).ok()).collect()
 }
 
+pub fn get_clusters_status() -> Vec<ClusterStatus> {
+    CLUSTERS_MAP
+        .get_clone()
+        .0
+        .values()
+        .map(|cluster| ClusterStatus { name: cluster.get_name(), hosts: cluster.hosts_status() })
+        .collect()
+}
+
+pub fn has_cluster(cluster_id: &str) -> bool {
+    CLUSTERS_MAP_CACHE.with_borrow_mut(|watcher| watcher.cached_or_latest().contains_key(cluster_id))
+}
+
 pub fn get_http_connection(cluster_id: ClusterID, context: RoutingContext) -> Result<HttpChannel> {
     with_cluster(cluster_id, |cluster| cluster.get_http_connection(context))
 }
@@ -321,3 +346,25 @@ where
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    #[test]
+    fn configured_weights_are_used_when_the_runtime_zeroes_them_all() {
+        let cluster = |name: &str, weight| WeightedClusterSpecifier {
+            cluster: name.into(),
+            weight: NonZeroU32::new(weight).unwrap(),
+            runtime_key: Some(format!("{name}.weight").into()),
+        };
+        let clusters = [cluster("zero_weight_a", 1), cluster("zero_weight_b", 1)];
+        assert!(choose_weighted_cluster(&clusters, |_| 0).is_some());
+
+        let overridden = |cluster: &WeightedClusterSpecifier| u64::from(cluster.cluster == "zero_weight_b");
+        for _ in 0..10 {
+            assert_eq!(choose_weighted_cluster(&clusters, overridden).unwrap().cluster, "zero_weight_b");
+        }
+    }
+}
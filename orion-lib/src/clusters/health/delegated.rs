@@ -0,0 +1,206 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Health checks delegated to the proxy by a health discovery service (HDS) server.
+//!
+//! The endpoints assigned are checked with the same checkers as the clusters routed to, but they belong to clusters
+//! of their own, never routed to, whose health is only reported back to the server.
+
+use http::uri::Authority;
+use orion_configuration::config::cluster::{Cluster as ClusterConfig, HealthStatus};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::{
+    checkers::EndpointHealthChecker,
+    manager::{spawn_checkers, EndpointChannels},
+    EndpointHealthUpdate,
+};
+use crate::{
+    clusters::{
+        cluster::{ClusterOps, ClusterType, PartialClusterType},
+        status::ClusterStatus,
+    },
+    transport::{GrpcService, HttpChannel, TcpChannelConnector},
+    Result, SecretManager,
+};
+
+impl EndpointChannels for ClusterType {
+    fn http_channels(&mut self) -> Result<Vec<(Authority, HttpChannel)>> {
+        Ok(self.all_http_channels())
+    }
+
+    fn tcp_channels(&mut self) -> Result<Vec<(Authority, TcpChannelConnector)>> {
+        Ok(self.all_tcp_channels())
+    }
+
+    fn grpc_channels(&mut self) -> Result<Vec<Result<(Authority, GrpcService)>>> {
+        Ok(self.all_grpc_channels())
+    }
+}
+
+struct DelegatedCluster {
+    cluster: ClusterType,
+    checkers: Vec<EndpointHealthChecker>,
+}
+
+pub struct DelegatedHealthChecks {
+    clusters: Vec<DelegatedCluster>,
+    updates: mpsc::Receiver<EndpointHealthUpdate>,
+}
+
+impl Default for DelegatedHealthChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DelegatedHealthChecks {
+    pub fn new() -> Self {
+        Self { clusters: Vec::new(), updates: mpsc::channel(1).1 }
+    }
+
+    /// Health checks the endpoints of these clusters, instead of those assigned before. The endpoints are unhealthy
+    /// until they are checked.
+    pub fn assign(&mut self, clusters: Vec<ClusterConfig>) {
+        self.stop();
+        // a channel of its own, for the health of the endpoints assigned before not to be taken for the new ones
+        let (updates_sender, updates) = mpsc::channel(1000);
+        self.updates = updates;
+        let secrets = SecretManager::new();
+        for config in clusters {
+            let name = config.name.clone();
+            let health_check = config.health_check.clone();
+            let cluster = PartialClusterType::try_from((config, &secrets)).and_then(PartialClusterType::build);
+            let mut cluster = match cluster {
+                Ok(cluster) => cluster,
+                Err(err) => {
+                    warn!("Could not health check the endpoints of cluster {name}: {err}");
+                    continue;
+                },
+            };
+            let checkers = match health_check {
+                Some(health_check) => {
+                    for (endpoint, _) in cluster.all_tcp_channels() {
+                        cluster.update_health(&endpoint, HealthStatus::Unhealthy);
+                    }
+                    let cluster_name = cluster.get_name();
+                    spawn_checkers(cluster_name, health_check, &mut cluster, &updates_sender)
+                },
+                None => Vec::new(),
+            };
+            self.clusters.push(DelegatedCluster { cluster, checkers });
+        }
+    }
+
+    /// The health of the endpoints assigned, as last checked.
+    pub fn report(&mut self) -> Vec<ClusterStatus> {
+        while let Ok(EndpointHealthUpdate { endpoint, health, .. }) = self.updates.try_recv() {
+            if let Some(DelegatedCluster { cluster, .. }) =
+                self.clusters.iter_mut().find(|delegated| delegated.cluster.get_name() == endpoint.cluster)
+            {
+                cluster.update_health(&endpoint.endpoint, health);
+            }
+        }
+        self.clusters
+            .iter()
+            .map(|DelegatedCluster { cluster, .. }| ClusterStatus {
+                name: cluster.get_name(),
+                hosts: cluster.hosts_status(),
+            })
+            .collect()
+    }
+
+    /// Stops the checkers of the endpoints assigned, in the background for the new ones not to wait for them.
+    fn stop(&mut self) {
+        let checkers: Vec<_> = self.clusters.drain(..).flat_map(|delegated| delegated.checkers).collect();
+        if !checkers.is_empty() {
+            tokio::spawn(async move {
+                for checker in checkers {
+                    checker.stop().await;
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use orion_data_plane_api::{
+        decode::from_yaml, envoy_data_plane_api::envoy::config::cluster::v3::Cluster as EnvoyCluster,
+    };
+
+    use super::*;
+
+    fn cluster(port: u16) -> ClusterConfig {
+        let cluster = format!(
+            r#"
+name: shared_backends
+type: STATIC
+health_checks:
+- timeout: 1s
+  interval: 0.05s
+  healthy_threshold: 1
+  unhealthy_threshold: 1
+  tcp_health_check: {{}}
+load_assignment:
+  endpoints:
+  - lb_endpoints:
+    - endpoint:
+        address:
+          socket_address:
+            address: 127.0.0.1
+            port_value: {port}
+"#
+        );
+        let envoy_cluster: EnvoyCluster = from_yaml(&cluster).unwrap();
+        ClusterConfig::try_from(envoy_cluster).unwrap()
+    }
+
+    #[tokio::test]
+    async fn endpoints_assigned_are_unhealthy_until_checked() {
+        let backend = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = backend.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = backend.accept().await {
+                drop(stream);
+            }
+        });
+
+        let mut health_checks = DelegatedHealthChecks::new();
+        health_checks.assign(vec![cluster(port)]);
+        let report = health_checks.report();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].name, "shared_backends");
+        assert_eq!(report[0].hosts[0].health, HealthStatus::Unhealthy);
+
+        let mut health = HealthStatus::Unhealthy;
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            health = health_checks.report()[0].hosts[0].health;
+            if health == HealthStatus::Healthy {
+                break;
+            }
+        }
+        assert_eq!(health, HealthStatus::Healthy);
+
+        health_checks.assign(Vec::new());
+        assert!(health_checks.report().is_empty());
+    }
+}
@@ -17,13 +17,18 @@
 
 use std::collections::HashMap;
 
+use http::uri::Authority;
 use orion_configuration::config::cluster::{health_check::HealthCheckProtocol, HealthCheck};
 use tokio::sync::mpsc;
 
-use crate::clusters::{
-    cluster::{ClusterOps, ClusterType},
-    clusters_manager,
-    health::{checkers::EndpointHealthChecker, EndpointHealthUpdate},
+use crate::{
+    clusters::{
+        cluster::{ClusterOps, ClusterType},
+        clusters_manager,
+        health::{checkers::EndpointHealthChecker, EndpointHealthUpdate},
+    },
+    transport::{GrpcService, HttpChannel, TcpChannelConnector},
+    Result,
 };
 
 use super::EndpointId;
@@ -49,83 +54,13 @@ impl HealthCheckManager {
         let cluster_name = cluster_config.get_name();
         self.stop_cluster(cluster_name).await;
         if let Some(health_check_config) = cluster_config.into_health_check() {
-            let HealthCheck { cluster: cluster_config, protocol } = health_check_config;
-
-            let checkers = self.checkers.entry(cluster_name.to_owned()).or_default();
-
-            match protocol {
-                HealthCheckProtocol::Http(http_config) => {
-                    let Ok(endpoints) = clusters_manager::all_http_connections(cluster_name) else {
-                        return;
-                    };
-
-                    for (authority, channel) in endpoints {
-                        let endpoint_id = EndpointId { cluster: cluster_name.to_owned(), endpoint: authority };
-
-                        let new_checker = EndpointHealthChecker::try_new_http(
-                            endpoint_id.clone(),
-                            cluster_config.clone(),
-                            http_config.clone(),
-                            channel,
-                            self.updates_from_checkers_sender.clone(),
-                        );
-
-                        match new_checker {
-                            Ok(checker) => {
-                                checkers.push(checker);
-                            },
-                            Err(err) => tracing::warn!(
-                                "Could not start new HTTP health checker for endpoint {} in cluster {}: {}",
-                                endpoint_id.endpoint,
-                                endpoint_id.cluster,
-                                err
-                            ),
-                        }
-                    }
-                },
-                HealthCheckProtocol::Tcp(tcp_config) => {
-                    let Ok(endpoints) = clusters_manager::all_tcp_connections(cluster_name) else {
-                        return;
-                    };
-
-                    for (authority, channel) in endpoints {
-                        let endpoint_id = EndpointId { cluster: cluster_name.to_owned(), endpoint: authority };
-
-                        checkers.push(EndpointHealthChecker::new_tcp(
-                            endpoint_id.clone(),
-                            cluster_config.clone(),
-                            tcp_config.clone(),
-                            channel,
-                            self.updates_from_checkers_sender.clone(),
-                        ));
-                    }
-                },
-                HealthCheckProtocol::Grpc(grpc_config) => {
-                    let Ok(endpoints) = clusters_manager::all_grpc_connections(cluster_name) else {
-                        return;
-                    };
-
-                    for endpoint_result in endpoints {
-                        let (endpoint, channel) = match endpoint_result {
-                            Ok(result) => result,
-                            Err(err) => {
-                                tracing::error!("Failed to obtain gRPC client in cluster {}: {}", cluster_name, err);
-                                continue;
-                            },
-                        };
-
-                        let endpoint_id = EndpointId { cluster: cluster_name.to_owned(), endpoint };
-
-                        checkers.push(EndpointHealthChecker::new_grpc(
-                            endpoint_id.clone(),
-                            cluster_config.clone(),
-                            grpc_config.clone(),
-                            channel,
-                            self.updates_from_checkers_sender.clone(),
-                        ));
-                    }
-                },
-            }
+            let checkers = spawn_checkers(
+                cluster_name,
+                health_check_config,
+                &mut RegisteredCluster(cluster_name),
+                &self.updates_from_checkers_sender,
+            );
+            self.checkers.insert(cluster_name.to_owned(), checkers);
         }
     }
 
@@ -138,3 +73,115 @@ impl HealthCheckManager {
         }
     }
 }
+
+/// The channels to the endpoints of a cluster, its health checkers connect with.
+pub(super) trait EndpointChannels {
+    fn http_channels(&mut self) -> Result<Vec<(Authority, HttpChannel)>>;
+    fn tcp_channels(&mut self) -> Result<Vec<(Authority, TcpChannelConnector)>>;
+    fn grpc_channels(&mut self) -> Result<Vec<Result<(Authority, GrpcService)>>>;
+}
+
+/// The channels of a cluster routed to, from the clusters manager.
+struct RegisteredCluster(&'static str);
+
+impl EndpointChannels for RegisteredCluster {
+    fn http_channels(&mut self) -> Result<Vec<(Authority, HttpChannel)>> {
+        clusters_manager::all_http_connections(self.0)
+    }
+
+    fn tcp_channels(&mut self) -> Result<Vec<(Authority, TcpChannelConnector)>> {
+        clusters_manager::all_tcp_connections(self.0)
+    }
+
+    fn grpc_channels(&mut self) -> Result<Vec<Result<(Authority, GrpcService)>>> {
+        clusters_manager::all_grpc_connections(self.0)
+    }
+}
+
+/// Starts a health checker for every endpoint of a cluster, sending the health of the endpoints to `sender`.
+pub(super) fn spawn_checkers(
+    cluster_name: &str,
+    health_check_config: HealthCheck,
+    channels: &mut impl EndpointChannels,
+    sender: &mpsc::Sender<EndpointHealthUpdate>,
+) -> Vec<EndpointHealthChecker> {
+    let HealthCheck { cluster: cluster_config, protocol } = health_check_config;
+
+    let mut checkers = Vec::new();
+
+    match protocol {
+        HealthCheckProtocol::Http(http_config) => {
+            let Ok(endpoints) = channels.http_channels() else {
+                return checkers;
+            };
+
+            for (authority, channel) in endpoints {
+                let endpoint_id = EndpointId { cluster: cluster_name.to_owned(), endpoint: authority };
+
+                let new_checker = EndpointHealthChecker::try_new_http(
+                    endpoint_id.clone(),
+                    cluster_config.clone(),
+                    http_config.clone(),
+                    channel,
+                    sender.clone(),
+                );
+
+                match new_checker {
+                    Ok(checker) => {
+                        checkers.push(checker);
+                    },
+                    Err(err) => tracing::warn!(
+                        "Could not start new HTTP health checker for endpoint {} in cluster {}: {}",
+                        endpoint_id.endpoint,
+                        endpoint_id.cluster,
+                        err
+                    ),
+                }
+            }
+        },
+        HealthCheckProtocol::Tcp(tcp_config) => {
+            let Ok(endpoints) = channels.tcp_channels() else {
+                return checkers;
+            };
+
+            for (authority, channel) in endpoints {
+                let endpoint_id = EndpointId { cluster: cluster_name.to_owned(), endpoint: authority };
+
+                checkers.push(EndpointHealthChecker::new_tcp(
+                    endpoint_id.clone(),
+                    cluster_config.clone(),
+                    tcp_config.clone(),
+                    channel,
+                    sender.clone(),
+                ));
+            }
+        },
+        HealthCheckProtocol::Grpc(grpc_config) => {
+            let Ok(endpoints) = channels.grpc_channels() else {
+                return checkers;
+            };
+
+            for endpoint_result in endpoints {
+                let (endpoint, channel) = match endpoint_result {
+                    Ok(result) => result,
+                    Err(err) => {
+                        tracing::error!("Failed to obtain gRPC client in cluster {}: {}", cluster_name, err);
+                        continue;
+                    },
+                };
+
+                let endpoint_id = EndpointId { cluster: cluster_name.to_owned(), endpoint };
+
+                checkers.push(EndpointHealthChecker::new_grpc(
+                    endpoint_id.clone(),
+                    cluster_config.clone(),
+                    grpc_config.clone(),
+                    channel,
+                    sender.clone(),
+                ));
+            }
+        },
+    }
+
+    checkers
+}
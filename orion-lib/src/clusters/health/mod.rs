@@ -17,10 +17,12 @@
 
 mod checkers;
 mod counter;
+mod delegated;
 mod manager;
 
 use http::uri::Authority;
 
+pub use delegated::DelegatedHealthChecks;
 pub use manager::HealthCheckManager;
 pub use orion_configuration::config::cluster::HealthStatus;
 
@@ -15,7 +15,12 @@
 //
 //
 
-use std::{fmt::Display, sync::Arc, time::Duration};
+use std::{
+    fmt::Display,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use compact_str::CompactString;
 use http::uri::Authority;
@@ -23,8 +28,8 @@ use http::uri::Authority;
 use orion_configuration::config::{
     cluster::{
         ClusterLoadAssignment as ClusterLoadAssignmentConfig, HealthStatus, HttpProtocolOptions,
-        InternalEndpointAddress, LbEndpoint as LbEndpointConfig, LbPolicy,
-        LocalityLbEndpoints as LocalityLbEndpointsConfig,
+        InternalEndpointAddress, LbEndpoint as LbEndpointConfig, LbPolicy, Locality,
+        LocalityLbEndpoints as LocalityLbEndpointsConfig, OutlierDetection,
     },
     core::envoy_conversions::{Address, InternalAddress},
     transport::BindDeviceOptions,
@@ -40,6 +45,7 @@ use super::{
         EndpointWithLoad, WeightedEndpoint,
     },
     health::{EndpointHealth, ValueUpdated},
+    status::HostStatus,
 };
 use crate::{
     transport::{
@@ -54,6 +60,7 @@ static DUMMY_AUTHORITY: std::sync::OnceLock<Authority> = std::sync::OnceLock::ne
 pub struct LbEndpoint {
     pub name: CompactString,
     pub address: EndpointAddressType,
+    pub hostname: Option<CompactString>,
     pub bind_device_options: BindDeviceOptions,
 
     pub weight: u32,
@@ -269,6 +276,7 @@ impl LbEndpoint {
 #[derive(Debug, Clone)]
 pub struct PartialLbEndpoint {
     pub address: Address,
+    pub hostname: Option<CompactString>,
     pub bind_device_options: BindDeviceOptions,
     pub weight: u32,
     pub health_status: HealthStatus,
@@ -278,6 +286,7 @@ impl PartialLbEndpoint {
     fn new(value: &LbEndpoint) -> Self {
         PartialLbEndpoint {
             address: value.address.to_address(),
+            hostname: value.hostname.clone(),
             bind_device_options: value.bind_device_options.clone(),
             weight: value.weight,
             health_status: value.health_status,
@@ -318,15 +327,22 @@ impl LbEndpointBuilder {
     pub fn build(self) -> Result<Arc<LbEndpoint>> {
         let cluster_name = self.cluster_name;
 
-        let PartialLbEndpoint { ref address, bind_device_options, weight, health_status } = self.endpoint;
+        let PartialLbEndpoint { ref address, hostname, bind_device_options, weight, health_status } = self.endpoint;
 
         let address = match address {
-            Address::Socket(hostname, port) => {
-                let authority = http::uri::Authority::try_from(format!("{hostname}:{port}"))?;
+            Address::Socket(host, port) => {
+                let authority = http::uri::Authority::try_from(format!("{host}:{port}"))?;
+                // the endpoints of DNS clusters are named by their hostname, unless one is configured
+                let auto_host = match &hostname {
+                    Some(hostname) => Some(http::uri::Authority::try_from(hostname.as_str())?),
+                    None if host.parse::<IpAddr>().is_err() => Some(http::uri::Authority::try_from(host.as_str())?),
+                    None => None,
+                };
                 let mut builder = HttpChannelBuilder::new(bind_device_options.clone().clone())
                     .with_timeout(self.connect_timeout)
                     .with_address(address.clone())
                     .with_authority(authority.clone())
+                    .with_hostname(auto_host)
                     .with_cluster_name(cluster_name);
 
                 // Configure TLS if needed
@@ -385,6 +401,7 @@ impl LbEndpointBuilder {
         Ok(Arc::new(LbEndpoint {
             name: cluster_name.into(),
             address,
+            hostname,
             bind_device_options: bind_device_options.clone(),
             weight,
             health_status,
@@ -398,9 +415,16 @@ impl TryFrom<LbEndpointConfig> for PartialLbEndpoint {
     fn try_from(lb_endpoint: LbEndpointConfig) -> Result<Self> {
         let health_status = lb_endpoint.health_status;
         let address = lb_endpoint.address;
+        let hostname = lb_endpoint.hostname;
 
         let weight = lb_endpoint.load_balancing_weight.into();
-        Ok(PartialLbEndpoint { address, bind_device_options: BindDeviceOptions::default(), weight, health_status })
+        Ok(PartialLbEndpoint {
+            address,
+            hostname,
+            bind_device_options: BindDeviceOptions::default(),
+            weight,
+            health_status,
+        })
     }
 }
 
@@ -409,6 +433,7 @@ pub struct LocalityLbEndpoints {
     pub name: &'static str,
     pub endpoints: Vec<Arc<LbEndpoint>>,
     pub priority: u32,
+    pub locality: Option<Locality>,
     pub healthy_endpoints: u32,
     pub total_endpoints: u32,
     pub transport_socket: UpstreamTransportSocketConfigurator,
@@ -440,6 +465,7 @@ impl LocalityLbEndpoints {
 pub struct PartialLocalityLbEndpoints {
     endpoints: Vec<PartialLbEndpoint>,
     pub priority: u32,
+    pub locality: Option<Locality>,
 }
 #[derive(Debug, Clone, Default, TypedBuilder)]
 #[builder(build_method(vis="", name=prepare), field_defaults(setter(prefix = "with_")))]
@@ -456,7 +482,7 @@ pub struct LocalityLbEndpointsBuilder {
 impl LocalityLbEndpointsBuilder {
     pub fn build(self) -> Result<LocalityLbEndpoints> {
         let cluster_name = self.cluster_name;
-        let PartialLocalityLbEndpoints { endpoints, priority } = self.endpoints;
+        let PartialLocalityLbEndpoints { endpoints, priority, locality } = self.endpoints;
 
         let endpoints: Vec<Arc<LbEndpoint>> = endpoints
             .into_iter()
@@ -494,6 +520,7 @@ impl LocalityLbEndpointsBuilder {
             name: cluster_name,
             endpoints,
             priority,
+            locality,
             healthy_endpoints,
             total_endpoints,
             transport_socket: self.transport_socket,
@@ -509,7 +536,7 @@ impl TryFrom<LocalityLbEndpointsConfig> for PartialLocalityLbEndpoints {
     fn try_from(value: LocalityLbEndpointsConfig) -> Result<Self> {
         let endpoints = value.lb_endpoints.into_iter().map(PartialLbEndpoint::try_from).collect::<Result<_>>()?;
         let priority = value.priority;
-        Ok(PartialLocalityLbEndpoints { priority, endpoints })
+        Ok(PartialLocalityLbEndpoints { priority, endpoints, locality: value.locality })
     }
 }
 
@@ -532,13 +559,27 @@ impl BalancerType {
             BalancerType::Maglev(balancer) => balancer.update_health(endpoint, health),
         }
     }
+    pub fn health(&self, endpoint: &LbEndpoint) -> Option<HealthStatus> {
+        match self {
+            BalancerType::RoundRobin(balancer) => balancer.health(endpoint),
+            BalancerType::Random(balancer) => balancer.health(endpoint),
+            BalancerType::LeastRequests(balancer) => balancer.health(endpoint),
+            BalancerType::RingHash(balancer) => balancer.health(endpoint),
+            BalancerType::Maglev(balancer) => balancer.health(endpoint),
+        }
+    }
+
     fn next_item(&mut self, maybe_hash: Option<HashState>) -> Option<Arc<LbEndpoint>> {
+        self.next_item_by_hash(maybe_hash.and_then(HashState::compute))
+    }
+
+    fn next_item_by_hash(&mut self, hash: Option<u64>) -> Option<Arc<LbEndpoint>> {
         match self {
             BalancerType::RoundRobin(balancer) => balancer.next_item(None),
             BalancerType::Random(balancer) => balancer.next_item(None),
             BalancerType::LeastRequests(balancer) => balancer.next_item(None),
-            BalancerType::RingHash(balancer) => balancer.next_item(maybe_hash.and_then(HashState::compute)),
-            BalancerType::Maglev(balancer) => balancer.next_item(maybe_hash.and_then(HashState::compute)),
+            BalancerType::RingHash(balancer) => balancer.next_item(hash),
+            BalancerType::Maglev(balancer) => balancer.next_item(hash),
         }
     }
 }
@@ -550,6 +591,7 @@ pub struct ClusterLoadAssignment {
     pub transport_socket: UpstreamTransportSocketConfigurator,
     protocol_options: HttpProtocolOptions,
     balancer: BalancerType,
+    outlier_detection: Option<OutlierDetection>,
     pub endpoints: Vec<LocalityLbEndpoints>,
 }
 
@@ -565,10 +607,55 @@ impl PartialClusterLoadAssignment {
 
 impl ClusterLoadAssignment {
     pub fn get_http_channel(&mut self, hash: Option<HashState>) -> Result<HttpChannel> {
-        let endpoint = self.balancer.next_item(hash).ok_or("No active endpoint")?;
+        let endpoint = self.next_http_endpoint(hash).ok_or("No active endpoint")?;
         Ok(endpoint.http_channel().ok_or("No HTTP channel available for this endpoint")?.clone())
     }
 
+    /// Picks the next endpoint, passing over those outlier detection ejects. When every endpoint picked is ejected,
+    /// the first one is used all the same rather than failing the request.
+    fn next_http_endpoint(&mut self, hash: Option<HashState>) -> Option<Arc<LbEndpoint>> {
+        let Some(outlier_detection) = self.outlier_detection else {
+            return self.balancer.next_item(hash);
+        };
+        let now = Instant::now();
+        let total = self.all_endpoints_iter().count();
+        let hash = hash.and_then(HashState::compute);
+        let first = self.balancer.next_item_by_hash(hash)?;
+        let mut endpoint = Arc::clone(&first);
+        for _ in 0..total {
+            if !self.is_outlier(&endpoint, &outlier_detection, total, now) {
+                return Some(endpoint);
+            }
+            endpoint = self.balancer.next_item_by_hash(hash)?;
+        }
+        Some(first)
+    }
+
+    fn is_outlier(
+        &self,
+        endpoint: &LbEndpoint,
+        outlier_detection: &OutlierDetection,
+        total: usize,
+        now: Instant,
+    ) -> bool {
+        let Some(stats) = endpoint.http_channel().map(|channel| &channel.stats) else {
+            return false;
+        };
+        if stats.is_ejected(now) {
+            return true;
+        }
+        let threshold = outlier_detection.consecutive_5xx.get();
+        if stats.consecutive_5xx() < threshold {
+            return false;
+        }
+        let ejected = self
+            .all_endpoints_iter()
+            .filter(|endpoint| endpoint.http_channel().is_some_and(|channel| channel.stats.is_ejected(now)))
+            .count();
+        ejected < outlier_detection.max_ejected(total)
+            && stats.eject_after(threshold, |times| outlier_detection.ejection_time(times), now)
+    }
+
     pub fn get_tcp_channel(&mut self) -> Result<TcpChannelConnector> {
         let endpoint = self.balancer.next_item(None).ok_or("No active endpoint")?;
         Ok(endpoint.tcp_channel().ok_or("No TCP channel available for this endpoint")?.clone())
@@ -611,6 +698,24 @@ impl ClusterLoadAssignment {
         }
     }
 
+    pub fn hosts_status(&self) -> Vec<HostStatus> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .flat_map(|locality| locality.endpoints.iter().map(move |endpoint| (locality, endpoint)))
+            .map(|(locality, endpoint)| HostStatus {
+                address: endpoint.address.to_address().to_string(),
+                health: self.balancer.health(endpoint).unwrap_or(endpoint.health_status),
+                eds_health: endpoint.health_status,
+                weight: endpoint.weight,
+                priority: locality.priority,
+                locality: locality.locality.clone(),
+                outlier_ejected: endpoint.http_channel().is_some_and(|channel| channel.stats.is_ejected(now)),
+                stats: endpoint.http_channel().map(|channel| channel.stats.snapshot()).unwrap_or_default(),
+            })
+            .collect()
+    }
+
     pub fn rebuild(self) -> Result<Self> {
         let endpoints = self
             .endpoints
@@ -643,6 +748,8 @@ pub struct ClusterLoadAssignmentBuilder {
     server_name: Option<ServerName<'static>>,
     #[builder(default)]
     connection_timeout: Option<Duration>,
+    #[builder(default)]
+    outlier_detection: Option<OutlierDetection>,
 }
 
 impl ClusterLoadAssignmentBuilder {
@@ -683,6 +790,7 @@ impl ClusterLoadAssignmentBuilder {
             cluster_name,
             protocol_options,
             balancer,
+            outlier_detection: self.outlier_detection,
             transport_socket: self.transport_socket,
             endpoints,
         })
@@ -705,13 +813,21 @@ impl TryFrom<ClusterLoadAssignmentConfig> for PartialClusterLoadAssignment {
 
 #[cfg(test)]
 mod test {
-    use super::{EndpointAddressType, LbEndpoint};
+    use super::{
+        ClusterLoadAssignmentBuilder, ClusterLoadAssignmentConfig, EndpointAddressType, LbEndpoint, LbEndpointConfig,
+        LbPolicy, LocalityLbEndpointsConfig, OutlierDetection, PartialClusterLoadAssignment,
+    };
     use crate::{
         clusters::health::HealthStatus,
         transport::{HttpChannelBuilder, TcpChannelConnector, UpstreamTransportSocketConfigurator},
     };
-    use http::uri::Authority;
+    use http::{uri::Authority, StatusCode};
     use orion_configuration::config::{core::envoy_conversions::Address, transport::BindDeviceOptions};
+    use std::{
+        num::NonZeroU32,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
 
     impl LbEndpoint {
         /// This function is used by unit tests in other modules
@@ -739,12 +855,61 @@ mod test {
             Self {
                 name: "Cluster".into(),
                 address: EndpointAddressType::Socket(authority, http_channel, tcp_channel),
+                hostname: None,
                 bind_device_options,
                 weight,
                 health_status,
             }
         }
     }
+
+    #[tokio::test]
+    async fn outliers_are_passed_over_until_their_ejection_is_over() {
+        let endpoint = |port| LbEndpointConfig {
+            address: Address::Socket("127.0.0.1".to_owned(), port),
+            hostname: None,
+            health_status: HealthStatus::Healthy,
+            load_balancing_weight: NonZeroU32::MIN,
+        };
+        let cla = ClusterLoadAssignmentConfig {
+            cluster_name: "outlier_cluster".to_owned(),
+            endpoints: vec![LocalityLbEndpointsConfig {
+                priority: 0,
+                locality: None,
+                lb_endpoints: vec![endpoint(9200), endpoint(9201)],
+            }],
+        };
+        let outlier_detection = OutlierDetection {
+            consecutive_5xx: NonZeroU32::MIN.saturating_add(1),
+            base_ejection_time: Duration::from_secs(30),
+            max_ejection_time: Duration::from_secs(300),
+            max_ejection_percent: 10,
+        };
+        let mut cla = ClusterLoadAssignmentBuilder::builder()
+            .with_cla(PartialClusterLoadAssignment::try_from(cla).unwrap())
+            .with_cluster_name("outlier_cluster")
+            .with_bind_device_options(BindDeviceOptions::default())
+            .with_lb_policy(LbPolicy::RoundRobin)
+            .with_transport_socket(UpstreamTransportSocketConfigurator::None)
+            .with_outlier_detection(Some(outlier_detection))
+            .prepare()
+            .build()
+            .unwrap();
+
+        let failing = cla.get_http_channel(None).unwrap().stats;
+        let unavailable =
+            hyper::Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body(()).map_err(crate::Error::from);
+        failing.record_result(&unavailable);
+        failing.record_result(&unavailable);
+
+        for _ in 0..4 {
+            assert!(!Arc::ptr_eq(&cla.get_http_channel(None).unwrap().stats, &failing));
+        }
+        let ejected: Vec<_> = cla.hosts_status().into_iter().map(|host| host.outlier_ejected).collect();
+        assert_eq!(ejected.iter().filter(|ejected| **ejected).count(), 1);
+        assert!(failing.is_ejected(Instant::now()));
+        assert!(!failing.is_ejected(Instant::now() + Duration::from_secs(30)));
+    }
 }
 
 fn dummy_authority() -> &'static Authority {
@@ -0,0 +1,206 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! The load of the upstream clusters, as reported to a load reporting service (LRS) server.
+//!
+//! The load of a cluster is broken down by the locality of its endpoints, and only counts what happened since it
+//! was last reported, save for the requests and connections still active.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use orion_configuration::config::cluster::Locality;
+use parking_lot::Mutex;
+
+use super::{clusters_manager::get_clusters_status, status::ClusterStatus};
+use crate::transport::HostStatsSnapshot;
+
+static DROPPED_REQUESTS: Lazy<Mutex<HashMap<&'static str, u64>>> = Lazy::new(Mutex::default);
+
+/// Counts a request routed to a cluster none of the endpoints of which could be sent it.
+pub(crate) fn request_dropped(cluster_name: &'static str) {
+    *DROPPED_REQUESTS.lock().entry(cluster_name).or_default() += 1;
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalityLoad {
+    pub locality: Option<Locality>,
+    pub issued_requests: u64,
+    pub successful_requests: u64,
+    pub error_requests: u64,
+    pub requests_in_progress: u64,
+    pub active_connections: u64,
+    pub new_connections: u64,
+    pub failed_connections: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterLoad {
+    pub cluster_name: &'static str,
+    pub localities: Vec<LocalityLoad>,
+    pub dropped_requests: u64,
+    /// How long the load was measured for.
+    pub interval: Duration,
+}
+
+/// Measures the load of the clusters between two reports.
+#[derive(Debug)]
+pub struct LoadReporter {
+    /// The counters of each endpoint, by cluster and address, when the load was last reported.
+    reported: HashMap<(&'static str, String), HostStatsSnapshot>,
+    dropped_requests: HashMap<&'static str, u64>,
+    since: Instant,
+}
+
+impl Default for LoadReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadReporter {
+    /// Starts measuring the load from now on.
+    pub fn new() -> Self {
+        let mut reporter = Self { reported: HashMap::new(), dropped_requests: HashMap::new(), since: Instant::now() };
+        reporter.report(Some(&[]));
+        reporter
+    }
+
+    /// The load of the clusters since the last report, of all of them if none are given.
+    pub fn report(&mut self, cluster_names: Option<&[String]>) -> Vec<ClusterLoad> {
+        let dropped_requests = DROPPED_REQUESTS.lock().clone();
+        self.measure(&get_clusters_status(), &dropped_requests, cluster_names)
+    }
+
+    fn measure(
+        &mut self,
+        clusters: &[ClusterStatus],
+        dropped_requests: &HashMap<&'static str, u64>,
+        cluster_names: Option<&[String]>,
+    ) -> Vec<ClusterLoad> {
+        let now = Instant::now();
+        let interval = now.duration_since(self.since);
+        self.since = now;
+
+        let mut loads = Vec::new();
+        // the counters of the clusters not reported are kept up to date too, for them to only count the load since
+        // they are asked for
+        for cluster in clusters {
+            let mut localities = BTreeMap::<Option<Locality>, LocalityLoad>::new();
+            for host in &cluster.hosts {
+                let previous =
+                    self.reported.insert((cluster.name, host.address.clone()), host.stats).unwrap_or_default();
+                let current = host.stats;
+                let load = localities
+                    .entry(host.locality.clone())
+                    .or_insert_with(|| LocalityLoad { locality: host.locality.clone(), ..Default::default() });
+                load.issued_requests += count_since(current.rq_total, previous.rq_total);
+                load.successful_requests += count_since(current.rq_success, previous.rq_success);
+                load.error_requests += count_since(current.rq_error, previous.rq_error);
+                load.requests_in_progress += current.rq_active;
+                load.active_connections += current.cx_active;
+                load.new_connections += count_since(current.cx_total, previous.cx_total);
+                load.failed_connections += count_since(current.cx_connect_fail, previous.cx_connect_fail);
+            }
+            let dropped_total = dropped_requests.get(cluster.name).copied().unwrap_or_default();
+            let dropped = count_since(
+                dropped_total,
+                self.dropped_requests.insert(cluster.name, dropped_total).unwrap_or_default(),
+            );
+            if cluster_names.is_none_or(|names| names.iter().any(|name| name == cluster.name)) {
+                loads.push(ClusterLoad {
+                    cluster_name: cluster.name,
+                    localities: localities.into_values().collect(),
+                    dropped_requests: dropped,
+                    interval,
+                });
+            }
+        }
+
+        let existing: HashSet<_> = clusters.iter().map(|cluster| cluster.name).collect();
+        self.reported.retain(|(cluster_name, _), _| existing.contains(cluster_name));
+        self.dropped_requests.retain(|cluster_name, _| existing.contains(cluster_name));
+        loads
+    }
+}
+
+/// What a counter counted since its previous value. The counters start over when the channel to an endpoint is
+/// rebuilt, everything they counted since then being new.
+fn count_since(current: u64, previous: u64) -> u64 {
+    current.checked_sub(previous).unwrap_or(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clusters::status::HostStatus;
+    use orion_configuration::config::cluster::HealthStatus;
+
+    fn host(address: &str, zone: &str, stats: HostStatsSnapshot) -> HostStatus {
+        HostStatus {
+            address: address.to_owned(),
+            health: HealthStatus::Healthy,
+            eds_health: HealthStatus::Healthy,
+            weight: 1,
+            priority: 0,
+            locality: Some(Locality { zone: zone.into(), ..Default::default() }),
+            outlier_ejected: false,
+            stats,
+        }
+    }
+
+    #[test]
+    fn load_since_the_last_report_by_locality() {
+        let stats = |rq_total, rq_success, rq_active| HostStatsSnapshot {
+            rq_total,
+            rq_success,
+            rq_active,
+            ..Default::default()
+        };
+        let clusters = |a: HostStatsSnapshot, b: HostStatsSnapshot, c: HostStatsSnapshot| {
+            vec![ClusterStatus {
+                name: "backends",
+                hosts: vec![host("10.0.0.1:80", "a", a), host("10.0.0.2:80", "a", b), host("10.0.1.1:80", "b", c)],
+            }]
+        };
+        let mut reporter = LoadReporter::new();
+        let dropped = HashMap::from([("backends", 2)]);
+        let loads = reporter.measure(&clusters(stats(5, 4, 1), stats(3, 3, 0), stats(0, 0, 0)), &dropped, None);
+        assert_eq!(loads.len(), 1);
+        assert_eq!(loads[0].dropped_requests, 2);
+        let zone_a = &loads[0].localities[0];
+        assert_eq!((zone_a.issued_requests, zone_a.successful_requests, zone_a.requests_in_progress), (8, 7, 1));
+
+        let dropped = HashMap::from([("backends", 3)]);
+        let loads = reporter.measure(&clusters(stats(6, 5, 0), stats(3, 3, 0), stats(2, 1, 1)), &dropped, None);
+        assert_eq!(loads[0].dropped_requests, 1);
+        let issued: Vec<_> = loads[0].localities.iter().map(|load| load.issued_requests).collect();
+        assert_eq!(issued, [1, 2]);
+
+        let loads = reporter.measure(&clusters(stats(6, 5, 0), stats(1, 1, 0), stats(2, 1, 0)), &dropped, None);
+        let issued: Vec<_> = loads[0].localities.iter().map(|load| load.issued_requests).collect();
+        assert_eq!(issued, [1, 0], "counted anew once started over");
+
+        let names = ["other".to_owned()];
+        assert!(reporter
+            .measure(&clusters(stats(6, 5, 0), stats(3, 3, 0), stats(2, 1, 1)), &dropped, Some(&names))
+            .is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+pub use crate::transport::HostStatsSnapshot;
+use orion_configuration::config::cluster::{HealthStatus, Locality};
+use serde::Serialize;
+
+/// Runtime state of a cluster, as reported by the admin `/clusters` endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterStatus {
+    pub name: &'static str,
+    pub hosts: Vec<HostStatus>,
+}
+
+/// Runtime state of a single upstream host of a cluster.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostStatus {
+    pub address: String,
+    /// The health the host is load balanced by, as active health checking last found it.
+    pub health: HealthStatus,
+    /// The health the host was configured or discovered with.
+    pub eds_health: HealthStatus,
+    pub weight: u32,
+    pub priority: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locality: Option<Locality>,
+    /// Whether outlier detection has the host out of load balancing.
+    pub outlier_ejected: bool,
+    pub stats: HostStatsSnapshot,
+}
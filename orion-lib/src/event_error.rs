@@ -52,8 +52,12 @@ pub enum EventKind {
     ClusterNotFound,
     DirectResponse,
     FilterChainNotFound,
+    FilterConfigNotFound,
+    HealthCheckFailed,
+    HealthCheckOk,
     InternalRedirect,
     NoHealthyUpstream,
+    Overloaded,
     RouteNotFound,
     UpgradeFailed,
     RbacAccessDenied(CompactString),
@@ -77,8 +81,12 @@ impl EventKind {
             EventKind::ClusterNotFound => Some(ResponseCodeDetails("cluster_not_found")),
             EventKind::DirectResponse => Some(ResponseCodeDetails("direct_response")),
             EventKind::FilterChainNotFound => Some(ResponseCodeDetails("filter_chain_not_found")),
+            EventKind::FilterConfigNotFound => Some(ResponseCodeDetails("filter_config_not_found")),
+            EventKind::HealthCheckFailed => Some(ResponseCodeDetails("health_check_failed")),
+            EventKind::HealthCheckOk => Some(ResponseCodeDetails("health_check_ok")),
             EventKind::InternalRedirect => Some(ResponseCodeDetails("internal_redirect")),
             EventKind::NoHealthyUpstream => Some(ResponseCodeDetails("no_healthy_upstream")),
+            EventKind::Overloaded => Some(ResponseCodeDetails("overload")),
             EventKind::RouteNotFound => Some(ResponseCodeDetails("route_not_found")),
             EventKind::UpgradeFailed => Some(ResponseCodeDetails("upgrade_failed")),
             EventKind::RbacAccessDenied(id) => {
@@ -97,6 +105,7 @@ impl EventKind {
                 EventError::RouteTimeout => Some(ConnectionTerminationDetails("route timeout was reached")),
                 _ => None,
             },
+            EventKind::Overloaded => Some(ConnectionTerminationDetails("overload manager reset")),
             EventKind::RbacAccessDenied(id) => {
                 Some(ConnectionTerminationDetails(format!("rbac_access_denied_matched_policy[{id}]").to_static_str()))
             },
@@ -0,0 +1,152 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Filter configurations discovered over ECDS.
+//!
+//! The filters configured this way look their configuration up by name every time they run, for every request or
+//! connection, so that a configuration is swapped without the listeners using it being rebuilt.
+
+use std::{collections::HashMap, sync::Arc};
+
+use arc_swap::ArcSwap;
+use compact_str::CompactString;
+use once_cell::sync::Lazy;
+use orion_configuration::config::{
+    extension_config::{ExtensionConfig, ExtensionFilter},
+    listener_filters::{DiscoveredListenerFilter, ListenerFilterConfig},
+    network_filters::http_connection_manager::http_filters::HttpFilter,
+};
+
+use crate::listeners::http_connection_manager::HttpFilterValue;
+
+/// A discovered configuration, with the HTTP filter built from it if it is the configuration of one.
+#[derive(Debug)]
+pub(crate) struct DiscoveredConfig {
+    pub(crate) config: ExtensionConfig,
+    pub(crate) http_filter: Option<HttpFilterValue>,
+}
+
+static EXTENSION_CONFIGS: Lazy<ArcSwap<HashMap<CompactString, Arc<DiscoveredConfig>>>> = Lazy::new(ArcSwap::default);
+
+/// Adds or replaces the configuration discovered under its name.
+pub fn update(config: ExtensionConfig) {
+    let http_filter = match &config.filter {
+        ExtensionFilter::Http(filter) => Some(HttpFilterValue::new(&config.name, filter.clone())),
+        ExtensionFilter::Listener(_) => None,
+    };
+    let discovered = Arc::new(DiscoveredConfig { config, http_filter });
+    EXTENSION_CONFIGS.rcu(|configs| {
+        let mut configs = HashMap::clone(configs);
+        configs.insert(discovered.config.name.clone(), Arc::clone(&discovered));
+        configs
+    });
+}
+
+/// Removes the configuration discovered under this name, the filters using it falling back to their default one.
+pub fn remove(name: &str) {
+    EXTENSION_CONFIGS.rcu(|configs| {
+        let mut configs = HashMap::clone(configs);
+        configs.remove(name);
+        configs
+    });
+}
+
+pub fn contains(name: &str) -> bool {
+    EXTENSION_CONFIGS.load().contains_key(name)
+}
+
+/// The discovered configurations of HTTP filters, as shown by the config dump.
+pub fn http_filters() -> Vec<HttpFilter> {
+    let mut filters: Vec<_> = EXTENSION_CONFIGS
+        .load()
+        .values()
+        .filter_map(|discovered| match &discovered.config.filter {
+            ExtensionFilter::Http(filter) => {
+                Some(HttpFilter { name: discovered.config.name.clone(), disabled: false, filter: filter.clone() })
+            },
+            ExtensionFilter::Listener(_) => None,
+        })
+        .collect();
+    filters.sort_by(|a, b| a.name.cmp(&b.name));
+    filters
+}
+
+pub(crate) fn get(name: &str) -> Option<Arc<DiscoveredConfig>> {
+    EXTENSION_CONFIGS.load().get(name).cloned()
+}
+
+/// The configuration a discovered listener filter runs with: the discovered one if it is of an accepted type,
+/// its default one otherwise.
+pub(crate) fn listener_filter_config(filter: &DiscoveredListenerFilter) -> Option<ListenerFilterConfig> {
+    get(&filter.name)
+        .and_then(|discovered| match &discovered.config.filter {
+            ExtensionFilter::Listener(config) if filter.source.accepts(&discovered.config.type_url) => {
+                Some(config.clone())
+            },
+            _ => None,
+        })
+        .or_else(|| filter.source.default_config.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_configuration::config::{
+        extension_config::ExtensionConfigSource, listener_filters::DownstreamProxyProtocolConfig, ConfigSource,
+        ConfigSourceSpecifier,
+    };
+
+    #[test]
+    fn discovered_listener_filter_falls_back_to_its_default() {
+        let filter = DiscoveredListenerFilter {
+            name: "ecds_test_listener_filter".into(),
+            source: ExtensionConfigSource {
+                config_source: ConfigSource {
+                    config_source_specifier: ConfigSourceSpecifier::ADS,
+                    initial_fetch_timeout: None,
+                },
+                default_config: Some(ListenerFilterConfig::Ignored),
+                apply_default_config_without_warming: true,
+                type_urls: vec![
+                    "type.googleapis.com/envoy.extensions.filters.listener.tls_inspector.v3.TlsInspector".into()
+                ],
+            },
+        };
+        assert_eq!(listener_filter_config(&filter), Some(ListenerFilterConfig::Ignored));
+
+        update(ExtensionConfig {
+            name: filter.name.clone(),
+            type_url: "type.googleapis.com/envoy.extensions.filters.listener.tls_inspector.v3.TlsInspector".into(),
+            filter: ExtensionFilter::Listener(ListenerFilterConfig::TlsInspector),
+        });
+        assert!(contains(&filter.name));
+        assert_eq!(listener_filter_config(&filter), Some(ListenerFilterConfig::TlsInspector));
+
+        update(ExtensionConfig {
+            name: filter.name.clone(),
+            type_url: "type.googleapis.com/envoy.extensions.filters.listener.proxy_protocol.v3.ProxyProtocol".into(),
+            filter: ExtensionFilter::Listener(ListenerFilterConfig::ProxyProtocol(
+                DownstreamProxyProtocolConfig::default(),
+            )),
+        });
+        assert_eq!(listener_filter_config(&filter), Some(ListenerFilterConfig::Ignored), "type not accepted");
+
+        remove(&filter.name);
+        assert!(!contains(&filter.name));
+        assert_eq!(listener_filter_config(&filter), Some(ListenerFilterConfig::Ignored));
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Layered runtime.
+//!
+//! The values of all the layers are merged into a snapshot, a value of a layer overriding the values of the layers
+//! before it. The snapshot is republished whenever a disk layer changes or the admin layer is modified, and is read
+//! on the request path without locking.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use arc_swap::ArcSwap;
+use compact_str::CompactString;
+use once_cell::sync::Lazy;
+use orion_configuration::config::{
+    bootstrap::Node,
+    layered_runtime::{
+        DiskLayer, FractionalPercent, LayeredRuntime, RuntimeFractionalPercent, RuntimeLayerSpecifier, RuntimeValue,
+    },
+};
+use orion_xds::watcher::MoveWatcher;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::{debug, info, warn};
+
+use crate::Result;
+
+type Values = BTreeMap<CompactString, RuntimeValue>;
+
+enum LayerKind {
+    Static,
+    Disk {
+        /// The directory holding the values.
+        root: PathBuf,
+        symlink_root: PathBuf,
+        /// The target of `symlink_root` when the layer was last loaded.
+        target: Option<PathBuf>,
+    },
+    Admin,
+}
+
+struct Layer {
+    name: CompactString,
+    kind: LayerKind,
+    values: Values,
+}
+
+static LAYERS: Lazy<Mutex<Vec<Layer>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static SNAPSHOT: Lazy<ArcSwap<HashMap<CompactString, RuntimeValue>>> = Lazy::new(ArcSwap::default);
+
+/// Loads the layers of the bootstrap, replacing any previous ones.
+pub fn initialize(config: &LayeredRuntime, node: &Node) {
+    let layers = config
+        .layers
+        .iter()
+        .map(|layer| {
+            let (kind, values) = match &layer.layer {
+                RuntimeLayerSpecifier::StaticLayer(values) => (LayerKind::Static, values.clone()),
+                RuntimeLayerSpecifier::DiskLayer(disk_layer) => {
+                    let symlink_root = disk_layer.symlink_root.clone();
+                    let target = fs::canonicalize(&symlink_root).ok();
+                    let root = disk_layer_root(disk_layer, node);
+                    let values = load_disk_layer(&layer.name, &root);
+                    (LayerKind::Disk { root, symlink_root, target }, values)
+                },
+                RuntimeLayerSpecifier::AdminLayer => (LayerKind::Admin, Values::new()),
+            };
+            Layer { name: layer.name.clone(), kind, values }
+        })
+        .collect();
+    let mut current = LAYERS.lock();
+    *current = layers;
+    publish(&current);
+}
+
+fn disk_layer_root(disk_layer: &DiskLayer, node: &Node) -> PathBuf {
+    let mut root = disk_layer.symlink_root.clone();
+    if let Some(subdirectory) = &disk_layer.subdirectory {
+        root.push(subdirectory);
+    }
+    if disk_layer.append_service_cluster {
+        root.push(node.cluster_id.as_str());
+    }
+    root
+}
+
+fn load_disk_layer(name: &str, root: &Path) -> Values {
+    let mut values = Values::new();
+    if let Err(e) = walk_disk_layer(root, "", &mut values) {
+        warn!("runtime layer {name}: can't read {}: {e}", root.display());
+    }
+    values
+}
+
+/// Every file is a value, keyed by its path relative to the root with `/` replaced by `.`. Hidden files are skipped.
+fn walk_disk_layer(directory: &Path, prefix: &str, values: &mut Values) -> std::io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let key = if prefix.is_empty() { file_name.to_owned() } else { format!("{prefix}.{file_name}") };
+        let path = entry.path();
+        if path.is_dir() {
+            walk_disk_layer(&path, &key, values)?;
+        } else {
+            values.insert(key.into(), RuntimeValue::parse(&fs::read_to_string(&path)?));
+        }
+    }
+    Ok(())
+}
+
+fn publish(layers: &[Layer]) {
+    let mut snapshot = HashMap::new();
+    for layer in layers {
+        snapshot.extend(layer.values.iter().map(|(key, value)| (key.clone(), value.clone())));
+    }
+    SNAPSHOT.store(Arc::new(snapshot));
+}
+
+pub fn has_disk_layers() -> bool {
+    LAYERS.lock().iter().any(|layer| matches!(layer.kind, LayerKind::Disk { .. }))
+}
+
+/// Reloads the disk layers whose symlink root now points somewhere else.
+pub fn reload_disk_layers() {
+    let mut layers = LAYERS.lock();
+    let mut changed = false;
+    for layer in layers.iter_mut() {
+        if let LayerKind::Disk { root, symlink_root, target } = &mut layer.kind {
+            let new_target = fs::canonicalize(&*symlink_root).ok();
+            if new_target != *target {
+                info!("runtime layer {}: {} changed, reloading", layer.name, symlink_root.display());
+                *target = new_target;
+                layer.values = load_disk_layer(&layer.name, root);
+                changed = true;
+            }
+        }
+    }
+    if changed {
+        publish(&layers);
+    }
+}
+
+/// Watches the disk layers for symlink swaps, a new symlink being moved onto the symlink root as Envoy expects.
+pub async fn watch_disk_layers() {
+    let symlink_roots: Vec<PathBuf> = LAYERS
+        .lock()
+        .iter()
+        .filter_map(|layer| match &layer.kind {
+            LayerKind::Disk { symlink_root, .. } => Some(symlink_root.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut watcher = match MoveWatcher::new() {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("runtime: can't watch the disk layers: {e}");
+            return;
+        },
+    };
+    for symlink_root in &symlink_roots {
+        if let Err(e) = watcher.watch_file(symlink_root) {
+            warn!("runtime: can't watch {}: {e}", symlink_root.display());
+        }
+    }
+    loop {
+        if let Err(e) = watcher.moved().await {
+            warn!("runtime: stopped watching the disk layers: {e}");
+            return;
+        }
+        reload_disk_layers();
+    }
+}
+
+/// Sets values of the admin layer, an empty value removing the key.
+pub fn set_admin_values<'a>(values: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<()> {
+    let mut layers = LAYERS.lock();
+    let Some(admin_layer) = layers.iter_mut().rev().find(|layer| matches!(layer.kind, LayerKind::Admin)) else {
+        return Err("No admin layer specified".into());
+    };
+    for (key, value) in values {
+        if value.is_empty() {
+            admin_layer.values.remove(key);
+        } else {
+            debug!("runtime: setting {key} to {value}");
+            admin_layer.values.insert(key.into(), RuntimeValue::parse(value));
+        }
+    }
+    publish(&layers);
+    Ok(())
+}
+
+pub fn get(key: &str) -> Option<RuntimeValue> {
+    SNAPSHOT.load().get(key).cloned()
+}
+
+/// The integer value of `key`, or `default` if it is not set or not an integer.
+pub fn get_integer(key: &str, default: u64) -> u64 {
+    SNAPSHOT.load().get(key).and_then(RuntimeValue::as_integer).unwrap_or(default)
+}
+
+/// Decides whether a feature is enabled for the current request, sampling the runtime value of its key or its
+/// default value.
+pub fn feature_enabled(fraction: &RuntimeFractionalPercent) -> bool {
+    let percent = fraction
+        .runtime_key
+        .as_ref()
+        .and_then(|key| SNAPSHOT.load().get(key).and_then(|value| value.as_fractional_percent(fraction.default_value)))
+        .unwrap_or(fraction.default_value);
+    sample(percent)
+}
+
+fn sample(percent: FractionalPercent) -> bool {
+    let denominator = percent.denominator.value();
+    percent.numerator >= denominator
+        || (percent.numerator > 0 && rand::random_range(0..denominator) < percent.numerator)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuntimeDump {
+    pub layers: Vec<CompactString>,
+    pub entries: BTreeMap<CompactString, RuntimeEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuntimeEntry {
+    pub final_value: String,
+    /// The value of the key in each layer, empty where it is not set.
+    pub layer_values: Vec<String>,
+}
+
+/// The runtime as served by the admin `/runtime` endpoint.
+pub fn dump() -> RuntimeDump {
+    let layers = LAYERS.lock();
+    let mut entries = BTreeMap::<CompactString, RuntimeEntry>::new();
+    for (index, layer) in layers.iter().enumerate() {
+        for (key, value) in &layer.values {
+            let entry = entries.entry(key.clone()).or_insert_with(|| RuntimeEntry {
+                final_value: String::new(),
+                layer_values: vec![String::new(); layers.len()],
+            });
+            entry.layer_values[index] = value.to_string();
+            entry.final_value = value.to_string();
+        }
+    }
+    RuntimeDump { layers: layers.iter().map(|layer| layer.name.clone()).collect(), entries }
+}
+
+// the disk layer is swapped through a symlink
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use orion_configuration::config::layered_runtime::{Denominator, RuntimeLayer};
+
+    fn layer(name: &str, layer: RuntimeLayerSpecifier) -> RuntimeLayer {
+        RuntimeLayer { name: name.into(), layer }
+    }
+
+    fn percent(numerator: u32) -> FractionalPercent {
+        FractionalPercent { numerator, denominator: Denominator::Hundred }
+    }
+
+    // the runtime is global: a single test goes through the layers to avoid tests racing each other
+    #[tokio::test]
+    async fn layers_override_each_other() {
+        let directory = std::env::temp_dir().join(format!("orion-runtime-{}", std::process::id()));
+        let version_1 = directory.join("v1");
+        let version_2 = directory.join("v2");
+        fs::create_dir_all(version_1.join("cluster_a/feature")).unwrap();
+        fs::create_dir_all(&version_2).unwrap();
+        fs::write(version_1.join("cluster_a/feature/weight"), "7\n").unwrap();
+        fs::write(version_1.join("cluster_a/.hidden"), "1").unwrap();
+        fs::write(version_2.join("feature.weight"), "9").unwrap();
+        let symlink_root = directory.join("current");
+        std::os::unix::fs::symlink(&version_1, &symlink_root).unwrap();
+
+        let config = LayeredRuntime {
+            layers: vec![
+                layer(
+                    "static",
+                    RuntimeLayerSpecifier::StaticLayer(
+                        [
+                            ("feature.weight".into(), RuntimeValue::Number(3.0)),
+                            ("disabled".into(), RuntimeValue::Number(0.0)),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    ),
+                ),
+                layer(
+                    "disk",
+                    RuntimeLayerSpecifier::DiskLayer(DiskLayer {
+                        symlink_root: symlink_root.clone(),
+                        subdirectory: None,
+                        append_service_cluster: true,
+                    }),
+                ),
+                layer("admin", RuntimeLayerSpecifier::AdminLayer),
+            ],
+        };
+        let node = Node { id: "node".into(), cluster_id: "cluster_a".into(), metadata: None, ..Default::default() };
+        initialize(&config, &node);
+        assert_eq!(get_integer("feature.weight", 1), 7);
+        assert_eq!(get_integer("missing", 1), 1);
+        assert!(get("cluster_a..hidden").is_none() && get(".hidden").is_none());
+
+        let disabled = RuntimeFractionalPercent { default_value: percent(100), runtime_key: Some("disabled".into()) };
+        assert!(!feature_enabled(&disabled));
+        set_admin_values([("disabled", "100")]).unwrap();
+        assert!(feature_enabled(&disabled));
+        set_admin_values([("disabled", "")]).unwrap();
+        assert!(!feature_enabled(&disabled));
+        assert!(feature_enabled(&RuntimeFractionalPercent { default_value: percent(100), runtime_key: None }));
+
+        let dump = dump();
+        assert_eq!(dump.layers, vec!["static", "disk", "admin"]);
+        assert_eq!(dump.entries["feature.weight"].final_value, "7");
+        assert_eq!(dump.entries["feature.weight"].layer_values, vec!["3", "7", ""]);
+
+        // swapping the symlink reloads the layer
+        let watching = tokio::spawn(watch_disk_layers());
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        fs::create_dir_all(version_2.join("cluster_a")).unwrap();
+        fs::rename(version_2.join("feature.weight"), version_2.join("cluster_a/feature.weight")).unwrap();
+        std::os::unix::fs::symlink(&version_2, directory.join("next")).unwrap();
+        fs::rename(directory.join("next"), &symlink_root).unwrap();
+        for _ in 0..50 {
+            if get_integer("feature.weight", 1) == 9 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        watching.abort();
+        assert_eq!(get_integer("feature.weight", 1), 9);
+
+        initialize(&LayeredRuntime::default(), &node);
+        assert!(set_admin_values([("key", "value")]).is_err());
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
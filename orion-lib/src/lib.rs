@@ -23,7 +23,13 @@ pub mod event_error;
 pub mod access_log;
 mod body;
 pub mod clusters;
+pub mod extension_configs;
+pub mod layered_runtime;
+pub mod lifecycle;
 mod listeners;
+pub mod on_demand;
+pub mod overload;
+pub mod scoped_routes;
 mod secrets;
 pub(crate) mod thread_local;
 mod transport;
@@ -40,11 +46,11 @@ pub use crate::configuration::get_listeners_and_clusters;
 
 pub use clusters::{
     cluster::PartialClusterType,
-    health::{EndpointHealthUpdate, HealthCheckManager},
+    health::{DelegatedHealthChecks, EndpointHealthUpdate, HealthCheckManager},
     load_assignment::PartialClusterLoadAssignment,
     ClusterLoadAssignmentBuilder,
 };
-pub use listeners::listener::ListenerFactory;
+pub use listeners::listener::{listener_bound_address, ListenerFactory};
 pub use listeners_manager::{ListenerConfigurationChange, ListenersManager, RouteConfigurationChange};
 pub use orion_configuration::config::network_filters::http_connection_manager::RouteConfiguration;
 use orion_configuration::config::{
@@ -0,0 +1,169 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Server-wide health check and drain state, driven by the admin interface during shutdown, and the init manager
+//! the server waits for before being ready.
+
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use compact_str::CompactString;
+use once_cell::sync::Lazy;
+use orion_configuration::config::listener::TrafficDirection;
+use tokio::sync::watch;
+
+static HEALTH_CHECK_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Names of the listeners whose connections are being drained.
+static DRAINING_LISTENERS: Lazy<watch::Sender<HashSet<CompactString>>> =
+    Lazy::new(|| watch::Sender::new(HashSet::new()));
+
+/// Names of the init targets the server is still waiting for, `None` once they were all ready.
+static INIT_TARGETS: Lazy<watch::Sender<Option<HashSet<CompactString>>>> =
+    Lazy::new(|| watch::Sender::new(Some(HashSet::new())));
+
+/// The listeners affected by a drain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainScope {
+    /// Only the listeners with an inbound traffic direction.
+    InboundOnly,
+    All,
+}
+
+impl DrainScope {
+    pub fn covers(self, traffic_direction: TrafficDirection) -> bool {
+        match self {
+            DrainScope::InboundOnly => traffic_direction == TrafficDirection::Inbound,
+            DrainScope::All => true,
+        }
+    }
+
+    /// The scope covering the listeners of both `self` and `other`.
+    #[must_use]
+    pub fn union(self, other: DrainScope) -> DrainScope {
+        if self == DrainScope::All || other == DrainScope::All {
+            DrainScope::All
+        } else {
+            DrainScope::InboundOnly
+        }
+    }
+}
+
+/// Fails (or restores) the server health check: the health check HTTP filter answers with a 503 and the admin
+/// `/ready` endpoint reports the server as draining.
+pub fn set_health_check_failed(failed: bool) {
+    HEALTH_CHECK_FAILED.store(failed, Ordering::Relaxed);
+}
+
+pub fn health_check_failed() -> bool {
+    HEALTH_CHECK_FAILED.load(Ordering::Relaxed)
+}
+
+/// Has the server wait for a target, such as the first resources of a discovery service, before being ready.
+/// Targets added once the server is initialized are ignored: readiness is not lost again.
+pub fn add_init_target(name: &str) {
+    INIT_TARGETS
+        .send_if_modified(|targets| targets.as_mut().is_some_and(|targets| targets.insert(CompactString::from(name))));
+}
+
+/// Marks an init target as ready, the server being initialized once the last one is.
+pub fn init_target_ready(name: &str) {
+    INIT_TARGETS.send_if_modified(|targets| {
+        let Some(pending) = targets else {
+            return false;
+        };
+        if !pending.remove(name) {
+            return false;
+        }
+        if pending.is_empty() {
+            *targets = None;
+        }
+        true
+    });
+}
+
+/// Whether every init target is ready, which is the case of a server without any.
+pub fn is_initialized() -> bool {
+    INIT_TARGETS.borrow().as_ref().is_none_or(HashSet::is_empty)
+}
+
+/// Starts draining the connections of a listener. Draining can't be undone.
+pub(crate) fn start_draining(listener_name: &str) {
+    DRAINING_LISTENERS.send_if_modified(|listeners| listeners.insert(CompactString::from(listener_name)));
+}
+
+/// Whether any listener is being drained.
+pub fn is_draining() -> bool {
+    !DRAINING_LISTENERS.borrow().is_empty()
+}
+
+pub(crate) fn is_listener_draining(listener_name: &str) -> bool {
+    DRAINING_LISTENERS.borrow().contains(listener_name)
+}
+
+/// Resolves once the connections of the listener have to be drained.
+pub(crate) async fn listener_draining(listener_name: &str) {
+    let mut receiver = DRAINING_LISTENERS.subscribe();
+    // the sender lives in a static, so waiting can't fail
+    let _ = receiver.wait_for(|listeners| listeners.contains(listener_name)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn draining_wakes_up_connections() {
+        let waiter = tokio::spawn(listener_draining("lifecycle_test_listener"));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+        assert!(!is_listener_draining("lifecycle_test_listener"));
+
+        start_draining("lifecycle_test_listener");
+        pingora_timeout::fast_timeout::fast_timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert!(is_listener_draining("lifecycle_test_listener"));
+        assert!(is_draining());
+    }
+
+    #[test]
+    fn initialized_once_every_target_is_ready() {
+        add_init_target("lifecycle_test_lds");
+        add_init_target("lifecycle_test_cds");
+        assert!(!is_initialized());
+
+        init_target_ready("lifecycle_test_cds");
+        init_target_ready("lifecycle_test_unknown");
+        assert!(!is_initialized());
+        init_target_ready("lifecycle_test_lds");
+        assert!(is_initialized());
+
+        add_init_target("lifecycle_test_late");
+        assert!(is_initialized());
+    }
+
+    #[test]
+    fn drain_scope_covers_traffic_directions() {
+        assert!(DrainScope::InboundOnly.covers(TrafficDirection::Inbound));
+        assert!(!DrainScope::InboundOnly.covers(TrafficDirection::Outbound));
+        assert!(!DrainScope::InboundOnly.covers(TrafficDirection::Unspecified));
+        assert!(DrainScope::All.covers(TrafficDirection::Unspecified));
+        assert_eq!(DrainScope::InboundOnly.union(DrainScope::All), DrainScope::All);
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use crate::layered_runtime;
+use orion_configuration::config::network_filters::ConnectionLimit;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Counts the active connections of a listener or filter chain against a limit.
+///
+/// Clones share the same count, so the limit holds across all the worker threads running a copy of the listener.
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionLimiter {
+    max_connections: u64,
+    /// The runtime key overriding the maximum, read as connections are counted.
+    runtime_key: Option<String>,
+    delay: Option<Duration>,
+    active_connections: Arc<AtomicU64>,
+}
+
+/// Keeps a connection counted by a [`ConnectionLimiter`] for as long as it is alive.
+#[derive(Debug)]
+pub(crate) struct ConnectionLimitGuard(Arc<AtomicU64>);
+
+impl Drop for ConnectionLimitGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl From<ConnectionLimit> for ConnectionLimiter {
+    fn from(value: ConnectionLimit) -> Self {
+        let ConnectionLimit { max_connections, delay } = value;
+        Self {
+            max_connections: max_connections.get(),
+            runtime_key: None,
+            delay,
+            active_connections: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl ConnectionLimiter {
+    /// The limit of a listener, the configured one unless its Envoy runtime key is set, and none if neither is.
+    pub(crate) fn for_listener(name: &str, connection_limit: Option<ConnectionLimit>) -> Self {
+        let (max_connections, delay) = connection_limit
+            .map_or((u64::MAX, None), |ConnectionLimit { max_connections, delay }| (max_connections.get(), delay));
+        Self {
+            max_connections,
+            runtime_key: Some(format!("envoy.resource_limits.listener.{name}.connection_limit")),
+            delay,
+            active_connections: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Counts a new connection, or returns `None` if the limit has already been reached.
+    pub(crate) fn try_acquire(&self) -> Option<ConnectionLimitGuard> {
+        let max_connections = self
+            .runtime_key
+            .as_deref()
+            .map_or(self.max_connections, |key| layered_runtime::get_integer(key, self.max_connections));
+        self.active_connections
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |active| {
+                (active < max_connections).then_some(active + 1)
+            })
+            .ok()
+            .map(|_| ConnectionLimitGuard(Arc::clone(&self.active_connections)))
+    }
+
+    /// How long a rejected connection should be kept open before closing it.
+    pub(crate) fn delay(&self) -> Option<Duration> {
+        self.delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU64;
+
+    #[test]
+    fn limit_is_shared_between_clones() {
+        let limiter = ConnectionLimiter::from(ConnectionLimit { max_connections: NonZeroU64::MIN, delay: None });
+        let clone = limiter.clone();
+
+        let guard = limiter.try_acquire();
+        assert!(guard.is_some());
+        assert!(clone.try_acquire().is_none());
+
+        drop(guard);
+        assert!(clone.try_acquire().is_some());
+    }
+}
@@ -71,6 +71,8 @@ impl DownstreamConnectionMetadata {
 pub struct DownstreamMetadata {
     pub connection: DownstreamConnectionMetadata,
     pub server_name: Option<CompactString>,
+    /// whether TLS was terminated on the downstream connection
+    pub is_tls: bool,
 }
 
 impl DownstreamMetadata {
@@ -78,6 +80,6 @@ impl DownstreamMetadata {
     where
         S: Into<CompactString>,
     {
-        Self { connection, server_name: server_name.map(Into::into) }
+        Self { connection, server_name: server_name.map(Into::into), is_tls: false }
     }
 }
@@ -16,21 +16,28 @@
 //
 
 use super::{
+    connection_limit::ConnectionLimiter,
     http_connection_manager::{AlpnCodecs, HttpConnectionManager, HttpConnectionManagerBuilder},
     tcp_proxy::{TcpProxy, TcpProxyBuilder},
 };
 use crate::{
+    lifecycle,
     listeners::{
         filter_state::{DownstreamConnectionMetadata, DownstreamMetadata},
         http_connection_manager::ExtendedRequest,
     },
+    overload,
     secrets::{TlsConfigurator, WantsToBuildServer},
     transport::AsyncReadWrite,
     AsyncStream, ConversionContext, Error, Result,
 };
 use compact_str::CompactString;
 use futures::TryFutureExt;
-use hyper::{service::Service, Request};
+use hyper::{
+    header::{HeaderValue, CONNECTION},
+    service::Service,
+    Request, Version,
+};
 use hyper_util::rt::{TokioExecutor, TokioIo};
 use hyper_util::server::conn::auto::Builder as HyperServerBuilder;
 use opentelemetry::KeyValue;
@@ -45,9 +52,18 @@ use orion_metrics::{
     metrics::{http, tcp, tls},
     with_histogram, with_metric,
 };
+use parking_lot::Mutex;
 use rustls::{server::Acceptor, ServerConfig};
 use scopeguard::defer;
-use std::{sync::Arc, thread::ThreadId};
+use std::{
+    pin::pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::ThreadId,
+    time::{Duration, Instant},
+};
 use tracing::{debug, warn};
 
 #[derive(Debug, Clone)]
@@ -66,6 +82,7 @@ pub enum ConnectionHandler {
 pub struct Filterchain {
     pub name: CompactString,
     pub rbac_filters: Vec<NetworkRbac>,
+    pub(crate) connection_limiter: Option<ConnectionLimiter>,
     pub tls_configurator: Option<TlsConfigurator<ServerConfig, WantsToBuildServer>>,
 }
 
@@ -95,6 +112,7 @@ pub struct FilterchainBuilder {
     filter_chain_match_hash: u64,
     main_filter: MainFilterBuilder,
     rbac_filters: Vec<NetworkRbac>,
+    connection_limiter: Option<ConnectionLimiter>,
     tls_configurator: Option<TlsConfigurator<ServerConfig, WantsToBuildServer>>,
 }
 impl FilterchainBuilder {
@@ -109,6 +127,7 @@ impl FilterchainBuilder {
             name: filterchain_name,
             tls_configurator: self.tls_configurator,
             rbac_filters: self.rbac_filters,
+            connection_limiter: self.connection_limiter,
         };
         let handler = match self.main_filter {
             MainFilterBuilder::Http(http_connection_manager) => ConnectionHandler::Http(Arc::new(
@@ -132,6 +151,8 @@ impl TryFrom<ConversionContext<'_, FilterChainConfig>> for FilterchainBuilder {
         let main_filter = ConversionContext::new((filter_chain.terminal_filter, secret_manager)).try_into()?;
         let tls_config = filter_chain.tls_config;
         let rbac_filters = filter_chain.rbac;
+        // created once here, so that all the listeners built from this filter chain share the connection count
+        let connection_limiter = filter_chain.connection_limit.map(ConnectionLimiter::from);
         let tls_configurator =
             tls_config.map(|tls_config| TlsConfigurator::try_from((tls_config, secret_manager))).transpose()?;
         Ok(FilterchainBuilder {
@@ -140,6 +161,7 @@ impl TryFrom<ConversionContext<'_, FilterChainConfig>> for FilterchainBuilder {
             listener_name: None,
             main_filter,
             rbac_filters,
+            connection_limiter,
             tls_configurator,
         })
     }
@@ -168,6 +190,7 @@ impl FilterchainType {
         Some(stream)
     }
 
+    #[allow(clippy::too_many_lines)]
     pub async fn start_filterchain(
         &self,
         stream: AsyncStream,
@@ -231,18 +254,80 @@ impl FilterchainType {
                     CodecType::Http2 => hyper_server.http2_only(),
                     CodecType::Auto => hyper_server,
                 };
-                hyper_server
-                    .serve_connection_with_upgrades(
-                        stream,
-                        hyper::service::service_fn(|req: Request<hyper::body::Incoming>| {
-                            let handler_req =
-                                ExtendedRequest { request: req, downstream_metadata: downstream_metadata.clone() };
-                            req_handler.call(handler_req).map_err(orion_error::Error::into_inner)
-                        }),
-                    )
-                    .await
-                    .inspect_err(|err| debug!("{listener_name} : HTTP connection error: {err}"))
-                    .map_err(Error::from)
+                let activity = ConnectionActivity::new();
+                let mut connection = pin!(hyper_server.serve_connection_with_upgrades(
+                    stream,
+                    hyper::service::service_fn(|req: Request<hyper::body::Incoming>| {
+                        // when overloaded or draining, ask HTTP/1.x clients to close the connection after this response
+                        let is_http1 = matches!(req.version(), Version::HTTP_10 | Version::HTTP_11);
+                        let draining = is_http1 && lifecycle::is_listener_draining(listener_name);
+                        let disable_keepalive = is_http1 && !draining && overload::should_disable_keepalive();
+                        let request_guard = activity.begin_request();
+                        let handler_req =
+                            ExtendedRequest { request: req, downstream_metadata: downstream_metadata.clone() };
+                        req_handler
+                            .call(handler_req)
+                            .map_ok(move |mut response| {
+                                drop(request_guard);
+                                if draining {
+                                    response.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
+                                }
+                                if disable_keepalive {
+                                    response.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
+                                    with_metric!(
+                                        http::DOWNSTREAM_CX_OVERLOAD_DISABLE_KEEPALIVE,
+                                        add,
+                                        1,
+                                        shard_id,
+                                        &[KeyValue::new("listener", listener_name)]
+                                    );
+                                }
+                                response
+                            })
+                            .map_err(orion_error::Error::into_inner)
+                    }),
+                ));
+
+                // Connections opened before the listener started draining are shut down gracefully (GOAWAY for HTTP/2,
+                // close once idle for HTTP/1.x). Those accepted while draining still get their requests served, with
+                // HTTP/1.x keep-alive disabled.
+                let mut draining = pin!(async {
+                    if lifecycle::is_listener_draining(listener_name) {
+                        std::future::pending().await
+                    } else {
+                        lifecycle::listener_draining(listener_name).await;
+                    }
+                });
+                let result = loop {
+                    // the idle timeout may be reduced by the overload manager, so re-evaluate it on every wakeup
+                    let idle_timeout = http_connection_manager.idle_timeout.map(overload::scale_idle_timeout);
+                    let wakeup = idle_timeout
+                        .map(|timeout| activity.idle_deadline(timeout).unwrap_or_else(|| Instant::now() + timeout));
+                    tokio::select! {
+                        result = connection.as_mut() => break result,
+                        () = draining.as_mut() => {
+                            debug!("{listener_name} : draining HTTP connection");
+                            connection.as_mut().graceful_shutdown();
+                            break connection.await;
+                        },
+                        () = sleep_until_deadline(wakeup) => {
+                            let Some(timeout) = idle_timeout else { continue };
+                            if activity.idle_deadline(timeout).is_some_and(|deadline| deadline <= Instant::now()) {
+                                debug!("{listener_name} : closing HTTP connection idle for {timeout:?}");
+                                with_metric!(
+                                    http::DOWNSTREAM_CX_IDLE_TIMEOUT,
+                                    add,
+                                    1,
+                                    shard_id,
+                                    &[KeyValue::new("listener", listener_name)]
+                                );
+                                connection.as_mut().graceful_shutdown();
+                                break connection.await;
+                            }
+                        },
+                    }
+                };
+                result.inspect_err(|err| debug!("{listener_name} : HTTP connection error: {err}")).map_err(Error::from)
             },
             ConnectionHandler::Tcp(tcp_proxy) => {
                 with_metric!(tcp::DOWNSTREAM_CX_TOTAL, add, 1, shard_id, &[KeyValue::new("listener", listener_name)]);
@@ -284,6 +369,45 @@ fn negotiate_codec_type<'a>(codec_type: CodecType, client_alpns: impl Iterator<I
         .copied()
 }
 
+async fn sleep_until_deadline(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Tracks the in-flight requests of a downstream HTTP connection, so that it can be closed once idle.
+#[derive(Debug)]
+struct ConnectionActivity {
+    active_requests: AtomicUsize,
+    last_active: Mutex<Instant>,
+}
+
+struct RequestGuard(Arc<ConnectionActivity>);
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        *self.0.last_active.lock() = Instant::now();
+        self.0.active_requests.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl ConnectionActivity {
+    fn new() -> Arc<Self> {
+        Arc::new(Self { active_requests: AtomicUsize::new(0), last_active: Mutex::new(Instant::now()) })
+    }
+
+    fn begin_request(self: &Arc<Self>) -> RequestGuard {
+        self.active_requests.fetch_add(1, Ordering::Relaxed);
+        RequestGuard(Arc::clone(self))
+    }
+
+    /// The instant at which the connection becomes idle, or `None` while requests are in flight.
+    fn idle_deadline(&self, timeout: Duration) -> Option<Instant> {
+        (self.active_requests.load(Ordering::Relaxed) == 0).then(|| *self.last_active.lock() + timeout)
+    }
+}
+
 async fn start_tls(
     listener_name: &'static str,
     stream: AsyncStream,
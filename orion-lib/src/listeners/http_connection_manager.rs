@@ -25,11 +25,13 @@
 
 mod direct_response;
 mod http_modifiers;
+mod internal_redirect;
 mod redirect;
 mod route;
+mod route_scopes;
 mod upgrades;
 
-use ::http::HeaderValue;
+use ::http::{uri::Scheme, HeaderValue, StatusCode};
 use arc_swap::ArcSwap;
 use compact_str::{CompactString, ToCompactString};
 use core::time::Duration;
@@ -38,11 +40,11 @@ use hyper::{body::Incoming, service::Service, Request, Response};
 use opentelemetry::global::BoxedSpan;
 use opentelemetry::trace::{Span, Status};
 use opentelemetry::KeyValue;
-use orion_configuration::config::{ConfigSource, ConfigSourceSpecifier, GenericError};
+use orion_configuration::config::{ConfigSource, GenericError};
 use orion_format::types::ResponseFlags as FmtResponseFlags;
 use orion_tracing::span_state::SpanState;
 use orion_tracing::{attributes::HTTP_RESPONSE_STATUS_CODE, with_client_span, with_server_span};
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 
 use orion_configuration::config::network_filters::http_connection_manager::http_filters::{
     FilterConfigOverride, FilterOverride,
@@ -53,8 +55,14 @@ use orion_configuration::config::network_filters::tracing::{TracingConfig, Traci
 use orion_configuration::config::network_filters::{
     access_log::AccessLog,
     http_connection_manager::{
-        http_filters::{http_rbac::HttpRbac, HttpFilter as HttpFilterConfig, HttpFilterType},
-        route::{Action, RouteMatchResult},
+        http_filters::{
+            health_check::HealthCheck,
+            http_rbac::HttpRbac,
+            on_demand::{OnDemand, OnDemandCds},
+            HttpFilter as HttpFilterConfig, HttpFilterType,
+        },
+        route::{Action, RouteAction, RouteMatchResult},
+        scoped_routes::ScopedRoutes,
         CodecType, HttpConnectionManager as HttpConnectionManagerConfig, RdsSpecifier, RouteSpecifier, UpgradeType,
     },
 };
@@ -62,10 +70,12 @@ use orion_format::context::{
     DownstreamResponse, FinishContext, HttpRequestDuration, HttpResponseDuration, InitHttpContext,
 };
 
+use internal_redirect::BufferedRequest;
 use orion_format::LogFormatterLocal;
 use orion_metrics::{metrics::http, with_metric};
 use parking_lot::Mutex;
 use route::MatchedRequest;
+use route_scopes::RouteScopes;
 use scopeguard::defer;
 use std::collections::{HashMap, HashSet};
 use std::thread::ThreadId;
@@ -87,10 +97,12 @@ use crate::{
 
 use crate::{
     body::body_with_timeout::BodyWithTimeout,
+    extension_configs, layered_runtime, lifecycle,
     listeners::{
         access_log::AccessLogContext, filter_state::DownstreamMetadata, rate_limiter::LocalRateLimit,
         synthetic_http_response::SyntheticHttpResponse,
     },
+    overload,
     utils::http::{request_head_size, response_head_size},
     ConversionContext, PolyBody, Result, RouteConfiguration,
 };
@@ -126,10 +138,12 @@ impl HttpConnectionManagerBuilder {
             router_sender,
             codec_type: partial.codec_type,
             dynamic_route_name: partial.dynamic_route_name,
+            route_scopes: partial.scoped_routes.map(RouteScopes::new),
             http_filters_hcm: partial.http_filters_hcm,
             http_filters_per_route: ArcSwap::new(Arc::new(partial.http_filters_per_route)),
             enabled_upgrades: partial.enabled_upgrades,
             request_timeout: partial.request_timeout,
+            idle_timeout: partial.idle_timeout,
             access_log: partial.access_log,
             xff_settings: partial.xff_settings,
             request_id_handler: RequestIdManager::new(
@@ -158,10 +172,12 @@ pub struct PartialHttpConnectionManager {
     router: Option<RouteConfiguration>,
     codec_type: CodecType,
     dynamic_route_name: Option<CompactString>,
+    scoped_routes: Option<ScopedRoutes>,
     http_filters_hcm: Vec<Arc<HttpFilter>>,
-    http_filters_per_route: HashMap<RouteMatch, Vec<Arc<HttpFilter>>>,
+    http_filters_per_route: HashMap<CompactString, RouteFilters>,
     enabled_upgrades: Vec<UpgradeType>,
     request_timeout: Option<Duration>,
+    idle_timeout: Option<Duration>,
     access_log: Vec<AccessLog>,
     xff_settings: XffSettings,
     generate_request_id: bool,
@@ -188,24 +204,62 @@ pub enum HttpFilterValue {
     PeerMetadata,
     /// Envoy set filter state - parsed but not executed (metadata only)
     SetFilterState,
+    /// Runs ahead of routing, see [`apply_health_check`].
+    HealthCheck(HealthCheck),
+    /// Consulted once the request is routed to a cluster that isn't loaded, see [`HttpConnectionManager::on_demand_cds`].
+    OnDemand(OnDemand),
+    /// Configured over ECDS, see [`DiscoveredHttpFilter`].
+    Discovered(Box<DiscoveredHttpFilter>),
+}
+
+/// An HTTP filter whose configuration is discovered over ECDS, and looked up for every request.
+#[derive(Debug, Clone)]
+pub struct DiscoveredHttpFilter {
+    name: CompactString,
+    type_urls: Vec<CompactString>,
+    default_config: Option<HttpFilterValue>,
+}
+
+impl DiscoveredHttpFilter {
+    /// Applies the discovered configuration of the filter if it is of an accepted type, its default one otherwise,
+    /// `None` if there is neither.
+    fn with_config<R>(&self, apply: impl FnOnce(&HttpFilterValue) -> R) -> Option<R> {
+        let discovered = extension_configs::get(&self.name)
+            .filter(|discovered| self.type_urls.is_empty() || self.type_urls.contains(&discovered.config.type_url));
+        match discovered.as_ref().and_then(|discovered| discovered.http_filter.as_ref()) {
+            Some(filter) => Some(apply(filter)),
+            None => self.default_config.as_ref().map(apply),
+        }
+    }
 }
 
 impl From<HttpFilterConfig> for HttpFilter {
     fn from(value: HttpFilterConfig) -> Self {
         let HttpFilterConfig { name, disabled, filter } = value;
-        let filter = match filter {
+        let filter = HttpFilterValue::new(&name, filter);
+        Self { name, disabled, filter: Some(filter) }
+    }
+}
+
+impl HttpFilterValue {
+    pub(crate) fn new(name: &CompactString, filter: HttpFilterType) -> Self {
+        match filter {
             HttpFilterType::RateLimit(r) => HttpFilterValue::RateLimit(r.into()),
             HttpFilterType::Rbac(rbac) => HttpFilterValue::Rbac(rbac),
             HttpFilterType::Ingored => HttpFilterValue::Ignored,
             // Istio-specific filters: parsed but not executed (metadata/telemetry only)
             HttpFilterType::PeerMetadata(_) => HttpFilterValue::PeerMetadata,
             HttpFilterType::SetFilterState(_) => HttpFilterValue::SetFilterState,
-        };
-        Self { name, disabled, filter: Some(filter) }
+            HttpFilterType::HealthCheck(health_check) => HttpFilterValue::HealthCheck(health_check),
+            HttpFilterType::OnDemand(on_demand) => HttpFilterValue::OnDemand(on_demand),
+            HttpFilterType::ConfigDiscovery(source) => HttpFilterValue::Discovered(Box::new(DiscoveredHttpFilter {
+                name: name.clone(),
+                type_urls: source.type_urls,
+                default_config: source.default_config.map(|default_config| Self::new(name, default_config)),
+            })),
+        }
     }
-}
 
-impl HttpFilterValue {
     pub fn apply_request<B>(&self, request: &Request<B>) -> FilterDecision {
         match self {
             HttpFilterValue::Rbac(rbac) => apply_authorization_rules(rbac, request),
@@ -213,34 +267,55 @@ impl HttpFilterValue {
             HttpFilterValue::Ignored => FilterDecision::Continue,
             // Istio-specific filters: no-op execution (metadata/telemetry only)
             HttpFilterValue::PeerMetadata | HttpFilterValue::SetFilterState => FilterDecision::Continue,
+            // health checks are answered before the request is routed
+            HttpFilterValue::HealthCheck(_) => FilterDecision::Continue,
+            // clusters are discovered once the request is routed
+            HttpFilterValue::OnDemand(_) => FilterDecision::Continue,
+            // requests go through a filter only once it has a configuration
+            HttpFilterValue::Discovered(discovered) => {
+                discovered.with_config(|filter| filter.apply_request(request)).unwrap_or_else(|| {
+                    FilterDecision::DirectResponse(
+                        SyntheticHttpResponse::internal_error(
+                            EventKind::FilterConfigNotFound,
+                            ResponseFlags(FmtResponseFlags::NO_FILTER_CONFIG_FOUND),
+                        )
+                        .into_response(request.version()),
+                    )
+                })
+            },
         }
     }
     pub fn apply_response(&self, _response: &mut Response<PolyBody>) -> FilterDecision {
         match self {
             // RBAC and RateLimit do not apply on the response path
-            HttpFilterValue::Rbac(_) | HttpFilterValue::RateLimit(_) | HttpFilterValue::Ignored => {
-                FilterDecision::Continue
-            },
+            HttpFilterValue::Rbac(_)
+            | HttpFilterValue::RateLimit(_)
+            | HttpFilterValue::Ignored
+            | HttpFilterValue::HealthCheck(_)
+            | HttpFilterValue::OnDemand(_) => FilterDecision::Continue,
             // Istio-specific filters: no-op on response path
             HttpFilterValue::PeerMetadata | HttpFilterValue::SetFilterState => FilterDecision::Continue,
+            // none of the filters a discovered one can be applies on the response path either
+            HttpFilterValue::Discovered(_) => FilterDecision::Continue,
         }
     }
     fn from_filter_override(value: &FilterOverride) -> Option<Self> {
         match &value.filter_settings {
             Some(filter_settings) => match filter_settings {
-                FilterConfigOverride::LocalRateLimit(rl) => Some(HttpFilterValue::RateLimit((*rl).into())),
+                FilterConfigOverride::LocalRateLimit(rl) => Some(HttpFilterValue::RateLimit(rl.clone().into())),
                 FilterConfigOverride::Rbac(Some(rbac)) => Some(HttpFilterValue::Rbac(rbac.clone())),
                 FilterConfigOverride::Rbac(None) => None,
+                FilterConfigOverride::OnDemand(on_demand) => Some(HttpFilterValue::OnDemand(on_demand.clone())),
             },
             None => None,
         }
     }
 }
 
-fn per_route_http_filters(
-    route_config: &RouteConfiguration,
-    hcm_filters: &[Arc<HttpFilter>],
-) -> HashMap<RouteMatch, Vec<Arc<HttpFilter>>> {
+/// The filters of each route of a route configuration, with their per-route overrides.
+type RouteFilters = HashMap<RouteMatch, Vec<Arc<HttpFilter>>>;
+
+fn per_route_http_filters(route_config: &RouteConfiguration, hcm_filters: &[Arc<HttpFilter>]) -> RouteFilters {
     let mut per_route_filters: HashMap<RouteMatch, Vec<Arc<HttpFilter>>> = HashMap::new();
     for vh in &route_config.virtual_hosts {
         for route in &vh.routes {
@@ -272,6 +347,7 @@ impl TryFrom<ConversionContext<'_, HttpConnectionManagerConfig>> for PartialHttp
             .map(|f| Arc::new(HttpFilter::from(f)))
             .collect::<Vec<Arc<HttpFilter>>>();
         let request_timeout = configuration.request_timeout;
+        let idle_timeout = configuration.idle_timeout.filter(|timeout| !timeout.is_zero());
         let access_log = configuration.access_log;
         let xff_settings = configuration.xff_settings;
         let generate_request_id = configuration.generate_request_id;
@@ -279,15 +355,19 @@ impl TryFrom<ConversionContext<'_, HttpConnectionManagerConfig>> for PartialHttp
         let always_set_request_id_in_response = configuration.always_set_request_id_in_response;
 
         let mut http_filters_per_route = HashMap::new();
+        let mut scoped_routes = None;
         let (dynamic_route_name, router) = match configuration.route_specifier {
-            RouteSpecifier::Rds(RdsSpecifier {
-                route_config_name,
-                config_source: ConfigSource { config_source_specifier },
-            }) => match config_source_specifier {
-                ConfigSourceSpecifier::ADS => (Some(route_config_name.to_compact_string()), None),
+            // the xDS client subscribes to the route configuration on the stream of its config source
+            RouteSpecifier::Rds(RdsSpecifier { route_config_name, config_source: _ }) => {
+                (Some(route_config_name.to_compact_string()), None)
+            },
+            // and to the route configurations of the scopes, the scopes being discovered over SRDS if not configured
+            RouteSpecifier::ScopedRoutes(config) => {
+                scoped_routes = Some(config);
+                (None, None)
             },
             RouteSpecifier::RouteConfig(config) => {
-                http_filters_per_route = per_route_http_filters(&config, &http_filters_hcm);
+                http_filters_per_route.insert(config.name.clone(), per_route_http_filters(&config, &http_filters_hcm));
                 (None, Some(config))
             },
         };
@@ -296,10 +376,12 @@ impl TryFrom<ConversionContext<'_, HttpConnectionManagerConfig>> for PartialHttp
             router,
             codec_type,
             dynamic_route_name,
+            scoped_routes,
             http_filters_hcm,
             http_filters_per_route,
             enabled_upgrades,
             request_timeout,
+            idle_timeout,
             access_log,
             xff_settings,
             generate_request_id,
@@ -342,10 +424,13 @@ pub struct HttpConnectionManager {
     router_sender: watch::Sender<Option<Arc<RouteConfiguration>>>,
     pub codec_type: CodecType,
     dynamic_route_name: Option<CompactString>,
+    route_scopes: Option<RouteScopes>,
     http_filters_hcm: Vec<Arc<HttpFilter>>,
-    http_filters_per_route: ArcSwap<HashMap<RouteMatch, Vec<Arc<HttpFilter>>>>,
+    /// The filters of the routes of each route configuration in use, by route configuration name.
+    http_filters_per_route: ArcSwap<HashMap<CompactString, RouteFilters>>,
     enabled_upgrades: Vec<UpgradeType>,
     request_timeout: Option<Duration>,
+    pub idle_timeout: Option<Duration>,
     access_log: Vec<AccessLog>,
     xff_settings: XffSettings,
     request_id_handler: RequestIdManager,
@@ -359,6 +444,32 @@ impl fmt::Display for HttpConnectionManager {
 }
 
 impl HttpConnectionManager {
+    /// Health check requests usually have no route of their own, so they are answered before routing.
+    fn answer_health_check<B>(&self, request: &Request<B>) -> Option<Response<PolyBody>> {
+        self.http_filters_hcm.iter().filter(|filter| !filter.disabled).find_map(|filter| match &filter.filter {
+            Some(HttpFilterValue::HealthCheck(health_check)) => match apply_health_check(health_check, request) {
+                FilterDecision::DirectResponse(response) => Some(response),
+                FilterDecision::Continue | FilterDecision::Reroute => None,
+            },
+            _ => None,
+        })
+    }
+
+    /// The on-demand CDS configuration of the first enabled on-demand filter of a route, if any, for the requests it
+    /// routes to a cluster that isn't loaded.
+    fn on_demand_cds(&self, route_config_name: &str, route_match: &RouteMatch) -> Option<OnDemandCds> {
+        let guard = self.http_filters_per_route.load();
+        let route_filters = guard.get(route_config_name)?.get(route_match)?;
+        route_filters.iter().filter(|filter| !filter.disabled).find_map(|filter| match &filter.filter {
+            Some(HttpFilterValue::OnDemand(on_demand)) => Some(on_demand.odcds.clone()),
+            Some(HttpFilterValue::Discovered(discovered)) => discovered.with_config(|filter| match filter {
+                HttpFilterValue::OnDemand(on_demand) => Some(on_demand.odcds.clone()),
+                _ => None,
+            })?,
+            _ => None,
+        })?
+    }
+
     #[inline]
     pub fn get_tracing_key(&self) -> TracingKey {
         TracingKey(self.listener_name, self.filter_chain_match_hash)
@@ -370,7 +481,8 @@ impl HttpConnectionManager {
     }
 
     pub fn update_route(&self, route: RouteConfiguration) {
-        self.http_filters_per_route.swap(Arc::new(per_route_http_filters(&route, &self.http_filters_hcm)));
+        let route_filters = per_route_http_filters(&route, &self.http_filters_hcm);
+        self.http_filters_per_route.swap(Arc::new(HashMap::from([(route.name.clone(), route_filters)])));
         let _ = self.router_sender.send_replace(Some(Arc::new(route)));
     }
 
@@ -378,6 +490,35 @@ impl HttpConnectionManager {
         let _ = self.router_sender.send_replace(None);
     }
 
+    #[inline]
+    pub fn has_route_scopes(&self) -> bool {
+        self.route_scopes.is_some()
+    }
+
+    /// Adds or replaces a route configuration the scopes may route requests with, if routing by scope.
+    pub fn update_scoped_route(&self, id: &str, route: RouteConfiguration) {
+        let Some(route_scopes) = &self.route_scopes else {
+            return;
+        };
+        let route_filters = per_route_http_filters(&route, &self.http_filters_hcm);
+        self.http_filters_per_route.rcu(|filters| {
+            let mut filters = HashMap::clone(filters);
+            filters.insert(route.name.clone(), route_filters.clone());
+            filters
+        });
+        route_scopes.update(id, Arc::new(route));
+    }
+
+    pub fn remove_scoped_route(&self, id: &str) {
+        if self.route_scopes.as_ref().is_some_and(|route_scopes| route_scopes.remove(id)) {
+            self.http_filters_per_route.rcu(|filters| {
+                let mut filters = HashMap::clone(filters);
+                filters.remove(id);
+                filters
+            });
+        }
+    }
+
     pub(crate) fn request_handler(
         self: &Arc<Self>,
     ) -> Box<
@@ -416,6 +557,28 @@ pub struct CachedRoute<'a> {
     vh: &'a VirtualHost,
 }
 
+impl<'a> CachedRoute<'a> {
+    fn matched_request(
+        &mut self,
+        request: Request<BodyWithMetrics<PolyBody>>,
+        route_config_name: &'a str,
+        downstream_metadata: &DownstreamMetadata,
+        websocket_enabled_by_default: bool,
+    ) -> MatchedRequest<'a> {
+        MatchedRequest {
+            request,
+            route_name: &self.route.name,
+            route_config_name,
+            route: &self.route.route_match,
+            retry_policy: self.vh.retry_policy.as_ref(),
+            route_match: std::mem::take(&mut self.route_match),
+            original_destination_address: downstream_metadata.connection.original_destination_address(),
+            remote_address: downstream_metadata.connection.peer_address(),
+            websocket_enabled_by_default,
+        }
+    }
+}
+
 pub(crate) struct HttpRequestHandler {
     manager: Arc<HttpConnectionManager>,
     router: watch::Receiver<Option<Arc<RouteConfiguration>>>,
@@ -454,6 +617,7 @@ pub struct TransactionHandler {
     span_state: Option<Arc<SpanState>>,
     thread_id: ThreadId,
     trans_state: TransactionPhases,
+    upstream_request_attempts: AtomicU32,
 }
 
 #[derive(Debug)]
@@ -482,6 +646,7 @@ impl Default for TransactionHandler {
             span_state: None,
             thread_id: std::thread::current().id(),
             trans_state: TransactionPhases::new(),
+            upstream_request_attempts: AtomicU32::new(0),
         }
     }
 }
@@ -510,6 +675,7 @@ impl TransactionHandler {
             span_state: server_span.map(|span| Arc::new(SpanState::new(Some(span)))),
             thread_id,
             trans_state: TransactionPhases::new(),
+            upstream_request_attempts: AtomicU32::new(0),
         }
     }
 
@@ -518,6 +684,17 @@ impl TransactionHandler {
         self.thread_id
     }
 
+    /// Counts a request sent upstream on behalf of this transaction, be it a retry or an internal redirect.
+    #[inline]
+    pub fn upstream_request_attempted(&self) {
+        self.upstream_request_attempts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn upstream_request_attempts(&self) -> u32 {
+        self.upstream_request_attempts.load(Ordering::Relaxed)
+    }
+
     async fn handle_transaction<RC>(
         self: Arc<Self>,
         route_conf: RC,
@@ -543,7 +720,15 @@ impl TransactionHandler {
         );
 
         // process request, get the response and calcuate the first byte time
-        let result = route_conf.to_response(&self, (request, manager.clone(), downstream_metadata.clone())).await;
+        let result = if overload::should_reject_request() {
+            Ok(SyntheticHttpResponse::service_unavailable(
+                EventKind::Overloaded,
+                ResponseFlags(FmtResponseFlags::OVERLOAD_MANAGER_TERMINATED),
+            )
+            .into_response(request.version()))
+        } else {
+            route_conf.to_response(&self, (request, manager.clone(), downstream_metadata.clone())).await
+        };
         let first_byte_instant = Instant::now();
 
         result.map(|mut response| {
@@ -586,6 +771,7 @@ impl TransactionHandler {
                                 self.start_instant,
                                 ctx_bytes, // bytes received
                                 nbytes,    // bytes sent
+                                self.upstream_request_attempts(),
                                 listener_name,
                                 EventInfo {
                                     body_kind: BodyKind::Response,
@@ -713,6 +899,69 @@ fn select_virtual_host<'a, T>(request: &Request<T>, virtual_hosts: &'a [VirtualH
     virtual_host_with_max_score.map(|(vh, _)| vh)
 }
 
+/// The host virtual hosts are selected by.
+fn request_host<T>(request: &Request<T>) -> Option<&str> {
+    match request.headers().get(::http::header::HOST) {
+        Some(header_value) => header_value.to_str().ok(),
+        None => request.uri().host(),
+    }
+}
+
+/// Asks for the virtual host of a host none of the virtual hosts of a route configuration match, and waits for the
+/// route configuration to be updated with it, or for the management server not to have it, up to the fetch timeout of
+/// the VHDS config source. Returns the route configuration the request is then routed with.
+async fn discover_virtual_host(
+    mut route_conf: Arc<RouteConfiguration>,
+    host: String,
+    mut router: watch::Receiver<Option<Arc<RouteConfiguration>>>,
+) -> Arc<RouteConfiguration> {
+    let Some(vhds) = &route_conf.vhds else {
+        return route_conf;
+    };
+    // requests aren't paused indefinitely, even if the config source waits for its resources until they come
+    let timeout = vhds.config_source.fetch_timeout().unwrap_or(ConfigSource::DEFAULT_INITIAL_FETCH_TIMEOUT);
+    let Some(mut not_found) = crate::on_demand::request_virtual_host(&route_conf.name, &host) else {
+        return route_conf;
+    };
+    let ask = not_found.clone();
+    let not_found = async move {
+        // the virtual host being received leaves it to the route configuration
+        if not_found.wait_for(|not_found| *not_found).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(not_found, deadline);
+    let matches_host = |route_conf: &RouteConfiguration| {
+        route_conf.virtual_hosts.iter().any(|vh| vh.domains.iter().any(|domain| domain.eval_lpm_host(&host).is_some()))
+    };
+    loop {
+        tokio::select! {
+            changed = router.changed() => {
+                if changed.is_err() {
+                    return route_conf;
+                }
+                let Some(updated) = router.borrow_and_update().clone() else {
+                    return route_conf;
+                };
+                route_conf = updated;
+                if matches_host(&route_conf) {
+                    return route_conf;
+                }
+            },
+            () = &mut not_found => {
+                debug!("No virtual host for {host} in route configuration {}", route_conf.name);
+                return route_conf;
+            },
+            () = &mut deadline => {
+                debug!("Timed out waiting {timeout:?} for the virtual host of {host}");
+                crate::on_demand::virtual_host_timed_out(&route_conf.name, &host, &ask);
+                return route_conf;
+            },
+        }
+    }
+}
+
 // has to be a trait due to foreign impl rules.
 pub trait RequestHandler<R>: Sized {
     fn to_response(
@@ -725,11 +974,13 @@ pub trait RequestHandler<R>: Sized {
 #[inline]
 fn match_request_route<'a, B>(request: &Request<B>, route_config: &'a RouteConfiguration) -> Option<CachedRoute<'a>> {
     let chosen_vh = select_virtual_host(request, &route_config.virtual_hosts)?;
-    let (chosen_route, route_match_result) = chosen_vh
-        .routes
-        .iter()
-        .map(|route| (route, route.route_match.match_request(request)))
-        .find(|(_, match_result)| match_result.matched())?;
+    let (chosen_route, route_match_result) =
+        chosen_vh.routes.iter().map(|route| (route, route.route_match.match_request(request))).find(
+            |(route, match_result)| {
+                match_result.matched()
+                    && route.route_match.runtime_fraction.as_ref().is_none_or(layered_runtime::feature_enabled)
+            },
+        )?;
     Some(CachedRoute { route: chosen_route, route_match: route_match_result, vh: chosen_vh })
 }
 
@@ -740,6 +991,7 @@ impl
         Arc<DownstreamMetadata>,
     )> for Arc<RouteConfiguration>
 {
+    #[allow(clippy::too_many_lines)]
     async fn to_response(
         self,
         trans_handler: &TransactionHandler,
@@ -749,6 +1001,10 @@ impl
             Arc<DownstreamMetadata>,
         ),
     ) -> Result<Response<PolyBody>> {
+        if let Some(response) = connection_manager.answer_health_check(&request) {
+            return Ok(response);
+        }
+
         let mut processed_routes: HashSet<RouteMatch> = HashSet::new();
         let mut cached_route = match_request_route(&request, &self);
 
@@ -760,7 +1016,8 @@ impl
                 }
 
                 let guard = connection_manager.http_filters_per_route.load();
-                let route_filters = guard.get(&chosen_route.route.route_match);
+                let route_filters =
+                    guard.get(&self.name).and_then(|route_filters| route_filters.get(&chosen_route.route.route_match));
                 if let Some(route_filters) = route_filters {
                     let mut is_reroute = false;
                     for filter in route_filters {
@@ -797,40 +1054,101 @@ impl
             }
         }
 
-        if let Some(chosen_route) = cached_route {
+        if let Some(mut chosen_route) = cached_route {
             let websocket_enabled_by_default =
                 upgrade_utils::is_websocket_enabled_by_hcm(&connection_manager.enabled_upgrades);
 
-            let mut response = match &chosen_route.route.action {
+            let route_entry = chosen_route.route;
+            let mut response = match &route_entry.action {
                 Action::DirectResponse(dr) => dr.to_response(trans_handler, (request, &chosen_route.route.name)).await,
                 Action::Redirect(rd) => {
                     rd.to_response(trans_handler, (request, chosen_route.route_match, &chosen_route.route.name)).await
                 },
                 Action::Route(route) => {
-                    route
-                        .to_response(
-                            trans_handler,
-                            (
-                                MatchedRequest {
+                    // the request is only kept for internal redirects if its whole body fits in the buffer
+                    let (request, buffered) = if route.internal_redirect_policy.is_some()
+                        && BufferedRequest::can_buffer(&request, route_entry.per_request_buffer_limit_bytes)
+                    {
+                        let scheme = if downstream_metadata.is_tls { Scheme::HTTPS } else { Scheme::HTTP };
+                        BufferedRequest::buffer(request, scheme, route_entry.per_request_buffer_limit_bytes).await?
+                    } else {
+                        (request.map(BodyWithMetrics::map_into), None)
+                    };
+                    let matched_request = chosen_route.matched_request(
+                        request,
+                        &self.name,
+                        &downstream_metadata,
+                        websocket_enabled_by_default,
+                    );
+                    let mut response = route.to_response(trans_handler, (matched_request, &connection_manager)).await?;
+
+                    if let Some(mut buffered) = buffered {
+                        let mut visited_routes = vec![route_entry.name.as_str()];
+                        let mut redirects = 0;
+                        // each redirect is followed as the policy of the route that answered it allows
+                        while let Action::Route(RouteAction { internal_redirect_policy: Some(policy), .. }) =
+                            &chosen_route.route.action
+                        {
+                            let Some(location) = internal_redirect::redirect_location(policy, &response) else {
+                                break;
+                            };
+                            if redirects >= policy.max_internal_redirects {
+                                break;
+                            }
+                            let redirected = buffered.redirected(
+                                &location,
+                                response.status(),
+                                &policy.response_headers_to_copy,
+                                response.headers(),
+                            )?;
+                            let request = redirected.to_request();
+                            let Some(mut next_route) = match_request_route(&request, &self) else {
+                                break;
+                            };
+                            let next_entry = next_route.route;
+                            let Action::Route(next_action) = &next_entry.action else {
+                                break;
+                            };
+                            let target_scheme = location.scheme().unwrap_or(&Scheme::HTTP);
+                            let previously_visited = visited_routes.contains(&next_entry.name.as_str());
+                            if !policy.allows_target(
+                                &next_entry.name,
+                                previously_visited,
+                                buffered.downstream_scheme(),
+                                target_scheme,
+                            ) {
+                                break;
+                            }
+                            debug!("Following internal redirect to {location} over route {}", next_entry.name);
+                            buffered = redirected;
+                            redirects += 1;
+                            visited_routes.push(next_entry.name.as_str());
+                            response = if let Some(direct_response) = route_filter_response(
+                                &connection_manager,
+                                &self.name,
+                                &next_entry.route_match,
+                                &request,
+                            ) {
+                                direct_response
+                            } else {
+                                let matched_request = next_route.matched_request(
                                     request,
-                                    route_name: &chosen_route.route.name,
-                                    retry_policy: chosen_route.vh.retry_policy.as_ref(),
-                                    route_match: chosen_route.route_match,
-                                    original_destination_address: downstream_metadata
-                                        .connection
-                                        .original_destination_address(),
-                                    remote_address: downstream_metadata.connection.peer_address(),
+                                    &self.name,
+                                    &downstream_metadata,
                                     websocket_enabled_by_default,
-                                },
-                                &connection_manager,
-                            ),
-                        )
-                        .await
+                                );
+                                next_action.to_response(trans_handler, (matched_request, &connection_manager)).await?
+                            };
+                            chosen_route = next_route;
+                        }
+                    }
+                    Ok(response)
                 },
             }?;
 
             let guard = connection_manager.http_filters_per_route.load();
-            let route_filters = guard.get(&chosen_route.route.route_match);
+            let route_filters =
+                guard.get(&self.name).and_then(|route_filters| route_filters.get(&chosen_route.route.route_match));
             if let Some(route_filters) = route_filters {
                 for filter in route_filters.iter().rev() {
                     if filter.disabled {
@@ -919,6 +1237,7 @@ impl Service<ExtendedRequest<Incoming>> for HttpRequestHandler {
         let req_timeout = self.manager.request_timeout;
         let listener_name = self.manager.listener_name;
         let route_conf = self.router.borrow().clone();
+        let router = self.router.clone();
         let manager = Arc::clone(&self.manager);
 
         with_metric!(
@@ -1001,6 +1320,7 @@ impl Service<ExtendedRequest<Incoming>> for HttpRequestHandler {
                                 trans_handler.start_instant,
                                 nbytes,    // bytes received
                                 ctx_bytes, // bytes sent
+                                trans_handler.upstream_request_attempts(),
                                 listener_name,
                                 EventInfo {
                                     body_kind: BodyKind::Request,
@@ -1028,6 +1348,12 @@ impl Service<ExtendedRequest<Incoming>> for HttpRequestHandler {
                 })
             });
 
+            // a connection manager routing by scope routes the request with the route configuration of its scope
+            let route_conf = match &manager.route_scopes {
+                Some(route_scopes) => route_scopes.route_configuration(&request).await,
+                None => route_conf,
+            };
+
             let Some(route_conf) = route_conf else {
                 // immediately return a SyntheticHttpResponse, and calcuate the first byte instant
                 let resp = SyntheticHttpResponse::not_found(
@@ -1087,6 +1413,7 @@ impl Service<ExtendedRequest<Incoming>> for HttpRequestHandler {
                                     trans_handler.start_instant,
                                     ctx_bytes, // bytes received
                                     nbytes,    // bytes sent
+                                    trans_handler.upstream_request_attempts(),
                                     listener_name,
                                     EventInfo {
                                         body_kind: BodyKind::Response,
@@ -1116,10 +1443,30 @@ impl Service<ExtendedRequest<Incoming>> for HttpRequestHandler {
                 return Ok(response);
             };
 
-            let response = trans_handler
-                .clone()
-                .handle_transaction(route_conf, manager, permit, request, downstream_metadata)
-                .await;
+            // the virtual host of a host none of the virtual hosts match may be discovered on demand, the route
+            // configurations of scopes aside
+            let route_conf = match request_host(&request) {
+                Some(host)
+                    if route_conf.vhds.is_some()
+                        && manager.route_scopes.is_none()
+                        && select_virtual_host(&request, &route_conf.virtual_hosts).is_none() =>
+                {
+                    discover_virtual_host(route_conf, host.to_owned(), router).await
+                },
+                _ => route_conf,
+            };
+
+            // register the stream with the overload manager, so that it can be reset under memory pressure
+            let stream_tracker = overload::track_stream(Arc::clone(&request.body().state));
+            let transaction =
+                trans_handler.clone().handle_transaction(route_conf, manager, permit, request, downstream_metadata);
+            let response = match stream_tracker {
+                Some(tracker) => tokio::select! {
+                    response = transaction => response,
+                    () = tracker.reset_requested() => Err("stream reset by overload manager".into()),
+                },
+                None => transaction.await,
+            };
 
             trans_handler.trace_status_code(response, listener_name_for_trace)
         })
@@ -1147,6 +1494,7 @@ fn eval_http_finish_context(
     trans_start_time: Instant,
     bytes_received: u64,
     bytes_sent: u64,
+    upstream_request_attempt_count: u32,
     listener_name: &'static str,
     event: EventInfo,
     permit: Arc<Mutex<Option<Permit<'static, AccessLogMessage>>>>,
@@ -1155,6 +1503,7 @@ fn eval_http_finish_context(
         duration: trans_start_time.elapsed(),
         bytes_received,
         bytes_sent,
+        upstream_request_attempt_count,
         response_flags: event.response_flags.0,
         upstream_failure: event.event_kind.as_ref().and_then(|ev| {
             let EventKind::Error(err) = ev else {
@@ -1175,6 +1524,44 @@ fn eval_http_finish_context(
     log_access(permit, Target::Listener(listener_name.to_compact_string()), messages);
 }
 
+/// The response of the first request filter of a route that answers the request itself, if any.
+fn route_filter_response<B>(
+    connection_manager: &HttpConnectionManager,
+    route_config_name: &str,
+    route: &RouteMatch,
+    request: &Request<B>,
+) -> Option<Response<PolyBody>> {
+    let guard = connection_manager.http_filters_per_route.load();
+    let route_filters = guard.get(route_config_name).and_then(|route_filters| route_filters.get(route))?;
+    route_filters.iter().filter(|filter| !filter.disabled).filter_map(|filter| filter.filter.as_ref()).find_map(
+        |filter| match filter.apply_request(request) {
+            FilterDecision::DirectResponse(response) => Some(response),
+            FilterDecision::Continue | FilterDecision::Reroute => None,
+        },
+    )
+}
+
+/// Answers health check requests: with a 503 once the health check has been failed through the admin interface,
+/// with a 200 otherwise, unless in pass through mode where they are routed like any other request.
+fn apply_health_check<B>(health_check: &HealthCheck, req: &Request<B>) -> FilterDecision {
+    if !health_check.is_health_check(req) {
+        return FilterDecision::Continue;
+    }
+    if lifecycle::health_check_failed() {
+        FilterDecision::DirectResponse(
+            SyntheticHttpResponse::service_unavailable(EventKind::HealthCheckFailed, ResponseFlags::default())
+                .into_response(req.version()),
+        )
+    } else if health_check.pass_through_mode {
+        FilterDecision::Continue
+    } else {
+        FilterDecision::DirectResponse(
+            SyntheticHttpResponse::custom_error(StatusCode::OK, EventKind::HealthCheckOk, ResponseFlags::default())
+                .into_response(req.version()),
+        )
+    }
+}
+
 fn apply_authorization_rules<B>(rbac: &HttpRbac, req: &Request<B>) -> FilterDecision {
     debug!("Applying authorization rules {rbac:?} {:?}", &req.headers());
     let (permitted, enforced_policy) = rbac.is_permitted(req);
@@ -1232,4 +1619,263 @@ mod tests {
         let request = Request::builder().header("host", "domain2.com").body(()).unwrap();
         assert_eq!(select_virtual_host(&request, &[vh1.clone(), vh2.clone(), vh3.clone()]), None);
     }
+
+    #[test]
+    fn health_check_filter() {
+        let status = |decision: FilterDecision| match decision {
+            FilterDecision::DirectResponse(response) => Some(response.status()),
+            FilterDecision::Continue | FilterDecision::Reroute => None,
+        };
+        let answered = HealthCheck { pass_through_mode: false, headers: vec![] };
+        let pass_through = HealthCheck { pass_through_mode: true, headers: vec![] };
+        let request = Request::builder().uri("/healthz").body(()).unwrap();
+
+        assert_eq!(status(apply_health_check(&answered, &request)), Some(StatusCode::OK));
+        assert_eq!(status(apply_health_check(&pass_through, &request)), None);
+
+        lifecycle::set_health_check_failed(true);
+        assert_eq!(status(apply_health_check(&answered, &request)), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(status(apply_health_check(&pass_through, &request)), Some(StatusCode::SERVICE_UNAVAILABLE));
+        lifecycle::set_health_check_failed(false);
+    }
+
+    mod internal_redirects {
+        use super::*;
+        use crate::{
+            clusters::{cluster::PartialClusterType, clusters_manager},
+            listeners::filter_state::DownstreamConnectionMetadata,
+            SecretManager,
+        };
+        use ::http::{
+            header::{HOST, LOCATION},
+            HeaderMap, Method,
+        };
+        use bytes::Bytes;
+        use http_body_util::{BodyExt, Full};
+        use hyper::{client::conn::http1 as client_http1, server::conn::http1 as server_http1, service::service_fn};
+        use hyper_util::rt::TokioIo;
+        use orion_configuration::config::cluster::Cluster as ClusterConfig;
+        use orion_data_plane_api::{
+            decode::from_yaml,
+            envoy_data_plane_api::envoy::{
+                config::cluster::v3::Cluster as EnvoyCluster,
+                extensions::filters::network::http_connection_manager::v3::HttpConnectionManager as EnvoyHttpConnectionManager,
+            },
+        };
+        use orion_http_header::X_ENVOY_ORIGINAL_URL;
+        use std::{convert::Infallible, net::SocketAddr};
+        use tokio::net::{TcpListener, TcpStream};
+
+        const CONNECTION_MANAGER: &str = r#"
+stat_prefix: internal_redirects
+http_filters:
+- name: envoy.filters.http.router
+  typed_config:
+    "@type": type.googleapis.com/envoy.extensions.filters.http.router.v3.Router
+route_config:
+  name: internal_redirects
+  virtual_hosts:
+  - name: redirect_test
+    domains: ["*"]
+    routes:
+    - name: moved
+      match: { prefix: /moved }
+      route:
+        cluster: internal_redirect_upstream
+        internal_redirect_policy: {}
+    - name: small
+      match: { prefix: /small }
+      per_request_buffer_limit_bytes: 4
+      route:
+        cluster: internal_redirect_upstream
+        internal_redirect_policy: {}
+    - name: loop
+      match: { prefix: /loop/ }
+      route:
+        cluster: internal_redirect_upstream
+        internal_redirect_policy: { max_internal_redirects: 2 }
+    - name: first
+      match: { prefix: /first }
+      route:
+        cluster: internal_redirect_upstream
+        internal_redirect_policy: &previous_routes
+          max_internal_redirects: 5
+          predicates:
+          - name: previous_routes
+            typed_config:
+              "@type": type.googleapis.com/envoy.extensions.internal_redirect.previous_routes.v3.PreviousRoutesConfig
+    - name: second
+      match: { prefix: /second }
+      route:
+        cluster: internal_redirect_upstream
+        internal_redirect_policy: *previous_routes
+    - name: target
+      match: { prefix: /target }
+      route:
+        cluster: internal_redirect_upstream
+"#;
+
+        /// An upstream redirecting `/moved` and `/small` to `/target`, each `/loop/<n>` to the next one, and
+        /// `/first` and `/second` to one another. `/target` answers with the body it was sent.
+        async fn redirecting_upstream() -> (u16, Arc<Mutex<Vec<String>>>) {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let paths = Arc::new(Mutex::new(Vec::new()));
+            let requested = Arc::clone(&paths);
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let requested = Arc::clone(&requested);
+                    let service = service_fn(move |request: Request<Incoming>| {
+                        let requested = Arc::clone(&requested);
+                        async move {
+                            let path = request.uri().path().to_owned();
+                            requested.lock().push(path.clone());
+                            let location = match path.as_str() {
+                                "/moved" | "/small" => "/target".to_owned(),
+                                "/first" => "/second".to_owned(),
+                                "/second" => "/first".to_owned(),
+                                "/target" => {
+                                    let original_url = request.headers().get(X_ENVOY_ORIGINAL_URL).cloned();
+                                    let body = request.into_body().collect().await.unwrap().to_bytes();
+                                    let mut response = Response::new(Full::new(body));
+                                    if let Some(original_url) = original_url {
+                                        response.headers_mut().insert(X_ENVOY_ORIGINAL_URL, original_url);
+                                    }
+                                    return Ok::<_, Infallible>(response);
+                                },
+                                _ => {
+                                    let hop: u32 = path.trim_start_matches("/loop/").parse().unwrap();
+                                    format!("/loop/{}", hop + 1)
+                                },
+                            };
+                            let response = Response::builder()
+                                .status(StatusCode::FOUND)
+                                .header(LOCATION, format!("http://redirect.test{location}"))
+                                .body(Full::new(Bytes::new()))
+                                .unwrap();
+                            Ok(response)
+                        }
+                    });
+                    tokio::spawn(server_http1::Builder::new().serve_connection(TokioIo::new(stream), service));
+                }
+            });
+            (port, paths)
+        }
+
+        fn add_upstream_cluster(port: u16) {
+            let cluster = format!(
+                r#"
+name: internal_redirect_upstream
+type: STATIC
+load_assignment:
+  endpoints:
+  - lb_endpoints:
+    - endpoint:
+        address:
+          socket_address:
+            address: 127.0.0.1
+            port_value: {port}
+"#
+            );
+            let envoy_cluster: EnvoyCluster = from_yaml(&cluster).unwrap();
+            let cluster = ClusterConfig::try_from(envoy_cluster).unwrap();
+            let secret_manager = SecretManager::new();
+            clusters_manager::add_cluster(PartialClusterType::try_from((cluster, &secret_manager)).unwrap()).unwrap();
+        }
+
+        /// A downstream handing its requests to the route configuration of the connection manager, which answers
+        /// with the number of upstream requests attempted in `x-attempts`.
+        async fn downstream() -> SocketAddr {
+            let envoy_hcm: EnvoyHttpConnectionManager = from_yaml(CONNECTION_MANAGER).unwrap();
+            let config = HttpConnectionManagerConfig::try_from(envoy_hcm).unwrap();
+            let secret_manager = SecretManager::new();
+            let manager = HttpConnectionManagerBuilder::try_from(ConversionContext::new((config, &secret_manager)))
+                .unwrap()
+                .with_listener_name("internal_redirects")
+                .build()
+                .map(Arc::new)
+                .unwrap();
+            let route_conf = manager.router_sender.borrow().clone().unwrap();
+
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let local_address = listener.local_addr().unwrap();
+            tokio::spawn(async move {
+                while let Ok((stream, peer_address)) = listener.accept().await {
+                    let (manager, route_conf) = (Arc::clone(&manager), Arc::clone(&route_conf));
+                    let service = service_fn(move |request: Request<Incoming>| {
+                        let (manager, route_conf) = (Arc::clone(&manager), Arc::clone(&route_conf));
+                        let connection = DownstreamConnectionMetadata::Socket {
+                            peer_address,
+                            local_address,
+                            original_destination_address: None,
+                        };
+                        let downstream_metadata = Arc::new(DownstreamMetadata::new(connection, None::<&str>));
+                        async move {
+                            let trans_handler = TransactionHandler::default();
+                            let request = request.map(|body| {
+                                BodyWithMetrics::new(BodyKind::Request, BodyWithTimeout::new(None, body), |_, _, _| {})
+                            });
+                            let mut response = route_conf
+                                .to_response(&trans_handler, (request, manager, downstream_metadata))
+                                .await
+                                .map_err(orion_error::Error::into_inner)?;
+                            let attempts = HeaderValue::from(trans_handler.upstream_request_attempts());
+                            response.headers_mut().insert("x-attempts", attempts);
+                            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(response)
+                        }
+                    });
+                    tokio::spawn(server_http1::Builder::new().serve_connection(TokioIo::new(stream), service));
+                }
+            });
+            local_address
+        }
+
+        async fn send(address: SocketAddr, path: &str, body: &'static str) -> (StatusCode, HeaderMap, Bytes) {
+            let stream = TcpStream::connect(address).await.unwrap();
+            let (mut sender, connection) = client_http1::handshake(TokioIo::new(stream)).await.unwrap();
+            tokio::spawn(connection);
+            let request = Request::builder()
+                .method(if body.is_empty() { Method::GET } else { Method::POST })
+                .uri(path)
+                .header(HOST, "redirect.test")
+                .header(X_ENVOY_ORIGINAL_URL, "http://spoofed.test/")
+                .body(Full::new(Bytes::from_static(body.as_bytes())))
+                .unwrap();
+            let (parts, body) = sender.send_request(request).await.unwrap().into_parts();
+            (parts.status, parts.headers, body.collect().await.unwrap().to_bytes())
+        }
+
+        #[tokio::test]
+        async fn redirects_followed_as_the_policy_allows() {
+            let (port, upstream_paths) = redirecting_upstream().await;
+            add_upstream_cluster(port);
+            let address = downstream().await;
+            let requested = || std::mem::take(&mut *upstream_paths.lock());
+
+            let (status, headers, body) = send(address, "/moved", "data").await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body, "data", "the body is sent again");
+            assert_eq!(headers[X_ENVOY_ORIGINAL_URL], "http://redirect.test/moved", "the client one is replaced");
+            assert_eq!(headers["x-attempts"], "2");
+            assert_eq!(requested(), ["/moved", "/target"]);
+
+            let (status, headers, _) = send(address, "/loop/0", "").await;
+            assert_eq!(status, StatusCode::FOUND, "no more than max_internal_redirects are followed");
+            assert_eq!(headers[LOCATION], "http://redirect.test/loop/3");
+            assert_eq!(headers["x-attempts"], "3");
+            assert_eq!(requested(), ["/loop/0", "/loop/1", "/loop/2"]);
+
+            let (status, headers, _) = send(address, "/first", "").await;
+            assert_eq!(status, StatusCode::FOUND, "the first route was already visited");
+            assert_eq!(headers[LOCATION], "http://redirect.test/first");
+            assert_eq!(headers["x-attempts"], "2");
+            assert_eq!(requested(), ["/first", "/second"]);
+
+            let (status, headers, _) = send(address, "/small", "too large").await;
+            assert_eq!(status, StatusCode::FOUND, "a body over the buffer limit is not kept for redirects");
+            assert_eq!(headers[LOCATION], "http://redirect.test/target");
+            assert_eq!(headers["x-attempts"], "1");
+            assert_eq!(requested(), ["/small"]);
+        }
+    }
 }
@@ -17,9 +17,9 @@
 
 use super::upgrade_utils;
 use crate::{event_error::EventKind, listeners::synthetic_http_response::SyntheticHttpResponse, PolyBody};
-use http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, Response};
+use http::{header, uri::Authority, HeaderMap, HeaderName, HeaderValue, Method, Request, Response, Uri};
 use orion_configuration::config::network_filters::http_connection_manager::XffSettings;
-use orion_http_header::{X_ENVOY_EXTERNAL_ADDRESS, X_ENVOY_INTERNAL, X_FORWARDED_FOR};
+use orion_http_header::{X_ENVOY_EXTERNAL_ADDRESS, X_ENVOY_INTERNAL, X_FORWARDED_FOR, X_FORWARDED_HOST};
 use std::net::{IpAddr, SocketAddr};
 
 const HOP_BY_HOP_HEADERS: &[HeaderName] = &[
@@ -116,6 +116,24 @@ fn process_xff_headers<T>(request: &mut Request<T>, downstream_addr: SocketAddr,
     }
 }
 
+/// Appends the host the request was sent to to `x-forwarded-host`, before its authority gets rewritten.
+pub fn append_x_forwarded_host(uri: &Uri, headers: &mut HeaderMap) {
+    let Some(host) = uri
+        .authority()
+        .map(Authority::as_str)
+        .or_else(|| headers.get(header::HOST).and_then(|value| value.to_str().ok()))
+    else {
+        return;
+    };
+    let forwarded_host = match headers.get(X_FORWARDED_HOST).and_then(|value| value.to_str().ok()) {
+        Some(existing) if !existing.is_empty() => format!("{existing}, {host}"),
+        _ => host.to_owned(),
+    };
+    if let Ok(forwarded_host) = HeaderValue::from_str(&forwarded_host) {
+        headers.insert(X_FORWARDED_HOST, forwarded_host);
+    }
+}
+
 fn is_internal_ip(ip: IpAddr) -> bool {
     match ip {
         IpAddr::V4(ipv4) => ipv4.is_private() || ipv4.is_loopback(),
@@ -276,4 +294,19 @@ mod tests {
         assert_eq!(request.headers().get("x-forwarded-for").unwrap(), "10.20.30.40");
         //assert_eq!(request.headers().get("x-envoy-internal").unwrap(), "true");
     }
+
+    #[test]
+    fn test_original_host_appended_to_x_forwarded_host() {
+        let mut request = Request::get("/orders").body(()).unwrap();
+        request.headers_mut().insert("host", "shop.example.com".parse().unwrap());
+        let (parts, ()) = request.into_parts();
+        let mut headers = parts.headers;
+
+        append_x_forwarded_host(&parts.uri, &mut headers);
+        assert_eq!(headers.get("x-forwarded-host").unwrap(), "shop.example.com");
+
+        let uri: Uri = "http://edge.example.com/orders".parse().unwrap();
+        append_x_forwarded_host(&uri, &mut headers);
+        assert_eq!(headers.get("x-forwarded-host").unwrap(), "shop.example.com, edge.example.com");
+    }
 }
@@ -0,0 +1,267 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use crate::{
+    body::{
+        body_with_metrics::BodyWithMetrics, body_with_timeout::BodyWithTimeout, read_ahead::ReadAhead,
+        response_flags::BodyKind,
+    },
+    PolyBody, Result,
+};
+use bytes::{Bytes, BytesMut};
+use http::{
+    header::{CONTENT_LENGTH, HOST, LOCATION, TRANSFER_ENCODING, UPGRADE},
+    request::Parts,
+    uri::Scheme,
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri,
+};
+use http_body::{Body, Frame};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Incoming, Request, Response};
+use orion_configuration::config::network_filters::http_connection_manager::internal_redirect::InternalRedirectPolicy;
+use orion_error::Context;
+use orion_http_header::X_ENVOY_ORIGINAL_URL;
+
+/// How much of a request body is kept for internal redirects when its route does not say, the buffer limit envoy
+/// gives a connection by default.
+const DEFAULT_BUFFER_LIMIT_BYTES: u32 = 1024 * 1024;
+
+/// A request kept aside to be sent again to wherever its upstream redirects it.
+pub struct BufferedRequest {
+    parts: Parts,
+    body: Bytes,
+    scheme: Scheme,
+    /// the URL the request was first sent to, once it has been redirected
+    original_url: Option<HeaderValue>,
+}
+
+impl BufferedRequest {
+    /// Whether the request may be kept: upgrades never are, nor the bodies known to be larger than the buffer limit.
+    pub fn can_buffer<B: Body>(request: &Request<BodyWithMetrics<B>>, limit: Option<u32>) -> bool {
+        !request.headers().contains_key(UPGRADE) && request.body().inner.size_hint().lower() <= buffer_limit(limit)
+    }
+
+    /// Buffers a request received over a connection of `scheme`, reading its body up to the buffer limit whether or
+    /// not its length is known. The request to send upstream is given back along with the one kept, and on its own
+    /// when its body turns out to be larger than the limit, the part of the body already read sent ahead of the rest.
+    pub async fn buffer(
+        request: Request<BodyWithMetrics<BodyWithTimeout<Incoming>>>,
+        scheme: Scheme,
+        limit: Option<u32>,
+    ) -> Result<(Request<BodyWithMetrics<PolyBody>>, Option<Self>)> {
+        let (parts, body) = request.into_parts();
+        let mut body = body.map_into::<PolyBody>();
+        match read_up_to(&mut body, buffer_limit(limit)).await? {
+            Ok(body) => {
+                let buffered = Self { parts, body, scheme, original_url: None };
+                Ok((buffered.to_request(), Some(buffered)))
+            },
+            Err(read) => {
+                // the downstream body is still accounted for by its own metrics, the frames read included
+                let body = BodyWithMetrics::new(BodyKind::Request, ReadAhead::new(read, body).into(), |_, _, _| {});
+                Ok((Request::from_parts(parts, body), None))
+            },
+        }
+    }
+
+    pub fn to_request(&self) -> Request<BodyWithMetrics<PolyBody>> {
+        // the downstream body was already accounted for when it was buffered
+        let body = BodyWithMetrics::new(BodyKind::Request, Full::new(self.body.clone()).into(), |_, _, _| {});
+        Request::from_parts(self.parts.clone(), body)
+    }
+
+    /// The scheme of the downstream connection the request was received over.
+    pub fn downstream_scheme(&self) -> &Scheme {
+        &self.scheme
+    }
+
+    fn url(&self) -> String {
+        let authority = self.parts.headers.get(HOST).and_then(|host| host.to_str().ok());
+        let authority = authority.or_else(|| self.parts.uri.authority().map(http::uri::Authority::as_str));
+        let path_and_query = self.parts.uri.path_and_query().map_or("/", http::uri::PathAndQuery::as_str);
+        format!("{}://{}{path_and_query}", self.scheme, authority.unwrap_or_default())
+    }
+
+    /// The request sent to `location` once the upstream answered it with a redirect of `status`: a 303 turns it
+    /// into a GET without a body, and the URL it was first sent to is kept in `x-envoy-original-url`.
+    pub fn redirected(
+        &self,
+        location: &Uri,
+        status: StatusCode,
+        headers_to_copy: &[HeaderName],
+        response_headers: &HeaderMap,
+    ) -> Result<Self> {
+        let mut parts = self.parts.clone();
+        let mut body = self.body.clone();
+        // whatever the client sent in x-envoy-original-url is replaced on the first redirect
+        let original_url = match &self.original_url {
+            Some(original_url) => original_url.clone(),
+            None => {
+                HeaderValue::from_str(&self.url()).with_context_msg("failed to create x-envoy-original-url value")?
+            },
+        };
+        parts.headers.insert(X_ENVOY_ORIGINAL_URL, original_url.clone());
+        if let Some(authority) = location.authority() {
+            let host =
+                HeaderValue::from_str(authority.as_str()).with_context_msg("failed to create Host header value")?;
+            parts.headers.insert(HOST, host);
+        }
+        parts.uri = if parts.uri.authority().is_some() {
+            location.clone()
+        } else {
+            location.path_and_query().cloned().map_or_else(|| Uri::from_static("/"), Uri::from)
+        };
+        if status == StatusCode::SEE_OTHER && parts.method != Method::HEAD {
+            parts.method = Method::GET;
+            parts.headers.remove(CONTENT_LENGTH);
+            parts.headers.remove(TRANSFER_ENCODING);
+            body = Bytes::new();
+        }
+        for name in headers_to_copy {
+            parts.headers.remove(name);
+            for value in response_headers.get_all(name) {
+                parts.headers.append(name.clone(), value.clone());
+            }
+        }
+        Ok(Self { parts, body, scheme: self.scheme.clone(), original_url: Some(original_url) })
+    }
+}
+
+fn buffer_limit(limit: Option<u32>) -> u64 {
+    u64::from(limit.unwrap_or(DEFAULT_BUFFER_LIMIT_BYTES))
+}
+
+/// Reads a body up to `limit` bytes: the whole of it if it fits, or else the frames read until it went over.
+async fn read_up_to<B>(
+    body: &mut B,
+    limit: u64,
+) -> std::result::Result<std::result::Result<Bytes, Vec<Frame<Bytes>>>, B::Error>
+where
+    B: Body<Data = Bytes> + Unpin,
+{
+    let mut read = Vec::new();
+    let mut size = 0;
+    while let Some(frame) = body.frame().await {
+        let frame = frame?;
+        size += frame.data_ref().map_or(0, |data| data.len() as u64);
+        read.push(frame);
+        if size > limit {
+            return Ok(Err(read));
+        }
+    }
+    let mut data = BytesMut::new();
+    for frame in read {
+        if let Ok(chunk) = frame.into_data() {
+            data.extend_from_slice(&chunk);
+        }
+    }
+    Ok(Ok(data.freeze()))
+}
+
+/// The location an upstream response redirects to, if the policy follows it. It has to be an absolute http(s) URL.
+pub fn redirect_location<B>(policy: &InternalRedirectPolicy, response: &Response<B>) -> Option<Uri> {
+    if !policy.redirects_on(response.status()) {
+        return None;
+    }
+    let location: Uri = response.headers().get(LOCATION)?.to_str().ok()?.parse().ok()?;
+    let is_http = location.scheme().is_some_and(|scheme| *scheme == Scheme::HTTP || *scheme == Scheme::HTTPS);
+    (is_http && location.authority().is_some()).then_some(location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::StreamBody;
+    use std::convert::Infallible;
+
+    /// A body sent in chunks, without a length.
+    fn chunked(chunks: &[&'static [u8]]) -> impl Body<Data = Bytes, Error = Infallible> + Unpin {
+        let frames: Vec<_> =
+            chunks.iter().map(|chunk| Ok::<_, Infallible>(Frame::data(Bytes::from_static(chunk)))).collect();
+        StreamBody::new(futures::stream::iter(frames))
+    }
+
+    #[tokio::test]
+    async fn chunked_bodies_buffered_up_to_the_limit() {
+        let mut body = chunked(&[b"chunk", b"ed ", b"body"]);
+        assert_eq!(body.size_hint().exact(), None);
+        assert_eq!(read_up_to(&mut body, 13).await.unwrap().unwrap(), Bytes::from_static(b"chunked body"));
+
+        let mut body = chunked(&[b"chunk", b"ed ", b"body"]);
+        let read = read_up_to(&mut body, 6).await.unwrap().unwrap_err();
+        let read: Vec<_> = read.into_iter().filter_map(|frame| frame.into_data().ok()).collect();
+        assert_eq!(read, [Bytes::from_static(b"chunk"), Bytes::from_static(b"ed ")]);
+        let rest = ReadAhead::new(read.into_iter().map(Frame::data), body).collect().await.unwrap().to_bytes();
+        assert_eq!(rest, Bytes::from_static(b"chunked body"), "nothing read is lost when the body is too large");
+    }
+
+    #[test]
+    fn redirected_to_the_location_as_a_get_on_see_other() {
+        let (parts, ()) = Request::builder()
+            .method(Method::POST)
+            .uri("/legacy/upload?id=7")
+            .header(HOST, "legacy.example.com")
+            .header(CONTENT_LENGTH, "4")
+            .header(X_ENVOY_ORIGINAL_URL, "http://spoofed.example.com/")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let buffered =
+            BufferedRequest { parts, body: Bytes::from_static(b"data"), scheme: Scheme::HTTP, original_url: None };
+        let location: Uri = "http://api.example.com/v2/upload?id=7".parse().unwrap();
+        let mut response_headers = HeaderMap::new();
+        response_headers.insert("x-session", HeaderValue::from_static("abc"));
+        let copied = [HeaderName::from_static("x-session")];
+
+        let redirected =
+            buffered.redirected(&location, StatusCode::TEMPORARY_REDIRECT, &copied, &response_headers).unwrap();
+        assert_eq!(redirected.parts.method, Method::POST);
+        assert_eq!(redirected.body, Bytes::from_static(b"data"));
+        assert_eq!(redirected.parts.uri, "/v2/upload?id=7");
+        assert_eq!(redirected.parts.headers[HOST], "api.example.com");
+        assert_eq!(redirected.parts.headers["x-session"], "abc");
+        assert_eq!(redirected.parts.headers[X_ENVOY_ORIGINAL_URL], "http://legacy.example.com/legacy/upload?id=7");
+
+        let location: Uri = "http://other.example.com/done".parse().unwrap();
+        let again = redirected.redirected(&location, StatusCode::SEE_OTHER, &[], &HeaderMap::new()).unwrap();
+        assert_eq!(again.parts.method, Method::GET);
+        assert!(again.body.is_empty());
+        assert!(!again.parts.headers.contains_key(CONTENT_LENGTH));
+        assert_eq!(
+            again.parts.headers[X_ENVOY_ORIGINAL_URL], "http://legacy.example.com/legacy/upload?id=7",
+            "the original url is the first one"
+        );
+    }
+
+    #[test]
+    fn only_absolute_locations_of_the_policy_codes_followed() {
+        let policy = InternalRedirectPolicy::default();
+        let redirect = |status: StatusCode, location: &'static str| {
+            Response::builder().status(status).header(LOCATION, location).body(()).unwrap()
+        };
+        assert_eq!(
+            redirect_location(&policy, &redirect(StatusCode::FOUND, "http://api.example.com/v2")),
+            Some("http://api.example.com/v2".parse().unwrap())
+        );
+        assert_eq!(redirect_location(&policy, &redirect(StatusCode::FOUND, "/v2")), None);
+        assert_eq!(redirect_location(&policy, &redirect(StatusCode::FOUND, "ftp://api.example.com/v2")), None);
+        assert_eq!(
+            redirect_location(&policy, &redirect(StatusCode::MOVED_PERMANENTLY, "http://api.example.com")),
+            None
+        );
+    }
+}
@@ -17,31 +17,33 @@
 use super::{http_modifiers, upgrades as upgrade_utils, RequestHandler, TransactionHandler};
 use crate::event_error::{EventError, EventKind, TryInferFrom};
 use crate::{
-    body::{body_with_metrics::BodyWithMetrics, body_with_timeout::BodyWithTimeout, response_flags::ResponseFlags},
+    body::{body_with_metrics::BodyWithMetrics, response_flags::ResponseFlags},
     clusters::{
         balancers::hash_policy::HashState,
         clusters_manager::{self, RoutingContext},
+        load_report,
     },
     listeners::{
         access_log::AccessLogContext, http_connection_manager::HttpConnectionManager,
         synthetic_http_response::SyntheticHttpResponse,
     },
+    on_demand,
     transport::policy::{RequestContext, RequestExt},
     PolyBody, Result,
 };
 
 use http::{uri::Parts as UriParts, Uri};
-use hyper::{body::Incoming, Request, Response};
+use hyper::{Request, Response};
 use opentelemetry::trace::Span;
 use opentelemetry::KeyValue;
 use orion_configuration::config::network_filters::http_connection_manager::{
-    route::{RouteAction, RouteMatchResult},
+    route::{RouteAction, RouteMatch, RouteMatchResult},
     RetryPolicy,
 };
 use orion_error::Context;
 use orion_format::{
     context::{UpstreamContext, UpstreamRequest},
-    types::{ResponseFlagsLong, ResponseFlagsShort},
+    types::{ResponseFlags as FmtResponseFlags, ResponseFlagsLong, ResponseFlagsShort},
 };
 use orion_tracing::attributes::{UPSTREAM_ADDRESS, UPSTREAM_CLUSTER_NAME};
 use orion_tracing::http_tracer::{SpanKind, SpanName};
@@ -50,9 +52,12 @@ use std::net::SocketAddr;
 use tracing::{debug, info};
 
 pub struct MatchedRequest<'a> {
-    pub request: Request<BodyWithMetrics<BodyWithTimeout<Incoming>>>,
+    pub request: Request<BodyWithMetrics<PolyBody>>,
     pub retry_policy: Option<&'a RetryPolicy>,
     pub route_name: &'a str,
+    /// The route configuration and the route the request matched, which its per-route filters are looked up by.
+    pub route_config_name: &'a str,
+    pub route: &'a RouteMatch,
     pub remote_address: SocketAddr,
     pub route_match: RouteMatchResult,
     pub websocket_enabled_by_default: bool,
@@ -69,6 +74,8 @@ impl<'a> RequestHandler<(MatchedRequest<'a>, &HttpConnectionManager)> for &Route
         let MatchedRequest {
             request: downstream_request,
             route_name,
+            route_config_name,
+            route,
             retry_policy,
             remote_address,
             route_match,
@@ -80,6 +87,21 @@ impl<'a> RequestHandler<(MatchedRequest<'a>, &HttpConnectionManager)> for &Route
         info!("Handling request for {} {:?}", uri, &self.cluster_specifier);
         let cluster_id = clusters_manager::resolve_cluster(&self.cluster_specifier)
             .ok_or_else(|| "Failed to resolve cluster from specifier".to_owned())?;
+        if !clusters_manager::has_cluster(cluster_id) {
+            let discovered = match connection_manager.on_demand_cds(route_config_name, route) {
+                Some(odcds) => on_demand::discover_cluster(cluster_id, &odcds).await,
+                None => false,
+            };
+            if !discovered {
+                debug!("Cluster {cluster_id} not found for route {route_name}");
+                return Ok(SyntheticHttpResponse::custom_error(
+                    self.cluster_not_found_response_code,
+                    EventKind::ClusterNotFound,
+                    ResponseFlags(FmtResponseFlags::NO_CLUSTER_FOUND),
+                )
+                .into_response(downstream_request.version()));
+            }
+        }
         let routing_requirement = clusters_manager::get_cluster_routing_requirements(cluster_id);
         let hash_state = HashState::new(self.hash_policy.as_slice(), &downstream_request, remote_address);
         let routing_context = RoutingContext::try_from((
@@ -116,11 +138,16 @@ impl<'a> RequestHandler<(MatchedRequest<'a>, &HttpConnectionManager)> for &Route
                     };
 
                     let authority_replacement = if let Some(authority_rewrite) = &self.authority_rewrite {
-                        authority_rewrite.apply(&parts.uri, &parts.headers, &svc_channel.upstream_authority)
+                        let upstream_host = svc_channel.hostname.as_ref().unwrap_or(&svc_channel.upstream_authority);
+                        authority_rewrite.apply(&parts.uri, &parts.headers, upstream_host)
                     } else {
                         None
                     };
 
+                    if self.append_x_forwarded_host && authority_replacement.is_some() {
+                        http_modifiers::append_x_forwarded_host(&parts.uri, &mut parts.headers);
+                    }
+
                     if path_and_query_replacement.is_some() || authority_replacement.is_some() {
                         parts.uri = {
                             let UriParts { scheme, authority, path_and_query, .. } = parts.uri.into_parts();
@@ -138,7 +165,7 @@ impl<'a> RequestHandler<(MatchedRequest<'a>, &HttpConnectionManager)> for &Route
                         parts.headers.insert(http::header::HOST, header_value);
                     }
 
-                    Request::from_parts(parts, body.map_into())
+                    Request::from_parts(parts, body)
                 };
 
                 let mut client_span = connection_manager.http_tracer.try_create_span(
@@ -221,6 +248,7 @@ impl<'a> RequestHandler<(MatchedRequest<'a>, &HttpConnectionManager)> for &Route
             },
             // http connection not avaiable from cluster...
             Err(err) => {
+                load_report::request_dropped(cluster_id);
                 let err = err.into_inner();
                 let event_error = EventError::try_infer_from(&err);
                 let flags = event_error.clone().map(ResponseFlags::from).unwrap_or_default();
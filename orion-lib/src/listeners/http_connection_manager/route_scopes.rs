@@ -0,0 +1,190 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use std::{collections::HashMap, sync::Arc};
+
+use compact_str::CompactString;
+use hyper::Request;
+use orion_configuration::config::{
+    network_filters::http_connection_manager::scoped_routes::{
+        ScopeKey, ScopeKeyBuilder, ScopedRouteConfiguration, ScopedRoutes, ScopesSpecifier,
+    },
+    ConfigSource,
+};
+use tokio::sync::watch;
+use tracing::debug;
+
+use crate::{on_demand, scoped_routes, RouteConfiguration};
+
+/// The route configurations of an HTTP connection manager routing each request with the one of its scope.
+#[derive(Debug)]
+pub(super) struct RouteScopes {
+    key_builder: ScopeKeyBuilder,
+    rds_config_source: ConfigSource,
+    /// The scopes configured along with the connection manager, `None` if they are discovered over SRDS.
+    scopes: Option<HashMap<ScopeKey, Arc<ScopedRouteConfiguration>>>,
+    /// The route configurations received, by name. Every one is kept, a scope referring to it possibly coming later.
+    route_configs: watch::Sender<HashMap<CompactString, Arc<RouteConfiguration>>>,
+}
+
+impl RouteScopes {
+    pub(super) fn new(scoped_routes: ScopedRoutes) -> Self {
+        let ScopedRoutes { name: _, scope_key_builder, rds_config_source, scopes } = scoped_routes;
+        let scopes = match scopes {
+            ScopesSpecifier::Scopes(scopes) => {
+                Some(scopes.into_iter().map(|scope| (scope.key.clone(), Arc::new(scope))).collect())
+            },
+            ScopesSpecifier::ScopedRds(_) => None,
+        };
+        Self { key_builder: scope_key_builder, rds_config_source, scopes, route_configs: watch::Sender::default() }
+    }
+
+    fn scope(&self, key: &ScopeKey) -> Option<Arc<ScopedRouteConfiguration>> {
+        match &self.scopes {
+            Some(scopes) => scopes.get(key).cloned(),
+            None => scoped_routes::get(key),
+        }
+    }
+
+    pub(super) fn update(&self, name: &str, route: Arc<RouteConfiguration>) {
+        self.route_configs.send_modify(|route_configs| {
+            route_configs.insert(name.into(), route);
+        });
+    }
+
+    /// Whether a route configuration was removed.
+    pub(super) fn remove(&self, name: &str) -> bool {
+        self.route_configs.send_if_modified(|route_configs| route_configs.remove(name).is_some())
+    }
+
+    /// The route configuration of the scope of a request, waited for if the scope is loaded on demand. `None` if the
+    /// request has no scope, or if its route configuration isn't there.
+    pub(super) async fn route_configuration<B>(&self, request: &Request<B>) -> Option<Arc<RouteConfiguration>> {
+        let key = self.key_builder.scope_key(request)?;
+        let Some(scope) = self.scope(&key) else {
+            debug!("No route scope for key {key:?}");
+            return None;
+        };
+        let name = &scope.route_configuration_name;
+        if let Some(route_conf) = self.route_configs.borrow().get(name) {
+            return Some(Arc::clone(route_conf));
+        }
+        if scope.on_demand {
+            self.discover_route_configuration(name).await
+        } else {
+            None
+        }
+    }
+
+    /// Asks for the route configuration of a scope loaded on demand, and waits for it for up to the fetch timeout of
+    /// the RDS config source of the scopes, or for the management server not to have it.
+    async fn discover_route_configuration(&self, name: &str) -> Option<Arc<RouteConfiguration>> {
+        let mut route_configs = self.route_configs.subscribe();
+        let mut not_found = on_demand::request_route_configuration(name, &self.rds_config_source)?;
+        // requests aren't paused indefinitely, even if the config source waits for its resources until they come
+        let timeout = self.rds_config_source.fetch_timeout().unwrap_or(ConfigSource::DEFAULT_INITIAL_FETCH_TIMEOUT);
+        let ask = not_found.clone();
+        let not_found = async move {
+            // the route configuration being received leaves it to the connection manager
+            if not_found.wait_for(|not_found| *not_found).await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        };
+        let deadline = tokio::time::sleep(timeout);
+        tokio::pin!(not_found, deadline);
+        tokio::select! {
+            received = route_configs.wait_for(|route_configs| route_configs.contains_key(name)) => {
+                received.ok().and_then(|route_configs| route_configs.get(name).cloned())
+            },
+            () = &mut not_found => {
+                debug!("Route configuration {name} not found");
+                None
+            },
+            () = &mut deadline => {
+                debug!("Timed out waiting {timeout:?} for route configuration {name}");
+                on_demand::route_configuration_timed_out(name, &ask);
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_configuration::config::{
+        network_filters::http_connection_manager::{
+            header_modifer::HeaderModifier,
+            scoped_routes::{ExtractType, HeaderValueExtractor},
+        },
+        ConfigSourceSpecifier,
+    };
+
+    fn route_scopes() -> RouteScopes {
+        RouteScopes::new(ScopedRoutes {
+            name: "tenants".into(),
+            scope_key_builder: ScopeKeyBuilder {
+                fragments: vec![HeaderValueExtractor {
+                    name: http::HeaderName::from_static("x-tenant"),
+                    element_separator: CompactString::default(),
+                    extract: ExtractType::Index(0),
+                }],
+            },
+            rds_config_source: ConfigSource {
+                config_source_specifier: ConfigSourceSpecifier::ADS,
+                initial_fetch_timeout: Some(std::time::Duration::from_secs(60)),
+            },
+            scopes: ScopesSpecifier::Scopes(vec![ScopedRouteConfiguration {
+                name: "acme".into(),
+                on_demand: false,
+                route_configuration_name: "route_scopes_acme_routes".into(),
+                key: ScopeKey(vec!["acme".into()]),
+            }]),
+        })
+    }
+
+    fn route_configuration(name: &str) -> Arc<RouteConfiguration> {
+        Arc::new(RouteConfiguration {
+            name: name.into(),
+            most_specific_header_mutations_wins: false,
+            response_header_modifier: HeaderModifier::default(),
+            request_headers_to_add: Vec::new(),
+            request_headers_to_remove: Vec::new(),
+            virtual_hosts: Vec::new(),
+            vhds: None,
+        })
+    }
+
+    fn request(tenant: &str) -> Request<()> {
+        Request::get("http://example.com/").header("x-tenant", tenant).body(()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_are_routed_with_the_route_configuration_of_their_scope() {
+        let scopes = route_scopes();
+        assert!(scopes.route_configuration(&request("acme")).await.is_none(), "not received yet");
+        scopes.update("route_scopes_acme_routes", route_configuration("route_scopes_acme_routes"));
+        let route_conf = scopes.route_configuration(&request("acme")).await.unwrap();
+        assert_eq!(route_conf.name, "route_scopes_acme_routes");
+        assert!(scopes.route_configuration(&request("initech")).await.is_none());
+        assert!(scopes.route_configuration(&Request::get("http://example.com/").body(()).unwrap()).await.is_none());
+
+        assert!(scopes.remove("route_scopes_acme_routes"));
+        assert!(!scopes.remove("route_scopes_acme_routes"));
+        assert!(scopes.route_configuration(&request("acme")).await.is_none());
+    }
+}
@@ -16,22 +16,27 @@
 //
 
 use super::{
+    connection_limit::{ConnectionLimitGuard, ConnectionLimiter},
     filterchain::{ConnectionHandler, FilterchainBuilder, FilterchainType},
     listeners_manager::TlsContextChange,
 };
 use crate::{
+    extension_configs,
     listeners::filter_state::{DownstreamConnectionMetadata, DownstreamMetadata},
+    overload::{self, ConnectionRejection, DownstreamConnectionGuard},
     secrets::{TlsConfigurator, WantsToBuildServer},
     transport::{bind_device::BindDevice, tls_inspector, AsyncStream, ProxyProtocolReader, TlvListenerFilter},
     ConversionContext, Error, Result, RouteConfigurationChange,
 };
+use once_cell::sync::Lazy;
 use opentelemetry::KeyValue;
 use orion_configuration::config::{
     listener::{DetectedTransportProtocol, FilterChainMatch, Listener as ListenerConfig, MatchResult},
-    listener_filters::DownstreamProxyProtocolConfig,
+    listener_filters::{DiscoveredListenerFilter, DownstreamProxyProtocolConfig, ListenerFilterConfig},
     transport::BindDeviceOptions,
 };
 use orion_interner::StringInterner;
+use parking_lot::RwLock;
 
 use orion_metrics::{
     metrics::{http, listeners},
@@ -48,13 +53,26 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::Duration,
 };
 use tokio::{
-    net::{TcpListener, TcpSocket},
+    net::{TcpListener, TcpSocket, TcpStream},
     sync::broadcast::{self},
 };
 use tracing::{debug, info, warn};
 
+/// The addresses the socket listeners are bound to by name, which tell the port picked for those asking for any.
+static BOUND_ADDRESSES: Lazy<RwLock<HashMap<&'static str, SocketAddr>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// The address a socket listener is bound to, `None` until it is.
+pub fn listener_bound_address(name: &str) -> Option<SocketAddr> {
+    BOUND_ADDRESSES.read().get(name).copied()
+}
+
+pub(crate) fn forget_bound_address(name: &str) {
+    BOUND_ADDRESSES.write().remove(name);
+}
+
 #[derive(Debug, Clone)]
 struct PartialListener {
     name: &'static str,
@@ -64,6 +82,8 @@ struct PartialListener {
     with_tls_inspector: bool,
     proxy_protocol_config: Option<DownstreamProxyProtocolConfig>,
     with_tlv_listener_filter: bool,
+    discovered_listener_filters: Vec<DiscoveredListenerFilter>,
+    connection_limiter: Option<ConnectionLimiter>,
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +118,7 @@ impl TryFrom<ConversionContext<'_, ListenerConfig>> for PartialListener {
         let with_tls_inspector = listener.with_tls_inspector;
         let proxy_protocol_config = listener.proxy_protocol_config;
         let with_tlv_listener_filter = listener.with_tlv_listener_filter;
+        let discovered_listener_filters = listener.discovered_listener_filters;
         debug!("Listener {name} :TLS Inspector is {with_tls_inspector}");
         debug!("Listener {name} :TLV listener filter is {with_tlv_listener_filter}");
 
@@ -107,8 +128,11 @@ impl TryFrom<ConversionContext<'_, ListenerConfig>> for PartialListener {
             .map(|f| FilterchainBuilder::try_from(ConversionContext::new((f.1, secret_manager))).map(|x| (f.0, x)))
             .collect::<Result<_>>()?;
         let bind_device_options = listener.bind_device_options;
+        // created once here, so that the listeners running on every worker thread share the connection count
+        let connection_limiter = Some(ConnectionLimiter::for_listener(name, listener.connection_limit));
 
-        if !with_tls_inspector {
+        // a discovered listener filter may turn out to be a TLS inspector
+        if !with_tls_inspector && discovered_listener_filters.is_empty() {
             let has_server_names = filter_chains.keys().any(|m| !m.server_names.is_empty());
             if has_server_names {
                 return Err((format!(
@@ -125,6 +149,8 @@ impl TryFrom<ConversionContext<'_, ListenerConfig>> for PartialListener {
             with_tls_inspector,
             proxy_protocol_config,
             with_tlv_listener_filter,
+            discovered_listener_filters,
+            connection_limiter,
         })
     }
 }
@@ -143,6 +169,8 @@ impl ListenerFactory {
             with_tls_inspector,
             proxy_protocol_config,
             with_tlv_listener_filter,
+            discovered_listener_filters,
+            connection_limiter,
         } = self.listener;
 
         let filter_chains = filter_chains
@@ -158,6 +186,8 @@ impl ListenerFactory {
             with_tls_inspector,
             proxy_protocol_config: proxy_protocol_config.map(Arc::new),
             with_tlv_listener_filter,
+            discovered_listener_filters,
+            connection_limiter,
             route_updates_receiver,
             secret_updates_receiver,
         })
@@ -181,6 +211,8 @@ pub struct Listener {
     with_tls_inspector: bool,
     proxy_protocol_config: Option<Arc<DownstreamProxyProtocolConfig>>,
     with_tlv_listener_filter: bool,
+    discovered_listener_filters: Vec<DiscoveredListenerFilter>,
+    connection_limiter: Option<ConnectionLimiter>,
     route_updates_receiver: broadcast::Receiver<RouteConfigurationChange>,
     secret_updates_receiver: broadcast::Receiver<TlsContextChange>,
 }
@@ -202,6 +234,8 @@ impl Listener {
             with_tls_inspector: false,
             proxy_protocol_config: None,
             with_tlv_listener_filter: false,
+            discovered_listener_filters: Vec::new(),
+            connection_limiter: None,
             route_updates_receiver: route_rx,
             secret_updates_receiver: secret_rx,
         }
@@ -230,6 +264,8 @@ impl Listener {
             with_tls_inspector,
             proxy_protocol_config,
             with_tlv_listener_filter,
+            discovered_listener_filters,
+            connection_limiter,
             route_updates_receiver,
             secret_updates_receiver,
         } = self;
@@ -240,6 +276,12 @@ impl Listener {
                     Ok(x) => x,
                     Err(e) => return e,
                 };
+                match listener.local_addr() {
+                    Ok(bound_address) => {
+                        BOUND_ADDRESSES.write().insert(name, bound_address);
+                    },
+                    Err(e) => warn!("listener '{name}': could not get the bound address: {e}"),
+                }
                 info!("listener '{name}' started: {local_address}");
                 Self::run_socket_listener(
                     name,
@@ -249,6 +291,8 @@ impl Listener {
                     with_tls_inspector,
                     proxy_protocol_config,
                     with_tlv_listener_filter,
+                    discovered_listener_filters,
+                    connection_limiter,
                     route_updates_receiver,
                     secret_updates_receiver,
                 )
@@ -270,6 +314,7 @@ impl Listener {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn run_socket_listener(
         name: &'static str,
         local_address: SocketAddr,
@@ -278,6 +323,8 @@ impl Listener {
         with_tls_inspector: bool,
         proxy_protocol_config: Option<Arc<DownstreamProxyProtocolConfig>>,
         with_tlv_listener_filter: bool,
+        discovered_listener_filters: Vec<DiscoveredListenerFilter>,
+        connection_limiter: Option<ConnectionLimiter>,
         mut route_updates_receiver: broadcast::Receiver<RouteConfigurationChange>,
         mut secret_updates_receiver: broadcast::Receiver<TlsContextChange>,
     ) -> Error {
@@ -291,7 +338,44 @@ impl Listener {
                 maybe_stream = listener.accept() => {
                     match maybe_stream {
                         Ok((stream, peer_addr)) => {
-
+                            let cx_guard = match overload::admit_downstream_connection() {
+                                Ok(guard) => guard,
+                                Err(rejection) => {
+                                    let shard_id = std::thread::current().id();
+                                    match rejection {
+                                        ConnectionRejection::GlobalConnectionLimit => {
+                                            with_metric!(listeners::DOWNSTREAM_GLOBAL_CX_OVERFLOW, add, 1, shard_id, &[KeyValue::new("listener", listener_name)]);
+                                        },
+                                        ConnectionRejection::Overloaded => {
+                                            with_metric!(listeners::DOWNSTREAM_CX_OVERLOAD_REJECT, add, 1, shard_id, &[KeyValue::new("listener", listener_name)]);
+                                        },
+                                    }
+                                    debug!("{listener_name}: rejected connection from {peer_addr}: {rejection:?}");
+                                    drop(stream);
+                                    continue;
+                                },
+                            };
+                            let listener_cx_guard = match connection_limiter.as_ref().map(ConnectionLimiter::try_acquire) {
+                                Some(None) => {
+                                    let shard_id = std::thread::current().id();
+                                    with_metric!(listeners::DOWNSTREAM_CX_OVERFLOW, add, 1, shard_id, &[KeyValue::new("listener", listener_name)]);
+                                    debug!("{listener_name}: rejected connection from {peer_addr}: listener connection limit reached");
+                                    close_after_delay(stream, connection_limiter.as_ref().and_then(ConnectionLimiter::delay));
+                                    continue;
+                                },
+                                Some(guard) => guard,
+                                None => None,
+                            };
+                            let Some((with_tls_inspector, proxy_protocol_config, with_tlv_listener_filter)) = resolve_listener_filters(
+                                &discovered_listener_filters,
+                                with_tls_inspector,
+                                proxy_protocol_config.clone(),
+                                with_tlv_listener_filter,
+                            ) else {
+                                debug!("{listener_name}: rejected connection from {peer_addr}: a discovered listener filter has no configuration");
+                                drop(stream);
+                                continue;
+                            };
 
                             let original_destination_address:Option<SocketAddr> = {
                                 let raw_socket = stream.as_fd();
@@ -330,7 +414,6 @@ impl Listener {
                             with_metric!(listeners::DOWNSTREAM_CX_ACTIVE, add, 1, shard_id,&[KeyValue::new("listener", listener_name)]);
 
                             let filter_chains = Arc::clone(&filter_chains);
-                            let proxy_protocol_config = proxy_protocol_config.clone();
                             // spawn a separate task for handling this client<->proxy connection
                             // we spawn before we know if we want to process this route because we might need to run the tls_inspector which could
                             // stall if the client is slow to send the ClientHello and end up blocking the acceptance of new connections
@@ -339,7 +422,7 @@ impl Listener {
                             // before we have the ClientHello and the ones after. since we might already have enough info to decide to drop the connection
                             // or pick a specific filter_chain to run, or we could simply if-else on the with_tls_inspector variable.
 
-                            tokio::spawn(Self::process_listener_update(name, filter_chains, with_tls_inspector, proxy_protocol_config, with_tlv_listener_filter, local_address, peer_addr, original_destination_address,  Box::new(stream), start));
+                            tokio::spawn(Self::process_listener_update(name, filter_chains, with_tls_inspector, proxy_protocol_config, with_tlv_listener_filter, local_address, peer_addr, original_destination_address,  Box::new(stream), start, cx_guard, listener_cx_guard));
                         },
                         Err(e) => {warn!("failed to accept tcp connection: {e}");}
                     }
@@ -514,6 +597,8 @@ impl Listener {
         original_destination_address: Option<SocketAddr>,
         mut stream: AsyncStream,
         start_instant: std::time::Instant,
+        _cx_guard: DownstreamConnectionGuard,
+        _listener_cx_guard: Option<ConnectionLimitGuard>,
     ) -> Result<()> {
         let shard_id = std::thread::current().id();
 
@@ -620,10 +705,34 @@ impl Listener {
                 filterchain.filter_chain().name
             );
             if let Some(stream) = filterchain.apply_rbac(stream, &downstream_metadata, server_name.as_deref()) {
+                let _filter_chain_cx_guard = match &filterchain.filter_chain().connection_limiter {
+                    Some(limiter) => {
+                        let Some(guard) = limiter.try_acquire() else {
+                            with_metric!(
+                                listeners::DOWNSTREAM_CX_OVERFLOW,
+                                add,
+                                1,
+                                shard_id,
+                                &[KeyValue::new("listener", listener_name)]
+                            );
+                            debug!(
+                                "{listener_name} : rejected connection from {peer_addr}: connection limit of filter chain {} reached",
+                                filterchain.filter_chain().name
+                            );
+                            if let Some(delay) = limiter.delay() {
+                                tokio::time::sleep(delay).await;
+                            }
+                            return Ok(());
+                        };
+                        Some(guard)
+                    },
+                    None => None,
+                };
+                let is_tls = filterchain.filter_chain().tls_configurator.is_some();
                 return filterchain
                     .start_filterchain(
                         stream,
-                        Arc::new(DownstreamMetadata { connection: downstream_metadata, server_name }),
+                        Arc::new(DownstreamMetadata { connection: downstream_metadata, server_name, is_tls }),
                         shard_id,
                         listener_name,
                         start_instant,
@@ -692,6 +801,9 @@ impl Listener {
                                 debug!("{listener_name} Route updated {id} {route:?}");
                                 http_manager.update_route(route.clone());
                             }
+                        } else if http_manager.has_route_scopes() {
+                            debug!("{listener_name} Scoped route updated {id}");
+                            http_manager.update_scoped_route(&id, route.clone());
                         } else {
                             debug!("{listener_name} Got route update but id doesn't match {route_id:?} {id}");
                         }
@@ -705,6 +817,8 @@ impl Listener {
                             if route_id == id {
                                 http_manager.remove_route();
                             }
+                        } else {
+                            http_manager.remove_scoped_route(&id);
                         }
                     }
                 }
@@ -713,6 +827,35 @@ impl Listener {
     }
 }
 
+/// The listener filters a connection goes through: those of the listener, along with the discovered ones as they are
+/// configured now. `None` if a discovered filter has no configuration, in which case the connection is closed.
+fn resolve_listener_filters(
+    discovered_listener_filters: &[DiscoveredListenerFilter],
+    mut with_tls_inspector: bool,
+    mut proxy_protocol_config: Option<Arc<DownstreamProxyProtocolConfig>>,
+    mut with_tlv_listener_filter: bool,
+) -> Option<(bool, Option<Arc<DownstreamProxyProtocolConfig>>, bool)> {
+    for filter in discovered_listener_filters {
+        match extension_configs::listener_filter_config(filter)? {
+            ListenerFilterConfig::TlsInspector => with_tls_inspector = true,
+            ListenerFilterConfig::ProxyProtocol(config) => proxy_protocol_config = Some(Arc::new(config)),
+            ListenerFilterConfig::TlvListenerFilter(_) => with_tlv_listener_filter = true,
+            ListenerFilterConfig::Ignored | ListenerFilterConfig::ConfigDiscovery(_) => (),
+        }
+    }
+    Some((with_tls_inspector, proxy_protocol_config, with_tlv_listener_filter))
+}
+
+/// Closes a connection over a connection limit once its delay is over, without holding up the accept loop.
+fn close_after_delay(stream: TcpStream, delay: Option<Duration>) {
+    if let Some(delay) = delay {
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            drop(stream);
+        });
+    }
+}
+
 fn configure_and_start_tcp_listener(addr: SocketAddr, bind_device_options: BindDeviceOptions) -> Result<TcpListener> {
     let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
     socket.set_reuseaddr(true)?;
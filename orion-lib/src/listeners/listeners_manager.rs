@@ -23,16 +23,26 @@ use orion_configuration::config::{
     network_filters::http_connection_manager::RouteConfiguration, Listener as ListenerConfig,
 };
 
-use super::listener::{Listener, ListenerFactory};
-use crate::{secrets::TransportSecret, ConfigDump, Result};
+use super::listener::{self, Listener, ListenerFactory};
+use crate::{
+    lifecycle::{self, DrainScope},
+    secrets::TransportSecret,
+    ConfigDump, Result,
+};
 #[derive(Debug, Clone)]
 pub enum ListenerConfigurationChange {
     Added(Box<(ListenerFactory, ListenerConfig)>),
     Removed(String),
     TlsContextChanged((String, TransportSecret)),
     GetConfiguration(mpsc::Sender<ConfigDump>),
+    /// Starts draining the connections of the listeners in scope.
+    DrainListeners(DrainScope),
+    /// Closes the listeners in scope, which stop accepting connections. Listeners in scope added later on are not
+    /// started.
+    StopListeners(DrainScope),
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum RouteConfigurationChange {
     Added((String, RouteConfiguration)),
@@ -59,6 +69,7 @@ pub struct ListenersManager {
     route_configuration_channel: mpsc::Receiver<RouteConfigurationChange>,
     listener_handles: MultiMap<String, ListenerInfo>,
     version_counter: u64,
+    stopped: Option<DrainScope>,
 }
 
 impl ListenersManager {
@@ -71,6 +82,7 @@ impl ListenersManager {
             route_configuration_channel,
             listener_handles: MultiMap::new(),
             version_counter: 0,
+            stopped: None,
         }
     }
 
@@ -84,6 +96,10 @@ impl ListenersManager {
                     match listener_configuration_change {
                         ListenerConfigurationChange::Added(boxed) => {
                             let (factory, listener_conf) = *boxed;
+                            if self.stopped.is_some_and(|scope| scope.covers(listener_conf.traffic_direction)) {
+                                info!("Not starting listener {}: listeners have been stopped", listener_conf.name);
+                                continue;
+                            }
                             let listener = factory.clone()
                                 .make_listener(tx_route_updates.subscribe(), tx_secret_updates.subscribe())?;
                             if let Err(e) = self.start_listener(listener, listener_conf) {
@@ -107,6 +123,18 @@ impl ListenersManager {
                                 .collect();
                             config_dump_tx.send(ConfigDump { listeners: Some(listeners), ..Default::default() }).await?;
                         },
+                        ListenerConfigurationChange::DrainListeners(scope) => {
+                            for name in self.listener_names(scope) {
+                                info!("Draining listener {name}");
+                                lifecycle::start_draining(&name);
+                            }
+                        },
+                        ListenerConfigurationChange::StopListeners(scope) => {
+                            self.stopped = Some(self.stopped.map_or(scope, |stopped| stopped.union(scope)));
+                            for name in self.listener_names(scope) {
+                                let _ = self.stop_listener(&name);
+                            }
+                        },
                     }
                 },
                 Some(route_configuration_change) = self.route_configuration_channel.recv() => {
@@ -151,6 +179,14 @@ impl ListenersManager {
         Ok(())
     }
 
+    fn listener_names(&self, scope: DrainScope) -> Vec<String> {
+        self.listener_handles
+            .iter()
+            .filter(|(_, info)| scope.covers(info.listener_conf.traffic_direction))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
     pub fn stop_listener(&mut self, listener_name: &str) -> Result<()> {
         if let Some(listeners) = self.listener_handles.get_vec_mut(listener_name) {
             info!("Stopping all {} version(s) of listener {}", listeners.len(), listener_name);
@@ -159,6 +195,7 @@ impl ListenersManager {
                 listener_info.handle.abort();
             }
             self.listener_handles.remove(listener_name);
+            listener::forget_bound_address(listener_name);
         } else {
             info!("No listeners found with name {}", listener_name);
         }
@@ -176,7 +213,9 @@ mod tests {
 
     use super::*;
     use orion_configuration::config::{
-        listener::ListenerAddress, transport::BindDeviceOptions, Listener as ListenerConfig,
+        listener::{ListenerAddress, TrafficDirection},
+        transport::BindDeviceOptions,
+        Listener as ListenerConfig,
     };
     use tracing_test::traced_test;
 
@@ -205,6 +244,9 @@ mod tests {
             proxy_protocol_config: None,
             with_tlv_listener_filter: false,
             tlv_listener_filter_config: None,
+            discovered_listener_filters: Vec::new(),
+            connection_limit: None,
+            traffic_direction: TrafficDirection::default(),
         };
         man.start_listener(l1, l1_info.clone()).unwrap();
         assert!(routeb_tx1.send(RouteConfigurationChange::Removed("n/a".into())).is_ok());
@@ -226,6 +268,49 @@ mod tests {
         tokio::task::yield_now().await;
     }
 
+    #[tokio::test]
+    async fn bound_address_tells_the_port_picked() {
+        let chan = 10;
+        let name = "bound-address-listener";
+
+        let (_conf_tx, conf_rx) = mpsc::channel(chan);
+        let (_route_tx, route_rx) = mpsc::channel(chan);
+        let mut man = ListenersManager::new(conf_rx, route_rx);
+
+        let (_routeb_tx, routeb_rx) = broadcast::channel(chan);
+        let (_secb_tx, secb_rx) = broadcast::channel(chan);
+        let listener = Listener::test_listener(name, routeb_rx, secb_rx);
+        let listener_info = ListenerConfig {
+            name: name.into(),
+            address: ListenerAddress::Socket(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)),
+            filter_chains: HashMap::default(),
+            bind_device_options: BindDeviceOptions::default(),
+            with_tls_inspector: false,
+            proxy_protocol_config: None,
+            with_tlv_listener_filter: false,
+            tlv_listener_filter_config: None,
+            discovered_listener_filters: Vec::new(),
+            connection_limit: None,
+            traffic_direction: TrafficDirection::default(),
+        };
+        man.start_listener(listener, listener_info).unwrap();
+
+        let mut bound_address = None;
+        for _ in 0..100 {
+            bound_address = listener::listener_bound_address(name);
+            if bound_address.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let bound_address = bound_address.unwrap();
+        assert_eq!(bound_address.ip(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_ne!(bound_address.port(), 0);
+
+        man.stop_listener(name).unwrap();
+        assert_eq!(listener::listener_bound_address(name), None);
+    }
+
     #[traced_test]
     #[tokio::test]
     async fn start_listener_shutdown() {
@@ -251,6 +336,9 @@ mod tests {
             proxy_protocol_config: None,
             with_tlv_listener_filter: false,
             tlv_listener_filter_config: None,
+            discovered_listener_filters: Vec::new(),
+            connection_limit: None,
+            traffic_direction: TrafficDirection::default(),
         };
         man.start_listener(l1, l1_info).unwrap();
 
@@ -293,6 +381,9 @@ mod tests {
             proxy_protocol_config: None,
             with_tlv_listener_filter: false,
             tlv_listener_filter_config: None,
+            discovered_listener_filters: Vec::new(),
+            connection_limit: None,
+            traffic_direction: TrafficDirection::default(),
         };
         man.start_listener(l1, l1_info).unwrap();
         assert!(routeb_tx1.send(RouteConfigurationChange::Removed("n/a".into())).is_ok());
@@ -310,6 +401,9 @@ mod tests {
             proxy_protocol_config: None,
             with_tlv_listener_filter: false,
             tlv_listener_filter_config: None,
+            discovered_listener_filters: Vec::new(),
+            connection_limit: None,
+            traffic_direction: TrafficDirection::default(),
         };
         man.start_listener(l2, l2_info).unwrap();
         assert!(routeb_tx2.send(RouteConfigurationChange::Removed("n/a".into())).is_ok());
@@ -327,6 +421,9 @@ mod tests {
             proxy_protocol_config: None,
             with_tlv_listener_filter: false,
             tlv_listener_filter_config: None,
+            discovered_listener_filters: Vec::new(),
+            connection_limit: None,
+            traffic_direction: TrafficDirection::default(),
         };
         man.start_listener(l3, l3_info).unwrap();
         assert!(routeb_tx3.send(RouteConfigurationChange::Removed("n/a".into())).is_ok());
@@ -21,12 +21,16 @@ use tracing::warn;
 
 use token_bucket::TokenBucket;
 
-use orion_configuration::config::network_filters::http_connection_manager::http_filters::local_rate_limit::LocalRateLimit as LocalRateLimitConfig;
+use orion_configuration::config::{
+    layered_runtime::RuntimeFractionalPercent,
+    network_filters::http_connection_manager::http_filters::local_rate_limit::LocalRateLimit as LocalRateLimitConfig,
+};
 
 use crate::{body::response_flags::ResponseFlags, event_error::EventKind};
 use orion_format::types::ResponseFlags as FmtResponseFlags;
 
 use crate::{
+    layered_runtime,
     listeners::{http_connection_manager::FilterDecision, synthetic_http_response::SyntheticHttpResponse},
     runtime_config,
 };
@@ -35,12 +39,20 @@ use crate::{
 pub struct LocalRateLimit {
     pub status: StatusCode,
     pub token_bucket: Option<TokenBucket>,
+    pub filter_enabled: Option<RuntimeFractionalPercent>,
+    pub filter_enforced: Option<RuntimeFractionalPercent>,
 }
 
 impl LocalRateLimit {
     pub fn run<B>(&self, req: &Request<B>) -> FilterDecision {
+        if !self.filter_enabled.as_ref().is_none_or(layered_runtime::feature_enabled) {
+            return FilterDecision::Continue;
+        }
         if let Some(token_bucket) = &self.token_bucket {
             if !token_bucket.consume(1) {
+                if !self.filter_enforced.as_ref().is_none_or(layered_runtime::feature_enabled) {
+                    return FilterDecision::Continue;
+                }
                 let status = self.status;
                 return FilterDecision::DirectResponse(
                     SyntheticHttpResponse::custom_error(
@@ -58,8 +70,8 @@ impl LocalRateLimit {
 
 impl From<LocalRateLimitConfig> for LocalRateLimit {
     fn from(rate_limit: LocalRateLimitConfig) -> Self {
-        let status = rate_limit.status;
-        if let Some(token_bucket) = rate_limit.token_bucket {
+        let LocalRateLimitConfig { status, token_bucket, filter_enabled, filter_enforced } = rate_limit;
+        if let Some(token_bucket) = token_bucket {
             let max_tokens = token_bucket.max_tokens;
             let tokens_per_fill = token_bucket.tokens_per_fill;
             let fill_interval = token_bucket.fill_interval;
@@ -71,8 +83,8 @@ impl From<LocalRateLimitConfig> for LocalRateLimit {
                 fill_interval
             };
             let tb = TokenBucket::new(max_tokens, tokens_per_fill, fill_interval);
-            return Self { status, token_bucket: Some(tb) };
+            return Self { status, token_bucket: Some(tb), filter_enabled, filter_enforced };
         }
-        Self { status, token_bucket: None }
+        Self { status, token_bucket: None, filter_enabled, filter_enforced }
     }
 }
@@ -64,7 +64,6 @@ impl SyntheticHttpResponse {
         }
     }
 
-    #[allow(dead_code)]
     pub fn service_unavailable(event_kind: EventKind, response_flags: ResponseFlags) -> Self {
         Self {
             http_status: StatusCode::SERVICE_UNAVAILABLE,
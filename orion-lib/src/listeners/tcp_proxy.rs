@@ -22,6 +22,7 @@ use crate::{
         find_error_in_chain, ConnectionTerminationDetails, ResponseCodeDetails, UpstreamTransportEventError,
     },
     listeners::{access_log::AccessLogContext, filter_state::DownstreamMetadata},
+    on_demand,
     transport::connector::TcpErrorContext,
     AsyncStream, Result,
 };
@@ -29,7 +30,10 @@ use compact_str::ToCompactString;
 use http::uri::Authority;
 use orion_configuration::config::{
     cluster::ClusterSpecifier as ClusterSpecifierConfig,
-    network_filters::{access_log::AccessLog, tcp_proxy::TcpProxy as TcpProxyConfig},
+    network_filters::{
+        access_log::AccessLog, http_connection_manager::http_filters::on_demand::OnDemandCds,
+        tcp_proxy::TcpProxy as TcpProxyConfig,
+    },
 };
 use orion_format::{
     context::{FinishContext, InitContext, TcpContext},
@@ -44,6 +48,7 @@ pub struct TcpProxy {
     pub listener_name: &'static str,
     cluster: ClusterSpecifierConfig,
     pub access_log: Vec<AccessLog>,
+    on_demand: Option<OnDemandCds>,
 }
 
 #[derive(Debug, Clone)]
@@ -64,8 +69,8 @@ impl TcpProxyBuilder {
     }
     pub fn build(self) -> Result<TcpProxy> {
         let listener_name = self.listener_name.ok_or("listener name is not set")?;
-        let TcpProxyConfig { cluster_specifier, access_log } = self.tcp_proxy_config;
-        Ok(TcpProxy { listener_name, access_log, cluster: cluster_specifier })
+        let TcpProxyConfig { cluster_specifier, access_log, on_demand } = self.tcp_proxy_config;
+        Ok(TcpProxy { listener_name, access_log, cluster: cluster_specifier, on_demand })
     }
 }
 
@@ -91,6 +96,11 @@ impl TcpProxy {
         info!("Handling request TCP for {} {:?} {:?}", self.listener_name, cluster_selector, downstream_metadata);
         let cluster_id = clusters_manager::resolve_cluster(cluster_selector)
             .ok_or_else(|| "Failed to resolve cluster from specifier".to_owned())?;
+        if let Some(odcds) = &self.on_demand {
+            if !clusters_manager::has_cluster(cluster_id) {
+                on_demand::discover_cluster(cluster_id, odcds).await;
+            }
+        }
 
         let routing_context = RoutingContext::Authority(
             Authority::try_from(downstream_metadata.connection.local_address().to_string())?,
@@ -111,6 +121,8 @@ impl TcpProxy {
         let maybe_upstream_local_addr: Option<SocketAddr>;
         let maybe_upstream_peer_addr: Option<SocketAddr>;
 
+        let upstream_request_attempt_count = u32::from(maybe_connector.is_ok());
+
         let res = match maybe_connector {
             Ok(connector) => {
                 let channel_result = connector.connect(Some(&downstream_metadata.connection)).await;
@@ -199,6 +211,7 @@ impl TcpProxy {
             duration: start_instant.elapsed(),
             bytes_received,
             bytes_sent,
+            upstream_request_attempt_count,
             response_flags,
             upstream_failure: maybe_upstream_transport_error.map(|x| x.0),
             response_code_details: maybe_response_code_details.map(|x| x.0),
@@ -0,0 +1,392 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Resources asked for from the management servers when a request needs them.
+//!
+//! A request for a host none of the virtual hosts of a route configuration discovering them over VHDS match is paused
+//! while its virtual host is asked for. The requests for the same host share a single ask, which is settled either
+//! by the route configuration being updated with the virtual host, or by the management server not having it.
+//!
+//! Likewise, a request or connection routed to a cluster that isn't loaded is paused while the cluster is asked for,
+//! if on-demand CDS is configured for it, until the cluster is added or the management server doesn't have it.
+//!
+//! And a request whose route scope is loaded on demand is paused while the route configuration of the scope is asked
+//! for, until it is received or the management server doesn't have it.
+//!
+//! An ask the paused requests time out waiting on is given up on, the resource being unsubscribed from, and there are
+//! only so many asks of each type at once.
+
+use std::collections::HashMap;
+
+use compact_str::CompactString;
+use once_cell::sync::Lazy;
+use orion_configuration::config::{
+    network_filters::http_connection_manager::http_filters::on_demand::OnDemandCds, ConfigSource,
+};
+use parking_lot::Mutex;
+use tokio::sync::{mpsc, watch};
+use tracing::debug;
+
+use crate::clusters::clusters_manager;
+
+/// A virtual host asked for, under the `<route configuration name>/<host>` name VHDS knows it by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VirtualHostRequest {
+    pub route_config_name: CompactString,
+    pub alias: String,
+}
+
+/// A cluster asked for, from the config source of the on-demand CDS configuration of the route or TCP proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterRequest {
+    pub name: String,
+    pub config_source: ConfigSource,
+}
+
+/// The route configuration of a scope loaded on demand, asked for from the RDS config source of its scopes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteConfigurationRequest {
+    pub name: String,
+    pub config_source: ConfigSource,
+}
+
+/// A resource asked for, or given up on once the requests needing it timed out waiting for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ask<R> {
+    Subscribe(R),
+    Unsubscribe(R),
+}
+
+/// The most resources of a type asked for and not settled at once. The requests needing more are handled as if the
+/// resources couldn't be asked for, clients not being able to have the proxy subscribe to any number of names.
+const MAX_PENDING_ASKS: usize = 1024;
+
+/// The resources of a type asked for, by name.
+struct PendingAsks<R> {
+    requests: Option<mpsc::UnboundedSender<Ask<R>>>,
+    /// The resources asked for and not settled yet, each set to `true` if the management server doesn't have it.
+    pending: HashMap<String, (R, watch::Sender<bool>)>,
+}
+
+impl<R> Default for PendingAsks<R> {
+    fn default() -> Self {
+        Self { requests: None, pending: HashMap::new() }
+    }
+}
+
+impl<R: Clone> PendingAsks<R> {
+    /// The resources asked for from now on, those asked for until now being forgotten.
+    fn requests(&mut self) -> mpsc::UnboundedReceiver<Ask<R>> {
+        let (requests_tx, requests_rx) = mpsc::unbounded_channel();
+        self.requests = Some(requests_tx);
+        self.pending.clear();
+        requests_rx
+    }
+
+    /// Asks for a resource with the request made for it, unless it was already.
+    fn ask(&mut self, name: &str, request: impl FnOnce() -> R) -> Option<watch::Receiver<bool>> {
+        if let Some((_, pending)) = self.pending.get(name) {
+            return Some(pending.subscribe());
+        }
+        if self.pending.len() >= MAX_PENDING_ASKS {
+            debug!("Not asking for {name}, {MAX_PENDING_ASKS} resources of its type are asked for already");
+            return None;
+        }
+        let request = request();
+        self.requests.as_ref()?.send(Ask::Subscribe(request.clone())).ok()?;
+        let (not_found_tx, not_found_rx) = watch::channel(false);
+        self.pending.insert(name.to_owned(), (request, not_found_tx));
+        Some(not_found_rx)
+    }
+
+    fn received(&mut self, name: &str) {
+        self.pending.remove(name);
+    }
+
+    fn not_found(&mut self, name: &str) {
+        if let Some((_, pending)) = self.pending.remove(name) {
+            pending.send_replace(true);
+        }
+    }
+
+    /// Gives up on the ask the receiver is for, if it is still pending rather than settled and asked for again.
+    fn timed_out(&mut self, name: &str, not_found: &watch::Receiver<bool>) {
+        if self.pending.get(name).is_some_and(|(_, pending)| pending.subscribe().same_channel(not_found)) {
+            if let Some((request, _)) = self.pending.remove(name) {
+                if let Some(requests) = &self.requests {
+                    let _ = requests.send(Ask::Unsubscribe(request));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct OnDemand {
+    virtual_hosts: PendingAsks<VirtualHostRequest>,
+    clusters: PendingAsks<ClusterRequest>,
+    route_configurations: PendingAsks<RouteConfigurationRequest>,
+}
+
+static ON_DEMAND: Lazy<Mutex<OnDemand>> = Lazy::new(Mutex::default);
+
+/// The virtual hosts asked for from now on, for them to be subscribed to. Virtual hosts aren't asked for before this
+/// is called, the requests needing them being routed with the virtual hosts there are.
+pub fn virtual_host_requests() -> mpsc::UnboundedReceiver<Ask<VirtualHostRequest>> {
+    ON_DEMAND.lock().virtual_hosts.requests()
+}
+
+/// Asks for the virtual host of a host, unless it was already. The receiver returned is set to `true` if the
+/// management server doesn't have it, and is `None` if virtual hosts can't be asked for.
+pub(crate) fn request_virtual_host(route_config_name: &str, host: &str) -> Option<watch::Receiver<bool>> {
+    let alias = format!("{route_config_name}/{host}");
+    ON_DEMAND
+        .lock()
+        .virtual_hosts
+        .ask(&alias, || VirtualHostRequest { route_config_name: route_config_name.into(), alias: alias.clone() })
+}
+
+/// Settles the ask for a virtual host received, the paused requests being resumed once their route configuration
+/// is updated with it.
+pub fn virtual_host_received(alias: &str) {
+    ON_DEMAND.lock().virtual_hosts.received(alias);
+}
+
+/// Settles the ask for a virtual host the management server doesn't have, resuming the requests paused for it.
+pub fn virtual_host_not_found(alias: &str) {
+    ON_DEMAND.lock().virtual_hosts.not_found(alias);
+}
+
+/// Gives up on the virtual host of a host a request timed out waiting for, for it to be unsubscribed from.
+pub(crate) fn virtual_host_timed_out(route_config_name: &str, host: &str, not_found: &watch::Receiver<bool>) {
+    ON_DEMAND.lock().virtual_hosts.timed_out(&format!("{route_config_name}/{host}"), not_found);
+}
+
+/// The clusters asked for from now on, for them to be subscribed to. Clusters aren't asked for before this is called,
+/// the requests and connections needing them failing as if there was no on-demand CDS.
+pub fn cluster_requests() -> mpsc::UnboundedReceiver<Ask<ClusterRequest>> {
+    ON_DEMAND.lock().clusters.requests()
+}
+
+/// Asks for a cluster, unless it was already. The receiver returned is set to `true` if the management server
+/// doesn't have it, is closed once it is added, and is `None` if clusters can't be asked for.
+pub(crate) fn request_cluster(name: &str, config_source: &ConfigSource) -> Option<watch::Receiver<bool>> {
+    ON_DEMAND
+        .lock()
+        .clusters
+        .ask(name, || ClusterRequest { name: name.to_owned(), config_source: config_source.clone() })
+}
+
+/// Settles the ask for a cluster once it is added, resuming the requests and connections paused for it.
+pub fn cluster_received(name: &str) {
+    ON_DEMAND.lock().clusters.received(name);
+}
+
+/// Settles the ask for a cluster the management server doesn't have, resuming the requests and connections paused
+/// for it.
+pub fn cluster_not_found(name: &str) {
+    ON_DEMAND.lock().clusters.not_found(name);
+}
+
+/// The route configurations asked for from now on, for them to be subscribed to. Route configurations aren't asked for
+/// before this is called, the requests of the scopes loaded on demand not being routed until then.
+pub fn route_configuration_requests() -> mpsc::UnboundedReceiver<Ask<RouteConfigurationRequest>> {
+    ON_DEMAND.lock().route_configurations.requests()
+}
+
+/// Asks for a route configuration, unless it was already. The receiver returned is set to `true` if the management
+/// server doesn't have it, is closed once it is received, and is `None` if route configurations can't be asked for.
+pub(crate) fn request_route_configuration(name: &str, config_source: &ConfigSource) -> Option<watch::Receiver<bool>> {
+    ON_DEMAND
+        .lock()
+        .route_configurations
+        .ask(name, || RouteConfigurationRequest { name: name.to_owned(), config_source: config_source.clone() })
+}
+
+/// Settles the ask for a route configuration received, the paused requests being resumed once the connection managers
+/// of their scopes are updated with it.
+pub fn route_configuration_received(name: &str) {
+    ON_DEMAND.lock().route_configurations.received(name);
+}
+
+/// Settles the ask for a route configuration the management server doesn't have, resuming the requests paused for it.
+pub fn route_configuration_not_found(name: &str) {
+    ON_DEMAND.lock().route_configurations.not_found(name);
+}
+
+/// Gives up on a route configuration a request timed out waiting for, for it to be unsubscribed from.
+pub(crate) fn route_configuration_timed_out(name: &str, not_found: &watch::Receiver<bool>) {
+    ON_DEMAND.lock().route_configurations.timed_out(name, not_found);
+}
+
+/// Waits for a cluster that isn't loaded to be discovered, for as long as the on-demand CDS configuration allows.
+/// Returns whether the cluster is there to be routed to.
+pub(crate) async fn discover_cluster(name: &str, odcds: &OnDemandCds) -> bool {
+    let Some(mut not_found) = request_cluster(name, &odcds.config_source) else {
+        return false;
+    };
+    // the cluster may have been added after it was looked up, before it was asked for
+    if clusters_manager::has_cluster(name) {
+        cluster_received(name);
+        return true;
+    }
+    let timed_out = tokio::select! {
+        settled = not_found.wait_for(|not_found| *not_found) => {
+            if settled.is_ok() {
+                debug!("Cluster {name} not found by on-demand CDS");
+            }
+            false
+        },
+        () = tokio::time::sleep(odcds.timeout) => {
+            debug!("Timed out waiting {:?} for cluster {name} to be discovered", odcds.timeout);
+            true
+        },
+    };
+    if timed_out {
+        ON_DEMAND.lock().clusters.timed_out(name, &not_found);
+    }
+    clusters_manager::has_cluster(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn requests_for_the_same_host_share_one_ask() {
+        assert!(request_virtual_host("on_demand_routes", "unasked.example.com").is_none());
+
+        let mut requests = virtual_host_requests();
+        let mut first = request_virtual_host("on_demand_routes", "example.com:8080").unwrap();
+        let mut second = request_virtual_host("on_demand_routes", "example.com:8080").unwrap();
+        assert_eq!(
+            requests.recv().await,
+            Some(Ask::Subscribe(VirtualHostRequest {
+                route_config_name: "on_demand_routes".into(),
+                alias: "on_demand_routes/example.com:8080".to_owned()
+            }))
+        );
+        assert!(requests.try_recv().is_err(), "asked for once");
+
+        virtual_host_not_found("on_demand_routes/example.com:8080");
+        assert!(*first.wait_for(|not_found| *not_found).await.unwrap());
+        assert!(*second.wait_for(|not_found| *not_found).await.unwrap());
+
+        let mut received = request_virtual_host("on_demand_routes", "example.com:8080").unwrap();
+        assert!(requests.recv().await.is_some(), "asked for again once settled");
+        virtual_host_received("on_demand_routes/example.com:8080");
+        assert!(received.wait_for(|not_found| *not_found).await.is_err(), "waiters left to their route configuration");
+    }
+
+    #[tokio::test]
+    async fn clusters_are_discovered_once_added() {
+        let config_source = ConfigSource {
+            config_source_specifier: orion_configuration::config::ConfigSourceSpecifier::ADS,
+            initial_fetch_timeout: None,
+        };
+        let odcds = OnDemandCds { config_source: config_source.clone(), timeout: std::time::Duration::from_secs(60) };
+        let mut requests = cluster_requests();
+
+        let missing = tokio::spawn(async move { discover_cluster("on_demand_missing", &odcds).await });
+        assert_eq!(
+            requests.recv().await,
+            Some(Ask::Subscribe(ClusterRequest {
+                name: "on_demand_missing".to_owned(),
+                config_source: config_source.clone()
+            }))
+        );
+        cluster_not_found("on_demand_missing");
+        assert!(!missing.await.unwrap(), "resumed as soon as the management server doesn't have it");
+
+        let mut first = request_cluster("on_demand_asked", &config_source).unwrap();
+        let _second = request_cluster("on_demand_asked", &config_source).unwrap();
+        assert!(matches!(requests.recv().await, Some(Ask::Subscribe(request)) if request.name == "on_demand_asked"));
+        assert!(requests.try_recv().is_err(), "asked for once");
+        cluster_received("on_demand_asked");
+        assert!(first.wait_for(|not_found| *not_found).await.is_err(), "resumed once added");
+
+        let odcds = OnDemandCds { config_source, timeout: std::time::Duration::from_millis(10) };
+        assert!(!discover_cluster("on_demand_timed_out", &odcds).await);
+        assert!(
+            matches!(requests.recv().await, Some(Ask::Subscribe(request)) if request.name == "on_demand_timed_out")
+        );
+        assert!(
+            matches!(requests.recv().await, Some(Ask::Unsubscribe(request)) if request.name == "on_demand_timed_out"),
+            "given up on once timed out"
+        );
+        assert!(!ON_DEMAND.lock().clusters.pending.contains_key("on_demand_timed_out"));
+    }
+
+    #[tokio::test]
+    async fn route_configurations_are_asked_for_once() {
+        let config_source = ConfigSource {
+            config_source_specifier: orion_configuration::config::ConfigSourceSpecifier::ADS,
+            initial_fetch_timeout: None,
+        };
+        assert!(request_route_configuration("on_demand_unasked_routes", &config_source).is_none());
+
+        let mut requests = route_configuration_requests();
+        let mut first = request_route_configuration("on_demand_tenant_routes", &config_source).unwrap();
+        let _second = request_route_configuration("on_demand_tenant_routes", &config_source).unwrap();
+        assert_eq!(
+            requests.recv().await,
+            Some(Ask::Subscribe(RouteConfigurationRequest {
+                name: "on_demand_tenant_routes".to_owned(),
+                config_source: config_source.clone()
+            }))
+        );
+        assert!(requests.try_recv().is_err(), "asked for once");
+        route_configuration_not_found("on_demand_tenant_routes");
+        assert!(*first.wait_for(|not_found| *not_found).await.unwrap());
+
+        let mut received = request_route_configuration("on_demand_tenant_routes", &config_source).unwrap();
+        assert!(requests.recv().await.is_some(), "asked for again once settled");
+        route_configuration_received("on_demand_tenant_routes");
+        assert!(received.wait_for(|not_found| *not_found).await.is_err(), "waiters left to their connection manager");
+    }
+
+    #[test]
+    fn asks_timed_out_are_unsubscribed_from() {
+        let mut asks = PendingAsks::default();
+        let mut requests = asks.requests();
+        let stale = asks.ask("timed_out", || "timed_out").unwrap();
+        assert_eq!(requests.try_recv(), Ok(Ask::Subscribe("timed_out")));
+        asks.timed_out("timed_out", &stale);
+        assert_eq!(requests.try_recv(), Ok(Ask::Unsubscribe("timed_out")));
+        assert!(asks.pending.is_empty());
+
+        let asked_again = asks.ask("timed_out", || "timed_out").unwrap();
+        assert_eq!(requests.try_recv(), Ok(Ask::Subscribe("timed_out")));
+        asks.timed_out("timed_out", &stale);
+        assert!(requests.try_recv().is_err(), "the ask asked again isn't the one timed out");
+        asks.received("timed_out");
+        asks.timed_out("timed_out", &asked_again);
+        assert!(requests.try_recv().is_err(), "settled before timing out");
+    }
+
+    #[test]
+    fn only_so_many_resources_are_asked_for_at_once() {
+        let mut asks = PendingAsks::default();
+        let _requests = asks.requests();
+        let pending: Vec<_> =
+            (0..MAX_PENDING_ASKS).map(|i| asks.ask(&i.to_string(), || i).expect("asked for")).collect();
+        assert!(asks.ask("one too many", || MAX_PENDING_ASKS).is_none());
+        assert!(asks.ask("0", || 0).is_some(), "joining an ask asks for nothing more");
+        asks.timed_out("0", &pending[0]);
+        assert!(asks.ask("one too many", || MAX_PENDING_ASKS).is_some());
+    }
+}
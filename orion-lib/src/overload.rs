@@ -0,0 +1,346 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Overload manager.
+//!
+//! A single background task samples the configured resource monitors every `refresh_interval` and publishes the
+//! resulting state of each overload action, a value between 0.0 (inactive) and 1.0 (saturated). Listeners and the
+//! HTTP connection manager consult the published state on their hot paths, so reading it is lock-free.
+
+use std::{
+    collections::HashMap,
+    num::NonZeroU64,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use once_cell::sync::Lazy;
+use orion_configuration::config::overload::{
+    OverloadActionKind, OverloadActionType, OverloadAdjust, OverloadManager as OverloadManagerConfig, ResourceMonitor,
+    TimerType, Trigger,
+};
+use parking_lot::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::{body::body_with_metrics::MetricsState, layered_runtime};
+
+struct OverloadState {
+    /// `f64` bit patterns, indexed by [`action_index`].
+    actions: [AtomicU64; OverloadActionKind::ALL.len()],
+    /// Limit of the `global_downstream_max_connections` resource monitor, zero when not configured.
+    max_downstream_connections: AtomicU64,
+    /// Bootstrap `max_downstream_connections`, zero when not configured.
+    bootstrap_max_downstream_connections: AtomicU64,
+    track_streams: AtomicBool,
+    idle_timeout_adjust: Mutex<Option<OverloadAdjust>>,
+}
+
+static OVERLOAD_STATE: OverloadState = OverloadState {
+    actions: [const { AtomicU64::new(0) }; OverloadActionKind::ALL.len()],
+    max_downstream_connections: AtomicU64::new(0),
+    bootstrap_max_downstream_connections: AtomicU64::new(0),
+    track_streams: AtomicBool::new(false),
+    idle_timeout_adjust: Mutex::new(None),
+};
+
+static ACTIVE_DOWNSTREAM_CONNECTIONS: AtomicU64 = AtomicU64::new(0);
+
+static TRACKED_STREAMS: Lazy<Mutex<HashMap<u64, TrackedStream>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(0);
+
+const fn action_index(kind: OverloadActionKind) -> usize {
+    match kind {
+        OverloadActionKind::StopAcceptingConnections => 0,
+        OverloadActionKind::DisableHttpKeepalive => 1,
+        OverloadActionKind::ReduceTimeouts => 2,
+        OverloadActionKind::StopAcceptingRequests => 3,
+        OverloadActionKind::ResetHighMemoryStream => 4,
+    }
+}
+
+pub(crate) fn action_state(kind: OverloadActionKind) -> f64 {
+    f64::from_bits(OVERLOAD_STATE.actions[action_index(kind)].load(Ordering::Relaxed))
+}
+
+fn set_action_state(kind: OverloadActionKind, state: f64) -> f64 {
+    f64::from_bits(OVERLOAD_STATE.actions[action_index(kind)].swap(state.to_bits(), Ordering::Relaxed))
+}
+
+pub(crate) fn is_saturated(kind: OverloadActionKind) -> bool {
+    action_state(kind) >= 1.0
+}
+
+/// Scaled actions fire for a fraction of the events equal to their current state.
+fn should_fire(kind: OverloadActionKind) -> bool {
+    let state = action_state(kind);
+    state >= 1.0 || (state > 0.0 && rand::random::<f64>() < state)
+}
+
+pub(crate) fn should_disable_keepalive() -> bool {
+    should_fire(OverloadActionKind::DisableHttpKeepalive)
+}
+
+pub(crate) fn should_reject_request() -> bool {
+    is_saturated(OverloadActionKind::StopAcceptingRequests)
+}
+
+/// Applies the `reduce_timeouts` action to a downstream connection idle timeout.
+pub(crate) fn scale_idle_timeout(timeout: Duration) -> Duration {
+    match *OVERLOAD_STATE.idle_timeout_adjust.lock() {
+        Some(adjust) => adjust.scale(timeout, action_state(OverloadActionKind::ReduceTimeouts)),
+        None => timeout,
+    }
+}
+
+pub(crate) fn active_downstream_connections() -> u64 {
+    ACTIVE_DOWNSTREAM_CONNECTIONS.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConnectionRejection {
+    /// The bootstrap `max_downstream_connections`, the `global_downstream_max_connections` resource monitor or the
+    /// `overload.global_downstream_max_connections` runtime limit has been reached.
+    GlobalConnectionLimit,
+    /// The `stop_accepting_connections` overload action is active.
+    Overloaded,
+}
+
+/// Counts an accepted downstream connection towards the global connection count for as long as it is alive.
+#[derive(Debug)]
+pub(crate) struct DownstreamConnectionGuard(());
+
+impl Drop for DownstreamConnectionGuard {
+    fn drop(&mut self) {
+        ACTIVE_DOWNSTREAM_CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// The runtime key Envoy sets the global downstream connection limit with, instead of the bootstrap.
+const GLOBAL_DOWNSTREAM_MAX_CONNECTIONS_RUNTIME_KEY: &str = "overload.global_downstream_max_connections";
+
+/// Installs the bootstrap `max_downstream_connections` limit, shared by all listeners.
+pub fn set_max_downstream_connections(max_downstream_connections: Option<NonZeroU64>) {
+    OVERLOAD_STATE
+        .bootstrap_max_downstream_connections
+        .store(max_downstream_connections.map_or(0, NonZeroU64::get), Ordering::Relaxed);
+}
+
+pub(crate) fn admit_downstream_connection() -> Result<DownstreamConnectionGuard, ConnectionRejection> {
+    if is_saturated(OverloadActionKind::StopAcceptingConnections) {
+        return Err(ConnectionRejection::Overloaded);
+    }
+    let max = [
+        OVERLOAD_STATE.max_downstream_connections.load(Ordering::Relaxed),
+        OVERLOAD_STATE.bootstrap_max_downstream_connections.load(Ordering::Relaxed),
+        layered_runtime::get_integer(GLOBAL_DOWNSTREAM_MAX_CONNECTIONS_RUNTIME_KEY, 0),
+    ]
+    .into_iter()
+    .filter(|max| *max != 0)
+    .min();
+    let previous = ACTIVE_DOWNSTREAM_CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+    let guard = DownstreamConnectionGuard(());
+    if max.is_some_and(|max| previous >= max) {
+        return Err(ConnectionRejection::GlobalConnectionLimit);
+    }
+    Ok(guard)
+}
+
+struct TrackedStream {
+    usage: Arc<MetricsState>,
+    reset: CancellationToken,
+}
+
+/// Registration of an in-flight HTTP stream, used by the `reset_high_memory_stream` action to pick the streams to
+/// reset. Dropping it unregisters the stream.
+pub(crate) struct StreamTracker {
+    id: u64,
+    reset: CancellationToken,
+}
+
+impl StreamTracker {
+    pub(crate) async fn reset_requested(&self) {
+        self.reset.cancelled().await;
+    }
+}
+
+impl Drop for StreamTracker {
+    fn drop(&mut self) {
+        TRACKED_STREAMS.lock().remove(&self.id);
+    }
+}
+
+/// Returns `None` unless the `reset_high_memory_stream` action is configured.
+pub(crate) fn track_stream(usage: Arc<MetricsState>) -> Option<StreamTracker> {
+    if !OVERLOAD_STATE.track_streams.load(Ordering::Relaxed) {
+        return None;
+    }
+    let id = NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed);
+    let reset = CancellationToken::new();
+    TRACKED_STREAMS.lock().insert(id, TrackedStream { usage, reset: reset.clone() });
+    Some(StreamTracker { id, reset })
+}
+
+/// Resets the in-flight stream that buffered the most request bytes.
+fn reset_most_expensive_stream() {
+    let streams = TRACKED_STREAMS.lock();
+    let most_expensive = streams
+        .iter()
+        .filter(|(_, stream)| !stream.reset.is_cancelled())
+        .max_by_key(|(_, stream)| stream.usage.bytes());
+    if let Some((id, stream)) = most_expensive {
+        debug!("overload manager: resetting stream {id} ({} bytes)", stream.usage.bytes());
+        stream.reset.cancel();
+    }
+}
+
+pub struct OverloadManager {
+    refresh_interval: Duration,
+    resource_monitors: Vec<ResourceMonitor>,
+    actions: Vec<(OverloadActionKind, Vec<Trigger>)>,
+}
+
+impl OverloadManager {
+    /// Builds the overload manager and installs the parts of the configuration that are enforced without sampling.
+    pub fn new(config: OverloadManagerConfig) -> Self {
+        let OverloadManagerConfig { refresh_interval, resource_monitors, actions } = config;
+
+        let max_downstream_connections = resource_monitors
+            .iter()
+            .find_map(|monitor| match monitor {
+                ResourceMonitor::GlobalDownstreamMaxConnections { max_active_downstream_connections } => {
+                    Some(max_active_downstream_connections.get())
+                },
+                _ => None,
+            })
+            .unwrap_or(0);
+        OVERLOAD_STATE.max_downstream_connections.store(max_downstream_connections, Ordering::Relaxed);
+
+        let idle_timeout_adjust = actions.iter().find_map(|action| match &action.action {
+            OverloadActionType::ReduceTimeouts { timer_scale_factors } => timer_scale_factors
+                .iter()
+                .find(|scale_timer| scale_timer.timer == TimerType::HttpDownstreamConnectionIdle)
+                .map(|scale_timer| scale_timer.overload_adjust),
+            _ => None,
+        });
+        *OVERLOAD_STATE.idle_timeout_adjust.lock() = idle_timeout_adjust;
+
+        let actions = actions.into_iter().map(|action| (action.action.kind(), action.triggers)).collect::<Vec<_>>();
+        OVERLOAD_STATE.track_streams.store(
+            actions.iter().any(|(kind, _)| *kind == OverloadActionKind::ResetHighMemoryStream),
+            Ordering::Relaxed,
+        );
+
+        for (kind, triggers) in &actions {
+            for trigger in triggers {
+                if !resource_monitors.iter().any(|monitor| monitor.kind() == trigger.resource) {
+                    warn!("overload action {kind:?} is triggered by {:?}, which is not configured", trigger.resource);
+                }
+            }
+        }
+
+        Self { refresh_interval, resource_monitors, actions }
+    }
+
+    pub async fn run(self) {
+        info!("overload manager started (refresh interval {:?})", self.refresh_interval);
+        let mut interval = tokio::time::interval(self.refresh_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.refresh();
+        }
+    }
+
+    fn refresh(&self) {
+        let pressures = self
+            .resource_monitors
+            .iter()
+            .filter_map(|monitor| resource_pressure(monitor).map(|pressure| (monitor.kind(), pressure)))
+            .collect::<HashMap<_, _>>();
+
+        for (kind, triggers) in &self.actions {
+            let state = triggers
+                .iter()
+                .filter_map(|trigger| pressures.get(&trigger.resource).map(|pressure| trigger.kind.evaluate(*pressure)))
+                .fold(0.0, f64::max);
+            let previous = set_action_state(*kind, state);
+            if previous <= 0.0 && state > 0.0 {
+                warn!("overload action {kind:?} activated (state {state:.2})");
+            } else if previous > 0.0 && state <= 0.0 {
+                info!("overload action {kind:?} deactivated");
+            }
+        }
+
+        if action_state(OverloadActionKind::ResetHighMemoryStream) > 0.0 {
+            reset_most_expensive_stream();
+        }
+    }
+}
+
+/// Current usage of a resource relative to its limit, or `None` if it could not be measured.
+fn resource_pressure(monitor: &ResourceMonitor) -> Option<f64> {
+    #[allow(clippy::cast_precision_loss)]
+    match monitor {
+        ResourceMonitor::FixedHeap { max_heap_size_bytes } => {
+            memory_stats::memory_stats().map(|stats| stats.physical_mem as f64 / max_heap_size_bytes.get() as f64)
+        },
+        ResourceMonitor::GlobalDownstreamMaxConnections { max_active_downstream_connections } => {
+            Some(active_downstream_connections() as f64 / max_active_downstream_connections.get() as f64)
+        },
+        ResourceMonitor::FileDescriptors { max_file_descriptors } => {
+            let max = max_file_descriptors.map(NonZeroU64::get).or_else(file_descriptor_limit)?;
+            let open = open_file_descriptors()?;
+            Some(open as f64 / max as f64)
+        },
+    }
+}
+
+fn open_file_descriptors() -> Option<u64> {
+    std::fs::read_dir("/proc/self/fd").ok().map(|entries| entries.count() as u64)
+}
+
+/// Soft `RLIMIT_NOFILE` of the process.
+fn file_descriptor_limit() -> Option<u64> {
+    let limits = std::fs::read_to_string("/proc/self/limits").ok()?;
+    limits
+        .lines()
+        .find_map(|line| line.strip_prefix("Max open files"))
+        .and_then(|values| values.split_whitespace().next())
+        .and_then(|soft| soft.parse().ok())
+        .filter(|limit| *limit > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_monitors_report_pressure() {
+        let heap = ResourceMonitor::FixedHeap { max_heap_size_bytes: NonZeroU64::MIN };
+        // any process uses more than a single byte of memory
+        assert!(resource_pressure(&heap).is_some_and(|pressure| pressure > 1.0));
+
+        if cfg!(target_os = "linux") {
+            let fds = ResourceMonitor::FileDescriptors { max_file_descriptors: None };
+            assert!(resource_pressure(&fds).is_some_and(|pressure| pressure > 0.0 && pressure < 1.0));
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Route scopes discovered over SRDS.
+//!
+//! They are shared by every HTTP connection manager discovering its scopes, which looks the scope of a request up by
+//! its key for every request, so that scopes come and go without the listeners using them being rebuilt.
+
+use std::{collections::HashMap, sync::Arc};
+
+use arc_swap::ArcSwap;
+use compact_str::CompactString;
+use once_cell::sync::Lazy;
+use orion_configuration::config::network_filters::http_connection_manager::scoped_routes::{
+    ScopeKey, ScopedRouteConfiguration,
+};
+
+#[derive(Debug, Default, Clone)]
+struct Scopes {
+    by_key: HashMap<ScopeKey, Arc<ScopedRouteConfiguration>>,
+    keys: HashMap<CompactString, ScopeKey>,
+}
+
+static SCOPES: Lazy<ArcSwap<Scopes>> = Lazy::new(ArcSwap::default);
+
+/// Adds or replaces the scope discovered under its name.
+pub fn update(scope: ScopedRouteConfiguration) {
+    let scope = Arc::new(scope);
+    SCOPES.rcu(|scopes| {
+        let mut scopes = Scopes::clone(scopes);
+        if let Some(key) = scopes.keys.insert(scope.name.clone(), scope.key.clone()) {
+            scopes.by_key.remove(&key);
+        }
+        scopes.by_key.insert(scope.key.clone(), Arc::clone(&scope));
+        scopes
+    });
+}
+
+/// Removes the scope discovered under this name, its requests no longer being routed.
+pub fn remove(name: &str) {
+    SCOPES.rcu(|scopes| {
+        let mut scopes = Scopes::clone(scopes);
+        if let Some(key) = scopes.keys.remove(name) {
+            scopes.by_key.remove(&key);
+        }
+        scopes
+    });
+}
+
+pub(crate) fn get(key: &ScopeKey) -> Option<Arc<ScopedRouteConfiguration>> {
+    SCOPES.load().by_key.get(key).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(name: &str, key: &str) -> ScopedRouteConfiguration {
+        ScopedRouteConfiguration {
+            name: name.into(),
+            on_demand: false,
+            route_configuration_name: format!("{name}_routes").into(),
+            key: ScopeKey(vec![key.into()]),
+        }
+    }
+
+    #[test]
+    fn scopes_are_looked_up_by_key() {
+        let key = |key: &str| ScopeKey(vec![key.into()]);
+        update(scope("srds_test_tenant", "srds_test_acme"));
+        assert_eq!(get(&key("srds_test_acme")).unwrap().route_configuration_name, "srds_test_tenant_routes");
+
+        update(scope("srds_test_tenant", "srds_test_initech"));
+        assert!(get(&key("srds_test_acme")).is_none(), "replaced along with its key");
+        assert!(get(&key("srds_test_initech")).is_some());
+
+        remove("srds_test_tenant");
+        assert!(get(&key("srds_test_initech")).is_none());
+    }
+}
@@ -29,7 +29,7 @@ use orion_configuration::config::{
     cluster::{TlsConfig as TlsClientConfig, TlsSecret},
     listener::TlsConfig as TlsServerConfig,
     secret::TlsCertificate as TlsCertificateConfig,
-    transport::{CommonTlsValidationContext, Secrets, TlsVersion},
+    transport::{CommonTlsValidationContext, SdsConfig, Secrets, TlsVersion},
 };
 use rustls::{
     client::danger::ServerCertVerifier,
@@ -332,7 +332,7 @@ impl TryFrom<(TlsServerConfig, &SecretManager)> for TlsConfigurator<ServerConfig
             },
             Secrets::SdsConfig(sds) => {
                 let mut certs_and_secret_ids = vec![];
-                for sds_config_name in sds {
+                for SdsConfig { name: sds_config_name, .. } in sds {
                     if let Some(certificate) = secret_manager.get_certificate(&sds_config_name)? {
                         let server_cert: ServerCert = certificate.try_into()?;
                         let secret = SecretHolder::new(sds_config_name.clone(), server_cert);
@@ -389,7 +389,7 @@ impl TryFrom<(TlsClientConfig, &SecretManager)> for TlsConfigurator<ClientConfig
 
         let (secret_id, client_certificate) = match context.secret {
             Some(TlsSecret::Certificate(cert)) => (None, Some(ClientCert::try_from(cert)?)),
-            Some(TlsSecret::SdsConfig(sds_config_name)) => {
+            Some(TlsSecret::SdsConfig(SdsConfig { name: sds_config_name, .. })) => {
                 let cert = secret_manager.get_certificate(&sds_config_name)?.map(ClientCert::try_from).transpose()?;
                 (Some(sds_config_name), cert)
             },
@@ -433,7 +433,7 @@ impl TlsConfigurator<(), ()> {
             Some(CommonTlsValidationContext::ValidationContext(validation_context)) => {
                 Ok((None, Some(CertStore::try_from(&validation_context)?.into())))
             },
-            Some(CommonTlsValidationContext::SdsConfig(sds_config_name)) => {
+            Some(CommonTlsValidationContext::SdsConfig(SdsConfig { name: sds_config_name, .. })) => {
                 if let Some(cert_store) = secret_manager.get_validation_context(&sds_config_name)? {
                     Ok((Some(sds_config_name.clone()), Some(cert_store.try_into()?)))
                 } else {
@@ -15,18 +15,34 @@
 //
 //
 
-use futures::{future::BoxFuture, FutureExt, TryFutureExt};
+use compact_str::CompactString;
+use futures::{future::BoxFuture, FutureExt, StreamExt, TryFutureExt};
 use http::{
     uri::{Authority, Scheme},
-    Request, Uri,
+    HeaderMap, HeaderValue, Request, Uri,
 };
-use std::{iter::Cycle, sync::Arc, vec::IntoIter};
+use http_body::Frame;
+use http_body_util::{BodyStream, StreamBody};
+use orion_configuration::config::cluster::ClusterSpecifier;
+use pingora_timeout::fast_timeout::fast_timeout;
+use std::{
+    iter::Cycle,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+    vec::IntoIter,
+};
+use tokio::sync::watch;
+use tracing::{info, warn};
 
 use orion_xds::grpc_deps::GrpcBody;
 use tower::Service;
 
 use crate::{
     body::{body_with_metrics::BodyWithMetrics, response_flags::BodyKind},
+    clusters::clusters_manager::{self, RoutingContext},
     listeners::http_connection_manager::{RequestHandler, TransactionHandler},
     transport::{policy::RequestExt, HttpChannel},
 };
@@ -133,3 +149,221 @@ impl Service<Request<GrpcBody>> for SimpleRoundRobinGrpcServiceLB {
         }
     }
 }
+
+/// How often the first cluster is tried again while the management server is reached through another one.
+const PRIMARY_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+const UNAVAILABLE: &str = "14";
+
+/// Tells whether a cluster can be connected to.
+type Probe = fn(&str) -> BoxFuture<'static, bool>;
+
+/// gRPC channel to a management server reachable through several clusters, in order of preference. Each stream that
+/// cannot be opened has the next cluster used for the following one, the first cluster being tried again after the
+/// last, so that losing a management server does not stop configuration updates. While another cluster is used, the
+/// first one is tried again in the background and the stream is moved back to it as soon as it can be connected to,
+/// as gRFC A71 asks. The endpoint of a cluster is picked anew for each stream, following the changes to the cluster.
+#[derive(Clone, Debug)]
+pub struct FailoverGrpcService {
+    cluster_names: Arc<[CompactString]>,
+    current: Arc<watch::Sender<usize>>,
+    retrying_primary: Arc<AtomicBool>,
+    probe: Probe,
+    retry_interval: Duration,
+    initial_metadata: HeaderMap,
+}
+
+impl FailoverGrpcService {
+    pub fn new(cluster_names: Vec<CompactString>, initial_metadata: HeaderMap) -> Self {
+        Self {
+            cluster_names: cluster_names.into(),
+            current: Arc::new(watch::Sender::new(0)),
+            retrying_primary: Arc::new(AtomicBool::new(false)),
+            probe: can_connect,
+            retry_interval: PRIMARY_RETRY_INTERVAL,
+            initial_metadata,
+        }
+    }
+
+    fn current(&self) -> usize {
+        *self.current.borrow()
+    }
+
+    /// Moves on to the cluster after the one that failed, unless another stream has already done so.
+    fn fail_over(&self, index: usize) {
+        let next = (index + 1) % self.cluster_names.len();
+        let moved = self.current.send_if_modified(|current| {
+            let moved = *current == index && next != index;
+            if moved {
+                *current = next;
+            }
+            moved
+        });
+        if moved {
+            warn!(
+                "xDS server cluster {} can't be reached, failing over to {}",
+                self.cluster_names[index], self.cluster_names[next]
+            );
+            if next != 0 {
+                self.retry_primary();
+            }
+        }
+    }
+
+    /// Tries the first cluster again in the background until it can be connected to, and switches back to it.
+    fn retry_primary(&self) {
+        if self.retrying_primary.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let this = self.clone();
+        tokio::spawn(async move {
+            let primary = this.cluster_names[0].clone();
+            while this.current() != 0 {
+                tokio::time::sleep(this.retry_interval).await;
+                if (this.probe)(&primary).await && this.current.send_replace(0) != 0 {
+                    info!("xDS server cluster {primary} can be reached again, switching back to it");
+                }
+            }
+            this.retrying_primary.store(false, Ordering::Relaxed);
+            // another stream may have failed over while this one was done retrying
+            if this.current() != 0 {
+                this.retry_primary();
+            }
+        });
+    }
+
+    /// Ends a stream opened through another cluster than the first as unavailable once the first one is back, for
+    /// the next stream to be opened through it.
+    fn until_back_to_primary(&self, index: usize, response: http::Response<GrpcBody>) -> http::Response<GrpcBody> {
+        if index == 0 {
+            return response;
+        }
+        let mut current = self.current.subscribe();
+        response.map(|body| {
+            let frames = async_stream::stream! {
+                let mut frames = BodyStream::new(body);
+                let back_to_primary = async move { current.wait_for(|current| *current == 0).await.is_ok() };
+                tokio::pin!(back_to_primary);
+                loop {
+                    tokio::select! {
+                        frame = frames.next() => match frame {
+                            Some(frame) => yield frame,
+                            None => break,
+                        },
+                        true = &mut back_to_primary => {
+                            let mut trailers = HeaderMap::new();
+                            trailers.insert("grpc-status", HeaderValue::from_static(UNAVAILABLE));
+                            trailers.insert("grpc-message", HeaderValue::from_static("switching back to the primary xDS server"));
+                            yield Ok(Frame::trailers(trailers));
+                            break;
+                        },
+                    }
+                }
+            };
+            GrpcBody::new(StreamBody::new(frames))
+        })
+    }
+}
+
+fn grpc_connection(cluster_name: &str) -> Result<GrpcService, crate::Error> {
+    let cluster_id = clusters_manager::resolve_cluster(&ClusterSpecifier::Cluster(cluster_name.into()))
+        .ok_or_else(|| format!("Failed to resolve cluster {cluster_name}"))?;
+    clusters_manager::get_grpc_connection(cluster_id, RoutingContext::None)
+}
+
+/// Whether a connection to an endpoint of the cluster can be opened, its TLS handshake included.
+fn can_connect(cluster_name: &str) -> BoxFuture<'static, bool> {
+    let connector = clusters_manager::resolve_cluster(&ClusterSpecifier::Cluster(cluster_name.into()))
+        .ok_or_else(|| crate::Error::from(format!("Failed to resolve cluster {cluster_name}")))
+        .and_then(|cluster_id| clusters_manager::get_tcp_connection(cluster_id, RoutingContext::None));
+    match connector {
+        Ok(connector) => fast_timeout(PRIMARY_RETRY_INTERVAL, connector.connect(None))
+            .map(|result| result.is_ok_and(|connection| connection.is_ok()))
+            .boxed(),
+        Err(_) => futures::future::ready(false).boxed(),
+    }
+}
+
+/// Whether the management server answered right away that it can't serve the stream, as when shutting down.
+fn is_unavailable(response: &http::Response<GrpcBody>) -> bool {
+    response.headers().get("grpc-status").is_some_and(|status| status == UNAVAILABLE)
+}
+
+impl Service<Request<GrpcBody>> for FailoverGrpcService {
+    type Response = http::Response<GrpcBody>;
+    type Error = orion_xds::grpc_deps::Error;
+    type Future = BoxFuture<'static, std::result::Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::result::Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut grpc_req: Request<GrpcBody>) -> Self::Future {
+        if self.cluster_names.is_empty() {
+            return Box::pin(futures::future::err("No xDS server clusters configured".into()));
+        }
+        let index = self.current();
+        grpc_req.headers_mut().extend(self.initial_metadata.clone());
+        match grpc_connection(&self.cluster_names[index]) {
+            Ok(mut service) => {
+                let this = self.clone();
+                service
+                    .call(grpc_req)
+                    .inspect({
+                        let this = this.clone();
+                        move |result| {
+                            if result.as_ref().map_or(true, is_unavailable) {
+                                this.fail_over(index);
+                            }
+                        }
+                    })
+                    .map_ok(move |response| this.until_back_to_primary(index, response))
+                    .boxed()
+            },
+            Err(err) => {
+                self.fail_over(index);
+                Box::pin(futures::future::err(Box::new(crate::Error::into_inner(err)) as orion_xds::grpc_deps::Error))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use http_body_util::BodyExt;
+    use orion_xds::grpc_deps::Status;
+
+    static PRIMARY_REACHABLE: AtomicBool = AtomicBool::new(false);
+
+    fn primary_reachable(_cluster_name: &str) -> BoxFuture<'static, bool> {
+        futures::future::ready(PRIMARY_REACHABLE.load(Ordering::Relaxed)).boxed()
+    }
+
+    #[tokio::test]
+    async fn switches_back_to_the_primary_once_it_can_be_reached() {
+        let service = FailoverGrpcService {
+            probe: primary_reachable,
+            retry_interval: Duration::from_millis(10),
+            ..FailoverGrpcService::new(vec!["primary".into(), "secondary".into()], HeaderMap::new())
+        };
+        service.fail_over(0);
+        assert_eq!(service.current(), 1);
+        let pending = StreamBody::new(futures::stream::pending::<Result<Frame<Bytes>, Status>>());
+        let mut body = service.until_back_to_primary(1, http::Response::new(GrpcBody::new(pending))).into_body();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(service.current(), 1, "the secondary is kept while the primary can't be reached");
+
+        PRIMARY_REACHABLE.store(true, Ordering::Relaxed);
+        let frame = fast_timeout(Duration::from_secs(1), body.frame()).await.unwrap().unwrap().unwrap();
+        let trailers = frame.into_trailers().unwrap();
+        assert_eq!(trailers.get("grpc-status").unwrap(), UNAVAILABLE);
+        assert_eq!(service.current(), 0);
+        assert!(body.frame().await.is_none());
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use crate::event_error::{EventError, TryInferFrom};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Request and connection counters of a single upstream host.
+///
+/// The counters are shared by every clone of the channel, so they add up the traffic of all the worker threads.
+#[derive(Debug, Default)]
+pub struct HostStats {
+    cx_active: AtomicU64,
+    cx_total: AtomicU64,
+    cx_connect_fail: AtomicU64,
+    rq_active: AtomicU64,
+    rq_total: AtomicU64,
+    rq_success: AtomicU64,
+    rq_error: AtomicU64,
+    rq_timeout: AtomicU64,
+    /// Server errors and failures since the last success, which outlier detection ejects the host on.
+    consecutive_5xx: AtomicU32,
+    ejection: Mutex<Ejection>,
+}
+
+/// When outlier detection last ejected the host, and how many times it did.
+#[derive(Debug, Default)]
+struct Ejection {
+    until: Option<Instant>,
+    times: u32,
+}
+
+/// A point-in-time copy of [`HostStats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct HostStatsSnapshot {
+    pub cx_active: u64,
+    pub cx_total: u64,
+    pub cx_connect_fail: u64,
+    pub rq_active: u64,
+    pub rq_total: u64,
+    pub rq_success: u64,
+    pub rq_error: u64,
+    pub rq_timeout: u64,
+}
+
+/// Keeps a request counted as active for as long as it is alive.
+pub(crate) struct ActiveRequestGuard(Arc<HostStats>);
+
+impl Drop for ActiveRequestGuard {
+    fn drop(&mut self) {
+        self.0.rq_active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl HostStats {
+    pub(crate) fn begin_request(self: &Arc<Self>) -> ActiveRequestGuard {
+        self.rq_active.fetch_add(1, Ordering::Relaxed);
+        ActiveRequestGuard(Arc::clone(self))
+    }
+
+    pub(crate) fn add_requests(&self, count: u64) {
+        self.rq_total.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Records the outcome of a request: server errors and failures count as errors, timeouts are also counted
+    /// separately.
+    pub(crate) fn record_result<T>(&self, result: &crate::Result<hyper::Response<T>>) {
+        match result {
+            Ok(response) if response.status().is_server_error() => {
+                self.rq_error.fetch_add(1, Ordering::Relaxed);
+                self.consecutive_5xx.fetch_add(1, Ordering::Relaxed);
+            },
+            Ok(_) => {
+                self.rq_success.fetch_add(1, Ordering::Relaxed);
+                self.consecutive_5xx.store(0, Ordering::Relaxed);
+            },
+            Err(err) => {
+                self.rq_error.fetch_add(1, Ordering::Relaxed);
+                self.consecutive_5xx.fetch_add(1, Ordering::Relaxed);
+                if matches!(
+                    EventError::try_infer_from(err.as_ref()),
+                    Some(EventError::RouteTimeout | EventError::PerTryTimeout)
+                ) {
+                    self.rq_timeout.fetch_add(1, Ordering::Relaxed);
+                }
+            },
+        }
+    }
+
+    pub(crate) fn consecutive_5xx(&self) -> u32 {
+        self.consecutive_5xx.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn is_ejected(&self, now: Instant) -> bool {
+        self.ejection.lock().until.is_some_and(|until| until > now)
+    }
+
+    /// Ejects the host once it failed `threshold` requests in a row, for as long as `ejection_time` says for the
+    /// number of times it was ejected. The errors start being counted again from zero.
+    pub(crate) fn eject_after(
+        &self,
+        threshold: u32,
+        ejection_time: impl FnOnce(u32) -> Duration,
+        now: Instant,
+    ) -> bool {
+        let mut ejection = self.ejection.lock();
+        if self.consecutive_5xx() < threshold {
+            // another thread got there first
+            return ejection.until.is_some_and(|until| until > now);
+        }
+        self.consecutive_5xx.store(0, Ordering::Relaxed);
+        ejection.times = ejection.times.saturating_add(1);
+        ejection.until = Some(now + ejection_time(ejection.times));
+        true
+    }
+
+    pub(crate) fn connections_opened(&self, count: u64) {
+        self.cx_total.fetch_add(count, Ordering::Relaxed);
+        self.cx_active.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn connections_closed(&self, count: u64) {
+        // saturate rather than wrap if a close is ever reported for a connection opened before the stats existed
+        let _ = self
+            .cx_active
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |active| Some(active.saturating_sub(count)));
+    }
+
+    pub(crate) fn connections_failed(&self, count: u64) {
+        self.cx_connect_fail.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HostStatsSnapshot {
+        HostStatsSnapshot {
+            cx_active: self.cx_active.load(Ordering::Relaxed),
+            cx_total: self.cx_total.load(Ordering::Relaxed),
+            cx_connect_fail: self.cx_connect_fail.load(Ordering::Relaxed),
+            rq_active: self.rq_active.load(Ordering::Relaxed),
+            rq_total: self.rq_total.load(Ordering::Relaxed),
+            rq_success: self.rq_success.load(Ordering::Relaxed),
+            rq_error: self.rq_error.load(Ordering::Relaxed),
+            rq_timeout: self.rq_timeout.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use http::StatusCode;
+
+    #[test]
+    fn counts_requests_and_outcomes() {
+        let stats = Arc::new(HostStats::default());
+
+        let guard = stats.begin_request();
+        stats.add_requests(1);
+        assert_eq!(stats.snapshot().rq_active, 1);
+        drop(guard);
+
+        let ok = hyper::Response::builder().status(StatusCode::OK).body(()).map_err(Error::from);
+        let unavailable =
+            hyper::Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body(()).map_err(Error::from);
+        let timeout: crate::Result<hyper::Response<()>> = Err(EventError::RouteTimeout.into());
+        stats.record_result(&ok);
+        stats.record_result(&unavailable);
+        stats.record_result(&timeout);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.rq_active, 0);
+        assert_eq!(snapshot.rq_total, 1);
+        assert_eq!(snapshot.rq_success, 1);
+        assert_eq!(snapshot.rq_error, 2);
+        assert_eq!(snapshot.rq_timeout, 1);
+        assert_eq!(stats.consecutive_5xx(), 2);
+        stats.record_result(&ok);
+        assert_eq!(stats.consecutive_5xx(), 0);
+    }
+
+    #[test]
+    fn ejected_after_consecutive_server_errors() {
+        let stats = HostStats::default();
+        let now = Instant::now();
+        let ejection_time = |times| Duration::from_secs(10) * times;
+        let unavailable =
+            hyper::Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body(()).map_err(Error::from);
+        stats.record_result(&unavailable);
+        assert!(!stats.eject_after(2, ejection_time, now));
+        stats.record_result(&unavailable);
+        assert!(stats.eject_after(2, ejection_time, now));
+        assert!(stats.is_ejected(now + Duration::from_secs(9)));
+        assert!(!stats.is_ejected(now + Duration::from_secs(10)));
+        assert_eq!(stats.consecutive_5xx(), 0);
+
+        stats.record_result(&unavailable);
+        stats.record_result(&unavailable);
+        assert!(stats.eject_after(2, ejection_time, now));
+        assert!(stats.is_ejected(now + Duration::from_secs(19)));
+    }
+
+    #[test]
+    fn active_connections_never_underflow() {
+        let stats = HostStats::default();
+        stats.connections_opened(2);
+        stats.connections_closed(3);
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.cx_total, 2);
+        assert_eq!(snapshot.cx_active, 0);
+    }
+}
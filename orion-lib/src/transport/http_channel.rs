@@ -17,6 +17,7 @@
 
 use super::{
     connector::LocalConnectorWithDNSResolver,
+    host_stats::HostStats,
     policy::{RequestContext, RequestExt},
 };
 use crate::{
@@ -39,7 +40,11 @@ use http_body_util::BodyExt;
 use hyper::{body::Incoming, Request, Uri};
 use hyper_rustls::{FixedServerNameResolver, HttpsConnector};
 use hyper_util::{
-    client::legacy::{connect::Connect, Builder, Client},
+    client::legacy::{
+        connect::Connect,
+        pool::{EventHandler, PoolEvent},
+        Builder, Client, PoolKey,
+    },
     rt::tokio::{TokioExecutor, TokioTimer},
 };
 use hyperlocal::UnixConnector;
@@ -58,6 +63,7 @@ use rustls::{pki_types::ServerName, ClientConfig};
 use scopeguard::defer;
 use smol_str::ToSmolStr;
 use std::{
+    any::Any,
     io::ErrorKind,
     mem,
     result::Result as StdResult,
@@ -67,14 +73,6 @@ use std::{
 };
 use tracing::{debug, info, warn};
 
-#[cfg(feature = "metrics")]
-use {
-    hyper_util::client::legacy::{
-        pool::{EventHandler, PoolEvent},
-        PoolKey,
-    },
-    std::any::Any,
-};
 const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
 
 type IncomingResult = (std::result::Result<Response<Incoming>, Error>, Duration);
@@ -104,7 +102,10 @@ pub struct HttpChannel {
     pub client: HttpChannelClient,
     pub http_version: Codec,
     pub upstream_authority: Authority, // upstream authority
+    /// The hostname of the endpoint, which `auto_host_rewrite` rewrites the authority to.
+    pub hostname: Option<Authority>,
     pub cluster_name: &'static str,
+    pub stats: Arc<HostStats>,
 }
 
 #[derive(Clone, Debug)]
@@ -125,6 +126,7 @@ pub struct HttpChannelBuilder {
     tls: Option<TlsConfigurator<ClientConfig, WantsToBuildClient>>,
     address: Option<Address>,
     authority: Option<Authority>,
+    hostname: Option<Authority>,
     bind_device_options: BindDeviceOptions,
     server_name: Option<ServerName<'static>>,
     http_protocol_options: HttpProtocolOptions,
@@ -161,6 +163,10 @@ impl HttpChannelBuilder {
         Self { authority: Some(authority), ..self }
     }
 
+    pub fn with_hostname(self, hostname: Option<Authority>) -> Self {
+        Self { hostname, ..self }
+    }
+
     pub fn with_address(self, address: Address) -> Self {
         Self { address: Some(address), ..self }
     }
@@ -192,7 +198,7 @@ impl HttpChannelBuilder {
         self.build_channel_from_authority()
     }
 
-    fn configure_hyper_client(&self) -> Builder {
+    fn configure_hyper_client(&self, stats: &Arc<HostStats>) -> Builder {
         let mut client_builder = Client::builder(TokioExecutor::new());
         client_builder
             .timer(TokioTimer::new())
@@ -205,11 +211,8 @@ impl HttpChannelBuilder {
 
         self.configure_http2_if_needed(&mut client_builder, configured_upstream_http_version);
 
-        #[cfg(feature = "metrics")]
-        {
-            let cluster_name = self.cluster_name.unwrap_or_default();
-            client_builder.pool_event_handler(EventHandler::new(update_upstream_stats, cluster_name));
-        }
+        let tag = UpstreamStatsTag { cluster_name: self.cluster_name.unwrap_or_default(), stats: Arc::clone(stats) };
+        client_builder.pool_event_handler(EventHandler::new(update_upstream_stats, tag));
 
         client_builder
     }
@@ -238,7 +241,8 @@ impl HttpChannelBuilder {
 
     fn build_channel_from_authority(self) -> crate::Result<HttpChannel> {
         let authority = self.authority.clone().ok_or_else(|| Error::from("Authority is mandatory"))?;
-        let client_builder = self.configure_hyper_client();
+        let stats = Arc::new(HostStats::default());
+        let client_builder = self.configure_hyper_client(&stats);
 
         if let Some(tls_context) = self.tls {
             // Build TLS client inline to avoid ownership issues
@@ -272,7 +276,9 @@ impl HttpChannelBuilder {
                 )),
                 http_version: self.http_protocol_options.codec,
                 upstream_authority: authority,
+                hostname: self.hostname,
                 cluster_name: self.cluster_name.unwrap_or_default(),
+                stats,
             })
         } else {
             // Build plain client inline
@@ -287,7 +293,9 @@ impl HttpChannelBuilder {
                 client: HttpChannelClient::Plain(Arc::new(LocalObject::new(client_builder, connector))),
                 http_version: self.http_protocol_options.codec,
                 upstream_authority: authority,
+                hostname: self.hostname,
                 cluster_name: self.cluster_name.unwrap_or_default(),
+                stats,
             })
         }
     }
@@ -297,7 +305,8 @@ impl HttpChannelBuilder {
 
         match &self.address {
             Some(Address::Pipe(name, _)) => {
-                let client_builder = self.configure_hyper_client();
+                let stats = Arc::new(HostStats::default());
+                let client_builder = self.configure_hyper_client(&stats);
                 warn!("Building address from a pipe {name}");
                 let uri: hyper::Uri = Uri::new(name.clone(), "").into();
                 let authority = uri.authority().cloned().unwrap_or(Authority::from_static("none"));
@@ -307,7 +316,9 @@ impl HttpChannelBuilder {
                     client: HttpChannelClient::Unix(uri, Arc::new(client_builder.build(UnixConnector))),
                     http_version: self.http_protocol_options.codec,
                     upstream_authority: authority,
+                    hostname: self.hostname,
                     cluster_name: self.cluster_name.unwrap_or_default(),
+                    stats,
                 })
             },
             _ => Err(Error::from("Trying to build a pipe address from invalid address")),
@@ -315,11 +326,17 @@ impl HttpChannelBuilder {
     }
 }
 
-#[cfg(feature = "metrics")]
-#[allow(clippy::needless_pass_by_value)]
+/// Identifies the cluster and host whose connection pool events are being reported.
+struct UpstreamStatsTag {
+    cluster_name: &'static str,
+    stats: Arc<HostStats>,
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::too_many_lines)]
 fn update_upstream_stats(event: PoolEvent, tag: &dyn Any, keys: &[&PoolKey]) {
-    use tracing::debug;
-    let cluster_name = *(tag.downcast_ref::<&str>().unwrap_or(&""));
+    let tag = tag.downcast_ref::<UpstreamStatsTag>();
+    let cluster_name = tag.map(|tag| tag.cluster_name).unwrap_or_default();
+    let host_stats = tag.map(|tag| tag.stats.as_ref());
     let shard_id = std::thread::current().id();
 
     for key in keys {
@@ -327,6 +344,14 @@ fn update_upstream_stats(event: PoolEvent, tag: &dyn Any, keys: &[&PoolKey]) {
     }
 
     let num_events = keys.len() as u64;
+    if let Some(host_stats) = host_stats {
+        match event {
+            PoolEvent::NewConnection => host_stats.connections_opened(num_events),
+            PoolEvent::IdleConnectionClosed | PoolEvent::ConnectionClosed => host_stats.connections_closed(num_events),
+            PoolEvent::ConnectionError | PoolEvent::ConnectionTimeout => host_stats.connections_failed(num_events),
+        }
+    }
+
     match event {
         PoolEvent::NewConnection => {
             with_metric!(
@@ -407,7 +432,7 @@ fn update_upstream_stats(event: PoolEvent, tag: &dyn Any, keys: &[&PoolKey]) {
 impl<'a> RequestHandler<RequestExt<'a, Request<BodyWithMetrics<PolyBody>>>> for &HttpChannel {
     async fn to_response(
         self,
-        _trans_handler: &TransactionHandler,
+        trans_handler: &TransactionHandler,
         request: RequestExt<'a, Request<BodyWithMetrics<PolyBody>>>,
     ) -> Result<Response<crate::PolyBody>> {
         let version = request.req.version();
@@ -419,14 +444,16 @@ impl<'a> RequestHandler<RequestExt<'a, Request<BodyWithMetrics<PolyBody>>>> for
                 let req = maybe_normalize_uri(request.req, false)?;
 
                 let result = if let Some(t) = route_timeout {
-                    match fast_timeout(t, self.send_request(retry_policy, client, req, cluster_name)).await {
+                    match fast_timeout(t, self.send_request(trans_handler, retry_policy, client, req, cluster_name))
+                        .await
+                    {
                         Ok(result) => result,
                         Err(_) => (Err(EventError::RouteTimeout.into()), t),
                     }
                 } else {
-                    self.send_request(retry_policy, client, req, cluster_name).await
+                    self.send_request(trans_handler, retry_policy, client, req, cluster_name).await
                 };
-                HttpChannel::handle_response(result, route_timeout, version)
+                self.handle_response(result, route_timeout, version)
             },
             HttpChannelClient::Tls(context) => {
                 let ClientContext { configured_upstream_http_version, client: sender } = context;
@@ -439,15 +466,17 @@ impl<'a> RequestHandler<RequestExt<'a, Request<BodyWithMetrics<PolyBody>>>> for
                 let req = maybe_normalize_uri(request.req, true)?;
                 let req = maybe_change_http_protocol_version(req, configured_version)?;
                 let result = if let Some(t) = route_timeout {
-                    match fast_timeout(t, self.send_request(retry_policy, client, req, cluster_name)).await {
+                    match fast_timeout(t, self.send_request(trans_handler, retry_policy, client, req, cluster_name))
+                        .await
+                    {
                         Ok(result) => result,
                         Err(_) => (Err(EventError::RouteTimeout.into()), t),
                     }
                 } else {
-                    self.send_request(retry_policy, client, req, cluster_name).await
+                    self.send_request(trans_handler, retry_policy, client, req, cluster_name).await
                 };
 
-                HttpChannel::handle_response(result, route_timeout, version)
+                self.handle_response(result, route_timeout, version)
             },
             HttpChannelClient::Unix(uri, sender) => {
                 let RequestContext { route_timeout, retry_policy } = request.ctx.clone();
@@ -460,14 +489,16 @@ impl<'a> RequestHandler<RequestExt<'a, Request<BodyWithMetrics<PolyBody>>>> for
                 *req.uri_mut() = Uri::from_parts(parts).expect("We do expect this to work");
 
                 let result = if let Some(t) = route_timeout {
-                    match fast_timeout(t, self.send_request(retry_policy, client, req, cluster_name)).await {
+                    match fast_timeout(t, self.send_request(trans_handler, retry_policy, client, req, cluster_name))
+                        .await
+                    {
                         Ok(result) => result,
                         Err(_) => (Err(EventError::RouteTimeout.into()), t),
                     }
                 } else {
-                    self.send_request(retry_policy, client, req, cluster_name).await
+                    self.send_request(trans_handler, retry_policy, client, req, cluster_name).await
                 };
-                HttpChannel::handle_response(result, route_timeout, version)
+                self.handle_response(result, route_timeout, version)
             },
         }
     }
@@ -479,6 +510,7 @@ impl HttpChannel {
     /// duration does not include the time spent receiving the Body of the Response.
     async fn send_request<C>(
         &self,
+        trans_handler: &TransactionHandler,
         retry_policy: Option<&RetryPolicy>,
         sender: &Client<C, BodyWithMetrics<PolyBody>>,
         req: Request<BodyWithMetrics<PolyBody>>,
@@ -489,6 +521,7 @@ impl HttpChannel {
     {
         let thread_id = std::thread::current().id();
 
+        let _active_request = self.stats.begin_request();
         with_metric!(clusters::UPSTREAM_RQ_ACTIVE, add, 1, thread_id, &[KeyValue::new("cluster", cluster_name)]);
         defer! {
             with_metric!(clusters::UPSTREAM_RQ_ACTIVE, sub, 1, thread_id, &[KeyValue::new("cluster", cluster_name)]);
@@ -497,7 +530,8 @@ impl HttpChannel {
         match retry_policy {
             Some(policy) if policy.is_retriable(&req) => {
                 let (resp, dur, total_request) =
-                    self.send_with_retry(policy, sender, req, thread_id, cluster_name).await;
+                    self.send_with_retry(trans_handler, policy, sender, req, thread_id, cluster_name).await;
+                self.stats.add_requests(total_request as u64);
                 with_metric!(
                     clusters::UPSTREAM_RQ_TOTAL,
                     add,
@@ -515,16 +549,20 @@ impl HttpChannel {
                 (resp, dur)
             },
             _ => {
+                self.stats.add_requests(1);
                 with_metric!(clusters::UPSTREAM_RQ_TOTAL, add, 1, thread_id, &[KeyValue::new("cluster", cluster_name)]);
                 let start_time = Instant::now();
+                trans_handler.upstream_request_attempted();
                 let resp = sender.request(req).await.map_err(Error::from);
                 (resp, start_time.elapsed())
             },
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn send_with_retry<C>(
         &self,
+        trans_handler: &TransactionHandler,
         retry_policy: &RetryPolicy,
         sender: &Client<C, BodyWithMetrics<PolyBody>>,
         req: Request<BodyWithMetrics<PolyBody>>,
@@ -551,6 +589,7 @@ impl HttpChannel {
         for (index, back_off) in retry_policy.exponential_back_off().iter().enumerate() {
             let back_off = back_off.unwrap_or(Duration::from_secs(1));
             total_requests += 1;
+            trans_handler.upstream_request_attempted();
             let cloned_body =
                 BodyWithMetrics { inner: body.clone().into(), guard: guard.clone(), state: state.clone() };
 
@@ -604,10 +643,12 @@ impl HttpChannel {
     }
 
     fn handle_response(
+        &self,
         result: IncomingResult,
         route_timeout: Option<Duration>,
         version: http::Version,
     ) -> StdResult<hyper::Response<PolyBody>, Error> {
+        self.stats.record_result(&result.0);
         match result {
             (Ok(response), elapsed) => {
                 // calculate the remaining timeout (relative to the route timeout) for receiving
@@ -20,6 +20,7 @@ use tokio::io::{AsyncRead, AsyncWrite};
 pub mod bind_device;
 pub mod connector;
 mod grpc_channel;
+mod host_stats;
 mod http_channel;
 mod resolver;
 pub mod tcp_channel;
@@ -31,7 +32,8 @@ pub mod tlv_listener_filter;
 pub mod transport_socket;
 
 pub use self::{
-    grpc_channel::{GrpcService, SimpleRoundRobinGrpcServiceLB},
+    grpc_channel::{FailoverGrpcService, GrpcService, SimpleRoundRobinGrpcServiceLB},
+    host_stats::HostStatsSnapshot,
     http_channel::{HttpChannel, HttpChannelBuilder},
     proxy_protocol::ProxyProtocolReader,
     tcp_channel::TcpChannelConnector,
@@ -39,6 +39,8 @@ pub static DOWNSTREAM_RQ_TOTAL: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLoc
 pub static DOWNSTREAM_RQ_ACTIVE: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
 pub static DOWNSTREAM_CX_RX_BYTES_TOTAL: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
 pub static DOWNSTREAM_CX_TX_BYTES_TOTAL: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
+pub static DOWNSTREAM_CX_OVERLOAD_DISABLE_KEEPALIVE: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
+pub static DOWNSTREAM_CX_IDLE_TIMEOUT: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
 
 #[cfg(feature = "metrics")]
 pub(crate) fn init_http_metrics() {
@@ -126,4 +128,16 @@ pub(crate) fn init_http_metrics() {
         "downstream_cx_tx_bytes_total",
         "Total number of bytes sent on downstream HTTP connections"
     );
+    init_observable_counter!(
+        DOWNSTREAM_CX_OVERLOAD_DISABLE_KEEPALIVE,
+        "http",
+        "downstream_cx_overload_disable_keepalive",
+        "Total HTTP/1.x connections for which keepalive has been disabled due to overload"
+    );
+    init_observable_counter!(
+        DOWNSTREAM_CX_IDLE_TIMEOUT,
+        "http",
+        "downstream_cx_idle_timeout",
+        "Total downstream HTTP connections closed due to idle timeout"
+    );
 }
@@ -26,6 +26,9 @@ pub static DOWNSTREAM_CX_TOTAL: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLoc
 pub static DOWNSTREAM_CX_DESTROY: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
 pub static DOWNSTREAM_CX_ACTIVE: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
 pub static NO_FILTER_CHAIN_MATCH: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
+pub static DOWNSTREAM_CX_OVERFLOW: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
+pub static DOWNSTREAM_CX_OVERLOAD_REJECT: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
+pub static DOWNSTREAM_GLOBAL_CX_OVERFLOW: OnceLock<Metric<ShardedU64<ThreadId>>> = OnceLock::new();
 pub static DOWNSTREAM_CX_LENGTH_MS: OnceLock<Histogram<u64>> = OnceLock::new();
 
 #[cfg(feature = "metrics")]
@@ -50,5 +53,23 @@ pub(crate) fn init_listeners_metrics() {
         "no_filter_chain_match",
         "Total connections with no filter chain match"
     );
+    init_observable_counter!(
+        DOWNSTREAM_CX_OVERFLOW,
+        "listeners",
+        "downstream_cx_overflow",
+        "Total connections rejected due to enforcement of listener connection limit"
+    );
+    init_observable_counter!(
+        DOWNSTREAM_CX_OVERLOAD_REJECT,
+        "listeners",
+        "downstream_cx_overload_reject",
+        "Total connections rejected due to configured overload actions"
+    );
+    init_observable_counter!(
+        DOWNSTREAM_GLOBAL_CX_OVERFLOW,
+        "listeners",
+        "downstream_global_cx_overflow",
+        "Total connections rejected due to enforcement of global connection limit"
+    );
     init_observable_gauge!(DOWNSTREAM_CX_ACTIVE, "listeners", "downstream_cx_active", "Total active connections");
 }
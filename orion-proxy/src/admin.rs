@@ -20,18 +20,34 @@ use std::{
     time::{Duration, Instant},
 };
 
-use axum::{extract::State, routing::get, Json, Router};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
 use orion_configuration::config::Bootstrap;
 use orion_error::{Error, Result};
 use orion_lib::{ConfigurationSenders, SecretManager};
+use orion_xds::xds::client_status::ClientStatusServer;
 use parking_lot::RwLock;
-use serde::Serialize;
-use serde_json::{json, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
 
+mod clusters;
 #[cfg(feature = "config-dump")]
 mod config_dump;
+mod lifecycle;
+mod listeners;
+pub(crate) mod logging;
 #[cfg(feature = "prometheus")]
 mod prometheus;
+mod runtime;
+mod xds_status;
+
+const CLIENT_STATUS_SERVICE_PATH: &str = "/envoy.service.status.v3.ClientStatusDiscoveryService/{*method}";
 
 #[allow(dead_code)]
 #[derive(Clone)]
@@ -41,6 +57,8 @@ struct AdminState {
     secret_manager: Arc<RwLock<SecretManager>>,
     server_info: ServerInfo,
     server_startup: Instant,
+    /// Cancelled to shut the proxy down.
+    shutdown: CancellationToken,
 }
 
 #[allow(dead_code)]
@@ -74,7 +92,21 @@ fn build_admin_router(admin_state: AdminState) -> Router {
         router = router.route("/stats", get(stats_handler));
     }
 
+    router = router.route("/clusters", get(clusters::get_clusters));
+    router = router.route("/drain_listeners", post(lifecycle::post_drain_listeners));
+    router = router.route("/healthcheck/fail", post(lifecycle::post_healthcheck_fail));
+    router = router.route("/healthcheck/ok", post(lifecycle::post_healthcheck_ok));
+    router = router.route("/listeners", get(listeners::get_listeners));
+    router = router.route("/logging", get(logging::get_logging).post(logging::post_logging));
+    router = router.route("/quitquitquit", post(lifecycle::post_quitquitquit));
     router = router.route("/ready", get(get_ready));
+    router = router.route("/runtime", get(runtime::get_runtime));
+    router = router.route("/runtime_modify", post(runtime::post_runtime_modify));
+    router = router.route("/xds_status", get(xds_status::get_xds_status));
+
+    // the CSDS gRPC service is served along with the admin endpoints, over HTTP/2
+    let node = admin_state.bootstrap.node.clone().unwrap_or_default();
+    router = router.route_service(CLIENT_STATUS_SERVICE_PATH, ClientStatusServer::new(node).into_service());
 
     router.with_state(admin_state)
 }
@@ -83,6 +115,7 @@ pub async fn start_admin_server(
     bootstrap: Bootstrap,
     configuration_senders: Vec<ConfigurationSenders>,
     secret_manager: Arc<RwLock<SecretManager>>,
+    shutdown: CancellationToken,
 ) -> Result<()> {
     let admin_state = AdminState {
         bootstrap: bootstrap.clone(),
@@ -90,6 +123,7 @@ pub async fn start_admin_server(
         secret_manager,
         server_info: ServerInfo::default(),
         server_startup: Instant::now(),
+        shutdown,
     };
     let app = build_admin_router(admin_state);
     let address =
@@ -100,9 +134,49 @@ pub async fn start_admin_server(
     Ok(())
 }
 
-async fn get_ready(State(mut admin_state): State<AdminState>) -> Json<Value> {
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// The `?format=` query parameter accepted by the endpoints that can answer in text or JSON.
+#[derive(Debug, Default, Deserialize)]
+struct FormatQuery {
+    #[serde(default)]
+    format: OutputFormat,
+}
+
+impl OutputFormat {
+    fn render<T: Serialize>(self, value: &T, text: impl FnOnce(&T) -> String) -> Response {
+        match self {
+            OutputFormat::Json => Json(json!(value)).into_response(),
+            OutputFormat::Text => {
+                (StatusCode::OK, [(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")], text(value))
+                    .into_response()
+            },
+        }
+    }
+}
+
+/// Reports the server as initializing, with a 503, until its init targets are ready, and as draining once its
+/// health check has been failed or its listeners drained.
+async fn get_ready(State(mut admin_state): State<AdminState>) -> Response {
     admin_state.server_info.uptime_all_epochs = Some(admin_state.server_startup.elapsed());
-    Json(json!(admin_state.server_info))
+    if orion_lib::lifecycle::health_check_failed() || orion_lib::lifecycle::is_draining() {
+        admin_state.server_info.state = ProxyState::Draining;
+    } else if !orion_lib::lifecycle::is_initialized() {
+        admin_state.server_info.state = ProxyState::Initializing;
+    }
+    let status = match admin_state.server_info.state {
+        ProxyState::Live => StatusCode::OK,
+        ProxyState::Draining | ProxyState::PreInitializing | ProxyState::Initializing => {
+            StatusCode::SERVICE_UNAVAILABLE
+        },
+    };
+    (status, Json(json!(admin_state.server_info))).into_response()
 }
 
 #[cfg(test)]
@@ -125,6 +199,7 @@ mod tests {
             secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
             server_info: ServerInfo::default(),
             server_startup,
+            shutdown: CancellationToken::new(),
         };
         let app = build_admin_router(admin_state);
         let server = TestServer::new(app).unwrap();
@@ -152,5 +227,23 @@ mod tests {
 
         // The uptime should be at least 10ms (our sleep)
         assert!(uptime_duration >= Duration::from_millis(10));
+
+        // a failed health check makes the server draining, until it is restored
+        server.post("/healthcheck/fail").await.assert_status_ok();
+        let response = server.get("/ready").await;
+        response.assert_status_service_unavailable();
+        assert_eq!(response.json::<serde_json::Value>()["state"], "Draining");
+
+        server.post("/healthcheck/ok").await.assert_status_ok();
+        server.get("/ready").await.assert_status_ok();
+
+        // the server is initializing until its init targets are ready
+        orion_lib::lifecycle::add_init_target("admin_test_lds");
+        let response = server.get("/ready").await;
+        response.assert_status_service_unavailable();
+        assert_eq!(response.json::<serde_json::Value>()["state"], "Initializing");
+
+        orion_lib::lifecycle::init_target_ready("admin_test_lds");
+        server.get("/ready").await.assert_status_ok();
     }
 }
@@ -0,0 +1,211 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use super::FormatQuery;
+use axum::{extract::Query, response::Response};
+use orion_configuration::config::cluster::HealthStatus;
+use orion_lib::clusters::{
+    clusters_manager::get_clusters_status,
+    status::{ClusterStatus, HostStatus},
+};
+use serde::Serialize;
+use std::fmt::Write;
+
+#[derive(Serialize)]
+struct ClustersResponse {
+    cluster_statuses: Vec<ClusterStatus>,
+}
+
+pub async fn get_clusters(Query(query): Query<FormatQuery>) -> Response {
+    let response = ClustersResponse { cluster_statuses: get_clusters_status() };
+    query.format.render(&response, |response| render_text(&response.cluster_statuses))
+}
+
+/// Renders one `cluster::host::field::value` line per host field, like Envoy does.
+fn render_text(clusters: &[ClusterStatus]) -> String {
+    let mut text = String::new();
+    for cluster in clusters {
+        let name = cluster.name;
+        for host in &cluster.hosts {
+            let address = &host.address;
+            let stats = &host.stats;
+            for (field, value) in [
+                ("cx_active", stats.cx_active),
+                ("cx_connect_fail", stats.cx_connect_fail),
+                ("cx_total", stats.cx_total),
+                ("rq_active", stats.rq_active),
+                ("rq_error", stats.rq_error),
+                ("rq_success", stats.rq_success),
+                ("rq_timeout", stats.rq_timeout),
+                ("rq_total", stats.rq_total),
+            ] {
+                let _ = writeln!(text, "{name}::{address}::{field}::{value}");
+            }
+            let _ = writeln!(text, "{name}::{address}::health_flags::{}", health_flags(host));
+            let _ = writeln!(text, "{name}::{address}::weight::{}", host.weight);
+            let _ = writeln!(text, "{name}::{address}::priority::{}", host.priority);
+            let _ = writeln!(text, "{name}::{address}::outlier_ejected::{}", host.outlier_ejected);
+        }
+    }
+    text
+}
+
+/// Envoy's health flags: an unhealthy host counts as failing active health checking unless EDS already had it
+/// unhealthy, since that is what it starts from.
+fn health_flags(host: &HostStatus) -> String {
+    let mut flags = String::new();
+    if host.health == HealthStatus::Unhealthy && host.eds_health == HealthStatus::Healthy {
+        flags.push_str("/failed_active_hc");
+    }
+    if host.outlier_ejected {
+        flags.push_str("/failed_outlier_check");
+    }
+    if host.eds_health == HealthStatus::Unhealthy {
+        flags.push_str("/failed_eds_health");
+    }
+    if flags.is_empty() {
+        flags.push_str("healthy");
+    }
+    flags
+}
+
+#[cfg(test)]
+mod clusters_tests {
+    use super::{super::*, *};
+    use axum_test::TestServer;
+    use compact_str::CompactString;
+    use orion_configuration::config::{
+        cluster::{
+            Cluster, ClusterDiscoveryType, ClusterLoadAssignment, HttpProtocolOptions, LbEndpoint, LbPolicy,
+            LocalityLbEndpoints,
+        },
+        core::envoy_conversions::Address,
+        transport::BindDeviceOptions,
+        Bootstrap,
+    };
+    use orion_lib::clusters::status::HostStatsSnapshot;
+    use std::{num::NonZeroU32, time::Duration};
+
+    fn admin_server() -> TestServer {
+        let admin_state = AdminState {
+            bootstrap: Bootstrap::default(),
+            configuration_senders: vec![],
+            secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
+            server_info: ServerInfo::default(),
+            server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
+        };
+        TestServer::new(build_admin_router(admin_state)).unwrap()
+    }
+
+    fn add_cluster(name: &str, port: u16, health_status: HealthStatus) {
+        let cluster = Cluster {
+            name: CompactString::from(name),
+            discovery_settings: ClusterDiscoveryType::Static(ClusterLoadAssignment {
+                cluster_name: name.to_owned(),
+                endpoints: vec![LocalityLbEndpoints {
+                    priority: 1,
+                    locality: None,
+                    lb_endpoints: vec![LbEndpoint {
+                        address: Address::Socket("127.0.0.1".to_owned(), port),
+                        hostname: None,
+                        health_status,
+                        load_balancing_weight: NonZeroU32::new(3).unwrap(),
+                    }],
+                }],
+            }),
+            transport_socket: None,
+            bind_device_options: BindDeviceOptions::default(),
+            load_balancing_policy: LbPolicy::default(),
+            http_protocol_options: HttpProtocolOptions::default(),
+            health_check: None,
+            outlier_detection: None,
+            connect_timeout: Some(Duration::from_secs(5)),
+            cleanup_interval: None,
+            internal_transport_socket: None,
+        };
+        let secret_manager = orion_lib::SecretManager::default();
+        let partial_cluster =
+            orion_lib::clusters::cluster::PartialClusterType::try_from((cluster, &secret_manager)).unwrap();
+        orion_lib::clusters::clusters_manager::add_cluster(partial_cluster).unwrap();
+    }
+
+    #[tokio::test]
+    async fn clusters_json() {
+        add_cluster("clusters_json", 9100, HealthStatus::Healthy);
+        let server = admin_server();
+
+        let response = server.get("/clusters").add_query_param("format", "json").await;
+        response.assert_status_ok();
+        let value: serde_json::Value = response.json();
+        let cluster = value["cluster_statuses"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|cluster| cluster["name"] == "clusters_json")
+            .unwrap();
+        let host = &cluster["hosts"][0];
+        assert_eq!(host["address"], "127.0.0.1:9100");
+        assert_eq!(host["health"], "Healthy");
+        assert_eq!(host["eds_health"], "Healthy");
+        assert_eq!(host["weight"], 3);
+        assert_eq!(host["priority"], 1);
+        assert_eq!(host["outlier_ejected"], false);
+        assert_eq!(host["stats"]["rq_active"], 0);
+    }
+
+    #[tokio::test]
+    async fn clusters_text() {
+        add_cluster("clusters_text", 9101, HealthStatus::Unhealthy);
+        let server = admin_server();
+
+        let response = server.get("/clusters").await;
+        response.assert_status_ok();
+        let text = response.text();
+        assert!(text.contains("clusters_text::127.0.0.1:9101::health_flags::/failed_eds_health\n"));
+        assert!(text.contains("clusters_text::127.0.0.1:9101::weight::3\n"));
+        assert!(text.contains("clusters_text::127.0.0.1:9101::cx_active::0\n"));
+    }
+
+    #[test]
+    fn health_flags_tell_the_failures_apart() {
+        let host = |health, eds_health, outlier_ejected| HostStatus {
+            address: "127.0.0.1:9102".to_owned(),
+            health,
+            eds_health,
+            weight: 1,
+            priority: 0,
+            locality: None,
+            outlier_ejected,
+            stats: HostStatsSnapshot::default(),
+        };
+        assert_eq!(health_flags(&host(HealthStatus::Healthy, HealthStatus::Healthy, false)), "healthy");
+        assert_eq!(health_flags(&host(HealthStatus::Unhealthy, HealthStatus::Healthy, false)), "/failed_active_hc");
+        assert_eq!(health_flags(&host(HealthStatus::Unhealthy, HealthStatus::Unhealthy, false)), "/failed_eds_health");
+        assert_eq!(
+            health_flags(&host(HealthStatus::Unhealthy, HealthStatus::Healthy, true)),
+            "/failed_active_hc/failed_outlier_check"
+        );
+    }
+
+    #[tokio::test]
+    async fn clusters_unknown_format() {
+        let server = admin_server();
+        let response = server.get("/clusters").add_query_param("format", "yaml").await;
+        response.assert_status_bad_request();
+    }
+}
@@ -16,7 +16,11 @@
 //
 
 use super::AdminState;
-use axum::{extract::State, response::Json};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
 use orion_configuration::config::{
     cluster::{ClusterDiscoveryType, LocalityLbEndpoints as LocalityLbEndpointsConfig},
     core::DataSource,
@@ -25,12 +29,57 @@ use orion_configuration::config::{
     secret::{Secret, Type},
 };
 use orion_lib::{
-    clusters::clusters_manager::get_all_clusters, ConfigDump, ConfigurationSenders, ListenerConfigurationChange,
+    clusters::clusters_manager::{get_all_clusters, get_clusters_status},
+    extension_configs, ConfigDump, ConfigurationSenders, ListenerConfigurationChange,
 };
-use serde_json::{json, Value};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
 use tokio::sync::mpsc;
 
-use crate::xds_configurator::send_change_to_runtimes;
+use crate::{xds_cache, xds_configurator::send_change_to_runtimes};
+
+mod envoy_format;
+
+/// The sections of the native config dump that can be selected with `?resource=`.
+const NATIVE_RESOURCES: &[&str] = &[
+    "bootstrap",
+    "listeners",
+    "clusters",
+    "ecds_filter_http",
+    "endpoints",
+    "routes",
+    "secrets",
+    "cluster_statuses",
+    "xds_cache",
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DumpFormat {
+    /// Orion's own configuration types.
+    #[default]
+    Native,
+    /// Envoy's `envoy.admin.v3.ConfigDump`, holding the xDS resources as they were received.
+    Envoy,
+}
+
+/// The query parameters of `/config_dump`, which follow Envoy's.
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigDumpQuery {
+    /// Only dumps one kind of resource: a section of the native dump (`clusters`, ...) or a repeated field of one of
+    /// the Envoy config dumps (`dynamic_active_clusters`, ...).
+    resource: Option<String>,
+    /// Comma-separated field paths to keep, applied to each resource when `resource` is set and to each config dump
+    /// otherwise.
+    mask: Option<String>,
+    /// Only dumps the resources whose name matches.
+    name_regex: Option<String>,
+    /// A flag: also dumps the endpoints of the clusters as they currently are, with their health.
+    include_eds: Option<String>,
+    #[serde(default)]
+    format: DumpFormat,
+}
 
 pub fn redact_secrets(secrets: Vec<Secret>) -> Vec<Secret> {
     secrets
@@ -47,7 +96,50 @@ pub fn redact_secrets(secrets: Vec<Secret>) -> Vec<Secret> {
         .collect()
 }
 
-pub async fn get_config_dump(State(admin_state): State<AdminState>) -> Json<Value> {
+pub async fn get_config_dump(State(admin_state): State<AdminState>, Query(query): Query<ConfigDumpQuery>) -> Response {
+    let name_regex = match query.name_regex.as_deref().map(Regex::new).transpose() {
+        Ok(name_regex) => name_regex,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("invalid name_regex: {e}\n")).into_response(),
+    };
+    let include_eds = query.include_eds.is_some();
+    let mut dump = match query.format {
+        DumpFormat::Native => native_config_dump(admin_state, include_eds).await,
+        DumpFormat::Envoy => match envoy_format::config_dump(&admin_state.bootstrap, include_eds) {
+            Ok(dump) => dump,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("{e}\n")).into_response(),
+        },
+    };
+    let mask = query.mask.as_deref().map(parse_mask);
+
+    if let Some(resource) = query.resource.as_deref() {
+        let Some(resources) = select_resources(dump, resource, query.format) else {
+            return (StatusCode::NOT_FOUND, format!("unknown resource \"{resource}\"\n")).into_response();
+        };
+        let mut resources = filter_by_name(resources, name_regex.as_ref());
+        if let Some(mask) = &mask {
+            resources = apply_mask(&resources, mask);
+        }
+        return match query.format {
+            DumpFormat::Native => Json(json!({ resource: resources })).into_response(),
+            DumpFormat::Envoy => Json(json!({ "configs": resources })).into_response(),
+        };
+    }
+
+    // in the Envoy format, each config dump is filtered on its own
+    let dumps = match query.format {
+        DumpFormat::Native => &mut dump,
+        DumpFormat::Envoy => &mut dump["configs"],
+    };
+    if let Some(name_regex) = &name_regex {
+        filter_all_by_name(dumps, name_regex);
+    }
+    if let Some(mask) = &mask {
+        *dumps = apply_mask(dumps, mask);
+    }
+    Json(dump).into_response()
+}
+
+async fn native_config_dump(admin_state: AdminState, include_eds: bool) -> Value {
     // Unwrap listeners and routes configuration channels
     let mut listeners_senders = Vec::with_capacity(admin_state.configuration_senders.len());
     for ConfigurationSenders { listener_configuration_sender, .. } in admin_state.configuration_senders {
@@ -88,16 +180,123 @@ pub async fn get_config_dump(State(admin_state): State<AdminState>) -> Json<Valu
         .flat_map(|cluster| match &cluster.discovery_settings {
             ClusterDiscoveryType::Static(load_assignment)
             | ClusterDiscoveryType::Eds(Some(load_assignment), _)
-            | ClusterDiscoveryType::StrictDns(load_assignment) => load_assignment.endpoints.clone(),
+            | ClusterDiscoveryType::StrictDns(load_assignment)
+            | ClusterDiscoveryType::LogicalDns(load_assignment) => load_assignment.endpoints.clone(),
             ClusterDiscoveryType::Eds(None, _) | ClusterDiscoveryType::OriginalDst(_) => vec![],
         })
         .collect();
     config.endpoints = (!endpoints.is_empty()).then_some(endpoints);
 
+    let ecds_filter_http = extension_configs::http_filters();
+    config.ecds_filter_http = (!ecds_filter_http.is_empty()).then_some(ecds_filter_http);
+
     let secrets: Vec<Secret> = redact_secrets(admin_state.secret_manager.read().get_all_secrets());
     config.secrets = (!secrets.is_empty()).then_some(secrets);
 
-    Json(json!(config))
+    let mut dump = json!(config);
+    if include_eds {
+        dump["cluster_statuses"] = json!(get_clusters_status());
+    }
+    let xds_cache = xds_cache::status();
+    if !xds_cache.is_empty() {
+        dump["xds_cache"] = json!(xds_cache);
+    }
+    dump
+}
+
+/// The resources of one kind: the section of the native dump, or the items of the Envoy config dump holding them,
+/// each tagged with its type. `None` if there is no such kind of resource.
+fn select_resources(mut dump: Value, resource: &str, format: DumpFormat) -> Option<Value> {
+    match format {
+        DumpFormat::Native => NATIVE_RESOURCES
+            .contains(&resource)
+            .then(|| dump.get_mut(resource).map(Value::take).unwrap_or_else(|| Value::Array(Vec::new()))),
+        DumpFormat::Envoy => {
+            let (config_dump_type, resource_type) = envoy_format::resource_types(resource)?;
+            let resources = dump["configs"]
+                .as_array_mut()
+                .and_then(|configs| configs.iter_mut().find(|config| config["@type"] == config_dump_type.as_str()))
+                .and_then(|config| config.get_mut(resource))
+                .and_then(|resources| resources.as_array_mut())
+                .map(std::mem::take)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|mut resource| {
+                    if let Value::Object(fields) = &mut resource {
+                        fields.insert("@type".to_owned(), Value::String(resource_type.clone()));
+                    }
+                    resource
+                })
+                .collect();
+            Some(Value::Array(resources))
+        },
+    }
+}
+
+/// The name of a resource, either one of its fields or a field of the configuration it wraps, as Envoy's dumps do.
+fn resource_name(resource: &Value) -> Option<&str> {
+    own_name(resource).or_else(|| resource.as_object()?.values().filter(|value| value.is_object()).find_map(own_name))
+}
+
+fn own_name(resource: &Value) -> Option<&str> {
+    ["name", "cluster_name", "route_config_name"].into_iter().find_map(|field| resource.get(field)?.as_str())
+}
+
+/// Keeps the resources whose name matches. Resources without a name can't match.
+fn filter_by_name(resources: Value, name_regex: Option<&Regex>) -> Value {
+    match (resources, name_regex) {
+        (Value::Array(resources), Some(name_regex)) => Value::Array(
+            resources
+                .into_iter()
+                .filter(|resource| resource_name(resource).is_some_and(|name| name_regex.is_match(name)))
+                .collect(),
+        ),
+        (resources, _) => resources,
+    }
+}
+
+/// Filters the resources of every section of the dumps by name.
+fn filter_all_by_name(dumps: &mut Value, name_regex: &Regex) {
+    let sections: Vec<&mut Map<String, Value>> = match dumps {
+        Value::Object(sections) => vec![sections],
+        Value::Array(dumps) => dumps.iter_mut().filter_map(Value::as_object_mut).collect(),
+        _ => vec![],
+    };
+    for section in sections.into_iter().flat_map(|sections| sections.values_mut()) {
+        if section.is_array() {
+            *section = filter_by_name(section.take(), Some(name_regex));
+        }
+    }
+}
+
+fn parse_mask(mask: &str) -> Vec<Vec<String>> {
+    mask.split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(|path| path.split('.').map(ToOwned::to_owned).collect())
+        .collect()
+}
+
+/// Keeps the fields on the mask paths, like a protobuf `FieldMask`. Arrays are masked item by item and the `@type`
+/// of the messages is always kept.
+fn apply_mask(value: &Value, mask: &[Vec<String>]) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(|item| apply_mask(item, mask)).collect()),
+        Value::Object(fields) => {
+            let mut masked = Map::new();
+            for (name, field) in fields {
+                let sub_mask: Vec<Vec<String>> =
+                    mask.iter().filter(|path| path.first() == Some(name)).map(|path| path[1..].to_vec()).collect();
+                if name == "@type" || sub_mask.iter().any(Vec::is_empty) {
+                    masked.insert(name.clone(), field.clone());
+                } else if !sub_mask.is_empty() {
+                    masked.insert(name.clone(), apply_mask(field, &sub_mask));
+                }
+            }
+            Value::Object(masked)
+        },
+        value => value.clone(),
+    }
 }
 
 #[cfg(test)]
@@ -225,6 +424,7 @@ mod config_dump_tests {
             secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
             server_info: ServerInfo::default(),
             server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
         };
         let app = build_admin_router(admin_state);
         let server = TestServer::new(app).unwrap();
@@ -239,7 +439,7 @@ mod config_dump_tests {
     async fn config_dump_listeners_and_routes() {
         use compact_str::CompactString;
         use orion_configuration::config::{
-            listener::{FilterChain, FilterChainMatch, Listener, ListenerAddress, MainFilter},
+            listener::{FilterChain, FilterChainMatch, Listener, ListenerAddress, MainFilter, TrafficDirection},
             network_filters::http_connection_manager::{
                 route::{Action, RouteMatch},
                 CodecType, HttpConnectionManager, Route, RouteConfiguration, RouteSpecifier, VirtualHost, XffSettings,
@@ -262,9 +462,11 @@ mod config_dump_tests {
                         filter_chain_match_hash: 0,
                         tls_config: None,
                         rbac: vec![],
+                        connection_limit: None,
                         terminal_filter: MainFilter::Http(HttpConnectionManager {
                             codec_type: CodecType::Http1,
                             request_timeout: Some(Duration::from_secs(10)),
+                            idle_timeout: None,
                             http_filters: vec![],
                             enabled_upgrades: vec![],
                             route_specifier: RouteSpecifier::RouteConfig(RouteConfiguration {
@@ -283,6 +485,7 @@ mod config_dump_tests {
                                         request_headers_to_remove: vec![],
                                         route_match: RouteMatch::default(),
                                         typed_per_filter_config: HashMap::new(),
+                                        per_request_buffer_limit_bytes: None,
                                         action: Action::DirectResponse(
                                             orion_configuration::config::network_filters::http_connection_manager::route::DirectResponseAction {
                                                 status: http::StatusCode::OK,
@@ -295,6 +498,7 @@ mod config_dump_tests {
                                     request_headers_to_remove: vec![],
                                     retry_policy: None,
                                 }],
+                                vhds: None,
                             }),
                             access_log: vec![],
                             xff_settings: XffSettings { use_remote_address: true, skip_xff_append: false, xff_num_trusted_hops: 0 },
@@ -312,6 +516,9 @@ mod config_dump_tests {
             with_tls_inspector: false,
             with_tlv_listener_filter: false,
             tlv_listener_filter_config: None,
+            discovered_listener_filters: Vec::new(),
+            connection_limit: None,
+            traffic_direction: TrafficDirection::default(),
         };
         let (configuration_senders, handle) = spawn_mock_listener_manager(Some(vec![listener]));
         let admin_state = AdminState {
@@ -320,6 +527,7 @@ mod config_dump_tests {
             secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
             server_info: ServerInfo::default(),
             server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
         };
         let app = build_admin_router(admin_state);
         let server = TestServer::new(app).unwrap();
@@ -346,8 +554,10 @@ mod config_dump_tests {
                 cluster_name: "kdjfk".to_owned(),
                 endpoints: vec![LocalityLbEndpoints {
                     priority: 0,
+                    locality: None,
                     lb_endpoints: vec![LbEndpoint {
                         address: endpoint_addr,
+                        hostname: None,
                         health_status: HealthStatus::default(),
                         load_balancing_weight: NonZeroU32::new(1).unwrap(),
                     }],
@@ -358,6 +568,7 @@ mod config_dump_tests {
             load_balancing_policy: LbPolicy::default(),
             http_protocol_options: HttpProtocolOptions::default(),
             health_check: None,
+            outlier_detection: None,
             connect_timeout: Some(Duration::from_secs(5)),
             cleanup_interval: None,
             internal_transport_socket: None,
@@ -373,6 +584,7 @@ mod config_dump_tests {
             secret_manager: Arc::new(RwLock::new(secret_manager)),
             server_info: ServerInfo::default(),
             server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
         };
         let app = build_admin_router(admin_state);
         let server = TestServer::new(app).unwrap();
@@ -398,8 +610,10 @@ mod config_dump_tests {
                 cluster_name: "kdjfk".to_owned(),
                 endpoints: vec![LocalityLbEndpoints {
                     priority: 0,
+                    locality: None,
                     lb_endpoints: vec![LbEndpoint {
                         address: endpoint_addr,
+                        hostname: None,
                         health_status: HealthStatus::default(),
                         load_balancing_weight: NonZeroU32::new(1).unwrap(),
                     }],
@@ -410,6 +624,7 @@ mod config_dump_tests {
             load_balancing_policy: LbPolicy::default(),
             http_protocol_options: HttpProtocolOptions::default(),
             health_check: None,
+            outlier_detection: None,
             connect_timeout: Some(Duration::from_secs(5)),
             cleanup_interval: None,
             internal_transport_socket: None,
@@ -425,6 +640,7 @@ mod config_dump_tests {
             secret_manager: Arc::new(RwLock::new(secret_manager)),
             server_info: ServerInfo::default(),
             server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
         };
         let app = build_admin_router(admin_state);
         let server = TestServer::new(app).unwrap();
@@ -515,6 +731,7 @@ mod config_dump_tests {
             secret_manager: Arc::new(RwLock::new(secret_manager)),
             server_info: ServerInfo::default(),
             server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
         };
         let app = build_admin_router(admin_state);
         let server = TestServer::new(app).unwrap();
@@ -529,4 +746,168 @@ mod config_dump_tests {
         assert_eq!(value["secrets"][1]["validation_context"]["TrustedCA"]["inline_string"], ca_pem);
         handle.abort();
     }
+
+    fn filter_test_state() -> AdminState {
+        AdminState {
+            bootstrap: Bootstrap::default(),
+            configuration_senders: vec![],
+            secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
+            server_info: ServerInfo::default(),
+            server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn config_dump_filtered_native_resources() {
+        use orion_configuration::config::cluster::{
+            Cluster, ClusterDiscoveryType, ClusterLoadAssignment, HealthStatus, HttpProtocolOptions, LbEndpoint,
+            LbPolicy, LocalityLbEndpoints,
+        };
+        use std::{num::NonZeroU32, time::Duration};
+        for name in ["filtered_kept", "filtered_dropped"] {
+            let cluster = Cluster {
+                name: CompactString::from(name),
+                discovery_settings: ClusterDiscoveryType::Static(ClusterLoadAssignment {
+                    cluster_name: name.to_owned(),
+                    endpoints: vec![LocalityLbEndpoints {
+                        priority: 0,
+                        locality: None,
+                        lb_endpoints: vec![LbEndpoint {
+                            address: Address::Socket("127.0.0.1".to_owned(), 9100),
+                            hostname: None,
+                            health_status: HealthStatus::default(),
+                            load_balancing_weight: NonZeroU32::new(1).unwrap(),
+                        }],
+                    }],
+                }),
+                transport_socket: None,
+                bind_device_options: BindDeviceOptions::default(),
+                load_balancing_policy: LbPolicy::default(),
+                http_protocol_options: HttpProtocolOptions::default(),
+                health_check: None,
+                outlier_detection: None,
+                connect_timeout: Some(Duration::from_secs(5)),
+                cleanup_interval: None,
+                internal_transport_socket: None,
+            };
+            let partial_cluster = orion_lib::clusters::cluster::PartialClusterType::try_from((
+                cluster,
+                &orion_lib::SecretManager::default(),
+            ))
+            .unwrap();
+            let _ = orion_lib::clusters::clusters_manager::add_cluster(partial_cluster);
+        }
+        let (configuration_senders, handle) = spawn_mock_listener_manager(None);
+        let admin_state = AdminState { configuration_senders: vec![configuration_senders], ..filter_test_state() };
+        let server = TestServer::new(build_admin_router(admin_state)).unwrap();
+
+        let response = server
+            .get("/config_dump")
+            .add_query_param("resource", "clusters")
+            .add_query_param("name_regex", "^filtered_k")
+            .add_query_param("mask", "name,connect_timeout")
+            .await;
+        response.assert_status_ok();
+        let value: serde_json::Value = response.json();
+        let clusters = value["clusters"].as_array().unwrap();
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0]["name"], "filtered_kept");
+        assert_eq!(clusters[0].as_object().unwrap().len(), 2);
+        assert!(value.get("bootstrap").is_none());
+
+        let response = server
+            .get("/config_dump")
+            .add_query_param("include_eds", "")
+            .add_query_param("name_regex", "^filtered_")
+            .add_query_param("mask", "cluster_statuses.name,cluster_statuses.hosts.health")
+            .await;
+        response.assert_status_ok();
+        let value: serde_json::Value = response.json();
+        assert_eq!(
+            value,
+            json!({"cluster_statuses": [
+                {"name": "filtered_dropped", "hosts": [{"health": "Healthy"}]},
+                {"name": "filtered_kept", "hosts": [{"health": "Healthy"}]},
+            ]})
+        );
+
+        server
+            .get("/config_dump")
+            .add_query_param("resource", "dynamic_active_clusters")
+            .await
+            .assert_status_not_found();
+        server.get("/config_dump").add_query_param("name_regex", "(").await.assert_status_bad_request();
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn config_dump_envoy_format() {
+        use orion_data_plane_api::envoy_data_plane_api::{
+            envoy::{
+                config::cluster::v3::Cluster as EnvoyCluster,
+                extensions::transport_sockets::tls::v3::{secret, Secret as EnvoySecret},
+            },
+            google::protobuf::Any,
+            prost::Message,
+        };
+        use orion_xds::xds::{accepted, model::TypeUrl};
+
+        let cluster = EnvoyCluster { name: "envoy_dump_cluster".to_owned(), ..Default::default() };
+        let any = Any { type_url: TypeUrl::Cluster.to_string(), value: cluster.encode_to_vec() };
+        accepted::insert(TypeUrl::Cluster, "envoy_dump_cluster".to_owned(), "v7".to_owned(), any);
+        let secret = EnvoySecret {
+            name: "envoy_dump_secret".to_owned(),
+            r#type: Some(secret::Type::TlsCertificate(EnvoyTlsCertificate {
+                private_key: Some(EnvoyDataSource {
+                    specifier: Some(InlineString("private_data".into())),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })),
+        };
+        let any = Any { type_url: TypeUrl::Secret.to_string(), value: secret.encode_to_vec() };
+        accepted::insert(TypeUrl::Secret, "envoy_dump_secret".to_owned(), "v1".to_owned(), any);
+        let server = TestServer::new(build_admin_router(filter_test_state())).unwrap();
+
+        let response = server.get("/config_dump").add_query_param("format", "envoy").await;
+        response.assert_status_ok();
+        let value: serde_json::Value = response.json();
+        let configs = value["configs"].as_array().unwrap();
+        assert_eq!(configs[0]["@type"], "type.googleapis.com/envoy.admin.v3.BootstrapConfigDump");
+        assert!(configs
+            .iter()
+            .all(|config| config["@type"] != "type.googleapis.com/envoy.admin.v3.EndpointsConfigDump"));
+        let secrets = configs.iter().find_map(|config| config.get("dynamic_active_secrets")).unwrap();
+        let secret = secrets.as_array().unwrap().iter().find(|secret| secret["name"] == "envoy_dump_secret").unwrap();
+        assert_eq!(secret["secret"]["tls_certificate"]["private_key"]["inline_string"], "[redacted]");
+
+        let response = server
+            .get("/config_dump")
+            .add_query_param("format", "envoy")
+            .add_query_param("resource", "dynamic_active_clusters")
+            .add_query_param("name_regex", "^envoy_dump_")
+            .add_query_param("mask", "version_info,cluster.name")
+            .await;
+        response.assert_status_ok();
+        let value: serde_json::Value = response.json();
+        assert_eq!(
+            value,
+            json!({"configs": [{
+                "@type": "type.googleapis.com/envoy.admin.v3.ClustersConfigDump.DynamicCluster",
+                "version_info": "v7",
+                "cluster": {"@type": "type.googleapis.com/envoy.config.cluster.v3.Cluster", "name": "envoy_dump_cluster"},
+            }]})
+        );
+
+        let response =
+            server.get("/config_dump").add_query_param("format", "envoy").add_query_param("include_eds", "").await;
+        response.assert_status_ok();
+        let value: serde_json::Value = response.json();
+        assert!(value["configs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|config| config["@type"] == "type.googleapis.com/envoy.admin.v3.EndpointsConfigDump"));
+    }
 }
@@ -0,0 +1,292 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! The config dump in Envoy's proto-JSON shape (`envoy.admin.v3.ConfigDump`), as expected by Envoy tooling.
+//!
+//! Only the resources received through xDS can be dumped this way, since they are the only ones whose original
+//! Envoy configuration is kept: the resources of the bootstrap are only part of the native dump.
+
+use orion_configuration::config::{cluster::HealthStatus, Bootstrap};
+use orion_data_plane_api::envoy_data_plane_api::{
+    envoy::{
+        admin::v3::{
+            clusters_config_dump::DynamicCluster,
+            ecds_config_dump::EcdsFilterConfig,
+            endpoints_config_dump::{DynamicEndpointConfig, StaticEndpointConfig},
+            listeners_config_dump::{DynamicListener, DynamicListenerState},
+            routes_config_dump::DynamicRouteConfig,
+            secrets_config_dump::DynamicSecret,
+            BootstrapConfigDump, ClientResourceStatus, ClustersConfigDump, ConfigDump as EnvoyConfigDump,
+            EcdsConfigDump, EndpointsConfigDump, ListenersConfigDump, RoutesConfigDump, SecretsConfigDump,
+        },
+        config::{
+            bootstrap::v3::Bootstrap as EnvoyBootstrap,
+            core::v3::{
+                address::Address as EnvoyAddressKind, data_source::Specifier, socket_address::PortSpecifier,
+                Address as EnvoyAddress, DataSource, HealthStatus as EnvoyHealthStatus, Locality as EnvoyLocality,
+                Node as EnvoyNode, SocketAddress,
+            },
+            endpoint::v3::{
+                lb_endpoint::HostIdentifier, ClusterLoadAssignment, Endpoint, LbEndpoint, LocalityLbEndpoints,
+            },
+        },
+        extensions::transport_sockets::tls::v3::{secret, Secret as EnvoySecret},
+    },
+    google::protobuf::{Any, Timestamp, UInt32Value},
+    prost::{Message, Name},
+    prost_reflect::{DescriptorPool, ReflectMessage, SerializeOptions},
+};
+use orion_lib::clusters::{clusters_manager::get_clusters_status, status::ClusterStatus};
+use orion_xds::xds::{
+    accepted::{accepted_resource, accepted_resources, AcceptedResource},
+    model::TypeUrl,
+};
+use serde_json::Value;
+use std::{collections::BTreeMap, time::SystemTime};
+
+/// Builds the Envoy config dump. The endpoints are only dumped with `include_eds`, from the live state of the
+/// clusters, as Envoy does.
+pub fn config_dump(bootstrap: &Bootstrap, include_eds: bool) -> Result<Value, String> {
+    let mut configs = vec![
+        to_any(&bootstrap_dump(bootstrap)),
+        to_any(&clusters_dump()),
+        to_any(&listeners_dump()),
+        to_any(&routes_dump()),
+        to_any(&secrets_dump()),
+    ];
+    let ecds_dump = ecds_dump();
+    if !ecds_dump.ecds_filters.is_empty() {
+        configs.push(to_any(&ecds_dump));
+    }
+    if include_eds {
+        configs.push(to_any(&endpoints_dump()));
+    }
+    let config_dump = EnvoyConfigDump { configs };
+    let options = SerializeOptions::new().use_proto_field_name(true);
+    config_dump
+        .transcode_to_dynamic()
+        .serialize_with_options(serde_json::value::Serializer, &options)
+        .map_err(|e| format!("failed to serialize the config dump: {e}"))
+}
+
+fn descriptor_pool() -> DescriptorPool {
+    EnvoyConfigDump::default().descriptor().parent_pool().clone()
+}
+
+/// Finds the config dump holding the `resource` repeated field, returning the type urls of the config dump and of
+/// the resources.
+pub fn resource_types(resource: &str) -> Option<(String, String)> {
+    let pool = descriptor_pool();
+    [
+        ClustersConfigDump::full_name(),
+        ListenersConfigDump::full_name(),
+        RoutesConfigDump::full_name(),
+        SecretsConfigDump::full_name(),
+        EcdsConfigDump::full_name(),
+        EndpointsConfigDump::full_name(),
+    ]
+    .into_iter()
+    .find_map(|config_dump| {
+        let field = pool.get_message_by_name(&config_dump)?.get_field_by_name(resource)?;
+        let resource_type = field.kind().as_message().filter(|_| field.is_list())?.full_name().to_owned();
+        Some((type_url(&config_dump), type_url(&resource_type)))
+    })
+}
+
+fn type_url(full_name: &str) -> String {
+    format!("type.googleapis.com/{full_name}")
+}
+
+fn to_any<T: Message + Name>(message: &T) -> Any {
+    Any { type_url: type_url(&T::full_name()), value: message.encode_to_vec() }
+}
+
+fn timestamp(time: SystemTime) -> Timestamp {
+    let since_epoch = time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+    Timestamp {
+        seconds: i64::try_from(since_epoch.as_secs()).unwrap_or(i64::MAX),
+        nanos: i32::try_from(since_epoch.subsec_nanos()).unwrap_or_default(),
+    }
+}
+
+const ACKED: i32 = ClientResourceStatus::Acked as i32;
+
+fn bootstrap_dump(bootstrap: &Bootstrap) -> BootstrapConfigDump {
+    let node = bootstrap.node.as_ref().map(EnvoyNode::from);
+    BootstrapConfigDump { bootstrap: Some(EnvoyBootstrap { node, ..Default::default() }), last_updated: None }
+}
+
+fn clusters_dump() -> ClustersConfigDump {
+    let dynamic_active_clusters = accepted_resources(TypeUrl::Cluster)
+        .into_iter()
+        .map(|AcceptedResource { version_info, last_updated, resource, .. }| DynamicCluster {
+            version_info,
+            cluster: Some(resource),
+            last_updated: Some(timestamp(last_updated)),
+            client_status: ACKED,
+            ..Default::default()
+        })
+        .collect();
+    ClustersConfigDump { dynamic_active_clusters, ..Default::default() }
+}
+
+fn listeners_dump() -> ListenersConfigDump {
+    let dynamic_listeners = accepted_resources(TypeUrl::Listener)
+        .into_iter()
+        .map(|AcceptedResource { name, version_info, last_updated, resource, .. }| DynamicListener {
+            name,
+            active_state: Some(DynamicListenerState {
+                version_info,
+                listener: Some(resource),
+                last_updated: Some(timestamp(last_updated)),
+            }),
+            client_status: ACKED,
+            ..Default::default()
+        })
+        .collect();
+    ListenersConfigDump { dynamic_listeners, ..Default::default() }
+}
+
+fn routes_dump() -> RoutesConfigDump {
+    let dynamic_route_configs = accepted_resources(TypeUrl::RouteConfiguration)
+        .into_iter()
+        .map(|AcceptedResource { version_info, last_updated, resource, .. }| DynamicRouteConfig {
+            version_info,
+            route_config: Some(resource),
+            last_updated: Some(timestamp(last_updated)),
+            client_status: ACKED,
+            ..Default::default()
+        })
+        .collect();
+    RoutesConfigDump { dynamic_route_configs, ..Default::default() }
+}
+
+fn secrets_dump() -> SecretsConfigDump {
+    let dynamic_active_secrets = accepted_resources(TypeUrl::Secret)
+        .into_iter()
+        .map(|AcceptedResource { name, version_info, last_updated, resource, .. }| DynamicSecret {
+            name,
+            version_info,
+            last_updated: Some(timestamp(last_updated)),
+            secret: Some(redact_secret(resource)),
+            client_status: ACKED,
+            ..Default::default()
+        })
+        .collect();
+    SecretsConfigDump { dynamic_active_secrets, ..Default::default() }
+}
+
+fn ecds_dump() -> EcdsConfigDump {
+    let ecds_filters = accepted_resources(TypeUrl::TypedExtensionConfig)
+        .into_iter()
+        .map(|AcceptedResource { version_info, last_updated, resource, .. }| EcdsFilterConfig {
+            version_info,
+            ecds_filter: Some(resource),
+            last_updated: Some(timestamp(last_updated)),
+            client_status: ACKED,
+            ..Default::default()
+        })
+        .collect();
+    EcdsConfigDump { ecds_filters }
+}
+
+/// Hides the private key of TLS certificates, like the native dump does.
+fn redact_secret(resource: Any) -> Any {
+    match EnvoySecret::decode(resource.value.as_slice()) {
+        Ok(mut envoy_secret) => {
+            if let Some(secret::Type::TlsCertificate(certificate)) = &mut envoy_secret.r#type {
+                if certificate.private_key.is_some() {
+                    certificate.private_key = Some(DataSource {
+                        specifier: Some(Specifier::InlineString("[redacted]".to_owned())),
+                        ..Default::default()
+                    });
+                }
+            }
+            Any { type_url: resource.type_url, value: envoy_secret.encode_to_vec() }
+        },
+        // a secret that can't be decoded can't have been accepted, but never leak it
+        Err(_) => Any { type_url: resource.type_url, value: Vec::new() },
+    }
+}
+
+fn endpoints_dump() -> EndpointsConfigDump {
+    let mut dump = EndpointsConfigDump::default();
+    for cluster in get_clusters_status() {
+        let endpoint_config = Some(to_any(&live_load_assignment(&cluster)));
+        match accepted_resource(TypeUrl::ClusterLoadAssignment, cluster.name) {
+            Some(AcceptedResource { version_info, last_updated, .. }) => {
+                dump.dynamic_endpoint_configs.push(DynamicEndpointConfig {
+                    version_info,
+                    endpoint_config,
+                    last_updated: Some(timestamp(last_updated)),
+                    client_status: ACKED,
+                    ..Default::default()
+                });
+            },
+            None => dump.static_endpoint_configs.push(StaticEndpointConfig { endpoint_config, last_updated: None }),
+        }
+    }
+    dump
+}
+
+/// The endpoints of a cluster as they currently are, with their health as seen by the health checks.
+fn live_load_assignment(cluster: &ClusterStatus) -> ClusterLoadAssignment {
+    let mut localities = BTreeMap::<_, Vec<LbEndpoint>>::new();
+    for host in &cluster.hosts {
+        let health_status = match host.health {
+            HealthStatus::Healthy => EnvoyHealthStatus::Healthy,
+            HealthStatus::Unhealthy => EnvoyHealthStatus::Unhealthy,
+        };
+        localities.entry((host.priority, host.locality.clone())).or_default().push(LbEndpoint {
+            health_status: health_status as i32,
+            load_balancing_weight: Some(UInt32Value { value: host.weight }),
+            host_identifier: Some(HostIdentifier::Endpoint(Endpoint {
+                address: Some(envoy_address(&host.address)),
+                ..Default::default()
+            })),
+            ..Default::default()
+        });
+    }
+    let endpoints = localities
+        .into_iter()
+        .map(|((priority, locality), lb_endpoints)| LocalityLbEndpoints {
+            priority,
+            locality: locality.map(|locality| EnvoyLocality {
+                region: locality.region.into(),
+                zone: locality.zone.into(),
+                sub_zone: locality.sub_zone.into(),
+            }),
+            lb_endpoints,
+            ..Default::default()
+        })
+        .collect();
+    ClusterLoadAssignment { cluster_name: cluster.name.to_owned(), endpoints, ..Default::default() }
+}
+
+fn envoy_address(address: &str) -> EnvoyAddress {
+    let (host, port) = match address.rsplit_once(':').map(|(host, port)| (host, port.parse::<u32>())) {
+        Some((host, Ok(port))) => (host.trim_start_matches('[').trim_end_matches(']'), Some(port)),
+        _ => (address, None),
+    };
+    EnvoyAddress {
+        address: Some(EnvoyAddressKind::SocketAddress(SocketAddress {
+            address: host.to_owned(),
+            port_specifier: port.map(PortSpecifier::PortValue),
+            ..Default::default()
+        })),
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use super::AdminState;
+use axum::extract::{Query, State};
+use orion_configuration::config::Runtime;
+use orion_lib::{
+    lifecycle::{self, DrainScope},
+    ConfigurationSenders, ListenerConfigurationChange, RUNTIME_CONFIG,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::info;
+
+use crate::xds_configurator::send_change_to_runtimes;
+
+/// Leaves the time to send the response before the proxy starts shutting down.
+const QUIT_DELAY: Duration = Duration::from_millis(100);
+
+/// Both parameters are flags: only their presence matters.
+#[derive(Debug, Default, Deserialize)]
+pub struct DrainQuery {
+    inboundonly: Option<String>,
+    graceful: Option<String>,
+}
+
+pub async fn post_healthcheck_fail() -> &'static str {
+    info!("health check failed through the admin API");
+    lifecycle::set_health_check_failed(true);
+    "OK\n"
+}
+
+pub async fn post_healthcheck_ok() -> &'static str {
+    info!("health check restored through the admin API");
+    lifecycle::set_health_check_failed(false);
+    "OK\n"
+}
+
+/// Drains the listeners, all of them or only the inbound ones. The connections of the listeners are drained right
+/// away. A graceful drain keeps the listeners accepting connections for the drain time before closing them,
+/// otherwise they are closed immediately.
+pub async fn post_drain_listeners(
+    State(admin_state): State<AdminState>,
+    Query(query): Query<DrainQuery>,
+) -> &'static str {
+    let scope = if query.inboundonly.is_some() { DrainScope::InboundOnly } else { DrainScope::All };
+    let listeners_senders: Vec<_> = admin_state
+        .configuration_senders
+        .into_iter()
+        .map(|ConfigurationSenders { listener_configuration_sender, .. }| listener_configuration_sender)
+        .collect();
+
+    let _ = send_change_to_runtimes(&listeners_senders, ListenerConfigurationChange::DrainListeners(scope)).await;
+    if query.graceful.is_some() {
+        let drain_time = RUNTIME_CONFIG.get().map_or_else(|| Runtime::default().drain_time(), Runtime::drain_time);
+        info!("draining listeners ({scope:?}) for {drain_time:?} before closing them");
+        tokio::spawn(async move {
+            tokio::time::sleep(drain_time).await;
+            let _ =
+                send_change_to_runtimes(&listeners_senders, ListenerConfigurationChange::StopListeners(scope)).await;
+        });
+    } else {
+        info!("draining listeners ({scope:?}) and closing them");
+        let _ = send_change_to_runtimes(&listeners_senders, ListenerConfigurationChange::StopListeners(scope)).await;
+    }
+    "OK\n"
+}
+
+pub async fn post_quitquitquit(State(admin_state): State<AdminState>) -> &'static str {
+    info!("shutdown requested through the admin API");
+    tokio::spawn(async move {
+        tokio::time::sleep(QUIT_DELAY).await;
+        admin_state.shutdown.cancel();
+    });
+    "OK\n"
+}
+
+#[cfg(test)]
+mod lifecycle_tests {
+    use super::{super::*, *};
+    use axum_test::TestServer;
+    use orion_configuration::config::Bootstrap;
+    use tokio::sync::mpsc;
+
+    fn admin_state(configuration_senders: Vec<ConfigurationSenders>) -> AdminState {
+        AdminState {
+            bootstrap: Bootstrap::default(),
+            configuration_senders,
+            secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
+            server_info: ServerInfo::default(),
+            server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn drain_inbound_listeners() {
+        let (list_tx, mut list_rx) = mpsc::channel(10);
+        let (route_tx, _route_rx) = mpsc::channel(10);
+        let configuration_senders =
+            ConfigurationSenders { listener_configuration_sender: list_tx, route_configuration_sender: route_tx };
+        let server = TestServer::new(build_admin_router(admin_state(vec![configuration_senders]))).unwrap();
+
+        let response = server.post("/drain_listeners").add_query_param("inboundonly", "").await;
+        response.assert_status_ok();
+        assert!(matches!(
+            list_rx.recv().await,
+            Some(ListenerConfigurationChange::DrainListeners(DrainScope::InboundOnly))
+        ));
+        assert!(matches!(
+            list_rx.recv().await,
+            Some(ListenerConfigurationChange::StopListeners(DrainScope::InboundOnly))
+        ));
+    }
+
+    #[tokio::test]
+    async fn quitquitquit_shuts_down() {
+        let state = admin_state(vec![]);
+        let shutdown = state.shutdown.clone();
+        let server = TestServer::new(build_admin_router(state)).unwrap();
+
+        server.post("/quitquitquit").await.assert_status_ok();
+        pingora_timeout::fast_timeout::fast_timeout(Duration::from_secs(1), shutdown.cancelled()).await.unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use super::{AdminState, FormatQuery};
+use axum::{
+    extract::{Query, State},
+    response::Response,
+};
+use compact_str::CompactString;
+use orion_configuration::config::listener::ListenerAddress;
+use orion_lib::{listener_bound_address, ConfigDump, ConfigurationSenders, ListenerConfigurationChange};
+use serde::Serialize;
+use std::fmt::Write;
+use tokio::sync::mpsc;
+
+use crate::xds_configurator::send_change_to_runtimes;
+
+#[derive(Serialize)]
+struct ListenerStatus {
+    name: CompactString,
+    /// The bound socket address, or `internal` for internal listeners, which are only reachable by name.
+    local_address: String,
+}
+
+#[derive(Serialize)]
+struct ListenersResponse {
+    listener_statuses: Vec<ListenerStatus>,
+}
+
+pub async fn get_listeners(State(admin_state): State<AdminState>, Query(query): Query<FormatQuery>) -> Response {
+    let listeners_senders: Vec<_> = admin_state
+        .configuration_senders
+        .into_iter()
+        .map(|ConfigurationSenders { listener_configuration_sender, .. }| listener_configuration_sender)
+        .collect();
+
+    // every runtime runs the same listeners, so the first answer is enough
+    let (sender, mut receiver) = mpsc::channel::<ConfigDump>(listeners_senders.len().max(1));
+    let _ = send_change_to_runtimes(&listeners_senders, ListenerConfigurationChange::GetConfiguration(sender)).await;
+    let mut listener_statuses: Vec<_> = receiver
+        .recv()
+        .await
+        .and_then(|config| config.listeners)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|listener| {
+            let local_address = match listener.address {
+                // the configured address until the listener is bound
+                ListenerAddress::Socket(address) => {
+                    listener_bound_address(&listener.name).unwrap_or(address).to_string()
+                },
+                ListenerAddress::Internal(_) => "internal".to_owned(),
+            };
+            ListenerStatus { name: listener.name, local_address }
+        })
+        .collect();
+    listener_statuses.sort_by(|a, b| a.name.cmp(&b.name));
+
+    query.format.render(&ListenersResponse { listener_statuses }, |response| {
+        let mut text = String::new();
+        for listener in &response.listener_statuses {
+            let _ = writeln!(text, "{}::{}", listener.name, listener.local_address);
+        }
+        text
+    })
+}
+
+#[cfg(test)]
+mod listeners_tests {
+    use super::{super::*, *};
+    use axum_test::TestServer;
+    use orion_configuration::config::{
+        listener::{InternalListener, Listener, TrafficDirection},
+        transport::BindDeviceOptions,
+        Bootstrap,
+    };
+    use std::{
+        collections::HashMap,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+    };
+    use tokio::task::JoinHandle;
+
+    fn listener(name: &str, address: ListenerAddress) -> Listener {
+        Listener {
+            name: CompactString::from(name),
+            address,
+            filter_chains: HashMap::new(),
+            bind_device_options: BindDeviceOptions::default(),
+            with_tls_inspector: false,
+            proxy_protocol_config: None,
+            with_tlv_listener_filter: false,
+            tlv_listener_filter_config: None,
+            discovered_listener_filters: Vec::new(),
+            connection_limit: None,
+            traffic_direction: TrafficDirection::default(),
+        }
+    }
+
+    fn spawn_mock_listener_manager(listeners: Vec<Listener>) -> (ConfigurationSenders, JoinHandle<()>) {
+        let (list_tx, mut list_rx) = mpsc::channel(10);
+        let (route_tx, _route_rx) = mpsc::channel(10);
+        let handle = tokio::spawn(async move {
+            while let Some(message) = list_rx.recv().await {
+                if let ListenerConfigurationChange::GetConfiguration(response_sender) = message {
+                    let config = ConfigDump { listeners: Some(listeners.clone()), ..Default::default() };
+                    let _ = response_sender.send(config).await;
+                }
+            }
+        });
+        (ConfigurationSenders { listener_configuration_sender: list_tx, route_configuration_sender: route_tx }, handle)
+    }
+
+    fn admin_server(configuration_senders: ConfigurationSenders) -> TestServer {
+        let admin_state = AdminState {
+            bootstrap: Bootstrap::default(),
+            configuration_senders: vec![configuration_senders],
+            secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
+            server_info: ServerInfo::default(),
+            server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
+        };
+        TestServer::new(build_admin_router(admin_state)).unwrap()
+    }
+
+    fn mock_listeners() -> Vec<Listener> {
+        vec![
+            listener("public", ListenerAddress::Socket(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8080))),
+            listener("internal_tunnel", ListenerAddress::Internal(InternalListener { buffer_size_kb: None })),
+        ]
+    }
+
+    #[tokio::test]
+    async fn listeners_text() {
+        let (configuration_senders, handle) = spawn_mock_listener_manager(mock_listeners());
+        let server = admin_server(configuration_senders);
+
+        let response = server.get("/listeners").await;
+        response.assert_status_ok();
+        assert_eq!(response.text(), "internal_tunnel::internal\npublic::127.0.0.1:8080\n");
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn listeners_json() {
+        let (configuration_senders, handle) = spawn_mock_listener_manager(mock_listeners());
+        let server = admin_server(configuration_senders);
+
+        let response = server.get("/listeners").add_query_param("format", "json").await;
+        response.assert_status_ok();
+        let value: serde_json::Value = response.json();
+        assert_eq!(value["listener_statuses"][0]["name"], "internal_tunnel");
+        assert_eq!(value["listener_statuses"][0]["local_address"], "internal");
+        assert_eq!(value["listener_statuses"][1]["name"], "public");
+        assert_eq!(value["listener_statuses"][1]["local_address"], "127.0.0.1:8080");
+        handle.abort();
+    }
+}
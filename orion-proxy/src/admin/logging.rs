@@ -0,0 +1,302 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use parking_lot::Mutex;
+use std::{collections::BTreeMap, fmt::Write, str::FromStr, sync::OnceLock};
+use tracing::info;
+use tracing_subscriber::{filter::LevelFilter, EnvFilter};
+
+use crate::proxy_tracing::FilterReloadHandle;
+
+/// Envoy-style component names, as accepted by Istio's `--component-log-level`, and the modules they cover.
+const COMPONENTS: &[(&str, &[&str])] = &[
+    ("access_log", &["orion_lib::access_log", "orion_lib::listeners::access_log"]),
+    ("admin", &["orion_proxy::admin"]),
+    ("config", &["orion_configuration", "orion_proxy::xds_configurator"]),
+    ("conn_handler", &["orion_lib::listeners::listener", "orion_lib::listeners::listeners_manager"]),
+    ("connection", &["orion_lib::transport"]),
+    ("filter", &["orion_lib::listeners::filterchain", "orion_lib::listeners::rate_limiter"]),
+    ("hc", &["orion_lib::clusters::health"]),
+    ("http", &["orion_lib::listeners::http_connection_manager"]),
+    ("main", &["orion_proxy::proxy", "orion_proxy::runtime"]),
+    ("misc", &["orion_proxy::signal", "orion_proxy::core_affinity", "orion_lib::utils"]),
+    ("overload", &["orion_lib::overload"]),
+    ("router", &["orion_lib::listeners::http_connection_manager::route"]),
+    ("secret", &["orion_lib::secrets"]),
+    ("tcp", &["orion_lib::listeners::tcp_proxy"]),
+    ("upstream", &["orion_lib::clusters"]),
+    ("xds", &["orion_xds"]),
+];
+
+static LOG_LEVEL_CONTROLLER: OnceLock<LogLevelController> = OnceLock::new();
+
+struct LogLevelController {
+    handle: FilterReloadHandle,
+    levels: Mutex<LogLevels>,
+}
+
+/// Takes over the tracing filter, so that its levels can be changed through the `/logging` endpoint.
+///
+/// The filter currently in place, coming from the configuration or `RUST_LOG`, is the starting point.
+pub(crate) fn install(handle: FilterReloadHandle) {
+    let current = handle.with_current(ToString::to_string).unwrap_or_default();
+    let controller = LogLevelController { handle, levels: Mutex::new(LogLevels::parse(&current)) };
+    if LOG_LEVEL_CONTROLLER.set(controller).is_err() {
+        tracing::warn!("log level control was already installed");
+    }
+}
+
+/// The log levels in force, split into the global level and the per-module ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LogLevels {
+    global: LevelFilter,
+    modules: BTreeMap<String, LevelFilter>,
+    /// Directives that are not plain `module=level` pairs (span or field filters), kept as they were configured.
+    other: Vec<String>,
+}
+
+impl LogLevels {
+    fn parse(filter: &str) -> Self {
+        // with no default directive, anything not matched by another directive is disabled
+        let mut levels = Self { global: LevelFilter::OFF, modules: BTreeMap::new(), other: Vec::new() };
+        for directive in filter.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+            if let Ok(level) = parse_level(directive) {
+                levels.global = level;
+            } else if let Some((module, level)) = directive
+                .split_once('=')
+                .filter(|(module, _)| is_module_path(module))
+                .and_then(|(module, level)| parse_level(level).ok().map(|level| (module, level)))
+            {
+                levels.modules.insert(module.to_owned(), level);
+            } else {
+                levels.other.push(directive.to_owned());
+            }
+        }
+        levels
+    }
+
+    fn to_filter(&self) -> Result<EnvFilter, String> {
+        let directives = std::iter::once(display_level(self.global))
+            .chain(self.other.iter().cloned())
+            .chain(self.modules.iter().map(|(module, level)| format!("{module}={}", display_level(*level))))
+            .collect::<Vec<_>>()
+            .join(",");
+        EnvFilter::builder().parse(&directives).map_err(|e| format!("invalid log filter \"{directives}\": {e}"))
+    }
+
+    /// The level that applies to `module`: the one of its closest configured ancestor, or the global one.
+    fn level_of(&self, module: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(configured, _)| {
+                module.strip_prefix(configured.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(configured, _)| configured.len())
+            .map_or(self.global, |(_, level)| *level)
+    }
+
+    /// Applies a single `name=level` change. `name` is `level` for the global level, a component name or a module
+    /// path. As in Envoy, changing the global level resets every other logger to it.
+    fn apply(&mut self, name: &str, level: &str) -> Result<(), String> {
+        let level = parse_level(level)?;
+        if name == "level" {
+            self.global = level;
+            self.modules.clear();
+        } else if let Some((_, modules)) = COMPONENTS.iter().find(|(component, _)| *component == name) {
+            for module in *modules {
+                self.modules.insert((*module).to_owned(), level);
+            }
+        } else if is_module_path(name) {
+            self.modules.insert(name.to_owned(), level);
+        } else {
+            return Err(format!("unknown logger \"{name}\""));
+        }
+        Ok(())
+    }
+
+    fn render(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "active loggers:");
+        let _ = writeln!(text, "  global: {}", display_level(self.global));
+        let _ = writeln!(text, "components:");
+        for (component, modules) in COMPONENTS {
+            let level = modules.first().map_or(self.global, |module| self.level_of(module));
+            let _ = writeln!(text, "  {component}: {}", display_level(level));
+        }
+        if !self.modules.is_empty() {
+            let _ = writeln!(text, "modules:");
+            for (module, level) in &self.modules {
+                let _ = writeln!(text, "  {module}: {}", display_level(*level));
+            }
+        }
+        text
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    match level.to_lowercase().as_str() {
+        // Envoy level names
+        "warning" => Ok(LevelFilter::WARN),
+        "critical" => Ok(LevelFilter::ERROR),
+        level => LevelFilter::from_str(level).map_err(|_| format!("invalid log level \"{level}\"")),
+    }
+}
+
+fn display_level(level: LevelFilter) -> String {
+    level.to_string().to_lowercase()
+}
+
+fn is_module_path(name: &str) -> bool {
+    !name.is_empty()
+        && name.split("::").all(|segment| {
+            !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+}
+
+fn unavailable() -> Response {
+    (StatusCode::SERVICE_UNAVAILABLE, "log level control is not available\n").into_response()
+}
+
+fn text_response(status: StatusCode, text: String) -> Response {
+    (status, [(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")], text).into_response()
+}
+
+pub async fn get_logging() -> Response {
+    match LOG_LEVEL_CONTROLLER.get() {
+        Some(controller) => text_response(StatusCode::OK, controller.levels.lock().render()),
+        None => unavailable(),
+    }
+}
+
+/// Changes log levels. Accepts `level=<level>` for the global level, `paths=<name>:<level>,...` and
+/// `<name>=<level>`, where a name is either a component or a module path.
+pub async fn post_logging(Query(params): Query<Vec<(String, String)>>) -> Response {
+    let Some(controller) = LOG_LEVEL_CONTROLLER.get() else {
+        return unavailable();
+    };
+
+    let mut levels = controller.levels.lock();
+    let mut updated = levels.clone();
+    for (name, value) in &params {
+        let result = if name == "paths" {
+            value.split(',').filter(|path| !path.is_empty()).try_for_each(|path| {
+                let (name, level) =
+                    path.split_once(':').ok_or_else(|| format!("expected <name>:<level>, got \"{path}\""))?;
+                updated.apply(name, level)
+            })
+        } else {
+            updated.apply(name, value)
+        };
+        if let Err(err) = result {
+            return text_response(StatusCode::BAD_REQUEST, format!("{err}\n"));
+        }
+    }
+
+    if updated != *levels {
+        let result = updated.to_filter().and_then(|filter| {
+            controller.handle.reload(filter).map_err(|e| format!("failed to reload the log filter: {e}"))
+        });
+        if let Err(err) = result {
+            return text_response(StatusCode::INTERNAL_SERVER_ERROR, format!("{err}\n"));
+        }
+        info!("log levels changed through the admin API: {params:?}");
+        *levels = updated;
+    }
+
+    text_response(StatusCode::OK, levels.render())
+}
+
+#[cfg(test)]
+mod logging_tests {
+    use super::{super::*, *};
+    use axum_test::TestServer;
+    use std::{sync::Arc, time::Instant};
+    use tracing_subscriber::{reload, Registry};
+
+    #[test]
+    fn parse_and_rebuild_filter() {
+        let levels = LogLevels::parse("info,orion_lib::clusters=debug,orion_xds[span]=trace");
+        assert_eq!(levels.global, LevelFilter::INFO);
+        assert_eq!(levels.modules.get("orion_lib::clusters"), Some(&LevelFilter::DEBUG));
+        assert_eq!(levels.other, vec!["orion_xds[span]=trace".to_owned()]);
+        assert!(levels.to_filter().is_ok());
+        assert_eq!(LogLevels::parse("").global, LevelFilter::OFF);
+    }
+
+    #[test]
+    fn module_levels_are_inherited() {
+        let mut levels = LogLevels::parse("warn");
+        levels.apply("orion_lib::clusters", "debug").unwrap();
+        assert_eq!(levels.level_of("orion_lib::clusters::health"), LevelFilter::DEBUG);
+        assert_eq!(levels.level_of("orion_lib::clustersx"), LevelFilter::WARN);
+        assert_eq!(levels.level_of("orion_lib"), LevelFilter::WARN);
+
+        levels.apply("hc", "trace").unwrap();
+        assert_eq!(levels.level_of("orion_lib::clusters::health"), LevelFilter::TRACE);
+
+        levels.apply("level", "critical").unwrap();
+        assert_eq!(levels.global, LevelFilter::ERROR);
+        assert!(levels.modules.is_empty());
+
+        assert!(levels.apply("not a module", "info").is_err());
+        assert!(levels.apply("upstream", "loud").is_err());
+    }
+
+    #[tokio::test]
+    async fn change_levels_through_admin_api() {
+        static LAYER: OnceLock<reload::Layer<EnvFilter, Registry>> = OnceLock::new();
+        let (layer, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let _ = LAYER.set(layer);
+        install(handle.clone());
+
+        let admin_state = AdminState {
+            bootstrap: Bootstrap::default(),
+            configuration_senders: vec![],
+            secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
+            server_info: ServerInfo::default(),
+            server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
+        };
+        let server = TestServer::new(build_admin_router(admin_state)).unwrap();
+
+        let response = server.get("/logging").await;
+        response.assert_status_ok();
+        assert!(response.text().contains("  global: info\n"));
+
+        let response = server.post("/logging").add_query_param("paths", "upstream:debug,orion_xds:trace").await;
+        response.assert_status_ok();
+        let text = response.text();
+        assert!(text.contains("  upstream: debug\n"));
+        assert!(text.contains("  xds: trace\n"));
+        let filter = handle.with_current(ToString::to_string).unwrap();
+        assert!(filter.contains("orion_lib::clusters=debug"));
+        assert!(filter.contains("orion_xds=trace"));
+
+        let response = server.post("/logging").add_query_param("misc", "bogus").await;
+        response.assert_status_bad_request();
+
+        let response = server.post("/logging").add_query_param("level", "warning").await;
+        response.assert_status_ok();
+        assert!(response.text().contains("  upstream: warn\n"));
+        assert_eq!(handle.with_current(ToString::to_string).unwrap(), "warn");
+    }
+}
@@ -111,6 +111,9 @@ pub(crate) async fn prometheus_handler(
     process_metric!(registry, &listeners::DOWNSTREAM_CX_DESTROY, IntCounterVec, populate_counter_vec);
     process_metric!(registry, &listeners::DOWNSTREAM_CX_ACTIVE, IntGaugeVec, populate_gauge_vec);
     process_metric!(registry, &listeners::NO_FILTER_CHAIN_MATCH, IntCounterVec, populate_counter_vec);
+    process_metric!(registry, &listeners::DOWNSTREAM_CX_OVERFLOW, IntCounterVec, populate_counter_vec);
+    process_metric!(registry, &listeners::DOWNSTREAM_CX_OVERLOAD_REJECT, IntCounterVec, populate_counter_vec);
+    process_metric!(registry, &listeners::DOWNSTREAM_GLOBAL_CX_OVERFLOW, IntCounterVec, populate_counter_vec);
 
     // clusters metrics
     process_metric!(registry, &clusters::UPSTREAM_RQ_TOTAL, IntCounterVec, populate_counter_vec);
@@ -141,6 +144,8 @@ pub(crate) async fn prometheus_handler(
     process_metric!(registry, &http::DOWNSTREAM_RQ_ACTIVE, IntGaugeVec, populate_gauge_vec);
     process_metric!(registry, &http::DOWNSTREAM_CX_RX_BYTES_TOTAL, IntCounterVec, populate_counter_vec);
     process_metric!(registry, &http::DOWNSTREAM_CX_TX_BYTES_TOTAL, IntCounterVec, populate_counter_vec);
+    process_metric!(registry, &http::DOWNSTREAM_CX_OVERLOAD_DISABLE_KEEPALIVE, IntCounterVec, populate_counter_vec);
+    process_metric!(registry, &http::DOWNSTREAM_CX_IDLE_TIMEOUT, IntCounterVec, populate_counter_vec);
 
     // server metrics
     process_metric!(registry, &server::UPTIME, IntGaugeVec, populate_gauge_vec);
@@ -0,0 +1,98 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use orion_lib::layered_runtime;
+use tracing::info;
+
+/// The layers of the runtime and the values of each key, in every layer and after merging them.
+pub async fn get_runtime() -> Response {
+    Json(layered_runtime::dump()).into_response()
+}
+
+/// Sets values of the admin layer, an empty value removing the key.
+pub async fn post_runtime_modify(Query(params): Query<Vec<(String, String)>>) -> Response {
+    match layered_runtime::set_admin_values(params.iter().map(|(key, value)| (key.as_str(), value.as_str()))) {
+        Ok(()) => {
+            info!("runtime values changed through the admin API: {params:?}");
+            "OK\n".into_response()
+        },
+        Err(err) => (StatusCode::SERVICE_UNAVAILABLE, format!("{err}\n")).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use axum::http::StatusCode;
+    use axum_test::TestServer;
+    use orion_configuration::config::{
+        bootstrap::Node,
+        layered_runtime::{LayeredRuntime, RuntimeLayer, RuntimeLayerSpecifier, RuntimeValue},
+    };
+    use orion_lib::layered_runtime;
+    use std::{sync::Arc, time::Instant};
+
+    #[tokio::test]
+    async fn modify_admin_layer() {
+        let admin_state = AdminState {
+            bootstrap: Bootstrap::default(),
+            configuration_senders: vec![],
+            secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
+            server_info: ServerInfo::default(),
+            server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
+        };
+        let server = TestServer::new(build_admin_router(admin_state)).unwrap();
+        let node = Node { id: "node".into(), cluster_id: "cluster".into(), metadata: None, ..Default::default() };
+
+        layered_runtime::initialize(&LayeredRuntime::default(), &node);
+        let response = server.post("/runtime_modify").add_query_param("feature", "50").await;
+        response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        let config = LayeredRuntime {
+            layers: vec![
+                RuntimeLayer {
+                    name: "static".into(),
+                    layer: RuntimeLayerSpecifier::StaticLayer(
+                        [("feature".into(), RuntimeValue::Number(10.0))].into_iter().collect(),
+                    ),
+                },
+                RuntimeLayer { name: "admin".into(), layer: RuntimeLayerSpecifier::AdminLayer },
+            ],
+        };
+        layered_runtime::initialize(&config, &node);
+        let response = server.post("/runtime_modify").add_query_param("feature", "50").await;
+        response.assert_status_ok();
+        assert_eq!(layered_runtime::get_integer("feature", 0), 50);
+
+        let response = server.get("/runtime").await;
+        response.assert_status_ok();
+        assert_eq!(
+            response.json::<serde_json::Value>(),
+            json!({
+                "layers": ["static", "admin"],
+                "entries": { "feature": { "final_value": "50", "layer_values": ["10", "50"] } }
+            })
+        );
+    }
+}
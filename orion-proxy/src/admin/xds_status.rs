@@ -0,0 +1,161 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use super::FormatQuery;
+use axum::{extract::Query, response::Response};
+use orion_xds::xds::client_status::{resource_statuses, ResourceStatus};
+use serde::Serialize;
+use std::{fmt::Write, time::SystemTime};
+
+#[derive(Serialize)]
+struct XdsResourceStatus {
+    type_url: String,
+    name: String,
+    client_status: &'static str,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    version_info: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_updated: Option<SystemTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_state: Option<UpdateFailureState>,
+}
+
+#[derive(Serialize)]
+struct UpdateFailureState {
+    version_info: String,
+    details: String,
+    last_update_attempt: SystemTime,
+}
+
+#[derive(Serialize)]
+struct XdsStatusResponse {
+    resource_statuses: Vec<XdsResourceStatus>,
+}
+
+impl From<ResourceStatus> for XdsResourceStatus {
+    fn from(status: ResourceStatus) -> Self {
+        let ResourceStatus { type_url, name, client_status, version_info, last_updated, error_state } = status;
+        Self {
+            type_url: type_url.to_string(),
+            name,
+            client_status: client_status.as_str(),
+            version_info,
+            last_updated,
+            error_state: error_state.map(|failure| UpdateFailureState {
+                version_info: failure.version_info,
+                details: failure.details,
+                last_update_attempt: failure.last_update_attempt,
+            }),
+        }
+    }
+}
+
+/// The status of every xDS resource of the proxy, as reported over CSDS.
+pub async fn get_xds_status(Query(query): Query<FormatQuery>) -> Response {
+    let response =
+        XdsStatusResponse { resource_statuses: resource_statuses().into_iter().map(XdsResourceStatus::from).collect() };
+    query.format.render(&response, |response| render_text(&response.resource_statuses))
+}
+
+/// Renders one `type_url::name::field::value` line per resource field, like the clusters are.
+fn render_text(statuses: &[XdsResourceStatus]) -> String {
+    let mut text = String::new();
+    for status in statuses {
+        let prefix = format!("{}::{}", status.type_url, status.name);
+        let _ = writeln!(text, "{prefix}::client_status::{}", status.client_status);
+        if !status.version_info.is_empty() {
+            let _ = writeln!(text, "{prefix}::version_info::{}", status.version_info);
+        }
+        if let Some(failure) = &status.error_state {
+            let _ = writeln!(text, "{prefix}::error_state::version_info::{}", failure.version_info);
+            let _ = writeln!(text, "{prefix}::error_state::details::{}", failure.details);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use axum_test::TestServer;
+    use orion_configuration::config::{bootstrap::Node, Bootstrap};
+    use orion_data_plane_api::envoy_data_plane_api::envoy::{
+        admin::v3::ClientResourceStatus,
+        service::status::v3::{
+            client_status_discovery_service_client::ClientStatusDiscoveryServiceClient, ClientStatusRequest,
+        },
+    };
+    use orion_xds::xds::{client_status, model::TypeUrl};
+
+    fn admin_router() -> Router {
+        let admin_state = AdminState {
+            bootstrap: Bootstrap {
+                node: Some(Node { id: "xds-status-node".into(), ..Default::default() }),
+                ..Default::default()
+            },
+            configuration_senders: vec![],
+            secret_manager: Arc::new(RwLock::new(orion_lib::SecretManager::default())),
+            server_info: ServerInfo::default(),
+            server_startup: Instant::now(),
+            shutdown: CancellationToken::new(),
+        };
+        build_admin_router(admin_state)
+    }
+
+    #[tokio::test]
+    async fn nacked_resources_are_reported_with_their_error() {
+        client_status::acked(TypeUrl::Cluster, "xds_status_cluster", "1");
+        let rejection = orion_xds::xds::model::RejectedConfig::from(("xds_status_cluster".to_owned(), "bad lb policy"));
+        client_status::nacked(TypeUrl::Cluster, [("xds_status_cluster", "2")], &[rejection]);
+
+        let server = TestServer::new(admin_router()).unwrap();
+        let text = server.get("/xds_status").await.text();
+        let prefix = "type.googleapis.com/envoy.config.cluster.v3.Cluster::xds_status_cluster";
+        assert!(text.contains(&format!("{prefix}::client_status::NACKED\n")), "{text}");
+        assert!(text.contains(&format!("{prefix}::version_info::1\n")), "{text}");
+        assert!(text.contains(&format!("{prefix}::error_state::details::bad lb policy\n")), "{text}");
+
+        let json: serde_json::Value = server.get("/xds_status").add_query_param("format", "json").await.json();
+        let status = json["resource_statuses"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|status| status["name"] == "xds_status_cluster")
+            .unwrap();
+        assert_eq!(status["client_status"], "NACKED");
+        assert_eq!(status["error_state"]["version_info"], "2");
+    }
+
+    #[tokio::test]
+    async fn client_status_served_over_grpc() {
+        client_status::requested(TypeUrl::RouteConfiguration, "xds_status_routes");
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, admin_router()).await });
+
+        let mut client = ClientStatusDiscoveryServiceClient::connect(format!("http://{address}")).await.unwrap();
+        let response = client
+            .fetch_client_status(ClientStatusRequest { exclude_resource_contents: true, ..Default::default() })
+            .await
+            .unwrap()
+            .into_inner();
+        let config = &response.config[0];
+        assert_eq!(config.node.as_ref().map(|node| node.id.as_str()), Some("xds-status-node"));
+        let routes = config.generic_xds_configs.iter().find(|config| config.name == "xds_status_routes").unwrap();
+        assert_eq!(routes.client_status, ClientResourceStatus::Requested as i32);
+    }
+}
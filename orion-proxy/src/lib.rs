@@ -24,6 +24,7 @@ mod core_affinity;
 mod proxy;
 mod runtime;
 mod signal;
+mod xds_cache;
 mod xds_configurator;
 
 pub fn run() -> Result<()> {
@@ -35,6 +36,7 @@ pub fn run() -> Result<()> {
     RUNTIME_CONFIG.set(runtime).map_err(|_| "runtime config was somehow set before we had a chance to set it")?;
 
     tracing_manager.update(logging)?;
+    admin::logging::install(tracing_manager.filter_reload_handle());
 
     #[cfg(target_os = "linux")]
     if !(caps::has_cap(None, caps::CapSet::Permitted, caps::Capability::CAP_NET_RAW)?) {
@@ -61,7 +63,7 @@ mod proxy_tracing {
 
     type RegistryLayer =
         fmt::Layer<Layered<reload::Layer<EnvFilter, Registry>, Registry>, DefaultFields, Format, NonBlocking>;
-    type FilterReloadHandle = Handle<EnvFilter, Registry>;
+    pub(crate) type FilterReloadHandle = Handle<EnvFilter, Registry>;
     type LayerReloadHandle = Handle<
         fmt::Layer<Layered<reload::Layer<EnvFilter, Registry>, Registry>, DefaultFields, Format, NonBlocking>,
         Layered<reload::Layer<EnvFilter, Registry>, Registry>,
@@ -104,6 +106,10 @@ mod proxy_tracing {
             Ok(())
         }
 
+        pub fn filter_reload_handle(&self) -> FilterReloadHandle {
+            self.filter_reload_handle.clone()
+        }
+
         fn init_tracing(
             registry: Registry,
             log_level: EnvFilter,
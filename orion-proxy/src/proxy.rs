@@ -20,14 +20,17 @@ use crate::{
     core_affinity,
     runtime::{self, RuntimeId},
     signal::wait_signal,
-    xds_configurator::XdsConfigurationHandler,
+    xds_configurator::{
+        health_check_for_hds_server, report_load, static_secrets, Authorities, XdsConfigurationHandler, XDS_INIT_TARGET,
+    },
 };
 use compact_str::ToCompactString;
 use futures::future::join_all;
 use orion_configuration::config::{
-    bootstrap::Node,
+    bootstrap::{DynamicResources, Node},
     log::AccessLogConfig,
     network_filters::tracing::{TracingConfig, TracingKey},
+    overload::OverloadManager as OverloadManagerConfig,
     runtime::Affinity,
     Bootstrap,
 };
@@ -35,8 +38,10 @@ use orion_error::Context;
 use orion_lib::{
     access_log::{start_access_loggers, update_configuration, Target},
     clusters::cluster::ClusterType,
-    get_listeners_and_clusters, new_configuration_channel, runtime_config, ConfigurationReceivers,
-    ConfigurationSenders, ListenerConfigurationChange, PartialClusterType, Result, SecretManager,
+    get_listeners_and_clusters, layered_runtime, new_configuration_channel,
+    overload::{self, OverloadManager},
+    runtime_config, ConfigurationReceivers, ConfigurationSenders, ListenerConfigurationChange, PartialClusterType,
+    Result, SecretManager,
 };
 use orion_metrics::{metrics::init_global_metrics, wait_for_metrics_setup, Metrics, VecMetrics};
 use parking_lot::RwLock;
@@ -100,7 +105,6 @@ struct ProxyConfiguration {
     secret_manager: Arc<RwLock<SecretManager>>,
     listener_factories: Vec<orion_lib::ListenerFactory>,
     clusters: Vec<orion_lib::PartialClusterType>,
-    ads_cluster_names: Vec<String>,
     access_log_config: Option<AccessLogConfig>,
     tracing: HashMap<TracingKey, TracingConfig>,
     metrics: Vec<Metrics>,
@@ -134,15 +138,14 @@ fn launch_runtimes(
         .flat_map(orion_configuration::config::Listener::get_tracing_configurations)
         .collect::<HashMap<_, _>>();
 
-    let ads_cluster_names: Vec<String> = bootstrap.get_ads_configs().iter().map(ToString::to_string).collect();
-    let node = bootstrap.node.clone().unwrap_or_else(|| Node { id: "".into(), cluster_id: "".into(), metadata: None });
+    let node = bootstrap.node.clone().unwrap_or_default();
 
     let (secret_manager, listener_factories, clusters) =
         get_listeners_and_clusters(bootstrap.clone()).with_context_msg("Failed to get listeners and clusters")?;
     let secret_manager = Arc::new(RwLock::new(secret_manager));
 
-    if listener_factories.is_empty() && ads_cluster_names.is_empty() {
-        return Err("No listeners and no ads clusters configured".into());
+    if listener_factories.is_empty() && !bootstrap.has_dynamic_resources() {
+        return Err("No listeners and no xDS clusters configured".into());
     }
 
     let config = ProxyConfiguration {
@@ -151,7 +154,6 @@ fn launch_runtimes(
         secret_manager,
         listener_factories,
         clusters,
-        ads_cluster_names,
         access_log_config,
         tracing,
         metrics: metrics.clone(),
@@ -248,7 +250,7 @@ fn spawn_services_runtime_from_thread(
         let rt = runtime::build_tokio_runtime(&thread_name, threads_num, affinity_info, None);
         rt.block_on(async {
             tokio::select! {
-                result = run_services(config) => {
+                result = run_services(config, ct.clone()) => {
                     if let Err(err) = result {
                         warn!("Error in services runtime: {err:?}");
                     }
@@ -270,7 +272,7 @@ fn build_thread_name(thread_name: &'static str, affinity_info: Option<&(RuntimeI
     }
 }
 
-async fn run_services(config: ProxyConfiguration) -> Result<()> {
+async fn run_services(config: ProxyConfiguration, shutdown: tokio_util::sync::CancellationToken) -> Result<()> {
     let ProxyConfiguration {
         bootstrap,
         node,
@@ -278,7 +280,6 @@ async fn run_services(config: ProxyConfiguration) -> Result<()> {
         secret_manager,
         listener_factories,
         clusters,
-        ads_cluster_names,
         access_log_config,
         metrics,
         #[allow(unused_variables)]
@@ -286,6 +287,39 @@ async fn run_services(config: ProxyConfiguration) -> Result<()> {
     } = config;
     let mut set: JoinSet<Result<()>> = JoinSet::new();
 
+    // install the global connection limit and spawn the overload manager, before any listener starts accepting connections...
+    overload::set_max_downstream_connections(bootstrap.max_downstream_connections);
+    if let Some(conf) = bootstrap.overload_manager.clone() {
+        spawn_overload_manager(&mut set, conf);
+    }
+
+    // load the runtime layers, and watch the disk ones for changes...
+    layered_runtime::initialize(&bootstrap.layered_runtime.clone().unwrap_or_default(), &node);
+    if layered_runtime::has_disk_layers() {
+        set.spawn(async {
+            layered_runtime::watch_disk_layers().await;
+            Ok(())
+        });
+    }
+
+    // spawn the load reporting service...
+    if let Some(load_stats_config) = bootstrap.load_stats_config.clone() {
+        let node = node.clone();
+        set.spawn(async move {
+            report_load(node, load_stats_config).await;
+            Ok(())
+        });
+    }
+
+    // spawn the delegated health checks...
+    if let Some(hds_config) = bootstrap.hds_config.clone() {
+        let node = node.clone();
+        set.spawn(async move {
+            health_check_for_hds_server(node, hds_config).await;
+            Ok(())
+        });
+    }
+
     // spawn XDS configuration service...
     spawn_xds_client(
         &mut set,
@@ -295,7 +329,6 @@ async fn run_services(config: ProxyConfiguration) -> Result<()> {
         secret_manager.clone(),
         listener_factories,
         clusters,
-        ads_cluster_names,
     );
 
     // spawn access loggers service...
@@ -305,7 +338,7 @@ async fn run_services(config: ProxyConfiguration) -> Result<()> {
 
     // spawn admin interface task
     if bootstrap.admin.is_some() {
-        spawn_admin_service(&mut set, bootstrap, configuration_senders, secret_manager);
+        spawn_admin_service(&mut set, bootstrap, configuration_senders, secret_manager, shutdown);
     }
 
     // spawn metrics exporter...
@@ -336,19 +369,39 @@ fn spawn_xds_client(
     secret_manager: Arc<RwLock<SecretManager>>,
     listener_factories: Vec<orion_lib::ListenerFactory>,
     clusters: Vec<orion_lib::PartialClusterType>,
-    ads_cluster_names: Vec<String>,
 ) {
+    let static_secrets = static_secrets(&bootstrap.static_resources);
+    // the secrets of static resources discovered from files are read even without a management server
+    let dynamic_resources = bootstrap
+        .dynamic_resources
+        .clone()
+        .filter(|_| bootstrap.has_dynamic_resources())
+        .or_else(|| (!static_secrets.is_empty()).then(DynamicResources::default));
+    let authorities = Authorities::from(&bootstrap);
+    if dynamic_resources.is_some() {
+        orion_lib::lifecycle::add_init_target(XDS_INIT_TARGET);
+    }
     set.spawn(async move {
         let initial_clusters =
             configure_initial_resources(bootstrap, listener_factories, clusters, configuration_senders.clone()).await?;
-        if !ads_cluster_names.is_empty() {
-            let mut xds_handler = XdsConfigurationHandler::new(secret_manager, configuration_senders);
-            _ = xds_handler.run_loop(node, initial_clusters, ads_cluster_names).await;
+        if let Some(dynamic_resources) = dynamic_resources {
+            let xds_cache_dir = runtime_config().xds_cache_dir.clone();
+            let mut xds_handler =
+                XdsConfigurationHandler::new(secret_manager, configuration_senders, xds_cache_dir, static_secrets);
+            _ = Box::pin(xds_handler.run_loop(node, initial_clusters, dynamic_resources, authorities)).await;
         }
         Ok(())
     });
 }
 
+fn spawn_overload_manager(set: &mut JoinSet<Result<()>>, conf: OverloadManagerConfig) {
+    let overload_manager = OverloadManager::new(conf);
+    set.spawn(async move {
+        overload_manager.run().await;
+        Ok(())
+    });
+}
+
 fn spawn_access_loggers(set: &mut JoinSet<Result<()>>, bootstrap: Bootstrap, conf: AccessLogConfig) {
     let listeners = bootstrap.static_resources.listeners;
     set.spawn(async move {
@@ -379,9 +432,10 @@ fn spawn_admin_service(
     bootstrap: Bootstrap,
     configuration_senders: Vec<ConfigurationSenders>,
     secret_manager: Arc<RwLock<SecretManager>>,
+    shutdown: tokio_util::sync::CancellationToken,
 ) {
     set.spawn(async move {
-        _ = start_admin_server(bootstrap, configuration_senders, secret_manager).await;
+        _ = start_admin_server(bootstrap, configuration_senders, secret_manager, shutdown).await;
         Ok(())
     });
 }
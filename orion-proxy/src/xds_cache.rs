@@ -0,0 +1,360 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! On-disk cache of the last xDS resources accepted, one file per type of resources.
+//!
+//! The proxy starts with the cached resources when it restarts, so that it doesn't come up with its static resources
+//! only while the management server can't be reached. Once the stream of a type of resources is up again, cached
+//! listeners and clusters the management server no longer sends are removed.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::SystemTime,
+};
+
+use orion_configuration::config::{
+    cluster::ClusterLoadAssignment,
+    extension_config::ExtensionConfig,
+    network_filters::http_connection_manager::{
+        scoped_routes::ScopedRouteConfiguration, RouteConfiguration, VirtualHost,
+    },
+    secret::Secret,
+    Cluster, Listener,
+};
+use orion_xds::xds::model::{ResourceId, ResourceVersion, TypeUrl, XdsResourcePayload, XdsResourceUpdate};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// The types of resources in the order they are loaded in, so that what a resource refers to is there before it.
+const LOAD_ORDER: [TypeUrl; 8] = [
+    TypeUrl::Secret,
+    TypeUrl::TypedExtensionConfig,
+    TypeUrl::Cluster,
+    TypeUrl::ClusterLoadAssignment,
+    TypeUrl::Listener,
+    TypeUrl::RouteConfiguration,
+    TypeUrl::ScopedRouteConfiguration,
+    TypeUrl::VirtualHost,
+];
+
+static CACHE_STATUS: LazyLock<RwLock<BTreeMap<TypeUrl, TypeStatus>>> = LazyLock::new(|| RwLock::new(BTreeMap::new()));
+
+#[derive(Debug, Clone, Copy)]
+struct TypeStatus {
+    written_at: SystemTime,
+    resources: usize,
+    warm: bool,
+}
+
+/// How old the cached resources of a type are, as shown by `/config_dump`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CacheStatus {
+    pub type_url: String,
+    pub resources: usize,
+    /// Seconds since the resources were last written to disk.
+    pub age_seconds: u64,
+    /// Whether resources loaded from disk are still in use, the management server not having sent this type yet.
+    pub warm: bool,
+}
+
+/// The cache status of every type of resources, empty when there is no cache.
+pub fn status() -> Vec<CacheStatus> {
+    let now = SystemTime::now();
+    CACHE_STATUS
+        .read()
+        .iter()
+        .map(|(type_url, status)| CacheStatus {
+            type_url: type_url.to_string(),
+            resources: status.resources,
+            age_seconds: now.duration_since(status.written_at).unwrap_or_default().as_secs(),
+            warm: status.warm,
+        })
+        .collect()
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CachedPayload {
+    Listener(Listener),
+    Cluster(Cluster),
+    Endpoints(ClusterLoadAssignment),
+    RouteConfiguration(RouteConfiguration),
+    Secret(Secret),
+    ExtensionConfig(ExtensionConfig),
+    VirtualHost(VirtualHost),
+    ScopedRoute(ScopedRouteConfiguration),
+}
+
+impl From<&XdsResourcePayload> for CachedPayload {
+    fn from(payload: &XdsResourcePayload) -> Self {
+        match payload {
+            XdsResourcePayload::Listener(_, listener) => Self::Listener(listener.clone()),
+            XdsResourcePayload::Cluster(_, cluster) => Self::Cluster(cluster.clone()),
+            XdsResourcePayload::Endpoints(_, cla) => Self::Endpoints(cla.clone()),
+            XdsResourcePayload::RouteConfiguration(_, route) => Self::RouteConfiguration(route.clone()),
+            XdsResourcePayload::Secret(_, secret) => Self::Secret(secret.clone()),
+            XdsResourcePayload::ExtensionConfig(_, config) => Self::ExtensionConfig(config.clone()),
+            XdsResourcePayload::VirtualHost(_, virtual_host, _) => Self::VirtualHost(virtual_host.clone()),
+            XdsResourcePayload::ScopedRoute(_, scope) => Self::ScopedRoute(scope.clone()),
+        }
+    }
+}
+
+impl CachedPayload {
+    fn into_payload(self, id: ResourceId) -> XdsResourcePayload {
+        match self {
+            Self::Listener(listener) => XdsResourcePayload::Listener(id, listener),
+            Self::Cluster(cluster) => XdsResourcePayload::Cluster(id, cluster),
+            Self::Endpoints(cla) => XdsResourcePayload::Endpoints(id, cla),
+            Self::RouteConfiguration(route) => XdsResourcePayload::RouteConfiguration(id, route),
+            Self::Secret(secret) => XdsResourcePayload::Secret(id, secret),
+            Self::ExtensionConfig(config) => XdsResourcePayload::ExtensionConfig(id, config),
+            Self::VirtualHost(virtual_host) => XdsResourcePayload::VirtualHost(id, virtual_host, Vec::new()),
+            Self::ScopedRoute(scope) => XdsResourcePayload::ScopedRoute(id, scope),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResource {
+    version: ResourceVersion,
+    resource: CachedPayload,
+}
+
+/// The content of the cache file of a type of resources.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    written_at: SystemTime,
+    resources: BTreeMap<ResourceId, CachedResource>,
+}
+
+/// A change to a resource, to be recorded once the resource is accepted.
+#[allow(clippy::large_enum_variant)]
+pub enum CacheChange {
+    Update(TypeUrl, ResourceId, CachedResource),
+    Remove(TypeUrl, ResourceId),
+}
+
+impl CacheChange {
+    pub fn of(update: &XdsResourceUpdate) -> Self {
+        match update {
+            XdsResourceUpdate::Update(id, payload, version) => {
+                let resource = CachedResource { version: version.clone(), resource: CachedPayload::from(payload) };
+                Self::Update(payload.type_url(), id.clone(), resource)
+            },
+            XdsResourceUpdate::Remove(id, type_url) => Self::Remove(*type_url, id.clone()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct XdsCache {
+    directory: PathBuf,
+    resources: BTreeMap<TypeUrl, BTreeMap<ResourceId, CachedResource>>,
+    /// The resources loaded from disk the management server hasn't sent since.
+    warm: BTreeMap<TypeUrl, BTreeSet<ResourceId>>,
+    changed: HashSet<TypeUrl>,
+}
+
+impl XdsCache {
+    /// Loads the cached resources, returning them as updates to be applied in order.
+    pub fn load(directory: PathBuf) -> (Self, Vec<XdsResourceUpdate>) {
+        let mut cache = Self { directory, resources: BTreeMap::new(), warm: BTreeMap::new(), changed: HashSet::new() };
+        let mut updates = Vec::new();
+        for type_url in LOAD_ORDER {
+            let path = cache.path(type_url);
+            let cache_file = match fs::read(&path) {
+                Ok(content) => serde_json::from_slice::<CacheFile>(&content)
+                    .inspect_err(|e| warn!("Ignoring the xDS cache file {}: {e}", path.display()))
+                    .ok(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    warn!("Can't read the xDS cache file {}: {e}", path.display());
+                    None
+                },
+            };
+            let Some(CacheFile { written_at, resources }) = cache_file else {
+                continue;
+            };
+            info!("Loaded {} cached {type_url} resources from {}", resources.len(), path.display());
+            set_status(type_url, TypeStatus { written_at, resources: resources.len(), warm: !resources.is_empty() });
+            cache.warm.insert(type_url, resources.keys().cloned().collect());
+            updates.extend(resources.iter().map(|(id, CachedResource { version, resource })| {
+                XdsResourceUpdate::Update(id.clone(), resource.clone().into_payload(id.clone()), version.clone())
+            }));
+            cache.resources.insert(type_url, resources);
+        }
+        (cache, updates)
+    }
+
+    /// Forgets the resources loaded from disk when they are not part of the first resources of their type sent by
+    /// the management server. The ones that have to be removed from the configuration are returned: the listeners,
+    /// clusters and route scopes, of which the management server always sends them all.
+    pub fn reconcile(&mut self, type_url: TypeUrl, received: &HashSet<ResourceId>) -> Vec<ResourceId> {
+        let Some(warm) = self.warm.remove(&type_url) else {
+            return Vec::new();
+        };
+        let stale: Vec<_> = warm.into_iter().filter(|id| !received.contains(id)).collect();
+        if let Some(resources) = self.resources.get_mut(&type_url) {
+            for id in &stale {
+                resources.remove(id);
+            }
+        }
+        self.changed.insert(type_url);
+        match type_url {
+            TypeUrl::Listener | TypeUrl::Cluster | TypeUrl::ScopedRouteConfiguration => stale,
+            TypeUrl::RouteConfiguration
+            | TypeUrl::ClusterLoadAssignment
+            | TypeUrl::Secret
+            | TypeUrl::TypedExtensionConfig
+            | TypeUrl::VirtualHost => Vec::new(),
+        }
+    }
+
+    /// Records a change to a resource the proxy accepted.
+    pub fn record(&mut self, change: CacheChange) {
+        let type_url = match change {
+            CacheChange::Update(type_url, id, resource) => {
+                self.resources.entry(type_url).or_default().insert(id, resource);
+                type_url
+            },
+            CacheChange::Remove(type_url, id) => {
+                self.resources.entry(type_url).or_default().remove(&id);
+                type_url
+            },
+        };
+        self.changed.insert(type_url);
+    }
+
+    /// Writes the types of resources that changed to disk, each atomically.
+    pub fn flush(&mut self) {
+        for type_url in std::mem::take(&mut self.changed) {
+            let resources = self.resources.get(&type_url).cloned().unwrap_or_default();
+            let count = resources.len();
+            let cache_file = CacheFile { written_at: SystemTime::now(), resources };
+            match write_atomically(&self.path(type_url), &cache_file) {
+                Ok(()) => set_status(
+                    type_url,
+                    TypeStatus {
+                        written_at: cache_file.written_at,
+                        resources: count,
+                        warm: self.warm.contains_key(&type_url),
+                    },
+                ),
+                Err(e) => warn!("Can't write the xDS cache of {type_url} resources: {e}"),
+            }
+        }
+    }
+
+    fn path(&self, type_url: TypeUrl) -> PathBuf {
+        let file_name = match type_url {
+            TypeUrl::Listener => "listeners.json",
+            TypeUrl::Cluster => "clusters.json",
+            TypeUrl::RouteConfiguration => "routes.json",
+            TypeUrl::ClusterLoadAssignment => "endpoints.json",
+            TypeUrl::Secret => "secrets.json",
+            TypeUrl::TypedExtensionConfig => "extension_configs.json",
+            TypeUrl::VirtualHost => "virtual_hosts.json",
+            TypeUrl::ScopedRouteConfiguration => "scoped_routes.json",
+        };
+        self.directory.join(file_name)
+    }
+}
+
+fn set_status(type_url: TypeUrl, status: TypeStatus) {
+    CACHE_STATUS.write().insert(type_url, status);
+}
+
+/// Writes a file next to the cache file and moves it onto it, so that the cache file is never seen half written.
+/// The files are only readable by their owner, since secrets are cached too.
+fn write_atomically(path: &Path, cache_file: &CacheFile) -> io::Result<()> {
+    let content = serde_json::to_vec(cache_file).map_err(io::Error::other)?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let staged = path.with_extension("json.tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&staged)?;
+    io::Write::write_all(&mut file, &content)?;
+    file.sync_all()?;
+    fs::rename(&staged, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route_update(name: &str) -> XdsResourceUpdate {
+        let route: RouteConfiguration =
+            serde_json::from_value(serde_json::json!({ "name": name, "virtual_hosts": [] })).unwrap();
+        XdsResourceUpdate::Update(
+            name.to_owned(),
+            XdsResourcePayload::RouteConfiguration(name.to_owned(), route),
+            "1".to_owned(),
+        )
+    }
+
+    #[test]
+    fn accepted_resources_are_loaded_back_until_reconciled() {
+        let directory = std::env::temp_dir().join(format!("orion-xds-cache-{}", std::process::id()));
+        let (mut cache, updates) = XdsCache::load(directory.clone());
+        assert!(updates.is_empty());
+        cache.record(CacheChange::of(&route_update("kept")));
+        cache.record(CacheChange::of(&route_update("removed")));
+        cache.record(CacheChange::of(&XdsResourceUpdate::Remove("removed".to_owned(), TypeUrl::RouteConfiguration)));
+        cache.record(CacheChange::of(&route_update("stale")));
+        cache.flush();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(directory.join("routes.json")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let (mut cache, updates) = XdsCache::load(directory.clone());
+        let ids: Vec<_> = updates.iter().map(XdsResourceUpdate::id).collect();
+        assert_eq!(ids, ["kept", "stale"]);
+        let XdsResourceUpdate::Update(_, XdsResourcePayload::RouteConfiguration(_, route), version) = &updates[0]
+        else {
+            unreachable!()
+        };
+        assert_eq!((route.name.as_str(), version.as_str()), ("kept", "1"));
+        let status = status();
+        assert!(status.iter().any(|status| status.type_url == TypeUrl::RouteConfiguration.to_string()
+            && status.resources == 2
+            && status.warm));
+
+        // routes are only sent when subscribed to, stale ones are forgotten without being removed
+        let received = HashSet::from(["kept".to_owned()]);
+        assert!(cache.reconcile(TypeUrl::RouteConfiguration, &received).is_empty());
+        assert!(cache.reconcile(TypeUrl::RouteConfiguration, &HashSet::new()).is_empty());
+        cache.flush();
+        let (_, updates) = XdsCache::load(directory.clone());
+        let ids: Vec<_> = updates.iter().map(XdsResourceUpdate::id).collect();
+        assert_eq!(ids, ["kept"]);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -18,32 +18,71 @@
 #[cfg(feature = "tracing")]
 use compact_str::ToCompactString;
 use futures::future::join_all;
-use orion_configuration::config::{bootstrap::Node, cluster::ClusterSpecifier, Listener};
+use http::HeaderMap;
+use orion_configuration::config::{
+    bootstrap::{AuthorityConfigSource, Bootstrap, DynamicResources, Node, StaticResources},
+    cluster::{ClusterDiscoveryType, EdsClusterConfig},
+    listener::MainFilter,
+    network_filters::http_connection_manager::{scoped_routes::ScopesSpecifier, RouteSpecifier},
+    transport::SdsConfig,
+    xdstp::XdstpName,
+    ApiConfigSource, ConfigSource, ConfigSourceSpecifier, Listener, PathConfigSource,
+};
 use orion_lib::{
     access_log::{update_configuration, Target},
-    clusters::cluster::ClusterType,
+    clusters::{
+        cluster::{ClusterOps, ClusterType},
+        FailoverGrpcService,
+    },
+    on_demand::{Ask, ClusterRequest, RouteConfigurationRequest, VirtualHostRequest},
     ConfigurationSenders, ConversionContext, EndpointHealthUpdate, HealthCheckManager, ListenerConfigurationChange,
     ListenerFactory, PartialClusterLoadAssignment, PartialClusterType, Result, RouteConfigurationChange, SecretManager,
 };
 use orion_xds::{
-    start_aggregate_client_no_retry_loop,
+    start_aggregate_client_no_retry_loop, start_path_client, start_typed_client_no_retry_loop,
     xds::{
-        bindings::AggregatedDiscoveryType,
-        client::{
-            DeltaClientBackgroundWorker, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager, XdsUpdateEvent,
-        },
+        client::{DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager, XdsUpdateEvent},
         model::{RejectedConfig, TypeUrl, XdsResourcePayload, XdsResourceUpdate},
     },
 };
 use parking_lot::RwLock;
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
 use tokio::{
     select,
     sync::mpsc::{self, Receiver, Sender},
 };
 use tracing::{debug, info, warn};
 
-const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+use crate::xds_cache::{CacheChange, XdsCache};
+use srds::ScopedRoutes;
+use vhds::VirtualHosts;
+use warming::{
+    cluster_secrets, cluster_warming_timeout, discovered_filters, listener_secrets, Dependency, Warming, WarmingTimeout,
+};
+
+mod health_discovery;
+mod load_reporting;
+mod srds;
+mod vhds;
+mod warming;
+
+pub use health_discovery::health_check_for_hds_server;
+pub use load_reporting::report_load;
+
+/// The secrets the static listeners and clusters of the bootstrap refer to that are discovered.
+pub fn static_secrets(static_resources: &StaticResources) -> Vec<SdsConfig> {
+    let listener_secrets = static_resources.listeners.iter().flat_map(listener_secrets);
+    let cluster_secrets = static_resources.clusters.iter().flat_map(cluster_secrets);
+    listener_secrets.chain(cluster_secrets).filter(|secret| secret.config_source.is_some()).cloned().collect()
+}
+
+/// Init target of the xDS configuration handler starting its streams, added before the admin server can tell
+/// whether the proxy is ready.
+pub const XDS_INIT_TARGET: &str = "xds";
 
 pub struct XdsConfigurationHandler {
     secret_manager: Arc<RwLock<SecretManager>>,
@@ -51,10 +90,23 @@ pub struct XdsConfigurationHandler {
     listeners_senders: Vec<Sender<ListenerConfigurationChange>>,
     route_senders: Vec<Sender<RouteConfigurationChange>>,
     health_updates_receiver: Receiver<EndpointHealthUpdate>,
+    cache_dir: Option<PathBuf>,
+    /// The secrets the static resources discover, subscribed to along with the dynamic resources.
+    static_secrets: Vec<SdsConfig>,
+    cache: Option<XdsCache>,
+    warming: Warming,
+    warming_timeouts: Receiver<WarmingTimeout>,
+    virtual_hosts: VirtualHosts,
+    scoped_routes: ScopedRoutes,
 }
 
 impl XdsConfigurationHandler {
-    pub fn new(secret_manager: Arc<RwLock<SecretManager>>, configuration_senders: Vec<ConfigurationSenders>) -> Self {
+    pub fn new(
+        secret_manager: Arc<RwLock<SecretManager>>,
+        configuration_senders: Vec<ConfigurationSenders>,
+        cache_dir: Option<PathBuf>,
+        static_secrets: Vec<SdsConfig>,
+    ) -> Self {
         let mut listeners_senders = Vec::with_capacity(configuration_senders.len());
         let mut route_senders = Vec::with_capacity(configuration_senders.len());
         for ConfigurationSenders { listener_configuration_sender, route_configuration_sender } in configuration_senders
@@ -64,50 +116,20 @@ impl XdsConfigurationHandler {
         }
         let (health_updates_sender, health_updates_receiver) = mpsc::channel(1000);
         let health_manager = HealthCheckManager::new(health_updates_sender);
-        Self { secret_manager, health_manager, listeners_senders, route_senders, health_updates_receiver }
-    }
-
-    // Resolve cluster name into working endpoint(s), return working client
-    fn resolve_endpoints(
-        cluster_name: &str,
-        node: &Node,
-    ) -> Result<(
-        DeltaClientBackgroundWorker<AggregatedDiscoveryType<orion_lib::clusters::SimpleRoundRobinGrpcServiceLB>>,
-        DeltaDiscoveryClient,
-        DeltaDiscoverySubscriptionManager,
-    )> {
-        let selector = ClusterSpecifier::Cluster(cluster_name.into());
-        let cluster_id = orion_lib::clusters::resolve_cluster(&selector)
-            .ok_or_else(|| format!("Failed to resolve cluster {cluster_name} from specifier"))?;
-        let grpc_connections = match orion_lib::clusters::all_grpc_connections(cluster_id) {
-            Ok(connections) => connections,
-            Err(err) => {
-                let msg = format!("Failed to get gRPC connections from cluster ({cluster_name}): {err}");
-                warn!(msg);
-                return Err(msg.into());
-            },
-        };
-        let grpc_services: Vec<orion_lib::clusters::GrpcService> = grpc_connections
-            .into_iter()
-            .filter_map(|result| match result {
-                Ok((_, grpc_service)) => Some(grpc_service),
-                Err(err) => {
-                    let msg = format!("Skipping (failed) gRPC endpoint for cluster ({cluster_name}): {err}");
-                    warn!(msg);
-                    None
-                },
-            })
-            .collect();
-
-        if grpc_services.is_empty() {
-            let msg = format!("Failed to locate any gRPC connections for cluster ({cluster_name})");
-            warn!(msg);
-            Err(msg.into())
-        } else {
-            let grpc_service_lb = orion_lib::clusters::SimpleRoundRobinGrpcServiceLB::new(grpc_services);
-            start_aggregate_client_no_retry_loop(node.clone(), grpc_service_lb)
-                .inspect_err(|e| warn!("Failed to connect to xDS server ({cluster_name}): {e}"))
-                .map_err(Into::into)
+        let (warming_timeouts_tx, warming_timeouts) = mpsc::channel(100);
+        Self {
+            secret_manager,
+            health_manager,
+            listeners_senders,
+            route_senders,
+            health_updates_receiver,
+            cache_dir,
+            static_secrets,
+            cache: None,
+            warming: Warming::new(warming_timeouts_tx),
+            warming_timeouts,
+            virtual_hosts: VirtualHosts::default(),
+            scoped_routes: ScopedRoutes::default(),
         }
     }
 
@@ -115,43 +137,99 @@ impl XdsConfigurationHandler {
         &mut self,
         node: Node,
         initial_clusters: Vec<ClusterType>,
-        ads_cluster_names: Vec<String>,
+        dynamic_resources: DynamicResources,
+        authorities: Authorities,
     ) -> Result<()> {
         for cluster in initial_clusters {
             self.health_manager.restart_cluster(cluster).await;
         }
 
-        let mut cluster_names = ads_cluster_names.into_iter().cycle();
-
-        let (mut worker, mut client, subscription_manager) = loop {
-            let Some(cluster_name) = cluster_names.next() else {
-                info!("No xDS clusters configured");
-                return Ok(());
-            };
+        let DynamicResources { ads_config, lds_config, cds_config, lds_resources_locator, cds_resources_locator } =
+            dynamic_resources;
+        let (updates_tx, mut updates_rx) = mpsc::channel(100);
+        let mut streams = XdsStreams::new(node, updates_tx, authorities);
+        // listeners and clusters named by a resources locator are discovered from the config source of its authority
+        let lds_config = streams.locator_config_source(lds_resources_locator.as_ref()).or(lds_config);
+        let cds_config = streams.locator_config_source(cds_resources_locator.as_ref()).or(cds_config);
+        let mut virtual_host_requests = orion_lib::on_demand::virtual_host_requests();
+        let mut cluster_requests = orion_lib::on_demand::cluster_requests();
+        let mut route_requests = orion_lib::on_demand::route_configuration_requests();
 
-            if let Ok(val) = Self::resolve_endpoints(&cluster_name, &node) {
-                break val;
+        // the proxy is ready once the first listeners and clusters were received, or waited for long enough
+        for (config_source, type_url) in [(&lds_config, TypeUrl::Listener), (&cds_config, TypeUrl::Cluster)] {
+            if config_source.is_none() && ads_config.grpc_cluster_specifiers.is_empty() {
+                continue;
+            }
+            let target = fetch_target(type_url);
+            orion_lib::lifecycle::add_init_target(&target);
+            let timeout = config_source
+                .as_ref()
+                .map_or(Some(ConfigSource::DEFAULT_INITIAL_FETCH_TIMEOUT), ConfigSource::fetch_timeout);
+            if let Some(timeout) = timeout {
+                tokio::spawn(async move {
+                    tokio::time::sleep(timeout).await;
+                    if !orion_lib::lifecycle::is_initialized() {
+                        warn!("No {type_url} resources received in {timeout:?}, initializing without them");
+                    }
+                    orion_lib::lifecycle::init_target_ready(&target);
+                });
             }
+        }
 
-            info!("Retrying XDS connection in {} seconds", RETRY_INTERVAL.as_secs());
-            tokio::time::sleep(RETRY_INTERVAL).await;
-        };
+        // the types served by management servers of their own or read from files are left out of the ADS stream
+        let mut discovered_elsewhere = Vec::new();
+        let mut subscribed_over_ads = Vec::new();
+        for (config_source, locator, type_url) in [
+            (lds_config, lds_resources_locator, TypeUrl::Listener),
+            (cds_config, cds_resources_locator, TypeUrl::Cluster),
+        ] {
+            let subscribed: Vec<_> = locator.iter().map(XdstpName::to_string).collect();
+            match config_source.map(|config_source| config_source.config_source_specifier) {
+                Some(ConfigSourceSpecifier::ApiConfigSource(source)) => {
+                    streams.stream_subscribed(&source, type_url, &subscribed);
+                    discovered_elsewhere.push(type_url);
+                },
+                Some(ConfigSourceSpecifier::PathConfigSource(source)) => {
+                    streams.watch(&source, type_url);
+                    discovered_elsewhere.push(type_url);
+                },
+                None | Some(ConfigSourceSpecifier::ADS) => {
+                    subscribed_over_ads.extend(subscribed.into_iter().map(|resource_id| (type_url, resource_id)));
+                },
+            }
+        }
+        streams.start_ads(&ads_config, &discovered_elsewhere, &subscribed_over_ads);
+        streams.subscribe_secrets(&std::mem::take(&mut self.static_secrets)).await;
 
-        tokio::spawn(async move {
-            let subscribe = worker.run().await;
-            info!("Worker exited {subscribe:?}");
-        });
+        // the last resources accepted are used until the management servers send theirs
+        if let Some(cache_dir) = self.cache_dir.take() {
+            let (cache, cached_updates) = XdsCache::load(cache_dir);
+            for update in cached_updates {
+                if let XdsResourceUpdate::Update(id, resource, _) = update {
+                    if let Err(e) = Box::pin(self.process_update_event(&id, resource, &mut streams)).await {
+                        warn!("Ignoring cached xDS resource {id}: {e}");
+                    }
+                }
+            }
+            self.send_discovered_virtual_hosts().await;
+            self.cache = Some(cache);
+        }
+        orion_lib::lifecycle::init_target_ready(XDS_INIT_TARGET);
 
         loop {
             select! {
-                Some(xds_update) = client.recv() => {
+                Some(xds_update) = updates_rx.recv() => {
                     info!("Got notification {xds_update:?}");
-                    let XdsUpdateEvent { ack_channel, updates } = xds_update;
+                    let XdsUpdateEvent { type_url, ack_channel, updates } = xds_update;
                     // Box::pin because the future from self.process_updates() is very large
-                    let rejected_updates = Box::pin(self.process_updates(updates, &subscription_manager)).await;
+                    let rejected_updates = Box::pin(self.process_updates(type_url, updates, &mut streams)).await;
                     let _ = ack_channel.send(rejected_updates);
                 },
                 Some(health_update) = self.health_updates_receiver.recv() => Self::process_health_event(&health_update),
+                Some(timeout) = self.warming_timeouts.recv() => self.process_warming_timeout(timeout).await,
+                Some(request) = virtual_host_requests.recv() => self.request_virtual_host(request, &mut streams).await,
+                Some(request) = cluster_requests.recv() => Self::request_cluster(request, &mut streams).await,
+                Some(request) = route_requests.recv() => Self::request_route_configuration(request, &mut streams).await,
                 else => break,
             }
         }
@@ -160,45 +238,85 @@ impl XdsConfigurationHandler {
         Ok(())
     }
 
+    /// Applies the resources of a response, and reconciles the cache with them, of its type even if it has none.
     async fn process_updates(
         &mut self,
+        type_url: TypeUrl,
         updates: Vec<XdsResourceUpdate>,
-        subscibtion_manager: &DeltaDiscoverySubscriptionManager,
+        streams: &mut XdsStreams,
     ) -> Vec<RejectedConfig> {
         let mut rejected_updates = Vec::new();
+        let mut received: HashMap<TypeUrl, HashSet<String>> = HashMap::from([(type_url, HashSet::new())]);
         for update in updates {
-            match update {
+            let change = self.cache.is_some().then(|| CacheChange::of(&update));
+            let result = match update {
                 XdsResourceUpdate::Update(id, resource, _) => {
-                    if let Err(e) = self.process_update_event(&id, resource, subscibtion_manager).await {
-                        rejected_updates.push(RejectedConfig::from((id, e)));
-                    }
+                    let type_url = resource.type_url();
+                    received.entry(type_url).or_default().insert(id.clone());
+                    Box::pin(self.process_update_event(&id, resource, streams)).await.map_err(|e| (id, e))
                 },
                 XdsResourceUpdate::Remove(id, resource) => {
-                    if let Err(e) = self.process_remove_event(&id, resource, subscibtion_manager).await {
-                        rejected_updates.push(RejectedConfig::from((id, e)));
+                    self.process_remove_event(&id, resource, streams).await.map_err(|e| (id, e))
+                },
+            };
+            match (result, change) {
+                (Ok(()), Some(change)) => {
+                    if let Some(cache) = &mut self.cache {
+                        cache.record(change);
                     }
                 },
+                (Ok(()), None) => {},
+                (Err(rejected), _) => rejected_updates.push(RejectedConfig::from(rejected)),
             }
         }
+        self.send_discovered_virtual_hosts().await;
+        self.reconcile_cache(&received, streams).await;
+        for type_url in received.keys() {
+            orion_lib::lifecycle::init_target_ready(&fetch_target(*type_url));
+        }
         rejected_updates
     }
 
-    async fn process_remove_event(
-        &mut self,
-        id: &str,
-        resource: TypeUrl,
-        subscibtion_manager: &DeltaDiscoverySubscriptionManager,
-    ) -> Result<()> {
+    /// Removes the cached resources the management servers didn't send back once they sent resources of their type,
+    /// and writes what changed to the cache.
+    async fn reconcile_cache(&mut self, received: &HashMap<TypeUrl, HashSet<String>>, streams: &mut XdsStreams) {
+        let Some(cache) = &mut self.cache else {
+            return;
+        };
+        let mut stale = Vec::new();
+        for (type_url, ids) in received {
+            stale.extend(cache.reconcile(*type_url, ids).into_iter().map(|id| (id, *type_url)));
+        }
+        for (id, type_url) in stale {
+            info!("Removing cached {type_url} {id} no longer sent by the management server");
+            if let Err(e) = self.process_remove_event(&id, type_url, streams).await {
+                warn!("Failed to remove cached {type_url} {id}: {e}");
+            }
+        }
+        if let Some(cache) = &mut self.cache {
+            cache.flush();
+        }
+    }
+
+    async fn process_remove_event(&mut self, id: &str, resource: TypeUrl, streams: &mut XdsStreams) -> Result<()> {
         match resource {
             orion_xds::xds::model::TypeUrl::Cluster => {
-                let maybe_unsubscribed =
-                    subscibtion_manager.subscribe(id.to_owned(), TypeUrl::ClusterLoadAssignment).await;
-                debug!("Updating unsubscribed for {id} {} {maybe_unsubscribed:?} ", TypeUrl::ClusterLoadAssignment);
-                orion_lib::clusters::remove_cluster(id)?;
+                let config_source = streams.endpoint_sources.remove(id);
+                streams.subscribe(config_source.as_ref(), id.to_owned(), TypeUrl::ClusterLoadAssignment).await;
+                self.warming.endpoints_removed(id);
+                // a cluster still waiting for its endpoints may not have any version of it in use
+                let was_warming = self.warming.take_cluster(id).is_some();
+                orion_lib::on_demand::cluster_not_found(id);
+                match orion_lib::clusters::remove_cluster(id) {
+                    Err(e) if !was_warming => return Err(e),
+                    _ => {},
+                }
                 self.health_manager.stop_cluster(id).await;
                 Ok(())
             },
             orion_xds::xds::model::TypeUrl::Listener => {
+                self.warming.cancel_listener(id);
+                self.scoped_routes.listener_removed(id);
                 let change = ListenerConfigurationChange::Removed(id.to_owned());
                 let _ = send_change_to_runtimes(&self.listeners_senders, change).await;
                 // remove access logs configuration...
@@ -209,13 +327,17 @@ impl XdsConfigurationHandler {
                 Ok(())
             },
             orion_xds::xds::model::TypeUrl::ClusterLoadAssignment => {
+                self.warming.endpoints_removed(id);
                 orion_lib::clusters::remove_cluster_load_assignment(id)?;
                 self.health_manager.stop_cluster(id).await;
                 Ok(())
             },
             orion_xds::xds::model::TypeUrl::RouteConfiguration => {
+                self.warming.route_removed(id);
+                self.virtual_hosts.route_removed(id);
                 let change = RouteConfigurationChange::Removed(id.to_owned());
                 let _ = send_change_to_runtimes(&self.route_senders, change).await;
+                orion_lib::on_demand::route_configuration_not_found(id);
                 Ok(())
             },
             orion_xds::xds::model::TypeUrl::Secret => {
@@ -223,6 +345,22 @@ impl XdsConfigurationHandler {
                 warn!("{msg}");
                 Err(msg.into())
             },
+            orion_xds::xds::model::TypeUrl::TypedExtensionConfig => {
+                // the filters using it fall back to their default configuration, if they have one
+                orion_lib::extension_configs::remove(id);
+                Ok(())
+            },
+            orion_xds::xds::model::TypeUrl::VirtualHost => {
+                // also how the management server tells it doesn't have a virtual host asked for on demand
+                self.virtual_hosts.remove(id);
+                orion_lib::on_demand::virtual_host_not_found(id);
+                Ok(())
+            },
+            orion_xds::xds::model::TypeUrl::ScopedRouteConfiguration => {
+                self.scoped_routes.scope_removed(id);
+                orion_lib::scoped_routes::remove(id);
+                Ok(())
+            },
         }
     }
 
@@ -231,7 +369,7 @@ impl XdsConfigurationHandler {
         &mut self,
         _: &str,
         resource: XdsResourcePayload,
-        subscibtion_manager: &DeltaDiscoverySubscriptionManager,
+        streams: &mut XdsStreams,
     ) -> Result<()> {
         match resource {
             XdsResourcePayload::Listener(id, listener) => {
@@ -241,31 +379,22 @@ impl XdsConfigurationHandler {
 
                 match factory {
                     Ok(factory) => {
-                        let change = ListenerConfigurationChange::Added(Box::new((factory, listener.clone())));
-                        let subscriptions  = listener.filter_chains.values().filter_map(|fc|
-                            match &fc.terminal_filter{
-                                orion_configuration::config::listener::MainFilter::Http(http_connection_manager) => match &http_connection_manager.route_specifier{
-                                    orion_configuration::config::network_filters::http_connection_manager::RouteSpecifier::Rds(rds_specifier) => Some(async {
-                                        let id= rds_specifier.route_config_name.to_string();
-                                        let maybe_subscribed = subscibtion_manager.subscribe(id.clone(), TypeUrl::RouteConfiguration).await;
-                                        debug!("Updating subscription for {id} {} {maybe_subscribed:?} ", TypeUrl::RouteConfiguration);
-                                }),
-                                    _ => None,
-                                }
-
-                                orion_configuration::config::listener::MainFilter::Tcp(_) => None,
-                            }
-                        ).collect::<Vec<_>>();
-
-                        let _res = join_all(subscriptions).await;
-
-                        let _ = send_change_to_runtimes(&self.listeners_senders, change).await;
-                        // update access logs configuration...
-                        self.access_log_listener_update(&id, &listener).await;
+                        self.subscribe_routes(&id, &listener, streams).await;
+                        for filter in discovered_filters(&listener) {
+                            let id = filter.name.to_owned();
+                            streams.subscribe(Some(filter.config_source), id, TypeUrl::TypedExtensionConfig).await;
+                        }
+                        streams.subscribe_secrets(listener_secrets(&listener)).await;
 
-                        // update tracer configuration...
-                        #[cfg(feature = "tracing")]
-                        self.tracer_listener_update(&id, &listener);
+                        // the listener isn't used before its routes, secrets and filter configurations are there
+                        let missing = self.warming.missing_dependencies(&listener, &self.secret_manager.read());
+                        if missing.is_empty() {
+                            self.warming.cancel_listener(&id);
+                            self.add_listener(&id, factory, listener).await;
+                        } else {
+                            debug!("Listener {id} is warming, waiting for {missing:?}");
+                            self.warming.warm_listener(id, listener, missing);
+                        }
                         Ok(())
                     },
                     Err(err) => {
@@ -276,16 +405,31 @@ impl XdsConfigurationHandler {
             },
             XdsResourcePayload::Cluster(id, cluster) => {
                 debug!("Got update for cluster: {id}: {:#?}", cluster);
+                let eds_config_source = match &cluster.discovery_settings {
+                    ClusterDiscoveryType::Eds(_, Some(EdsClusterConfig { config_source, .. })) => config_source.clone(),
+                    _ => None,
+                };
+                // a cluster whose endpoints are discovered isn't used before they are there
+                let needs_endpoints = matches!(cluster.discovery_settings, ClusterDiscoveryType::Eds(None, _))
+                    && self.warming.needs_endpoints(&id);
+                let secrets: Vec<_> = cluster_secrets(&cluster).into_iter().cloned().collect();
                 let cluster_builder = PartialClusterType::try_from((cluster, &*self.secret_manager.read()));
                 match cluster_builder {
                     Ok(cluster) => {
-                        let maybe_subscribed =
-                            subscibtion_manager.subscribe(id.clone(), TypeUrl::ClusterLoadAssignment).await;
-                        debug!(
-                            "Updating subscription for {id} {} {maybe_subscribed:?} ",
-                            TypeUrl::ClusterLoadAssignment
-                        );
-                        self.add_cluster(cluster).await
+                        streams.subscribe(eds_config_source.as_ref(), id.clone(), TypeUrl::ClusterLoadAssignment).await;
+                        streams.subscribe_secrets(&secrets).await;
+                        let warming_timeout = cluster_warming_timeout(eds_config_source.as_ref());
+                        if let Some(config_source) = eds_config_source {
+                            streams.endpoint_sources.insert(id.clone(), config_source);
+                        }
+                        if needs_endpoints {
+                            debug!("Cluster {id} is warming, waiting for its endpoints");
+                            self.warming.warm_cluster(id, cluster, warming_timeout);
+                            Ok(())
+                        } else {
+                            self.warming.take_cluster(&id);
+                            self.add_cluster(cluster).await
+                        }
                     },
                     Err(err) => {
                         warn!("Got invalid update for cluster {id}");
@@ -295,8 +439,15 @@ impl XdsConfigurationHandler {
             },
             XdsResourcePayload::RouteConfiguration(id, route) => {
                 debug!("Got update for route configuration {id}: {:#?}", route);
+                if let Some(vhds) = &route.vhds {
+                    streams.subscribe(Some(&vhds.config_source), id.clone(), TypeUrl::VirtualHost).await;
+                }
+                let route = self.virtual_hosts.route_received(&id, route);
                 let change = RouteConfigurationChange::Added((id.clone(), route));
                 let _ = send_change_to_runtimes(&self.route_senders, change).await;
+                orion_lib::on_demand::route_configuration_received(&id);
+                self.warming.route_received(&id);
+                self.dependency_ready(&Dependency::Route(id)).await;
                 Ok(())
             },
             XdsResourcePayload::Endpoints(id, cla) => {
@@ -305,8 +456,12 @@ impl XdsConfigurationHandler {
 
                 match cla {
                     Ok(cla) => {
+                        if let Some(cluster) = self.warming.take_cluster(&id) {
+                            self.add_cluster(cluster).await?;
+                        }
                         let cluster_name = id.clone();
                         let cluster_config = orion_lib::clusters::change_cluster_load_assignment(&cluster_name, &cla)?;
+                        self.warming.endpoints_received(&id);
                         self.health_manager.restart_cluster(cluster_config).await;
                         Ok(())
                     },
@@ -328,6 +483,7 @@ impl XdsConfigurationHandler {
                         }
                         let change = ListenerConfigurationChange::TlsContextChanged((id.clone(), secret));
                         let _ = send_change_to_runtimes(&self.listeners_senders, change).await;
+                        self.dependency_ready(&Dependency::Secret(id)).await;
                         Ok(())
                     },
                     Err(err) => {
@@ -336,6 +492,178 @@ impl XdsConfigurationHandler {
                     },
                 }
             },
+            XdsResourcePayload::ExtensionConfig(id, config) => {
+                debug!("Got update for extension config {id}: {:#?}", config);
+                // the filters look their configuration up for each request or connection, nothing to rebuild
+                let name = config.name.to_string();
+                orion_lib::extension_configs::update(config);
+                self.dependency_ready(&Dependency::ExtensionConfig(name)).await;
+                Ok(())
+            },
+            XdsResourcePayload::VirtualHost(id, virtual_host, aliases) => {
+                debug!("Got update for virtual host {id}: {:#?}", virtual_host);
+                // the route configuration is sent to the listeners once the whole batch of updates is processed
+                self.virtual_hosts.update(&id, virtual_host)?;
+                for alias in aliases {
+                    orion_lib::on_demand::virtual_host_received(&alias);
+                }
+                Ok(())
+            },
+            XdsResourcePayload::ScopedRoute(id, scope) => {
+                debug!("Got update for route scope {id}: {:#?}", scope);
+                for (config_source, name) in self.scoped_routes.scope_updated(&id, &scope) {
+                    streams.subscribe(Some(&config_source), name, TypeUrl::RouteConfiguration).await;
+                }
+                orion_lib::scoped_routes::update(scope);
+                Ok(())
+            },
+        }
+    }
+
+    /// Subscribes to the route configurations of the HTTP connection managers of a listener: the one of each
+    /// connection manager discovering it, and those of the scopes of each connection manager routing by scope, but
+    /// for the scopes loaded on demand. The scopes discovered over SRDS are discovered from then on.
+    async fn subscribe_routes(&mut self, id: &str, listener: &Listener, streams: &mut XdsStreams) {
+        let mut srds_rds_config_sources = Vec::new();
+        for filter_chain in listener.filter_chains.values() {
+            let MainFilter::Http(http_connection_manager) = &filter_chain.terminal_filter else {
+                continue;
+            };
+            match &http_connection_manager.route_specifier {
+                RouteSpecifier::Rds(rds_specifier) => {
+                    let id = rds_specifier.route_config_name.to_string();
+                    let config_source = Some(&rds_specifier.config_source);
+                    streams.subscribe(config_source, id, TypeUrl::RouteConfiguration).await;
+                },
+                RouteSpecifier::ScopedRoutes(scoped_routes) => match &scoped_routes.scopes {
+                    ScopesSpecifier::Scopes(scopes) => {
+                        for scope in scopes.iter().filter(|scope| !scope.on_demand) {
+                            let id = scope.route_configuration_name.to_string();
+                            let config_source = Some(&scoped_routes.rds_config_source);
+                            streams.subscribe(config_source, id, TypeUrl::RouteConfiguration).await;
+                        }
+                    },
+                    ScopesSpecifier::ScopedRds(config_source) => {
+                        streams.discover_all(config_source, TypeUrl::ScopedRouteConfiguration);
+                        srds_rds_config_sources.push(scoped_routes.rds_config_source.clone());
+                    },
+                },
+                RouteSpecifier::RouteConfig(_) => {},
+            }
+        }
+        for (config_source, name) in self.scoped_routes.listener_updated(id, srds_rds_config_sources) {
+            streams.subscribe(Some(&config_source), name, TypeUrl::RouteConfiguration).await;
+        }
+    }
+
+    /// Sends the route configurations whose discovered virtual hosts changed to the listeners.
+    async fn send_discovered_virtual_hosts(&mut self) {
+        for (id, route) in self.virtual_hosts.take_changed() {
+            let change = RouteConfigurationChange::Added((id, route));
+            let _ = send_change_to_runtimes(&self.route_senders, change).await;
+        }
+    }
+
+    /// Subscribes to a virtual host a request asked for, if its route configuration discovers virtual hosts, or
+    /// unsubscribes from it once the requests timed out waiting for it.
+    async fn request_virtual_host(&mut self, ask: Ask<VirtualHostRequest>, streams: &mut XdsStreams) {
+        let (Ask::Subscribe(VirtualHostRequest { route_config_name, alias })
+        | Ask::Unsubscribe(VirtualHostRequest { route_config_name, alias })) = &ask;
+        match (self.virtual_hosts.config_source(route_config_name).cloned(), &ask) {
+            // a file holds every virtual host there is
+            (Some(config_source), _)
+                if matches!(config_source.config_source_specifier, ConfigSourceSpecifier::PathConfigSource(_)) =>
+            {
+                orion_lib::on_demand::virtual_host_not_found(alias);
+            },
+            (Some(config_source), Ask::Subscribe(_)) => {
+                debug!("Asking for virtual host {alias} on demand");
+                streams.subscribe(Some(&config_source), alias.clone(), TypeUrl::VirtualHost).await;
+            },
+            (Some(config_source), Ask::Unsubscribe(_)) => {
+                debug!("Giving up on virtual host {alias} asked for on demand");
+                streams.unsubscribe(&config_source, alias.clone(), TypeUrl::VirtualHost).await;
+            },
+            (None, _) => orion_lib::on_demand::virtual_host_not_found(alias),
+        }
+    }
+
+    /// Subscribes to a cluster a request or connection asked for, on the stream of its on-demand CDS config source,
+    /// or unsubscribes from it once they timed out waiting for it.
+    async fn request_cluster(ask: Ask<ClusterRequest>, streams: &mut XdsStreams) {
+        match ask {
+            Ask::Subscribe(ClusterRequest { name, config_source }) => {
+                debug!("Asking for cluster {name} on demand");
+                streams.subscribe(Some(&config_source), name, TypeUrl::Cluster).await;
+            },
+            Ask::Unsubscribe(ClusterRequest { name, config_source }) => {
+                debug!("Giving up on cluster {name} asked for on demand");
+                streams.unsubscribe(&config_source, name, TypeUrl::Cluster).await;
+            },
+        }
+    }
+
+    /// Subscribes to the route configuration of a scope loaded on demand a request asked for, on the stream of the RDS
+    /// config source of its scopes, or unsubscribes from it once the requests timed out waiting for it.
+    async fn request_route_configuration(ask: Ask<RouteConfigurationRequest>, streams: &mut XdsStreams) {
+        match ask {
+            Ask::Subscribe(RouteConfigurationRequest { name, config_source }) => {
+                debug!("Asking for route configuration {name} on demand");
+                streams.subscribe(Some(&config_source), name, TypeUrl::RouteConfiguration).await;
+            },
+            Ask::Unsubscribe(RouteConfigurationRequest { name, config_source }) => {
+                debug!("Giving up on route configuration {name} asked for on demand");
+                streams.unsubscribe(&config_source, name, TypeUrl::RouteConfiguration).await;
+            },
+        }
+    }
+
+    async fn add_listener(&mut self, id: &str, factory: ListenerFactory, listener: Listener) {
+        let change = ListenerConfigurationChange::Added(Box::new((factory, listener.clone())));
+        let _ = send_change_to_runtimes(&self.listeners_senders, change).await;
+        // update access logs configuration...
+        self.access_log_listener_update(id, &listener).await;
+
+        // update tracer configuration...
+        #[cfg(feature = "tracing")]
+        self.tracer_listener_update(id, &listener);
+    }
+
+    /// Starts using a listener done warming, with the secrets there are by now.
+    async fn add_warm_listener(&mut self, id: &str, listener: Listener) {
+        let factory =
+            ListenerFactory::try_from(ConversionContext::new((listener.clone(), &*self.secret_manager.read())));
+        match factory {
+            Ok(factory) => self.add_listener(id, factory, listener).await,
+            Err(err) => warn!("Failed to start warm listener {id}: {err}"),
+        }
+    }
+
+    async fn dependency_ready(&mut self, dependency: &Dependency) {
+        for (id, listener) in self.warming.dependency_ready(dependency) {
+            debug!("Listener {id} is warm");
+            self.add_warm_listener(&id, listener).await;
+        }
+    }
+
+    /// Starts using a resource without what it was waiting for, as Envoy does.
+    async fn process_warming_timeout(&mut self, timeout: WarmingTimeout) {
+        match timeout {
+            WarmingTimeout::Cluster(id, epoch) => {
+                if let Some(cluster) = self.warming.take_timed_out_cluster(&id, epoch) {
+                    warn!("No endpoints received for cluster {id} in time, using it without");
+                    if let Err(err) = self.add_cluster(cluster).await {
+                        warn!("Failed to add cluster {id}: {err}");
+                    }
+                }
+            },
+            WarmingTimeout::Listener(id, epoch) => {
+                if let Some(listener) = self.warming.take_timed_out_listener(&id, epoch) {
+                    let missing = self.warming.missing_dependencies(&listener, &self.secret_manager.read());
+                    warn!("Listener {id} still waits for {missing:?}, using it without");
+                    self.add_warm_listener(&id, listener).await;
+                }
+            },
         }
     }
 
@@ -366,6 +694,7 @@ impl XdsConfigurationHandler {
 
     async fn add_cluster(&mut self, cluster: PartialClusterType) -> Result<()> {
         let cluster_config = orion_lib::clusters::add_cluster(cluster)?;
+        orion_lib::on_demand::cluster_received(cluster_config.get_name());
         self.health_manager.restart_cluster(cluster_config).await;
         Ok(())
     }
@@ -379,6 +708,214 @@ impl XdsConfigurationHandler {
     }
 }
 
+/// The xDS streams resources are subscribed to on: the ADS one, one per type of resources served by each
+/// management server of its own, and one per file resources are read from. These are started when first needed,
+/// all their updates being handled together.
+struct XdsStreams {
+    node: Node,
+    authorities: Authorities,
+    ads: Option<DeltaDiscoverySubscriptionManager>,
+    streams: HashMap<(ApiConfigSource, TypeUrl), DeltaDiscoverySubscriptionManager>,
+    files: HashSet<(PathConfigSource, TypeUrl)>,
+    updates_tx: Sender<XdsUpdateEvent>,
+    /// Config sources of the endpoints of the clusters not discovering them over ADS.
+    endpoint_sources: HashMap<String, ConfigSource>,
+}
+
+impl XdsStreams {
+    fn new(node: Node, updates_tx: Sender<XdsUpdateEvent>, authorities: Authorities) -> Self {
+        Self {
+            node,
+            authorities,
+            ads: None,
+            streams: HashMap::new(),
+            files: HashSet::new(),
+            updates_tx,
+            endpoint_sources: HashMap::new(),
+        }
+    }
+
+    /// Subscribes to a resource on the stream of its config source, the one of its authority for an `xdstp://` name.
+    async fn subscribe(&mut self, config_source: Option<&ConfigSource>, id: String, type_url: TypeUrl) {
+        let authority_config_source = XdstpName::is_xdstp(&id)
+            .then(|| id.parse::<XdstpName>().ok())
+            .flatten()
+            .and_then(|name| self.authorities.config_source(&name.authority).cloned());
+        let config_source = authority_config_source.as_ref().or(config_source);
+        let subscription_manager = match config_source.map(|config_source| &config_source.config_source_specifier) {
+            None | Some(ConfigSourceSpecifier::ADS) => self.ads.as_ref(),
+            Some(ConfigSourceSpecifier::ApiConfigSource(source)) => self.stream(source, type_url),
+            Some(ConfigSourceSpecifier::PathConfigSource(source)) => {
+                // a file holds every resource of its type, there is nothing to subscribe to
+                self.watch(source, type_url);
+                return;
+            },
+        };
+        let Some(subscription_manager) = subscription_manager else {
+            warn!("No xDS stream to subscribe to {type_url} {id} on");
+            return;
+        };
+        let maybe_subscribed = subscription_manager.subscribe(id.clone(), type_url).await;
+        debug!("Updating subscription for {id} {type_url} {maybe_subscribed:?} ");
+    }
+
+    /// Unsubscribes from a resource on the stream of its config source, if there is one.
+    async fn unsubscribe(&mut self, config_source: &ConfigSource, id: String, type_url: TypeUrl) {
+        let subscription_manager = match &config_source.config_source_specifier {
+            ConfigSourceSpecifier::ADS => self.ads.as_ref(),
+            ConfigSourceSpecifier::ApiConfigSource(source) => self.streams.get(&(source.clone(), type_url)),
+            ConfigSourceSpecifier::PathConfigSource(_) => None,
+        };
+        if let Some(subscription_manager) = subscription_manager {
+            let maybe_unsubscribed = subscription_manager.unsubscribe(id.clone(), type_url).await;
+            debug!("Updating subscription for {id} {type_url} {maybe_unsubscribed:?} ");
+        }
+    }
+
+    /// Subscribes to the secrets referred to by name that are discovered, each from its own config source. The others
+    /// are static secrets of the bootstrap.
+    async fn subscribe_secrets(&mut self, secrets: impl IntoIterator<Item = &SdsConfig>) {
+        for SdsConfig { name, config_source } in secrets {
+            if let Some(config_source) = config_source {
+                self.subscribe(Some(config_source), name.to_string(), TypeUrl::Secret).await;
+            }
+        }
+    }
+
+    /// The config source of the authority of a resources locator.
+    fn locator_config_source(&self, locator: Option<&XdstpName>) -> Option<ConfigSource> {
+        locator.and_then(|locator| self.authorities.config_source(&locator.authority)).cloned()
+    }
+
+    /// The stream of a type of resources from a management server of its own, started if it isn't yet.
+    fn stream(&mut self, source: &ApiConfigSource, type_url: TypeUrl) -> Option<&DeltaDiscoverySubscriptionManager> {
+        self.stream_subscribed(source, type_url, &[])
+    }
+
+    /// The stream of a type of resources, started if it isn't yet with these resources subscribed to from the start
+    /// instead of every resource of the type.
+    fn stream_subscribed(
+        &mut self,
+        source: &ApiConfigSource,
+        type_url: TypeUrl,
+        subscribed: &[String],
+    ) -> Option<&DeltaDiscoverySubscriptionManager> {
+        let key = (source.clone(), type_url);
+        if !self.streams.contains_key(&key) {
+            let (mut worker, client, subscription_manager) =
+                start_typed_client_no_retry_loop(self.node.clone(), xds_channel(source), type_url, source, subscribed)
+                    .inspect_err(|e| warn!("Failed to start xDS client for {type_url}: {e}"))
+                    .ok()?;
+            tokio::spawn(async move {
+                let subscribe = worker.run().await;
+                info!("Worker exited {subscribe:?}");
+            });
+            self.forward_updates(client);
+            self.streams.insert(key.clone(), subscription_manager);
+        }
+        self.streams.get(&key)
+    }
+
+    /// Discovers every resource of a type from a config source, for the types that are subscribed to with a wildcard.
+    fn discover_all(&mut self, config_source: &ConfigSource, type_url: TypeUrl) {
+        match &config_source.config_source_specifier {
+            // the ADS stream discovers every type there is from the start
+            ConfigSourceSpecifier::ADS => {},
+            ConfigSourceSpecifier::ApiConfigSource(source) => {
+                self.stream(source, type_url);
+            },
+            ConfigSourceSpecifier::PathConfigSource(source) => self.watch(source, type_url),
+        }
+    }
+
+    /// Reads a type of resources from a file, and again each time a file is moved onto it, if not done yet.
+    fn watch(&mut self, source: &PathConfigSource, type_url: TypeUrl) {
+        if self.files.insert((source.clone(), type_url)) {
+            let (mut worker, client) = start_path_client(source.clone(), type_url);
+            tokio::spawn(async move {
+                let subscribe = worker.run().await;
+                info!("Worker exited {subscribe:?}");
+            });
+            self.forward_updates(client);
+        }
+    }
+
+    fn start_ads(
+        &mut self,
+        ads_config: &ApiConfigSource,
+        discovered_elsewhere: &[TypeUrl],
+        subscribed: &[(TypeUrl, String)],
+    ) {
+        if ads_config.grpc_cluster_specifiers.is_empty() {
+            info!("No xDS clusters configured");
+            return;
+        }
+        match start_aggregate_client_no_retry_loop(
+            self.node.clone(),
+            xds_channel(ads_config),
+            ads_config,
+            discovered_elsewhere,
+            subscribed,
+        ) {
+            Ok((mut worker, client, subscription_manager)) => {
+                tokio::spawn(async move {
+                    let subscribe = worker.run().await;
+                    info!("Worker exited {subscribe:?}");
+                });
+                self.forward_updates(client);
+                self.ads = Some(subscription_manager);
+            },
+            Err(e) => warn!("Failed to start ADS client: {e}"),
+        }
+    }
+
+    fn forward_updates(&self, mut client: DeltaDiscoveryClient) {
+        let updates_tx = self.updates_tx.clone();
+        tokio::spawn(async move {
+            while let Some(xds_update) = client.recv().await {
+                if updates_tx.send(xds_update).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Where the resources of each authority of the `xdstp://` names are discovered from, as set in the bootstrap.
+#[derive(Debug, Clone, Default)]
+pub struct Authorities {
+    config_sources: Vec<AuthorityConfigSource>,
+    default_config_source: Option<ConfigSource>,
+}
+
+impl From<&Bootstrap> for Authorities {
+    fn from(bootstrap: &Bootstrap) -> Self {
+        Self {
+            config_sources: bootstrap.config_sources.clone(),
+            default_config_source: bootstrap.default_config_source.clone(),
+        }
+    }
+}
+
+impl Authorities {
+    fn config_source(&self, authority: &str) -> Option<&ConfigSource> {
+        AuthorityConfigSource::find(&self.config_sources, self.default_config_source.as_ref(), authority)
+    }
+}
+
+/// The init target of the first resources of a type the proxy discovers.
+fn fetch_target(type_url: TypeUrl) -> String {
+    format!("{type_url} discovery")
+}
+
+/// Channel to a management server, failing over from each of its clusters to the next one, the headers it is
+/// configured with being sent along each stream.
+fn xds_channel(source: &ApiConfigSource) -> FailoverGrpcService {
+    let initial_metadata =
+        source.initial_metadata.iter().map(|header| (header.key.clone(), header.value.clone())).collect::<HeaderMap>();
+    FailoverGrpcService::new(source.grpc_cluster_specifiers.clone(), initial_metadata)
+}
+
 pub async fn send_change_to_runtimes<Change: Clone>(channels: &[Sender<Change>], change: Change) -> Result<()> {
     let futures: Vec<_> = channels
         .iter()
@@ -0,0 +1,178 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! The endpoints assigned by the HDS server of the bootstrap, health checked with the checkers of the clusters.
+
+use std::{collections::BTreeMap, net::SocketAddr};
+
+use orion_configuration::config::{
+    bootstrap::Node,
+    cluster::{Cluster as ClusterConfig, HealthStatus},
+    ApiConfigSource, GenericError,
+};
+use orion_data_plane_api::envoy_data_plane_api::envoy::{
+    config::{
+        cluster::v3::{
+            cluster::{ClusterDiscoveryType, DiscoveryType},
+            Cluster as EnvoyCluster,
+        },
+        core::v3::{
+            address::Address as EnvoyAddressKind, socket_address::PortSpecifier, Address as EnvoyAddress,
+            HealthStatus as EnvoyHealthStatus, Locality as EnvoyLocality, SocketAddress,
+        },
+        endpoint::v3::{lb_endpoint::HostIdentifier, ClusterLoadAssignment, Endpoint, LbEndpoint, LocalityLbEndpoints},
+    },
+    service::health::v3::{
+        health_discovery_service_client::HealthDiscoveryServiceClient, ClusterEndpointsHealth, ClusterHealthCheck,
+        EndpointHealth, LocalityEndpoints, LocalityEndpointsHealth,
+    },
+};
+use orion_lib::{clusters::status::ClusterStatus, DelegatedHealthChecks};
+use orion_xds::xds::health_discovery::{self, HealthDiscoveryClient};
+use tracing::warn;
+
+use super::xds_channel;
+
+struct ClusterHealthChecks(DelegatedHealthChecks);
+
+impl health_discovery::DelegatedHealthChecks for ClusterHealthChecks {
+    fn assign(&mut self, clusters: Vec<ClusterHealthCheck>) {
+        let clusters = clusters
+            .into_iter()
+            .filter_map(|cluster| {
+                let name = cluster.cluster_name.clone();
+                cluster_config(cluster)
+                    .inspect_err(|err| warn!("Could not health check the endpoints of cluster {name}: {err}"))
+                    .ok()
+            })
+            .collect();
+        self.0.assign(clusters);
+    }
+
+    fn report(&mut self) -> Vec<ClusterEndpointsHealth> {
+        self.0.report().into_iter().map(cluster_endpoints_health).collect()
+    }
+}
+
+/// Health checks the endpoints assigned by the HDS server for as long as the proxy runs.
+pub async fn health_check_for_hds_server(node: Node, hds_config: ApiConfigSource) {
+    let client = HealthDiscoveryServiceClient::new(xds_channel(&hds_config));
+    let mut client = HealthDiscoveryClient::new(node, client, ClusterHealthChecks(DelegatedHealthChecks::new()));
+    if let Err(err) = client.run().await {
+        warn!("delegated health checking stopped: {err}");
+    }
+}
+
+/// The endpoints assigned as a static cluster, checked as configured by the server.
+fn cluster_config(cluster: ClusterHealthCheck) -> Result<ClusterConfig, GenericError> {
+    let ClusterHealthCheck {
+        cluster_name,
+        health_checks,
+        locality_endpoints,
+        transport_socket_matches,
+        upstream_bind_config,
+    } = cluster;
+    let endpoints = locality_endpoints
+        .into_iter()
+        .map(|LocalityEndpoints { locality, endpoints }| LocalityLbEndpoints {
+            locality,
+            lb_endpoints: endpoints
+                .into_iter()
+                .map(|endpoint| LbEndpoint {
+                    host_identifier: Some(HostIdentifier::Endpoint(endpoint)),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        })
+        .collect();
+    ClusterConfig::try_from(EnvoyCluster {
+        name: cluster_name.clone(),
+        cluster_discovery_type: Some(ClusterDiscoveryType::Type(DiscoveryType::Static.into())),
+        load_assignment: Some(ClusterLoadAssignment { cluster_name, endpoints, ..Default::default() }),
+        health_checks,
+        transport_socket_matches,
+        upstream_bind_config,
+        ..Default::default()
+    })
+}
+
+fn cluster_endpoints_health(cluster: ClusterStatus) -> ClusterEndpointsHealth {
+    let mut localities = BTreeMap::<_, Vec<EndpointHealth>>::new();
+    for host in cluster.hosts {
+        let Ok(address) = host.address.parse::<SocketAddr>() else {
+            warn!("Could not report the health of endpoint {} of cluster {}", host.address, cluster.name);
+            continue;
+        };
+        let health_status = match host.health {
+            HealthStatus::Healthy => EnvoyHealthStatus::Healthy,
+            HealthStatus::Unhealthy => EnvoyHealthStatus::Unhealthy,
+        };
+        localities.entry(host.locality).or_default().push(EndpointHealth {
+            endpoint: Some(Endpoint {
+                address: Some(EnvoyAddress {
+                    address: Some(EnvoyAddressKind::SocketAddress(SocketAddress {
+                        address: address.ip().to_string(),
+                        port_specifier: Some(PortSpecifier::PortValue(address.port().into())),
+                        ..Default::default()
+                    })),
+                }),
+                ..Default::default()
+            }),
+            health_status: health_status.into(),
+        });
+    }
+    let locality_endpoints_health = localities
+        .into_iter()
+        .map(|(locality, endpoints_health)| LocalityEndpointsHealth {
+            locality: locality.map(EnvoyLocality::from),
+            endpoints_health,
+        })
+        .collect();
+    ClusterEndpointsHealth { cluster_name: cluster.name.to_owned(), locality_endpoints_health }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_data_plane_api::decode::from_yaml;
+
+    #[test]
+    fn endpoints_assigned_are_checked_as_a_static_cluster() {
+        const ASSIGNED: &str = r#"
+cluster_name: shared_backends
+health_checks:
+- timeout: 1s
+  interval: 5s
+  healthy_threshold: 1
+  unhealthy_threshold: 1
+  tcp_health_check: {}
+locality_endpoints:
+- locality:
+    zone: eu-1
+  endpoints:
+  - address:
+      socket_address:
+        address: 10.0.0.1
+        port_value: 8080
+"#;
+        let assigned: ClusterHealthCheck = from_yaml(ASSIGNED).unwrap();
+        let cluster = cluster_config(assigned).unwrap();
+        assert_eq!(cluster.name, "shared_backends");
+        assert!(cluster.health_check.is_some());
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! The load of the upstream clusters, reported to the LRS server of the bootstrap.
+
+use orion_configuration::config::{bootstrap::Node, ApiConfigSource};
+use orion_data_plane_api::envoy_data_plane_api::{
+    envoy::{
+        config::{
+            core::v3::Locality as EnvoyLocality,
+            endpoint::v3::{ClusterStats, UpstreamLocalityStats},
+        },
+        service::load_stats::v3::load_reporting_service_client::LoadReportingServiceClient,
+    },
+    google::protobuf::Duration as ProtobufDuration,
+};
+use orion_lib::clusters::load_report::{ClusterLoad, LoadReporter, LocalityLoad};
+use orion_xds::xds::load_stats::{LoadReportingClient, LoadSource, ReportedClusters};
+use tracing::warn;
+
+use super::xds_channel;
+
+struct ClusterLoadSource(LoadReporter);
+
+impl LoadSource for ClusterLoadSource {
+    fn reset(&mut self) {
+        self.0 = LoadReporter::new();
+    }
+
+    fn report(&mut self, clusters: &ReportedClusters) -> Vec<ClusterStats> {
+        self.0.report(clusters.names()).into_iter().map(cluster_stats).collect()
+    }
+}
+
+/// Reports the load of the clusters to the LRS server for as long as the proxy runs.
+pub async fn report_load(node: Node, load_stats_config: ApiConfigSource) {
+    let client = LoadReportingServiceClient::new(xds_channel(&load_stats_config));
+    let mut client = LoadReportingClient::new(node, client, ClusterLoadSource(LoadReporter::new()));
+    if let Err(err) = client.run().await {
+        warn!("load reporting stopped: {err}");
+    }
+}
+
+fn cluster_stats(load: ClusterLoad) -> ClusterStats {
+    let ClusterLoad { cluster_name, localities, dropped_requests, interval } = load;
+    ClusterStats {
+        cluster_name: cluster_name.to_owned(),
+        upstream_locality_stats: localities.into_iter().map(upstream_locality_stats).collect(),
+        total_dropped_requests: dropped_requests,
+        load_report_interval: Some(ProtobufDuration {
+            seconds: i64::try_from(interval.as_secs()).unwrap_or(i64::MAX),
+            nanos: i32::try_from(interval.subsec_nanos()).unwrap_or_default(),
+        }),
+        ..Default::default()
+    }
+}
+
+fn upstream_locality_stats(load: LocalityLoad) -> UpstreamLocalityStats {
+    let LocalityLoad {
+        locality,
+        issued_requests,
+        successful_requests,
+        error_requests,
+        requests_in_progress,
+        active_connections,
+        new_connections,
+        failed_connections,
+    } = load;
+    UpstreamLocalityStats {
+        locality: locality.map(EnvoyLocality::from),
+        total_issued_requests: issued_requests,
+        total_successful_requests: successful_requests,
+        total_error_requests: error_requests,
+        total_requests_in_progress: requests_in_progress,
+        total_active_connections: active_connections,
+        total_new_connections: new_connections,
+        total_fail_connections: failed_connections,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_configuration::config::cluster::Locality;
+    use std::time::Duration;
+
+    #[test]
+    fn load_reported_by_locality() {
+        let load = ClusterLoad {
+            cluster_name: "backends",
+            localities: vec![LocalityLoad {
+                locality: Some(Locality { region: "eu".into(), zone: "eu-1".into(), ..Default::default() }),
+                issued_requests: 10,
+                successful_requests: 8,
+                error_requests: 2,
+                ..Default::default()
+            }],
+            dropped_requests: 3,
+            interval: Duration::from_millis(1500),
+        };
+        let stats = cluster_stats(load);
+        assert_eq!(stats.cluster_name, "backends");
+        assert_eq!(stats.total_dropped_requests, 3);
+        assert_eq!(stats.load_report_interval, Some(ProtobufDuration { seconds: 1, nanos: 500_000_000 }));
+        let locality = &stats.upstream_locality_stats[0];
+        assert_eq!(locality.locality.as_ref().map(|locality| locality.zone.as_str()), Some("eu-1"));
+        assert_eq!(
+            (locality.total_issued_requests, locality.total_successful_requests, locality.total_error_requests),
+            (10, 8, 2)
+        );
+    }
+}
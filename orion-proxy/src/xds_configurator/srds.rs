@@ -0,0 +1,114 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use std::collections::{BTreeMap, HashMap};
+
+use orion_configuration::config::{
+    network_filters::http_connection_manager::scoped_routes::ScopedRouteConfiguration, ConfigSource,
+};
+
+/// The scopes discovered over SRDS, and the RDS config sources of the connection managers routing with them. The
+/// route configurations of the scopes not loaded on demand are subscribed to on each of these config sources, as
+/// scopes and listeners come.
+#[derive(Default)]
+pub(super) struct ScopedRoutes {
+    /// The RDS config sources of the connection managers discovering their scopes, by listener.
+    rds_config_sources: HashMap<String, Vec<ConfigSource>>,
+    /// The route configurations of the scopes not loaded on demand, by scope name.
+    route_configs: BTreeMap<String, String>,
+}
+
+impl ScopedRoutes {
+    /// Keeps the RDS config sources of the connection managers of a listener discovering their scopes, returning the
+    /// route configurations to subscribe to on them.
+    pub(super) fn listener_updated(
+        &mut self,
+        id: &str,
+        rds_config_sources: Vec<ConfigSource>,
+    ) -> Vec<(ConfigSource, String)> {
+        if rds_config_sources.is_empty() {
+            self.listener_removed(id);
+            return Vec::new();
+        }
+        let subscriptions = rds_config_sources
+            .iter()
+            .flat_map(|config_source| self.route_configs.values().map(|name| (config_source.clone(), name.clone())))
+            .collect();
+        self.rds_config_sources.insert(id.to_owned(), rds_config_sources);
+        subscriptions
+    }
+
+    pub(super) fn listener_removed(&mut self, id: &str) {
+        self.rds_config_sources.remove(id);
+    }
+
+    /// Keeps the route configuration of a scope not loaded on demand, returning the subscriptions to it.
+    pub(super) fn scope_updated(&mut self, id: &str, scope: &ScopedRouteConfiguration) -> Vec<(ConfigSource, String)> {
+        if scope.on_demand {
+            self.scope_removed(id);
+            return Vec::new();
+        }
+        let name = scope.route_configuration_name.to_string();
+        self.route_configs.insert(id.to_owned(), name.clone());
+        let mut config_sources: Vec<&ConfigSource> = Vec::new();
+        for config_source in self.rds_config_sources.values().flatten() {
+            if !config_sources.contains(&config_source) {
+                config_sources.push(config_source);
+            }
+        }
+        config_sources.into_iter().map(|config_source| (config_source.clone(), name.clone())).collect()
+    }
+
+    pub(super) fn scope_removed(&mut self, id: &str) {
+        self.route_configs.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(on_demand: bool) -> ScopedRouteConfiguration {
+        serde_json::from_value(serde_json::json!({
+            "name": "acme",
+            "on_demand": on_demand,
+            "route_configuration_name": "acme_routes",
+            "key": ["acme"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn route_configurations_of_scopes_are_subscribed_to_on_every_rds_config_source() {
+        let ads: ConfigSource =
+            serde_json::from_value(serde_json::json!({ "config_source_specifier": "ADS" })).unwrap();
+        let mut scoped_routes = ScopedRoutes::default();
+        assert!(scoped_routes.scope_updated("acme", &scope(false)).is_empty(), "no listener routes with it yet");
+        assert_eq!(
+            scoped_routes.listener_updated("listener", vec![ads.clone()]),
+            [(ads.clone(), "acme_routes".into())]
+        );
+        assert_eq!(scoped_routes.listener_updated("other_listener", vec![ads.clone()]).len(), 1);
+        assert_eq!(scoped_routes.scope_updated("acme", &scope(false)), [(ads.clone(), "acme_routes".into())]);
+
+        assert!(scoped_routes.scope_updated("acme", &scope(true)).is_empty(), "loaded on demand");
+        assert!(scoped_routes.listener_updated("listener", vec![ads]).is_empty());
+        scoped_routes.listener_removed("listener");
+        scoped_routes.listener_removed("other_listener");
+        assert!(scoped_routes.scope_updated("acme", &scope(false)).is_empty());
+    }
+}
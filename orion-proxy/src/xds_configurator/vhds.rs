@@ -0,0 +1,155 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use orion_configuration::config::{
+    network_filters::http_connection_manager::{RouteConfiguration, VirtualHost},
+    ConfigSource,
+};
+use orion_lib::Result;
+
+struct DiscoveredRoute {
+    /// The route configuration as received over RDS.
+    route: RouteConfiguration,
+    /// The virtual hosts discovered over VHDS, by their `<route configuration name>/<virtual host name>` name.
+    virtual_hosts: BTreeMap<String, VirtualHost>,
+}
+
+impl DiscoveredRoute {
+    /// The route configuration with the virtual hosts discovered added to its own.
+    fn merged(&self) -> RouteConfiguration {
+        let mut route = self.route.clone();
+        route.virtual_hosts.extend(self.virtual_hosts.values().cloned());
+        route
+    }
+}
+
+/// The route configurations discovering virtual hosts over VHDS, with the virtual hosts discovered so far. Virtual
+/// hosts come one at a time, so the route configurations they change are only sent to the listeners once per
+/// batch of updates.
+#[derive(Default)]
+pub(super) struct VirtualHosts {
+    routes: HashMap<String, DiscoveredRoute>,
+    /// The route configurations whose virtual hosts changed since they were last sent to the listeners.
+    changed: BTreeSet<String>,
+}
+
+impl VirtualHosts {
+    /// Keeps a route configuration received over RDS, returning it with the virtual hosts already discovered for it.
+    pub(super) fn route_received(&mut self, id: &str, route: RouteConfiguration) -> RouteConfiguration {
+        if route.vhds.is_none() {
+            self.route_removed(id);
+            return route;
+        }
+        self.changed.remove(id);
+        let discovered = self
+            .routes
+            .entry(id.to_owned())
+            .and_modify(|discovered| discovered.route = route.clone())
+            .or_insert_with(|| DiscoveredRoute { route, virtual_hosts: BTreeMap::new() });
+        discovered.merged()
+    }
+
+    pub(super) fn route_removed(&mut self, id: &str) {
+        self.routes.remove(id);
+        self.changed.remove(id);
+    }
+
+    /// Where the virtual hosts of a route configuration are discovered from, if they are.
+    pub(super) fn config_source(&self, route_config_name: &str) -> Option<&ConfigSource> {
+        let discovered = self.routes.get(route_config_name)?;
+        discovered.route.vhds.as_ref().map(|vhds| &vhds.config_source)
+    }
+
+    /// The route configuration a virtual host belongs to, by the prefix of its name.
+    fn route_of(&self, id: &str) -> Option<String> {
+        self.routes
+            .keys()
+            .filter(|route_config_name| {
+                id.strip_prefix(route_config_name.as_str()).is_some_and(|name| name.starts_with('/'))
+            })
+            .max_by_key(|route_config_name| route_config_name.len())
+            .cloned()
+    }
+
+    pub(super) fn update(&mut self, id: &str, virtual_host: VirtualHost) -> Result<()> {
+        let route_config_name =
+            self.route_of(id).ok_or_else(|| format!("no route configuration discovers virtual host {id}"))?;
+        if let Some(discovered) = self.routes.get_mut(&route_config_name) {
+            discovered.virtual_hosts.insert(id.to_owned(), virtual_host);
+            self.changed.insert(route_config_name);
+        }
+        Ok(())
+    }
+
+    pub(super) fn remove(&mut self, id: &str) {
+        for (route_config_name, discovered) in &mut self.routes {
+            if discovered.virtual_hosts.remove(id).is_some() {
+                self.changed.insert(route_config_name.clone());
+            }
+        }
+    }
+
+    /// The route configurations whose virtual hosts changed, with the virtual hosts discovered added to their own.
+    pub(super) fn take_changed(&mut self) -> Vec<(String, RouteConfiguration)> {
+        std::mem::take(&mut self.changed)
+            .into_iter()
+            .filter_map(|id| {
+                let route = self.routes.get(&id)?.merged();
+                Some((id, route))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn virtual_host(name: &str) -> VirtualHost {
+        serde_json::from_value(serde_json::json!({ "name": name, "domains": [name], "routes": [] })).unwrap()
+    }
+
+    #[test]
+    fn discovered_virtual_hosts_are_added_to_their_route_configuration() {
+        let route: RouteConfiguration = serde_json::from_value(serde_json::json!({
+            "name": "routes",
+            "virtual_hosts": [virtual_host("static.example.com")],
+            "vhds": { "config_source": { "config_source_specifier": "ADS" } },
+        }))
+        .unwrap();
+        let mut virtual_hosts = VirtualHosts::default();
+        assert_eq!(virtual_hosts.route_received("routes", route.clone()), route);
+        assert!(virtual_hosts.config_source("routes").is_some());
+
+        virtual_hosts.update("routes/a.example.com", virtual_host("a.example.com")).unwrap();
+        virtual_hosts.update("routes/b.example.com", virtual_host("b.example.com")).unwrap();
+        assert!(virtual_hosts.update("other/c.example.com", virtual_host("c.example.com")).is_err());
+        let changed = virtual_hosts.take_changed();
+        assert_eq!(changed.len(), 1, "sent once for both virtual hosts");
+        let names: Vec<_> = changed[0].1.virtual_hosts.iter().map(|vh| vh.name.as_str()).collect();
+        assert_eq!(names, ["static.example.com", "a.example.com", "b.example.com"]);
+        assert!(virtual_hosts.take_changed().is_empty());
+
+        virtual_hosts.remove("routes/a.example.com");
+        let names: Vec<_> =
+            virtual_hosts.route_received("routes", route).virtual_hosts.into_iter().map(|vh| vh.name).collect();
+        assert_eq!(names, ["static.example.com", "b.example.com"]);
+        assert!(virtual_hosts.take_changed().is_empty(), "sent along with the route configuration");
+    }
+}
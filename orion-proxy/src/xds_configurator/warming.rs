@@ -0,0 +1,406 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use orion_configuration::config::{
+    cluster::TlsSecret,
+    listener::MainFilter,
+    network_filters::http_connection_manager::{http_filters::HttpFilterType, RouteSpecifier},
+    transport::{CommonTlsValidationContext, SdsConfig, Secrets, UpstreamTransportSocketConfig},
+    Cluster, ConfigSource, Listener,
+};
+use orion_lib::{extension_configs, lifecycle, PartialClusterType, SecretManager};
+use tokio::sync::mpsc::Sender;
+
+/// What a listener waits for before being used.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) enum Dependency {
+    Route(String),
+    Secret(String),
+    ExtensionConfig(String),
+}
+
+/// Sent once a resource has been warming for as long as what it waits for is waited for.
+#[derive(Debug)]
+pub(super) enum WarmingTimeout {
+    Cluster(String, u64),
+    Listener(String, u64),
+}
+
+struct WarmingCluster {
+    cluster: PartialClusterType,
+    epoch: u64,
+}
+
+struct WarmingListener {
+    listener: Listener,
+    missing: HashSet<Dependency>,
+    epoch: u64,
+}
+
+/// A filter of a listener whose configuration is discovered over ECDS.
+pub(super) struct DiscoveredFilter<'a> {
+    pub(super) name: &'a str,
+    pub(super) config_source: &'a ConfigSource,
+    /// Whether the listener waits for the configuration, rather than starting with the default one.
+    pub(super) needs_warming: bool,
+}
+
+/// The clusters waiting for their endpoints and the listeners waiting for their routes, secrets and filter
+/// configurations, the version of each already in use, if any, being used in the meantime. Those received before the
+/// server is initialized are init targets of their own.
+pub(super) struct Warming {
+    clusters: HashMap<String, WarmingCluster>,
+    listeners: HashMap<String, WarmingListener>,
+    /// The clusters whose endpoints were received, which don't wait for them again when updated.
+    clusters_with_endpoints: HashSet<String>,
+    routes: HashSet<String>,
+    /// Tells the timeouts of a resource from those of the versions of it it replaced.
+    epoch: u64,
+    timeouts_tx: Sender<WarmingTimeout>,
+}
+
+impl Warming {
+    pub(super) fn new(timeouts_tx: Sender<WarmingTimeout>) -> Self {
+        Self {
+            clusters: HashMap::new(),
+            listeners: HashMap::new(),
+            clusters_with_endpoints: HashSet::new(),
+            routes: HashSet::new(),
+            epoch: 0,
+            timeouts_tx,
+        }
+    }
+
+    pub(super) fn needs_endpoints(&self, id: &str) -> bool {
+        !self.clusters_with_endpoints.contains(id)
+    }
+
+    pub(super) fn endpoints_received(&mut self, id: &str) {
+        self.clusters_with_endpoints.insert(id.to_owned());
+    }
+
+    pub(super) fn endpoints_removed(&mut self, id: &str) {
+        self.clusters_with_endpoints.remove(id);
+    }
+
+    pub(super) fn route_received(&mut self, id: &str) {
+        self.routes.insert(id.to_owned());
+    }
+
+    pub(super) fn route_removed(&mut self, id: &str) {
+        self.routes.remove(id);
+    }
+
+    pub(super) fn warm_cluster(&mut self, id: String, cluster: PartialClusterType, timeout: Option<Duration>) {
+        self.epoch += 1;
+        lifecycle::add_init_target(&cluster_target(&id));
+        self.spawn_timeout(timeout, WarmingTimeout::Cluster(id.clone(), self.epoch));
+        self.clusters.insert(id, WarmingCluster { cluster, epoch: self.epoch });
+    }
+
+    /// The cluster warming under this name, done warming.
+    pub(super) fn take_cluster(&mut self, id: &str) -> Option<PartialClusterType> {
+        let WarmingCluster { cluster, .. } = self.clusters.remove(id)?;
+        lifecycle::init_target_ready(&cluster_target(id));
+        Some(cluster)
+    }
+
+    pub(super) fn take_timed_out_cluster(&mut self, id: &str, epoch: u64) -> Option<PartialClusterType> {
+        self.clusters.get(id).is_some_and(|warming| warming.epoch == epoch).then(|| self.take_cluster(id)).flatten()
+    }
+
+    /// The routes, secrets and filter configurations the listener refers to that are not there yet.
+    pub(super) fn missing_dependencies(
+        &self,
+        listener: &Listener,
+        secret_manager: &SecretManager,
+    ) -> HashSet<Dependency> {
+        let mut missing: HashSet<_> = discovered_filters(listener)
+            .into_iter()
+            .filter(|filter| filter.needs_warming && !extension_configs::contains(filter.name))
+            .map(|filter| Dependency::ExtensionConfig(filter.name.to_owned()))
+            .collect();
+        for filter_chain in listener.filter_chains.values() {
+            if let MainFilter::Http(http_connection_manager) = &filter_chain.terminal_filter {
+                if let RouteSpecifier::Rds(rds_specifier) = &http_connection_manager.route_specifier {
+                    if !self.routes.contains(rds_specifier.route_config_name.as_str()) {
+                        missing.insert(Dependency::Route(rds_specifier.route_config_name.to_string()));
+                    }
+                }
+            }
+            let Some(tls_config) = &filter_chain.tls_config else {
+                continue;
+            };
+            let common_tls_context = &tls_config.common_tls_context;
+            if let Secrets::SdsConfig(secrets) = &common_tls_context.secrets {
+                for SdsConfig { name, .. } in secrets {
+                    if !matches!(secret_manager.get_certificate(name), Ok(Some(_))) {
+                        missing.insert(Dependency::Secret(name.to_string()));
+                    }
+                }
+            }
+            if let Some(CommonTlsValidationContext::SdsConfig(SdsConfig { name, .. })) =
+                &common_tls_context.validation_context
+            {
+                if !matches!(secret_manager.get_validation_context(name), Ok(Some(_))) {
+                    missing.insert(Dependency::Secret(name.to_string()));
+                }
+            }
+        }
+        missing
+    }
+
+    pub(super) fn warm_listener(&mut self, id: String, listener: Listener, missing: HashSet<Dependency>) {
+        self.epoch += 1;
+        lifecycle::add_init_target(&listener_target(&id));
+        self.spawn_timeout(listener_warming_timeout(&listener), WarmingTimeout::Listener(id.clone(), self.epoch));
+        self.listeners.insert(id, WarmingListener { listener, missing, epoch: self.epoch });
+    }
+
+    /// Forgets the listener warming under this name, as when it is removed or replaced with one that is warm.
+    pub(super) fn cancel_listener(&mut self, id: &str) {
+        if self.listeners.remove(id).is_some() {
+            lifecycle::init_target_ready(&listener_target(id));
+        }
+    }
+
+    pub(super) fn take_timed_out_listener(&mut self, id: &str, epoch: u64) -> Option<Listener> {
+        if self.listeners.get(id).is_none_or(|warming| warming.epoch != epoch) {
+            return None;
+        }
+        let WarmingListener { listener, .. } = self.listeners.remove(id)?;
+        lifecycle::init_target_ready(&listener_target(id));
+        Some(listener)
+    }
+
+    /// The listeners that were only waiting for this dependency, done warming.
+    pub(super) fn dependency_ready(&mut self, dependency: &Dependency) -> Vec<(String, Listener)> {
+        let warm: Vec<_> = self
+            .listeners
+            .iter_mut()
+            .filter_map(|(id, warming)| {
+                (warming.missing.remove(dependency) && warming.missing.is_empty()).then(|| id.clone())
+            })
+            .collect();
+        warm.into_iter()
+            .filter_map(|id| {
+                let WarmingListener { listener, .. } = self.listeners.remove(&id)?;
+                lifecycle::init_target_ready(&listener_target(&id));
+                Some((id, listener))
+            })
+            .collect()
+    }
+
+    fn spawn_timeout(&self, timeout: Option<Duration>, warming_timeout: WarmingTimeout) {
+        let Some(timeout) = timeout else {
+            return;
+        };
+        let timeouts_tx = self.timeouts_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            let _ = timeouts_tx.send(warming_timeout).await;
+        });
+    }
+}
+
+/// How long the endpoints of a cluster discovered from this source are waited for.
+pub(super) fn cluster_warming_timeout(eds_config_source: Option<&ConfigSource>) -> Option<Duration> {
+    eds_config_source.map_or(Some(ConfigSource::DEFAULT_INITIAL_FETCH_TIMEOUT), ConfigSource::fetch_timeout)
+}
+
+/// The filters of a listener whose configuration is discovered: its discovered listener filters and the discovered
+/// HTTP filters of its HTTP connection managers.
+pub(super) fn discovered_filters(listener: &Listener) -> Vec<DiscoveredFilter<'_>> {
+    let listener_filters = listener.discovered_listener_filters.iter().map(|filter| DiscoveredFilter {
+        name: &filter.name,
+        config_source: &filter.source.config_source,
+        needs_warming: filter.source.needs_warming(),
+    });
+    let http_filters = listener
+        .filter_chains
+        .values()
+        .filter_map(|filter_chain| match &filter_chain.terminal_filter {
+            MainFilter::Http(http_connection_manager) => Some(&http_connection_manager.http_filters),
+            MainFilter::Tcp(_) => None,
+        })
+        .flatten()
+        .filter_map(|filter| match &filter.filter {
+            HttpFilterType::ConfigDiscovery(source) => Some(DiscoveredFilter {
+                name: &filter.name,
+                config_source: &source.config_source,
+                needs_warming: source.needs_warming(),
+            }),
+            _ => None,
+        });
+    listener_filters.chain(http_filters).collect()
+}
+
+/// The secrets the TLS contexts of a listener's filter chains refer to by name.
+pub(super) fn listener_secrets(listener: &Listener) -> Vec<&SdsConfig> {
+    listener
+        .filter_chains
+        .values()
+        .filter_map(|filter_chain| filter_chain.tls_config.as_ref())
+        .flat_map(|tls_config| {
+            let common_tls_context = &tls_config.common_tls_context;
+            let secrets = match &common_tls_context.secrets {
+                Secrets::SdsConfig(secrets) => secrets.as_slice(),
+                Secrets::Certificates(_) => &[],
+            };
+            secrets.iter().chain(validation_context_secret(common_tls_context.validation_context.as_ref()))
+        })
+        .collect()
+}
+
+/// The secrets the TLS context of a cluster's transport socket refers to by name.
+pub(super) fn cluster_secrets(cluster: &Cluster) -> Vec<&SdsConfig> {
+    let tls_config = match &cluster.transport_socket {
+        Some(UpstreamTransportSocketConfig::Tls(tls_config)) => Some(tls_config),
+        Some(UpstreamTransportSocketConfig::ProxyProtocol(proxy_protocol)) => proxy_protocol.inner_tls_config.as_ref(),
+        Some(UpstreamTransportSocketConfig::RawBuffer) | None => None,
+    };
+    tls_config
+        .into_iter()
+        .flat_map(|tls_config| {
+            let secret = match &tls_config.secret {
+                Some(TlsSecret::SdsConfig(secret)) => Some(secret),
+                Some(TlsSecret::Certificate(_)) | None => None,
+            };
+            secret.into_iter().chain(validation_context_secret(tls_config.validation_context.as_ref()))
+        })
+        .collect()
+}
+
+fn validation_context_secret(validation_context: Option<&CommonTlsValidationContext>) -> Option<&SdsConfig> {
+    match validation_context {
+        Some(CommonTlsValidationContext::SdsConfig(secret)) => Some(secret),
+        _ => None,
+    }
+}
+
+/// How long the routes, secrets and filter configurations of a listener are waited for: as long as its slowest route
+/// or filter config source allows, and the default initial fetch timeout if it has nothing to discover.
+fn listener_warming_timeout(listener: &Listener) -> Option<Duration> {
+    let route_timeouts =
+        listener.filter_chains.values().filter_map(|filter_chain| match &filter_chain.terminal_filter {
+            MainFilter::Http(http_connection_manager) => match &http_connection_manager.route_specifier {
+                RouteSpecifier::Rds(rds_specifier) => Some(rds_specifier.config_source.fetch_timeout()),
+                // the scopes and their route configurations come and go with the requests routed in the meantime
+                RouteSpecifier::ScopedRoutes(_) | RouteSpecifier::RouteConfig(_) => None,
+            },
+            MainFilter::Tcp(_) => None,
+        });
+    let filter_timeouts = discovered_filters(listener)
+        .into_iter()
+        .filter(|filter| filter.needs_warming)
+        .map(|filter| filter.config_source.fetch_timeout());
+    let mut timeouts = route_timeouts.chain(filter_timeouts).peekable();
+    if timeouts.peek().is_none() {
+        return Some(ConfigSource::DEFAULT_INITIAL_FETCH_TIMEOUT);
+    }
+    timeouts.try_fold(Duration::ZERO, |longest, timeout| timeout.map(|timeout| longest.max(timeout)))
+}
+
+fn cluster_target(id: &str) -> String {
+    format!("cluster {id}")
+}
+
+fn listener_target(id: &str) -> String {
+    format!("listener {id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_configuration::config::{Bootstrap, ConfigSourceSpecifier};
+
+    const BOOTSTRAP: &str = r#"
+static_resources:
+  listeners:
+  - name: listener
+    address:
+      socket_address: { address: 127.0.0.1, port_value: 8443 }
+    filter_chains:
+    - filters:
+      - name: tcp
+        typed_config:
+          "@type": type.googleapis.com/envoy.extensions.filters.network.tcp_proxy.v3.TcpProxy
+          stat_prefix: tcp
+          cluster: cluster
+      transport_socket:
+        name: envoy.transport_sockets.tls
+        typed_config:
+          "@type": type.googleapis.com/envoy.extensions.transport_sockets.tls.v3.DownstreamTlsContext
+          common_tls_context:
+            tls_certificate_sds_secret_configs:
+            - name: static_certificate
+            - name: file_certificate
+              sds_config:
+                path_config_source:
+                  path: /etc/orion/secrets.yaml
+  clusters:
+  - name: cluster
+    type: STATIC
+    load_assignment:
+      cluster_name: cluster
+      endpoints:
+      - lb_endpoints:
+        - endpoint:
+            address:
+              socket_address: { address: 127.0.0.1, port_value: 8080 }
+    transport_socket:
+      name: envoy.transport_sockets.tls
+      typed_config:
+        "@type": type.googleapis.com/envoy.extensions.transport_sockets.tls.v3.UpstreamTlsContext
+        sni: upstream
+        common_tls_context:
+          validation_context_sds_secret_config:
+            name: ads_validation_context
+            sds_config:
+              ads: {}
+"#;
+
+    #[test]
+    fn secrets_are_discovered_from_their_own_config_source() {
+        let bootstrap = Bootstrap::deserialize_from_envoy(BOOTSTRAP.as_bytes()).unwrap();
+        let static_resources = &bootstrap.static_resources;
+
+        let listener_secrets = listener_secrets(&static_resources.listeners[0]);
+        assert_eq!(
+            listener_secrets.iter().map(|secret| secret.name.as_str()).collect::<Vec<_>>(),
+            ["static_certificate", "file_certificate"]
+        );
+        assert_eq!(listener_secrets[0].config_source, None, "a static secret of the bootstrap");
+        assert!(matches!(
+            listener_secrets[1].config_source.as_deref().map(|source| &source.config_source_specifier),
+            Some(ConfigSourceSpecifier::PathConfigSource(source)) if source.path.as_os_str() == "/etc/orion/secrets.yaml"
+        ));
+
+        let cluster_secrets = cluster_secrets(&static_resources.clusters[0]);
+        assert_eq!(cluster_secrets.len(), 1);
+        assert_eq!(cluster_secrets[0].name, "ads_validation_context");
+        assert!(matches!(
+            cluster_secrets[0].config_source.as_deref().map(|source| &source.config_source_specifier),
+            Some(ConfigSourceSpecifier::ADS)
+        ));
+    }
+}
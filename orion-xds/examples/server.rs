@@ -17,15 +17,21 @@
 
 #![allow(clippy::expect_used)]
 
-use orion_data_plane_api::envoy_data_plane_api::envoy::extensions::filters::network::http_connection_manager::v3::http_connection_manager::CodecType;
+use orion_data_plane_api::envoy_data_plane_api::envoy::{
+    config::core::v3::Node,
+    extensions::filters::network::http_connection_manager::v3::http_connection_manager::CodecType,
+};
 use orion_xds::xds::{
+    model::TypeUrl,
     resources,
-    server::{ServerAction, start_aggregate_server},
+    server::{start_aggregate_server, Snapshot, SnapshotCache},
 };
-use std::{future::IntoFuture, time::Duration};
+use std::{future::IntoFuture, sync::Arc, time::Duration};
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+const ALL_NODES: &str = "all";
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::registry()
@@ -33,19 +39,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let (delta_resource_tx, delta_resources_rx) = tokio::sync::mpsc::channel(100);
-    let (_stream_resource_tx, stream_resources_rx) = tokio::sync::mpsc::channel(100);
+    // every proxy is served the same resources
+    let cache = Arc::new(SnapshotCache::with_node_hash(|_: &Node| ALL_NODES.to_owned()));
     let addr = "127.0.0.1:50051".parse()?;
 
+    let server_cache = Arc::clone(&cache);
     let grpc_server = tokio::spawn(async move {
         info!("Server started");
-        let res = start_aggregate_server(addr, delta_resources_rx, stream_resources_rx).await;
+        let res = start_aggregate_server(addr, server_cache).await;
         info!("Server stopped {res:?}");
     });
     tokio::time::sleep(std::time::Duration::from_secs(10)).await;
 
     let _xds_resource_producer = tokio::spawn(async move {
-        loop {
+        for version in 0.. {
             let id = uuid::Uuid::new_v4().to_string();
             let listener_id = format!("Listener-{id}");
             let cluster_id = format!("Cluster-{id}");
@@ -59,9 +66,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             info!("Adding cluster {cluster_id}");
             let cluster_resource = resources::create_cluster_resource(&cluster);
 
-            if delta_resource_tx.send(ServerAction::Add(cluster_resource.clone())).await.is_err() {
-                break;
-            }
+            let snapshot = Snapshot::new().with_resources(TypeUrl::Cluster, format!("{version}.1"), [cluster_resource]);
+            cache.set_snapshot(ALL_NODES, snapshot.clone());
             tokio::time::sleep(Duration::from_secs(5)).await;
             let listener = resources::create_listener(
                 &listener_id,
@@ -72,15 +78,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             );
             let listener_resource = resources::create_listener_resource(&listener);
             info!("Adding listener {listener_resource:?}");
-            if delta_resource_tx.send(ServerAction::Add(listener_resource)).await.is_err() {
-                break;
-            }
+            let snapshot = snapshot.with_resources(TypeUrl::Listener, format!("{version}.2"), [listener_resource]);
+            cache.set_snapshot(ALL_NODES, snapshot.clone());
             tokio::time::sleep(Duration::from_secs(15)).await;
 
             info!("Removing cluster {cluster_id}");
-            if delta_resource_tx.send(ServerAction::Remove(cluster_resource)).await.is_err() {
-                break;
-            }
+            let snapshot = snapshot.with_resources(TypeUrl::Cluster, format!("{version}.3"), []);
+            cache.set_snapshot(ALL_NODES, snapshot.clone());
             tokio::time::sleep(Duration::from_secs(5)).await;
             let listener = resources::create_listener(
                 &listener_id,
@@ -91,9 +95,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             );
             let listener_resource = resources::create_listener_resource(&listener);
             info!("Removing listener {listener_resource:?}");
-            if delta_resource_tx.send(ServerAction::Remove(listener_resource)).await.is_err() {
-                break;
-            }
+            cache.set_snapshot(ALL_NODES, snapshot.with_resources(TypeUrl::Listener, format!("{version}.4"), []));
         }
     });
 
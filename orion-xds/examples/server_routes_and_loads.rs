@@ -17,15 +17,19 @@
 
 #![allow(clippy::expect_used)]
 
-use std::{future::IntoFuture, time::Duration};
+use std::{future::IntoFuture, sync::Arc, time::Duration};
 
+use orion_data_plane_api::envoy_data_plane_api::envoy::config::core::v3::Node;
 use orion_xds::xds::{
+    model::TypeUrl,
     resources,
-    server::{start_aggregate_server, ServerAction},
+    server::{start_aggregate_server, Snapshot, SnapshotCache},
 };
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+const ALL_NODES: &str = "all";
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::registry()
@@ -33,13 +37,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let (delta_resource_tx, delta_resources_rx) = tokio::sync::mpsc::channel(100);
-    let (_stream_resource_tx, stream_resources_rx) = tokio::sync::mpsc::channel(100);
+    // every proxy is served the same resources
+    let cache = Arc::new(SnapshotCache::with_node_hash(|_: &Node| ALL_NODES.to_owned()));
     let addr = "127.0.0.1:50051".parse()?;
 
+    let server_cache = Arc::clone(&cache);
     let grpc_server = tokio::spawn(async move {
         info!("Server started");
-        let res = start_aggregate_server(addr, delta_resources_rx, stream_resources_rx).await;
+        let res = start_aggregate_server(addr, server_cache).await;
         info!("Server stopped {res:?}");
     });
     tokio::time::sleep(std::time::Duration::from_secs(10)).await;
@@ -57,9 +62,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("Adding Cluster Load Assignment for cluster {cluster_id}");
         let load_assigment_resource = resources::create_load_assignment_resource(&cluster_id, &cla);
 
-        if delta_resource_tx.send(ServerAction::Add(load_assigment_resource.clone())).await.is_err() {
-            return;
-        }
+        let snapshot = Snapshot::new().with_resources(TypeUrl::ClusterLoadAssignment, "1", [load_assigment_resource]);
+        cache.set_snapshot(ALL_NODES, snapshot.clone());
         tokio::time::sleep(Duration::from_secs(5)).await;
 
         info!("Adding Route configuration  {route_id}");
@@ -68,22 +72,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let route_configuration_resource =
             resources::create_route_configuration_resource(&route_id, &route_configuration);
 
-        if delta_resource_tx.send(ServerAction::Add(route_configuration_resource.clone())).await.is_err() {
-            return;
-        }
+        let snapshot = snapshot.with_resources(TypeUrl::RouteConfiguration, "1", [route_configuration_resource]);
+        cache.set_snapshot(ALL_NODES, snapshot.clone());
 
         tokio::time::sleep(Duration::from_secs(15)).await;
 
         info!("Removing cluster load assignment {cluster_id}");
-        if delta_resource_tx.send(ServerAction::Remove(load_assigment_resource)).await.is_err() {
-            return;
-        }
+        let snapshot = snapshot.with_resources(TypeUrl::ClusterLoadAssignment, "2", []);
+        cache.set_snapshot(ALL_NODES, snapshot.clone());
         tokio::time::sleep(Duration::from_secs(5)).await;
 
         info!("Removing route configuration {route_id}");
-        if delta_resource_tx.send(ServerAction::Remove(route_configuration_resource)).await.is_err() {
-            return;
-        }
+        cache.set_snapshot(ALL_NODES, snapshot.with_resources(TypeUrl::RouteConfiguration, "2", []));
         tokio::time::sleep(Duration::from_secs(5)).await;
     });
 
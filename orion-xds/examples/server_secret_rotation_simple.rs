@@ -15,16 +15,22 @@
 //
 //
 
-use std::future::IntoFuture;
+use std::{future::IntoFuture, sync::Arc};
 
 use orion_data_plane_api::envoy_data_plane_api::envoy::{
-    config::core::v3::{data_source::Specifier, DataSource},
+    config::core::v3::{data_source::Specifier, DataSource, Node},
     extensions::transport_sockets::tls::v3::{secret, CertificateValidationContext},
 };
-use orion_xds::xds::{resources, server::start_aggregate_server};
+use orion_xds::xds::{
+    model::TypeUrl,
+    resources,
+    server::{start_aggregate_server, Snapshot, SnapshotCache},
+};
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+const ALL_NODES: &str = "all";
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::registry()
@@ -32,13 +38,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let (_, delta_resources_rx) = tokio::sync::mpsc::channel(100);
-    let (_stream_resource_tx, stream_resources_rx) = tokio::sync::mpsc::channel(100);
+    // every proxy is served the same resources
+    let cache = Arc::new(SnapshotCache::with_node_hash(|_: &Node| ALL_NODES.to_owned()));
     let addr = "127.0.0.1:50051".parse()?;
 
+    let server_cache = Arc::clone(&cache);
     let grpc_server = tokio::spawn(async move {
         info!("Server started");
-        let res = start_aggregate_server(addr, delta_resources_rx, stream_resources_rx).await;
+        let res = start_aggregate_server(addr, server_cache).await;
         info!("Server stopped {res:?}");
     });
     tokio::time::sleep(std::time::Duration::from_secs(10)).await;
@@ -65,7 +72,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let secret_type = secret::Type::ValidationContext(validation_context);
         let secret = resources::create_secret(secret_id, secret_type);
         info!("Adding upstream secret {secret_id}");
-        let _secret_resource = resources::create_secret_resource(secret_id, &secret);
+        let secret_resource = resources::create_secret_resource(secret_id, &secret);
+        cache.set_snapshot(ALL_NODES, Snapshot::new().with_resources(TypeUrl::Secret, "1", [secret_resource]));
     };
     let _xds_resource_producer = tokio::spawn(var_name);
 
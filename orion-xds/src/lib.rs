@@ -15,17 +15,42 @@
 //
 //
 
+pub mod watcher;
 pub mod xds;
 
 pub use crate::xds::model::XdsError;
 use crate::xds::{
-    bindings::AggregatedDiscoveryType,
-    client::{DeltaDiscoveryClient, DiscoveryClientBuilder, RETRY_INTERVAL},
+    bindings::{
+        AggregatedDiscoveryType, ClusterDiscoveryType, EndpointDiscoveryType, ExtensionConfigDiscoveryType,
+        ListenerDiscoveryType, RouteDiscoveryType, ScopedRouteDiscoveryType, SecretsDiscoveryType,
+        VirtualHostDiscoveryType,
+    },
+    client::{
+        build_path_client, DeltaDiscoveryClient, DiscoveryClientBuilder, PathClientBackgroundWorker, MAX_BACKOFF,
+        RETRY_INTERVAL,
+    },
+    model::{ResourceId, TypeUrl},
 };
 use http::{Request, Response};
-use orion_configuration::config::bootstrap::Node;
+use orion_configuration::config::{
+    bootstrap::{ApiType, Node},
+    ApiConfigSource, PathConfigSource,
+};
 use orion_data_plane_api::envoy_data_plane_api::{
-    envoy::service::discovery::v3::aggregated_discovery_service_client::AggregatedDiscoveryServiceClient, tonic,
+    envoy::service::{
+        cluster::v3::cluster_discovery_service_client::ClusterDiscoveryServiceClient,
+        discovery::v3::aggregated_discovery_service_client::AggregatedDiscoveryServiceClient,
+        endpoint::v3::endpoint_discovery_service_client::EndpointDiscoveryServiceClient,
+        extension::v3::extension_config_discovery_service_client::ExtensionConfigDiscoveryServiceClient,
+        listener::v3::listener_discovery_service_client::ListenerDiscoveryServiceClient,
+        route::v3::{
+            route_discovery_service_client::RouteDiscoveryServiceClient,
+            scoped_routes_discovery_service_client::ScopedRoutesDiscoveryServiceClient,
+            virtual_host_discovery_service_client::VirtualHostDiscoveryServiceClient,
+        },
+        secret::v3::secret_discovery_service_client::SecretDiscoveryServiceClient,
+    },
+    tonic,
 };
 use tonic::{
     body::Body,
@@ -34,7 +59,7 @@ use tonic::{
 };
 use tower::Service;
 use tracing::info;
-use xds::client::{DeltaClientBackgroundWorker, DeltaDiscoverySubscriptionManager};
+use xds::client::{DeltaClientBackgroundWorker, DeltaDiscoverySubscriptionManager, DiscoveryClientBackgroundWorker};
 
 pub mod grpc_deps {
     pub use orion_data_plane_api::envoy_data_plane_api::{
@@ -71,11 +96,19 @@ pub async fn start_aggregate_client(
     DiscoveryClientBuilder::new(node, aggregated_discovery_service_client).build()
 }
 
+/// Starts an ADS client, leaving out the types of resources discovered from other management servers.
 pub fn start_aggregate_client_no_retry_loop<C>(
     node: Node,
     channel: C,
+    ads_config: &ApiConfigSource,
+    discovered_elsewhere: &[TypeUrl],
+    subscribed: &[(TypeUrl, ResourceId)],
 ) -> Result<
-    (DeltaClientBackgroundWorker<AggregatedDiscoveryType<C>>, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager),
+    (
+        DiscoveryClientBackgroundWorker<AggregatedDiscoveryType<C>>,
+        DeltaDiscoveryClient,
+        DeltaDiscoverySubscriptionManager,
+    ),
     XdsError,
 >
 where
@@ -85,5 +118,167 @@ where
     let underlying_client =
         AggregatedDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
     let aggregated_discovery_service_client = AggregatedDiscoveryType { underlying_client };
-    DiscoveryClientBuilder::new(node, aggregated_discovery_service_client).build()
+    discovered_elsewhere
+        .iter()
+        .fold(DiscoveryClientBuilder::new(node, aggregated_discovery_service_client), |builder, type_url| {
+            builder.without_resource_type(*type_url)
+        })
+        .subscribe_resource_names(subscribed)
+        .with_rate_limit_settings(ads_config.rate_limit_settings)
+        .with_max_backoff(ads_config.max_backoff.unwrap_or(MAX_BACKOFF))
+        .build_for(ads_config.api_type)
+}
+
+/// Background worker of a stream discovering a single type of resources.
+#[derive(Debug)]
+pub enum TypedDiscoveryClientBackgroundWorker<C>
+where
+    C: Service<Request<Body>, Response = Response<Body>, Error = TonicError> + Send,
+    C::Future: Send,
+{
+    Listeners(DiscoveryClientBackgroundWorker<ListenerDiscoveryType<C>>),
+    Clusters(DiscoveryClientBackgroundWorker<ClusterDiscoveryType<C>>),
+    Routes(DiscoveryClientBackgroundWorker<RouteDiscoveryType<C>>),
+    Endpoints(DiscoveryClientBackgroundWorker<EndpointDiscoveryType<C>>),
+    Secrets(DiscoveryClientBackgroundWorker<SecretsDiscoveryType<C>>),
+    ExtensionConfigs(DiscoveryClientBackgroundWorker<ExtensionConfigDiscoveryType<C>>),
+    VirtualHosts(DiscoveryClientBackgroundWorker<VirtualHostDiscoveryType<C>>),
+    ScopedRoutes(DiscoveryClientBackgroundWorker<ScopedRouteDiscoveryType<C>>),
+}
+
+impl<C> TypedDiscoveryClientBackgroundWorker<C>
+where
+    C: Service<Request<Body>, Response = Response<Body>, Error = TonicError> + Send,
+    C::Future: Send,
+{
+    pub async fn run(&mut self) -> Result<(), XdsError> {
+        match self {
+            Self::Listeners(worker) => worker.run().await,
+            Self::Clusters(worker) => worker.run().await,
+            Self::Routes(worker) => worker.run().await,
+            Self::Endpoints(worker) => worker.run().await,
+            Self::Secrets(worker) => worker.run().await,
+            Self::ExtensionConfigs(worker) => worker.run().await,
+            Self::VirtualHosts(worker) => worker.run().await,
+            Self::ScopedRoutes(worker) => worker.run().await,
+        }
+    }
+}
+
+/// Starts a client discovering a single type of resources, from a management server of its own.
+pub fn start_typed_client_no_retry_loop<C>(
+    node: Node,
+    channel: C,
+    type_url: TypeUrl,
+    config_source: &ApiConfigSource,
+    subscribed: &[ResourceId],
+) -> Result<(TypedDiscoveryClientBackgroundWorker<C>, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager), XdsError>
+where
+    C: Service<Request<Body>, Response = Response<Body>, Error = TonicError> + Send,
+    C::Future: Send,
+{
+    fn with_worker<W, C>(
+        (worker, client, subscription_manager): (W, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager),
+        variant: impl FnOnce(W) -> TypedDiscoveryClientBackgroundWorker<C>,
+    ) -> (TypedDiscoveryClientBackgroundWorker<C>, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager)
+    where
+        C: Service<Request<Body>, Response = Response<Body>, Error = TonicError> + Send,
+        C::Future: Send,
+    {
+        (variant(worker), client, subscription_manager)
+    }
+
+    let subscribed: Vec<_> = subscribed.iter().map(|resource_id| (type_url, resource_id.clone())).collect();
+    match type_url {
+        TypeUrl::Listener => {
+            let underlying_client =
+                ListenerDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
+            DiscoveryClientBuilder::new(node, ListenerDiscoveryType { underlying_client })
+                .subscribe_resource_names(&subscribed)
+                .with_rate_limit_settings(config_source.rate_limit_settings)
+                .with_max_backoff(config_source.max_backoff.unwrap_or(MAX_BACKOFF))
+                .build_for(config_source.api_type)
+                .map(|parts| with_worker(parts, TypedDiscoveryClientBackgroundWorker::Listeners))
+        },
+        TypeUrl::Cluster => {
+            let underlying_client =
+                ClusterDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
+            DiscoveryClientBuilder::new(node, ClusterDiscoveryType { underlying_client })
+                .subscribe_resource_names(&subscribed)
+                .with_rate_limit_settings(config_source.rate_limit_settings)
+                .with_max_backoff(config_source.max_backoff.unwrap_or(MAX_BACKOFF))
+                .build_for(config_source.api_type)
+                .map(|parts| with_worker(parts, TypedDiscoveryClientBackgroundWorker::Clusters))
+        },
+        TypeUrl::RouteConfiguration => {
+            let underlying_client =
+                RouteDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
+            DiscoveryClientBuilder::new(node, RouteDiscoveryType { underlying_client })
+                .subscribe_resource_names(&subscribed)
+                .with_rate_limit_settings(config_source.rate_limit_settings)
+                .with_max_backoff(config_source.max_backoff.unwrap_or(MAX_BACKOFF))
+                .build_for(config_source.api_type)
+                .map(|parts| with_worker(parts, TypedDiscoveryClientBackgroundWorker::Routes))
+        },
+        TypeUrl::ClusterLoadAssignment => {
+            let underlying_client =
+                EndpointDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
+            DiscoveryClientBuilder::new(node, EndpointDiscoveryType { underlying_client })
+                .subscribe_resource_names(&subscribed)
+                .with_rate_limit_settings(config_source.rate_limit_settings)
+                .with_max_backoff(config_source.max_backoff.unwrap_or(MAX_BACKOFF))
+                .build_for(config_source.api_type)
+                .map(|parts| with_worker(parts, TypedDiscoveryClientBackgroundWorker::Endpoints))
+        },
+        TypeUrl::Secret => {
+            let underlying_client =
+                SecretDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
+            DiscoveryClientBuilder::new(node, SecretsDiscoveryType { underlying_client })
+                .subscribe_resource_names(&subscribed)
+                .with_rate_limit_settings(config_source.rate_limit_settings)
+                .with_max_backoff(config_source.max_backoff.unwrap_or(MAX_BACKOFF))
+                .build_for(config_source.api_type)
+                .map(|parts| with_worker(parts, TypedDiscoveryClientBackgroundWorker::Secrets))
+        },
+        TypeUrl::TypedExtensionConfig => {
+            let underlying_client =
+                ExtensionConfigDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
+            DiscoveryClientBuilder::new(node, ExtensionConfigDiscoveryType { underlying_client })
+                .subscribe_resource_names(&subscribed)
+                .with_rate_limit_settings(config_source.rate_limit_settings)
+                .with_max_backoff(config_source.max_backoff.unwrap_or(MAX_BACKOFF))
+                .build_for(config_source.api_type)
+                .map(|parts| with_worker(parts, TypedDiscoveryClientBackgroundWorker::ExtensionConfigs))
+        },
+        TypeUrl::VirtualHost => {
+            let underlying_client =
+                VirtualHostDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
+            // VHDS only has the incremental flavour of the protocol
+            DiscoveryClientBuilder::new(node, VirtualHostDiscoveryType { underlying_client })
+                .subscribe_resource_names(&subscribed)
+                .with_rate_limit_settings(config_source.rate_limit_settings)
+                .with_max_backoff(config_source.max_backoff.unwrap_or(MAX_BACKOFF))
+                .build_for(ApiType::DeltaGrpc)
+                .map(|parts| with_worker(parts, TypedDiscoveryClientBackgroundWorker::VirtualHosts))
+        },
+        TypeUrl::ScopedRouteConfiguration => {
+            let underlying_client =
+                ScopedRoutesDiscoveryServiceClient::new(channel).max_decoding_message_size(DECODED_MESSAGE_SIZE);
+            DiscoveryClientBuilder::new(node, ScopedRouteDiscoveryType { underlying_client })
+                .subscribe_resource_names(&subscribed)
+                .with_rate_limit_settings(config_source.rate_limit_settings)
+                .with_max_backoff(config_source.max_backoff.unwrap_or(MAX_BACKOFF))
+                .build_for(config_source.api_type)
+                .map(|parts| with_worker(parts, TypedDiscoveryClientBackgroundWorker::ScopedRoutes))
+        },
+    }
+}
+
+/// Starts a client reading a single type of resources from a file, rather than from a management server.
+pub fn start_path_client(
+    config_source: PathConfigSource,
+    type_url: TypeUrl,
+) -> (PathClientBackgroundWorker, DeltaDiscoveryClient) {
+    info!("Reading {type_url} resources from {}", config_source.path.display());
+    build_path_client(config_source, type_url)
 }
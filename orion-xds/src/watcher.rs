@@ -0,0 +1,143 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Waits for files to be moved onto paths, the way Envoy notices the files it reads being replaced atomically.
+
+use notify::{
+    event::{ModifyKind, RenameMode},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+};
+use tokio::sync::mpsc;
+
+enum Watched {
+    File { directory: PathBuf, name: OsString },
+    Directory(PathBuf),
+}
+
+impl Watched {
+    fn is_moved_onto(&self, destination: &Path) -> bool {
+        match self {
+            Self::File { directory, name } => {
+                destination.parent() == Some(directory) && destination.file_name() == Some(name)
+            },
+            Self::Directory(directory) => destination.parent() == Some(directory),
+        }
+    }
+}
+
+/// Watches for files moved onto paths, or into directories, with the file system notifications of the platform.
+pub struct MoveWatcher {
+    watcher: RecommendedWatcher,
+    watched: Vec<Watched>,
+    events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+}
+
+impl std::fmt::Debug for MoveWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MoveWatcher").field("watched", &self.watched.len()).finish_non_exhaustive()
+    }
+}
+
+impl MoveWatcher {
+    pub fn new() -> io::Result<Self> {
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = events_tx.send(event);
+        })
+        .map_err(io::Error::other)?;
+        Ok(Self { watcher, watched: Vec::new(), events })
+    }
+
+    /// Watches for files moved onto `path`. Its directory is what is watched, since the file itself is replaced.
+    pub fn watch_file(&mut self, path: &Path) -> io::Result<()> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?
+            .to_owned();
+        let directory = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        self.watcher.watch(directory, RecursiveMode::NonRecursive).map_err(io::Error::other)?;
+        self.watched.push(Watched::File { directory: directory.to_owned(), name });
+        Ok(())
+    }
+
+    /// Watches for any file moved into `directory`.
+    pub fn watch_directory(&mut self, directory: &Path) -> io::Result<()> {
+        self.watcher.watch(directory, RecursiveMode::NonRecursive).map_err(io::Error::other)?;
+        self.watched.push(Watched::Directory(directory.to_owned()));
+        Ok(())
+    }
+
+    /// Waits for a file to be moved onto a path watched, or into a directory watched.
+    pub async fn moved(&mut self) -> io::Result<()> {
+        while let Some(event) = self.events.recv().await {
+            if self.is_watched_move(&event.map_err(io::Error::other)?) {
+                return Ok(());
+            }
+        }
+        Err(io::Error::other("file system notifications stopped"))
+    }
+
+    fn is_watched_move(&self, event: &Event) -> bool {
+        // a move reported with both of its ends was already reported with its destination alone
+        let EventKind::Modify(ModifyKind::Name(RenameMode::To | RenameMode::Any)) = event.kind else {
+            return false;
+        };
+        event.paths.iter().any(|destination| self.watched.iter().any(|watched| watched.is_moved_onto(destination)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn moved_within(watcher: &mut MoveWatcher, timeout: Duration) -> bool {
+        tokio::select! {
+            moved = watcher.moved() => moved.is_ok(),
+            () = tokio::time::sleep(timeout) => false,
+        }
+    }
+
+    #[tokio::test]
+    async fn only_moves_onto_the_paths_watched_are_noticed() {
+        let directory = std::env::temp_dir().join(format!("orion-xds-watcher-{}", std::process::id()));
+        let watched_directory = directory.join("watched");
+        std::fs::create_dir_all(&watched_directory).unwrap();
+        let mut watcher = MoveWatcher::new().unwrap();
+        watcher.watch_file(&directory.join("config.yaml")).unwrap();
+        watcher.watch_directory(&watched_directory).unwrap();
+
+        std::fs::write(directory.join("config.yaml"), "written in place").unwrap();
+        std::fs::write(directory.join("other.yaml"), "staged").unwrap();
+        std::fs::rename(directory.join("other.yaml"), directory.join("another.yaml")).unwrap();
+        assert!(!moved_within(&mut watcher, Duration::from_millis(200)).await);
+
+        std::fs::write(directory.join("config.yaml.new"), "staged").unwrap();
+        std::fs::rename(directory.join("config.yaml.new"), directory.join("config.yaml")).unwrap();
+        assert!(moved_within(&mut watcher, Duration::from_secs(5)).await);
+
+        std::fs::rename(directory.join("another.yaml"), watched_directory.join("another.yaml")).unwrap();
+        assert!(moved_within(&mut watcher, Duration::from_secs(5)).await);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
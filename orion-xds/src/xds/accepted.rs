@@ -0,0 +1,91 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! The xDS resources accepted by the proxy, as they were sent by the management server.
+//!
+//! They are kept so that the admin interface can dump them in their original Envoy shape.
+
+use super::model::{ResourceId, ResourceVersion, TypeUrl};
+use orion_data_plane_api::envoy_data_plane_api::google::protobuf::Any;
+use parking_lot::RwLock;
+use std::{collections::BTreeMap, sync::LazyLock, time::SystemTime};
+
+static ACCEPTED_RESOURCES: LazyLock<RwLock<BTreeMap<(TypeUrl, ResourceId), AcceptedResource>>> =
+    LazyLock::new(|| RwLock::new(BTreeMap::new()));
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptedResource {
+    pub type_url: TypeUrl,
+    pub name: ResourceId,
+    pub version_info: ResourceVersion,
+    /// When the resource was last accepted.
+    pub last_updated: SystemTime,
+    pub resource: Any,
+}
+
+/// Records a resource accepted by the proxy, replacing its previous version.
+pub fn insert(type_url: TypeUrl, name: ResourceId, version_info: ResourceVersion, resource: Any) {
+    let accepted =
+        AcceptedResource { type_url, name: name.clone(), version_info, last_updated: SystemTime::now(), resource };
+    ACCEPTED_RESOURCES.write().insert((type_url, name), accepted);
+}
+
+pub fn remove(type_url: TypeUrl, name: &str) {
+    ACCEPTED_RESOURCES.write().remove(&(type_url, name.to_owned()));
+}
+
+/// The accepted resources of the given type, ordered by name.
+pub fn accepted_resources(type_url: TypeUrl) -> Vec<AcceptedResource> {
+    ACCEPTED_RESOURCES
+        .read()
+        .range((type_url, ResourceId::new())..)
+        .take_while(|((resource_type, _), _)| *resource_type == type_url)
+        .map(|(_, resource)| resource.clone())
+        .collect()
+}
+
+pub fn accepted_resource(type_url: TypeUrl, name: &str) -> Option<AcceptedResource> {
+    ACCEPTED_RESOURCES.read().get(&(type_url, name.to_owned())).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resources_are_kept_per_type() {
+        let any = |value: &[u8]| Any { type_url: TypeUrl::Cluster.to_string(), value: value.to_vec() };
+        insert(TypeUrl::Cluster, "accepted_b".to_owned(), "1".to_owned(), any(b"b"));
+        insert(TypeUrl::Cluster, "accepted_a".to_owned(), "1".to_owned(), any(b"a"));
+        insert(TypeUrl::Secret, "accepted_a".to_owned(), "1".to_owned(), any(b"s"));
+        insert(TypeUrl::Cluster, "accepted_a".to_owned(), "2".to_owned(), any(b"a2"));
+
+        let clusters: Vec<_> = accepted_resources(TypeUrl::Cluster)
+            .into_iter()
+            .filter(|resource| resource.name.starts_with("accepted_"))
+            .map(|resource| (resource.name, resource.version_info))
+            .collect();
+        assert_eq!(
+            clusters,
+            vec![("accepted_a".to_owned(), "2".to_owned()), ("accepted_b".to_owned(), "1".to_owned())]
+        );
+
+        remove(TypeUrl::Cluster, "accepted_a");
+        assert!(accepted_resource(TypeUrl::Cluster, "accepted_a").is_none());
+        assert!(accepted_resource(TypeUrl::Secret, "accepted_a").is_some());
+    }
+}
@@ -28,8 +28,13 @@ use orion_data_plane_api::envoy_data_plane_api::{
             DeltaDiscoveryResponse, DiscoveryRequest, DiscoveryResponse,
         },
         endpoint::v3::endpoint_discovery_service_client::EndpointDiscoveryServiceClient,
+        extension::v3::extension_config_discovery_service_client::ExtensionConfigDiscoveryServiceClient,
         listener::v3::listener_discovery_service_client::ListenerDiscoveryServiceClient,
-        route::v3::route_discovery_service_client::RouteDiscoveryServiceClient,
+        route::v3::{
+            route_discovery_service_client::RouteDiscoveryServiceClient,
+            scoped_routes_discovery_service_client::ScopedRoutesDiscoveryServiceClient,
+            virtual_host_discovery_service_client::VirtualHostDiscoveryServiceClient,
+        },
         secret::v3::secret_discovery_service_client::SecretDiscoveryServiceClient,
     },
     tonic,
@@ -109,11 +114,16 @@ where
 
 #[derive(Debug)]
 /// Handle to CDS client
-pub struct ClusterDiscoveryType {
-    pub underlying_client: ClusterDiscoveryServiceClient<Channel>,
+pub struct ClusterDiscoveryType<C = Channel> {
+    pub underlying_client: ClusterDiscoveryServiceClient<C>,
 }
 
-impl TypedXdsBinding for ClusterDiscoveryType {
+impl<C> TypedXdsBinding for ClusterDiscoveryType<C>
+where
+    C: tower::Service<http::Request<tonic::body::Body>, Response = http::Response<tonic::body::Body>> + Send,
+    C::Error: Into<StdError>,
+    C::Future: Send,
+{
     fn type_url() -> Option<TypeUrl> {
         Some(TypeUrl::Cluster)
     }
@@ -135,11 +145,16 @@ impl TypedXdsBinding for ClusterDiscoveryType {
 
 /// Handle to LDS Client
 #[derive(Debug)]
-pub struct ListenerDiscoveryType {
-    underlying_client: ListenerDiscoveryServiceClient<Channel>,
+pub struct ListenerDiscoveryType<C = Channel> {
+    pub underlying_client: ListenerDiscoveryServiceClient<C>,
 }
 
-impl TypedXdsBinding for ListenerDiscoveryType {
+impl<C> TypedXdsBinding for ListenerDiscoveryType<C>
+where
+    C: tower::Service<http::Request<tonic::body::Body>, Response = http::Response<tonic::body::Body>> + Send,
+    C::Error: Into<StdError>,
+    C::Future: Send,
+{
     fn type_url() -> Option<TypeUrl> {
         Some(TypeUrl::Listener)
     }
@@ -160,11 +175,16 @@ impl TypedXdsBinding for ListenerDiscoveryType {
 
 /// Handle to RDS Client
 #[derive(Debug)]
-pub struct RouteDiscoveryType {
-    underlying_client: RouteDiscoveryServiceClient<Channel>,
+pub struct RouteDiscoveryType<C = Channel> {
+    pub underlying_client: RouteDiscoveryServiceClient<C>,
 }
 
-impl TypedXdsBinding for RouteDiscoveryType {
+impl<C> TypedXdsBinding for RouteDiscoveryType<C>
+where
+    C: tower::Service<http::Request<tonic::body::Body>, Response = http::Response<tonic::body::Body>> + Send,
+    C::Error: Into<StdError>,
+    C::Future: Send,
+{
     fn type_url() -> Option<TypeUrl> {
         Some(TypeUrl::RouteConfiguration)
     }
@@ -186,11 +206,16 @@ impl TypedXdsBinding for RouteDiscoveryType {
 
 /// Handle to EDS Client
 #[derive(Debug)]
-pub struct EndpointDiscoveryType {
-    underlying_client: EndpointDiscoveryServiceClient<Channel>,
+pub struct EndpointDiscoveryType<C = Channel> {
+    pub underlying_client: EndpointDiscoveryServiceClient<C>,
 }
 
-impl TypedXdsBinding for EndpointDiscoveryType {
+impl<C> TypedXdsBinding for EndpointDiscoveryType<C>
+where
+    C: tower::Service<http::Request<tonic::body::Body>, Response = http::Response<tonic::body::Body>> + Send,
+    C::Error: Into<StdError>,
+    C::Future: Send,
+{
     fn type_url() -> Option<TypeUrl> {
         Some(TypeUrl::ClusterLoadAssignment)
     }
@@ -212,11 +237,16 @@ impl TypedXdsBinding for EndpointDiscoveryType {
 
 /// Handle to SDS Client
 #[derive(Debug)]
-pub struct SecretsDiscoveryType {
-    underlying_client: SecretDiscoveryServiceClient<Channel>,
+pub struct SecretsDiscoveryType<C = Channel> {
+    pub underlying_client: SecretDiscoveryServiceClient<C>,
 }
 
-impl TypedXdsBinding for SecretsDiscoveryType {
+impl<C> TypedXdsBinding for SecretsDiscoveryType<C>
+where
+    C: tower::Service<http::Request<tonic::body::Body>, Response = http::Response<tonic::body::Body>> + Send,
+    C::Error: Into<StdError>,
+    C::Future: Send,
+{
     fn type_url() -> Option<TypeUrl> {
         Some(TypeUrl::Secret)
     }
@@ -235,3 +265,96 @@ impl TypedXdsBinding for SecretsDiscoveryType {
         Box::pin(self.underlying_client.stream_secrets(request))
     }
 }
+
+/// Handle to ECDS Client
+#[derive(Debug)]
+pub struct ExtensionConfigDiscoveryType<C = Channel> {
+    pub underlying_client: ExtensionConfigDiscoveryServiceClient<C>,
+}
+
+impl<C> TypedXdsBinding for ExtensionConfigDiscoveryType<C>
+where
+    C: tower::Service<http::Request<tonic::body::Body>, Response = http::Response<tonic::body::Body>> + Send,
+    C::Error: Into<StdError>,
+    C::Future: Send,
+{
+    fn type_url() -> Option<TypeUrl> {
+        Some(TypeUrl::TypedExtensionConfig)
+    }
+
+    fn delta_request(
+        &mut self,
+        request: impl Stream<Item = DeltaDiscoveryRequest> + Send + 'static,
+    ) -> DeltaDiscoveryResponseFuture<'_> {
+        Box::pin(self.underlying_client.delta_extension_configs(request))
+    }
+
+    fn stream_request(
+        &mut self,
+        request: impl Stream<Item = DiscoveryRequest> + Send + 'static,
+    ) -> DiscoveryResponseFuture<'_> {
+        Box::pin(self.underlying_client.stream_extension_configs(request))
+    }
+}
+
+/// Handle to VHDS Client
+#[derive(Debug)]
+pub struct VirtualHostDiscoveryType<C = Channel> {
+    pub underlying_client: VirtualHostDiscoveryServiceClient<C>,
+}
+
+impl<C> TypedXdsBinding for VirtualHostDiscoveryType<C>
+where
+    C: tower::Service<http::Request<tonic::body::Body>, Response = http::Response<tonic::body::Body>> + Send,
+    C::Error: Into<StdError>,
+    C::Future: Send,
+{
+    fn type_url() -> Option<TypeUrl> {
+        Some(TypeUrl::VirtualHost)
+    }
+
+    fn delta_request(
+        &mut self,
+        request: impl Stream<Item = DeltaDiscoveryRequest> + Send + 'static,
+    ) -> DeltaDiscoveryResponseFuture<'_> {
+        Box::pin(self.underlying_client.delta_virtual_hosts(request))
+    }
+
+    fn stream_request(
+        &mut self,
+        _request: impl Stream<Item = DiscoveryRequest> + Send + 'static,
+    ) -> DiscoveryResponseFuture<'_> {
+        Box::pin(async { Err(tonic::Status::unimplemented("virtual hosts are only discovered incrementally")) })
+    }
+}
+
+/// Handle to SRDS Client
+#[derive(Debug)]
+pub struct ScopedRouteDiscoveryType<C = Channel> {
+    pub underlying_client: ScopedRoutesDiscoveryServiceClient<C>,
+}
+
+impl<C> TypedXdsBinding for ScopedRouteDiscoveryType<C>
+where
+    C: tower::Service<http::Request<tonic::body::Body>, Response = http::Response<tonic::body::Body>> + Send,
+    C::Error: Into<StdError>,
+    C::Future: Send,
+{
+    fn type_url() -> Option<TypeUrl> {
+        Some(TypeUrl::ScopedRouteConfiguration)
+    }
+
+    fn delta_request(
+        &mut self,
+        request: impl Stream<Item = DeltaDiscoveryRequest> + Send + 'static,
+    ) -> DeltaDiscoveryResponseFuture<'_> {
+        Box::pin(self.underlying_client.delta_scoped_routes(request))
+    }
+
+    fn stream_request(
+        &mut self,
+        request: impl Stream<Item = DiscoveryRequest> + Send + 'static,
+    ) -> DiscoveryResponseFuture<'_> {
+        Box::pin(self.underlying_client.stream_scoped_routes(request))
+    }
+}
@@ -16,13 +16,17 @@
 //
 
 use super::{
-    bindings,
+    accepted, bindings, client_status,
     model::{RejectedConfig, ResourceId, ResourceVersion, TypeUrl, XdsError, XdsResourcePayload, XdsResourceUpdate},
     request::{DeltaDiscoveryRequestBuilder, StatusBuilder},
 };
 use core::result::Result::{Err, Ok};
 
-use orion_configuration::config::bootstrap::Node;
+use orion_configuration::config::{
+    bootstrap::{ApiType, Node},
+    xdstp::XdstpName,
+    PathConfigSource, RateLimitSettings,
+};
 use orion_data_plane_api::envoy_data_plane_api::{
     envoy::service::discovery::v3::{DeltaDiscoveryRequest, DeltaDiscoveryResponse},
     tonic,
@@ -37,6 +41,13 @@ use tokio::{
 };
 use tracing::{debug, info, warn};
 
+mod path;
+mod rate_limit;
+mod sotw;
+pub use path::PathClientBackgroundWorker;
+use rate_limit::RequestRateLimiter;
+pub use sotw::SotwClientBackgroundWorker;
+
 pub const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
 pub const MAX_BACKOFF: Duration = Duration::from_secs(20);
 pub const BACKOFF_INTERVAL: Duration = Duration::from_secs(2);
@@ -46,7 +57,10 @@ pub const ACK_TIMEOUT: Duration = Duration::from_secs(20);
 pub struct DiscoveryClientBuilder<C: bindings::TypedXdsBinding> {
     node: Node,
     client_binding: C,
+    resource_types: Vec<TypeUrl>,
     initial_subscriptions: HashMap<TypeUrl, HashSet<ResourceId>>,
+    rate_limit_settings: Option<RateLimitSettings>,
+    max_backoff: Duration,
     error: Option<String>,
 }
 
@@ -55,7 +69,36 @@ where
     C: bindings::TypedXdsBinding,
 {
     pub fn new(node: Node, client: C) -> DiscoveryClientBuilder<C> {
-        DiscoveryClientBuilder { node, client_binding: client, initial_subscriptions: HashMap::new(), error: None }
+        DiscoveryClientBuilder {
+            node,
+            client_binding: client,
+            resource_types: resource_types::<C>(),
+            initial_subscriptions: HashMap::new(),
+            rate_limit_settings: None,
+            max_backoff: MAX_BACKOFF,
+            error: None,
+        }
+    }
+
+    /// Leaves a type of resources out of an aggregated stream, for it to be discovered from elsewhere.
+    #[must_use]
+    pub fn without_resource_type(mut self, type_url: TypeUrl) -> Self {
+        self.resource_types.retain(|resource_type| *resource_type != type_url);
+        self
+    }
+
+    /// Limits the rate of the discovery requests sent to the management server.
+    #[must_use]
+    pub fn with_rate_limit_settings(mut self, rate_limit_settings: Option<RateLimitSettings>) -> Self {
+        self.rate_limit_settings = rate_limit_settings;
+        self
+    }
+
+    /// Caps how long to wait before opening a stream again after it failed.
+    #[must_use]
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
     }
 
     #[must_use]
@@ -68,7 +111,18 @@ where
         self
     }
 
-    fn subscribe_resource_name_by_typeurl(mut self, resource_id: ResourceId, type_url: TypeUrl) -> Self {
+    /// Subscribes to resources from the start, see [`Self::subscribe_resource_name_by_typeurl`].
+    #[must_use]
+    pub fn subscribe_resource_names(self, resources: &[(TypeUrl, ResourceId)]) -> Self {
+        resources.iter().fold(self, |builder, (type_url, resource_id)| {
+            builder.subscribe_resource_name_by_typeurl(resource_id.clone(), *type_url)
+        })
+    }
+
+    /// Subscribes to a resource from the start, a type any resource of which is subscribed to this way not being
+    /// subscribed to with a wildcard.
+    #[must_use]
+    pub fn subscribe_resource_name_by_typeurl(mut self, resource_id: ResourceId, type_url: TypeUrl) -> Self {
         let configured_type_url = C::type_url();
         if configured_type_url.is_none() || configured_type_url.is_some_and(|type_is_set| type_is_set == type_url) {
             self.initial_subscriptions.entry(type_url).or_default().insert(resource_id);
@@ -82,16 +136,70 @@ where
         self,
     ) -> Result<(DeltaClientBackgroundWorker<C>, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager), XdsError>
     {
+        let (parts, client, subscription_manager) = self.into_parts()?;
+        let WorkerParts {
+            node,
+            client_binding,
+            resource_types,
+            initial_subscriptions,
+            rate_limiter,
+            max_backoff,
+            subscriptions_rx,
+            resources_tx,
+        } = parts;
+        let worker = DeltaClientBackgroundWorker {
+            node,
+            client_binding,
+            resource_types,
+            initial_subscriptions,
+            rate_limiter,
+            max_backoff,
+            subscriptions_rx,
+            resources_tx,
+        };
+        Ok((worker, client, subscription_manager))
+    }
+
+    /// Builds a client speaking the state-of-the-world flavour of the protocol.
+    pub fn build_sotw(
+        self,
+    ) -> Result<(SotwClientBackgroundWorker<C>, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager), XdsError>
+    {
+        // virtual hosts are only ever discovered incrementally
+        let (parts, client, subscription_manager) = self.without_resource_type(TypeUrl::VirtualHost).into_parts()?;
+        Ok((SotwClientBackgroundWorker::new(parts), client, subscription_manager))
+    }
+
+    /// Builds a client speaking the flavour of the protocol configured in the bootstrap.
+    pub fn build_for(
+        self,
+        api_type: ApiType,
+    ) -> Result<(DiscoveryClientBackgroundWorker<C>, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager), XdsError>
+    {
+        match api_type {
+            ApiType::DeltaGrpc => self.build().map(|(worker, client, subscription_manager)| {
+                (DiscoveryClientBackgroundWorker::Delta(worker), client, subscription_manager)
+            }),
+            ApiType::Grpc => self.build_sotw().map(|(worker, client, subscription_manager)| {
+                (DiscoveryClientBackgroundWorker::StateOfTheWorld(worker), client, subscription_manager)
+            }),
+        }
+    }
+
+    fn into_parts(self) -> Result<(WorkerParts<C>, DeltaDiscoveryClient, DeltaDiscoverySubscriptionManager), XdsError> {
         if let Some(err) = self.error {
             Err(XdsError::BuilderFailed(err))
         } else {
             let (subscription_updates_tx, subscription_updates_rx) = mpsc::channel::<SubscriptionEvent>(100);
             let (resource_updates_tx, resource_updates_rx) = mpsc::channel::<XdsUpdateEvent>(100);
             Ok((
-                DeltaClientBackgroundWorker {
+                WorkerParts {
                     node: self.node,
                     client_binding: self.client_binding,
+                    resource_types: self.resource_types,
                     initial_subscriptions: self.initial_subscriptions,
+                    rate_limiter: self.rate_limit_settings.map(RequestRateLimiter::new),
+                    max_backoff: self.max_backoff,
                     subscriptions_rx: subscription_updates_rx,
                     resources_tx: resource_updates_tx,
                 },
@@ -102,6 +210,45 @@ where
     }
 }
 
+pub(crate) fn build_path_client(
+    config_source: PathConfigSource,
+    type_url: TypeUrl,
+) -> (PathClientBackgroundWorker, DeltaDiscoveryClient) {
+    let (resource_updates_tx, resource_updates_rx) = mpsc::channel::<XdsUpdateEvent>(100);
+    (
+        PathClientBackgroundWorker::new(config_source, type_url, resource_updates_tx),
+        DeltaDiscoveryClient { resources_rx: resource_updates_rx },
+    )
+}
+
+/// What a background worker is made of, whatever the flavour of the protocol.
+struct WorkerParts<C> {
+    node: Node,
+    client_binding: C,
+    resource_types: Vec<TypeUrl>,
+    initial_subscriptions: HashMap<TypeUrl, HashSet<ResourceId>>,
+    rate_limiter: Option<RequestRateLimiter>,
+    max_backoff: Duration,
+    subscriptions_rx: mpsc::Receiver<SubscriptionEvent>,
+    resources_tx: mpsc::Sender<XdsUpdateEvent>,
+}
+
+/// Background worker of either flavour of the protocol, both reporting their updates to the same client.
+#[derive(Debug)]
+pub enum DiscoveryClientBackgroundWorker<C: bindings::TypedXdsBinding> {
+    Delta(DeltaClientBackgroundWorker<C>),
+    StateOfTheWorld(SotwClientBackgroundWorker<C>),
+}
+
+impl<C: bindings::TypedXdsBinding> DiscoveryClientBackgroundWorker<C> {
+    pub async fn run(&mut self) -> Result<(), XdsError> {
+        match self {
+            Self::Delta(worker) => worker.run().await,
+            Self::StateOfTheWorld(worker) => worker.run().await,
+        }
+    }
+}
+
 /// Incremental Client that operates the delta version of the xDS protocol
 /// use to consume xDS configuration updates asynchronously, modify resource subscriptions
 
@@ -133,6 +280,8 @@ impl DeltaDiscoverySubscriptionManager {
 
 #[derive(Debug)]
 pub struct XdsUpdateEvent {
+    /// The type of the resources the response was for, which it may have none of.
+    pub type_url: TypeUrl,
     pub updates: Vec<XdsResourceUpdate>,
     pub ack_channel: oneshot::Sender<Vec<RejectedConfig>>,
 }
@@ -148,7 +297,10 @@ pub enum SubscriptionEvent {
 pub struct DeltaClientBackgroundWorker<C: bindings::TypedXdsBinding> {
     node: Node,
     client_binding: C,
+    resource_types: Vec<TypeUrl>,
     initial_subscriptions: HashMap<TypeUrl, HashSet<ResourceId>>,
+    rate_limiter: Option<RequestRateLimiter>,
+    max_backoff: Duration,
     subscriptions_rx: mpsc::Receiver<SubscriptionEvent>,
     resources_tx: mpsc::Sender<XdsUpdateEvent>,
 }
@@ -183,48 +335,77 @@ impl DiscoveryClientState {
     }
 }
 
+/// Waits before reconnecting after the stream ended, longer after each failure.
+pub(crate) async fn back_off(result: Result<(), XdsError>, backoff: &mut Duration, max_backoff: Duration) {
+    match result {
+        Err(ref e @ XdsError::GrpcStatus(ref status)) => {
+            let next_backoff = std::cmp::min(max_backoff, *backoff * 2);
+            let err_detail = e.to_string();
+            warn!("xDS client error: {err_detail:?}");
+            if status.code() == tonic::Code::Unknown
+                || status.code() == tonic::Code::Cancelled
+                || status.code() == tonic::Code::DeadlineExceeded
+                || status.code() == tonic::Code::Unavailable
+            {
+                warn!("xDS client terminated: {}, retrying in {:?}", err_detail, next_backoff);
+            } else {
+                warn!("xDS client interrupted: {}, retrying in {:?}", err_detail, next_backoff);
+            }
+            tokio::time::sleep(next_backoff).await;
+            *backoff = next_backoff;
+        },
+        Err(e) => {
+            let next_backoff = std::cmp::min(max_backoff, *backoff * 2);
+            let backoff_slowly = next_backoff + BACKOFF_INTERVAL;
+            warn!("xDS client error: {:?}, retrying in {:?}", e, backoff_slowly);
+            tokio::time::sleep(backoff_slowly).await;
+            *backoff = backoff_slowly;
+        },
+        Ok(()) => {
+            warn!("xDS client closed");
+        },
+    }
+}
+
+/// The types of resources discovered through a binding, all of them for ADS.
+fn resource_types<C: bindings::TypedXdsBinding>() -> Vec<TypeUrl> {
+    match C::type_url() {
+        Some(type_url) => vec![type_url],
+        _ => vec![
+            TypeUrl::Secret,
+            TypeUrl::Cluster,
+            TypeUrl::ClusterLoadAssignment,
+            TypeUrl::TypedExtensionConfig,
+            TypeUrl::Listener,
+            TypeUrl::RouteConfiguration,
+            TypeUrl::ScopedRouteConfiguration,
+            TypeUrl::VirtualHost,
+        ],
+    }
+}
+
 impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
     async fn persistently_connect(&mut self, state: &mut DiscoveryClientState) {
-        match self.continuously_discover_resources(state).await {
-            Err(ref e @ XdsError::GrpcStatus(ref status)) => {
-                let backoff = std::cmp::min(MAX_BACKOFF, state.backoff * 2);
-                let err_detail = e.to_string();
-                warn!("xDS client error: {err_detail:?}");
-                if status.code() == tonic::Code::Unknown
-                    || status.code() == tonic::Code::Cancelled
-                    || status.code() == tonic::Code::DeadlineExceeded
-                    || status.code() == tonic::Code::Unavailable
-                {
-                    warn!("xDS client terminated: {}, retrying in {:?}", err_detail, backoff);
-                } else {
-                    warn!("xDS client interrupted: {}, retrying in {:?}", err_detail, backoff);
-                }
-                let backoff = std::cmp::min(MAX_BACKOFF, state.backoff * 2);
-                tokio::time::sleep(backoff).await;
-                state.backoff = backoff;
-            },
-            Err(e) => {
-                let backoff = std::cmp::min(MAX_BACKOFF, state.backoff * 2);
-                let backoff_slowly = backoff + BACKOFF_INTERVAL;
-                warn!("xDS client error: {:?}, retrying in {:?}", e, backoff_slowly);
-                tokio::time::sleep(backoff_slowly).await;
-                state.backoff = backoff_slowly;
-            },
-            Ok(()) => {
-                warn!("xDS client closed");
-            },
-        }
+        let result = self.continuously_discover_resources(state).await;
+        back_off(result, &mut state.backoff, self.max_backoff).await;
     }
 
     async fn continuously_discover_resources(&mut self, state: &mut DiscoveryClientState) -> Result<(), XdsError> {
         let (discovery_requests_tx, mut discovery_requests_rx) = mpsc::channel::<DeltaDiscoveryRequest>(100);
         let initial_requests = self.build_initial_discovery_requests(state);
+        let rate_limiter = self.rate_limiter.clone();
         let request_stream = async_stream::stream! {
             for request in initial_requests {
+                if let Some(rate_limiter) = &rate_limiter {
+                    rate_limiter.acquire().await;
+                }
                 info!("sending initial discovery request {request:?}");
                 yield request;
             }
             while let Some(message) = discovery_requests_rx.recv().await {
+                if let Some(rate_limiter) = &rate_limiter {
+                    rate_limiter.acquire().await;
+                }
                 info!("sending upstream xDS message {message:?}");
                 yield message
             }
@@ -263,12 +444,13 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
             SubscriptionEvent::Subscribe(type_url, resource_id) => {
                 debug!("processing new subscription type_url={} {resource_id}", type_url.to_string());
                 let is_new = state.subscriptions.entry(type_url).or_default().insert(resource_id.clone());
+                client_status::requested(type_url, &resource_id);
                 if is_new {
                     if let Err(err) = discovery_requests_tx
                         .send(
                             DeltaDiscoveryRequestBuilder::for_resource(type_url)
                                 .with_node_id(self.node.clone())
-                                .with_resource_names_subscribe(vec![resource_id])
+                                .with_resource_names_subscribe(vec![self.subscribed_name(&resource_id)])
                                 .build(),
                         )
                         .await
@@ -280,12 +462,13 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
             SubscriptionEvent::Unsubscribe(type_url, resource_id) => {
                 debug!("processing unsubscribe type_url={} {resource_id}", type_url.to_string());
                 let was_subscribed = state.subscriptions.entry(type_url).or_default().remove(resource_id.as_str());
+                client_status::forget(type_url, &resource_id);
                 if was_subscribed {
                     if let Err(err) = discovery_requests_tx
                         .send(
                             DeltaDiscoveryRequestBuilder::for_resource(type_url)
                                 .with_node_id(self.node.clone())
-                                .with_resource_names_unsubscribe(vec![resource_id])
+                                .with_resource_names_unsubscribe(vec![self.subscribed_name(&resource_id)])
                                 .build(),
                         )
                         .await
@@ -304,6 +487,7 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
         state: &mut DiscoveryClientState,
     ) -> Result<(), XdsError> {
         let type_url = TypeUrl::try_from(response.type_url.as_str())?;
+        let response = self.with_subscribed_names(response, state.subscriptions.get(&type_url));
         let nonce = response.nonce.clone();
         info!(type_url = type_url.to_string(), size = response.resources.len(), "received config resources from xDS");
         let for_removal = Self::process_resource_ids_for_removal(state, &response, type_url);
@@ -321,7 +505,8 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
                 let mut batched_updates = Vec::<XdsResourceUpdate>::new();
                 batched_updates.append(&mut decoded_updates);
                 batched_updates.append(&mut removal_notifications);
-                let batch_notification = XdsUpdateEvent { updates: batched_updates, ack_channel: internal_ack_tx };
+                let batch_notification =
+                    XdsUpdateEvent { type_url, updates: batched_updates, ack_channel: internal_ack_tx };
                 self.resources_tx.send(batch_notification).await.map_err(
                     |e: mpsc::error::SendError<XdsUpdateEvent>| XdsError::InternalProcessingError(e.to_string()),
                 )?;
@@ -336,8 +521,10 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
                                     for (resource_id, resource_version) in pending_update_versions.drain() {
                                         tracked_resources.insert(resource_id, resource_version);
                                     }
+                                    Self::record_accepted_resources(&response, type_url, &for_removal, state.subscriptions.get(&type_url));
                                     None
                                 } else {
+                                    Self::record_rejected_resources(&response, type_url, &rejected_configs);
                                     let error_msg = rejected_configs.into_iter()
                                             .map(|reject| reject.to_string())
                                             .collect::<Vec<String>>()
@@ -375,6 +562,7 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
             },
 
             Err(decoding_errors) => {
+                Self::record_rejected_resources(&response, type_url, &decoding_errors);
                 let error_msg =
                     decoding_errors.into_iter().map(|reject| reject.to_string()).collect::<Vec<String>>().join("; ");
                 warn!(
@@ -394,31 +582,75 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
     }
 
     fn build_initial_discovery_requests(&self, tracking_state: &DiscoveryClientState) -> Vec<DeltaDiscoveryRequest> {
-        let resource_types = match C::type_url() {
-            Some(type_url) => vec![type_url],
-            _ => vec![
-                TypeUrl::Secret,
-                TypeUrl::Cluster,
-                TypeUrl::ClusterLoadAssignment,
-                TypeUrl::Listener,
-                TypeUrl::RouteConfiguration,
-            ],
-        };
-        resource_types
+        self.resource_types
             .iter()
             .map(|resource_type| {
                 let subscriptions = tracking_state.subscriptions.get(resource_type).cloned().unwrap_or_default();
-                let already_tracked: HashMap<ResourceId, ResourceVersion> =
-                    tracking_state.tracked.get(resource_type).cloned().unwrap_or_default();
+                for resource_id in &subscriptions {
+                    client_status::requested(*resource_type, resource_id);
+                }
+                let already_tracked: HashMap<ResourceId, ResourceVersion> = tracking_state
+                    .tracked
+                    .get(resource_type)
+                    .into_iter()
+                    .flatten()
+                    .map(|(resource_id, version)| (self.subscribed_name(resource_id), version.clone()))
+                    .collect();
                 DeltaDiscoveryRequestBuilder::for_resource(resource_type.to_owned())
                     .with_node_id(self.node.clone())
                     .with_initial_resource_versions(already_tracked)
-                    .with_resource_names_subscribe(subscriptions.into_iter().collect())
+                    .with_resource_names_subscribe(subscriptions.iter().map(|id| self.subscribed_name(id)).collect())
                     .build()
             })
             .collect()
     }
 
+    /// The name a resource is asked for by, an `xdstp://` name carrying the context parameters of the node.
+    fn subscribed_name(&self, resource_id: &str) -> ResourceId {
+        if self.node.context_params.is_empty() || !XdstpName::is_xdstp(resource_id) {
+            return resource_id.to_owned();
+        }
+        match resource_id.parse::<XdstpName>() {
+            Ok(name) => name.with_context_params(&self.node.context_params).to_string(),
+            Err(_) => resource_id.to_owned(),
+        }
+    }
+
+    /// Names the resources of a response as they were subscribed to, the ones of a glob collection by their name
+    /// without the context parameters of the node.
+    fn with_subscribed_names(
+        &self,
+        mut response: DeltaDiscoveryResponse,
+        subscriptions: Option<&HashSet<ResourceId>>,
+    ) -> DeltaDiscoveryResponse {
+        if self.node.context_params.is_empty() {
+            return response;
+        }
+        let subscribed: HashMap<ResourceId, &ResourceId> = subscriptions
+            .into_iter()
+            .flatten()
+            .filter(|resource_id| XdstpName::is_xdstp(resource_id))
+            .map(|resource_id| (self.subscribed_name(resource_id), resource_id))
+            .collect();
+        let subscribed_name = |name: &mut String| {
+            if !XdstpName::is_xdstp(name) {
+                return;
+            }
+            if let Some(resource_id) = subscribed.get(name.as_str()) {
+                name.clone_from(resource_id);
+            } else if let Ok(xdstp_name) = name.parse::<XdstpName>() {
+                *name = xdstp_name.without_context_params(&self.node.context_params).to_string();
+            }
+        };
+        for resource in &mut response.resources {
+            subscribed_name(&mut resource.name);
+        }
+        for name in &mut response.removed_resources {
+            subscribed_name(name);
+        }
+        response
+    }
+
     fn process_resource_ids_for_removal(
         state: &mut DiscoveryClientState,
         response: &DeltaDiscoveryResponse,
@@ -449,6 +681,10 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
             .filter_map(|resource| {
                 let resource_id = resource.name.clone();
                 let resource_version = resource.version.clone();
+                // a virtual host asked for on demand that the management server doesn't have comes without a body
+                if type_url == TypeUrl::VirtualHost && resource.resource.is_none() {
+                    return Some(XdsResourceUpdate::Remove(resource_id, type_url));
+                }
                 let decoded = XdsResourcePayload::try_from((resource, type_url));
                 if decoded.is_err() {
                     let error_msg = format!(
@@ -470,6 +706,36 @@ impl<C: bindings::TypedXdsBinding> DeltaClientBackgroundWorker<C> {
         }
     }
 
+    fn record_accepted_resources(
+        response: &DeltaDiscoveryResponse,
+        type_url: TypeUrl,
+        removed: &[ResourceId],
+        subscriptions: Option<&HashSet<ResourceId>>,
+    ) {
+        for resource in &response.resources {
+            if let Some(any) = &resource.resource {
+                accepted::insert(type_url, resource.name.clone(), resource.version.clone(), any.clone());
+                client_status::acked(type_url, &resource.name, &resource.version);
+            } else {
+                client_status::does_not_exist(type_url, &resource.name);
+            }
+        }
+        for resource_id in removed {
+            accepted::remove(type_url, resource_id);
+            // a resource subscribed to by name is still wanted, the ones of a wildcard subscription are just gone
+            if subscriptions.is_some_and(|subscriptions| subscriptions.contains(resource_id)) {
+                client_status::does_not_exist(type_url, resource_id);
+            } else {
+                client_status::forget(type_url, resource_id);
+            }
+        }
+    }
+
+    fn record_rejected_resources(response: &DeltaDiscoveryResponse, type_url: TypeUrl, rejected: &[RejectedConfig]) {
+        let resources = response.resources.iter().map(|resource| (resource.name.as_str(), resource.version.as_str()));
+        client_status::nacked(type_url, resources, rejected);
+    }
+
     fn extract_update_versions(updates: &[XdsResourceUpdate]) -> HashMap<ResourceId, ResourceVersion> {
         let mut update_versions = HashMap::<ResourceId, ResourceVersion>::new();
         for update in updates {
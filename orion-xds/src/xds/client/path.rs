@@ -0,0 +1,230 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Resources read from a file instead of a management server.
+//!
+//! The file holds a `DiscoveryResponse`, in YAML or JSON, with every resource of its type. As with Envoy, the file is
+//! only read again when a file is moved onto it (or into its watched directory), so that each update is applied
+//! atomically; a resource missing from the new content has been removed.
+
+use super::{
+    sotw::{decode_response, record_accepted_resources},
+    XdsUpdateEvent, ACK_TIMEOUT,
+};
+use crate::{
+    watcher::MoveWatcher,
+    xds::{
+        client_status,
+        model::{resource_name, RejectedConfig, ResourceId, TypeUrl, XdsError, XdsResourceUpdate},
+    },
+};
+use orion_configuration::config::PathConfigSource;
+use orion_data_plane_api::{decode::from_yaml, envoy_data_plane_api::envoy::service::discovery::v3::DiscoveryResponse};
+use std::{collections::HashSet, path::PathBuf};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time,
+};
+use tracing::{debug, info, warn};
+
+/// Background worker reading a type of resources from a file, each time it is replaced.
+#[derive(Debug)]
+pub struct PathClientBackgroundWorker {
+    path: PathBuf,
+    watched_directory: Option<PathBuf>,
+    type_url: TypeUrl,
+    resources_tx: mpsc::Sender<XdsUpdateEvent>,
+    /// Resources of the last accepted content of the file.
+    accepted: HashSet<ResourceId>,
+}
+
+impl PathClientBackgroundWorker {
+    pub(super) fn new(
+        config_source: PathConfigSource,
+        type_url: TypeUrl,
+        resources_tx: mpsc::Sender<XdsUpdateEvent>,
+    ) -> Self {
+        let PathConfigSource { path, watched_directory } = config_source;
+        Self { path, watched_directory, type_url, resources_tx, accepted: HashSet::new() }
+    }
+
+    pub async fn run(&mut self) -> Result<(), XdsError> {
+        let mut watcher = self
+            .watch()
+            .map_err(|e| XdsError::InternalProcessingError(format!("can't watch {}: {e}", self.path.display())))?;
+        self.load().await?;
+        loop {
+            watcher
+                .moved()
+                .await
+                .map_err(|e| XdsError::InternalProcessingError(format!("can't watch {}: {e}", self.path.display())))?;
+            debug!(path = %self.path.display(), "file moved, reading resources again");
+            self.load().await?;
+        }
+    }
+
+    fn watch(&self) -> std::io::Result<MoveWatcher> {
+        let mut watcher = MoveWatcher::new()?;
+        watcher.watch_file(&self.path)?;
+        if let Some(directory) = &self.watched_directory {
+            watcher.watch_directory(directory)?;
+        }
+        Ok(watcher)
+    }
+
+    async fn load(&mut self) -> Result<(), XdsError> {
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(err) => {
+                warn!(path = %self.path.display(), "failed to read xDS resources: {err}");
+                return Ok(());
+            },
+        };
+        let DiscoveryResponse { version_info, resources, .. } = match from_yaml::<DiscoveryResponse>(&content) {
+            Ok(response) => response,
+            Err(err) => {
+                warn!(path = %self.path.display(), "failed to parse xDS resources: {err}");
+                return Ok(());
+            },
+        };
+        let type_url = self.type_url;
+        if let Some(any) = resources.iter().find(|any| any.type_url != type_url.to_string()) {
+            warn!(path = %self.path.display(), "rejecting {} in a file of {type_url}", any.type_url);
+            return Ok(());
+        }
+        info!(path = %self.path.display(), type_url = type_url.to_string(), version_info, "read xDS resources from file");
+        // the names of the resources are told apart from their decoding, to know which ones a rejection is of
+        let names: Vec<_> = resources.iter().filter_map(|any| resource_name(type_url, any).ok()).collect();
+        let resources = match decode_response(resources, type_url, &version_info) {
+            Ok(resources) => resources,
+            Err(decoding_errors) => {
+                client_status::nacked(
+                    type_url,
+                    names.iter().map(|name| (name.as_str(), version_info.as_str())),
+                    &decoding_errors,
+                );
+                let error_msg =
+                    decoding_errors.into_iter().map(|reject| reject.to_string()).collect::<Vec<String>>().join("; ");
+                warn!(path = %self.path.display(), error_msg, "rejecting xDS resources that can't be decoded");
+                return Ok(());
+            },
+        };
+
+        let received = resources.iter().map(|(resource, _)| resource.name.clone()).collect::<HashSet<_>>();
+        let removed = self.accepted.difference(&received).cloned().collect::<Vec<_>>();
+        let (resources, payloads): (Vec<_>, Vec<_>) = resources.into_iter().unzip();
+        let updates = resources
+            .iter()
+            .zip(payloads)
+            .map(|(resource, payload)| XdsResourceUpdate::Update(resource.name.clone(), payload, version_info.clone()))
+            .chain(removed.iter().map(|resource_id| XdsResourceUpdate::Remove(resource_id.clone(), type_url)))
+            .collect();
+
+        let (internal_ack_tx, internal_ack_rx) = oneshot::channel::<Vec<RejectedConfig>>();
+        self.resources_tx
+            .send(XdsUpdateEvent { type_url, updates, ack_channel: internal_ack_tx })
+            .await
+            .map_err(|e: mpsc::error::SendError<XdsUpdateEvent>| XdsError::InternalProcessingError(e.to_string()))?;
+        tokio::select! {
+            ack = internal_ack_rx => match ack {
+                Ok(rejected_configs) if rejected_configs.is_empty() => {
+                    self.accepted = received;
+                    record_accepted_resources(&resources, type_url, &removed);
+                },
+                Ok(rejected_configs) => {
+                    let rejected = resources.iter().map(|resource| (resource.name.as_str(), resource.version.as_str()));
+                    client_status::nacked(type_url, rejected, &rejected_configs);
+                    let error_msg = rejected_configs.into_iter()
+                        .map(|reject| reject.to_string())
+                        .collect::<Vec<String>>()
+                        .join("; ");
+                    warn!(path = %self.path.display(), error_msg, "xDS resources from file were rejected");
+                },
+                Err(err) => warn!("error in reading internal ack/nack {:?}", err),
+            },
+            () = time::sleep(ACK_TIMEOUT) => {
+                warn!(path = %self.path.display(), "timed out while waiting to apply xDS resources from file");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fmt::Write, path::Path, time::Duration};
+
+    async fn next_event(resources_rx: &mut mpsc::Receiver<XdsUpdateEvent>) -> XdsUpdateEvent {
+        tokio::select! {
+            Some(event) = resources_rx.recv() => event,
+            () = time::sleep(Duration::from_secs(5)) => unreachable!("timed out waiting for resources from file"),
+        }
+    }
+
+    /// Writes clusters to a file, in the shape of a discovery response.
+    fn write_clusters(path: &Path, names: &[&str]) {
+        let mut content = String::from("version_info: \"1\"\nresources:\n");
+        for name in names {
+            write!(content, "  - \"@type\": {}\n    name: {name}\n    type: EDS\n", TypeUrl::Cluster).unwrap();
+        }
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn names(event: &XdsUpdateEvent) -> Vec<(String, bool)> {
+        let mut names = event
+            .updates
+            .iter()
+            .map(|update| match update {
+                XdsResourceUpdate::Update(name, _, _) => (name.clone(), true),
+                XdsResourceUpdate::Remove(name, _) => (name.clone(), false),
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn resources_are_read_again_when_the_file_is_moved() {
+        let directory = std::env::temp_dir().join(format!("orion-xds-path-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("cds.yaml");
+        write_clusters(&path, &["a", "b"]);
+
+        let (resources_tx, mut resources_rx) = mpsc::channel(10);
+        let config_source = PathConfigSource { path: path.clone(), watched_directory: None };
+        let mut worker = PathClientBackgroundWorker::new(config_source, TypeUrl::Cluster, resources_tx);
+        tokio::spawn(async move { worker.run().await });
+
+        let event = next_event(&mut resources_rx).await;
+        assert_eq!(event.type_url, TypeUrl::Cluster);
+        assert_eq!(names(&event), vec![("a".to_owned(), true), ("b".to_owned(), true)]);
+        event.ack_channel.send(vec![]).unwrap();
+
+        // writing in place isn't picked up, moving a file onto the path is
+        write_clusters(&path, &["a", "b", "c"]);
+        let staged = directory.join("cds.yaml.new");
+        write_clusters(&staged, &["a"]);
+        std::fs::rename(&staged, &path).unwrap();
+
+        let event = next_event(&mut resources_rx).await;
+        assert_eq!(names(&event), vec![("a".to_owned(), true), ("b".to_owned(), false)]);
+        event.ack_channel.send(vec![]).unwrap();
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
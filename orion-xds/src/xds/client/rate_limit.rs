@@ -0,0 +1,92 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+use std::sync::Arc;
+
+use orion_configuration::config::RateLimitSettings;
+use tokio::{
+    sync::Mutex,
+    time::{self, Instant},
+};
+
+/// Token bucket spacing out the discovery requests sent to a management server, shared by the streams opened one
+/// after the other so that reconnecting does not refill it.
+#[derive(Debug, Clone)]
+pub(super) struct RequestRateLimiter {
+    settings: RateLimitSettings,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: u32,
+    last_fill: Instant,
+}
+
+impl RequestRateLimiter {
+    pub(super) fn new(settings: RateLimitSettings) -> Self {
+        let bucket = Bucket { tokens: settings.max_tokens, last_fill: Instant::now() };
+        Self { settings, bucket: Arc::new(Mutex::new(bucket)) }
+    }
+
+    /// Waits for a token to send a request with.
+    pub(super) async fn acquire(&self) {
+        let RateLimitSettings { max_tokens, fill_interval } = self.settings;
+        loop {
+            let next_fill = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                let fills = now.duration_since(bucket.last_fill).as_nanos() / fill_interval.as_nanos().max(1);
+                let fills = u32::try_from(fills).unwrap_or(u32::MAX);
+                if fills > 0 {
+                    bucket.tokens = bucket.tokens.saturating_add(fills).min(max_tokens);
+                    // a full bucket starts filling again from now, otherwise fills keep their pace
+                    bucket.last_fill =
+                        if bucket.tokens == max_tokens { now } else { bucket.last_fill + fill_interval * fills };
+                }
+                if bucket.tokens > 0 {
+                    bucket.tokens -= 1;
+                    return;
+                }
+                bucket.last_fill + fill_interval
+            };
+            time::sleep_until(next_fill).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn requests_are_spaced_out_once_tokens_run_out() {
+        let fill_interval = Duration::from_millis(50);
+        let start = Instant::now();
+        let limiter = RequestRateLimiter::new(RateLimitSettings { max_tokens: 2, fill_interval });
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() < fill_interval);
+
+        limiter.acquire().await;
+        assert!(start.elapsed() >= fill_interval);
+        limiter.acquire().await;
+        assert!(start.elapsed() >= fill_interval * 2);
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! State-of-the-world flavour of the xDS protocol.
+//!
+//! Each response holds every resource of its type the client is subscribed to, with a single version. Listeners and
+//! clusters are subscribed to with a wildcard unless resources of their type are subscribed to from the start, and a
+//! listener or cluster missing from a response has been removed. The other types are only sent when asked for by
+//! name, and a missing resource is simply not part of the response.
+//!
+//! `xdstp://` names, glob collections included, are asked for as they are, the context parameters of the node being
+//! left to the incremental flavour.
+
+use super::{
+    back_off, RequestRateLimiter, SubscriptionEvent, WorkerParts, XdsUpdateEvent, ACK_TIMEOUT, INITIAL_BACKOFF,
+};
+use crate::xds::{
+    accepted, bindings, client_status,
+    model::{
+        resource_name, RejectedConfig, ResourceId, ResourceVersion, TypeUrl, XdsError, XdsResourcePayload,
+        XdsResourceUpdate,
+    },
+    request::{DiscoveryRequestBuilder, StatusBuilder},
+};
+use orion_configuration::config::bootstrap::Node;
+use orion_data_plane_api::envoy_data_plane_api::{
+    envoy::service::discovery::v3::{DiscoveryRequest, DiscoveryResponse, Resource},
+    google::{protobuf::Any, rpc::Status},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time,
+};
+use tracing::{debug, info, warn};
+
+/// Background worker that handles interactions with remote xDS services, in the state-of-the-world protocol
+#[derive(Debug)]
+pub struct SotwClientBackgroundWorker<C: bindings::TypedXdsBinding> {
+    node: Node,
+    client_binding: C,
+    resource_types: Vec<TypeUrl>,
+    wildcard_types: HashSet<TypeUrl>,
+    initial_subscriptions: HashMap<TypeUrl, HashSet<ResourceId>>,
+    rate_limiter: Option<RequestRateLimiter>,
+    max_backoff: Duration,
+    subscriptions_rx: mpsc::Receiver<SubscriptionEvent>,
+    resources_tx: mpsc::Sender<XdsUpdateEvent>,
+}
+
+#[derive(Debug)]
+struct SotwClientState {
+    backoff: Duration,
+    types: HashMap<TypeUrl, TypeState>,
+    subscriptions: HashMap<TypeUrl, HashSet<ResourceId>>,
+}
+
+#[derive(Debug, Default)]
+struct TypeState {
+    /// Version of the last accepted response, sent back with every request.
+    version_info: ResourceVersion,
+    /// Nonce of the last response received on the current stream.
+    nonce: String,
+    /// Resources of the last accepted response, for the types whose omitted resources are removed.
+    resources: HashSet<ResourceId>,
+}
+
+/// Each response of these types holds all their resources, so the ones left out of it have been removed. They are
+/// the only types that can be subscribed to with a wildcard, for the others the client asks for resources by name.
+fn removes_omitted(type_url: TypeUrl) -> bool {
+    matches!(type_url, TypeUrl::Listener | TypeUrl::Cluster | TypeUrl::ScopedRouteConfiguration)
+}
+
+impl<C: bindings::TypedXdsBinding> SotwClientBackgroundWorker<C> {
+    pub(super) fn new(parts: WorkerParts<C>) -> Self {
+        let WorkerParts {
+            node,
+            client_binding,
+            resource_types,
+            initial_subscriptions,
+            rate_limiter,
+            max_backoff,
+            subscriptions_rx,
+            resources_tx,
+        } = parts;
+        // the types some resources of which are subscribed to from the start are only asked for by name
+        let wildcard_types = resource_types
+            .iter()
+            .copied()
+            .filter(|type_url| {
+                removes_omitted(*type_url) && initial_subscriptions.get(type_url).is_none_or(HashSet::is_empty)
+            })
+            .collect();
+        Self {
+            node,
+            client_binding,
+            resource_types,
+            wildcard_types,
+            initial_subscriptions,
+            rate_limiter,
+            max_backoff,
+            subscriptions_rx,
+            resources_tx,
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<(), XdsError> {
+        let mut connection_id = 0;
+        let mut state = SotwClientState {
+            backoff: INITIAL_BACKOFF,
+            types: HashMap::new(),
+            subscriptions: self.initial_subscriptions.clone(),
+        };
+        loop {
+            connection_id += 1;
+            debug!(connection_id, "starting xDS (re)connect cycle {:?}", state.backoff);
+            // nonces only make sense on the stream they were received on
+            for type_state in state.types.values_mut() {
+                type_state.nonce.clear();
+            }
+            let result = self.continuously_discover_resources(&mut state).await;
+            back_off(result, &mut state.backoff, self.max_backoff).await;
+        }
+    }
+
+    async fn continuously_discover_resources(&mut self, state: &mut SotwClientState) -> Result<(), XdsError> {
+        let (discovery_requests_tx, mut discovery_requests_rx) = mpsc::channel::<DiscoveryRequest>(100);
+        // a request without names is a wildcard, so the types asked for by name wait for their first subscription
+        let initial_requests = self
+            .resource_types
+            .iter()
+            .filter(|type_url| {
+                self.is_wildcard(**type_url) || state.subscriptions.get(type_url).is_some_and(|names| !names.is_empty())
+            })
+            .map(|type_url| self.build_request(*type_url, state).build())
+            .collect::<Vec<_>>();
+        let rate_limiter = self.rate_limiter.clone();
+        let request_stream = async_stream::stream! {
+            for request in initial_requests {
+                if let Some(rate_limiter) = &rate_limiter {
+                    rate_limiter.acquire().await;
+                }
+                info!("sending initial discovery request {request:?}");
+                yield request;
+            }
+            while let Some(message) = discovery_requests_rx.recv().await {
+                if let Some(rate_limiter) = &rate_limiter {
+                    rate_limiter.acquire().await;
+                }
+                info!("sending upstream xDS message {message:?}");
+                yield message
+            }
+            warn!("outbound discovery request stream has ended!");
+        };
+        let mut response_stream =
+            self.client_binding.stream_request(request_stream).await.map_err(XdsError::GrpcStatus)?.into_inner();
+        info!("xDS stream established");
+        debug!("XDS client connection backoff has been reset");
+        state.backoff = INITIAL_BACKOFF;
+
+        loop {
+            tokio::select! {
+                Some(event) = self.subscriptions_rx.recv() => {
+                    self.process_subscription_event(event, state, &discovery_requests_tx).await;
+                }
+                discovered = response_stream.message() => {
+                    let payload = discovered?;
+                    let discovery_response = payload.ok_or(XdsError::UnknownResourceType("empty payload received".to_owned()))?;
+                    self.process_discovery_response(discovery_response, &discovery_requests_tx, state).await?;
+                }
+                else => {
+                    warn!("xDS stream has ended");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn is_wildcard(&self, type_url: TypeUrl) -> bool {
+        self.wildcard_types.contains(&type_url)
+    }
+
+    /// A request for the current subscriptions of a type, acknowledging the last accepted version.
+    fn build_request(&self, type_url: TypeUrl, state: &SotwClientState) -> DiscoveryRequestBuilder {
+        let resource_names: Vec<_> = if self.is_wildcard(type_url) {
+            Vec::new()
+        } else {
+            state.subscriptions.get(&type_url).map(|names| names.iter().cloned().collect()).unwrap_or_default()
+        };
+        for name in &resource_names {
+            client_status::requested(type_url, name);
+        }
+        let type_state = state.types.get(&type_url);
+        DiscoveryRequestBuilder::for_resource(type_url)
+            .with_node_id(self.node.clone())
+            .with_version_info(type_state.map(|type_state| type_state.version_info.clone()).unwrap_or_default())
+            .with_nonce(type_state.map(|type_state| type_state.nonce.clone()).unwrap_or_default())
+            .with_resource_names(resource_names)
+    }
+
+    async fn process_subscription_event(
+        &self,
+        event: SubscriptionEvent,
+        state: &mut SotwClientState,
+        discovery_requests_tx: &mpsc::Sender<DiscoveryRequest>,
+    ) {
+        let (type_url, changed) = match event {
+            SubscriptionEvent::Subscribe(type_url, resource_id) => {
+                debug!("processing new subscription type_url={type_url} {resource_id}");
+                (type_url, state.subscriptions.entry(type_url).or_default().insert(resource_id))
+            },
+            SubscriptionEvent::Unsubscribe(type_url, resource_id) => {
+                debug!("processing unsubscribe type_url={type_url} {resource_id}");
+                if !self.is_wildcard(type_url) {
+                    client_status::forget(type_url, &resource_id);
+                }
+                (type_url, state.subscriptions.entry(type_url).or_default().remove(resource_id.as_str()))
+            },
+        };
+        // the subscriptions to wildcard types don't change what is asked for
+        if changed && !self.is_wildcard(type_url) {
+            if let Err(err) = discovery_requests_tx.send(self.build_request(type_url, state).build()).await {
+                warn!("problems updating subscription: {:?}", err);
+            }
+        }
+    }
+
+    async fn process_discovery_response(
+        &mut self,
+        response: DiscoveryResponse,
+        acknowledgments_tx: &mpsc::Sender<DiscoveryRequest>,
+        state: &mut SotwClientState,
+    ) -> Result<(), XdsError> {
+        let type_url = TypeUrl::try_from(response.type_url.as_str())?;
+        let DiscoveryResponse { version_info, resources, nonce, .. } = response;
+        info!(
+            type_url = type_url.to_string(),
+            version_info,
+            size = resources.len(),
+            "received config resources from xDS"
+        );
+        state.types.entry(type_url).or_default().nonce.clone_from(&nonce);
+
+        // the names of the resources are told apart from their decoding, to know which ones a rejection is of
+        let names: Vec<_> = resources.iter().filter_map(|any| resource_name(type_url, any).ok()).collect();
+        let resources = match decode_response(resources, type_url, &version_info) {
+            Ok(resources) => resources,
+            Err(decoding_errors) => {
+                client_status::nacked(
+                    type_url,
+                    names.iter().map(|name| (name.as_str(), version_info.as_str())),
+                    &decoding_errors,
+                );
+                let error_msg =
+                    decoding_errors.into_iter().map(|reject| reject.to_string()).collect::<Vec<String>>().join("; ");
+                warn!(
+                    type_url = type_url.to_string(),
+                    error_msg, nonce, "decoding error, rejecting configs with nack response"
+                );
+                let nack = StatusBuilder::invalid_argument().with_message(error_msg).build();
+                self.send_response(type_url, state, Some(nack), acknowledgments_tx).await;
+                return Ok(());
+            },
+        };
+
+        let received = resources.iter().map(|(resource, _)| resource.name.clone()).collect::<HashSet<_>>();
+        let removed = if removes_omitted(type_url) {
+            state
+                .types
+                .get(&type_url)
+                .map(|type_state| type_state.resources.difference(&received).cloned().collect::<Vec<_>>())
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let (resources, payloads): (Vec<Resource>, Vec<XdsResourcePayload>) = resources.into_iter().unzip();
+        let updates = resources
+            .iter()
+            .zip(payloads)
+            .map(|(resource, payload)| XdsResourceUpdate::Update(resource.name.clone(), payload, version_info.clone()))
+            .chain(removed.iter().map(|resource_id| XdsResourceUpdate::Remove(resource_id.clone(), type_url)))
+            .collect();
+
+        let (internal_ack_tx, internal_ack_rx) = oneshot::channel::<Vec<RejectedConfig>>();
+        self.resources_tx
+            .send(XdsUpdateEvent { type_url, updates, ack_channel: internal_ack_tx })
+            .await
+            .map_err(|e: mpsc::error::SendError<XdsUpdateEvent>| XdsError::InternalProcessingError(e.to_string()))?;
+
+        let error_detail = tokio::select! {
+            ack = internal_ack_rx => match ack {
+                Ok(rejected_configs) if rejected_configs.is_empty() => {
+                    debug!(type_url = type_url.to_string(), nonce, "sending ack response after processing");
+                    let type_state = state.types.entry(type_url).or_default();
+                    type_state.version_info = version_info;
+                    if removes_omitted(type_url) {
+                        type_state.resources = received;
+                    }
+                    record_accepted_resources(&resources, type_url, &removed);
+                    None
+                },
+                Ok(rejected_configs) => {
+                    client_status::nacked(type_url, names.iter().map(|name| (name.as_str(), version_info.as_str())), &rejected_configs);
+                    let error_msg = rejected_configs.into_iter()
+                        .map(|reject| reject.to_string())
+                        .collect::<Vec<String>>()
+                        .join("; ");
+                    warn!(type_url = type_url.to_string(), error_msg, nonce, "rejecting configs with nack response");
+                    Some(StatusBuilder::invalid_argument().with_message(error_msg).build())
+                },
+                Err(err) => {
+                    warn!("error in reading internal ack/nack {:?}", err);
+                    return Ok(());
+                },
+            },
+            () = time::sleep(ACK_TIMEOUT) => {
+                warn!("timed out while waiting to acknowledge config updates");
+                let error_msg = format!("timed out trying to apply resource updates for [{version_info}]");
+                Some(StatusBuilder::unspecified_error().with_message(error_msg).build())
+            }
+        };
+        self.send_response(type_url, state, error_detail, acknowledgments_tx).await;
+        Ok(())
+    }
+
+    /// Acknowledges the last response of a type, or rejects it with the version of the last accepted one.
+    async fn send_response(
+        &self,
+        type_url: TypeUrl,
+        state: &SotwClientState,
+        error_detail: Option<Status>,
+        acknowledgments_tx: &mpsc::Sender<DiscoveryRequest>,
+    ) {
+        let request = self.build_request(type_url, state).with_error_detail(error_detail).build();
+        if let Err(err) = acknowledgments_tx.send(request).await {
+            warn!("error in send xDS ack/nack upstream {:?}", err);
+        }
+    }
+}
+
+/// Decodes the resources of a response, rejecting the whole response if any of them can't be.
+pub(super) fn decode_response(
+    resources: Vec<Any>,
+    type_url: TypeUrl,
+    version_info: &str,
+) -> Result<Vec<(Resource, XdsResourcePayload)>, Vec<RejectedConfig>> {
+    let mut decoding_errors = Vec::<RejectedConfig>::new();
+    let mut decoded = Vec::with_capacity(resources.len());
+    for any in resources {
+        let result = resource_name(type_url, &any).and_then(|name| {
+            let resource =
+                Resource { name, version: version_info.to_owned(), resource: Some(any), ..Default::default() };
+            XdsResourcePayload::try_from((resource.clone(), type_url)).map(|payload| (resource, payload))
+        });
+        match result {
+            Ok(resource) => decoded.push(resource),
+            Err(err) => {
+                let error_msg = format!("problem decoding config update: error {err:?}");
+                warn!(error_msg);
+                decoding_errors.push(RejectedConfig::from((type_url.to_string(), orion_error::Error::from(error_msg))));
+            },
+        }
+    }
+    if decoding_errors.is_empty() {
+        Ok(decoded)
+    } else {
+        Err(decoding_errors)
+    }
+}
+
+pub(super) fn record_accepted_resources(resources: &[Resource], type_url: TypeUrl, removed: &[ResourceId]) {
+    for resource in resources {
+        if let Some(any) = &resource.resource {
+            accepted::insert(type_url, resource.name.clone(), resource.version.clone(), any.clone());
+            client_status::acked(type_url, &resource.name, &resource.version);
+        }
+    }
+    // only the resources of the types whose responses hold them all are removed by leaving them out
+    for resource_id in removed {
+        accepted::remove(type_url, resource_id);
+        client_status::forget(type_url, resource_id);
+    }
+}
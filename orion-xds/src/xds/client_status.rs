@@ -0,0 +1,341 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! The status of the xDS resources of the proxy, as reported over the Client Status Discovery Service (CSDS).
+//!
+//! A resource is `REQUESTED` once subscribed to by name, `ACKED` once a version of it is accepted, `NACKED` once a
+//! version of it is rejected, and `DOES_NOT_EXIST` once the management server says it has none. The last rejection
+//! of a resource is kept until a version of it is accepted, so that why it failed can be told without the logs.
+
+use super::{
+    accepted,
+    model::{RejectedConfig, ResourceId, ResourceVersion, TypeUrl},
+};
+use orion_configuration::config::bootstrap::Node;
+use orion_data_plane_api::envoy_data_plane_api::{
+    envoy::{
+        admin::v3::{ClientResourceStatus, UpdateFailureState},
+        config::core::v3::Node as EnvoyNode,
+        service::status::v3::{
+            client_config::GenericXdsConfig,
+            client_status_discovery_service_server::{
+                ClientStatusDiscoveryService, ClientStatusDiscoveryServiceServer,
+            },
+            ClientConfig, ClientStatusRequest, ClientStatusResponse,
+        },
+    },
+    google::protobuf::Timestamp,
+    tonic::{self, Response, Status},
+};
+use parking_lot::RwLock;
+use std::{collections::BTreeMap, pin::Pin, sync::LazyLock, time::SystemTime};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tracing::warn;
+
+static RESOURCE_STATUSES: LazyLock<RwLock<BTreeMap<(TypeUrl, ResourceId), ResourceStatus>>> =
+    LazyLock::new(|| RwLock::new(BTreeMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientStatus {
+    Requested,
+    Acked,
+    Nacked,
+    DoesNotExist,
+}
+
+impl ClientStatus {
+    pub fn as_str(self) -> &'static str {
+        ClientResourceStatus::from(self).as_str_name()
+    }
+}
+
+impl From<ClientStatus> for ClientResourceStatus {
+    fn from(value: ClientStatus) -> Self {
+        match value {
+            ClientStatus::Requested => ClientResourceStatus::Requested,
+            ClientStatus::Acked => ClientResourceStatus::Acked,
+            ClientStatus::Nacked => ClientResourceStatus::Nacked,
+            ClientStatus::DoesNotExist => ClientResourceStatus::DoesNotExist,
+        }
+    }
+}
+
+/// A version of a resource the proxy rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateFailure {
+    pub version_info: ResourceVersion,
+    pub details: String,
+    pub last_update_attempt: SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceStatus {
+    pub type_url: TypeUrl,
+    pub name: ResourceId,
+    pub client_status: ClientStatus,
+    /// The version last accepted, empty if none was.
+    pub version_info: ResourceVersion,
+    /// When a version of the resource was last accepted.
+    pub last_updated: Option<SystemTime>,
+    /// The last rejection of the resource, until a version of it is accepted.
+    pub error_state: Option<UpdateFailure>,
+}
+
+impl ResourceStatus {
+    fn new(type_url: TypeUrl, name: ResourceId) -> Self {
+        Self {
+            type_url,
+            name,
+            client_status: ClientStatus::Requested,
+            version_info: ResourceVersion::new(),
+            last_updated: None,
+            error_state: None,
+        }
+    }
+}
+
+fn update(type_url: TypeUrl, name: &str, update: impl FnOnce(&mut ResourceStatus)) {
+    let mut statuses = RESOURCE_STATUSES.write();
+    let status =
+        statuses.entry((type_url, name.to_owned())).or_insert_with(|| ResourceStatus::new(type_url, name.to_owned()));
+    update(status);
+}
+
+/// Records a resource subscribed to by name, unless a version of it was already received.
+pub fn requested(type_url: TypeUrl, name: &str) {
+    update(type_url, name, |status| {
+        if status.client_status == ClientStatus::DoesNotExist {
+            status.client_status = ClientStatus::Requested;
+        }
+    });
+}
+
+pub fn acked(type_url: TypeUrl, name: &str, version_info: &str) {
+    update(type_url, name, |status| {
+        status.client_status = ClientStatus::Acked;
+        version_info.clone_into(&mut status.version_info);
+        status.last_updated = Some(SystemTime::now());
+        status.error_state = None;
+    });
+}
+
+pub fn does_not_exist(type_url: TypeUrl, name: &str) {
+    update(type_url, name, |status| {
+        status.client_status = ClientStatus::DoesNotExist;
+        status.version_info.clear();
+        status.last_updated = None;
+    });
+}
+
+/// Records the rejection of the resources of a response, given by name and version. A rejection naming none of them,
+/// like the ones of a response that could not be decoded, is of all of them.
+pub fn nacked<'a>(
+    type_url: TypeUrl,
+    resources: impl IntoIterator<Item = (&'a str, &'a str)>,
+    rejections: &[RejectedConfig],
+) {
+    let resources: Vec<_> = resources.into_iter().collect();
+    let unnamed = rejections
+        .iter()
+        .filter(|rejection| !resources.iter().any(|(name, _)| *name == rejection.name()))
+        .map(|rejection| rejection.reason().to_string())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let now = SystemTime::now();
+    for (name, version_info) in resources {
+        let details = rejections
+            .iter()
+            .find(|rejection| rejection.name() == name)
+            .map(|rejection| rejection.reason().to_string())
+            .unwrap_or_else(|| unnamed.clone());
+        if details.is_empty() {
+            continue;
+        }
+        update(type_url, name, |status| {
+            status.client_status = ClientStatus::Nacked;
+            status.error_state =
+                Some(UpdateFailure { version_info: version_info.to_owned(), details, last_update_attempt: now });
+        });
+    }
+}
+
+/// Stops tracking a resource no longer subscribed to, or removed from a wildcard subscription.
+pub fn forget(type_url: TypeUrl, name: &str) {
+    RESOURCE_STATUSES.write().remove(&(type_url, name.to_owned()));
+}
+
+/// The status of every resource, ordered by type and name.
+pub fn resource_statuses() -> Vec<ResourceStatus> {
+    RESOURCE_STATUSES.read().values().cloned().collect()
+}
+
+pub fn resource_status(type_url: TypeUrl, name: &str) -> Option<ResourceStatus> {
+    RESOURCE_STATUSES.read().get(&(type_url, name.to_owned())).cloned()
+}
+
+fn timestamp(time: SystemTime) -> Timestamp {
+    let since_epoch = time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+    Timestamp {
+        seconds: i64::try_from(since_epoch.as_secs()).unwrap_or(i64::MAX),
+        nanos: i32::try_from(since_epoch.subsec_nanos()).unwrap_or_default(),
+    }
+}
+
+/// The status of the resources of a node, with the contents of the versions accepted unless excluded.
+pub fn client_config(node: &Node, exclude_resource_contents: bool) -> ClientConfig {
+    let generic_xds_configs = resource_statuses()
+        .into_iter()
+        .map(|status| {
+            let xds_config = if exclude_resource_contents || status.client_status == ClientStatus::DoesNotExist {
+                None
+            } else {
+                accepted::accepted_resource(status.type_url, &status.name).map(|accepted| accepted.resource)
+            };
+            GenericXdsConfig {
+                type_url: status.type_url.to_string(),
+                name: status.name,
+                version_info: status.version_info,
+                xds_config,
+                last_updated: status.last_updated.map(timestamp),
+                client_status: ClientResourceStatus::from(status.client_status).into(),
+                error_state: status.error_state.map(|failure| UpdateFailureState {
+                    failed_configuration: None,
+                    last_update_attempt: Some(timestamp(failure.last_update_attempt)),
+                    details: failure.details,
+                    version_info: failure.version_info,
+                }),
+                ..Default::default()
+            }
+        })
+        .collect();
+    ClientConfig { node: Some(EnvoyNode::from(node)), generic_xds_configs, ..Default::default() }
+}
+
+/// Serves the status of the resources of the proxy over CSDS. The proxy only knows of itself, so the node matchers
+/// of the requests are not looked at.
+#[derive(Debug, Clone)]
+pub struct ClientStatusServer {
+    node: Node,
+}
+
+impl ClientStatusServer {
+    pub fn new(node: Node) -> Self {
+        Self { node }
+    }
+
+    pub fn into_service(self) -> ClientStatusDiscoveryServiceServer<Self> {
+        ClientStatusDiscoveryServiceServer::new(self)
+    }
+
+    fn response(&self, request: &ClientStatusRequest) -> ClientStatusResponse {
+        ClientStatusResponse { config: vec![client_config(&self.node, request.exclude_resource_contents)] }
+    }
+}
+
+#[tonic::async_trait]
+impl ClientStatusDiscoveryService for ClientStatusServer {
+    type StreamClientStatusStream =
+        Pin<Box<dyn Stream<Item = std::result::Result<ClientStatusResponse, Status>> + Send>>;
+
+    async fn stream_client_status(
+        &self,
+        req: tonic::Request<tonic::Streaming<ClientStatusRequest>>,
+    ) -> std::result::Result<Response<Self::StreamClientStatusStream>, Status> {
+        let mut requests = req.into_inner();
+        let server = self.clone();
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            loop {
+                match requests.message().await {
+                    Ok(Some(request)) => {
+                        if tx.send(Ok(server.response(&request))).await.is_err() {
+                            return;
+                        }
+                    },
+                    Ok(None) => return,
+                    Err(status) => {
+                        warn!("CSDS stream error: {status}");
+                        return;
+                    },
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::StreamClientStatusStream))
+    }
+
+    async fn fetch_client_status(
+        &self,
+        req: tonic::Request<ClientStatusRequest>,
+    ) -> std::result::Result<Response<ClientStatusResponse>, Status> {
+        Ok(Response::new(self.response(req.get_ref())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejections_are_kept_until_a_version_is_accepted() {
+        requested(TypeUrl::RouteConfiguration, "status_routes");
+        assert_eq!(
+            resource_status(TypeUrl::RouteConfiguration, "status_routes").map(|status| status.client_status),
+            Some(ClientStatus::Requested)
+        );
+
+        acked(TypeUrl::RouteConfiguration, "status_routes", "1");
+        let rejections = [RejectedConfig::from(("status_routes".to_owned(), "unknown cluster"))];
+        nacked(TypeUrl::RouteConfiguration, [("status_routes", "2"), ("status_other_routes", "2")], &rejections);
+        let status = resource_status(TypeUrl::RouteConfiguration, "status_routes").unwrap();
+        assert_eq!((status.client_status, status.version_info.as_str()), (ClientStatus::Nacked, "1"));
+        let failure = status.error_state.unwrap();
+        assert_eq!((failure.version_info.as_str(), failure.details.as_str()), ("2", "unknown cluster"));
+        assert!(resource_status(TypeUrl::RouteConfiguration, "status_other_routes").is_none(), "not rejected");
+
+        acked(TypeUrl::RouteConfiguration, "status_routes", "3");
+        let status = resource_status(TypeUrl::RouteConfiguration, "status_routes").unwrap();
+        assert_eq!(status.client_status, ClientStatus::Acked);
+        assert!(status.error_state.is_none());
+
+        does_not_exist(TypeUrl::RouteConfiguration, "status_routes");
+        requested(TypeUrl::RouteConfiguration, "status_routes");
+        assert_eq!(
+            resource_status(TypeUrl::RouteConfiguration, "status_routes").map(|status| status.client_status),
+            Some(ClientStatus::Requested)
+        );
+        forget(TypeUrl::RouteConfiguration, "status_routes");
+        assert!(resource_status(TypeUrl::RouteConfiguration, "status_routes").is_none());
+    }
+
+    #[test]
+    fn undecodable_responses_are_rejected_for_all_of_their_resources() {
+        let rejections = [RejectedConfig::from((TypeUrl::Cluster.to_string(), "invalid cluster"))];
+        nacked(TypeUrl::Cluster, [("status_a", "1"), ("status_b", "1")], &rejections);
+        let config = client_config(&Node::default(), true);
+        let nacked: Vec<_> = config
+            .generic_xds_configs
+            .iter()
+            .filter(|config| config.name.starts_with("status_") && config.type_url == TypeUrl::Cluster.to_string())
+            .map(|config| {
+                (config.name.as_str(), config.client_status, config.error_state.as_ref().unwrap().details.as_str())
+            })
+            .collect();
+        let status = ClientResourceStatus::Nacked as i32;
+        assert_eq!(nacked, [("status_a", status, "invalid cluster"), ("status_b", status, "invalid cluster")]);
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Health checks endpoints on behalf of a health discovery service (HDS) server.
+//!
+//! The server assigns the client the endpoints to health check and how often it wants to hear about them, and the
+//! client sends it the health of those endpoints at that interval, until the server assigns it other endpoints.
+
+use std::time::Duration;
+
+use orion_configuration::config::{bootstrap::Node, grpc};
+use orion_data_plane_api::envoy_data_plane_api::{
+    envoy::{
+        config::core::v3::Node as EnvoyNode,
+        service::health::v3::{
+            capability::Protocol, health_check_request_or_endpoint_health_response::RequestType,
+            health_discovery_service_client::HealthDiscoveryServiceClient, Capability, ClusterEndpointsHealth,
+            ClusterHealthCheck, EndpointHealthResponse, HealthCheckRequest, HealthCheckRequestOrEndpointHealthResponse,
+            HealthCheckSpecifier,
+        },
+    },
+    google::protobuf::Duration as ProtobufDuration,
+    tonic::{self, codegen::StdError},
+};
+use tokio::{
+    sync::mpsc,
+    time::{self, Instant},
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, info, warn};
+
+use super::{
+    client::{back_off, INITIAL_BACKOFF, MAX_BACKOFF},
+    model::XdsError,
+};
+
+/// Health checks the endpoints assigned by the server.
+pub trait DelegatedHealthChecks: Send {
+    /// Health checks the endpoints of these clusters, instead of those assigned before.
+    fn assign(&mut self, clusters: Vec<ClusterHealthCheck>);
+    /// The health of the endpoints assigned, as last checked.
+    fn report(&mut self) -> Vec<ClusterEndpointsHealth>;
+}
+
+#[derive(Debug)]
+pub struct HealthDiscoveryClient<C, H> {
+    node: Node,
+    client: HealthDiscoveryServiceClient<C>,
+    health_checks: H,
+    /// The clusters last assigned, not assigned again when the server sends them anew.
+    assigned: Option<Vec<ClusterHealthCheck>>,
+    max_backoff: Duration,
+}
+
+impl<C, H> HealthDiscoveryClient<C, H>
+where
+    C: tonic::client::GrpcService<tonic::body::Body> + Send,
+    C::Error: Into<StdError>,
+    C::ResponseBody: tonic::codegen::Body<Data = tonic::codegen::Bytes> + Send + 'static,
+    <C::ResponseBody as tonic::codegen::Body>::Error: Into<StdError> + Send,
+    C::Future: Send,
+    H: DelegatedHealthChecks,
+{
+    pub fn new(node: Node, client: HealthDiscoveryServiceClient<C>, health_checks: H) -> Self {
+        Self { node, client, health_checks, assigned: None, max_backoff: MAX_BACKOFF }
+    }
+
+    #[must_use]
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Health checks the endpoints assigned by the server, reconnecting whenever the stream ends. The endpoints
+    /// assigned keep being checked while reconnecting, until the server assigns others.
+    pub async fn run(&mut self) -> Result<(), XdsError> {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            let result = self.health_check(&mut backoff).await;
+            back_off(result, &mut backoff, self.max_backoff).await;
+        }
+    }
+
+    async fn health_check(&mut self, backoff: &mut Duration) -> Result<(), XdsError> {
+        let (requests_tx, requests_rx) = mpsc::channel::<HealthCheckRequestOrEndpointHealthResponse>(16);
+        let node = EnvoyNode::from(&self.node);
+        // HDS has no protocol to advertise gRPC health checks with, which are not converted from Envoy's either
+        let capability = Capability { health_check_protocols: vec![Protocol::Http.into(), Protocol::Tcp.into()] };
+        // the server only assigns endpoints once it knows who the client is and what it can check
+        let request = HealthCheckRequest { node: Some(node), capability: Some(capability) };
+        requests_tx
+            .send(HealthCheckRequestOrEndpointHealthResponse {
+                request_type: Some(RequestType::HealthCheckRequest(request)),
+            })
+            .await
+            .map_err(|e| XdsError::InternalProcessingError(e.to_string()))?;
+        let mut responses = self.client.stream_health_check(ReceiverStream::new(requests_rx)).await?.into_inner();
+        info!("HDS stream established");
+        *backoff = INITIAL_BACKOFF;
+
+        let mut reporting: Option<(Duration, Instant)> = None;
+        loop {
+            let next_report = reporting.map(|(_, next_report)| next_report);
+            tokio::select! {
+                specifier = responses.message() => {
+                    let Some(specifier) = specifier? else {
+                        warn!("HDS stream has ended");
+                        return Ok(());
+                    };
+                    let HealthCheckSpecifier { cluster_health_checks, interval } = specifier;
+                    let interval = Self::interval(interval);
+                    // checking the same endpoints anew would have them reported unhealthy until checked again
+                    if self.assigned.as_ref() == Some(&cluster_health_checks) {
+                        debug!("HDS server assigns the same clusters again");
+                    } else {
+                        debug!("HDS server assigns {} clusters", cluster_health_checks.len());
+                        self.health_checks.assign(cluster_health_checks.clone());
+                        self.assigned = Some(cluster_health_checks);
+                    }
+                    if reporting.is_none_or(|(reported_every, _)| reported_every != interval) {
+                        debug!("reporting the health of the endpoints assigned every {interval:?}");
+                        reporting = Some((interval, Instant::now() + interval));
+                    }
+                }
+                () = time::sleep_until(next_report.unwrap_or_else(Instant::now)), if next_report.is_some() => {
+                    if let Some((interval, next_report)) = &mut reporting {
+                        let cluster_endpoints_health = self.health_checks.report();
+                        debug!("reporting the health of the endpoints of {} clusters", cluster_endpoints_health.len());
+                        let response = EndpointHealthResponse { cluster_endpoints_health, ..Default::default() };
+                        requests_tx
+                            .send(HealthCheckRequestOrEndpointHealthResponse {
+                                request_type: Some(RequestType::EndpointHealthResponse(response)),
+                            })
+                            .await
+                            .map_err(|e| XdsError::InternalProcessingError(e.to_string()))?;
+                        *next_report += *interval;
+                    }
+                }
+            }
+        }
+    }
+
+    /// How often the server wants to hear about the endpoints it assigned.
+    fn interval(interval: Option<ProtobufDuration>) -> Duration {
+        interval
+            .and_then(|interval| grpc::Duration::try_from(interval).ok())
+            .map(|interval| interval.0)
+            .filter(|interval| !interval.is_zero())
+            .unwrap_or(DEFAULT_HEALTH_REPORTING_INTERVAL)
+    }
+}
+
+/// The interval used if the server doesn't set one.
+const DEFAULT_HEALTH_REPORTING_INTERVAL: Duration = Duration::from_secs(1);
@@ -0,0 +1,174 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! Reports the load of the upstream clusters to a load reporting service (LRS) server.
+//!
+//! The server tells the client which clusters it wants the load of and how often, and the client sends it the load
+//! of those clusters since the previous report at that interval, until the server changes its mind.
+
+use std::time::Duration;
+
+use orion_configuration::config::{bootstrap::Node, grpc};
+use orion_data_plane_api::envoy_data_plane_api::{
+    envoy::{
+        config::{core::v3::Node as EnvoyNode, endpoint::v3::ClusterStats},
+        service::load_stats::v3::{
+            load_reporting_service_client::LoadReportingServiceClient, LoadStatsRequest, LoadStatsResponse,
+        },
+    },
+    tonic::{self, codegen::StdError},
+};
+use tokio::{
+    sync::mpsc,
+    time::{self, Instant},
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, info, warn};
+
+use super::{
+    client::{back_off, INITIAL_BACKOFF, MAX_BACKOFF},
+    model::XdsError,
+};
+
+/// Tells the server the client reports the load of all of the clusters when asked to.
+const SUPPORTS_SEND_ALL_CLUSTERS: &str = "envoy.lrs.supports_send_all_clusters";
+
+/// The clusters the server wants the load of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportedClusters {
+    All,
+    Only(Vec<String>),
+}
+
+impl ReportedClusters {
+    pub fn names(&self) -> Option<&[String]> {
+        match self {
+            Self::All => None,
+            Self::Only(names) => Some(names),
+        }
+    }
+}
+
+/// Measures the load reported to the server.
+pub trait LoadSource: Send {
+    /// Starts measuring the load from now on, the load before a stream is established being of no use to the server.
+    fn reset(&mut self);
+    /// The load of the clusters since the previous report.
+    fn report(&mut self, clusters: &ReportedClusters) -> Vec<ClusterStats>;
+}
+
+/// What the server last asked the client for.
+struct Reporting {
+    clusters: ReportedClusters,
+    interval: Duration,
+    next_report: Instant,
+}
+
+#[derive(Debug)]
+pub struct LoadReportingClient<C, S> {
+    node: Node,
+    client: LoadReportingServiceClient<C>,
+    source: S,
+    max_backoff: Duration,
+}
+
+impl<C, S> LoadReportingClient<C, S>
+where
+    C: tonic::client::GrpcService<tonic::body::Body> + Send,
+    C::Error: Into<StdError>,
+    C::ResponseBody: tonic::codegen::Body<Data = tonic::codegen::Bytes> + Send + 'static,
+    <C::ResponseBody as tonic::codegen::Body>::Error: Into<StdError> + Send,
+    C::Future: Send,
+    S: LoadSource,
+{
+    pub fn new(node: Node, client: LoadReportingServiceClient<C>, source: S) -> Self {
+        Self { node, client, source, max_backoff: MAX_BACKOFF }
+    }
+
+    #[must_use]
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Reports the load to the server, reconnecting whenever the stream ends.
+    pub async fn run(&mut self) -> Result<(), XdsError> {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            let result = self.report_load(&mut backoff).await;
+            back_off(result, &mut backoff, self.max_backoff).await;
+        }
+    }
+
+    async fn report_load(&mut self, backoff: &mut Duration) -> Result<(), XdsError> {
+        let (requests_tx, requests_rx) = mpsc::channel::<LoadStatsRequest>(16);
+        let node =
+            EnvoyNode { client_features: vec![SUPPORTS_SEND_ALL_CLUSTERS.to_owned()], ..EnvoyNode::from(&self.node) };
+        // the server only answers once it knows who the client is
+        requests_tx
+            .send(LoadStatsRequest { node: Some(node), cluster_stats: Vec::new() })
+            .await
+            .map_err(|e| XdsError::InternalProcessingError(e.to_string()))?;
+        let mut responses = self.client.stream_load_stats(ReceiverStream::new(requests_rx)).await?.into_inner();
+        info!("LRS stream established");
+        *backoff = INITIAL_BACKOFF;
+
+        let mut reporting: Option<Reporting> = None;
+        loop {
+            let next_report = reporting.as_ref().map(|reporting| reporting.next_report);
+            tokio::select! {
+                response = responses.message() => {
+                    let Some(response) = response? else {
+                        warn!("LRS stream has ended");
+                        return Ok(());
+                    };
+                    if reporting.is_none() {
+                        self.source.reset();
+                    }
+                    reporting = Some(Self::reporting(response));
+                }
+                () = time::sleep_until(next_report.unwrap_or_else(Instant::now)), if next_report.is_some() => {
+                    if let Some(reporting) = &mut reporting {
+                        let cluster_stats = self.source.report(&reporting.clusters);
+                        debug!("reporting the load of {} clusters", cluster_stats.len());
+                        requests_tx
+                            .send(LoadStatsRequest { node: None, cluster_stats })
+                            .await
+                            .map_err(|e| XdsError::InternalProcessingError(e.to_string()))?;
+                        reporting.next_report += reporting.interval;
+                    }
+                }
+            }
+        }
+    }
+
+    /// What the server asks for, counting the interval from now on.
+    fn reporting(response: LoadStatsResponse) -> Reporting {
+        let LoadStatsResponse { clusters, send_all_clusters, load_reporting_interval, .. } = response;
+        let clusters = if send_all_clusters { ReportedClusters::All } else { ReportedClusters::Only(clusters) };
+        let interval = load_reporting_interval
+            .and_then(|interval| grpc::Duration::try_from(interval).ok())
+            .map(|interval| interval.0)
+            .filter(|interval| !interval.is_zero())
+            .unwrap_or(DEFAULT_LOAD_REPORTING_INTERVAL);
+        debug!("LRS server asks for the load of {clusters:?} every {interval:?}");
+        Reporting { clusters, interval, next_report: Instant::now() + interval }
+    }
+}
+
+/// The interval used if the server doesn't set one.
+const DEFAULT_LOAD_REPORTING_INTERVAL: Duration = Duration::from_secs(10);
@@ -15,8 +15,12 @@
 //
 //
 
+pub mod accepted;
 pub mod bindings;
 pub mod client;
+pub mod client_status;
+pub mod health_discovery;
+pub mod load_stats;
 pub mod model;
 
 mod request;
@@ -18,18 +18,30 @@
 use core::result::Result::Err;
 
 use orion_configuration::config::{
-    cluster::ClusterLoadAssignment, network_filters::http_connection_manager::RouteConfiguration, secret::Secret,
+    cluster::ClusterLoadAssignment,
+    extension_config::ExtensionConfig,
+    network_filters::http_connection_manager::{
+        scoped_routes::ScopedRouteConfiguration, RouteConfiguration, VirtualHost,
+    },
+    secret::Secret,
     Cluster, GenericError, Listener,
 };
 use orion_data_plane_api::envoy_data_plane_api::{
     envoy::{
         config::{
-            cluster::v3::Cluster as EnvoyCluster, endpoint::v3::ClusterLoadAssignment as EnvoyClusterLoadAssignment,
-            listener::v3::Listener as EnvoyListener, route::v3::RouteConfiguration as EnvoyRouteConfiguration,
+            cluster::v3::Cluster as EnvoyCluster,
+            core::v3::TypedExtensionConfig as EnvoyTypedExtensionConfig,
+            endpoint::v3::ClusterLoadAssignment as EnvoyClusterLoadAssignment,
+            listener::v3::Listener as EnvoyListener,
+            route::v3::{
+                RouteConfiguration as EnvoyRouteConfiguration,
+                ScopedRouteConfiguration as EnvoyScopedRouteConfiguration, VirtualHost as EnvoyVirtualHost,
+            },
         },
         extensions::transport_sockets::tls::v3::Secret as EnvoySecret,
         service::discovery::v3::Resource,
     },
+    google::protobuf::Any,
     prost,
     prost::Message,
     tonic,
@@ -66,6 +78,25 @@ pub enum XdsResourcePayload {
     Endpoints(ResourceId, ClusterLoadAssignment),
     RouteConfiguration(ResourceId, RouteConfiguration),
     Secret(ResourceId, Secret),
+    ExtensionConfig(ResourceId, ExtensionConfig),
+    /// A virtual host discovered over VHDS, with the names it was asked for on demand under.
+    VirtualHost(ResourceId, VirtualHost, Vec<ResourceId>),
+    ScopedRoute(ResourceId, ScopedRouteConfiguration),
+}
+
+impl XdsResourcePayload {
+    pub fn type_url(&self) -> TypeUrl {
+        match self {
+            XdsResourcePayload::Listener(..) => TypeUrl::Listener,
+            XdsResourcePayload::Cluster(..) => TypeUrl::Cluster,
+            XdsResourcePayload::Endpoints(..) => TypeUrl::ClusterLoadAssignment,
+            XdsResourcePayload::RouteConfiguration(..) => TypeUrl::RouteConfiguration,
+            XdsResourcePayload::Secret(..) => TypeUrl::Secret,
+            XdsResourcePayload::ExtensionConfig(..) => TypeUrl::TypedExtensionConfig,
+            XdsResourcePayload::VirtualHost(..) => TypeUrl::VirtualHost,
+            XdsResourcePayload::ScopedRoute(..) => TypeUrl::ScopedRouteConfiguration,
+        }
+    }
 }
 
 impl TryFrom<(Resource, TypeUrl)> for XdsResourcePayload {
@@ -73,6 +104,7 @@ impl TryFrom<(Resource, TypeUrl)> for XdsResourcePayload {
 
     fn try_from((resource, type_url): (Resource, TypeUrl)) -> Result<XdsResourcePayload, XdsError> {
         let resource_id = resource.name;
+        let aliases = resource.aliases;
         resource.resource.ok_or(XdsError::MissingResource()).and_then(|res| match type_url {
             TypeUrl::Listener => {
                 let decoded = EnvoyListener::decode(res.value.as_slice())?.try_into()?;
@@ -94,17 +126,47 @@ impl TryFrom<(Resource, TypeUrl)> for XdsResourcePayload {
                 let decoded = EnvoySecret::decode(res.value.as_slice())?.try_into()?;
                 Ok(XdsResourcePayload::Secret(resource_id, decoded))
             },
+            TypeUrl::TypedExtensionConfig => {
+                let decoded = EnvoyTypedExtensionConfig::decode(res.value.as_slice())?.try_into()?;
+                Ok(XdsResourcePayload::ExtensionConfig(resource_id, decoded))
+            },
+            TypeUrl::VirtualHost => {
+                let decoded = EnvoyVirtualHost::decode(res.value.as_slice())?.try_into()?;
+                Ok(XdsResourcePayload::VirtualHost(resource_id, decoded, aliases))
+            },
+            TypeUrl::ScopedRouteConfiguration => {
+                let decoded = EnvoyScopedRouteConfiguration::decode(res.value.as_slice())?.try_into()?;
+                Ok(XdsResourcePayload::ScopedRoute(resource_id, decoded))
+            },
         })
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Debug, Copy, Clone, Deserialize)]
+/// The name of a resource received without it, as the resources of state-of-the-world responses are.
+pub fn resource_name(type_url: TypeUrl, resource: &Any) -> Result<ResourceId, XdsError> {
+    let value = resource.value.as_slice();
+    Ok(match type_url {
+        TypeUrl::Listener => EnvoyListener::decode(value)?.name,
+        TypeUrl::Cluster => EnvoyCluster::decode(value)?.name,
+        TypeUrl::RouteConfiguration => EnvoyRouteConfiguration::decode(value)?.name,
+        TypeUrl::ClusterLoadAssignment => EnvoyClusterLoadAssignment::decode(value)?.cluster_name,
+        TypeUrl::Secret => EnvoySecret::decode(value)?.name,
+        TypeUrl::TypedExtensionConfig => EnvoyTypedExtensionConfig::decode(value)?.name,
+        TypeUrl::VirtualHost => EnvoyVirtualHost::decode(value)?.name,
+        TypeUrl::ScopedRouteConfiguration => EnvoyScopedRouteConfiguration::decode(value)?.name,
+    })
+}
+
+#[derive(Eq, Hash, PartialEq, PartialOrd, Ord, Debug, Copy, Clone, Deserialize)]
 pub enum TypeUrl {
     Listener,
     Cluster,
     RouteConfiguration,
     ClusterLoadAssignment,
     Secret,
+    TypedExtensionConfig,
+    VirtualHost,
+    ScopedRouteConfiguration,
 }
 
 impl fmt::Display for TypeUrl {
@@ -120,6 +182,11 @@ impl fmt::Display for TypeUrl {
                 TypeUrl::ClusterLoadAssignment =>
                     "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment".to_owned(),
                 TypeUrl::Secret => "type.googleapis.com/envoy.extensions.transport_sockets.tls.v3.Secret".to_owned(),
+                TypeUrl::TypedExtensionConfig =>
+                    "type.googleapis.com/envoy.config.core.v3.TypedExtensionConfig".to_owned(),
+                TypeUrl::VirtualHost => "type.googleapis.com/envoy.config.route.v3.VirtualHost".to_owned(),
+                TypeUrl::ScopedRouteConfiguration =>
+                    "type.googleapis.com/envoy.config.route.v3.ScopedRouteConfiguration".to_owned(),
             }
         )
     }
@@ -135,6 +202,11 @@ impl TryFrom<&str> for TypeUrl {
             "type.googleapis.com/envoy.config.route.v3.RouteConfiguration" => Ok(TypeUrl::RouteConfiguration),
             "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment" => Ok(TypeUrl::ClusterLoadAssignment),
             "type.googleapis.com/envoy.extensions.transport_sockets.tls.v3.Secret" => Ok(TypeUrl::Secret),
+            "type.googleapis.com/envoy.config.core.v3.TypedExtensionConfig" => Ok(TypeUrl::TypedExtensionConfig),
+            "type.googleapis.com/envoy.config.route.v3.VirtualHost" => Ok(TypeUrl::VirtualHost),
+            "type.googleapis.com/envoy.config.route.v3.ScopedRouteConfiguration" => {
+                Ok(TypeUrl::ScopedRouteConfiguration)
+            },
             value => Err(XdsError::UnknownResourceType(value.to_owned())),
         }
     }
@@ -150,6 +222,15 @@ impl<E: Into<orion_error::Error>> From<(ResourceId, E)> for RejectedConfig {
         RejectedConfig { name: context.0, reason: context.1.into() }
     }
 }
+impl RejectedConfig {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn reason(&self) -> &orion_error::Error {
+        &self.reason
+    }
+}
 impl Display for RejectedConfig {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(f, "{}: {}", self.name, self.reason)
@@ -19,7 +19,10 @@ use super::model::{ResourceId, ResourceVersion, TypeUrl};
 
 use orion_configuration::config::bootstrap::Node;
 use orion_data_plane_api::envoy_data_plane_api::{
-    envoy::{config::core::v3::Node as EnvoyNode, service::discovery::v3::DeltaDiscoveryRequest},
+    envoy::{
+        config::core::v3::Node as EnvoyNode,
+        service::discovery::v3::{DeltaDiscoveryRequest, DiscoveryRequest},
+    },
     google::rpc::Status,
     tonic,
 };
@@ -108,10 +111,9 @@ impl DeltaDiscoveryRequestBuilder {
     }
 
     pub fn build(self) -> DeltaDiscoveryRequest {
-        let Node { id, cluster_id, metadata } = self.node.unwrap_or_default();
         let nounce = self.nounce.unwrap_or_default();
         DeltaDiscoveryRequest {
-            node: Some(EnvoyNode { id: id.into(), cluster: cluster_id.into(), metadata, ..Default::default() }),
+            node: Some(EnvoyNode::from(&self.node.unwrap_or_default())),
             response_nonce: nounce,
             type_url: self.type_url.to_string(),
             resource_names_subscribe: self.resource_names_subscribe,
@@ -122,3 +124,64 @@ impl DeltaDiscoveryRequestBuilder {
         }
     }
 }
+
+pub struct DiscoveryRequestBuilder {
+    node: Option<Node>,
+    nonce: Option<String>,
+    type_url: TypeUrl,
+    version_info: ResourceVersion,
+    error_detail: Option<Status>,
+    resource_names: Vec<ResourceId>,
+}
+
+impl DiscoveryRequestBuilder {
+    pub fn for_resource(type_url: TypeUrl) -> Self {
+        DiscoveryRequestBuilder {
+            node: None,
+            nonce: None,
+            type_url,
+            version_info: ResourceVersion::new(),
+            error_detail: None,
+            resource_names: Vec::new(),
+        }
+    }
+
+    pub fn with_node_id(mut self, node: Node) -> Self {
+        self.node = Some(node);
+        self
+    }
+
+    pub fn with_nonce(mut self, nonce: String) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// The version of the last accepted response of this type.
+    pub fn with_version_info(mut self, version_info: ResourceVersion) -> Self {
+        self.version_info = version_info;
+        self
+    }
+
+    /// The resources the client is interested in, every resource of the type when empty.
+    pub fn with_resource_names(mut self, resource_names: Vec<ResourceId>) -> Self {
+        self.resource_names = resource_names;
+        self
+    }
+
+    pub fn with_error_detail(mut self, error_detail: Option<Status>) -> Self {
+        self.error_detail = error_detail;
+        self
+    }
+
+    pub fn build(self) -> DiscoveryRequest {
+        DiscoveryRequest {
+            version_info: self.version_info,
+            node: Some(EnvoyNode::from(&self.node.unwrap_or_default())),
+            resource_names: self.resource_names,
+            type_url: self.type_url.to_string(),
+            response_nonce: self.nonce.unwrap_or_default(),
+            error_detail: self.error_detail,
+            ..Default::default()
+        }
+    }
+}
@@ -15,40 +15,48 @@
 //
 //
 
-use std::{net::SocketAddr, pin::Pin};
+//! An aggregated discovery server serving many clients, over delta and state-of-the-world streams, from the
+//! snapshots of a [`SnapshotCache`].
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+};
 
-use atomic_take::AtomicTake;
 use orion_data_plane_api::envoy_data_plane_api::{
-    envoy::service::discovery::v3::{
-        aggregated_discovery_service_server::{AggregatedDiscoveryService, AggregatedDiscoveryServiceServer},
-        DeltaDiscoveryRequest, DeltaDiscoveryResponse, DiscoveryRequest, DiscoveryResponse, Resource, ResourceName,
+    envoy::{
+        config::core::v3::Node,
+        service::discovery::v3::{
+            aggregated_discovery_service_server::{AggregatedDiscoveryService, AggregatedDiscoveryServiceServer},
+            DeltaDiscoveryRequest, DeltaDiscoveryResponse, DiscoveryRequest, DiscoveryResponse, ResourceName,
+        },
     },
-    tonic::{self, transport::Server, IntoStreamingRequest, Response, Status},
+    tonic::{self, transport::Server, Response, Status},
 };
-use tokio::sync::mpsc::{self, Receiver};
+use tokio::sync::mpsc;
 use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
-use tracing::info;
+use tracing::{debug, info, warn};
 
-use crate::xds::{self, model::XdsError};
+use crate::xds::model::{TypeUrl, XdsError};
 
-pub enum ServerAction {
-    Add(Resource),
-    Remove(Resource),
-}
+mod cache;
+use cache::SnapshotWatch;
+pub use cache::{Nack, NodeHash, NodeStatus, Resources, Snapshot, SnapshotCache};
+mod health_discovery;
+pub use health_discovery::HealthDiscoveryServer;
+mod load_stats;
+pub use load_stats::LoadReportingServer;
 
-pub type ResourceAction = ServerAction;
 #[derive(Debug)]
 pub struct AggregateServer {
-    delta_resources_rx: AtomicTake<Receiver<ServerAction>>,
-    stream_resources_rx: AtomicTake<Receiver<ServerAction>>,
+    cache: Arc<SnapshotCache>,
 }
 
 impl AggregateServer {
-    pub fn new(delta_resources_rx: Receiver<ServerAction>, stream_resources_rx: Receiver<ServerAction>) -> Self {
-        Self {
-            delta_resources_rx: AtomicTake::new(delta_resources_rx),
-            stream_resources_rx: AtomicTake::new(stream_resources_rx),
-        }
+    pub fn new(cache: Arc<SnapshotCache>) -> Self {
+        Self { cache }
     }
 }
 
@@ -63,59 +71,11 @@ impl AggregatedDiscoveryService for AggregateServer {
         &self,
         req: tonic::Request<tonic::Streaming<DiscoveryRequest>>,
     ) -> AggregatedDiscoveryServiceResult<Self::StreamAggregatedResourcesStream> {
-        info!("AggregateServer::stream_aggregated_resources");
-        info!("\tclient connected from: {:?}", req.remote_addr());
-
+        info!("client connected from: {:?}", req.remote_addr());
+        // spawn and channel are required if you want handle "disconnect" functionality
+        // the `out_stream` will not be polled after client disconnect
         let (tx, rx) = mpsc::channel(128);
-        let mut resources_rx =
-            self.stream_resources_rx.take().ok_or(Status::internal("Resource stream is unavailable"))?;
-        tokio::spawn(async move {
-            while let Some(action) = resources_rx.recv().await {
-                let item = match action {
-                    xds::server::ServerAction::Add(resource) => {
-                        let Some(resource) = resource.resource else {
-                            continue;
-                        };
-                        DiscoveryResponse {
-                            type_url: resource.type_url.clone(),
-                            resources: vec![resource],
-                            nonce: uuid::Uuid::new_v4().to_string(),
-                            ..Default::default()
-                        }
-                    },
-                    xds::server::ServerAction::Remove(resource) => {
-                        let Some(resource) = resource.resource else {
-                            continue;
-                        };
-                        DiscoveryResponse {
-                            type_url: resource.type_url,
-                            nonce: uuid::Uuid::new_v4().to_string(),
-                            ..Default::default()
-                        }
-                    },
-                };
-
-                match tx.send(std::result::Result::<_, Status>::Ok(item)).await {
-                    Ok(()) => {
-                        // item (server response) was queued to be send to client
-                    },
-                    Err(_item) => {
-                        // output_stream was build from rx and both are dropped
-                        break;
-                    },
-                }
-                info!("\tclient disconnected");
-            }
-        });
-
-        let mut incoming_stream = req.into_streaming_request().into_inner();
-        tokio::spawn(async move {
-            while let Some(item) = incoming_stream.next().await {
-                info!("Sever : Got item {item:?}");
-            }
-            info!("Sever sice closed");
-        });
-
+        tokio::spawn(serve(Arc::clone(&self.cache), req.into_inner(), tx, SotwStream::default()));
         let output_stream = ReceiverStream::new(rx);
         Ok(Response::new(Box::pin(output_stream) as Self::StreamAggregatedResourcesStream))
     }
@@ -127,82 +87,351 @@ impl AggregatedDiscoveryService for AggregateServer {
         &self,
         req: tonic::Request<tonic::Streaming<DeltaDiscoveryRequest>>,
     ) -> AggregatedDiscoveryServiceResult<Self::DeltaAggregatedResourcesStream> {
-        info!("AggregateServer::delta_aggregated_resources");
-        info!("\tclient connected from: {:?}", req.remote_addr());
-
-        // spawn and channel are required if you want handle "disconnect" functionality
-        // the `out_stream` will not be polled after client disconnect
+        info!("client connected from: {:?}", req.remote_addr());
         let (tx, rx) = mpsc::channel(128);
-        let mut resources_rx = self.delta_resources_rx.take().ok_or(Status::internal("Delta stream is unavailable"))?;
-        tokio::spawn(async move {
-            while let Some(action) = resources_rx.recv().await {
-                let item = match action {
-                    xds::server::ServerAction::Add(r) => {
-                        let Some(ref resource) = r.resource else {
-                            continue;
-                        };
-                        DeltaDiscoveryResponse {
-                            type_url: resource.type_url.clone(),
-                            resources: vec![r],
-                            nonce: uuid::Uuid::new_v4().to_string(),
-                            system_version_info: "system_version_info".to_owned(),
-                            ..Default::default()
-                        }
-                    },
-                    xds::server::ServerAction::Remove(r) => {
-                        let Some(resource) = r.resource else {
-                            continue;
-                        };
-                        DeltaDiscoveryResponse {
-                            type_url: resource.type_url.clone(),
-                            nonce: uuid::Uuid::new_v4().to_string(),
-                            system_version_info: "system_version_info".to_owned(),
-                            removed_resource_names: vec![ResourceName {
-                                name: r.name.clone(),
-                                dynamic_parameter_constraints: None,
-                            }],
-                            removed_resources: vec![r.name],
-                            ..Default::default()
-                        }
-                    },
-                };
+        tokio::spawn(serve(Arc::clone(&self.cache), req.into_inner(), tx, DeltaStream::default()));
+        let output_stream = ReceiverStream::new(rx);
+        Ok(Response::new(Box::pin(output_stream) as Self::DeltaAggregatedResourcesStream))
+    }
+}
 
-                match tx.send(std::result::Result::<_, Status>::Ok(item)).await {
-                    Ok(()) => {
-                        // item (server response) was queued to be send to client
-                    },
-                    Err(_item) => {
-                        // output_stream was build from rx and both are dropped
+/// What a stream of either protocol has sent its client, and what the client subscribed to.
+trait StreamState: Send + 'static {
+    type Request: Send;
+    type Response: Send;
+
+    fn node(request: &Self::Request) -> Option<&Node>;
+
+    /// Takes a request in, returning the response to it, if there is anything to send.
+    fn request(&mut self, watch: &SnapshotWatch, request: Self::Request) -> Option<Self::Response>;
+
+    /// The responses sending what changed with a new snapshot.
+    fn snapshot_changed(&mut self, snapshot: &Snapshot) -> Vec<Self::Response>;
+}
+
+enum StreamEvent<T> {
+    Request(Option<std::result::Result<T, Status>>),
+    SnapshotChanged,
+}
+
+async fn serve<S: StreamState>(
+    cache: Arc<SnapshotCache>,
+    mut requests: tonic::Streaming<S::Request>,
+    responses: mpsc::Sender<std::result::Result<S::Response, Status>>,
+    mut state: S,
+) {
+    let mut watch: Option<SnapshotWatch> = None;
+    loop {
+        let event = tokio::select! {
+            request = requests.next() => StreamEvent::Request(request),
+            Some(Ok(())) = async {
+                match watch.as_mut() {
+                    Some(watch) => Some(watch.snapshot.changed().await),
+                    None => None,
+                }
+            } => StreamEvent::SnapshotChanged,
+        };
+        let pending = match event {
+            StreamEvent::Request(Some(Ok(request))) => {
+                let watch = if let Some(watch) = &watch {
+                    watch
+                } else {
+                    let Some(node) = S::node(&request) else {
+                        let _ = responses
+                            .send(Err(Status::invalid_argument("the first request must identify the node")))
+                            .await;
                         break;
+                    };
+                    let new_watch = cache.watch(node);
+                    debug!("stream opened for node {}", new_watch.node_id);
+                    watch.insert(new_watch)
+                };
+                state.request(watch, request).into_iter().collect()
+            },
+            StreamEvent::Request(Some(Err(status))) => {
+                warn!("stream failed: {status}");
+                break;
+            },
+            StreamEvent::Request(None) => break,
+            StreamEvent::SnapshotChanged => {
+                let snapshot = watch.as_mut().and_then(|watch| watch.snapshot.borrow_and_update().clone());
+                snapshot.map(|snapshot| state.snapshot_changed(&snapshot)).unwrap_or_default()
+            },
+        };
+        for response in pending {
+            if responses.send(Ok(response)).await.is_err() {
+                info!("client disconnected");
+                return;
+            }
+        }
+    }
+    info!("client disconnected");
+}
+
+fn type_url_of(type_url: &str) -> Option<TypeUrl> {
+    TypeUrl::try_from(type_url).inspect_err(|err| warn!("ignoring request: {err}")).ok()
+}
+
+/// A state-of-the-world stream, sending all the resources of a type whenever its version changes.
+#[derive(Default)]
+struct SotwStream {
+    types: HashMap<TypeUrl, SotwSubscription>,
+}
+
+#[derive(Default)]
+struct SotwSubscription {
+    /// The names subscribed to, all of them if empty.
+    names: BTreeSet<String>,
+    version: Option<String>,
+    nonce: String,
+}
+
+impl SotwSubscription {
+    fn respond(&mut self, type_url: TypeUrl, snapshot: &Snapshot) -> Option<DiscoveryResponse> {
+        let resources = snapshot.resources(type_url)?;
+        if self.version.as_ref() == Some(&resources.version) {
+            return None;
+        }
+        let nonce = uuid::Uuid::new_v4().to_string();
+        let response = DiscoveryResponse {
+            version_info: resources.version.clone(),
+            resources: resources
+                .items
+                .values()
+                .filter(|resource| self.names.is_empty() || self.names.contains(&resource.name))
+                .filter_map(|resource| resource.resource.clone())
+                .collect(),
+            type_url: type_url.to_string(),
+            nonce: nonce.clone(),
+            ..Default::default()
+        };
+        self.version = Some(resources.version.clone());
+        self.nonce = nonce;
+        Some(response)
+    }
+}
+
+impl StreamState for SotwStream {
+    type Request = DiscoveryRequest;
+    type Response = DiscoveryResponse;
+
+    fn node(request: &DiscoveryRequest) -> Option<&Node> {
+        request.node.as_ref()
+    }
+
+    fn request(&mut self, watch: &SnapshotWatch, request: DiscoveryRequest) -> Option<DiscoveryResponse> {
+        let type_url = type_url_of(&request.type_url)?;
+        let subscription = self.types.entry(type_url).or_default();
+        if !request.response_nonce.is_empty() {
+            if request.response_nonce != subscription.nonce {
+                debug!("ignoring request for {type_url} answering stale nonce {}", request.response_nonce);
+                return None;
+            }
+            match request.error_detail {
+                Some(error) => watch.nacked(
+                    type_url,
+                    Nack {
+                        version: subscription.version.clone().unwrap_or_default(),
+                        nonce: request.response_nonce,
+                        message: error.message,
                     },
-                }
+                ),
+                None => watch.acked(type_url),
             }
-            info!("\tclient disconnected");
-        });
+        }
+        let names: BTreeSet<_> = request.resource_names.into_iter().collect();
+        let names = if names.contains("*") { BTreeSet::new() } else { names };
+        if names != subscription.names {
+            subscription.names = names;
+            subscription.version = None;
+        }
+        let snapshot = watch.snapshot.borrow().clone()?;
+        subscription.respond(type_url, &snapshot)
+    }
+
+    fn snapshot_changed(&mut self, snapshot: &Snapshot) -> Vec<DiscoveryResponse> {
+        self.types.iter_mut().filter_map(|(type_url, subscription)| subscription.respond(*type_url, snapshot)).collect()
+    }
+}
+
+/// A delta stream, sending the resources of a type that changed, and the names of the ones removed.
+#[derive(Default)]
+struct DeltaStream {
+    types: HashMap<TypeUrl, DeltaSubscription>,
+}
 
-        let mut incoming_stream = req.into_streaming_request().into_inner();
-        tokio::spawn(async move {
-            while let Some(Ok(item)) = incoming_stream.next().await {
-                info!("Sever : Got item {item:?}");
+#[derive(Default)]
+struct DeltaSubscription {
+    wildcard: bool,
+    names: BTreeSet<String>,
+    /// The versions of the resources the client has.
+    sent: HashMap<String, String>,
+    /// The version of the resources last sent.
+    version: String,
+    nonce: String,
+}
+
+impl DeltaSubscription {
+    fn new(request: &DeltaDiscoveryRequest) -> Self {
+        Self {
+            wildcard: request.resource_names_subscribe.is_empty(),
+            sent: request.initial_resource_versions.clone().into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn update(&mut self, subscribe: Vec<String>, unsubscribe: Vec<String>) {
+        for name in subscribe {
+            if name == "*" {
+                self.wildcard = true;
+            } else {
+                self.names.insert(name);
             }
-            info!("Sever side closed");
-        });
+        }
+        for name in unsubscribe {
+            if name == "*" {
+                self.wildcard = false;
+            } else {
+                self.sent.remove(&name);
+                self.names.remove(&name);
+            }
+        }
+    }
 
-        let output_stream = ReceiverStream::new(rx);
-        Ok(Response::new(Box::pin(output_stream) as Self::DeltaAggregatedResourcesStream))
+    fn diff(&mut self, type_url: TypeUrl, snapshot: &Snapshot) -> Option<DeltaDiscoveryResponse> {
+        let resources = snapshot.resources(type_url);
+        let items = resources.map(|resources| &resources.items);
+        let updated: Vec<_> = items
+            .into_iter()
+            .flat_map(|items| items.values())
+            .filter(|resource| {
+                (self.wildcard || self.names.contains(&resource.name))
+                    && self.sent.get(&resource.name) != Some(&resource.version)
+            })
+            .cloned()
+            .collect();
+        let removed: Vec<_> =
+            self.sent.keys().filter(|name| !items.is_some_and(|items| items.contains_key(*name))).cloned().collect();
+        if updated.is_empty() && removed.is_empty() {
+            return None;
+        }
+        for name in &removed {
+            self.sent.remove(name);
+        }
+        for resource in &updated {
+            self.sent.insert(resource.name.clone(), resource.version.clone());
+        }
+        self.version = resources.map(|resources| resources.version.clone()).unwrap_or_default();
+        self.nonce = uuid::Uuid::new_v4().to_string();
+        Some(DeltaDiscoveryResponse {
+            system_version_info: self.version.clone(),
+            resources: updated,
+            type_url: type_url.to_string(),
+            removed_resource_names: removed
+                .iter()
+                .map(|name| ResourceName { name: name.clone(), dynamic_parameter_constraints: None })
+                .collect(),
+            removed_resources: removed,
+            nonce: self.nonce.clone(),
+            ..Default::default()
+        })
+    }
+}
+
+impl StreamState for DeltaStream {
+    type Request = DeltaDiscoveryRequest;
+    type Response = DeltaDiscoveryResponse;
+
+    fn node(request: &DeltaDiscoveryRequest) -> Option<&Node> {
+        request.node.as_ref()
+    }
+
+    fn request(&mut self, watch: &SnapshotWatch, request: DeltaDiscoveryRequest) -> Option<DeltaDiscoveryResponse> {
+        let type_url = type_url_of(&request.type_url)?;
+        let subscription = self.types.entry(type_url).or_insert_with(|| DeltaSubscription::new(&request));
+        if !request.response_nonce.is_empty() {
+            if request.response_nonce == subscription.nonce {
+                match request.error_detail {
+                    Some(error) => watch.nacked(
+                        type_url,
+                        Nack {
+                            version: subscription.version.clone(),
+                            nonce: request.response_nonce,
+                            message: error.message,
+                        },
+                    ),
+                    None => watch.acked(type_url),
+                }
+            } else {
+                // unlike in state-of-the-world, the subscription changes of the request still apply: they are
+                // never sent again
+                debug!("ignoring ack of {type_url} answering stale nonce {}", request.response_nonce);
+            }
+        }
+        subscription.update(request.resource_names_subscribe, request.resource_names_unsubscribe);
+        let snapshot = watch.snapshot.borrow().clone()?;
+        subscription.diff(type_url, &snapshot)
+    }
+
+    fn snapshot_changed(&mut self, snapshot: &Snapshot) -> Vec<DeltaDiscoveryResponse> {
+        self.types.iter_mut().filter_map(|(type_url, subscription)| subscription.diff(*type_url, snapshot)).collect()
     }
 }
 
-pub async fn start_aggregate_server(
-    addr: SocketAddr,
-    delta_resources_rx: Receiver<ResourceAction>,
-    stream_resources_rx: Receiver<ResourceAction>,
-) -> Result<(), XdsError> {
+pub async fn start_aggregate_server(addr: SocketAddr, cache: Arc<SnapshotCache>) -> Result<(), XdsError> {
     info!("Server started {addr:?}");
-    let server = AggregateServer::new(delta_resources_rx, stream_resources_rx);
+    let server = AggregateServer::new(cache);
     let aggregate_server = AggregatedDiscoveryServiceServer::new(server);
     let server =
         Server::builder().concurrency_limit_per_connection(256).add_service(aggregate_server).serve(addr).await;
     info!("Server exited {server:?}");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_data_plane_api::envoy_data_plane_api::{
+        envoy::service::discovery::v3::Resource,
+        google::{protobuf::Any, rpc::Status as RpcStatus},
+    };
+
+    fn cluster(name: &str, value: &[u8]) -> Resource {
+        Resource {
+            name: name.to_owned(),
+            resource: Some(Any { type_url: TypeUrl::Cluster.to_string(), value: value.to_vec() }),
+            ..Default::default()
+        }
+    }
+
+    fn nack(nonce: String) -> DeltaDiscoveryRequest {
+        DeltaDiscoveryRequest {
+            type_url: TypeUrl::Cluster.to_string(),
+            response_nonce: nonce,
+            error_detail: Some(RpcStatus { message: "invalid cluster".to_owned(), ..Default::default() }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn delta_stream_ignores_acks_of_stale_nonces() {
+        let cache = Arc::new(SnapshotCache::new());
+        cache.set_snapshot("node", Snapshot::new().with_resources(TypeUrl::Cluster, "1", [cluster("a", b"1")]));
+        let watch = cache.watch(&Node { id: "node".to_owned(), ..Default::default() });
+        let mut stream = DeltaStream::default();
+
+        let request = DeltaDiscoveryRequest { type_url: TypeUrl::Cluster.to_string(), ..Default::default() };
+        let stale = stream.request(&watch, request).unwrap().nonce;
+        cache.set_snapshot("node", Snapshot::new().with_resources(TypeUrl::Cluster, "2", [cluster("a", b"2")]));
+        let [current] = stream.snapshot_changed(&watch.snapshot.borrow().clone().unwrap()).try_into().unwrap();
+
+        // still subscribing to what the stale request asks for
+        let subscribe = DeltaDiscoveryRequest { resource_names_subscribe: vec!["b".to_owned()], ..nack(stale) };
+        assert!(stream.request(&watch, subscribe).is_none());
+        assert!(cache.status("node").unwrap().nacks.is_empty());
+        assert!(stream.types[&TypeUrl::Cluster].names.contains("b"));
+
+        assert!(stream.request(&watch, nack(current.nonce.clone())).is_none());
+        assert_eq!(cache.status("node").unwrap().nacks[&("node".to_owned(), TypeUrl::Cluster)].nonce, current.nonce);
+    }
+}
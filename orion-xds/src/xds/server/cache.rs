@@ -0,0 +1,322 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! The resources served to each node, as versioned snapshots.
+//!
+//! A snapshot holds every resource of every type a node is served, each type under its own version. The streams
+//! of a node watch its snapshot and send their clients what changed whenever it is replaced.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use orion_data_plane_api::envoy_data_plane_api::envoy::{config::core::v3::Node, service::discovery::v3::Resource};
+use parking_lot::Mutex;
+use tokio::sync::watch;
+
+use crate::xds::model::{ResourceId, TypeUrl};
+
+/// Tells which snapshot serves a node, by the key it is set under.
+pub trait NodeHash: Send + Sync {
+    fn hash(&self, node: &Node) -> String;
+}
+
+impl<F> NodeHash for F
+where
+    F: Fn(&Node) -> String + Send + Sync,
+{
+    fn hash(&self, node: &Node) -> String {
+        self(node)
+    }
+}
+
+/// The resources of one type in a snapshot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Resources {
+    /// The version reported to state-of-the-world clients.
+    pub version: String,
+    /// The resources by name, each with the version reported to delta clients.
+    pub items: BTreeMap<ResourceId, Resource>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    resources: BTreeMap<TypeUrl, Resources>,
+}
+
+impl Snapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the resources of a type under a version. The resources without a version of their own are versioned
+    /// by their content, so that delta clients are only sent the ones that changed.
+    #[must_use]
+    pub fn with_resources(
+        mut self,
+        type_url: TypeUrl,
+        version: impl Into<String>,
+        resources: impl IntoIterator<Item = Resource>,
+    ) -> Self {
+        let items = resources
+            .into_iter()
+            .map(|mut resource| {
+                if resource.version.is_empty() {
+                    let mut hasher = DefaultHasher::new();
+                    resource.resource.as_ref().map(|any| &any.value).hash(&mut hasher);
+                    resource.version = format!("{:016x}", hasher.finish());
+                }
+                (resource.name.clone(), resource)
+            })
+            .collect();
+        self.resources.insert(type_url, Resources { version: version.into(), items });
+        self
+    }
+
+    /// The resources of a type, if the snapshot has any version of them.
+    pub fn resources(&self, type_url: TypeUrl) -> Option<&Resources> {
+        self.resources.get(&type_url)
+    }
+
+    pub fn version(&self, type_url: TypeUrl) -> Option<&str> {
+        self.resources(type_url).map(|resources| resources.version.as_str())
+    }
+}
+
+/// A response a client rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nack {
+    /// The version of the resources rejected.
+    pub version: String,
+    pub nonce: String,
+    pub message: String,
+}
+
+/// How the clients of a node are doing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeStatus {
+    /// The streams open for the node.
+    pub streams: usize,
+    /// The last response of each type each client rejected, by the ID of the client's node, until the client accepts
+    /// one of that type. The clients of a node tell each other apart by their ID when they hash to the same node.
+    pub nacks: BTreeMap<(String, TypeUrl), Nack>,
+}
+
+struct NodeEntry {
+    snapshot: watch::Sender<Option<Arc<Snapshot>>>,
+    status: NodeStatus,
+}
+
+impl Default for NodeEntry {
+    fn default() -> Self {
+        Self { snapshot: watch::Sender::new(None), status: NodeStatus::default() }
+    }
+}
+
+/// The snapshots served to the nodes connected to the server, by the key their node hashes to, the node ID unless
+/// configured otherwise.
+pub struct SnapshotCache {
+    node_hash: Box<dyn NodeHash>,
+    nodes: Mutex<HashMap<String, NodeEntry>>,
+}
+
+impl Default for SnapshotCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for SnapshotCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnapshotCache").field("nodes", &self.node_ids()).finish_non_exhaustive()
+    }
+}
+
+impl SnapshotCache {
+    pub fn new() -> Self {
+        Self::with_node_hash(|node: &Node| node.id.clone())
+    }
+
+    pub fn with_node_hash(node_hash: impl NodeHash + 'static) -> Self {
+        Self { node_hash: Box::new(node_hash), nodes: Mutex::default() }
+    }
+
+    /// Serves a snapshot to a node, sending its clients what changed since the previous one.
+    pub fn set_snapshot(&self, node_id: &str, snapshot: Snapshot) {
+        self.nodes.lock().entry(node_id.to_owned()).or_default().snapshot.send_replace(Some(Arc::new(snapshot)));
+    }
+
+    /// Stops serving a node. Its clients keep the resources they were sent until a snapshot is set again.
+    pub fn clear_snapshot(&self, node_id: &str) {
+        let mut nodes = self.nodes.lock();
+        if let Some(entry) = nodes.get(node_id) {
+            entry.snapshot.send_replace(None);
+            if entry.status.streams == 0 {
+                nodes.remove(node_id);
+            }
+        }
+    }
+
+    pub fn snapshot(&self, node_id: &str) -> Option<Arc<Snapshot>> {
+        self.nodes.lock().get(node_id).and_then(|entry| entry.snapshot.borrow().clone())
+    }
+
+    pub fn status(&self, node_id: &str) -> Option<NodeStatus> {
+        self.nodes.lock().get(node_id).map(|entry| entry.status.clone())
+    }
+
+    /// The nodes with a snapshot or a stream open.
+    pub fn node_ids(&self) -> Vec<String> {
+        let mut node_ids: Vec<_> = self.nodes.lock().keys().cloned().collect();
+        node_ids.sort();
+        node_ids
+    }
+
+    /// Opens a stream for a node, watching its snapshot until the watch returned is dropped.
+    pub(super) fn watch(self: &Arc<Self>, node: &Node) -> SnapshotWatch {
+        let node_id = self.node_hash.hash(node);
+        let mut nodes = self.nodes.lock();
+        let entry = nodes.entry(node_id.clone()).or_default();
+        entry.status.streams += 1;
+        let snapshot = entry.snapshot.subscribe();
+        SnapshotWatch { cache: Arc::clone(self), node_id, client_id: node.id.clone(), snapshot }
+    }
+
+    fn update_status(&self, node_id: &str, update: impl FnOnce(&mut NodeStatus)) {
+        if let Some(entry) = self.nodes.lock().get_mut(node_id) {
+            update(&mut entry.status);
+        }
+    }
+}
+
+/// The snapshot of the node of a stream.
+pub(super) struct SnapshotWatch {
+    cache: Arc<SnapshotCache>,
+    pub(super) node_id: String,
+    /// The ID of the node of the client, which may hash to the same `node_id` as others.
+    client_id: String,
+    pub(super) snapshot: watch::Receiver<Option<Arc<Snapshot>>>,
+}
+
+impl SnapshotWatch {
+    pub(super) fn acked(&self, type_url: TypeUrl) {
+        self.cache.update_status(&self.node_id, |status| {
+            status.nacks.remove(&(self.client_id.clone(), type_url));
+        });
+    }
+
+    pub(super) fn nacked(&self, type_url: TypeUrl, nack: Nack) {
+        self.cache.update_status(&self.node_id, |status| {
+            status.nacks.insert((self.client_id.clone(), type_url), nack);
+        });
+    }
+}
+
+impl Drop for SnapshotWatch {
+    fn drop(&mut self) {
+        let mut nodes = self.cache.nodes.lock();
+        if let Some(entry) = nodes.get_mut(&self.node_id) {
+            entry.status.streams -= 1;
+            if entry.status.streams == 0 && entry.snapshot.borrow().is_none() {
+                nodes.remove(&self.node_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orion_data_plane_api::envoy_data_plane_api::google::protobuf::Any;
+
+    fn resource(name: &str, value: &[u8]) -> Resource {
+        Resource {
+            name: name.to_owned(),
+            resource: Some(Any { type_url: TypeUrl::Cluster.to_string(), value: value.to_vec() }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resources_are_versioned_by_their_content() {
+        let snapshot = Snapshot::new().with_resources(
+            TypeUrl::Cluster,
+            "1",
+            [resource("a", b"a"), resource("b", b"b"), Resource { version: "v7".to_owned(), ..resource("c", b"c") }],
+        );
+        assert_eq!(snapshot.version(TypeUrl::Cluster), Some("1"));
+        assert_eq!(snapshot.version(TypeUrl::Listener), None);
+        let items = &snapshot.resources(TypeUrl::Cluster).unwrap().items;
+        assert_ne!(items["a"].version, items["b"].version);
+        assert_eq!(items["c"].version, "v7");
+
+        let changed = Snapshot::new().with_resources(TypeUrl::Cluster, "2", [resource("a", b"a"), resource("b", b"x")]);
+        let changed_items = &changed.resources(TypeUrl::Cluster).unwrap().items;
+        assert_eq!(changed_items["a"].version, items["a"].version);
+        assert_ne!(changed_items["b"].version, items["b"].version);
+    }
+
+    #[test]
+    fn nodes_are_tracked_while_they_have_a_snapshot_or_a_stream() {
+        let cache = Arc::new(SnapshotCache::with_node_hash(|node: &Node| node.cluster.clone()));
+        cache.set_snapshot("gateways", Snapshot::new());
+        let watch =
+            cache.watch(&Node { id: "gateway-1".to_owned(), cluster: "gateways".to_owned(), ..Default::default() });
+        assert_eq!(watch.node_id, "gateways");
+        assert!(watch.snapshot.borrow().is_some());
+
+        let nack = Nack { version: "1".to_owned(), nonce: "n".to_owned(), message: "invalid".to_owned() };
+        watch.nacked(TypeUrl::Cluster, nack.clone());
+        assert_eq!(
+            cache.status("gateways").unwrap().nacks.get(&("gateway-1".to_owned(), TypeUrl::Cluster)),
+            Some(&nack)
+        );
+        watch.acked(TypeUrl::Cluster);
+        assert_eq!(cache.status("gateways").unwrap(), NodeStatus { streams: 1, nacks: BTreeMap::new() });
+
+        cache.clear_snapshot("gateways");
+        assert!(cache.snapshot("gateways").is_none());
+        assert_eq!(cache.node_ids(), ["gateways"], "still streaming");
+        drop(watch);
+        assert!(cache.node_ids().is_empty());
+    }
+
+    #[test]
+    fn nacks_are_kept_per_client_of_a_node() {
+        let cache = Arc::new(SnapshotCache::with_node_hash(|node: &Node| node.cluster.clone()));
+        let client = |id: &str| Node { id: id.to_owned(), cluster: "gateways".to_owned(), ..Default::default() };
+        let first = cache.watch(&client("gateway-1"));
+        let second = cache.watch(&client("gateway-2"));
+
+        let nack =
+            |nonce: &str| Nack { version: "1".to_owned(), nonce: nonce.to_owned(), message: "invalid".to_owned() };
+        first.nacked(TypeUrl::Cluster, nack("first"));
+        second.nacked(TypeUrl::Cluster, nack("second"));
+        let nacks = cache.status("gateways").unwrap().nacks;
+        assert_eq!(nacks[&("gateway-1".to_owned(), TypeUrl::Cluster)], nack("first"));
+        assert_eq!(nacks[&("gateway-2".to_owned(), TypeUrl::Cluster)], nack("second"));
+
+        second.acked(TypeUrl::Cluster);
+        let nacks = cache.status("gateways").unwrap().nacks;
+        assert_eq!(
+            nacks.into_iter().collect::<Vec<_>>(),
+            [(("gateway-1".to_owned(), TypeUrl::Cluster), nack("first"))]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! A health discovery service (HDS) server, assigning its clients endpoints to health check and handing out the
+//! health they report.
+
+use std::{pin::Pin, time::Duration};
+
+use orion_data_plane_api::envoy_data_plane_api::{
+    envoy::service::health::v3::{
+        health_check_request_or_endpoint_health_response::RequestType,
+        health_discovery_service_server::{HealthDiscoveryService, HealthDiscoveryServiceServer},
+        ClusterHealthCheck, HealthCheckRequestOrEndpointHealthResponse, HealthCheckSpecifier,
+    },
+    google::protobuf::Duration as ProtobufDuration,
+    tonic::{self, Response, Status},
+};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone)]
+pub struct HealthDiscoveryServer {
+    specifier: HealthCheckSpecifier,
+    reports: mpsc::Sender<RequestType>,
+}
+
+impl HealthDiscoveryServer {
+    /// Assigns the clients the endpoints of these clusters, to be reported every interval, sending every request
+    /// received to `reports`.
+    pub fn new(
+        cluster_health_checks: Vec<ClusterHealthCheck>,
+        interval: Duration,
+        reports: mpsc::Sender<RequestType>,
+    ) -> Self {
+        let interval = ProtobufDuration {
+            seconds: i64::try_from(interval.as_secs()).unwrap_or(i64::MAX),
+            nanos: i32::try_from(interval.subsec_nanos()).unwrap_or_default(),
+        };
+        Self { specifier: HealthCheckSpecifier { cluster_health_checks, interval: Some(interval) }, reports }
+    }
+
+    pub fn into_service(self) -> HealthDiscoveryServiceServer<Self> {
+        HealthDiscoveryServiceServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl HealthDiscoveryService for HealthDiscoveryServer {
+    type StreamHealthCheckStream =
+        Pin<Box<dyn Stream<Item = std::result::Result<HealthCheckSpecifier, Status>> + Send>>;
+
+    async fn stream_health_check(
+        &self,
+        req: tonic::Request<tonic::Streaming<HealthCheckRequestOrEndpointHealthResponse>>,
+    ) -> std::result::Result<Response<Self::StreamHealthCheckStream>, Status> {
+        info!("HDS client connected from: {:?}", req.remote_addr());
+        let mut requests = req.into_inner();
+        let specifier = self.specifier.clone();
+        let reports = self.reports.clone();
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            loop {
+                match requests.message().await {
+                    Ok(Some(HealthCheckRequestOrEndpointHealthResponse { request_type: Some(request) })) => {
+                        // the client is assigned its endpoints once it says who it is, in its first request
+                        if matches!(request, RequestType::HealthCheckRequest(_))
+                            && tx.send(Ok(specifier.clone())).await.is_err()
+                        {
+                            return;
+                        }
+                        if reports.send(request).await.is_err() {
+                            return;
+                        }
+                    },
+                    Ok(Some(_)) => (),
+                    Ok(None) => return,
+                    Err(status) => {
+                        warn!("HDS stream error: {status}");
+                        return;
+                    },
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::StreamHealthCheckStream))
+    }
+
+    async fn fetch_health_check(
+        &self,
+        _req: tonic::Request<HealthCheckRequestOrEndpointHealthResponse>,
+    ) -> std::result::Result<Response<HealthCheckSpecifier>, Status> {
+        Err(Status::unimplemented("only the streaming HDS API is served"))
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2025 The kmesh Authors
+//
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//
+
+//! A load reporting service (LRS) server, asking its clients for the load of some clusters and handing out what
+//! they report.
+
+use std::{pin::Pin, time::Duration};
+
+use orion_data_plane_api::envoy_data_plane_api::{
+    envoy::service::load_stats::v3::{
+        load_reporting_service_server::{LoadReportingService, LoadReportingServiceServer},
+        LoadStatsRequest, LoadStatsResponse,
+    },
+    google::protobuf::Duration as ProtobufDuration,
+    tonic::{self, Response, Status},
+};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tracing::{info, warn};
+
+#[derive(Debug, Clone)]
+pub struct LoadReportingServer {
+    response: LoadStatsResponse,
+    reports: mpsc::Sender<LoadStatsRequest>,
+}
+
+impl LoadReportingServer {
+    /// Asks the clients for the load of all of their clusters every interval, sending every request received to
+    /// `reports`.
+    pub fn new(load_reporting_interval: Duration, reports: mpsc::Sender<LoadStatsRequest>) -> Self {
+        let load_reporting_interval = ProtobufDuration {
+            seconds: i64::try_from(load_reporting_interval.as_secs()).unwrap_or(i64::MAX),
+            nanos: i32::try_from(load_reporting_interval.subsec_nanos()).unwrap_or_default(),
+        };
+        let response = LoadStatsResponse {
+            send_all_clusters: true,
+            load_reporting_interval: Some(load_reporting_interval),
+            ..Default::default()
+        };
+        Self { response, reports }
+    }
+
+    /// Asks for the load of these clusters only.
+    #[must_use]
+    pub fn with_clusters(mut self, clusters: Vec<String>) -> Self {
+        self.response.send_all_clusters = false;
+        self.response.clusters = clusters;
+        self
+    }
+
+    pub fn into_service(self) -> LoadReportingServiceServer<Self> {
+        LoadReportingServiceServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl LoadReportingService for LoadReportingServer {
+    type StreamLoadStatsStream = Pin<Box<dyn Stream<Item = std::result::Result<LoadStatsResponse, Status>> + Send>>;
+
+    async fn stream_load_stats(
+        &self,
+        req: tonic::Request<tonic::Streaming<LoadStatsRequest>>,
+    ) -> std::result::Result<Response<Self::StreamLoadStatsStream>, Status> {
+        info!("LRS client connected from: {:?}", req.remote_addr());
+        let mut requests = req.into_inner();
+        let response = self.response.clone();
+        let reports = self.reports.clone();
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            loop {
+                match requests.message().await {
+                    Ok(Some(request)) => {
+                        // the client is answered once it says who it is, in its first request
+                        if request.node.is_some() && tx.send(Ok(response.clone())).await.is_err() {
+                            return;
+                        }
+                        if reports.send(request).await.is_err() {
+                            return;
+                        }
+                    },
+                    Ok(None) => return,
+                    Err(status) => {
+                        warn!("LRS stream error: {status}");
+                        return;
+                    },
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::StreamLoadStatsStream))
+    }
+}
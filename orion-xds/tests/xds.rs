@@ -22,7 +22,14 @@ use orion_configuration::config::bootstrap::Node;
 use orion_data_plane_api::envoy_data_plane_api::prost::Message;
 use orion_data_plane_api::envoy_data_plane_api::{
     envoy::{
-        config::cluster::v3::Cluster,
+        config::{
+            cluster::v3::{
+                cluster::{ClusterDiscoveryType, DiscoveryType},
+                Cluster,
+            },
+            core::v3::Node as EnvoyNode,
+            endpoint::v3::ClusterStats,
+        },
         service::{
             cluster::v3::{
                 cluster_discovery_service_client::ClusterDiscoveryServiceClient,
@@ -31,11 +38,19 @@ use orion_data_plane_api::envoy_data_plane_api::{
             discovery::v3::{
                 aggregated_discovery_service_client::AggregatedDiscoveryServiceClient,
                 aggregated_discovery_service_server::{AggregatedDiscoveryService, AggregatedDiscoveryServiceServer},
-                DeltaDiscoveryResponse, DiscoveryResponse, Resource,
+                DeltaDiscoveryRequest, DeltaDiscoveryResponse, DiscoveryRequest, DiscoveryResponse, Resource,
             },
+            health::v3::{
+                health_check_request_or_endpoint_health_response::RequestType,
+                health_discovery_service_client::HealthDiscoveryServiceClient,
+                health_discovery_service_server::{HealthDiscoveryService, HealthDiscoveryServiceServer},
+                ClusterEndpointsHealth, ClusterHealthCheck, HealthCheckRequestOrEndpointHealthResponse,
+                HealthCheckSpecifier,
+            },
+            load_stats::v3::load_reporting_service_client::LoadReportingServiceClient,
         },
     },
-    google::protobuf::Any,
+    google::{protobuf::Any, rpc::Status as RpcStatus},
     tonic,
 };
 use std::{
@@ -51,10 +66,13 @@ use tonic::transport::Server;
 use orion_xds::xds::{
     bindings,
     client::DiscoveryClientBuilder,
+    health_discovery::{DelegatedHealthChecks, HealthDiscoveryClient},
+    load_stats::{LoadReportingClient, LoadSource, ReportedClusters},
     model::{TypeUrl, XdsResourceUpdate},
+    server::{AggregateServer, HealthDiscoveryServer, LoadReportingServer, Nack, Snapshot, SnapshotCache},
 };
 
-use futures::Stream;
+use futures::{Stream, StreamExt};
 
 use tokio::{
     sync::{mpsc, Mutex},
@@ -473,3 +491,714 @@ async fn test_aggregated_discovery() {
             panic!("timed out waiting for xds resource over update channel")
     }
 }
+
+pub struct MockSotwClusterService {
+    relay: Arc<Mutex<mpsc::Receiver<DiscoveryResponse>>>,
+    requests: mpsc::Sender<DiscoveryRequest>,
+}
+
+#[tonic::async_trait]
+impl ClusterDiscoveryService for MockSotwClusterService {
+    type StreamClustersStream = Pin<Box<dyn Stream<Item = Result<DiscoveryResponse, Status>> + Send>>;
+    async fn stream_clusters(
+        &self,
+        request: tonic::Request<tonic::Streaming<DiscoveryRequest>>,
+    ) -> std::result::Result<tonic::Response<Self::StreamClustersStream>, tonic::Status> {
+        let mut in_stream = request.into_inner();
+        let requests = self.requests.clone();
+        tokio::spawn(async move {
+            while let Ok(Some(request)) = in_stream.message().await {
+                if requests.send(request).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let (tx, rx) = mpsc::channel::<Result<DiscoveryResponse, tonic::Status>>(100);
+        let shared_receiver = self.relay.clone();
+        tokio::spawn(async move {
+            let mut receiver = shared_receiver.lock().await;
+            while let Some(response) = receiver.recv().await {
+                if tx.send(Ok(response)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::StreamClustersStream))
+    }
+
+    type DeltaClustersStream = Pin<Box<dyn Stream<Item = Result<DeltaDiscoveryResponse, Status>> + Send>>;
+    async fn delta_clusters(
+        &self,
+        _request: tonic::Request<tonic::Streaming<DeltaDiscoveryRequest>>,
+    ) -> std::result::Result<tonic::Response<Self::DeltaClustersStream>, tonic::Status> {
+        unimplemented!("not used by the state-of-the-world client");
+    }
+
+    async fn fetch_clusters(
+        &self,
+        _request: tonic::Request<DiscoveryRequest>,
+    ) -> std::result::Result<tonic::Response<DiscoveryResponse>, tonic::Status> {
+        unimplemented!("not used by proxy");
+    }
+}
+
+async fn receive<T>(next: impl std::future::Future<Output = Option<T>>) -> T {
+    tokio::select! {
+        Some(value) = next => value,
+        _ = time::sleep(Duration::from_secs(5)) => panic!("timed out waiting on channel"),
+    }
+}
+
+fn cluster_response(version: &str, nonce: &str, resources: Vec<Any>) -> DiscoveryResponse {
+    DiscoveryResponse {
+        version_info: version.to_owned(),
+        nonce: nonce.to_owned(),
+        type_url: "type.googleapis.com/envoy.config.cluster.v3.Cluster".to_owned(),
+        resources,
+        ..Default::default()
+    }
+}
+
+fn cluster_any(name: &str) -> Any {
+    let cluster = Cluster {
+        name: name.to_owned(),
+        cluster_discovery_type: Some(ClusterDiscoveryType::Type(DiscoveryType::Eds.into())),
+        ..Default::default()
+    };
+    Any { type_url: "type.googleapis.com/envoy.config.cluster.v3.Cluster".to_owned(), value: cluster.encode_to_vec() }
+}
+
+#[tokio::test]
+async fn test_state_of_the_world_client() {
+    let node = Node { id: "node-id".into(), cluster_id: "gw-cluster".into(), ..Default::default() };
+    let (server_side_response_tx, server_side_response_rx) = mpsc::channel::<DiscoveryResponse>(100);
+    let (requests_tx, mut requests_rx) = mpsc::channel::<DiscoveryRequest>(100);
+
+    let (client, server) = tokio::io::duplex(1024);
+    let cds_server =
+        MockSotwClusterService { relay: Arc::new(Mutex::new(server_side_response_rx)), requests: requests_tx };
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(ClusterDiscoveryServiceServer::new(cds_server))
+            .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
+            .await
+    });
+
+    let mut client = Some(client);
+    let channel = tonic::transport::Endpoint::try_from("http://[::]:50051")
+        .expect("failed to init Endpoint")
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let client = client.take();
+            async move {
+                if let Some(client) = client {
+                    Ok(TokioIo::new(client))
+                } else {
+                    Err(std::io::Error::other("client is already taken"))
+                }
+            }
+        }))
+        .await
+        .unwrap();
+
+    let cds_client = ClusterDiscoveryServiceClient::new(channel);
+    let typed_binding = bindings::ClusterDiscoveryType { underlying_client: cds_client };
+    let (mut worker, mut client, _subscription_manager) =
+        DiscoveryClientBuilder::<bindings::ClusterDiscoveryType>::new(node, typed_binding).build_sotw().unwrap();
+    tokio::spawn(async move {
+        let _status = worker.run().await;
+    });
+
+    let request = receive(requests_rx.recv()).await;
+    assert_eq!(request.version_info, "");
+    assert!(request.resource_names.is_empty(), "clusters are requested with a wildcard");
+
+    let _ =
+        server_side_response_tx.send(cluster_response("1", "nonce-1", vec![cluster_any("a"), cluster_any("b")])).await;
+    let event = receive(client.recv()).await;
+    let mut names: Vec<_> = event.updates.iter().map(XdsResourceUpdate::id).collect();
+    names.sort();
+    assert_eq!(names, vec!["a".to_owned(), "b".to_owned()]);
+    assert!(event.updates.iter().all(|update| matches!(update, XdsResourceUpdate::Update(..))));
+    let _ = event.ack_channel.send(vec![]);
+    let ack = receive(requests_rx.recv()).await;
+    assert_eq!((ack.version_info.as_str(), ack.response_nonce.as_str()), ("1", "nonce-1"));
+    assert!(ack.error_detail.is_none());
+
+    // a cluster missing from a later response has been removed
+    let _ = server_side_response_tx.send(cluster_response("2", "nonce-2", vec![cluster_any("a")])).await;
+    let event = receive(client.recv()).await;
+    assert_eq!(event.updates.len(), 2);
+    assert!(matches!(&event.updates[0], XdsResourceUpdate::Update(name, _, version) if name == "a" && version == "2"));
+    assert!(matches!(&event.updates[1], XdsResourceUpdate::Remove(name, TypeUrl::Cluster) if name == "b"));
+    let _ = event.ack_channel.send(vec![]);
+    let ack = receive(requests_rx.recv()).await;
+    assert_eq!((ack.version_info.as_str(), ack.response_nonce.as_str()), ("2", "nonce-2"));
+
+    // an undecodable response is rejected, keeping the last accepted version
+    let invalid = Any { type_url: "type.googleapis.com/envoy.config.cluster.v3.Cluster".to_owned(), value: vec![0xff] };
+    let _ = server_side_response_tx.send(cluster_response("3", "nonce-3", vec![invalid])).await;
+    let nack = receive(requests_rx.recv()).await;
+    assert_eq!((nack.version_info.as_str(), nack.response_nonce.as_str()), ("2", "nonce-3"));
+    assert!(nack.error_detail.is_some());
+}
+
+/// An aggregated discovery server which never answers, relaying the requests of its state-of-the-world streams.
+pub struct MockSotwAggregatedService {
+    requests: mpsc::Sender<DiscoveryRequest>,
+}
+
+#[tonic::async_trait]
+impl AggregatedDiscoveryService for MockSotwAggregatedService {
+    type StreamAggregatedResourcesStream = Pin<Box<dyn Stream<Item = Result<DiscoveryResponse, Status>> + Send>>;
+    async fn stream_aggregated_resources(
+        &self,
+        request: tonic::Request<tonic::Streaming<DiscoveryRequest>>,
+    ) -> std::result::Result<tonic::Response<Self::StreamAggregatedResourcesStream>, tonic::Status> {
+        let mut in_stream = request.into_inner();
+        let requests = self.requests.clone();
+        tokio::spawn(async move {
+            while let Ok(Some(request)) = in_stream.message().await {
+                if requests.send(request).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(futures::stream::pending()) as Self::StreamAggregatedResourcesStream))
+    }
+
+    type DeltaAggregatedResourcesStream = Pin<Box<dyn Stream<Item = Result<DeltaDiscoveryResponse, Status>> + Send>>;
+    async fn delta_aggregated_resources(
+        &self,
+        _request: tonic::Request<tonic::Streaming<DeltaDiscoveryRequest>>,
+    ) -> std::result::Result<tonic::Response<Self::DeltaAggregatedResourcesStream>, tonic::Status> {
+        unimplemented!("not used by the state-of-the-world client");
+    }
+}
+
+#[tokio::test]
+async fn test_state_of_the_world_client_asks_by_name_once_subscribed() {
+    let node = Node { id: "node-id".into(), cluster_id: "gw-cluster".into(), ..Default::default() };
+    let (requests_tx, mut requests_rx) = mpsc::channel::<DiscoveryRequest>(100);
+    let (client, server) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(AggregatedDiscoveryServiceServer::new(MockSotwAggregatedService { requests: requests_tx }))
+            .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
+            .await
+    });
+
+    let typed_binding = bindings::AggregatedDiscoveryType { underlying_client: connect(client).await.unwrap() };
+    let (mut worker, _client, subscription_manager) =
+        DiscoveryClientBuilder::new(node, typed_binding).build_sotw().unwrap();
+    tokio::spawn(async move {
+        let _status = worker.run().await;
+    });
+
+    // only the wildcard types are asked for, an empty list of names for the others being a wildcard too
+    let mut requested = Vec::new();
+    loop {
+        tokio::select! {
+            Some(request) = requests_rx.recv() => {
+                assert!(request.resource_names.is_empty());
+                requested.push(TypeUrl::try_from(request.type_url.as_str()).unwrap());
+            }
+            () = sleep(Duration::from_millis(500)) => break,
+        }
+    }
+    requested.sort();
+    assert_eq!(requested, vec![TypeUrl::Listener, TypeUrl::Cluster, TypeUrl::ScopedRouteConfiguration]);
+
+    let _ = subscription_manager.subscribe("routes-a".to_owned(), TypeUrl::RouteConfiguration).await;
+    let request = receive(requests_rx.recv()).await;
+    assert_eq!(TypeUrl::try_from(request.type_url.as_str()).unwrap(), TypeUrl::RouteConfiguration);
+    assert_eq!(request.resource_names, vec!["routes-a".to_owned()]);
+}
+
+#[tokio::test]
+async fn test_state_of_the_world_client_asks_for_the_resources_of_a_locator() {
+    const LISTENERS: &str = "xdstp://authority.example/envoy.config.listener.v3.Listener/*";
+    let node = Node { id: "node-id".into(), cluster_id: "gw-cluster".into(), ..Default::default() };
+    let (requests_tx, mut requests_rx) = mpsc::channel::<DiscoveryRequest>(100);
+    let (client, server) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(AggregatedDiscoveryServiceServer::new(MockSotwAggregatedService { requests: requests_tx }))
+            .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
+            .await
+    });
+
+    let typed_binding = bindings::AggregatedDiscoveryType { underlying_client: connect(client).await.unwrap() };
+    let (mut worker, _client, subscription_manager) = DiscoveryClientBuilder::new(node, typed_binding)
+        .subscribe_resource_names(&[(TypeUrl::Listener, LISTENERS.to_owned())])
+        .build_sotw()
+        .unwrap();
+    tokio::spawn(async move {
+        let _status = worker.run().await;
+    });
+
+    // the listeners are asked for by the name of their collection, the clusters still with a wildcard
+    let mut requested = Vec::new();
+    loop {
+        tokio::select! {
+            Some(request) = requests_rx.recv() => {
+                requested.push((TypeUrl::try_from(request.type_url.as_str()).unwrap(), request.resource_names));
+            }
+            () = sleep(Duration::from_millis(500)) => break,
+        }
+    }
+    requested.sort();
+    assert_eq!(
+        requested,
+        vec![
+            (TypeUrl::Listener, vec![LISTENERS.to_owned()]),
+            (TypeUrl::Cluster, vec![]),
+            (TypeUrl::ScopedRouteConfiguration, vec![]),
+        ]
+    );
+
+    let _ = subscription_manager.subscribe("listener-a".to_owned(), TypeUrl::Listener).await;
+    let request = receive(requests_rx.recv()).await;
+    assert_eq!(TypeUrl::try_from(request.type_url.as_str()).unwrap(), TypeUrl::Listener);
+    let mut names = request.resource_names;
+    names.sort();
+    assert_eq!(names, vec!["listener-a".to_owned(), LISTENERS.to_owned()]);
+}
+
+fn cluster_resource(name: &str) -> Resource {
+    Resource { name: name.to_owned(), resource: Some(cluster_any(name)), ..Default::default() }
+}
+
+async fn connect(
+    client: tokio::io::DuplexStream,
+) -> Result<AggregatedDiscoveryServiceClient<tonic::transport::Channel>, tonic::transport::Error> {
+    Ok(AggregatedDiscoveryServiceClient::new(connect_channel(client).await?))
+}
+
+async fn connect_channel(
+    client: tokio::io::DuplexStream,
+) -> Result<tonic::transport::Channel, tonic::transport::Error> {
+    let mut client = Some(client);
+    let channel = tonic::transport::Endpoint::from_static("http://[::]:50051")
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let client = client.take();
+            async move {
+                if let Some(client) = client {
+                    Ok(TokioIo::new(client))
+                } else {
+                    Err(std::io::Error::other("client is already taken"))
+                }
+            }
+        }))
+        .await?;
+    Ok(channel)
+}
+
+#[tokio::test]
+async fn test_snapshot_cache_serves_many_clients() {
+    let node = EnvoyNode { id: "node-a".to_owned(), ..Default::default() };
+    let cache = Arc::new(SnapshotCache::new());
+    cache.set_snapshot(
+        "node-a",
+        Snapshot::new().with_resources(TypeUrl::Cluster, "1", [cluster_resource("a"), cluster_resource("b")]),
+    );
+
+    let (delta_client, delta_server) = tokio::io::duplex(4096);
+    let (sotw_client, sotw_server) = tokio::io::duplex(4096);
+    let ads_server = AggregateServer::new(Arc::clone(&cache));
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(AggregatedDiscoveryServiceServer::new(ads_server))
+            .serve_with_incoming(tokio_stream::iter([Ok::<_, std::io::Error>(delta_server), Ok(sotw_server)]))
+            .await
+    });
+
+    let (delta_requests, rx) = mpsc::channel(10);
+    let mut delta_responses = connect(delta_client)
+        .await
+        .unwrap()
+        .delta_aggregated_resources(ReceiverStream::new(rx))
+        .await
+        .unwrap()
+        .into_inner();
+    let (sotw_requests, rx) = mpsc::channel(10);
+    let mut sotw_responses = connect(sotw_client)
+        .await
+        .unwrap()
+        .stream_aggregated_resources(ReceiverStream::new(rx))
+        .await
+        .unwrap()
+        .into_inner();
+
+    // the delta client already has cluster a, so it is only sent cluster b
+    let a_version = cache.snapshot("node-a").unwrap().resources(TypeUrl::Cluster).unwrap().items["a"].version.clone();
+    let _ = delta_requests
+        .send(DeltaDiscoveryRequest {
+            node: Some(node.clone()),
+            type_url: TypeUrl::Cluster.to_string(),
+            initial_resource_versions: [("a".to_owned(), a_version)].into(),
+            ..Default::default()
+        })
+        .await;
+    let response = receive(delta_responses.next()).await.unwrap();
+    let names: Vec<_> = response.resources.iter().map(|resource| resource.name.as_str()).collect();
+    assert_eq!(names, ["b"]);
+    let _ = delta_requests
+        .send(DeltaDiscoveryRequest {
+            type_url: TypeUrl::Cluster.to_string(),
+            response_nonce: response.nonce,
+            ..Default::default()
+        })
+        .await;
+
+    let _ = sotw_requests
+        .send(DiscoveryRequest { node: Some(node), type_url: TypeUrl::Cluster.to_string(), ..Default::default() })
+        .await;
+    let response = receive(sotw_responses.next()).await.unwrap();
+    assert_eq!((response.version_info.as_str(), response.resources.len()), ("1", 2));
+    assert_eq!(cache.status("node-a").unwrap().streams, 2);
+
+    // a new snapshot is sent to both, as a diff to the delta client
+    cache.set_snapshot(
+        "node-a",
+        Snapshot::new().with_resources(TypeUrl::Cluster, "2", [cluster_resource("a"), cluster_resource("c")]),
+    );
+    let delta = receive(delta_responses.next()).await.unwrap();
+    let names: Vec<_> = delta.resources.iter().map(|resource| resource.name.as_str()).collect();
+    assert_eq!((names, delta.removed_resources), (vec!["c"], vec!["b".to_owned()]));
+    let sotw = receive(sotw_responses.next()).await.unwrap();
+    assert_eq!((sotw.version_info.as_str(), sotw.resources.len()), ("2", 2));
+
+    // the rejection of the state-of-the-world client is tracked until it accepts a version
+    let _ = sotw_requests
+        .send(DiscoveryRequest {
+            version_info: "1".to_owned(),
+            type_url: TypeUrl::Cluster.to_string(),
+            response_nonce: sotw.nonce.clone(),
+            error_detail: Some(RpcStatus { message: "invalid cluster".to_owned(), ..Default::default() }),
+            ..Default::default()
+        })
+        .await;
+    let nack = Nack { version: "2".to_owned(), nonce: sotw.nonce, message: "invalid cluster".to_owned() };
+    for _ in 0..50 {
+        if cache.status("node-a").unwrap().nacks.contains_key(&("node-a".to_owned(), TypeUrl::Cluster)) {
+            break;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(cache.status("node-a").unwrap().nacks.get(&("node-a".to_owned(), TypeUrl::Cluster)), Some(&nack));
+
+    cache.set_snapshot("node-a", Snapshot::new().with_resources(TypeUrl::Cluster, "3", [cluster_resource("a")]));
+    let sotw = receive(sotw_responses.next()).await.unwrap();
+    let _ = sotw_requests
+        .send(DiscoveryRequest {
+            version_info: "3".to_owned(),
+            type_url: TypeUrl::Cluster.to_string(),
+            response_nonce: sotw.nonce,
+            ..Default::default()
+        })
+        .await;
+    let _ = receive(delta_responses.next()).await.unwrap();
+    for _ in 0..50 {
+        if cache.status("node-a").unwrap().nacks.is_empty() {
+            break;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    assert!(cache.status("node-a").unwrap().nacks.is_empty());
+}
+
+/// Reports the number of reports made so far as the dropped requests of each cluster asked for.
+#[derive(Default)]
+struct CountingLoadSource {
+    reports: u64,
+}
+
+impl LoadSource for CountingLoadSource {
+    fn reset(&mut self) {
+        self.reports = 0;
+    }
+
+    fn report(&mut self, clusters: &ReportedClusters) -> Vec<ClusterStats> {
+        self.reports += 1;
+        let names = clusters.names().map(<[String]>::to_vec).unwrap_or_else(|| vec!["all".to_owned()]);
+        names
+            .into_iter()
+            .map(|cluster_name| ClusterStats {
+                cluster_name,
+                total_dropped_requests: self.reports,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn test_load_reported_at_the_interval_the_server_sets() {
+    let (reports_tx, mut reports) = mpsc::channel(10);
+    let lrs_server =
+        LoadReportingServer::new(Duration::from_millis(50), reports_tx).with_clusters(vec!["backends".to_owned()]);
+    let (client, server) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(lrs_server.into_service())
+            .serve_with_incoming(tokio_stream::iter([Ok::<_, std::io::Error>(server)]))
+            .await
+    });
+
+    let channel = connect_channel(client).await.unwrap();
+    let node = Node { id: "node-a".into(), cluster_id: "gateways".into(), metadata: None, ..Default::default() };
+    let mut lrs_client =
+        LoadReportingClient::new(node, LoadReportingServiceClient::new(channel), CountingLoadSource::default());
+    tokio::spawn(async move { lrs_client.run().await });
+
+    let first = receive(reports.recv()).await;
+    let node = first.node.unwrap();
+    assert_eq!(node.id, "node-a");
+    assert!(node.client_features.contains(&"envoy.lrs.supports_send_all_clusters".to_owned()));
+    assert!(first.cluster_stats.is_empty());
+
+    for reported in 1..=2 {
+        let report = receive(reports.recv()).await;
+        assert!(report.node.is_none());
+        let stats: Vec<_> = report
+            .cluster_stats
+            .iter()
+            .map(|stats| (stats.cluster_name.as_str(), stats.total_dropped_requests))
+            .collect();
+        assert_eq!(stats, [("backends", reported)]);
+    }
+}
+
+/// Reports every cluster assigned, without any endpoint.
+#[derive(Default)]
+struct AssignedClusters {
+    clusters: Vec<String>,
+}
+
+impl DelegatedHealthChecks for AssignedClusters {
+    fn assign(&mut self, clusters: Vec<ClusterHealthCheck>) {
+        self.clusters = clusters.into_iter().map(|cluster| cluster.cluster_name).collect();
+    }
+
+    fn report(&mut self) -> Vec<ClusterEndpointsHealth> {
+        self.clusters
+            .iter()
+            .map(|cluster_name| ClusterEndpointsHealth { cluster_name: cluster_name.clone(), ..Default::default() })
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn test_health_reported_for_the_endpoints_the_server_assigns() {
+    let (reports_tx, mut reports) = mpsc::channel(10);
+    let assigned = ClusterHealthCheck { cluster_name: "shared_backends".to_owned(), ..Default::default() };
+    let hds_server = HealthDiscoveryServer::new(vec![assigned], Duration::from_millis(50), reports_tx);
+    let (client, server) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(hds_server.into_service())
+            .serve_with_incoming(tokio_stream::iter([Ok::<_, std::io::Error>(server)]))
+            .await
+    });
+
+    let channel = connect_channel(client).await.unwrap();
+    let node = Node { id: "node-a".into(), cluster_id: "gateways".into(), metadata: None, ..Default::default() };
+    let mut hds_client =
+        HealthDiscoveryClient::new(node, HealthDiscoveryServiceClient::new(channel), AssignedClusters::default());
+    tokio::spawn(async move { hds_client.run().await });
+
+    let RequestType::HealthCheckRequest(first) = receive(reports.recv()).await else {
+        panic!("the client first says who it is");
+    };
+    assert_eq!(first.node.unwrap().id, "node-a");
+    assert!(!first.capability.unwrap().health_check_protocols.is_empty());
+
+    for _ in 0..2 {
+        let RequestType::EndpointHealthResponse(report) = receive(reports.recv()).await else {
+            panic!("the client then reports the health of the endpoints assigned");
+        };
+        let clusters: Vec<_> =
+            report.cluster_endpoints_health.iter().map(|cluster| cluster.cluster_name.as_str()).collect();
+        assert_eq!(clusters, ["shared_backends"]);
+    }
+}
+
+/// Sends the clusters assigned on each assignment.
+struct RecordedAssignments {
+    assigned: mpsc::Sender<Vec<String>>,
+}
+
+impl DelegatedHealthChecks for RecordedAssignments {
+    fn assign(&mut self, clusters: Vec<ClusterHealthCheck>) {
+        let _ = self.assigned.try_send(clusters.into_iter().map(|cluster| cluster.cluster_name).collect());
+    }
+
+    fn report(&mut self) -> Vec<ClusterEndpointsHealth> {
+        Vec::new()
+    }
+}
+
+/// An HDS server assigning the same clusters twice, then other clusters.
+pub struct MockRepeatingHealthDiscoveryService;
+
+#[tonic::async_trait]
+impl HealthDiscoveryService for MockRepeatingHealthDiscoveryService {
+    type StreamHealthCheckStream = Pin<Box<dyn Stream<Item = Result<HealthCheckSpecifier, Status>> + Send>>;
+    async fn stream_health_check(
+        &self,
+        _request: tonic::Request<tonic::Streaming<HealthCheckRequestOrEndpointHealthResponse>>,
+    ) -> std::result::Result<tonic::Response<Self::StreamHealthCheckStream>, tonic::Status> {
+        let specifier = |cluster_name: &str| HealthCheckSpecifier {
+            cluster_health_checks: vec![ClusterHealthCheck {
+                cluster_name: cluster_name.to_owned(),
+                ..Default::default()
+            }],
+            interval: None,
+        };
+        let specifiers = [specifier("shared_backends"), specifier("shared_backends"), specifier("other_backends")];
+        let specifiers = futures::stream::iter(specifiers.map(Ok)).chain(futures::stream::pending());
+        Ok(Response::new(Box::pin(specifiers) as Self::StreamHealthCheckStream))
+    }
+
+    async fn fetch_health_check(
+        &self,
+        _request: tonic::Request<HealthCheckRequestOrEndpointHealthResponse>,
+    ) -> std::result::Result<tonic::Response<HealthCheckSpecifier>, tonic::Status> {
+        unimplemented!("not used by the client");
+    }
+}
+
+#[tokio::test]
+async fn test_health_checks_not_assigned_again_for_the_same_clusters() {
+    let (client, server) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(HealthDiscoveryServiceServer::new(MockRepeatingHealthDiscoveryService))
+            .serve_with_incoming(tokio_stream::iter([Ok::<_, std::io::Error>(server)]))
+            .await
+    });
+
+    let channel = connect_channel(client).await.unwrap();
+    let node = Node { id: "node-a".into(), cluster_id: "gateways".into(), metadata: None, ..Default::default() };
+    let (assigned_tx, mut assigned) = mpsc::channel(10);
+    let mut hds_client = HealthDiscoveryClient::new(
+        node,
+        HealthDiscoveryServiceClient::new(channel),
+        RecordedAssignments { assigned: assigned_tx },
+    );
+    tokio::spawn(async move { hds_client.run().await });
+
+    assert_eq!(receive(assigned.recv()).await, ["shared_backends"]);
+    assert_eq!(receive(assigned.recv()).await, ["other_backends"]);
+}
+
+pub struct MockDeltaClusterService {
+    relay: Arc<Mutex<mpsc::Receiver<DeltaDiscoveryResponse>>>,
+    requests: mpsc::Sender<DeltaDiscoveryRequest>,
+}
+
+#[tonic::async_trait]
+impl ClusterDiscoveryService for MockDeltaClusterService {
+    type StreamClustersStream = Pin<Box<dyn Stream<Item = Result<DiscoveryResponse, Status>> + Send>>;
+    async fn stream_clusters(
+        &self,
+        _request: tonic::Request<tonic::Streaming<DiscoveryRequest>>,
+    ) -> std::result::Result<tonic::Response<Self::StreamClustersStream>, tonic::Status> {
+        unimplemented!("not used by the incremental client");
+    }
+
+    type DeltaClustersStream = Pin<Box<dyn Stream<Item = Result<DeltaDiscoveryResponse, Status>> + Send>>;
+    async fn delta_clusters(
+        &self,
+        request: tonic::Request<tonic::Streaming<DeltaDiscoveryRequest>>,
+    ) -> std::result::Result<tonic::Response<Self::DeltaClustersStream>, tonic::Status> {
+        let mut in_stream = request.into_inner();
+        let requests = self.requests.clone();
+        tokio::spawn(async move {
+            while let Ok(Some(request)) = in_stream.message().await {
+                if requests.send(request).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let (tx, rx) = mpsc::channel::<Result<DeltaDiscoveryResponse, tonic::Status>>(100);
+        let shared_receiver = self.relay.clone();
+        tokio::spawn(async move {
+            let mut receiver = shared_receiver.lock().await;
+            while let Some(response) = receiver.recv().await {
+                if tx.send(Ok(response)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx)) as Self::DeltaClustersStream))
+    }
+
+    async fn fetch_clusters(
+        &self,
+        _request: tonic::Request<DiscoveryRequest>,
+    ) -> std::result::Result<tonic::Response<DiscoveryResponse>, tonic::Status> {
+        unimplemented!("not used by proxy");
+    }
+}
+
+#[tokio::test]
+async fn test_glob_collection_subscribed_to_with_the_context_params_of_the_node() {
+    const COLLECTION: &str = "xdstp://clusters.acme/envoy.config.cluster.v3.Cluster/edge/*";
+    const MEMBER: &str = "xdstp://clusters.acme/envoy.config.cluster.v3.Cluster/edge/backends";
+    let node = Node {
+        id: "node-a".into(),
+        context_params: [("xds.node.id".into(), "node-a".into())].into(),
+        ..Default::default()
+    };
+    let (responses_tx, responses_rx) = mpsc::channel::<DeltaDiscoveryResponse>(10);
+    let (requests_tx, mut requests_rx) = mpsc::channel::<DeltaDiscoveryRequest>(10);
+    let (client, server) = tokio::io::duplex(4096);
+    let cds_server = MockDeltaClusterService { relay: Arc::new(Mutex::new(responses_rx)), requests: requests_tx };
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(ClusterDiscoveryServiceServer::new(cds_server))
+            .serve_with_incoming(tokio_stream::iter([Ok::<_, std::io::Error>(server)]))
+            .await
+    });
+
+    let cds_client = ClusterDiscoveryServiceClient::new(connect_channel(client).await.unwrap());
+    let typed_binding = bindings::ClusterDiscoveryType { underlying_client: cds_client };
+    let (mut worker, mut client, _subscription_manager) =
+        DiscoveryClientBuilder::<bindings::ClusterDiscoveryType>::new(node, typed_binding)
+            .subscribe_resource_name(COLLECTION.to_owned())
+            .build()
+            .unwrap();
+    tokio::spawn(async move {
+        let _status = worker.run().await;
+    });
+
+    let request = receive(requests_rx.recv()).await;
+    assert_eq!(request.resource_names_subscribe, [format!("{COLLECTION}?xds.node.id=node-a")]);
+
+    // the members of the collection are named without the context parameters of the node
+    let mut member = cluster_resource(&format!("{MEMBER}?xds.node.id=node-a"));
+    member.version = "1".to_owned();
+    let _ = responses_tx
+        .send(DeltaDiscoveryResponse {
+            type_url: "type.googleapis.com/envoy.config.cluster.v3.Cluster".to_owned(),
+            nonce: "nonce-1".to_owned(),
+            resources: vec![member],
+            ..Default::default()
+        })
+        .await;
+    let event = receive(client.recv()).await;
+    assert!(matches!(&event.updates[..], [XdsResourceUpdate::Update(name, _, _)] if name == MEMBER));
+    let _ = event.ack_channel.send(vec![]);
+
+    let _ = responses_tx
+        .send(DeltaDiscoveryResponse {
+            type_url: "type.googleapis.com/envoy.config.cluster.v3.Cluster".to_owned(),
+            nonce: "nonce-2".to_owned(),
+            removed_resources: vec![format!("{MEMBER}?xds.node.id=node-a")],
+            ..Default::default()
+        })
+        .await;
+    let event = receive(client.recv()).await;
+    assert!(matches!(&event.updates[..], [XdsResourceUpdate::Remove(name, TypeUrl::Cluster)] if name == MEMBER));
+}